@@ -0,0 +1,382 @@
+//! 采集速度基准测试
+//!
+//! 对指定平台在短时间内按设定并发连续请求，统计延迟分布、吞吐与限流情况，
+//! 据此给出适合当前网络环境的请求间隔与并发数。请求计入每日预算，预算用尽时提前结束。
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::budget::{self, BudgetKind, Consumption};
+use crate::collectors::diagnosis::ApiErrorKind;
+use crate::collectors::Bounds;
+use crate::commands::{
+    collector_region, create_collector, get_collector_statuses, select_api_key,
+    MAX_COLLECTOR_CONCURRENCY,
+};
+use crate::error::{AppError, CmdResult};
+
+/// 缺省请求次数
+const DEFAULT_REQUESTS: u32 = 20;
+/// 单次测试最多请求次数
+const MAX_REQUESTS: u32 = 100;
+/// 缺省最长测试时长（秒）
+const DEFAULT_DURATION_SECS: u64 = 30;
+/// 单次测试最长时长（秒）
+const MAX_DURATION_SECS: u64 = 120;
+/// 缺省检索关键词
+const DEFAULT_KEYWORD: &str = "医院";
+/// 被限流达到该次数时提前结束，避免 Key 或 IP 被封禁
+const MAX_RATE_LIMITED: u32 = 3;
+/// 建议请求间隔的下限（毫秒），与 set_collector_delay 一致
+const MIN_DELAY_MS: u64 = 20;
+
+/// 基准测试配置
+#[derive(Debug, Clone, Deserialize)]
+pub struct BenchmarkConfig {
+    pub platform: String,
+    /// 区域代码，缺省使用当前区域
+    #[serde(default)]
+    pub region_code: Option<String>,
+    #[serde(default)]
+    pub keyword: Option<String>,
+    /// 请求次数，缺省 20，最多 100
+    #[serde(default)]
+    pub requests: Option<u32>,
+    /// 最长测试时长（秒），缺省 30
+    #[serde(default)]
+    pub duration_secs: Option<u64>,
+    /// 并发数，缺省按 Key 的 QPS 设定
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+}
+
+/// 延迟分布（毫秒）
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LatencyStats {
+    pub min: u64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+    pub mean: u64,
+}
+
+/// 基准测试结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkResult {
+    pub platform: String,
+    pub concurrency: usize,
+    pub requests: u32,
+    pub succeeded: u32,
+    pub failed: u32,
+    /// 其中被限流的次数
+    pub rate_limited: u32,
+    pub elapsed_secs: f64,
+    /// 成功请求的吞吐（次/秒）
+    pub throughput: f64,
+    /// 成功请求的延迟分布
+    pub latency: LatencyStats,
+    /// 出现过的错误信息（去重，最多 5 条）
+    pub errors: Vec<String>,
+    /// 提前结束的原因
+    pub stopped_reason: Option<String>,
+    pub suggested_delay_ms: u64,
+    pub suggested_concurrency: usize,
+    pub suggestion: String,
+}
+
+/// 按最近秩法计算分位数，samples 需已排序
+fn percentile(samples: &[u64], p: f64) -> u64 {
+    if samples.is_empty() {
+        return 0;
+    }
+    let rank = ((p / 100.0) * samples.len() as f64).ceil() as usize;
+    samples[rank.clamp(1, samples.len()) - 1]
+}
+
+fn latency_stats(samples: &mut [u64]) -> LatencyStats {
+    if samples.is_empty() {
+        return LatencyStats::default();
+    }
+    samples.sort_unstable();
+    LatencyStats {
+        min: samples[0],
+        p50: percentile(samples, 50.0),
+        p90: percentile(samples, 90.0),
+        p99: percentile(samples, 99.0),
+        max: samples[samples.len() - 1],
+        mean: samples.iter().sum::<u64>() / samples.len() as u64,
+    }
+}
+
+/// 根据测试结果给出请求间隔与并发数
+///
+/// 未限流时沿用 Key 的 QPS 对应的间隔；被限流时按实测吞吐的 80% 放慢；
+/// 失败较多时说明网络不稳定，间隔不小于中位延迟。并发数取在该间隔下覆盖 p90 延迟所需的线程数
+fn suggest(
+    latency: &LatencyStats,
+    throughput: f64,
+    rate_limited: u32,
+    failure_ratio: f64,
+    key_delay_ms: u64,
+) -> (u64, usize, String) {
+    let mut delay_ms = key_delay_ms.max(MIN_DELAY_MS);
+    let mut reasons = Vec::new();
+    if rate_limited > 0 && throughput > 0.0 {
+        let limited = (1000.0 / (throughput * 0.8)).ceil() as u64;
+        if limited > delay_ms {
+            delay_ms = limited;
+            reasons.push(format!(
+                "测试中被限流 {} 次，按实测吞吐的 80% 放慢",
+                rate_limited
+            ));
+        }
+    }
+    if failure_ratio > 0.2 && latency.p50 > delay_ms {
+        delay_ms = latency.p50;
+        reasons.push(format!(
+            "失败率 {:.0}%，网络不稳定，间隔不小于中位延迟",
+            failure_ratio * 100.0
+        ));
+    }
+    let concurrency = (latency.p90.div_ceil(delay_ms) as usize).clamp(1, MAX_COLLECTOR_CONCURRENCY);
+    if reasons.is_empty() {
+        reasons.push("未触发限流，可按 Key 的 QPS 设定采集".to_string());
+    }
+    let suggestion = format!(
+        "建议请求间隔 {}ms、并发 {}（p90 延迟 {}ms）；{}",
+        delay_ms,
+        concurrency,
+        latency.p90,
+        reasons.join("；")
+    );
+    (delay_ms, concurrency, suggestion)
+}
+
+/// 对平台做短时间压测，输出延迟分布与建议的请求间隔、并发数
+#[tauri::command]
+pub async fn benchmark_platform(config: BenchmarkConfig) -> CmdResult<BenchmarkResult> {
+    tokio::task::spawn_blocking(move || run_benchmark(config))
+        .await
+        .map_err(|e| format!("基准测试失败: {}", e))?
+}
+
+fn run_benchmark(config: BenchmarkConfig) -> CmdResult<BenchmarkResult> {
+    let platform = config.platform.clone();
+    if get_collector_statuses()
+        .get(&platform)
+        .is_some_and(|s| s.status == "running")
+    {
+        return Err(AppError::conflict("该平台正在采集，请先暂停后再测试"));
+    }
+
+    let (api_key, (key_delay_ms, key_concurrency)) = select_api_key(&platform)?;
+    let Some(mut collector) = create_collector(&platform, api_key) else {
+        return Err(AppError::invalid(format!("不支持的平台: {}", platform)));
+    };
+    let current = crate::config::get_current_region()?;
+    let region_code = config.region_code.unwrap_or(current.admin_code);
+    let bounds = Bounds {
+        min_lon: current.bounds.min_lon,
+        max_lon: current.bounds.max_lon,
+        min_lat: current.bounds.min_lat,
+        max_lat: current.bounds.max_lat,
+    };
+    collector.set_region(collector_region(&region_code, bounds).map_err(AppError::not_found)?);
+
+    let keyword = config
+        .keyword
+        .filter(|k| !k.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_KEYWORD.to_string());
+    let total = config
+        .requests
+        .unwrap_or(DEFAULT_REQUESTS)
+        .clamp(1, MAX_REQUESTS);
+    let duration = Duration::from_secs(
+        config
+            .duration_secs
+            .unwrap_or(DEFAULT_DURATION_SECS)
+            .clamp(1, MAX_DURATION_SECS),
+    );
+    let concurrency = config
+        .concurrency
+        .unwrap_or(key_concurrency)
+        .clamp(1, MAX_COLLECTOR_CONCURRENCY);
+    log::info!(
+        "{} 开始基准测试: {} 次请求，并发 {}",
+        platform,
+        total,
+        concurrency
+    );
+
+    let issued = AtomicU32::new(0);
+    let rate_limited = AtomicU32::new(0);
+    let stop = AtomicBool::new(false);
+    let stopped_reason: Mutex<Option<String>> = Mutex::new(None);
+    let latencies: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+    let errors: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    let failed = AtomicU32::new(0);
+
+    let started = Instant::now();
+    let finish = |reason: String| {
+        stop.store(true, Ordering::SeqCst);
+        if let Ok(mut r) = stopped_reason.lock() {
+            r.get_or_insert(reason);
+        }
+    };
+    thread::scope(|scope| {
+        for _ in 0..concurrency {
+            scope.spawn(|| loop {
+                if stop.load(Ordering::SeqCst) {
+                    return;
+                }
+                if started.elapsed() >= duration {
+                    finish("已达到测试时长".to_string());
+                    return;
+                }
+                let index = issued.fetch_add(1, Ordering::SeqCst);
+                if index >= total {
+                    return;
+                }
+                if budget::consume(&platform, BudgetKind::Requests) == Consumption::Exhausted {
+                    finish("今日请求预算已用尽".to_string());
+                    return;
+                }
+
+                // 轮换页码，避免平台侧缓存让延迟失真
+                let page = (index % 5) as usize + 1;
+                let begin = Instant::now();
+                let result = collector.search_poi(&keyword, page, "", "");
+                let elapsed_ms = begin.elapsed().as_millis() as u64;
+                match result {
+                    Ok(_) => {
+                        if let Ok(mut l) = latencies.lock() {
+                            l.push(elapsed_ms);
+                        }
+                    }
+                    Err(e) => {
+                        failed.fetch_add(1, Ordering::SeqCst);
+                        let kind = ApiErrorKind::classify(&e);
+                        if let Ok(mut errs) = errors.lock() {
+                            if errs.len() < 5 && !errs.contains(&e) {
+                                errs.push(e.clone());
+                            }
+                        }
+                        match kind {
+                            ApiErrorKind::RateLimited => {
+                                if rate_limited.fetch_add(1, Ordering::SeqCst) + 1
+                                    >= MAX_RATE_LIMITED
+                                {
+                                    finish("多次被限流，已提前结束".to_string());
+                                }
+                            }
+                            ApiErrorKind::QuotaExhausted
+                            | ApiErrorKind::KeyInvalid
+                            | ApiErrorKind::IpRestricted => finish(e),
+                            ApiErrorKind::Other => {}
+                        }
+                    }
+                }
+            });
+        }
+    });
+    let elapsed = started.elapsed().as_secs_f64();
+
+    let mut samples = latencies.into_inner().unwrap_or_default();
+    let succeeded = samples.len() as u32;
+    let failed = failed.into_inner();
+    let requests = succeeded + failed;
+    if requests == 0 {
+        return Err(AppError::from(
+            stopped_reason
+                .into_inner()
+                .ok()
+                .flatten()
+                .unwrap_or_else(|| "未能发出请求".to_string()),
+        ));
+    }
+    let latency = latency_stats(&mut samples);
+    let throughput = if elapsed > 0.0 {
+        succeeded as f64 / elapsed
+    } else {
+        0.0
+    };
+    let rate_limited = rate_limited.into_inner();
+    let (suggested_delay_ms, suggested_concurrency, suggestion) = suggest(
+        &latency,
+        throughput,
+        rate_limited,
+        failed as f64 / requests as f64,
+        key_delay_ms,
+    );
+    log::info!(
+        "{} 基准测试完成: 成功 {}/{}，p50 {}ms，p90 {}ms，{}",
+        platform,
+        succeeded,
+        requests,
+        latency.p50,
+        latency.p90,
+        suggestion
+    );
+
+    Ok(BenchmarkResult {
+        platform,
+        concurrency,
+        requests,
+        succeeded,
+        failed,
+        rate_limited,
+        elapsed_secs: elapsed,
+        throughput,
+        latency,
+        errors: errors.into_inner().unwrap_or_default(),
+        stopped_reason: stopped_reason.into_inner().ok().flatten(),
+        suggested_delay_ms,
+        suggested_concurrency,
+        suggestion,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_stats() {
+        let mut samples: Vec<u64> = (1..=10).rev().map(|v| v * 100).collect();
+        let stats = latency_stats(&mut samples);
+        assert_eq!(
+            stats,
+            LatencyStats {
+                min: 100,
+                p50: 500,
+                p90: 900,
+                p99: 1000,
+                max: 1000,
+                mean: 550,
+            }
+        );
+        assert_eq!(latency_stats(&mut []), LatencyStats::default());
+    }
+
+    #[test]
+    fn test_suggest() {
+        let latency = LatencyStats {
+            p50: 300,
+            p90: 800,
+            ..Default::default()
+        };
+        // 未限流：沿用 Key 的间隔，并发覆盖 p90 延迟
+        let (delay, concurrency, _) = suggest(&latency, 5.0, 0, 0.0, 367);
+        assert_eq!((delay, concurrency), (367, 3));
+        // 被限流：按实测吞吐 2.5 次/秒的 80% 放慢到 500ms
+        let (delay, concurrency, _) = suggest(&latency, 2.5, 2, 0.1, 367);
+        assert_eq!((delay, concurrency), (500, 2));
+        // 失败率高：间隔不小于中位延迟
+        let (delay, _, _) = suggest(&latency, 5.0, 0, 0.5, 100);
+        assert_eq!(delay, 300);
+    }
+}
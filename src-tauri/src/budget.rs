@@ -0,0 +1,306 @@
+//! 每日配额预算
+//!
+//! 按平台记录当天的 POI 请求数与瓦片请求数，采集、瓦片下载与预览代理共享同一份计数。
+//! 用量接近上限时提示，达到上限后由调用方自动暂停，跨天后计数清零。
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::{self, DailyBudget};
+use crate::error::{AppError, CmdResult};
+
+/// 用量写盘的最小间隔
+const USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 预算计数类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetKind {
+    /// POI 接口请求
+    Requests,
+    /// 瓦片请求
+    Tiles,
+}
+
+impl BudgetKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            BudgetKind::Requests => "请求",
+            BudgetKind::Tiles => "瓦片",
+        }
+    }
+}
+
+/// 单次消耗的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Consumption {
+    Allowed,
+    /// 本次消耗后首次越过警告阈值
+    Warning { used: u64, limit: u64 },
+    /// 今日预算已用尽，本次未计数
+    Exhausted,
+}
+
+/// 单个计数器的当日用量
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Counter {
+    used: u64,
+    #[serde(default)]
+    warned: bool,
+}
+
+impl Counter {
+    /// 按上限计数一次
+    fn consume(&mut self, limit: Option<u64>, warn_percent: u8) -> Consumption {
+        let Some(limit) = limit else {
+            self.used += 1;
+            return Consumption::Allowed;
+        };
+        if self.used >= limit {
+            return Consumption::Exhausted;
+        }
+
+        self.used += 1;
+        let threshold = limit * warn_percent.min(100) as u64;
+        if !self.warned && self.used * 100 >= threshold {
+            self.warned = true;
+            Consumption::Warning {
+                used: self.used,
+                limit,
+            }
+        } else {
+            Consumption::Allowed
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PlatformUsage {
+    #[serde(default)]
+    requests: Counter,
+    #[serde(default)]
+    tiles: Counter,
+}
+
+impl PlatformUsage {
+    fn counter(&mut self, kind: BudgetKind) -> &mut Counter {
+        match kind {
+            BudgetKind::Requests => &mut self.requests,
+            BudgetKind::Tiles => &mut self.tiles,
+        }
+    }
+}
+
+/// 当日用量账本
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UsageBook {
+    date: String,
+    platforms: HashMap<String, PlatformUsage>,
+    #[serde(skip)]
+    budgets: Option<Vec<DailyBudget>>,
+    #[serde(skip)]
+    last_flush: Option<Instant>,
+}
+
+impl UsageBook {
+    /// 跨天时清零计数
+    fn roll_over(&mut self) {
+        let today = today();
+        if self.date != today {
+            if !self.date.is_empty() {
+                log::info!("预算计数跨天清零: {} -> {}", self.date, today);
+            }
+            self.date = today;
+            self.platforms.clear();
+            self.flush();
+        }
+    }
+
+    fn budget(&mut self, platform: &str) -> Option<DailyBudget> {
+        let budgets = self.budgets.get_or_insert_with(|| {
+            config::list_daily_budgets().unwrap_or_else(|e| {
+                log::warn!("读取预算配置失败: {}", e);
+                Vec::new()
+            })
+        });
+        budgets.iter().find(|b| b.platform == platform).cloned()
+    }
+
+    fn flush(&mut self) {
+        self.last_flush = Some(Instant::now());
+        let result = serde_json::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                fs::write(config::budget_usage_path(), content).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            log::warn!("保存预算用量失败: {}", e);
+        }
+    }
+}
+
+static USAGE: Lazy<Mutex<UsageBook>> = Lazy::new(|| {
+    let book = fs::read_to_string(config::budget_usage_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    Mutex::new(book)
+});
+
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+fn limit_of(budget: Option<&DailyBudget>, kind: BudgetKind) -> Option<u64> {
+    budget.and_then(|b| match kind {
+        BudgetKind::Requests => b.max_requests,
+        BudgetKind::Tiles => b.max_tiles,
+    })
+}
+
+/// 消耗一次平台预算，未设置预算的平台只计数不限制
+pub fn consume(platform: &str, kind: BudgetKind) -> Consumption {
+    let Ok(mut book) = USAGE.lock() else {
+        return Consumption::Allowed;
+    };
+    book.roll_over();
+
+    let budget = book.budget(platform);
+    let warn_percent = budget.as_ref().map(|b| b.warn_percent).unwrap_or(100);
+    let result = book
+        .platforms
+        .entry(platform.to_string())
+        .or_default()
+        .counter(kind)
+        .consume(limit_of(budget.as_ref(), kind), warn_percent);
+
+    match result {
+        Consumption::Warning { used, limit } => {
+            log::warn!("{} 今日{}预算已用 {}/{}", platform, kind.label(), used, limit);
+            book.flush();
+        }
+        Consumption::Exhausted => log::warn!("{} 今日{}预算已用尽", platform, kind.label()),
+        Consumption::Allowed => {
+            if book
+                .last_flush
+                .is_none_or(|t| t.elapsed() >= USAGE_FLUSH_INTERVAL)
+            {
+                book.flush();
+            }
+        }
+    }
+    result
+}
+
+/// 平台今日预算是否已用尽
+pub fn is_exhausted(platform: &str, kind: BudgetKind) -> bool {
+    let Ok(mut book) = USAGE.lock() else {
+        return false;
+    };
+    book.roll_over();
+
+    let Some(limit) = limit_of(book.budget(platform).as_ref(), kind) else {
+        return false;
+    };
+    let used = book
+        .platforms
+        .get(platform)
+        .map(|u| match kind {
+            BudgetKind::Requests => u.requests.used,
+            BudgetKind::Tiles => u.tiles.used,
+        })
+        .unwrap_or(0);
+    used >= limit
+}
+
+/// 平台预算与当日用量
+#[derive(Debug, Clone, Serialize)]
+pub struct BudgetStatus {
+    pub platform: String,
+    pub max_requests: Option<u64>,
+    pub max_tiles: Option<u64>,
+    pub warn_percent: u8,
+    pub used_requests: u64,
+    pub used_tiles: u64,
+    pub date: String,
+}
+
+/// 获取各平台的预算与今日用量，包含未设置预算但有用量的平台
+#[tauri::command]
+pub fn get_daily_budgets() -> CmdResult<Vec<BudgetStatus>> {
+    let budgets = config::list_daily_budgets()?;
+    let mut book = USAGE.lock().map_err(|e| e.to_string())?;
+    book.roll_over();
+
+    let mut platforms: Vec<String> = budgets.iter().map(|b| b.platform.clone()).collect();
+    for platform in book.platforms.keys() {
+        if !platforms.contains(platform) {
+            platforms.push(platform.clone());
+        }
+    }
+    platforms.sort();
+
+    Ok(platforms
+        .into_iter()
+        .map(|platform| {
+            let budget = budgets.iter().find(|b| b.platform == platform);
+            let usage = book.platforms.get(&platform).cloned().unwrap_or_default();
+            BudgetStatus {
+                max_requests: budget.and_then(|b| b.max_requests),
+                max_tiles: budget.and_then(|b| b.max_tiles),
+                warn_percent: budget.map(|b| b.warn_percent).unwrap_or(100),
+                used_requests: usage.requests.used,
+                used_tiles: usage.tiles.used,
+                date: book.date.clone(),
+                platform,
+            }
+        })
+        .collect())
+}
+
+/// 设置平台每日预算，两项上限均为空时取消预算
+#[tauri::command]
+pub fn set_daily_budget(budget: DailyBudget) -> CmdResult<()> {
+    if budget.warn_percent == 0 || budget.warn_percent > 100 {
+        return Err(AppError::invalid("警告阈值需在 1-100 之间"));
+    }
+    let platform = budget.platform.clone();
+    let budgets = config::save_daily_budget(budget)?;
+
+    let mut book = USAGE.lock().map_err(|e| e.to_string())?;
+    // 调整上限后重新判断是否需要警告
+    if let Some(usage) = book.platforms.get_mut(&platform) {
+        usage.requests.warned = false;
+        usage.tiles.warned = false;
+    }
+    book.budgets = Some(budgets);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_consume() {
+        let mut counter = Counter::default();
+        assert_eq!(counter.consume(None, 80), Consumption::Allowed);
+        assert_eq!(counter.used, 1);
+
+        let mut counter = Counter::default();
+        for _ in 0..7 {
+            assert_eq!(counter.consume(Some(10), 80), Consumption::Allowed);
+        }
+        assert_eq!(
+            counter.consume(Some(10), 80),
+            Consumption::Warning { used: 8, limit: 10 }
+        );
+        assert_eq!(counter.consume(Some(10), 80), Consumption::Allowed);
+        assert_eq!(counter.consume(Some(10), 80), Consumption::Allowed);
+        assert_eq!(counter.consume(Some(10), 80), Consumption::Exhausted);
+        assert_eq!(counter.used, 10);
+    }
+}
@@ -1,27 +1,37 @@
 //! 高德地图 POI 采集器
+//!
+//! 按城市检索使用关键字搜索接口，网格模式与多边形范围使用多边形搜索接口，周边范围使用周边搜索接口，
+//! 单个查询最多取得约 900 条。
 
-use super::{Collector, POIData, RegionConfig};
-use crate::coords::amap_to_wgs84;
-use reqwest::blocking::Client;
+use super::http::{HttpFetcher, HttpResponse, ReqwestFetcher};
+use super::{Bounds, Collector, POIData, PoiDetail, RegionConfig, SearchCircle};
+use crate::coords::{amap_to_wgs84, wgs84_to_gcj02};
 use serde_json::Value;
 
 pub struct AmapCollector {
     api_key: String,
-    client: Client,
+    http: Box<dyn HttpFetcher>,
     region: Option<RegionConfig>,
 }
 
 impl AmapCollector {
     const API_URL: &'static str = "https://restapi.amap.com/v3/place/text";
+    const POLYGON_URL: &'static str = "https://restapi.amap.com/v3/place/polygon";
+    const AROUND_URL: &'static str = "https://restapi.amap.com/v3/place/around";
+    /// v5 详情接口支持以 | 分隔一次查询多个 id
+    const DETAIL_URL: &'static str = "https://restapi.amap.com/v5/place/detail";
     const PAGE_SIZE: i32 = 25;
+    const RESULT_CAP: usize = 900;
 
     pub fn new(api_key: String) -> Self {
+        Self::with_fetcher(api_key, Box::new(ReqwestFetcher::default()))
+    }
+
+    /// 使用指定的 HTTP 实现创建，测试时可注入录制响应
+    pub fn with_fetcher(api_key: String, http: Box<dyn HttpFetcher>) -> Self {
         Self {
             api_key,
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .unwrap_or_default(),
+            http,
             region: None,
         }
     }
@@ -78,6 +88,129 @@ impl AmapCollector {
             raw_data: raw.to_string(),
         })
     }
+
+    /// 解析详情接口返回的单个地点，business 与 photos 原样合并进 raw_data
+    fn parse_detail(raw: &Value) -> Option<PoiDetail> {
+        let id = raw.get("id")?.as_str()?.to_string();
+        let business = raw.get("business").cloned().unwrap_or(Value::Null);
+        // 空值以数组返回
+        let text = |key: &str| match business.get(key) {
+            Some(Value::String(s)) => s.trim().to_string(),
+            _ => String::new(),
+        };
+        let opening_hours = match text("opentime_week") {
+            week if week.is_empty() => text("opentime_today"),
+            week => week,
+        };
+        let photos_raw = raw.get("photos").cloned().unwrap_or(Value::Null);
+        let photos = photos_raw
+            .as_array()
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|p| p.get("url").and_then(|u| u.as_str()))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut extra = serde_json::Map::new();
+        let detail = PoiDetail {
+            id,
+            opening_hours,
+            tags: text("tag"),
+            price: text("cost"),
+            rating: text("rating"),
+            photos,
+            ..Default::default()
+        };
+        extra.insert("business".to_string(), business);
+        extra.insert("photos".to_string(), photos_raw);
+        Some(PoiDetail { extra, ..detail })
+    }
+
+    /// 请求一页检索结果，scope 为限定范围的参数，返回 (结果, 是否有下一页, 结果总数)
+    fn search_page(
+        &self,
+        url: &str,
+        scope: &[(&str, &str)],
+        keyword: &str,
+        page: usize,
+        category_name: &str,
+        category_id: &str,
+    ) -> Result<(Vec<POIData>, bool, usize), String> {
+        let offset = Self::PAGE_SIZE.to_string();
+        let page_num = page.to_string();
+        let mut query = vec![
+            ("key", self.api_key.as_str()),
+            ("keywords", keyword),
+            ("offset", &offset),
+            ("page", &page_num),
+            ("extensions", "all"),
+        ];
+        query.extend_from_slice(scope);
+        let response = self.http.get(url, &query)?;
+        let data = self.check_response(&response)?;
+
+        let pois = data.get("pois").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+        let total: usize = data.get("count")
+            .and_then(|c| c.as_str())
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(0);
+
+        let parsed: Vec<POIData> = pois.iter()
+            .filter_map(|raw| self.parse_poi_from_json(raw, category_name, category_id))
+            .collect();
+
+        let has_more = (page * Self::PAGE_SIZE as usize) < total
+            && pois.len() >= Self::PAGE_SIZE as usize;
+
+        Ok((parsed, has_more, total))
+    }
+
+    /// 多边形搜索的 polygon 参数：顶点转为 GCJ02，以「经度,纬度|经度,纬度」拼接
+    fn polygon_param(points: &[(f64, f64)]) -> String {
+        points
+            .iter()
+            .map(|&(lon, lat)| {
+                let (lon, lat) = wgs84_to_gcj02(lon, lat);
+                format!("{:.6},{:.6}", lon, lat)
+            })
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+
+    /// 检查 HTTP 状态与响应中的 status/infocode，成功时返回解析后的响应
+    fn check_response(&self, response: &HttpResponse) -> Result<Value, String> {
+        if response.status == 429 {
+            return Err("请求过于频繁 (429)".to_string());
+        }
+        // IP 被封禁时常直接返回 403/404 页面，交由连续错误检测判断
+        if response.is_client_error() {
+            return Err(format!("请求被拒绝 (HTTP {})", response.status));
+        }
+
+        let data = response.json()?;
+
+        // 检查响应状态
+        let status = data.get("status").and_then(|s| s.as_str()).unwrap_or("0");
+        if status != "1" {
+            let infocode = data.get("infocode").and_then(|c| c.as_str()).unwrap_or("");
+            let info = data.get("info").and_then(|c| c.as_str()).unwrap_or("");
+            if self.is_quota_error(&data) {
+                return Err(format!("API配额已耗尽 ({} {})", infocode, info));
+            }
+            return Err(match infocode {
+                "10001" | "10009" => format!("Key 无效 ({} {})", infocode, info),
+                "10005" | "10010" => format!("IP 被限制 ({} {})", infocode, info),
+                "10004" | "10014" | "10019" | "10020" | "10021" => {
+                    format!("请求过于频繁 ({} {})", infocode, info)
+                }
+                _ => format!("接口返回错误 ({} {})", infocode, info),
+            });
+        }
+        Ok(data)
+    }
 }
 
 impl Collector for AmapCollector {
@@ -95,58 +228,252 @@ impl Collector for AmapCollector {
 
     fn search_poi(&self, keyword: &str, page: usize, category_name: &str, category_id: &str) -> Result<(Vec<POIData>, bool), String> {
         let region = self.region.as_ref().ok_or("未设置区域配置")?;
+        let (pois, has_more, _) = self.search_page(
+            Self::API_URL,
+            &[("city", &region.city_code), ("citylimit", "true")],
+            keyword,
+            page,
+            category_name,
+            category_id,
+        )?;
+        Ok((pois, has_more))
+    }
 
-        let response = self.client
-            .get(Self::API_URL)
-            .query(&[
-                ("key", self.api_key.as_str()),
-                ("keywords", keyword),
-                ("city", &region.city_code),
-                ("citylimit", "true"),
-                ("offset", &Self::PAGE_SIZE.to_string()),
-                ("page", &page.to_string()),
-                ("extensions", "all"),
-            ])
-            .send()
-            .map_err(|e| format!("请求失败: {}", e))?;
-
-        if response.status() == 429 {
-            return Err("请求过于频繁 (429)".to_string());
-        }
-
-        let data: Value = response.json()
-            .map_err(|e| format!("解析响应失败: {}", e))?;
+    fn result_cap(&self) -> Option<usize> {
+        Some(Self::RESULT_CAP)
+    }
 
-        // 检查响应状态
-        let status = data.get("status").and_then(|s| s.as_str()).unwrap_or("0");
-        if status != "1" {
-            if self.is_quota_error(&data) {
-                return Err("API配额已耗尽".to_string());
-            }
-            return Ok((vec![], false));
-        }
+    fn search_poi_in_bounds(
+        &self,
+        keyword: &str,
+        page: usize,
+        bounds: &Bounds,
+        category_name: &str,
+        category_id: &str,
+    ) -> Result<(Vec<POIData>, bool, usize), String> {
+        // 矩形以左上、右下两个顶点表示
+        let polygon = Self::polygon_param(&[
+            (bounds.min_lon, bounds.max_lat),
+            (bounds.max_lon, bounds.min_lat),
+        ]);
+        self.search_page(
+            Self::POLYGON_URL,
+            &[("polygon", &polygon)],
+            keyword,
+            page,
+            category_name,
+            category_id,
+        )
+    }
 
-        let pois = data.get("pois").and_then(|p| p.as_array()).cloned().unwrap_or_default();
-        let total: i64 = data.get("count")
-            .and_then(|c| c.as_str())
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0);
+    fn supports_polygon_search(&self) -> bool {
+        true
+    }
 
-        let parsed: Vec<POIData> = pois.iter()
-            .filter_map(|raw| self.parse_poi_from_json(raw, category_name, category_id))
-            .collect();
+    fn search_poi_in_polygon(
+        &self,
+        keyword: &str,
+        page: usize,
+        ring: &[(f64, f64)],
+        category_name: &str,
+        category_id: &str,
+    ) -> Result<(Vec<POIData>, bool), String> {
+        let (pois, has_more, _) = self.search_page(
+            Self::POLYGON_URL,
+            &[("polygon", &Self::polygon_param(ring))],
+            keyword,
+            page,
+            category_name,
+            category_id,
+        )?;
+        Ok((pois, has_more))
+    }
 
-        let has_more = (page as i64 * Self::PAGE_SIZE as i64) < total 
-            && pois.len() >= Self::PAGE_SIZE as usize;
+    fn supports_around_search(&self) -> bool {
+        true
+    }
 
-        Ok((parsed, has_more))
+    fn search_poi_around(
+        &self,
+        keyword: &str,
+        page: usize,
+        circle: &SearchCircle,
+        category_name: &str,
+        category_id: &str,
+    ) -> Result<(Vec<POIData>, bool), String> {
+        let (lon, lat) = wgs84_to_gcj02(circle.lon, circle.lat);
+        let location = format!("{:.6},{:.6}", lon, lat);
+        let radius = format!("{:.0}", circle.radius);
+        let (pois, has_more, _) = self.search_page(
+            Self::AROUND_URL,
+            &[("location", &location), ("radius", &radius), ("sortrule", "distance")],
+            keyword,
+            page,
+            category_name,
+            category_id,
+        )?;
+        Ok((pois, has_more))
     }
 
     fn is_quota_error(&self, response: &Value) -> bool {
         if response.get("status").and_then(|s| s.as_str()) == Some("0") {
             let infocode = response.get("infocode").and_then(|c| c.as_str()).unwrap_or("");
-            return matches!(infocode, "10003" | "10004" | "10005" | "10009" | "10044");
+            // 10003: 日访问量超限；10044: 账号日访问量超限
+            return matches!(infocode, "10003" | "10044");
         }
         false
     }
+
+    fn detail_id_key(&self) -> Option<&'static str> {
+        Some("id")
+    }
+
+    fn fetch_details(&self, ids: &[String]) -> Result<Vec<PoiDetail>, String> {
+        let response = self.http.get(
+            Self::DETAIL_URL,
+            &[
+                ("key", self.api_key.as_str()),
+                ("id", &ids.join("|")),
+                ("show_fields", "business,photos"),
+            ],
+        )?;
+        let data = self.check_response(&response)?;
+
+        let pois = data.get("pois").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+        Ok(pois.iter().filter_map(Self::parse_detail).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::http::mock::{shanghai_region, MockFetcher};
+
+    fn collector(fetcher: &MockFetcher) -> AmapCollector {
+        let mut collector = AmapCollector::with_fetcher("test-key".into(), Box::new(fetcher.clone()));
+        collector.set_region(shanghai_region());
+        collector
+    }
+
+    #[test]
+    fn test_parse_recorded_response() {
+        let fetcher = MockFetcher::new().respond(200, include_str!("testdata/amap_place_text.json"));
+        let (pois, has_more) = collector(&fetcher).search_poi("广场", 1, "商业楼盘", "commercial").unwrap();
+
+        assert_eq!(fetcher.last_query("city").as_deref(), Some("021"));
+        assert_eq!(fetcher.last_query("page").as_deref(), Some("1"));
+        // 区域外的 POI 被过滤
+        assert_eq!(pois.len(), 2);
+        assert!(!has_more);
+
+        let square = &pois[0];
+        assert_eq!(square.name, "人民广场");
+        assert_eq!(square.address, "人民大道120号");
+        assert_eq!(square.phone, "021-63184658");
+        assert_eq!((square.original_lon, square.original_lat), (121.473701, 31.230416));
+        assert!((square.lon - 121.469).abs() < 0.002 && (square.lat - 31.232).abs() < 0.002);
+        // 空地址、空电话以数组返回
+        assert_eq!(pois[1].address, "");
+        assert_eq!(pois[1].phone, "");
+    }
+
+    #[test]
+    fn test_error_responses() {
+        let fetcher = MockFetcher::new()
+            .respond(200, r#"{"status":"0","info":"DAILY_QUERY_OVER_LIMIT","infocode":"10003"}"#)
+            .respond(200, r#"{"status":"0","info":"INVALID_USER_KEY","infocode":"10001"}"#)
+            .respond(403, "<html>Forbidden</html>")
+            .respond(200, "<html>");
+        let collector = collector(&fetcher);
+        let search = || collector.search_poi("广场", 1, "商业楼盘", "commercial").unwrap_err();
+
+        assert_eq!(search(), "API配额已耗尽 (10003 DAILY_QUERY_OVER_LIMIT)");
+        assert_eq!(search(), "Key 无效 (10001 INVALID_USER_KEY)");
+        assert_eq!(search(), "请求被拒绝 (HTTP 403)");
+        assert!(search().starts_with("解析响应失败"));
+    }
+
+    #[test]
+    fn test_search_in_bounds() {
+        let recorded = include_str!("testdata/amap_place_text.json");
+        let fetcher = MockFetcher::new().respond(200, recorded).respond(200, recorded);
+        let bounds = Bounds {
+            min_lon: 121.4,
+            max_lon: 121.5,
+            min_lat: 31.2,
+            max_lat: 31.3,
+        };
+        let (pois, has_more, total) = collector(&fetcher)
+            .search_poi_in_bounds("广场", 1, &bounds, "商业楼盘", "commercial")
+            .unwrap();
+
+        let requests = fetcher.requests.lock().clone();
+        assert!(requests[0].0.ends_with("/place/polygon"));
+        assert_eq!(fetcher.last_query("city"), None);
+        let polygon = fetcher.last_query("polygon").unwrap();
+        let corners: Vec<f64> = polygon.split(['|', ',']).map(|v| v.parse().unwrap()).collect();
+        // 西北角、东南角，偏移为 GCJ02
+        assert!((corners[0] - 121.4045).abs() < 0.001 && (corners[1] - 31.298).abs() < 0.001);
+        assert!(corners[2] > 121.5 && corners[3] < 31.2);
+        assert_eq!((pois.len(), has_more, total), (2, false, 3));
+
+        // 任意多边形按顶点顺序提交
+        let ring = [(121.4, 31.2), (121.5, 31.2), (121.45, 31.3), (121.4, 31.2)];
+        let (pois, _) = collector(&fetcher)
+            .search_poi_in_polygon("广场", 1, &ring, "商业楼盘", "commercial")
+            .unwrap();
+        assert_eq!(fetcher.last_query("polygon").unwrap().split('|').count(), 4);
+        assert_eq!(pois.len(), 2);
+    }
+
+    #[test]
+    fn test_search_around() {
+        let fetcher = MockFetcher::new().respond(200, include_str!("testdata/amap_place_text.json"));
+        let circle = SearchCircle {
+            lon: 121.4737,
+            lat: 31.2304,
+            radius: 1500.0,
+        };
+        let (pois, has_more) = collector(&fetcher)
+            .search_poi_around("广场", 1, &circle, "商业楼盘", "commercial")
+            .unwrap();
+
+        let requests = fetcher.requests.lock().clone();
+        assert!(requests[0].0.ends_with("/place/around"));
+        assert_eq!(fetcher.last_query("radius").as_deref(), Some("1500"));
+        // 中心点偏移为 GCJ02
+        let location = fetcher.last_query("location").unwrap();
+        let (lon, lat) = location.split_once(',').unwrap();
+        assert!((lon.parse::<f64>().unwrap() - 121.478).abs() < 0.001);
+        assert!((lat.parse::<f64>().unwrap() - 31.2284).abs() < 0.001);
+        assert_eq!((pois.len(), has_more), (2, false));
+    }
+
+    #[test]
+    fn test_fetch_details() {
+        let fetcher = MockFetcher::new()
+            .respond(200, include_str!("testdata/amap_place_detail.json"))
+            .respond(200, r#"{"status":"0","info":"USER_DAILY_QUERY_OVER_LIMIT","infocode":"10044"}"#);
+        let collector = collector(&fetcher);
+        let ids = vec!["B00155L9QA".to_string(), "B0FFG5PK2W".to_string()];
+        let details = collector.fetch_details(&ids).unwrap();
+
+        assert_eq!(fetcher.last_query("id").as_deref(), Some("B00155L9QA|B0FFG5PK2W"));
+        assert_eq!(details.len(), 2);
+        let square = &details[0];
+        assert_eq!(square.id, "B00155L9QA");
+        assert_eq!(square.opening_hours, "周一至周日 00:00-24:00");
+        assert_eq!((square.rating.as_str(), square.price.as_str()), ("4.6", ""));
+        assert_eq!(square.photos.len(), 2);
+        assert_eq!(square.extra["business"]["tel"], "021-63184658");
+        // 没有营业时间、照片时以空数组返回
+        assert_eq!(details[1].opening_hours, "10:00-21:30");
+        assert_eq!(details[1].price, "68.00");
+        assert!(details[1].photos.is_empty());
+
+        assert_eq!(
+            collector.fetch_details(&ids).unwrap_err(),
+            "API配额已耗尽 (10044 USER_DAILY_QUERY_OVER_LIMIT)"
+        );
+    }
 }
@@ -0,0 +1,89 @@
+//! 周边采集范围
+//!
+//! 以中心点（WGS84 经纬度）与半径限定采集范围，便于外业采集某点周边 N 米内的全部 POI。支持周边检索的平台
+//! （高德周边搜索、百度圆形区域检索）直接按圆形检索，其他平台以外接矩形作为区域边界；入库前统一剔除圆外的结果。
+
+use super::Bounds;
+use crate::coords::{haversine_distance, EARTH_RADIUS_M};
+use serde::{Deserialize, Serialize};
+
+/// 半径上限，与高德周边搜索一致
+pub const MAX_RADIUS_M: f64 = 50_000.0;
+
+/// 圆形采集范围
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchCircle {
+    pub lon: f64,
+    pub lat: f64,
+    /// 半径（米）
+    pub radius: f64,
+}
+
+impl SearchCircle {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(-180.0..=180.0).contains(&self.lon) || !(-90.0..=90.0).contains(&self.lat) {
+            return Err("周边中心点坐标超出经纬度范围".to_string());
+        }
+        if !(self.radius > 0.0 && self.radius <= MAX_RADIUS_M) {
+            return Err(format!("周边半径须大于 0 且不超过 {} 米", MAX_RADIUS_M));
+        }
+        Ok(())
+    }
+
+    /// 判断坐标是否在圆内
+    pub fn contains(&self, lon: f64, lat: f64) -> bool {
+        haversine_distance(self.lon, self.lat, lon, lat) <= self.radius
+    }
+
+    /// 外接矩形，按球面计算，与 contains 的距离一致
+    pub fn bounds(&self) -> Bounds {
+        let angle = self.radius / EARTH_RADIUS_M;
+        let d_lat = angle.to_degrees();
+        let ratio = angle.sin() / self.lat.to_radians().cos();
+        // 圆覆盖极点时经度不受限
+        let d_lon = if ratio >= 1.0 {
+            180.0
+        } else {
+            ratio.asin().to_degrees()
+        };
+        Bounds {
+            min_lon: self.lon - d_lon,
+            max_lon: self.lon + d_lon,
+            min_lat: self.lat - d_lat,
+            max_lat: self.lat + d_lat,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circle() {
+        let circle = SearchCircle {
+            lon: 121.4737,
+            lat: 31.2304,
+            radius: 1000.0,
+        };
+        assert!(circle.validate().is_ok());
+        assert!(circle.contains(121.4737, 31.2390));
+        assert!(!circle.contains(121.4737, 31.2404));
+
+        let b = circle.bounds();
+        assert!(circle.contains(circle.lon, b.max_lat - 1e-6));
+        assert!(circle.contains(b.max_lon - 1e-6, circle.lat));
+        assert!(!circle.contains(b.max_lon + 1e-4, circle.lat));
+
+        let too_large = SearchCircle {
+            radius: 60_000.0,
+            ..circle.clone()
+        };
+        assert!(too_large.validate().is_err());
+        let invalid = SearchCircle {
+            lat: 121.0,
+            ..circle
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -1,31 +1,112 @@
 //! 百度地图 POI 采集器
+//!
+//! 检索接口只返回名称、地址、电话等基本信息；营业时间、标签与价格需按 uid 调用详情接口补全，
+//! 详情接口单次最多查询 10 个 uid，额度单独计算，因此不在采集时调用，由补全命令按需执行。
+//! 网格模式按矩形范围检索，周边范围按圆形区域检索，单个查询最多取得 400 条。
 
-use super::{Collector, POIData, RegionConfig};
+use super::http::{HttpFetcher, ReqwestFetcher};
+use super::{Bounds, Collector, POIData, PoiDetail, RegionConfig, SearchCircle};
 use crate::coords::bd09_to_wgs84;
-use reqwest::blocking::Client;
 use serde_json::Value;
 
 pub struct BaiduCollector {
     api_key: String,
-    client: Client,
+    http: Box<dyn HttpFetcher>,
     region: Option<RegionConfig>,
 }
 
 impl BaiduCollector {
     const API_URL: &'static str = "https://api.map.baidu.com/place/v2/search";
+    const DETAIL_URL: &'static str = "https://api.map.baidu.com/place/v2/detail";
     const PAGE_SIZE: i32 = 20;
+    const RESULT_CAP: usize = 400;
 
     pub fn new(api_key: String) -> Self {
+        Self::with_fetcher(api_key, Box::new(ReqwestFetcher::default()))
+    }
+
+    /// 使用指定的 HTTP 实现创建，测试时可注入录制响应
+    pub fn with_fetcher(api_key: String, http: Box<dyn HttpFetcher>) -> Self {
         Self {
             api_key,
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .unwrap_or_default(),
+            http,
             region: None,
         }
     }
 
+    /// 请求一页检索结果，scope 为限定范围的参数，返回 (结果, 是否有下一页, 结果总数)
+    fn search_page(
+        &self,
+        scope: &[(&str, &str)],
+        keyword: &str,
+        page: usize,
+        category_name: &str,
+        category_id: &str,
+    ) -> Result<(Vec<POIData>, bool, usize), String> {
+        let page_size = Self::PAGE_SIZE.to_string();
+        let page_num = (page - 1).to_string();
+        let mut query = vec![
+            ("ak", self.api_key.as_str()),
+            ("query", keyword),
+            ("output", "json"),
+            ("page_size", &page_size),
+            ("page_num", &page_num),
+            ("scope", "2"),
+        ];
+        query.extend_from_slice(scope);
+        let response = self.http.get(Self::API_URL, &query)?;
+
+        if response.status == 429 {
+            return Err("请求过于频繁 (429)".to_string());
+        }
+
+        let data = response.json()?;
+
+        // 检查响应状态
+        let status = data.get("status").and_then(|s| s.as_i64()).unwrap_or(-1);
+        if status != 0 {
+            if self.is_quota_error(&data) {
+                return Err("API配额已耗尽".to_string());
+            }
+            return Ok((vec![], false, 0));
+        }
+
+        let pois = data.get("results").and_then(|p| p.as_array()).cloned().unwrap_or_default();
+        let total = data.get("total").and_then(|t| t.as_u64()).unwrap_or(0) as usize;
+
+        let parsed: Vec<POIData> = pois.iter()
+            .filter_map(|raw| self.parse_poi_from_json(raw, category_name, category_id))
+            .collect();
+
+        let has_more = (page * Self::PAGE_SIZE as usize) < total
+            && pois.len() >= Self::PAGE_SIZE as usize;
+
+        Ok((parsed, has_more, total))
+    }
+
+    /// 解析详情接口返回的单个地点，detail_info 原样合并进 raw_data
+    fn parse_detail(raw: &Value) -> Option<PoiDetail> {
+        let id = raw.get("uid")?.as_str()?.to_string();
+        let detail_info = raw.get("detail_info").cloned().unwrap_or(Value::Null);
+        // 价格、评分有时为数值
+        let text = |key: &str| match detail_info.get(key) {
+            Some(Value::String(s)) => s.trim().to_string(),
+            Some(Value::Number(n)) => n.to_string(),
+            _ => String::new(),
+        };
+        let mut extra = serde_json::Map::new();
+        let detail = PoiDetail {
+            id,
+            opening_hours: text("shop_hours"),
+            tags: text("tag"),
+            price: text("price"),
+            rating: text("overall_rating"),
+            ..Default::default()
+        };
+        extra.insert("detail_info".to_string(), detail_info);
+        Some(PoiDetail { extra, ..detail })
+    }
+
     fn parse_poi_from_json(&self, raw: &Value, category: &str, category_id: &str) -> Option<POIData> {
         let location = raw.get("location")?;
         let bd_lon = location.get("lng")?.as_f64()?;
@@ -83,53 +164,224 @@ impl Collector for BaiduCollector {
 
     fn search_poi(&self, keyword: &str, page: usize, category_name: &str, category_id: &str) -> Result<(Vec<POIData>, bool), String> {
         let region = self.region.as_ref().ok_or("未设置区域配置")?;
+        let (pois, has_more, _) = self.search_page(
+            &[("region", &region.name), ("city_limit", "true")],
+            keyword,
+            page,
+            category_name,
+            category_id,
+        )?;
+        Ok((pois, has_more))
+    }
+
+    fn result_cap(&self) -> Option<usize> {
+        Some(Self::RESULT_CAP)
+    }
+
+    fn search_poi_in_bounds(
+        &self,
+        keyword: &str,
+        page: usize,
+        bounds: &Bounds,
+        category_name: &str,
+        category_id: &str,
+    ) -> Result<(Vec<POIData>, bool, usize), String> {
+        // 矩形为「左下纬度,左下经度,右上纬度,右上经度」，coord_type=1 表示输入坐标为 WGS84
+        let rect = format!(
+            "{:.6},{:.6},{:.6},{:.6}",
+            bounds.min_lat, bounds.min_lon, bounds.max_lat, bounds.max_lon
+        );
+        self.search_page(
+            &[("bounds", &rect), ("coord_type", "1")],
+            keyword,
+            page,
+            category_name,
+            category_id,
+        )
+    }
+
+    fn supports_around_search(&self) -> bool {
+        true
+    }
 
-        let response = self.client
-            .get(Self::API_URL)
-            .query(&[
+    fn search_poi_around(
+        &self,
+        keyword: &str,
+        page: usize,
+        circle: &SearchCircle,
+        category_name: &str,
+        category_id: &str,
+    ) -> Result<(Vec<POIData>, bool), String> {
+        // 中心点为「纬度,经度」，radius_limit 限定只返回半径内的结果
+        let location = format!("{:.6},{:.6}", circle.lat, circle.lon);
+        let radius = format!("{:.0}", circle.radius);
+        let (pois, has_more, _) = self.search_page(
+            &[
+                ("location", &location),
+                ("radius", &radius),
+                ("radius_limit", "true"),
+                ("coord_type", "1"),
+            ],
+            keyword,
+            page,
+            category_name,
+            category_id,
+        )?;
+        Ok((pois, has_more))
+    }
+
+    fn is_quota_error(&self, response: &Value) -> bool {
+        let status = response.get("status").and_then(|s| s.as_i64()).unwrap_or(0);
+        matches!(status, 302 | 401 | 402 | 4)
+    }
+
+    fn detail_id_key(&self) -> Option<&'static str> {
+        Some("uid")
+    }
+
+    fn fetch_details(&self, uids: &[String]) -> Result<Vec<PoiDetail>, String> {
+        let response = self.http.get(
+            Self::DETAIL_URL,
+            &[
                 ("ak", self.api_key.as_str()),
-                ("query", keyword),
-                ("region", &region.name),
-                ("city_limit", "true"),
+                ("uids", &uids.join(",")),
                 ("output", "json"),
-                ("page_size", &Self::PAGE_SIZE.to_string()),
-                ("page_num", &(page - 1).to_string()),
                 ("scope", "2"),
-            ])
-            .send()
-            .map_err(|e| format!("请求失败: {}", e))?;
+            ],
+        )?;
 
-        if response.status() == 429 {
+        if response.status == 429 {
             return Err("请求过于频繁 (429)".to_string());
         }
 
-        let data: Value = response.json()
-            .map_err(|e| format!("解析响应失败: {}", e))?;
-
-        // 检查响应状态
+        let data = response.json()?;
         let status = data.get("status").and_then(|s| s.as_i64()).unwrap_or(-1);
         if status != 0 {
             if self.is_quota_error(&data) {
                 return Err("API配额已耗尽".to_string());
             }
-            return Ok((vec![], false));
+            let message = data.get("message").and_then(|m| m.as_str()).unwrap_or("");
+            return Err(format!("接口返回错误 ({} {})", status, message));
         }
 
-        let pois = data.get("results").and_then(|p| p.as_array()).cloned().unwrap_or_default();
-        let total = data.get("total").and_then(|t| t.as_i64()).unwrap_or(0);
+        // 只查询一个 uid 时 result 为对象
+        let results = match data.get("result") {
+            Some(Value::Array(items)) => items.clone(),
+            Some(item @ Value::Object(_)) => vec![item.clone()],
+            _ => Vec::new(),
+        };
+        Ok(results.iter().filter_map(Self::parse_detail).collect())
+    }
+}
 
-        let parsed: Vec<POIData> = pois.iter()
-            .filter_map(|raw| self.parse_poi_from_json(raw, category_name, category_id))
-            .collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::http::mock::{shanghai_region, MockFetcher};
 
-        let has_more = (page as i64 * Self::PAGE_SIZE as i64) < total 
-            && pois.len() >= Self::PAGE_SIZE as usize;
+    fn collector(fetcher: &MockFetcher) -> BaiduCollector {
+        let mut collector = BaiduCollector::with_fetcher("test-ak".into(), Box::new(fetcher.clone()));
+        collector.set_region(shanghai_region());
+        collector
+    }
+
+    #[test]
+    fn test_parse_recorded_response() {
+        let fetcher = MockFetcher::new().respond(200, include_str!("testdata/baidu_place_search.json"));
+        let (pois, has_more) = collector(&fetcher).search_poi("博物馆", 2, "地标建筑", "landmark").unwrap();
+
+        // 百度页码从 0 开始
+        assert_eq!(fetcher.last_query("page_num").as_deref(), Some("1"));
+        assert_eq!(fetcher.last_query("region").as_deref(), Some("上海市"));
+        // 坐标为 0 的条目被丢弃
+        assert_eq!(pois.len(), 1);
+        assert!(!has_more);
 
-        Ok((parsed, has_more))
+        let museum = &pois[0];
+        assert_eq!(museum.name, "上海博物馆");
+        assert_eq!(museum.phone, "(021)63723500");
+        assert_eq!(museum.platform, "baidu");
+        assert!((museum.lon - 121.471).abs() < 0.003 && (museum.lat - 31.231).abs() < 0.003);
     }
 
-    fn is_quota_error(&self, response: &Value) -> bool {
-        let status = response.get("status").and_then(|s| s.as_i64()).unwrap_or(0);
-        matches!(status, 302 | 401 | 402 | 4)
+    #[test]
+    fn test_search_in_bounds() {
+        let fetcher = MockFetcher::new().respond(200, include_str!("testdata/baidu_place_search.json"));
+        let bounds = Bounds {
+            min_lon: 121.4,
+            max_lon: 121.5,
+            min_lat: 31.2,
+            max_lat: 31.3,
+        };
+        let (pois, _, total) = collector(&fetcher)
+            .search_poi_in_bounds("博物馆", 1, &bounds, "地标建筑", "landmark")
+            .unwrap();
+
+        assert_eq!(
+            fetcher.last_query("bounds").as_deref(),
+            Some("31.200000,121.400000,31.300000,121.500000")
+        );
+        assert_eq!(fetcher.last_query("coord_type").as_deref(), Some("1"));
+        assert_eq!(fetcher.last_query("region"), None);
+        assert_eq!((pois.len(), total), (1, 2));
+
+        let circle = SearchCircle {
+            lon: 121.4737,
+            lat: 31.2304,
+            radius: 800.0,
+        };
+        let fetcher = MockFetcher::new().respond(200, include_str!("testdata/baidu_place_search.json"));
+        let (pois, _) = collector(&fetcher)
+            .search_poi_around("博物馆", 1, &circle, "地标建筑", "landmark")
+            .unwrap();
+        assert_eq!(fetcher.last_query("location").as_deref(), Some("31.230400,121.473700"));
+        assert_eq!(fetcher.last_query("radius").as_deref(), Some("800"));
+        assert_eq!(fetcher.last_query("radius_limit").as_deref(), Some("true"));
+        assert_eq!(pois.len(), 1);
+    }
+
+    #[test]
+    fn test_error_responses() {
+        let fetcher = MockFetcher::new()
+            .respond(200, r#"{"status":302,"message":"天配额超限，限制访问"}"#)
+            .respond(200, r#"{"status":2,"message":"Parameter Invalid"}"#)
+            .respond(429, "");
+        let collector = collector(&fetcher);
+        let search = || collector.search_poi("博物馆", 1, "地标建筑", "landmark");
+
+        assert_eq!(search().unwrap_err(), "API配额已耗尽");
+        // 其他错误视为无结果
+        assert_eq!(search().unwrap().0.len(), 0);
+        assert_eq!(search().unwrap_err(), "请求过于频繁 (429)");
+    }
+
+    #[test]
+    fn test_fetch_details() {
+        let fetcher = MockFetcher::new()
+            .respond(200, include_str!("testdata/baidu_place_detail.json"))
+            .respond(200, r#"{"status":0,"message":"ok","result":{"uid":"c3e6b9f9f5c0a1b2c3d4e5f6","name":"上海博物馆","detail_info":{"tag":"旅游景点;博物馆"}}}"#)
+            .respond(200, r#"{"status":302,"message":"天配额超限，限制访问"}"#);
+        let collector = collector(&fetcher);
+        let uids = vec!["a1b2c3d4e5f6a7b8c9d0e1f2".to_string(), "f0e1d2c3b4a5968778695a4b".to_string()];
+
+        let details = collector.fetch_details(&uids).unwrap();
+        assert_eq!(
+            fetcher.last_query("uids").as_deref(),
+            Some("a1b2c3d4e5f6a7b8c9d0e1f2,f0e1d2c3b4a5968778695a4b")
+        );
+        assert_eq!(details.len(), 2);
+        assert_eq!(details[0].opening_hours, "10:00-22:00");
+        assert_eq!(details[0].tags, "美食;中餐厅");
+        assert_eq!(details[0].price, "85");
+        assert_eq!(details[0].rating, "4.5");
+        assert_eq!(details[0].extra["detail_info"]["comment_num"], "1024");
+        // 数值价格与缺失字段
+        assert_eq!(details[1].price, "120");
+        assert_eq!(details[1].opening_hours, "");
+
+        // 单个 uid 时 result 为对象
+        let details = collector.fetch_details(&uids[..1]).unwrap();
+        assert_eq!(details[0].tags, "旅游景点;博物馆");
+        assert_eq!(collector.fetch_details(&uids).unwrap_err(), "API配额已耗尽");
     }
 }
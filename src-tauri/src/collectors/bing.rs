@@ -0,0 +1,255 @@
+//! Bing Maps POI 采集器
+//!
+//! 使用 Local Search 接口，以区域外接矩形作为 userMapView 限定搜索范围，适合境外区域。
+//! 接口不支持分页，每个关键词最多返回 25 条。返回坐标为 WGS84，无需转换。
+
+use super::http::{HttpFetcher, ReqwestFetcher};
+use super::{Collector, POIData, RegionConfig};
+use serde_json::Value;
+
+pub struct BingCollector {
+    api_key: String,
+    http: Box<dyn HttpFetcher>,
+    region: Option<RegionConfig>,
+}
+
+impl BingCollector {
+    const API_URL: &'static str = "https://dev.virtualearth.net/REST/v1/LocalSearch/";
+    const MAX_RESULTS: i32 = 25;
+
+    pub fn new(api_key: String) -> Self {
+        Self::with_fetcher(api_key, Box::new(ReqwestFetcher::default()))
+    }
+
+    /// 使用指定的 HTTP 实现创建，测试时可注入录制响应
+    pub fn with_fetcher(api_key: String, http: Box<dyn HttpFetcher>) -> Self {
+        Self {
+            api_key,
+            http,
+            region: None,
+        }
+    }
+
+    fn parse_poi_from_json(&self, raw: &Value, category: &str, category_id: &str) -> Option<POIData> {
+        // point.coordinates 为 [纬度, 经度]
+        let coordinates = raw.get("point")?.get("coordinates")?.as_array()?;
+        let lat = coordinates.first()?.as_f64()?;
+        let lon = coordinates.get(1)?.as_f64()?;
+
+        // userMapView 只是偏好，需按区域范围过滤
+        if let Some(ref region) = self.region {
+            let bounds = &region.bounds;
+            if lon < bounds.min_lon || lon > bounds.max_lon ||
+               lat < bounds.min_lat || lat > bounds.max_lat {
+                return None;
+            }
+        }
+
+        let name = raw.get("name")?.as_str()?.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let address = raw
+            .get("Address")
+            .and_then(|a| a.get("formattedAddress"))
+            .and_then(|a| a.as_str())
+            .unwrap_or("")
+            .to_string();
+        let phone = raw
+            .get("PhoneNumber")
+            .and_then(|p| p.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        Some(POIData {
+            name: name.to_string(),
+            lon,
+            lat,
+            original_lon: lon,
+            original_lat: lat,
+            category: category.to_string(),
+            category_id: category_id.to_string(),
+            address,
+            phone,
+            platform: "bing".to_string(),
+            raw_data: raw.to_string(),
+        })
+    }
+
+    /// 错误响应中的说明，errorDetails 为字符串数组
+    fn error_details(response: &Value) -> String {
+        response
+            .get("errorDetails")
+            .and_then(|d| d.as_array())
+            .map(|details| {
+                details
+                    .iter()
+                    .filter_map(|d| d.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Collector for BingCollector {
+    fn platform(&self) -> &'static str {
+        "bing"
+    }
+
+    fn set_api_key(&mut self, key: String) {
+        self.api_key = key;
+    }
+
+    fn set_region(&mut self, region: RegionConfig) {
+        self.region = Some(region);
+    }
+
+    fn search_poi(&self, keyword: &str, page: usize, category_name: &str, category_id: &str) -> Result<(Vec<POIData>, bool), String> {
+        let region = self.region.as_ref().ok_or("未设置区域配置")?;
+        // 不支持分页，只有第一页
+        if page > 1 {
+            return Ok((Vec::new(), false));
+        }
+
+        let b = &region.bounds;
+        let map_view = format!("{},{},{},{}", b.min_lat, b.min_lon, b.max_lat, b.max_lon);
+        let response = self.http.get(
+            Self::API_URL,
+            &[
+                ("key", self.api_key.as_str()),
+                ("query", keyword),
+                ("userMapView", &map_view),
+                ("maxResults", &Self::MAX_RESULTS.to_string()),
+                ("culture", "zh-Hans"),
+            ],
+        )?;
+
+        if response.status == 429 {
+            return Err("请求过于频繁 (429)".to_string());
+        }
+        // 错误响应同样带有 JSON 说明，无法解析时按 HTTP 状态报错
+        let data = match response.json() {
+            Ok(data) => data,
+            Err(_) if response.is_client_error() => {
+                return Err(format!("请求被拒绝 (HTTP {})", response.status));
+            }
+            Err(e) => return Err(e),
+        };
+
+        let status = data
+            .get("statusCode")
+            .and_then(|s| s.as_u64())
+            .unwrap_or(response.status as u64);
+        if status != 200 {
+            let details = Self::error_details(&data);
+            if self.is_quota_error(&data) {
+                return Err(format!("API配额已耗尽 ({} {})", status, details));
+            }
+            return Err(match status {
+                401 | 403 => format!("Key 无效 ({} {})", status, details),
+                429 => format!("请求过于频繁 ({} {})", status, details),
+                _ => format!("接口返回错误 ({} {})", status, details),
+            });
+        }
+
+        let resources = data
+            .get("resourceSets")
+            .and_then(|s| s.as_array())
+            .and_then(|sets| sets.first())
+            .and_then(|set| set.get("resources"))
+            .and_then(|r| r.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        let parsed: Vec<POIData> = resources.iter()
+            .filter_map(|raw| self.parse_poi_from_json(raw, category_name, category_id))
+            .collect();
+
+        Ok((parsed, false))
+    }
+
+    /// 免费或试用 Key 超出用量时返回 401/403，说明中带有 usage limit / quota 字样
+    fn is_quota_error(&self, response: &Value) -> bool {
+        let status = response.get("statusCode").and_then(|s| s.as_u64());
+        if !matches!(status, Some(401) | Some(403)) {
+            return false;
+        }
+        let details = Self::error_details(response).to_lowercase();
+        details.contains("limit") || details.contains("quota")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::http::mock::MockFetcher;
+    use crate::collectors::Bounds;
+
+    /// 录制响应所用的区域：伦敦市中心
+    fn london_region() -> RegionConfig {
+        RegionConfig {
+            name: "London".to_string(),
+            admin_code: String::new(),
+            city_code: String::new(),
+            bounds: Bounds {
+                min_lon: -0.2,
+                max_lon: -0.1,
+                min_lat: 51.45,
+                max_lat: 51.55,
+            },
+        }
+    }
+
+    fn collector(fetcher: &MockFetcher) -> BingCollector {
+        let mut collector = BingCollector::with_fetcher("test-key".into(), Box::new(fetcher.clone()));
+        collector.set_region(london_region());
+        collector
+    }
+
+    #[test]
+    fn test_parse_recorded_response() {
+        let fetcher = MockFetcher::new().respond(200, include_str!("testdata/bing_local_search.json"));
+        let collector = collector(&fetcher);
+        let (pois, has_more) = collector.search_poi("hospital", 1, "医疗", "hospital").unwrap();
+
+        assert_eq!(fetcher.last_query("userMapView").as_deref(), Some("51.45,-0.2,51.55,-0.1"));
+        // 区域外的 POI 被过滤
+        assert_eq!(pois.len(), 1);
+        assert!(!has_more);
+        let hospital = &pois[0];
+        assert_eq!(hospital.name, "St Thomas' Hospital");
+        assert_eq!(hospital.address, "Westminster Bridge Rd, London, SE1 7EH");
+        assert_eq!(hospital.phone, "020 7188 7188");
+        assert_eq!((hospital.lon, hospital.lat), (-0.118092, 51.498566));
+        assert_eq!(hospital.platform, "bing");
+
+        // 不支持分页，第二页不再请求
+        let (pois, has_more) = collector.search_poi("hospital", 2, "医疗", "hospital").unwrap();
+        assert!(pois.is_empty() && !has_more);
+        assert_eq!(fetcher.requests.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_error_responses() {
+        let fetcher = MockFetcher::new()
+            .respond(401, r#"{"statusCode":401,"statusDescription":"Unauthorized","errorDetails":["Access was denied. The account has exceeded its free usage limit."]}"#)
+            .respond(401, r#"{"statusCode":401,"statusDescription":"Unauthorized","errorDetails":["Access was denied. You may have entered your credentials incorrectly."]}"#)
+            .respond(429, "")
+            .respond(403, "<html>Forbidden</html>");
+        let collector = collector(&fetcher);
+        let search = || collector.search_poi("hospital", 1, "医疗", "hospital").unwrap_err();
+
+        assert_eq!(
+            search(),
+            "API配额已耗尽 (401 Access was denied. The account has exceeded its free usage limit.)"
+        );
+        assert_eq!(
+            search(),
+            "Key 无效 (401 Access was denied. You may have entered your credentials incorrectly.)"
+        );
+        assert_eq!(search(), "请求过于频繁 (429)");
+        assert_eq!(search(), "请求被拒绝 (HTTP 403)");
+    }
+}
@@ -0,0 +1,309 @@
+//! 平台类型码到内置类别的映射
+//!
+//! 用于根据 raw_data 中的平台分类信息（高德 typecode、百度 tag、天地图 typeName、OSM 标签、Google types、HERE 类别）
+//! 回填类别缺失的历史数据。
+
+use serde_json::Value;
+
+/// 高德 typecode 前缀映射，已按前缀长度从长到短排列
+const AMAP_TYPECODES: &[(&str, &str)] = &[
+    ("1203", "residential"),
+    ("1202", "commercial"),
+    ("1201", "industrial"),
+    ("1412", "school"),
+    ("1101", "nature"),
+    ("0701", "public_service"),
+    ("05", "business"),
+    ("06", "business"),
+    ("07", "public_service"),
+    ("08", "entertainment"),
+    ("09", "hospital"),
+    ("10", "business"),
+    ("11", "nature"),
+    ("12", "commercial"),
+    ("13", "government"),
+    ("14", "landmark"),
+    ("15", "transport"),
+    ("16", "business"),
+    ("17", "industrial"),
+    ("19", "admin"),
+    ("20", "public_service"),
+];
+
+/// 中文分类名称关键字映射（百度 tag、天地图 typeName），先出现的优先
+const TAG_KEYWORDS: &[(&str, &str)] = &[
+    ("住宅", "residential"),
+    ("小区", "residential"),
+    ("写字楼", "commercial"),
+    ("房地产", "commercial"),
+    ("小学", "school"),
+    ("中学", "school"),
+    ("幼儿园", "school"),
+    ("高等院校", "school"),
+    ("学校", "school"),
+    ("医疗", "hospital"),
+    ("医院", "hospital"),
+    ("药店", "hospital"),
+    ("政府", "government"),
+    ("公检法", "government"),
+    ("交通", "transport"),
+    ("加油", "transport"),
+    ("停车", "transport"),
+    ("园区", "industrial"),
+    ("公司企业", "industrial"),
+    ("美食", "business"),
+    ("餐饮", "business"),
+    ("购物", "business"),
+    ("酒店", "business"),
+    ("住宿", "business"),
+    ("金融", "business"),
+    ("休闲娱乐", "entertainment"),
+    ("运动健身", "entertainment"),
+    ("旅游景点", "nature"),
+    ("风景", "nature"),
+    ("自然地物", "nature"),
+    ("公园", "nature"),
+    ("行政地标", "admin"),
+    ("地名", "admin"),
+    ("宗教", "religious"),
+    ("寺", "religious"),
+    ("教堂", "religious"),
+    ("文化传媒", "landmark"),
+    ("科教文化", "landmark"),
+    ("生活服务", "public_service"),
+    ("邮", "public_service"),
+    ("农", "agriculture"),
+];
+
+/// OSM 标签映射，value 为 "*" 时匹配该 key 的任意值
+const OSM_TAGS: &[(&str, &str, &str)] = &[
+    ("amenity", "school", "school"),
+    ("amenity", "kindergarten", "school"),
+    ("amenity", "college", "school"),
+    ("amenity", "university", "school"),
+    ("amenity", "hospital", "hospital"),
+    ("amenity", "clinic", "hospital"),
+    ("amenity", "doctors", "hospital"),
+    ("amenity", "pharmacy", "hospital"),
+    ("amenity", "townhall", "government"),
+    ("amenity", "police", "government"),
+    ("amenity", "courthouse", "government"),
+    ("amenity", "bus_station", "transport"),
+    ("amenity", "parking", "transport"),
+    ("amenity", "fuel", "transport"),
+    ("highway", "bus_stop", "transport"),
+    ("amenity", "cinema", "entertainment"),
+    ("amenity", "cafe", "entertainment"),
+    ("amenity", "nightclub", "entertainment"),
+    ("leisure", "fitness_centre", "entertainment"),
+    ("amenity", "restaurant", "business"),
+    ("amenity", "fast_food", "business"),
+    ("amenity", "bank", "business"),
+    ("amenity", "marketplace", "business"),
+    ("tourism", "hotel", "business"),
+    ("shop", "*", "business"),
+    ("amenity", "place_of_worship", "religious"),
+    ("amenity", "post_office", "public_service"),
+    ("amenity", "community_centre", "public_service"),
+    ("amenity", "fire_station", "municipal"),
+    ("amenity", "waste_disposal", "municipal"),
+    ("tourism", "museum", "landmark"),
+    ("amenity", "library", "landmark"),
+    ("leisure", "park", "nature"),
+    ("tourism", "attraction", "nature"),
+    ("building", "residential", "residential"),
+    ("building", "apartments", "residential"),
+    ("landuse", "residential", "residential"),
+    ("building", "commercial", "commercial"),
+    ("building", "office", "commercial"),
+    ("landuse", "industrial", "industrial"),
+    ("building", "industrial", "industrial"),
+    ("landuse", "farmland", "agriculture"),
+    ("landuse", "orchard", "agriculture"),
+];
+
+/// Google Places types 映射，按 types 数组顺序取第一个能识别的类型
+const GOOGLE_TYPES: &[(&str, &str)] = &[
+    ("primary_school", "school"),
+    ("secondary_school", "school"),
+    ("school", "school"),
+    ("university", "school"),
+    ("hospital", "hospital"),
+    ("doctor", "hospital"),
+    ("pharmacy", "hospital"),
+    ("city_hall", "government"),
+    ("local_government_office", "government"),
+    ("police", "government"),
+    ("courthouse", "government"),
+    ("train_station", "transport"),
+    ("subway_station", "transport"),
+    ("bus_station", "transport"),
+    ("airport", "transport"),
+    ("parking", "transport"),
+    ("gas_station", "transport"),
+    ("movie_theater", "entertainment"),
+    ("night_club", "entertainment"),
+    ("gym", "entertainment"),
+    ("cafe", "entertainment"),
+    ("restaurant", "business"),
+    ("supermarket", "business"),
+    ("shopping_mall", "business"),
+    ("store", "business"),
+    ("bank", "business"),
+    ("lodging", "business"),
+    ("church", "religious"),
+    ("hindu_temple", "religious"),
+    ("mosque", "religious"),
+    ("synagogue", "religious"),
+    ("place_of_worship", "religious"),
+    ("post_office", "public_service"),
+    ("fire_station", "municipal"),
+    ("museum", "landmark"),
+    ("library", "landmark"),
+    ("stadium", "landmark"),
+    ("park", "nature"),
+    ("natural_feature", "nature"),
+    ("tourist_attraction", "nature"),
+    ("locality", "admin"),
+    ("sublocality", "admin"),
+];
+
+/// HERE 类别 ID 前缀映射（如 800-8200-0173 属于 800-8200 教育设施），按顺序取第一个匹配项
+const HERE_CATEGORIES: &[(&str, &str)] = &[
+    ("800-8200", "school"),
+    ("800-8000", "hospital"),
+    ("800-8100", "government"),
+    ("800-8300", "landmark"),
+    ("800-8500", "transport"),
+    ("400-4000", "transport"),
+    ("400-4100", "transport"),
+    ("700-7600", "transport"),
+    ("200", "entertainment"),
+    ("100", "business"),
+    ("500", "business"),
+    ("600", "business"),
+    ("700", "business"),
+    ("300-3200", "religious"),
+    ("300", "landmark"),
+    ("350", "nature"),
+    ("550-5510", "nature"),
+    ("900-9300", "residential"),
+    ("900", "admin"),
+];
+
+/// 内置类别对应的 HERE 类别 ID 前缀，Browse 接口按此筛选
+pub fn here_categories_for(category_id: &str) -> Vec<&'static str> {
+    HERE_CATEGORIES
+        .iter()
+        .filter(|(_, id)| *id == category_id)
+        .map(|(prefix, _)| *prefix)
+        .collect()
+}
+
+fn match_here_category(here_id: &str) -> Option<&'static str> {
+    HERE_CATEGORIES
+        .iter()
+        .find(|(prefix, _)| here_id.starts_with(prefix))
+        .map(|(_, id)| *id)
+}
+
+fn match_google_types(types: &[Value]) -> Option<&'static str> {
+    types.iter().filter_map(|t| t.as_str()).find_map(|t| {
+        GOOGLE_TYPES
+            .iter()
+            .find(|(name, _)| *name == t)
+            .map(|(_, id)| *id)
+    })
+}
+
+fn match_tag_text(text: &str) -> Option<&'static str> {
+    TAG_KEYWORDS
+        .iter()
+        .find(|(keyword, _)| text.contains(keyword))
+        .map(|(_, id)| *id)
+}
+
+fn match_amap_typecode(typecode: &str) -> Option<&'static str> {
+    // 多个类型码以 | 分隔，取第一个
+    let code = typecode.split('|').next()?.trim();
+    AMAP_TYPECODES
+        .iter()
+        .find(|(prefix, _)| code.starts_with(prefix))
+        .map(|(_, id)| *id)
+}
+
+fn match_osm_category(tag: &str) -> Option<&'static str> {
+    let (key, value) = tag.split_once('=')?;
+    OSM_TAGS
+        .iter()
+        .find(|(k, v, _)| *k == key && (*v == "*" || *v == value))
+        .map(|(_, _, id)| *id)
+}
+
+/// 根据平台原始数据推断内置类别 ID，无法识别时返回 None
+pub fn infer_category_id(platform: &str, raw: &Value) -> Option<&'static str> {
+    let text = |key: &str| raw.get(key).and_then(|v| v.as_str());
+
+    match platform {
+        "amap" => text("typecode")
+            .and_then(match_amap_typecode)
+            .or_else(|| text("type").and_then(match_tag_text)),
+        "baidu" => raw
+            .get("detail_info")
+            .and_then(|d| d.get("tag"))
+            .and_then(|v| v.as_str())
+            .or_else(|| text("tag"))
+            .and_then(match_tag_text),
+        "tianditu" => text("typeName").and_then(match_tag_text),
+        "osm" => text("osm_category").and_then(match_osm_category),
+        "google" => raw
+            .get("types")
+            .and_then(|v| v.as_array())
+            .and_then(|types| match_google_types(types)),
+        // 优先取主类别
+        "here" => {
+            let categories = raw.get("categories")?.as_array()?;
+            categories
+                .iter()
+                .find(|c| c.get("primary").and_then(|p| p.as_bool()) == Some(true))
+                .or_else(|| categories.first())
+                .and_then(|c| c.get("id"))
+                .and_then(|id| id.as_str())
+                .and_then(match_here_category)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn infers_from_platform_codes() {
+        let amap = json!({"typecode": "120302|120300", "type": "商务住宅;住宅区;住宅小区"});
+        assert_eq!(infer_category_id("amap", &amap), Some("residential"));
+        assert_eq!(infer_category_id("amap", &json!({"typecode": "141203"})), Some("school"));
+        assert_eq!(infer_category_id("amap", &json!({"typecode": "090100"})), Some("hospital"));
+
+        let baidu = json!({"detail_info": {"tag": "教育培训;小学"}});
+        assert_eq!(infer_category_id("baidu", &baidu), Some("school"));
+
+        let osm = json!({"osm_category": "shop=supermarket"});
+        assert_eq!(infer_category_id("osm", &osm), Some("business"));
+        assert_eq!(infer_category_id("osm", &json!({"osm_category": "unknown"})), None);
+
+        let google = json!({"types": ["point_of_interest", "train_station", "transit_station"]});
+        assert_eq!(infer_category_id("google", &google), Some("transport"));
+        assert_eq!(infer_category_id("google", &json!({"types": ["establishment"]})), None);
+
+        let here = json!({"categories": [
+            {"id": "100-1000-0000"},
+            {"id": "800-8200-0173", "primary": true}
+        ]});
+        assert_eq!(infer_category_id("here", &here), Some("school"));
+        assert_eq!(infer_category_id("here", &json!({"categories": [{"id": "600-6300-0066"}]})), Some("business"));
+        assert_eq!(here_categories_for("hospital"), vec!["800-8000"]);
+    }
+}
@@ -0,0 +1,146 @@
+//! 采集接口错误诊断
+//!
+//! 区分 Key 配额耗尽、Key 无效、IP 被限制与请求过频；同一错误连续出现多次时
+//! 判定为疑似 IP 封禁，并给出对应的提示与建议暂停时长。
+
+/// 连续相同错误达到该次数时判定为疑似封禁
+pub const BLOCK_STREAK_THRESHOLD: u32 = 5;
+
+/// 采集接口错误分类，依据各采集器返回的错误信息判断
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiErrorKind {
+    /// Key 当日配额耗尽
+    QuotaExhausted,
+    /// Key 无效或与平台不匹配
+    KeyInvalid,
+    /// IP 被平台限制访问
+    IpRestricted,
+    /// 请求过于频繁
+    RateLimited,
+    Other,
+}
+
+impl ApiErrorKind {
+    pub fn classify(message: &str) -> Self {
+        if message.contains("配额") {
+            ApiErrorKind::QuotaExhausted
+        } else if message.contains("Key 无效") {
+            ApiErrorKind::KeyInvalid
+        } else if message.contains("IP 被限制") {
+            ApiErrorKind::IpRestricted
+        } else if message.contains("过于频繁") || message.contains("429") {
+            ApiErrorKind::RateLimited
+        } else {
+            ApiErrorKind::Other
+        }
+    }
+}
+
+/// 需要停止采集时的诊断结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    /// quota_exhausted / key_invalid / ip_restricted / rate_limited / repeated_error
+    pub kind: &'static str,
+    /// 面向用户的提示
+    pub message: String,
+    /// 建议暂停的秒数，为空表示需要人工处理或次日再试
+    pub suggested_pause_secs: Option<u64>,
+    /// 是否可在暂停后继续，否则需更换 Key
+    pub resumable: bool,
+}
+
+/// 连续错误跟踪，成功请求后清零
+#[derive(Debug, Default)]
+pub struct ErrorStreak {
+    last: Option<String>,
+    count: u32,
+}
+
+impl ErrorStreak {
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.count = 0;
+    }
+
+    /// 记录一次错误，需要停止采集时返回诊断结果
+    pub fn record(&mut self, error: &str) -> Option<Diagnosis> {
+        if self.last.as_deref() == Some(error) {
+            self.count += 1;
+        } else {
+            self.last = Some(error.to_string());
+            self.count = 1;
+        }
+
+        match ApiErrorKind::classify(error) {
+            ApiErrorKind::QuotaExhausted => Some(Diagnosis {
+                kind: "quota_exhausted",
+                message: format!("{}：Key 今日配额已用完，请更换 Key 或次日再继续", error),
+                suggested_pause_secs: None,
+                resumable: false,
+            }),
+            ApiErrorKind::KeyInvalid => Some(Diagnosis {
+                kind: "key_invalid",
+                message: format!("{}：请检查 Key 是否正确、是否为 Web 服务类型", error),
+                suggested_pause_secs: None,
+                resumable: false,
+            }),
+            ApiErrorKind::IpRestricted => Some(Diagnosis {
+                kind: "ip_restricted",
+                message: format!("{}：当前 IP 被平台限制，建议暂停 1 小时或更换网络后继续", error),
+                suggested_pause_secs: Some(3600),
+                resumable: true,
+            }),
+            _ if self.count < BLOCK_STREAK_THRESHOLD => None,
+            ApiErrorKind::RateLimited => Some(Diagnosis {
+                kind: "rate_limited",
+                message: format!(
+                    "连续 {} 次{}：疑似 IP 被限流，建议暂停 10 分钟并调大请求间隔",
+                    self.count, error
+                ),
+                suggested_pause_secs: Some(600),
+                resumable: true,
+            }),
+            ApiErrorKind::Other => Some(Diagnosis {
+                kind: "repeated_error",
+                message: format!(
+                    "连续 {} 次返回相同错误（{}）：疑似 IP 被封禁，建议暂停 30 分钟后继续",
+                    self.count, error
+                ),
+                suggested_pause_secs: Some(1800),
+                resumable: true,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_streak() {
+        let mut streak = ErrorStreak::default();
+        let quota = streak.record("API配额已耗尽 (10003)").unwrap();
+        assert_eq!(quota.kind, "quota_exhausted");
+        assert!(!quota.resumable);
+
+        streak.reset();
+        for _ in 1..BLOCK_STREAK_THRESHOLD {
+            assert!(streak.record("请求失败: HTTP 404").is_none());
+        }
+        // 中途出现不同错误时重新计数
+        assert!(streak.record("解析响应失败").is_none());
+        for _ in 1..BLOCK_STREAK_THRESHOLD {
+            assert!(streak.record("请求失败: HTTP 404").is_none());
+        }
+        let blocked = streak.record("请求失败: HTTP 404").unwrap();
+        assert_eq!(blocked.kind, "repeated_error");
+        assert_eq!(blocked.suggested_pause_secs, Some(1800));
+
+        streak.reset();
+        assert_eq!(
+            streak.record("IP 被限制 (10010)").unwrap().kind,
+            "ip_restricted"
+        );
+    }
+}
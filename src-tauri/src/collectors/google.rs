@@ -0,0 +1,267 @@
+//! Google Places POI 采集器
+//!
+//! 使用 Places Text Search 接口，适合高德、百度覆盖较差的境外区域。接口不支持页码，
+//! 每页响应返回 next_page_token，下一页需携带该令牌请求，最多 3 页（60 条）。
+//! 返回坐标为 WGS84，无需转换。
+
+use super::http::{HttpFetcher, ReqwestFetcher};
+use super::{Collector, POIData, RegionConfig};
+use crate::coords::haversine_distance;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+pub struct GoogleCollector {
+    api_key: String,
+    http: Box<dyn HttpFetcher>,
+    region: Option<RegionConfig>,
+    /// 各关键词下一页的令牌：关键词 -> (页码, 令牌)
+    page_tokens: Mutex<HashMap<String, (usize, String)>>,
+}
+
+impl GoogleCollector {
+    const API_URL: &'static str = "https://maps.googleapis.com/maps/api/place/textsearch/json";
+    /// 接口允许的最大搜索半径（米）
+    const MAX_RADIUS_M: f64 = 50_000.0;
+    /// 令牌生成后需等待片刻才生效，期间请求返回 INVALID_REQUEST
+    const TOKEN_RETRY_DELAY: Duration = Duration::from_secs(2);
+    const TOKEN_RETRIES: usize = 2;
+
+    pub fn new(api_key: String) -> Self {
+        Self::with_fetcher(api_key, Box::new(ReqwestFetcher::default()))
+    }
+
+    /// 使用指定的 HTTP 实现创建，测试时可注入录制响应
+    pub fn with_fetcher(api_key: String, http: Box<dyn HttpFetcher>) -> Self {
+        Self {
+            api_key,
+            http,
+            region: None,
+            page_tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 以区域外接矩形的中心与半对角线作为搜索位置与半径
+    fn search_area(region: &RegionConfig) -> (String, String) {
+        let b = &region.bounds;
+        let (lon, lat) = ((b.min_lon + b.max_lon) / 2.0, (b.min_lat + b.max_lat) / 2.0);
+        let radius = haversine_distance(lon, lat, b.max_lon, b.max_lat).min(Self::MAX_RADIUS_M);
+        (format!("{:.6},{:.6}", lat, lon), format!("{:.0}", radius))
+    }
+
+    fn parse_poi_from_json(&self, raw: &Value, category: &str, category_id: &str) -> Option<POIData> {
+        let location = raw.get("geometry")?.get("location")?;
+        let lon = location.get("lng")?.as_f64()?;
+        let lat = location.get("lat")?.as_f64()?;
+
+        // 搜索半径只是偏好，需按区域范围过滤
+        if let Some(ref region) = self.region {
+            let bounds = &region.bounds;
+            if lon < bounds.min_lon || lon > bounds.max_lon ||
+               lat < bounds.min_lat || lat > bounds.max_lat {
+                return None;
+            }
+        }
+
+        let name = raw.get("name")?.as_str()?.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let address = raw
+            .get("formatted_address")
+            .and_then(|a| a.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        Some(POIData {
+            name: name.to_string(),
+            lon,
+            lat,
+            original_lon: lon,
+            original_lat: lat,
+            category: category.to_string(),
+            category_id: category_id.to_string(),
+            address,
+            // Text Search 不返回电话，需逐条调用 Place Details，费用较高暂不获取
+            phone: String::new(),
+            platform: "google".to_string(),
+            raw_data: raw.to_string(),
+        })
+    }
+
+    fn request(&self, query: &[(&str, &str)]) -> Result<Value, String> {
+        let response = self.http.get(Self::API_URL, query)?;
+        if response.status == 429 {
+            return Err("请求过于频繁 (429)".to_string());
+        }
+        if response.is_client_error() {
+            return Err(format!("请求被拒绝 (HTTP {})", response.status));
+        }
+        response.json()
+    }
+}
+
+impl Collector for GoogleCollector {
+    fn platform(&self) -> &'static str {
+        "google"
+    }
+
+    fn set_api_key(&mut self, key: String) {
+        self.api_key = key;
+    }
+
+    fn set_region(&mut self, region: RegionConfig) {
+        self.region = Some(region);
+        self.page_tokens.lock().clear();
+    }
+
+    fn search_poi(&self, keyword: &str, page: usize, category_name: &str, category_id: &str) -> Result<(Vec<POIData>, bool), String> {
+        let region = self.region.as_ref().ok_or("未设置区域配置")?;
+
+        let data = if page <= 1 {
+            let (location, radius) = Self::search_area(region);
+            self.request(&[
+                ("key", self.api_key.as_str()),
+                ("query", keyword),
+                ("location", &location),
+                ("radius", &radius),
+                ("language", "zh-CN"),
+            ])?
+        } else {
+            // 只能顺序翻页，没有上一页的令牌时视为没有更多结果
+            let token = match self.page_tokens.lock().remove(keyword) {
+                Some((next_page, token)) if next_page == page => token,
+                _ => return Ok((Vec::new(), false)),
+            };
+            let query = [("key", self.api_key.as_str()), ("pagetoken", token.as_str())];
+            let mut data = self.request(&query)?;
+            for _ in 0..Self::TOKEN_RETRIES {
+                if data.get("status").and_then(|s| s.as_str()) != Some("INVALID_REQUEST") {
+                    break;
+                }
+                thread::sleep(Self::TOKEN_RETRY_DELAY);
+                data = self.request(&query)?;
+            }
+            data
+        };
+
+        let status = data.get("status").and_then(|s| s.as_str()).unwrap_or("");
+        match status {
+            "OK" => {}
+            "ZERO_RESULTS" => return Ok((Vec::new(), false)),
+            _ => {
+                let message = data.get("error_message").and_then(|m| m.as_str()).unwrap_or("");
+                if self.is_quota_error(&data) {
+                    return Err(format!("API配额已耗尽 ({} {})", status, message));
+                }
+                return Err(match status {
+                    "REQUEST_DENIED" => format!("Key 无效 ({} {})", status, message),
+                    _ => format!("接口返回错误 ({} {})", status, message),
+                });
+            }
+        }
+
+        let results = data.get("results").and_then(|r| r.as_array()).cloned().unwrap_or_default();
+        let parsed: Vec<POIData> = results.iter()
+            .filter_map(|raw| self.parse_poi_from_json(raw, category_name, category_id))
+            .collect();
+
+        let next_token = data.get("next_page_token").and_then(|t| t.as_str());
+        let has_more = match next_token {
+            Some(token) => {
+                self.page_tokens
+                    .lock()
+                    .insert(keyword.to_string(), (page.max(1) + 1, token.to_string()));
+                true
+            }
+            None => false,
+        };
+
+        Ok((parsed, has_more))
+    }
+
+    fn is_quota_error(&self, response: &Value) -> bool {
+        response.get("status").and_then(|s| s.as_str()) == Some("OVER_QUERY_LIMIT")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::http::mock::MockFetcher;
+    use crate::collectors::Bounds;
+
+    /// 录制响应所用的区域：东京都心
+    fn tokyo_region() -> RegionConfig {
+        RegionConfig {
+            name: "東京".to_string(),
+            admin_code: String::new(),
+            city_code: String::new(),
+            bounds: Bounds {
+                min_lon: 139.6,
+                max_lon: 139.9,
+                min_lat: 35.5,
+                max_lat: 35.8,
+            },
+        }
+    }
+
+    fn collector(fetcher: &MockFetcher) -> GoogleCollector {
+        let mut collector = GoogleCollector::with_fetcher("test-key".into(), Box::new(fetcher.clone()));
+        collector.set_region(tokyo_region());
+        collector
+    }
+
+    #[test]
+    fn test_parse_and_paginate() {
+        let fetcher = MockFetcher::new()
+            .respond(200, include_str!("testdata/google_textsearch.json"))
+            .respond(200, r#"{"status":"OK","results":[{"name":"東京駅","formatted_address":"日本、〒100-0005 東京都千代田区丸の内１丁目","geometry":{"location":{"lat":35.6812362,"lng":139.7671248}}}]}"#);
+        let collector = collector(&fetcher);
+
+        let (pois, has_more) = collector.search_poi("station", 1, "交通", "transport").unwrap();
+        assert_eq!(fetcher.last_query("location").as_deref(), Some("35.650000,139.750000"));
+        assert_eq!(fetcher.last_query("query").as_deref(), Some("station"));
+        // 区域外的 POI 被过滤
+        assert_eq!(pois.len(), 1);
+        assert!(has_more);
+        assert_eq!(pois[0].name, "新宿駅");
+        assert_eq!(pois[0].address, "日本、〒160-0022 東京都新宿区新宿３丁目３８");
+        assert_eq!((pois[0].lon, pois[0].lat), (139.7002579, 35.6896067));
+        assert_eq!(pois[0].platform, "google");
+
+        let (pois, has_more) = collector.search_poi("station", 2, "交通", "transport").unwrap();
+        assert_eq!(fetcher.last_query("pagetoken").as_deref(), Some("NEXT_TOKEN"));
+        assert_eq!(fetcher.last_query("query"), None);
+        assert_eq!(pois.len(), 1);
+        assert!(!has_more);
+
+        // 没有令牌的页码不再请求
+        let (pois, has_more) = collector.search_poi("station", 3, "交通", "transport").unwrap();
+        assert!(pois.is_empty() && !has_more);
+        assert_eq!(fetcher.requests.lock().len(), 2);
+    }
+
+    #[test]
+    fn test_error_responses() {
+        let fetcher = MockFetcher::new()
+            .respond(200, r#"{"status":"OVER_QUERY_LIMIT","error_message":"You have exceeded your daily request quota","results":[]}"#)
+            .respond(200, r#"{"status":"REQUEST_DENIED","error_message":"The provided API key is invalid.","results":[]}"#)
+            .respond(200, r#"{"status":"ZERO_RESULTS","results":[]}"#)
+            .respond(403, "<html>Forbidden</html>");
+        let collector = collector(&fetcher);
+        let search = || collector.search_poi("station", 1, "交通", "transport");
+
+        assert_eq!(
+            search().unwrap_err(),
+            "API配额已耗尽 (OVER_QUERY_LIMIT You have exceeded your daily request quota)"
+        );
+        assert_eq!(search().unwrap_err(), "Key 无效 (REQUEST_DENIED The provided API key is invalid.)");
+        let (pois, has_more) = search().unwrap();
+        assert!(pois.is_empty() && !has_more);
+        assert_eq!(search().unwrap_err(), "请求被拒绝 (HTTP 403)");
+    }
+}
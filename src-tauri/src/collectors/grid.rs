@@ -0,0 +1,125 @@
+//! 网格自适应检索
+//!
+//! 高德、百度单个查询的结果数有上限，超出部分无法翻页取得。网格模式以采集范围为根网格按范围检索，
+//! 接口报告的结果总数达到上限时将网格四等分后分别检索，直到结果数低于上限或达到最大细分深度。
+//! 相邻网格在边界上重叠，同一 POI 可能被多个网格返回，按名称与坐标去重。
+
+use super::{Bounds, POIData};
+use std::collections::HashSet;
+
+/// 最大细分深度，根网格为 0，最深一层网格边长为根网格的 1/64
+pub const MAX_DEPTH: usize = 6;
+
+/// 检索网格
+#[derive(Debug, Clone)]
+pub struct GridCell {
+    pub bounds: Bounds,
+    pub depth: usize,
+}
+
+impl GridCell {
+    pub fn root(bounds: Bounds) -> Self {
+        Self { bounds, depth: 0 }
+    }
+
+    /// 结果总数达到上限且未到最大深度时需要细分
+    pub fn should_split(&self, total: usize, cap: usize) -> bool {
+        total >= cap && self.depth < MAX_DEPTH
+    }
+
+    /// 四等分为子网格，顺序为西南、东南、西北、东北
+    pub fn split(&self) -> [GridCell; 4] {
+        let b = &self.bounds;
+        let mid_lon = (b.min_lon + b.max_lon) / 2.0;
+        let mid_lat = (b.min_lat + b.max_lat) / 2.0;
+        let cell = |min_lon, max_lon, min_lat, max_lat| GridCell {
+            bounds: Bounds {
+                min_lon,
+                max_lon,
+                min_lat,
+                max_lat,
+            },
+            depth: self.depth + 1,
+        };
+        [
+            cell(b.min_lon, mid_lon, b.min_lat, mid_lat),
+            cell(mid_lon, b.max_lon, b.min_lat, mid_lat),
+            cell(b.min_lon, mid_lon, mid_lat, b.max_lat),
+            cell(mid_lon, b.max_lon, mid_lat, b.max_lat),
+        ]
+    }
+
+    /// 日志中显示的范围
+    pub fn label(&self) -> String {
+        let b = &self.bounds;
+        format!(
+            "{:.4},{:.4}~{:.4},{:.4}",
+            b.min_lon, b.min_lat, b.max_lon, b.max_lat
+        )
+    }
+}
+
+/// 已返回过的 POI，按与入库唯一约束相同的 (名称, 经度, 纬度) 判断
+#[derive(Debug, Default)]
+pub struct SeenPois(HashSet<(String, u64, u64)>);
+
+impl SeenPois {
+    /// 剔除此前网格已返回的 POI
+    pub fn retain_new(&mut self, pois: &mut Vec<POIData>) {
+        pois.retain(|p| self.0.insert((p.name.clone(), p.lon.to_bits(), p.lat.to_bits())));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poi(name: &str, lon: f64, lat: f64) -> POIData {
+        POIData {
+            name: name.to_string(),
+            lon,
+            lat,
+            original_lon: lon,
+            original_lat: lat,
+            category: String::new(),
+            category_id: String::new(),
+            address: String::new(),
+            phone: String::new(),
+            platform: "amap".to_string(),
+            raw_data: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_split_and_dedup() {
+        let root = GridCell::root(Bounds {
+            min_lon: 121.0,
+            max_lon: 122.0,
+            min_lat: 31.0,
+            max_lat: 32.0,
+        });
+        assert!(root.should_split(900, 900));
+        assert!(!root.should_split(899, 900));
+
+        let [south_west, _, _, north_east] = root.split();
+        assert_eq!(south_west.depth, 1);
+        assert_eq!(south_west.label(), "121.0000,31.0000~121.5000,31.5000");
+        assert_eq!(north_east.label(), "121.5000,31.5000~122.0000,32.0000");
+
+        let mut deepest = root;
+        while deepest.depth < MAX_DEPTH {
+            deepest = deepest.split()[0].clone();
+        }
+        assert!(!deepest.should_split(900, 900));
+
+        let mut seen = SeenPois::default();
+        let mut first = vec![poi("人民广场", 121.47, 31.23), poi("来福士", 121.48, 31.23)];
+        seen.retain_new(&mut first);
+        assert_eq!(first.len(), 2);
+        // 边界上的 POI 被相邻网格再次返回
+        let mut second = vec![poi("来福士", 121.48, 31.23), poi("大世界", 121.49, 31.23)];
+        seen.retain_new(&mut second);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].name, "大世界");
+    }
+}
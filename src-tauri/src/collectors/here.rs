@@ -0,0 +1,327 @@
+//! HERE POI 采集器
+//!
+//! 类别能对应到 HERE 类别时使用 Browse 接口按类别筛选、以关键词过滤名称，否则使用 Discover
+//! 接口按关键词搜索，均以区域外接矩形限定范围。接口不支持分页，每次最多返回 100 条。
+//! 同一地点会被多个关键词重复命中，按 HERE place id 去重。返回坐标为 WGS84，无需转换。
+
+use super::category_map;
+use super::http::{HttpFetcher, HttpResponse, ReqwestFetcher};
+use super::{Collector, POIData, RegionConfig};
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::thread;
+use std::time::Duration;
+
+pub struct HereCollector {
+    api_key: String,
+    http: Box<dyn HttpFetcher>,
+    region: Option<RegionConfig>,
+    /// 已返回过的 place id
+    seen_ids: Mutex<HashSet<String>>,
+    /// 429 后首次重试的等待时间，之后每次加倍
+    retry_delay: Duration,
+}
+
+impl HereCollector {
+    const DISCOVER_URL: &'static str = "https://discover.search.hereapi.com/v1/discover";
+    const BROWSE_URL: &'static str = "https://browse.search.hereapi.com/v1/browse";
+    const LIMIT: i32 = 100;
+    const RETRY_DELAY: Duration = Duration::from_secs(1);
+    const MAX_RETRIES: usize = 3;
+
+    pub fn new(api_key: String) -> Self {
+        Self::with_fetcher(api_key, Box::new(ReqwestFetcher::default()))
+    }
+
+    /// 使用指定的 HTTP 实现创建，测试时可注入录制响应
+    pub fn with_fetcher(api_key: String, http: Box<dyn HttpFetcher>) -> Self {
+        Self {
+            api_key,
+            http,
+            region: None,
+            seen_ids: Mutex::new(HashSet::new()),
+            retry_delay: Self::RETRY_DELAY,
+        }
+    }
+
+    fn parse_poi_from_json(&self, raw: &Value, category: &str, category_id: &str) -> Option<POIData> {
+        let position = raw.get("position")?;
+        let lon = position.get("lng")?.as_f64()?;
+        let lat = position.get("lat")?.as_f64()?;
+
+        // bbox 之外的结果（如按行政区匹配的地址）需过滤
+        if let Some(ref region) = self.region {
+            let bounds = &region.bounds;
+            if lon < bounds.min_lon || lon > bounds.max_lon ||
+               lat < bounds.min_lat || lat > bounds.max_lat {
+                return None;
+            }
+        }
+
+        let name = raw.get("title")?.as_str()?.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let address = raw
+            .get("address")
+            .and_then(|a| a.get("label"))
+            .and_then(|a| a.as_str())
+            .unwrap_or("")
+            .to_string();
+        // contacts 为数组，每项包含 phone、mobile 等联系方式数组
+        let phone = raw
+            .get("contacts")
+            .and_then(|c| c.as_array())
+            .and_then(|contacts| {
+                contacts.iter().find_map(|c| {
+                    c.get("phone")
+                        .or_else(|| c.get("mobile"))
+                        .and_then(|p| p.as_array())
+                        .and_then(|p| p.first())
+                        .and_then(|p| p.get("value"))
+                        .and_then(|v| v.as_str())
+                })
+            })
+            .unwrap_or("")
+            .to_string();
+
+        Some(POIData {
+            name: name.to_string(),
+            lon,
+            lat,
+            original_lon: lon,
+            original_lat: lat,
+            category: category.to_string(),
+            category_id: category_id.to_string(),
+            address,
+            phone,
+            platform: "here".to_string(),
+            raw_data: raw.to_string(),
+        })
+    }
+
+    /// 发起请求，429 限流时按指数退避重试，额度用尽不重试
+    fn request(&self, url: &str, query: &[(&str, &str)]) -> Result<HttpResponse, String> {
+        let mut delay = self.retry_delay;
+        for _ in 0..Self::MAX_RETRIES {
+            let response = self.http.get(url, query)?;
+            let quota = response.json().is_ok_and(|data| self.is_quota_error(&data));
+            if response.status != 429 || quota {
+                return Ok(response);
+            }
+            thread::sleep(delay);
+            delay *= 2;
+        }
+        self.http.get(url, query)
+    }
+
+    /// 错误说明，取 title 与 cause
+    fn error_message(response: &Value) -> String {
+        ["title", "cause"]
+            .iter()
+            .filter_map(|key| response.get(*key).and_then(|v| v.as_str()))
+            .collect::<Vec<_>>()
+            .join(": ")
+    }
+}
+
+impl Collector for HereCollector {
+    fn platform(&self) -> &'static str {
+        "here"
+    }
+
+    fn set_api_key(&mut self, key: String) {
+        self.api_key = key;
+    }
+
+    fn set_region(&mut self, region: RegionConfig) {
+        self.region = Some(region);
+        self.seen_ids.lock().clear();
+    }
+
+    fn search_poi(&self, keyword: &str, page: usize, category_name: &str, category_id: &str) -> Result<(Vec<POIData>, bool), String> {
+        let region = self.region.as_ref().ok_or("未设置区域配置")?;
+        // 不支持分页，只有第一页
+        if page > 1 {
+            return Ok((Vec::new(), false));
+        }
+
+        let b = &region.bounds;
+        let bbox = format!("bbox:{},{},{},{}", b.min_lon, b.min_lat, b.max_lon, b.max_lat);
+        let limit = Self::LIMIT.to_string();
+        let here_categories = category_map::here_categories_for(category_id).join(",");
+        let response = if here_categories.is_empty() {
+            self.request(
+                Self::DISCOVER_URL,
+                &[
+                    ("apiKey", self.api_key.as_str()),
+                    ("q", keyword),
+                    ("in", &bbox),
+                    ("limit", &limit),
+                    ("lang", "zh-CN"),
+                ],
+            )?
+        } else {
+            // Browse 要求提供 at，取区域中心
+            let at = format!("{:.6},{:.6}", (b.min_lat + b.max_lat) / 2.0, (b.min_lon + b.max_lon) / 2.0);
+            self.request(
+                Self::BROWSE_URL,
+                &[
+                    ("apiKey", self.api_key.as_str()),
+                    ("at", &at),
+                    ("in", &bbox),
+                    ("categories", &here_categories),
+                    ("name", keyword),
+                    ("limit", &limit),
+                    ("lang", "zh-CN"),
+                ],
+            )?
+        };
+
+        // 错误响应同样带有 JSON 说明，无法解析时按 HTTP 状态报错
+        let data = match response.json() {
+            Ok(data) => data,
+            Err(_) if response.status == 429 => return Err("请求过于频繁 (429)".to_string()),
+            Err(_) if response.is_client_error() => {
+                return Err(format!("请求被拒绝 (HTTP {})", response.status));
+            }
+            Err(e) => return Err(e),
+        };
+        if response.status != 200 {
+            let message = Self::error_message(&data);
+            if self.is_quota_error(&data) {
+                return Err(format!("API配额已耗尽 ({} {})", response.status, message));
+            }
+            return Err(match response.status {
+                401 | 403 => format!("Key 无效 ({} {})", response.status, message),
+                429 => format!("请求过于频繁 ({} {})", response.status, message),
+                status => format!("接口返回错误 ({} {})", status, message),
+            });
+        }
+
+        let items = data.get("items").and_then(|i| i.as_array()).cloned().unwrap_or_default();
+        let mut seen_ids = self.seen_ids.lock();
+        let parsed: Vec<POIData> = items.iter()
+            .filter(|raw| match raw.get("id").and_then(|id| id.as_str()) {
+                Some(id) => seen_ids.insert(id.to_string()),
+                None => true,
+            })
+            .filter_map(|raw| self.parse_poi_from_json(raw, category_name, category_id))
+            .collect();
+
+        Ok((parsed, false))
+    }
+
+    /// 免费额度用尽时返回 429 或 403，说明中带有 quota / limit exceeded 字样
+    fn is_quota_error(&self, response: &Value) -> bool {
+        let status = response.get("status").and_then(|s| s.as_u64());
+        if !matches!(status, Some(403) | Some(429)) {
+            return false;
+        }
+        let message = Self::error_message(response).to_lowercase();
+        message.contains("quota") || message.contains("limit exceeded")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::http::mock::MockFetcher;
+    use crate::collectors::Bounds;
+
+    /// 录制响应所用的区域：柏林市中心
+    fn berlin_region() -> RegionConfig {
+        RegionConfig {
+            name: "Berlin".to_string(),
+            admin_code: String::new(),
+            city_code: String::new(),
+            bounds: Bounds {
+                min_lon: 13.3,
+                max_lon: 13.45,
+                min_lat: 52.48,
+                max_lat: 52.55,
+            },
+        }
+    }
+
+    fn collector(fetcher: &MockFetcher) -> HereCollector {
+        let mut collector = HereCollector::with_fetcher("test-key".into(), Box::new(fetcher.clone()));
+        collector.retry_delay = Duration::ZERO;
+        collector.set_region(berlin_region());
+        collector
+    }
+
+    #[test]
+    fn test_parse_and_dedup() {
+        let recorded = include_str!("testdata/here_discover.json");
+        let fetcher = MockFetcher::new().respond(200, recorded).respond(200, recorded);
+        let collector = collector(&fetcher);
+
+        let (pois, has_more) = collector.search_poi("Bahnhof", 1, "其他", "other").unwrap();
+        let requests = fetcher.requests.lock().clone();
+        assert!(requests[0].0.contains("discover"));
+        assert_eq!(fetcher.last_query("in").as_deref(), Some("bbox:13.3,52.48,13.45,52.55"));
+        // 区域外与重复 id 的 POI 被过滤
+        assert_eq!(pois.len(), 2);
+        assert!(!has_more);
+        let station = &pois[0];
+        assert_eq!(station.name, "Berlin Hauptbahnhof");
+        assert_eq!(station.address, "Europaplatz 1, 10557 Berlin, Deutschland");
+        assert_eq!(station.phone, "+49302970");
+        assert_eq!((station.lon, station.lat), (13.36946, 52.52507));
+        assert_eq!(station.platform, "here");
+
+        // 其他关键词再次命中同一地点时不重复返回
+        let (pois, _) = collector.search_poi("Station", 1, "其他", "other").unwrap();
+        assert!(pois.is_empty());
+    }
+
+    #[test]
+    fn test_browse_by_category() {
+        let fetcher = MockFetcher::new().respond(200, r#"{"items":[]}"#);
+        let collector = collector(&fetcher);
+        collector.search_poi("Klinik", 1, "医疗", "hospital").unwrap();
+
+        let requests = fetcher.requests.lock().clone();
+        assert!(requests[0].0.contains("browse"));
+        assert_eq!(fetcher.last_query("categories").as_deref(), Some("800-8000"));
+        assert_eq!(fetcher.last_query("name").as_deref(), Some("Klinik"));
+        assert_eq!(fetcher.last_query("at").as_deref(), Some("52.515000,13.375000"));
+    }
+
+    #[test]
+    fn test_retry_and_errors() {
+        let too_many = r#"{"status":429,"title":"Too Many Requests","cause":"Rate limit for this service has been reached"}"#;
+        let fetcher = MockFetcher::new()
+            .respond(429, too_many)
+            .respond(200, r#"{"items":[]}"#)
+            .respond(429, too_many)
+            .respond(429, too_many)
+            .respond(429, too_many)
+            .respond(429, too_many)
+            .respond(429, r#"{"status":429,"title":"Too Many Requests","cause":"Quota exceeded for this plan"}"#)
+            .respond(401, r#"{"status":401,"title":"Unauthorized","cause":"apiKey invalid. apiKey not found."}"#);
+        let collector = collector(&fetcher);
+        let search = || collector.search_poi("Bahnhof", 1, "其他", "other");
+
+        // 429 后重试成功
+        assert!(search().unwrap().0.is_empty());
+        assert_eq!(fetcher.requests.lock().len(), 2);
+        // 重试次数用尽
+        assert_eq!(
+            search().unwrap_err(),
+            "请求过于频繁 (429 Too Many Requests: Rate limit for this service has been reached)"
+        );
+        assert_eq!(fetcher.requests.lock().len(), 6);
+        assert_eq!(
+            search().unwrap_err(),
+            "API配额已耗尽 (429 Too Many Requests: Quota exceeded for this plan)"
+        );
+        assert_eq!(
+            search().unwrap_err(),
+            "Key 无效 (401 Unauthorized: apiKey invalid. apiKey not found.)"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! 采集器 HTTP 抽象
+//!
+//! 采集器通过 HttpFetcher 发起请求，运行时使用 reqwest 实现；测试中注入返回录制响应的实现，
+//! 响应解析与错误分类无需联网即可验证。
+
+use reqwest::blocking::Client;
+use serde_json::Value;
+use std::time::Duration;
+
+/// HTTP 响应
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn is_client_error(&self) -> bool {
+        (400..500).contains(&self.status)
+    }
+
+    /// 按 JSON 解析响应体
+    pub fn json(&self) -> Result<Value, String> {
+        serde_json::from_str(&self.body).map_err(|e| format!("解析响应失败: {}", e))
+    }
+}
+
+/// 采集器使用的 HTTP 请求接口
+pub trait HttpFetcher: Send + Sync {
+    /// 发起 GET 请求，query 为查询参数
+    fn get(&self, url: &str, query: &[(&str, &str)]) -> Result<HttpResponse, String>;
+}
+
+/// 基于 reqwest 的实现
+pub struct ReqwestFetcher {
+    client: Client,
+}
+
+impl ReqwestFetcher {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl Default for ReqwestFetcher {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30))
+    }
+}
+
+impl HttpFetcher for ReqwestFetcher {
+    fn get(&self, url: &str, query: &[(&str, &str)]) -> Result<HttpResponse, String> {
+        let response = self
+            .client
+            .get(url)
+            .query(query)
+            .send()
+            .map_err(|e| format!("请求失败: {}", e))?;
+        let status = response.status().as_u16();
+        let body = response
+            .text()
+            .map_err(|e| format!("读取响应失败: {}", e))?;
+        Ok(HttpResponse { status, body })
+    }
+}
+
+/// 测试用：按顺序返回预设响应，并记录收到的请求
+#[cfg(test)]
+pub mod mock {
+    use super::{HttpFetcher, HttpResponse};
+    use crate::collectors::{Bounds, RegionConfig};
+    use parking_lot::Mutex;
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+
+    /// 收到的请求：URL 与查询参数
+    pub type RecordedRequest = (String, Vec<(String, String)>);
+
+    #[derive(Clone, Default)]
+    pub struct MockFetcher {
+        responses: Arc<Mutex<VecDeque<HttpResponse>>>,
+        pub requests: Arc<Mutex<Vec<RecordedRequest>>>,
+    }
+
+    impl MockFetcher {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// 追加一个响应
+        pub fn respond(self, status: u16, body: &str) -> Self {
+            self.responses.lock().push_back(HttpResponse {
+                status,
+                body: body.to_string(),
+            });
+            self
+        }
+
+        /// 最近一次请求中指定查询参数的值
+        pub fn last_query(&self, name: &str) -> Option<String> {
+            let requests = self.requests.lock();
+            let (_, query) = requests.last()?;
+            query
+                .iter()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.clone())
+        }
+    }
+
+    /// 录制响应所用的区域：上海市
+    pub fn shanghai_region() -> RegionConfig {
+        RegionConfig {
+            name: "上海市".to_string(),
+            admin_code: "310000".to_string(),
+            city_code: "021".to_string(),
+            bounds: Bounds {
+                min_lon: 120.85,
+                max_lon: 122.2,
+                min_lat: 30.67,
+                max_lat: 31.88,
+            },
+        }
+    }
+
+    impl HttpFetcher for MockFetcher {
+        fn get(&self, url: &str, query: &[(&str, &str)]) -> Result<HttpResponse, String> {
+            self.requests.lock().push((
+                url.to_string(),
+                query
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            ));
+            self.responses
+                .lock()
+                .pop_front()
+                .ok_or_else(|| "请求失败: 没有预设响应".to_string())
+        }
+    }
+}
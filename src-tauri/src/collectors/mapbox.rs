@@ -0,0 +1,234 @@
+//! Mapbox POI 采集器
+//!
+//! 使用 Search Box 接口的 forward 文本搜索，以区域外接矩形作为 bbox 限定范围，只返回 POI 类型。
+//! 接口不支持分页，每次最多返回 10 条。Key 为 Mapbox 访问令牌，返回坐标为 WGS84，无需转换。
+
+use super::http::{HttpFetcher, ReqwestFetcher};
+use super::{Collector, POIData, RegionConfig};
+use serde_json::Value;
+
+pub struct MapboxCollector {
+    access_token: String,
+    http: Box<dyn HttpFetcher>,
+    region: Option<RegionConfig>,
+}
+
+impl MapboxCollector {
+    const API_URL: &'static str = "https://api.mapbox.com/search/searchbox/v1/forward";
+    const LIMIT: i32 = 10;
+
+    pub fn new(access_token: String) -> Self {
+        Self::with_fetcher(access_token, Box::new(ReqwestFetcher::default()))
+    }
+
+    /// 使用指定的 HTTP 实现创建，测试时可注入录制响应
+    pub fn with_fetcher(access_token: String, http: Box<dyn HttpFetcher>) -> Self {
+        Self {
+            access_token,
+            http,
+            region: None,
+        }
+    }
+
+    fn parse_poi_from_json(&self, raw: &Value, category: &str, category_id: &str) -> Option<POIData> {
+        let properties = raw.get("properties")?;
+        // geometry.coordinates 为 [经度, 纬度]
+        let coordinates = raw.get("geometry")?.get("coordinates")?.as_array()?;
+        let lon = coordinates.first()?.as_f64()?;
+        let lat = coordinates.get(1)?.as_f64()?;
+
+        // bbox 之外的结果需过滤
+        if let Some(ref region) = self.region {
+            let bounds = &region.bounds;
+            if lon < bounds.min_lon || lon > bounds.max_lon ||
+               lat < bounds.min_lat || lat > bounds.max_lat {
+                return None;
+            }
+        }
+
+        let name = properties.get("name")?.as_str()?.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let text = |key: &str| properties.get(key).and_then(|v| v.as_str()).unwrap_or("");
+        // 部分 POI 没有 full_address，由街道地址与所在地拼接
+        let address = match text("full_address") {
+            "" => [text("address"), text("place_formatted")]
+                .iter()
+                .filter(|s| !s.is_empty())
+                .copied()
+                .collect::<Vec<_>>()
+                .join(", "),
+            full => full.to_string(),
+        };
+        let phone = properties
+            .get("metadata")
+            .and_then(|m| m.get("phone"))
+            .and_then(|p| p.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        Some(POIData {
+            name: name.to_string(),
+            lon,
+            lat,
+            original_lon: lon,
+            original_lat: lat,
+            category: category.to_string(),
+            category_id: category_id.to_string(),
+            address,
+            phone,
+            platform: "mapbox".to_string(),
+            raw_data: raw.to_string(),
+        })
+    }
+
+    fn error_message(response: &Value) -> &str {
+        response.get("message").and_then(|m| m.as_str()).unwrap_or("")
+    }
+}
+
+impl Collector for MapboxCollector {
+    fn platform(&self) -> &'static str {
+        "mapbox"
+    }
+
+    fn set_api_key(&mut self, key: String) {
+        self.access_token = key;
+    }
+
+    fn set_region(&mut self, region: RegionConfig) {
+        self.region = Some(region);
+    }
+
+    fn search_poi(&self, keyword: &str, page: usize, category_name: &str, category_id: &str) -> Result<(Vec<POIData>, bool), String> {
+        let region = self.region.as_ref().ok_or("未设置区域配置")?;
+        // 不支持分页，只有第一页
+        if page > 1 {
+            return Ok((Vec::new(), false));
+        }
+
+        let b = &region.bounds;
+        let bbox = format!("{},{},{},{}", b.min_lon, b.min_lat, b.max_lon, b.max_lat);
+        let response = self.http.get(
+            Self::API_URL,
+            &[
+                ("access_token", self.access_token.as_str()),
+                ("q", keyword),
+                ("bbox", &bbox),
+                ("types", "poi"),
+                ("limit", &Self::LIMIT.to_string()),
+                ("language", "zh"),
+            ],
+        )?;
+
+        // 错误响应同样带有 JSON 说明，无法解析时按 HTTP 状态报错
+        let data = match response.json() {
+            Ok(data) => data,
+            Err(_) if response.status == 429 => return Err("请求过于频繁 (429)".to_string()),
+            Err(_) if response.is_client_error() => {
+                return Err(format!("请求被拒绝 (HTTP {})", response.status));
+            }
+            Err(e) => return Err(e),
+        };
+        if response.status != 200 {
+            let message = Self::error_message(&data);
+            if self.is_quota_error(&data) {
+                return Err(format!("API配额已耗尽 ({} {})", response.status, message));
+            }
+            return Err(match response.status {
+                401 | 403 => format!("Key 无效 ({} {})", response.status, message),
+                429 => format!("请求过于频繁 ({} {})", response.status, message),
+                status => format!("接口返回错误 ({} {})", status, message),
+            });
+        }
+
+        let features = data.get("features").and_then(|f| f.as_array()).cloned().unwrap_or_default();
+        let parsed: Vec<POIData> = features.iter()
+            .filter_map(|raw| self.parse_poi_from_json(raw, category_name, category_id))
+            .collect();
+
+        Ok((parsed, false))
+    }
+
+    /// 账户超出用量或欠费时，说明中带有 quota / billing 字样
+    fn is_quota_error(&self, response: &Value) -> bool {
+        let message = Self::error_message(response).to_lowercase();
+        message.contains("quota") || message.contains("billing")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::http::mock::MockFetcher;
+    use crate::collectors::Bounds;
+
+    /// 录制响应所用的区域：巴黎市区
+    fn paris_region() -> RegionConfig {
+        RegionConfig {
+            name: "Paris".to_string(),
+            admin_code: String::new(),
+            city_code: String::new(),
+            bounds: Bounds {
+                min_lon: 2.22,
+                max_lon: 2.47,
+                min_lat: 48.81,
+                max_lat: 48.91,
+            },
+        }
+    }
+
+    fn collector(fetcher: &MockFetcher) -> MapboxCollector {
+        let mut collector = MapboxCollector::with_fetcher("pk.test".into(), Box::new(fetcher.clone()));
+        collector.set_region(paris_region());
+        collector
+    }
+
+    #[test]
+    fn test_parse_recorded_response() {
+        let fetcher = MockFetcher::new().respond(200, include_str!("testdata/mapbox_forward.json"));
+        let collector = collector(&fetcher);
+        let (pois, has_more) = collector.search_poi("musée", 1, "地标", "landmark").unwrap();
+
+        assert_eq!(fetcher.last_query("bbox").as_deref(), Some("2.22,48.81,2.47,48.91"));
+        assert_eq!(fetcher.last_query("types").as_deref(), Some("poi"));
+        // 区域外的 POI 被过滤
+        assert_eq!(pois.len(), 2);
+        assert!(!has_more);
+        let louvre = &pois[0];
+        assert_eq!(louvre.name, "Musée du Louvre");
+        assert_eq!(louvre.address, "Rue de Rivoli, 75001 Paris, France");
+        assert_eq!(louvre.phone, "+33140205050");
+        assert_eq!((louvre.lon, louvre.lat), (2.337644, 48.860611));
+        assert_eq!(louvre.platform, "mapbox");
+        // 没有 full_address 时拼接地址
+        assert_eq!(pois[1].address, "Avenue Gustave Eiffel, 75007 Paris, France");
+        assert_eq!(pois[1].phone, "");
+
+        // 不支持分页，第二页不再请求
+        let (pois, has_more) = collector.search_poi("musée", 2, "地标", "landmark").unwrap();
+        assert!(pois.is_empty() && !has_more);
+        assert_eq!(fetcher.requests.lock().len(), 1);
+    }
+
+    #[test]
+    fn test_error_responses() {
+        let fetcher = MockFetcher::new()
+            .respond(402, r#"{"message":"Monthly quota exceeded, please update your billing information"}"#)
+            .respond(401, r#"{"message":"Invalid Token"}"#)
+            .respond(429, r#"{"message":"Too Many Requests"}"#)
+            .respond(403, "<html>Forbidden</html>");
+        let collector = collector(&fetcher);
+        let search = || collector.search_poi("musée", 1, "地标", "landmark").unwrap_err();
+
+        assert_eq!(
+            search(),
+            "API配额已耗尽 (402 Monthly quota exceeded, please update your billing information)"
+        );
+        assert_eq!(search(), "Key 无效 (401 Invalid Token)");
+        assert_eq!(search(), "请求过于频繁 (429 Too Many Requests)");
+        assert_eq!(search(), "请求被拒绝 (HTTP 403)");
+    }
+}
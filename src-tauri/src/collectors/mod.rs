@@ -1,16 +1,31 @@
 //! 多平台 POI 采集器模块
 //!
-//! 支持天地图、高德地图、百度地图、OpenStreetMap
+//! 支持天地图、高德地图、百度地图、OpenStreetMap、Google Places、Bing Maps、HERE、Mapbox
 
 pub mod amap;
+pub mod around;
 pub mod baidu;
+pub mod bing;
+pub mod category_map;
+pub mod diagnosis;
+pub mod google;
+pub mod grid;
+pub mod here;
+pub mod http;
+pub mod mapbox;
 pub mod osm;
+pub mod polygon;
 pub mod tianditu;
 
 use serde::{Deserialize, Serialize};
 
 pub use amap::AmapCollector;
+pub use around::SearchCircle;
 pub use baidu::BaiduCollector;
+pub use bing::BingCollector;
+pub use google::GoogleCollector;
+pub use here::HereCollector;
+pub use mapbox::MapboxCollector;
 pub use osm::OsmCollector;
 pub use tianditu::TianDiTuCollector;
 
@@ -69,6 +84,25 @@ pub struct POIData {
     pub raw_data: String,
 }
 
+/// 详情接口单次最多查询的 POI 数
+pub const DETAIL_BATCH: usize = 10;
+
+/// 详情接口补全的 POI 信息，平台未提供的字段为空
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PoiDetail {
+    /// 平台 POI ID，百度为 uid，高德为 id
+    pub id: String,
+    pub opening_hours: String,
+    /// 平台原样返回的标签，百度以分号、高德以逗号分隔
+    pub tags: String,
+    pub price: String,
+    pub rating: String,
+    /// 照片地址
+    pub photos: Vec<String>,
+    /// 原样合并进 raw_data 的详情字段
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
 /// 采集器 trait
 pub trait Collector: Send + Sync {
     /// 平台名称
@@ -92,6 +126,67 @@ pub trait Collector: Send + Sync {
 
     /// 检查是否是配额错误
     fn is_quota_error(&self, response: &serde_json::Value) -> bool;
+
+    /// 单个查询最多能取得的结果数，不支持按范围检索的平台为 None
+    fn result_cap(&self) -> Option<usize> {
+        None
+    }
+
+    /// 在指定范围 (WGS84) 内按关键词检索，返回 (结果, 是否有下一页, 接口报告的结果总数)
+    fn search_poi_in_bounds(
+        &self,
+        _keyword: &str,
+        _page: usize,
+        _bounds: &Bounds,
+        _category_name: &str,
+        _category_id: &str,
+    ) -> Result<(Vec<POIData>, bool, usize), String> {
+        Err(format!("{} 不支持按范围检索", self.platform()))
+    }
+
+    /// 是否支持按多边形检索
+    fn supports_polygon_search(&self) -> bool {
+        false
+    }
+
+    /// 在多边形 (WGS84，首尾顶点相同) 内按关键词检索
+    fn search_poi_in_polygon(
+        &self,
+        _keyword: &str,
+        _page: usize,
+        _ring: &[(f64, f64)],
+        _category_name: &str,
+        _category_id: &str,
+    ) -> Result<(Vec<POIData>, bool), String> {
+        Err(format!("{} 不支持按多边形检索", self.platform()))
+    }
+
+    /// 是否支持按中心点与半径检索
+    fn supports_around_search(&self) -> bool {
+        false
+    }
+
+    /// 在圆形范围内按关键词检索
+    fn search_poi_around(
+        &self,
+        _keyword: &str,
+        _page: usize,
+        _circle: &SearchCircle,
+        _category_name: &str,
+        _category_id: &str,
+    ) -> Result<(Vec<POIData>, bool), String> {
+        Err(format!("{} 不支持周边检索", self.platform()))
+    }
+
+    /// raw_data 中平台 POI ID 的字段名，不支持详情补全的平台为 None
+    fn detail_id_key(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// 按平台 POI ID 批量查询详情，一次最多 DETAIL_BATCH 个，查不到的 ID 不出现在结果中
+    fn fetch_details(&self, _ids: &[String]) -> Result<Vec<PoiDetail>, String> {
+        Err(format!("{} 不支持详情补全", self.platform()))
+    }
 }
 
 /// 默认 POI 类别
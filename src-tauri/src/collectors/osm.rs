@@ -1,20 +1,166 @@
 //! OpenStreetMap POI 采集器
 //!
-//! 使用 Overpass API，无需 API Key
+//! 使用 Overpass API，无需 API Key。设置中选定的标签（如 opening_hours、website）
+//! 以结构化 JSON 保存在 raw_data 的 tags 字段中，导出时可展开为列。
+//! 默认按行政区名称的 area 查询，多边形范围使用 poly 过滤。
 
 use super::{Collector, POIData, RegionConfig};
 use serde::Deserialize;
 
+/// Overpass API 镜像列表（按优先级排序，优先使用俄罗斯镜像，国内访问更稳定）
+const OVERPASS_ENDPOINTS: [&str; 4] = [
+    "https://overpass.openstreetmap.ru/api/interpreter",
+    "https://maps.mail.ru/osm/tools/overpass/api/interpreter",
+    "https://overpass.kumi.systems/api/interpreter",
+    "https://overpass-api.de/api/interpreter",
+];
+
+/// 依次尝试各镜像服务器执行 Overpass 查询
+fn post_overpass(
+    client: &reqwest::blocking::Client,
+    query: &str,
+) -> Result<reqwest::blocking::Response, String> {
+    let mut last_error = String::new();
+
+    for (idx, endpoint) in OVERPASS_ENDPOINTS.iter().enumerate() {
+        log::info!("[OSM] 尝试服务器 {}/{}...", idx + 1, OVERPASS_ENDPOINTS.len());
+        match client
+            .post(*endpoint)
+            .body(query.to_string())
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .header("User-Agent", "POI-Collector/1.0")
+            .send()
+        {
+            Ok(resp) if resp.status().is_success() => {
+                log::info!("[OSM] 服务器 {} 响应成功!", idx + 1);
+                return Ok(resp);
+            }
+            Ok(resp) => {
+                last_error = format!("服务器返回 HTTP {}", resp.status());
+                log::warn!("[OSM] 服务器 {} 失败: {}", idx + 1, last_error);
+            }
+            Err(e) => {
+                // 判断错误类型，给出更友好的提示
+                if e.is_timeout() {
+                    last_error = "连接超时（可能需要网络代理）".to_string();
+                } else if e.is_connect() {
+                    last_error = "无法连接服务器（请检查网络）".to_string();
+                } else {
+                    last_error = e.to_string();
+                }
+                log::warn!("[OSM] 服务器 {} 失败: {}", idx + 1, last_error);
+            }
+        }
+    }
+
+    Err(format!(
+        "无法访问 Overpass API，请检查网络连接。最后错误: {}",
+        last_error
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct OverpassCountResponse {
+    elements: Vec<OverpassCountElement>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OverpassCountElement {
+    tags: std::collections::HashMap<String, String>,
+}
+
+/// 区划数据中的占位层级（直辖市下的「市辖区」等），不作为上级区域
+const PLACEHOLDER_REGIONS: [&str; 4] = ["市辖区", "县", "省直辖县级行政区划", "自治区直辖县级行政区划"];
+
+/// 选取行政区的 Overpass 语句，结果存入 .searchArea
+///
+/// 区县名各地常有重名（北京、长春都有朝阳区），只按名称选取会把同名区域合并；
+/// 按区划代码找到上级区划，只在上级区域内选取该名称的行政区
+fn admin_area_query(admin_code: &str, name: &str) -> String {
+    let escape = |s: &str| s.replace(['"', '\\'], "");
+    let parent = std::iter::successors(
+        crate::regions::get_region_by_code(admin_code)
+            .and_then(|r| r.parent_code)
+            .and_then(|code| crate::regions::get_region_by_code(&code)),
+        |r| {
+            r.parent_code
+                .as_deref()
+                .and_then(crate::regions::get_region_by_code)
+        },
+    )
+    .find(|r| !PLACEHOLDER_REGIONS.contains(&r.name.as_str()));
+
+    match parent {
+        Some(parent) => format!(
+            "rel[\"name\"=\"{}\"][\"boundary\"=\"administrative\"];map_to_area->.parentArea;\n\
+             rel[\"name\"=\"{}\"][\"boundary\"=\"administrative\"](area.parentArea);map_to_area->.searchArea;",
+            escape(&parent.name),
+            escape(name)
+        ),
+        None => format!(
+            "area[\"name\"=\"{}\"][\"boundary\"=\"administrative\"]->.searchArea;",
+            escape(name)
+        ),
+    }
+}
+
+/// 统计行政区内满足任一标签过滤条件的 OSM 要素数量
+///
+/// filters 为 Overpass 标签过滤表达式，如 `["amenity"="school"]`
+pub fn count_features(region_code: &str, area_name: &str, filters: &[&str]) -> Result<u64, String> {
+    let selectors: String = filters
+        .iter()
+        .map(|f| format!("  nwr{}(area.searchArea);\n", f))
+        .collect();
+    let query = format!(
+        "[out:json][timeout:90];\n{}\n(\n{});\nout count;\n",
+        admin_area_query(region_code, area_name),
+        selectors
+    );
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(120))
+        .connect_timeout(std::time::Duration::from_secs(15))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+
+    let data: OverpassCountResponse = post_overpass(&client, &query)?
+        .json()
+        .map_err(|e| format!("解析 Overpass 响应失败: {}", e))?;
+
+    Ok(data
+        .elements
+        .first()
+        .and_then(|e| e.tags.get("total"))
+        .and_then(|t| t.parse().ok())
+        .unwrap_or(0))
+}
+
 pub struct OsmCollector {
     region: Option<RegionConfig>,
+    /// 保留到 raw_data 的标签
+    extra_tags: Vec<String>,
 }
 
 impl OsmCollector {
-    pub fn new() -> Self {
-        Self { region: None }
+    pub fn with_extra_tags(extra_tags: Vec<String>) -> Self {
+        Self {
+            region: None,
+            extra_tags,
+        }
     }
 }
 
+/// 从要素标签中取出选定的标签，缺失的标签不出现在结果中
+fn select_tags(
+    tags: &std::collections::HashMap<String, String>,
+    keys: &[String],
+) -> serde_json::Map<String, serde_json::Value> {
+    keys.iter()
+        .filter_map(|key| Some((key.clone(), serde_json::Value::from(tags.get(key)?.as_str()))))
+        .collect()
+}
+
 #[derive(Debug, Deserialize)]
 struct OverpassResponse {
     elements: Vec<OverpassElement>,
@@ -64,28 +210,78 @@ impl Collector for OsmCollector {
             return Ok((vec![], false));
         }
 
-        // 构建 Overpass QL 查询
-        // 使用基于区域名称的 area 查询，避免使用过大的 bounds
+        // 使用行政区 area 查询，避免使用过大的 bounds
         // area 查询比 bbox 查询更精确，对于中国城市效果更好
-        let escaped_keyword = keyword.replace("\"", "").replace("\\", "");
-        let escaped_region = region.name.replace("\"", "").replace("\\", "");
+        let query = build_query(
+            keyword,
+            &admin_area_query(&region.admin_code, &region.name),
+            "(area.searchArea)",
+        );
+
+        log::info!("[OSM] 搜索关键词: {} 区域: {}", keyword, region.name);
+        self.run_query(&query, category_name, category_id)
+    }
+
+    fn supports_polygon_search(&self) -> bool {
+        true
+    }
+
+    fn search_poi_in_polygon(
+        &self,
+        keyword: &str,
+        page: usize,
+        ring: &[(f64, f64)],
+        category_name: &str,
+        category_id: &str,
+    ) -> Result<(Vec<POIData>, bool), String> {
+        if page > 1 {
+            return Ok((vec![], false));
+        }
+        let query = build_query(keyword, "", &poly_filter(ring));
+        log::info!("[OSM] 搜索关键词: {} 多边形顶点: {}", keyword, ring.len());
+        self.run_query(&query, category_name, category_id)
+    }
 
-        // 使用 area 查询来限制到特定行政区
-        let query = format!(
-            r#"[out:json][timeout:60];
-area["name"~"{region}"]["boundary"="administrative"]->.searchArea;
+    fn is_quota_error(&self, _response: &serde_json::Value) -> bool {
+        // OSM 没有配额限制，但有速率限制
+        false
+    }
+}
+
+/// 构建按名称检索 node/way/relation 的 Overpass QL 查询，filter 为空间过滤，如 (area.searchArea)
+fn build_query(keyword: &str, header: &str, filter: &str) -> String {
+    let keyword = keyword.replace("\"", "").replace("\\", "");
+    format!(
+        r#"[out:json][timeout:60];
+{header}
 (
-  node["name"~"{keyword}",i](area.searchArea);
-  way["name"~"{keyword}",i](area.searchArea);
-  relation["name"~"{keyword}",i](area.searchArea);
+  node["name"~"{keyword}",i]{filter};
+  way["name"~"{keyword}",i]{filter};
+  relation["name"~"{keyword}",i]{filter};
 );
 out center body;
-"#,
-            keyword = escaped_keyword,
-            region = escaped_region
-        );
+"#
+    )
+}
 
-        log::info!("[OSM] 搜索关键词: {} 区域: {}", keyword, region.name);
+/// Overpass poly 过滤，顶点以「纬度 经度」空格分隔
+fn poly_filter(ring: &[(f64, f64)]) -> String {
+    let points: Vec<String> = ring
+        .iter()
+        .map(|(lon, lat)| format!("{:.6} {:.6}", lat, lon))
+        .collect();
+    format!(r#"(poly:"{}")"#, points.join(" "))
+}
+
+impl OsmCollector {
+    /// 执行 Overpass 查询并解析结果，区域外的要素被过滤
+    fn run_query(
+        &self,
+        query: &str,
+        category_name: &str,
+        category_id: &str,
+    ) -> Result<(Vec<POIData>, bool), String> {
+        let region = self.region.as_ref().ok_or("未设置区域")?;
         log::info!("[OSM] 正在连接 Overpass API 服务器...");
 
         // 调用 Overpass API - 使用多个镜像服务器
@@ -95,55 +291,7 @@ out center body;
             .build()
             .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
 
-        // Overpass API 镜像列表（按优先级排序，优先使用俄罗斯镜像，国内访问更稳定）
-        let endpoints = [
-            "https://overpass.openstreetmap.ru/api/interpreter",
-            "https://maps.mail.ru/osm/tools/overpass/api/interpreter",
-            "https://overpass.kumi.systems/api/interpreter",
-            "https://overpass-api.de/api/interpreter",
-        ];
-
-        let mut last_error = String::new();
-        let mut response_result = None;
-
-        for (idx, endpoint) in endpoints.iter().enumerate() {
-            log::info!("[OSM] 尝试服务器 {}/{}...", idx + 1, endpoints.len());
-            match client
-                .post(*endpoint)
-                .body(query.clone())
-                .header("Content-Type", "application/x-www-form-urlencoded")
-                .header("User-Agent", "POI-Collector/1.0")
-                .send()
-            {
-                Ok(resp) if resp.status().is_success() => {
-                    log::info!("[OSM] 服务器 {} 响应成功!", idx + 1);
-                    response_result = Some(resp);
-                    break;
-                }
-                Ok(resp) => {
-                    last_error = format!("服务器返回 HTTP {}", resp.status());
-                    log::warn!("[OSM] 服务器 {} 失败: {}", idx + 1, last_error);
-                }
-                Err(e) => {
-                    // 判断错误类型，给出更友好的提示
-                    if e.is_timeout() {
-                        last_error = "连接超时（可能需要网络代理）".to_string();
-                    } else if e.is_connect() {
-                        last_error = "无法连接服务器（请检查网络）".to_string();
-                    } else {
-                        last_error = e.to_string();
-                    }
-                    log::warn!("[OSM] 服务器 {} 失败: {}", idx + 1, last_error);
-                }
-            }
-        }
-
-        let response = response_result.ok_or_else(|| {
-            format!(
-                "无法访问 Overpass API，请检查网络连接。最后错误: {}",
-                last_error
-            )
-        })?;
+        let response = post_overpass(&client, query)?;
 
         let data: OverpassResponse = response
             .json()
@@ -205,10 +353,13 @@ out center body;
                 address,
                 phone,
                 platform: "osm".to_string(),
-                raw_data: format!(
-                    r#"{{"id":{},"type":"{}","osm_category":"{}"}}"#,
-                    element.id, element.element_type, osm_category
-                ),
+                raw_data: serde_json::json!({
+                    "id": element.id,
+                    "type": element.element_type,
+                    "osm_category": osm_category,
+                    "tags": select_tags(&tags, &self.extra_tags),
+                })
+                .to_string(),
             });
         }
 
@@ -220,11 +371,6 @@ out center body;
         // OSM 一次返回所有结果，没有更多页
         Ok((pois, false))
     }
-
-    fn is_quota_error(&self, _response: &serde_json::Value) -> bool {
-        // OSM 没有配额限制，但有速率限制
-        false
-    }
 }
 
 impl OsmCollector {
@@ -274,3 +420,53 @@ impl OsmCollector {
         "unknown".to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_tags() {
+        let tags: std::collections::HashMap<String, String> = [
+            ("name", "国家图书馆"),
+            ("opening_hours", "Tu-Su 09:00-17:00"),
+            ("wheelchair", "yes"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let keys = vec!["opening_hours".to_string(), "website".to_string(), "wheelchair".to_string()];
+
+        let selected = select_tags(&tags, &keys);
+        assert_eq!(
+            serde_json::Value::Object(selected),
+            serde_json::json!({"opening_hours": "Tu-Su 09:00-17:00", "wheelchair": "yes"})
+        );
+        assert!(select_tags(&tags, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_poly_query() {
+        let ring = [(116.3, 39.9), (116.4, 39.9), (116.35, 40.0), (116.3, 39.9)];
+        let query = build_query("图书\"馆", "", &poly_filter(&ring));
+        assert!(query.contains(
+            r#"node["name"~"图书馆",i](poly:"39.900000 116.300000 39.900000 116.400000 40.000000 116.350000 39.900000 116.300000");"#
+        ));
+        assert!(!query.contains("searchArea"));
+    }
+
+    #[test]
+    fn test_admin_area_query() {
+        // 同名区县在各自的上级城市内选取，直辖市跳过「市辖区」
+        let beijing = admin_area_query("110105", "朝阳区");
+        assert!(beijing.starts_with(r#"rel["name"="北京市"]"#));
+        assert!(beijing.contains(r#"rel["name"="朝阳区"]["boundary"="administrative"](area.parentArea)"#));
+        let changchun = admin_area_query("220104", "朝阳区");
+        assert!(changchun.starts_with(r#"rel["name"="长春市"]"#));
+
+        assert_eq!(
+            admin_area_query("11", "北京市"),
+            r#"area["name"="北京市"]["boundary"="administrative"]->.searchArea;"#
+        );
+    }
+}
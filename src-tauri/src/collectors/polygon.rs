@@ -0,0 +1,97 @@
+//! 多边形采集范围
+//!
+//! 前端以 GeoJSON 面要素（WGS84 经纬度）传入任意多边形，如项目红线。支持按多边形检索的平台
+//! （高德多边形搜索、Overpass poly 过滤）直接以多边形查询，其他平台按原方式检索；入库前统一剔除
+//! 多边形外的结果。含多个面或内环（洞）时以外接矩形查询，结果按奇偶规则过滤。
+
+use super::Bounds;
+use crate::polygon_stats::polygon_contains;
+use crate::tile_downloader::clip::{parse_geojson, polygon_bounds};
+use crate::tile_downloader::types::ClipPolygon;
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+pub struct SearchPolygon {
+    rings: ClipPolygon,
+}
+
+impl SearchPolygon {
+    pub fn from_geojson(value: &Value) -> Result<Self, String> {
+        Ok(Self {
+            rings: parse_geojson(value)?,
+        })
+    }
+
+    /// 判断坐标是否在多边形内
+    pub fn contains(&self, lon: f64, lat: f64) -> bool {
+        polygon_contains(&self.rings, lon, lat)
+    }
+
+    /// 外接矩形
+    pub fn bounds(&self) -> Bounds {
+        let extent = polygon_bounds(&self.rings);
+        Bounds {
+            min_lon: extent.west,
+            max_lon: extent.east,
+            min_lat: extent.south,
+            max_lat: extent.north,
+        }
+    }
+
+    /// 提交给接口的多边形：只有一个环时为该环，否则为外接矩形；首尾顶点相同
+    pub fn query_ring(&self) -> Vec<(f64, f64)> {
+        let mut ring = match self.rings.as_slice() {
+            [ring] => ring.clone(),
+            _ => {
+                let b = self.bounds();
+                vec![
+                    (b.min_lon, b.min_lat),
+                    (b.max_lon, b.min_lat),
+                    (b.max_lon, b.max_lat),
+                    (b.min_lon, b.max_lat),
+                ]
+            }
+        };
+        if ring.first() != ring.last() {
+            ring.push(ring[0]);
+        }
+        ring
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_ring() {
+        let triangle = SearchPolygon::from_geojson(&serde_json::json!({
+            "type": "Polygon",
+            "coordinates": [[[121.0, 31.0], [122.0, 31.0], [121.5, 32.0]]]
+        }))
+        .unwrap();
+        assert_eq!(triangle.query_ring().len(), 4);
+        assert_eq!(triangle.query_ring()[3], (121.0, 31.0));
+        assert!(triangle.contains(121.5, 31.5));
+        assert!(!triangle.contains(121.1, 31.9));
+
+        // 带洞的多边形以外接矩形查询
+        let with_hole = SearchPolygon::from_geojson(&serde_json::json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [
+                    [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]],
+                    [[4.0, 4.0], [6.0, 4.0], [6.0, 6.0], [4.0, 6.0], [4.0, 4.0]]
+                ]
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            with_hole.query_ring(),
+            vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0), (0.0, 0.0)]
+        );
+        assert!(!with_hole.contains(5.0, 5.0));
+        assert!(with_hole.contains(2.0, 2.0));
+    }
+}
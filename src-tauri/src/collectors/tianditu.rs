@@ -1,13 +1,13 @@
 //! 天地图 POI 采集器
 
+use super::http::{HttpFetcher, ReqwestFetcher};
 use super::{Collector, POIData, RegionConfig};
-use reqwest::blocking::Client;
 use serde::Serialize;
 use serde_json::Value;
 
 pub struct TianDiTuCollector {
     api_key: String,
-    client: Client,
+    http: Box<dyn HttpFetcher>,
     region: Option<RegionConfig>,
 }
 
@@ -29,12 +29,14 @@ impl TianDiTuCollector {
     const PAGE_SIZE: i32 = 100;
 
     pub fn new(api_key: String) -> Self {
+        Self::with_fetcher(api_key, Box::new(ReqwestFetcher::default()))
+    }
+
+    /// 使用指定的 HTTP 实现创建，测试时可注入录制响应
+    pub fn with_fetcher(api_key: String, http: Box<dyn HttpFetcher>) -> Self {
         Self {
             api_key,
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .unwrap_or_default(),
+            http,
             region: None,
         }
     }
@@ -114,22 +116,20 @@ impl Collector for TianDiTuCollector {
         let post_str = serde_json::to_string(&search_params)
             .map_err(|e| format!("序列化参数失败: {}", e))?;
 
-        let response = self.client
-            .get(Self::API_URL)
-            .query(&[
+        let response = self.http.get(
+            Self::API_URL,
+            &[
                 ("postStr", post_str.as_str()),
                 ("type", "query"),
                 ("tk", &self.api_key),
-            ])
-            .send()
-            .map_err(|e| format!("请求失败: {}", e))?;
+            ],
+        )?;
 
-        if response.status() == 429 {
+        if response.status == 429 {
             return Err("请求过于频繁 (429)".to_string());
         }
 
-        let data: Value = response.json()
-            .map_err(|e| format!("解析响应失败: {}", e))?;
+        let data = response.json()?;
 
         // 检查响应状态
         let status = data.get("status").and_then(|s| s.get("infocode"))
@@ -161,3 +161,41 @@ impl Collector for TianDiTuCollector {
         matches!(infocode, 10001 | 10002 | 10003)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::http::mock::{shanghai_region, MockFetcher};
+
+    fn collector(fetcher: &MockFetcher) -> TianDiTuCollector {
+        let mut collector = TianDiTuCollector::with_fetcher("test-tk".into(), Box::new(fetcher.clone()));
+        collector.set_region(shanghai_region());
+        collector
+    }
+
+    #[test]
+    fn test_parse_recorded_response() {
+        let fetcher = MockFetcher::new().respond(200, include_str!("testdata/tianditu_search.json"));
+        let (pois, has_more) = collector(&fetcher).search_poi("医院", 2, "医疗", "hospital").unwrap();
+
+        let post_str: Value = serde_json::from_str(&fetcher.last_query("postStr").unwrap()).unwrap();
+        assert_eq!(post_str["keyWord"], "上海市 医院");
+        assert_eq!(post_str["start"], 100);
+        assert_eq!(pois.len(), 2);
+        assert!(!has_more);
+
+        let ruijin = &pois[0];
+        assert_eq!(ruijin.name, "上海交通大学医学院附属瑞金医院");
+        assert_eq!((ruijin.lon, ruijin.lat), (121.46735, 31.21156));
+        assert_eq!(ruijin.category_id, "hospital");
+        assert_eq!(pois[1].phone, "");
+    }
+
+    #[test]
+    fn test_quota_error() {
+        let fetcher = MockFetcher::new()
+            .respond(200, r#"{"status":{"infocode":10003,"cndesc":"权限类型错误"}}"#);
+        let err = collector(&fetcher).search_poi("医院", 1, "医疗", "hospital").unwrap_err();
+        assert_eq!(err, "API配额已耗尽");
+    }
+}
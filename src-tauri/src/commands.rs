@@ -1,26 +1,88 @@
 use once_cell::sync::Lazy;
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter};
 
+use crate::budget::{self, BudgetKind, Consumption};
+use crate::collectors::diagnosis::{ApiErrorKind, Diagnosis, ErrorStreak};
+use crate::collectors::grid::{self, SeenPois};
+use crate::collectors::polygon::SearchPolygon;
 use crate::collectors::{
-    default_categories, AmapCollector, BaiduCollector, Bounds, Collector, OsmCollector,
-    RegionConfig as CollectorRegionConfig, TianDiTuCollector,
+    category_map, default_categories, AmapCollector, BaiduCollector, BingCollector, Bounds, Collector,
+    GoogleCollector, HereCollector, MapboxCollector, OsmCollector, POIData, RegionConfig as CollectorRegionConfig, SearchCircle, TianDiTuCollector,
 };
-use crate::config::{get_current_region, set_region, RegionConfig, PRESET_REGIONS};
-use crate::database::Database;
+use crate::config::{
+    get_current_region, render_filename, set_region, ExportSettings, FavoriteRegion, RegionConfig,
+    RegionProfile, SavedArea, PRESET_REGIONS,
+};
+use crate::database::{
+    CollectorSession, Database, Freshness, GridCell, NearbyPOI, PoiAlias, PoiFilter,
+    ResponseCacheKey,
+};
+use crate::db_crypto;
+use crate::error::{AppError, CmdResult};
+
+/// POI 数据库文件路径
+const POI_DB_PATH: &str = "poi_data.db";
 
 // Global state
 static DB: Lazy<Mutex<Database>> =
-    Lazy::new(|| Mutex::new(Database::new("poi_data.db").expect("Failed to init database")));
+    Lazy::new(|| Mutex::new(open_poi_db().expect("Failed to init database")));
 
 static COLLECTOR_STATUSES: Lazy<Mutex<HashMap<String, CollectorStatus>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// 采集分页响应缓存有效期（秒）
+const RESPONSE_CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+
+/// 默认请求间隔（毫秒）
+const DEFAULT_REQUEST_DELAY_MS: u64 = 500;
+
+/// 未设置 QPS 的 Key 按个人 Key 的上限处理
+const DEFAULT_KEY_QPS: u32 = 3;
+
+/// 支持 POI 采集的平台，osm 使用 Overpass API，无需 Key
+pub(crate) const COLLECTOR_PLATFORMS: [&str; 8] =
+    ["tianditu", "amap", "baidu", "osm", "google", "bing", "here", "mapbox"];
+
+/// 单个采集器最多并发的关键词数
+pub(crate) const MAX_COLLECTOR_CONCURRENCY: usize = 8;
+
+/// 今日请求预算用尽时 collect_keyword 返回的错误
+const BUDGET_EXHAUSTED: &str = "今日请求预算已用尽";
+
+/// 预算暂停后检查是否跨天的间隔
+const BUDGET_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 运行中采集器的可调参数
+#[derive(Debug, Clone)]
+struct CollectorControl {
+    /// 请求间隔（毫秒）
+    delay_ms: u64,
+    /// 并发采集的关键词数
+    concurrency: usize,
+    /// 待采类别队列
+    pending: Vec<Category>,
+    /// 跳过当前正在采集的类别
+    skip_current: bool,
+    /// 当前使用的 Key ID，OSM 为空
+    key_id: Option<i64>,
+    /// 待切换的 Key，由工作线程在下次请求前应用
+    key_switch: Option<ApiKey>,
+}
+
+static COLLECTOR_CONTROLS: Lazy<Mutex<HashMap<String, CollectorControl>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 今日配额已耗尽的 Key：Key ID -> 日期，次日恢复可用
+static EXHAUSTED_KEYS: Lazy<Mutex<HashMap<i64, String>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
 // 停止标志
 static STOP_FLAGS: Lazy<Mutex<HashMap<String, AtomicBool>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
@@ -33,8 +95,87 @@ pub struct CollectorStatus {
     pub completed_categories: Vec<String>,
     pub current_category_id: String,
     pub error_message: Option<String>,
+    #[serde(default)]
+    pub total_categories: usize,
+    #[serde(default)]
+    pub started_at: Option<String>,
+    /// 停止原因分类，见 Diagnosis::kind
+    #[serde(default)]
+    pub error_kind: Option<String>,
+    /// 建议暂停的秒数
+    #[serde(default)]
+    pub suggested_pause_secs: Option<u64>,
+    /// 最近一分钟的请求数，未运行时为 0
+    #[serde(default)]
+    pub requests_per_minute: f64,
+    /// 最近一分钟的新增 POI 数，未运行时为 0
+    #[serde(default)]
+    pub pois_per_minute: f64,
+    /// 最近一条采集日志（不含平台前缀）
+    #[serde(default)]
+    pub last_message: Option<String>,
+}
+
+impl CollectorStatus {
+    /// 尚未启动过的采集器状态
+    fn idle(platform: &str) -> Self {
+        Self {
+            platform: platform.to_string(),
+            status: "idle".to_string(),
+            total_collected: 0,
+            completed_categories: vec![],
+            current_category_id: String::new(),
+            error_message: None,
+            total_categories: 0,
+            started_at: None,
+            error_kind: None,
+            suggested_pause_secs: None,
+            requests_per_minute: 0.0,
+            pois_per_minute: 0.0,
+            last_message: None,
+        }
+    }
+}
+
+/// 采集中途停止的原因
+enum StopReason {
+    /// 今日请求预算用尽
+    Budget,
+    /// 接口错误诊断为需要停止
+    Api(Diagnosis),
+}
+
+/// 采集器启动参数，随采集会话持久化，用于恢复采集
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CollectorLaunch {
+    categories: Option<Vec<String>>,
+    regions: Option<Vec<String>>,
+    keywords: Option<Vec<String>>,
+    area_id: Option<String>,
+    /// 排除的下属区域
+    #[serde(default)]
+    exclude_codes: Option<Vec<String>>,
+    /// 采集完成后对新增 POI 调用详情接口补全
+    #[serde(default)]
+    enrich: bool,
+    /// 在收藏范围内按网格自适应细分检索
+    #[serde(default)]
+    grid: bool,
+    /// 多边形范围 (GeoJSON)
+    #[serde(default)]
+    polygon: Option<serde_json::Value>,
+    /// 周边范围（中心点与半径）
+    #[serde(default)]
+    around: Option<SearchCircle>,
 }
 
+/// 运行中采集进度写入数据库的心跳间隔
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+// 各平台最近一次心跳时间
+static LAST_HEARTBEAT: Lazy<Mutex<HashMap<String, Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Category {
     pub id: String,
@@ -49,6 +190,9 @@ pub struct ApiKey {
     pub api_key: String,
     pub is_active: bool,
     pub quota_exhausted: bool,
+    /// QPS 上限，未设置时按个人 Key 处理
+    #[serde(default)]
+    pub qps: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,6 +211,8 @@ pub struct Stats {
     pub total: i64,
     pub by_platform: HashMap<String, i64>,
     pub by_category: HashMap<String, i64>,
+    /// 超过过期天数未复核的数量
+    pub stale: i64,
 }
 
 fn get_poi_categories() -> Vec<Category> {
@@ -81,13 +227,196 @@ fn get_poi_categories() -> Vec<Category> {
 }
 
 fn update_status(platform: &str, f: impl FnOnce(&mut CollectorStatus)) {
-    if let Ok(mut statuses) = COLLECTOR_STATUSES.lock() {
-        if let Some(status) = statuses.get_mut(platform) {
+    let snapshot = match COLLECTOR_STATUSES.lock() {
+        Ok(mut statuses) => statuses.get_mut(platform).map(|status| {
             f(status);
+            if status.status != "running" {
+                status.requests_per_minute = 0.0;
+                status.pois_per_minute = 0.0;
+            }
+            status.clone()
+        }),
+        Err(_) => None,
+    };
+    if let Some(status) = snapshot {
+        persist_heartbeat(&status);
+    }
+}
+
+/// 将采集进度快照写入数据库：运行中按心跳间隔节流，状态变化时立即写入，完成后删除会话
+fn persist_heartbeat(status: &CollectorStatus) {
+    if status.status == "running" {
+        let Ok(mut beats) = LAST_HEARTBEAT.lock() else {
+            return;
+        };
+        if beats
+            .get(&status.platform)
+            .is_some_and(|t| t.elapsed() < HEARTBEAT_INTERVAL)
+        {
+            return;
+        }
+        beats.insert(status.platform.clone(), Instant::now());
+    } else if let Ok(mut beats) = LAST_HEARTBEAT.lock() {
+        beats.remove(&status.platform);
+    }
+
+    let Ok(db) = lock_db() else {
+        return;
+    };
+    let result = if status.status == "completed" {
+        db.delete_collector_session(&status.platform).map(|_| ())
+    } else {
+        db.heartbeat_collector_session(
+            &status.platform,
+            &status.status,
+            status.total_collected,
+            &status.current_category_id,
+            &status.completed_categories,
+        )
+    };
+    if let Err(e) = result {
+        log::warn!("写入采集心跳失败: {}", e);
+    }
+}
+
+/// 从待采队列取出下一个类别
+fn next_category(platform: &str) -> Option<Category> {
+    let mut controls = COLLECTOR_CONTROLS.lock().ok()?;
+    let control = controls.get_mut(platform)?;
+    control.skip_current = false;
+    if control.pending.is_empty() {
+        None
+    } else {
+        Some(control.pending.remove(0))
+    }
+}
+
+/// 当前类别是否已被移除
+fn category_skipped(platform: &str) -> bool {
+    COLLECTOR_CONTROLS
+        .lock()
+        .ok()
+        .and_then(|controls| controls.get(platform).map(|c| c.skip_current))
+        .unwrap_or(false)
+}
+
+fn request_delay(platform: &str) -> Duration {
+    let ms = COLLECTOR_CONTROLS
+        .lock()
+        .ok()
+        .and_then(|controls| controls.get(platform).map(|c| c.delay_ms))
+        .unwrap_or(DEFAULT_REQUEST_DELAY_MS);
+    Duration::from_millis(ms)
+}
+
+fn collector_concurrency(platform: &str) -> usize {
+    COLLECTOR_CONTROLS
+        .lock()
+        .ok()
+        .and_then(|controls| controls.get(platform).map(|c| c.concurrency))
+        .unwrap_or(1)
+}
+
+/// 根据 Key 的 QPS 上限计算请求间隔（毫秒）与并发数
+///
+/// 间隔预留 10% 余量避免触发限流；单次请求耗时按约 0.5 秒估算，
+/// 并发数取能跑满 QPS 所需的线程数
+fn schedule_for_qps(qps: u32) -> (u64, usize) {
+    let qps = qps.max(1);
+    let delay_ms = 1100u64.div_ceil(qps as u64);
+    let concurrency = (qps as usize).div_ceil(2).clamp(1, MAX_COLLECTOR_CONCURRENCY);
+    (delay_ms, concurrency)
+}
+
+/// 跨工作线程共享的请求限流器，保证相邻请求的发起间隔不小于设定值
+struct RequestLimiter {
+    next_slot: Mutex<Instant>,
+}
+
+impl RequestLimiter {
+    fn new() -> Self {
+        Self {
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// 预约下一个请求时间片并等待到该时刻
+    fn wait(&self, interval: Duration) {
+        let slot = match self.next_slot.lock() {
+            Ok(mut next) => {
+                let slot = (*next).max(Instant::now());
+                *next = slot + interval;
+                slot
+            }
+            Err(_) => return,
+        };
+        let now = Instant::now();
+        if slot > now {
+            thread::sleep(slot - now);
+        }
+    }
+}
+
+/// 速率统计窗口
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// 统计最近一分钟的请求数与新增 POI 数
+struct RateMeter {
+    started: Instant,
+    requests: VecDeque<Instant>,
+    pois: VecDeque<(Instant, i64)>,
+}
+
+impl RateMeter {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            requests: VecDeque::new(),
+            pois: VecDeque::new(),
+        }
+    }
+
+    fn record_request(&mut self) {
+        self.requests.push_back(Instant::now());
+    }
+
+    fn record_pois(&mut self, count: i64) {
+        if count > 0 {
+            self.pois.push_back((Instant::now(), count));
+        }
+    }
+
+    /// 每分钟请求数与新增 POI 数，运行不足一分钟时按已运行时长折算
+    fn per_minute(&mut self) -> (f64, f64) {
+        let now = Instant::now();
+        while self.requests.front().is_some_and(|t| now - *t > RATE_WINDOW) {
+            self.requests.pop_front();
+        }
+        while self.pois.front().is_some_and(|(t, _)| now - *t > RATE_WINDOW) {
+            self.pois.pop_front();
         }
+        let window = (now - self.started).clamp(Duration::from_secs(1), RATE_WINDOW);
+        let scale = 60.0 / window.as_secs_f64();
+        let round = |v: f64| (v * scale * 10.0).round() / 10.0;
+        let pois: i64 = self.pois.iter().map(|(_, n)| n).sum();
+        (round(self.requests.len() as f64), round(pois as f64))
     }
 }
 
+/// 记录请求或新增数，并刷新状态中的速率
+fn record_rate(job: &KeywordJob, record: impl FnOnce(&mut RateMeter)) {
+    let Ok(mut meter) = job.meter.lock() else {
+        return;
+    };
+    record(&mut meter);
+    let (requests, pois) = meter.per_minute();
+    drop(meter);
+    update_status(job.platform, |s| {
+        s.requests_per_minute = requests;
+        s.pois_per_minute = pois;
+    });
+}
+
 fn should_stop(platform: &str) -> bool {
     if let Ok(flags) = STOP_FLAGS.lock() {
         if let Some(flag) = flags.get(platform) {
@@ -97,33 +426,95 @@ fn should_stop(platform: &str) -> bool {
     false
 }
 
-fn emit_log(app: &AppHandle, message: &str) {
-    let _ = app.emit("collector-log", message);
+/// 推送采集日志，「[平台] 」开头的消息同时记为该平台的最近日志
+pub(crate) fn emit_log(app: &AppHandle, message: &str) {
+    if let Some((platform, text)) = message.strip_prefix('[').and_then(|m| m.split_once("] ")) {
+        if let Ok(mut statuses) = COLLECTOR_STATUSES.lock() {
+            if let Some(status) = statuses.get_mut(platform) {
+                status.last_message = Some(text.to_string());
+            }
+        }
+    }
+    crate::events::emit_log(app, message);
 }
 
 // Tauri Commands
 
+/// 获取 POI 统计，指定地区时自动包含下属区县
 #[tauri::command]
-pub fn get_stats() -> Result<Stats, String> {
-    let db = DB.lock().map_err(|e| e.to_string())?;
-    db.get_stats().map_err(|e| e.to_string())
+pub fn get_stats(region_codes: Option<Vec<String>>) -> CmdResult<Stats> {
+    let stale_days = crate::config::get_freshness_settings()?.stale_days;
+    let region_codes = region_codes
+        .filter(|codes| !codes.is_empty())
+        .map(|codes| regions::expand_region_codes(&codes));
+    let db = lock_db()?;
+    db.get_stats(region_codes, stale_days).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn get_freshness_settings() -> CmdResult<crate::config::FreshnessSettings> {
+    crate::config::get_freshness_settings().map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn set_freshness_settings(settings: crate::config::FreshnessSettings) -> CmdResult<()> {
+    if settings.stale_days == 0 {
+        return Err(AppError::invalid("过期天数需大于 0"));
+    }
+    crate::config::set_freshness_settings(&settings).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn get_osm_settings() -> CmdResult<crate::config::OsmSettings> {
+    crate::config::get_osm_settings().map_err(AppError::from)
 }
 
+/// 修改 OSM 保留的标签，之后启动的采集生效
 #[tauri::command]
-pub fn get_region_config() -> Result<RegionConfig, String> {
-    get_current_region()
+pub fn set_osm_settings(mut settings: crate::config::OsmSettings) -> CmdResult<()> {
+    let mut seen = std::collections::HashSet::new();
+    settings.extra_tags = settings
+        .extra_tags
+        .iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty() && seen.insert(t.clone()))
+        .collect();
+    crate::config::set_osm_settings(&settings).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn get_region_config() -> CmdResult<RegionConfig> {
+    get_current_region().map_err(AppError::from)
 }
 
 #[tauri::command]
 pub fn get_region_presets() -> Vec<RegionPreset> {
-    PRESET_REGIONS
+    let mut presets: Vec<RegionPreset> = PRESET_REGIONS
         .iter()
         .map(|(id, r)| RegionPreset {
             id: id.clone(),
             name: r.name.clone(),
             admin_code: r.admin_code.clone(),
+            custom: false,
         })
-        .collect()
+        .collect();
+
+    // 用户收藏的区域
+    let favorites = crate::config::list_favorite_regions().unwrap_or_else(|e| {
+        log::warn!("读取收藏区域失败: {}", e);
+        Vec::new()
+    });
+    presets.extend(favorites.iter().filter_map(|f| {
+        let r = f.to_region_config()?;
+        Some(RegionPreset {
+            id: f.code.clone(),
+            name: r.name,
+            admin_code: r.admin_code,
+            custom: true,
+        })
+    }));
+
+    presets
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -131,34 +522,194 @@ pub struct RegionPreset {
     pub id: String,
     pub name: String,
     pub admin_code: String,
+    /// 是否为用户收藏的区域
+    pub custom: bool,
 }
 
 #[tauri::command]
-pub fn set_region_by_preset(preset_id: String) -> Result<RegionConfig, String> {
-    let preset = PRESET_REGIONS
-        .get(&preset_id)
-        .ok_or_else(|| "Invalid preset ID".to_string())?;
+pub fn set_region_by_preset(preset_id: String) -> CmdResult<RegionConfig> {
+    let preset = crate::config::find_preset(&preset_id)
+        .ok_or_else(|| AppError::not_found("Invalid preset ID"))?;
     set_region(preset.clone()).map_err(|e| e.to_string())?;
-    Ok(preset.clone())
+    Ok(preset)
 }
 
+/// 收藏任意行政区为预置区域，bounds 从边界服务获取并缓存
 #[tauri::command]
-pub fn get_api_keys() -> Result<HashMap<String, Vec<ApiKey>>, String> {
-    let db = DB.lock().map_err(|e| e.to_string())?;
-    db.get_all_api_keys().map_err(|e| e.to_string())
+pub async fn add_region_preset(code: String) -> CmdResult<RegionPreset> {
+    if crate::regions::get_region_by_code(&code).is_none() {
+        return Err(AppError::not_found(format!("未找到区域代码: {}", code)));
+    }
+
+    let boundary = crate::tile_downloader::boundaries::get_region_boundary(code.clone(), None).await?;
+    let b = boundary.bounds;
+    if b.west > b.east || b.south > b.north {
+        return Err(AppError::invalid("边界数据无效"));
+    }
+
+    let favorite = FavoriteRegion {
+        code: code.clone(),
+        bounds: crate::config::Bounds {
+            min_lon: b.west,
+            max_lon: b.east,
+            min_lat: b.south,
+            max_lat: b.north,
+        },
+    };
+    let region = favorite
+        .to_region_config()
+        .ok_or_else(|| AppError::not_found(format!("未找到区域代码: {}", code)))?;
+    crate::config::add_favorite_region(favorite)?;
+
+    Ok(RegionPreset {
+        id: code,
+        name: region.name,
+        admin_code: region.admin_code,
+        custom: true,
+    })
 }
 
+/// 取消收藏预置区域
 #[tauri::command]
-pub fn add_api_key(platform: String, api_key: String, name: Option<String>) -> Result<i64, String> {
-    let db = DB.lock().map_err(|e| e.to_string())?;
-    db.add_api_key(&platform, &api_key, name.as_deref())
-        .map_err(|e| e.to_string())
+pub fn remove_region_preset(code: String) -> CmdResult<bool> {
+    crate::config::remove_favorite_region(&code).map_err(AppError::from)
 }
 
 #[tauri::command]
-pub fn delete_api_key(platform: String, key_id: i64) -> Result<(), String> {
-    let db = DB.lock().map_err(|e| e.to_string())?;
-    db.delete_api_key(key_id).map_err(|e| e.to_string())
+pub fn list_region_profiles() -> CmdResult<Vec<RegionProfile>> {
+    crate::config::list_region_profiles().map_err(AppError::from)
+}
+
+/// 保存命名地区配置，region 缺省时记录当前区域配置
+#[tauri::command]
+pub fn save_region_profile(
+    name: String,
+    region_codes: Vec<String>,
+    region: Option<RegionConfig>,
+) -> CmdResult<RegionProfile> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::invalid("请输入配置名称"));
+    }
+
+    let region = match region {
+        Some(region) => Some(region),
+        None => get_current_region().ok(),
+    };
+    let profile = RegionProfile {
+        name,
+        region_codes,
+        region,
+        updated_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    };
+    crate::config::save_region_profile(profile.clone())?;
+    Ok(profile)
+}
+
+/// 加载命名地区配置，并将其区域配置设为当前区域
+#[tauri::command]
+pub fn load_region_profile(name: String) -> CmdResult<RegionProfile> {
+    let profile = crate::config::get_region_profile(&name)?
+        .ok_or_else(|| AppError::not_found(format!("未找到地区配置: {}", name)))?;
+    if let Some(region) = &profile.region {
+        set_region(region.clone())?;
+    }
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn delete_region_profile(name: String) -> CmdResult<bool> {
+    crate::config::delete_region_profile(&name).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn list_saved_areas() -> CmdResult<Vec<SavedArea>> {
+    crate::config::list_saved_areas().map_err(AppError::from)
+}
+
+/// 保存范围收藏，id 为空时新建；提供多边形时按多边形计算外接矩形
+#[tauri::command]
+pub fn save_area(area: SavedArea) -> CmdResult<SavedArea> {
+    let mut area = area;
+    area.name = area.name.trim().to_string();
+    if area.name.is_empty() {
+        return Err(AppError::invalid("请输入范围名称"));
+    }
+    if area.id.is_empty() {
+        area.id = uuid::Uuid::new_v4().to_string();
+    }
+
+    if !area.polygon.is_empty() {
+        if area.polygon.len() < 3 {
+            return Err(AppError::invalid("多边形至少需要 3 个顶点"));
+        }
+        let (lons, lats): (Vec<f64>, Vec<f64>) = area.polygon.iter().copied().unzip();
+        area.bounds = crate::config::Bounds {
+            min_lon: lons.iter().copied().fold(f64::INFINITY, f64::min),
+            max_lon: lons.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            min_lat: lats.iter().copied().fold(f64::INFINITY, f64::min),
+            max_lat: lats.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        };
+    }
+    if area.bounds.min_lon >= area.bounds.max_lon || area.bounds.min_lat >= area.bounds.max_lat {
+        return Err(AppError::invalid("无效的范围边界"));
+    }
+
+    area.updated_at = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    crate::config::save_saved_area(area.clone())?;
+    Ok(area)
+}
+
+/// 按 ID 获取收藏的范围
+pub(crate) fn find_saved_area(id: &str) -> CmdResult<crate::config::SavedArea> {
+    crate::config::get_saved_area(id)?
+        .ok_or_else(|| AppError::not_found(format!("未找到收藏的范围: {}", id)))
+}
+
+/// 设置范围是否为关注区域
+#[tauri::command]
+pub fn set_area_watch(id: String, watch: bool) -> CmdResult<SavedArea> {
+    let mut area = find_saved_area(&id)?;
+    area.watch = watch;
+    crate::config::save_saved_area(area.clone())?;
+    Ok(area)
+}
+
+#[tauri::command]
+pub fn delete_saved_area(id: String) -> CmdResult<bool> {
+    crate::config::delete_saved_area(&id).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn get_api_keys() -> CmdResult<HashMap<String, Vec<ApiKey>>> {
+    let db = lock_db()?;
+    db.get_all_api_keys().map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn add_api_key(
+    platform: String,
+    api_key: String,
+    name: Option<String>,
+    qps: Option<u32>,
+) -> CmdResult<i64> {
+    let db = lock_db()?;
+    db.add_api_key(&platform, &api_key, name.as_deref(), qps.filter(|q| *q > 0))
+        .map_err(AppError::from)
+}
+
+/// 设置 Key 的 QPS 上限，下次启动采集时生效
+#[tauri::command]
+pub fn set_api_key_qps(key_id: i64, qps: Option<u32>) -> CmdResult<()> {
+    let db = lock_db()?;
+    db.set_api_key_qps(key_id, qps.filter(|q| *q > 0))
+        .map_err(|e| AppError::from(format!("设置 QPS 失败: {}", e)))
+}
+
+#[tauri::command]
+pub fn delete_api_key(platform: String, key_id: i64) -> CmdResult<()> {
+    let db = lock_db()?;
+    db.delete_api_key(key_id).map_err(AppError::from)
 }
 
 #[tauri::command]
@@ -168,7 +719,13 @@ pub fn get_categories() -> Vec<Category> {
 
 #[tauri::command]
 pub fn get_collector_statuses() -> HashMap<String, CollectorStatus> {
-    COLLECTOR_STATUSES.lock().unwrap().clone()
+    let mut statuses = COLLECTOR_STATUSES.lock().unwrap().clone();
+    for platform in COLLECTOR_PLATFORMS {
+        statuses
+            .entry(platform.to_string())
+            .or_insert_with(|| CollectorStatus::idle(platform));
+    }
+    statuses
 }
 
 #[tauri::command]
@@ -177,72 +734,84 @@ pub fn start_collector(
     platform: String,
     categories: Option<Vec<String>>,
     regions: Option<Vec<String>>,
-) -> Result<(), String> {
+    keywords: Option<Vec<String>>,
+    area_id: Option<String>,
+    exclude_codes: Option<Vec<String>>,
+    enrich: Option<bool>,
+    grid: Option<bool>,
+    polygon: Option<serde_json::Value>,
+    around: Option<SearchCircle>,
+) -> CmdResult<()> {
     // 检查是否已在运行
     {
         let statuses = COLLECTOR_STATUSES.lock().map_err(|e| e.to_string())?;
         if let Some(status) = statuses.get(&platform) {
             if status.status == "running" {
-                return Err("采集器已在运行中".to_string());
+                return Err(AppError::conflict("采集器已在运行中"));
             }
         }
     }
 
-    // 获取 API Key (OSM 不需要，使用免费的 Overpass API)
-    let api_key = if platform == "osm" {
-        String::new()
-    } else {
-        let db = DB.lock().map_err(|e| e.to_string())?;
-        let keys = db.get_all_api_keys().map_err(|e| e.to_string())?;
-        let platform_keys = keys.get(&platform).cloned().unwrap_or_default();
-        platform_keys
-            .into_iter()
-            .find(|k| k.is_active && !k.quota_exhausted)
-            .map(|k| k.api_key)
-            .ok_or_else(|| format!("{}没有可用的 API Key", platform))?
-    };
+    // 获取 API Key (OSM 不需要，使用免费的 Overpass API)，优先使用 QPS 上限最高的 Key
+    let (key, schedule) = select_key(&platform)?;
 
     // 获取区域配置 - 必须使用用户选择的地区
-    let region_codes = regions.ok_or_else(|| "请先选择采集地区".to_string())?;
+    let region_codes = regions.ok_or_else(|| AppError::invalid("请先选择采集地区"))?;
     if region_codes.is_empty() {
-        return Err("请先选择采集地区".to_string());
+        return Err(AppError::invalid("请先选择采集地区"));
     }
 
     // 使用第一个选中的区域
     let region_code = &region_codes[0];
 
-    // 从 regions 模块获取区域信息
-    let region_info = crate::regions::get_region_by_code(region_code)
-        .ok_or_else(|| format!("未找到区域代码: {}", region_code))?;
-
-    // 使用中国范围作为 bounds，让 API 按区域名称过滤
-    let bounds = Bounds {
-        min_lon: 73.0,
-        max_lon: 135.0,
-        min_lat: 18.0,
-        max_lat: 54.0,
-    };
-
-    // 获取父级城市代码
-    let city_code = if region_info.level == "district" {
-        region_info
-            .parent_code
-            .clone()
-            .unwrap_or_else(|| region_code.clone())
-    } else {
-        region_code.clone()
+    // 引用收藏范围时只保留范围内的结果，否则使用中国范围作为 bounds，让 API 按区域名称过滤
+    let area = area_id.as_deref().map(find_saved_area).transpose()?;
+    // 多边形范围以外接矩形作为区域边界，入库前剔除多边形外的结果
+    let search_polygon = polygon
+        .as_ref()
+        .map(SearchPolygon::from_geojson)
+        .transpose()
+        .map_err(AppError::invalid)?;
+    // 周边范围同样以外接矩形作为区域边界，入库前剔除圆外的结果
+    if let Some(circle) = &around {
+        circle.validate().map_err(AppError::invalid)?;
+    }
+    let bounds = match (&area, &search_polygon, &around) {
+        (Some(area), None, None) => Bounds {
+            min_lon: area.bounds.min_lon,
+            max_lon: area.bounds.max_lon,
+            min_lat: area.bounds.min_lat,
+            max_lat: area.bounds.max_lat,
+        },
+        (None, Some(polygon), None) => polygon.bounds(),
+        (None, None, Some(circle)) => circle.bounds(),
+        (None, None, None) => Bounds {
+            min_lon: 73.0,
+            max_lon: 135.0,
+            min_lat: 18.0,
+            max_lat: 54.0,
+        },
+        _ => return Err(AppError::invalid("收藏范围、多边形与周边范围只能指定一个")),
     };
 
-    log::info!("使用区域: {} ({})", region_info.name, region_code);
-
-    let collector_region = CollectorRegionConfig {
-        name: region_info.name,
-        admin_code: region_code.clone(),
-        city_code,
-        bounds,
-    };
+    let collector_region = collector_region(region_code, bounds).map_err(AppError::not_found)?;
+    log::info!("使用区域: {} ({})", collector_region.name, region_code);
+
+    // 排除下属区域：展开到区县后剔除，采集结果中落在被排除区县的 POI 不入库
+    let exclude_codes = exclude_codes.filter(|codes| !codes.is_empty());
+    let exclusion =
+        regions::RegionExclusion::new(region_code, exclude_codes.as_deref().unwrap_or_default())
+            .map_err(AppError::invalid)?;
+    if let Some(exclusion) = &exclusion {
+        let remaining = exclusion.remaining_districts(region_code);
+        if remaining.is_empty() {
+            return Err(AppError::invalid("排除后没有剩余的区县"));
+        }
+        log::info!("排除 {:?}，剩余 {} 个区县", exclude_codes, remaining.len());
+    }
 
     // 获取选中的类别
+    let categories_arg = categories.clone();
     let all_categories = get_poi_categories();
     let selected_cats: Vec<Category> = match categories {
         Some(ids) => all_categories
@@ -253,7 +822,73 @@ pub fn start_collector(
     };
 
     if selected_cats.is_empty() {
-        return Err("未选择采集类别".to_string());
+        return Err(AppError::invalid("未选择采集类别"));
+    }
+
+    // 指定关键词模式：用关键词列表覆盖类别词库，结果仍归入所选类别
+    let keywords: Option<Vec<String>> = keywords
+        .map(|list| {
+            list.into_iter()
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .filter(|list| !list.is_empty());
+    let selected_cats = match &keywords {
+        Some(list) => {
+            if categories_arg.as_ref().is_none_or(|c| c.len() != 1) {
+                return Err(AppError::invalid("指定关键词采集时请只选择一个类别"));
+            }
+            let mut cat = selected_cats.into_iter().next().unwrap();
+            log::info!("使用指定关键词采集 {}: {:?}", cat.name, list);
+            cat.keywords = list.clone();
+            vec![cat]
+        }
+        None => selected_cats,
+    };
+
+    let enrich = enrich.unwrap_or(false);
+    if enrich
+        && create_collector(&platform, String::new()).is_none_or(|c| c.detail_id_key().is_none())
+    {
+        return Err(AppError::invalid(format!(
+            "{} 不支持详情补全",
+            provenance::platform_name(&platform)
+        )));
+    }
+
+    // 网格模式按范围检索，需要收藏范围给出明确的边界
+    let grid = grid.unwrap_or(false);
+    if grid {
+        if area.is_none() {
+            return Err(AppError::invalid("网格检索需要选择收藏范围"));
+        }
+        if create_collector(&platform, String::new()).is_none_or(|c| c.result_cap().is_none()) {
+            return Err(AppError::invalid(format!(
+                "{} 不支持网格检索",
+                provenance::platform_name(&platform)
+            )));
+        }
+    }
+
+    // 记录启动参数，同时持久化以便崩溃重启后恢复
+    let started_at = chrono::Local::now().to_rfc3339();
+    {
+        let launch = CollectorLaunch {
+            categories: categories_arg,
+            regions: Some(region_codes.clone()),
+            keywords,
+            area_id,
+            exclude_codes,
+            enrich,
+            grid,
+            polygon,
+            around: around.clone(),
+        };
+        let launch_json = serde_json::to_string(&launch).map_err(|e| e.to_string())?;
+        lock_db()?
+            .start_collector_session(&platform, &launch_json, &started_at)
+            .map_err(|e| format!("记录采集会话失败: {}", e))?;
     }
 
     // 初始化状态
@@ -268,6 +903,13 @@ pub fn start_collector(
                 completed_categories: vec![],
                 current_category_id: String::new(),
                 error_message: None,
+                total_categories: selected_cats.len(),
+                started_at: Some(started_at),
+                error_kind: None,
+                suggested_pause_secs: None,
+                requests_per_minute: 0.0,
+                pois_per_minute: 0.0,
+                last_message: None,
             },
         );
     }
@@ -278,15 +920,37 @@ pub fn start_collector(
         flags.insert(platform.clone(), AtomicBool::new(false));
     }
 
+    // 初始化待采队列，请求间隔与并发按 Key 的 QPS 设定
+    {
+        let mut controls = COLLECTOR_CONTROLS.lock().map_err(|e| e.to_string())?;
+        let (delay_ms, concurrency) = schedule;
+        controls.insert(
+            platform.clone(),
+            CollectorControl {
+                delay_ms,
+                concurrency,
+                pending: selected_cats,
+                skip_current: false,
+                key_id: key.as_ref().map(|k| k.id),
+                key_switch: None,
+            },
+        );
+    }
+
     // 启动后台线程
     let platform_clone = platform.clone();
     thread::spawn(move || {
         run_collector(
             app,
             platform_clone,
-            api_key,
+            key,
             collector_region,
-            selected_cats,
+            area,
+            exclusion,
+            enrich,
+            grid,
+            search_polygon,
+            around,
         );
     });
 
@@ -294,38 +958,153 @@ pub fn start_collector(
     Ok(())
 }
 
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// 从 Key 表选取平台的 Key：指定 key_id 时使用该 Key，否则在启用、未耗尽配额的 Key 中
+/// 取 QPS 上限最高的，exclude 指定的 Key 不参与选取
+fn pick_api_key(platform: &str, key_id: Option<i64>, exclude: Option<i64>) -> CmdResult<ApiKey> {
+    let keys = lock_db()?.get_all_api_keys().map_err(|e| e.to_string())?;
+    let platform_keys = keys.get(platform).cloned().unwrap_or_default();
+    if let Some(id) = key_id {
+        return platform_keys
+            .into_iter()
+            .find(|k| k.id == id && k.is_active)
+            .ok_or_else(|| {
+                AppError::not_found(format!("{}没有 ID 为 {} 的可用 Key", platform, id))
+            });
+    }
+
+    let today = today();
+    let exhausted = EXHAUSTED_KEYS.lock().map_err(|e| e.to_string())?;
+    let has_active = platform_keys.iter().any(|k| k.is_active);
+    platform_keys
+        .into_iter()
+        .filter(|k| k.is_active && !k.quota_exhausted && Some(k.id) != exclude)
+        .filter(|k| exhausted.get(&k.id) != Some(&today))
+        .max_by_key(|k| k.qps.unwrap_or(DEFAULT_KEY_QPS))
+        .ok_or_else(|| {
+            let message = format!("{}没有可用的 API Key", platform);
+            // 有启用的 Key 但均已用尽配额或被排除
+            if has_active {
+                AppError::quota(message)
+            } else {
+                AppError::not_found(message)
+            }
+        })
+}
+
+/// 选取平台可用的 API Key，优先使用 QPS 上限最高的 Key，返回 Key 与 (请求间隔, 并发数)
+///
+/// OSM 不需要 Key，使用免费的 Overpass API，返回的 Key 为空
+fn select_key(platform: &str) -> CmdResult<(Option<ApiKey>, (u64, usize))> {
+    if platform == "osm" {
+        return Ok((None, (DEFAULT_REQUEST_DELAY_MS, 1)));
+    }
+    let key = pick_api_key(platform, None, None)?;
+    let qps = key.qps.unwrap_or(DEFAULT_KEY_QPS);
+    log::info!("{} 使用 QPS {} 的 Key 采集", platform, qps);
+    Ok((Some(key), schedule_for_qps(qps)))
+}
+
+/// 同 select_key，只返回 Key 字符串
+pub(crate) fn select_api_key(platform: &str) -> CmdResult<(String, (u64, usize))> {
+    let (key, schedule) = select_key(platform)?;
+    Ok((key.map(|k| k.api_key).unwrap_or_default(), schedule))
+}
+
+/// 按区域代码构造采集器区域配置，区县级使用父级城市代码
+pub(crate) fn collector_region(
+    region_code: &str,
+    bounds: Bounds,
+) -> Result<CollectorRegionConfig, String> {
+    let region_info = crate::regions::get_region_by_code(region_code)
+        .ok_or_else(|| format!("未找到区域代码: {}", region_code))?;
+    let city_code = if region_info.level == "district" {
+        region_info
+            .parent_code
+            .clone()
+            .unwrap_or_else(|| region_code.to_string())
+    } else {
+        region_code.to_string()
+    };
+    Ok(CollectorRegionConfig {
+        name: region_info.name,
+        admin_code: region_code.to_string(),
+        city_code,
+        bounds,
+    })
+}
+
+/// 创建平台采集器，不支持的平台返回 None
+pub(crate) fn create_collector(platform: &str, api_key: String) -> Option<Box<dyn Collector>> {
+    let collector: Box<dyn Collector> = match platform {
+        "tianditu" => Box::new(TianDiTuCollector::new(api_key)),
+        "amap" => Box::new(AmapCollector::new(api_key)),
+        "baidu" => Box::new(BaiduCollector::new(api_key)),
+        "osm" => Box::new(OsmCollector::with_extra_tags(
+            crate::config::get_osm_settings().unwrap_or_default().extra_tags,
+        )),
+        "google" => Box::new(GoogleCollector::new(api_key)),
+        "bing" => Box::new(BingCollector::new(api_key)),
+        "here" => Box::new(HereCollector::new(api_key)),
+        "mapbox" => Box::new(MapboxCollector::new(api_key)),
+        _ => return None,
+    };
+    Some(collector)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn run_collector(
     app: AppHandle,
     platform: String,
-    api_key: String,
+    key: Option<ApiKey>,
     region: CollectorRegionConfig,
-    categories: Vec<Category>,
+    area: Option<SavedArea>,
+    exclusion: Option<regions::RegionExclusion>,
+    enrich: bool,
+    grid: bool,
+    polygon: Option<SearchPolygon>,
+    around: Option<SearchCircle>,
 ) {
     emit_log(&app, &format!("[{}] 开始采集...", platform));
 
     // 创建采集器
-    let mut collector: Box<dyn Collector> = match platform.as_str() {
-        "tianditu" => Box::new(TianDiTuCollector::new(api_key)),
-        "amap" => Box::new(AmapCollector::new(api_key)),
-        "baidu" => Box::new(BaiduCollector::new(api_key)),
-        "osm" => Box::new(OsmCollector::new()),
-        _ => {
-            update_status(&platform, |s| {
-                s.status = "error".to_string();
-                s.error_message = Some("不支持的平台".to_string());
-            });
-            return;
-        }
+    let api_key = key.map(|k| k.api_key).unwrap_or_default();
+    let Some(mut collector) = create_collector(&platform, api_key) else {
+        update_status(&platform, |s| {
+            s.status = "error".to_string();
+            s.error_message = Some("不支持的平台".to_string());
+        });
+        return;
     };
 
     // 保存区域代码用于数据库插入（region 会被 move）
     let region_code = region.admin_code.clone();
+    // 网格模式以采集范围（即收藏范围的外接矩形）为根网格
+    let grid_root = grid.then(|| region.bounds.clone());
+    let result_cap = collector.result_cap().unwrap_or(usize::MAX);
+    // 支持按多边形检索的平台直接提交多边形，其他平台只在入库前过滤
+    let polygon_ring = polygon
+        .as_ref()
+        .filter(|_| collector.supports_polygon_search())
+        .map(SearchPolygon::query_ring);
+    let around_search = around
+        .as_ref()
+        .filter(|_| collector.supports_around_search());
     collector.set_region(region);
-
-    let mut total_collected: i64 = 0;
+    // 工作线程共享读锁发起请求，切换 Key 时取写锁
+    let collector = RwLock::new(collector);
+
+    let total_collected = AtomicI64::new(0);
+    let limiter = RequestLimiter::new();
+    let streak = Mutex::new(ErrorStreak::default());
+    let inserted_ids = Mutex::new(Vec::new());
+    let meter = Mutex::new(RateMeter::new());
     let mut completed_categories: Vec<String> = vec![];
 
-    for cat in &categories {
+    while let Some(cat) = next_category(&platform) {
         if should_stop(&platform) {
             emit_log(&app, &format!("[{}] 采集已暂停", platform));
             update_status(&platform, |s| {
@@ -340,96 +1119,92 @@ fn run_collector(
 
         emit_log(&app, &format!("[{}] 采集类别: {}", platform, cat.name));
 
-        for keyword in &cat.keywords {
-            if should_stop(&platform) {
-                return;
-            }
-
-            let mut page = 1;
-            loop {
-                if should_stop(&platform) {
-                    return;
-                }
-
-                // 限流：每次请求间隔 500ms
-                thread::sleep(Duration::from_millis(500));
-
-                match collector.search_poi(keyword, page, &cat.name, &cat.id) {
-                    Ok((pois, has_more)) => {
-                        if pois.is_empty() {
-                            break;
-                        }
+        // 每个类别开始时重新读取关注区域，运行中的调整随之生效
+        let watched = crate::config::list_watched_areas().unwrap_or_else(|e| {
+            log::warn!("读取关注区域失败: {}", e);
+            Vec::new()
+        });
 
-                        // 保存到数据库
-                        let saved = {
-                            if let Ok(db) = DB.lock() {
-                                let mut count = 0;
-                                for poi in &pois {
-                                    match db.insert_poi(
-                                        &poi.name,
-                                        poi.lon,
-                                        poi.lat,
-                                        poi.original_lon,
-                                        poi.original_lat,
-                                        &cat.name,
-                                        &cat.id,
-                                        &poi.address,
-                                        &poi.phone,
-                                        &poi.platform,
-                                        &region_code,
-                                        &poi.raw_data,
-                                    ) {
-                                        Ok(true) => count += 1,
-                                        Ok(false) => {} // 重复数据，忽略
-                                        Err(e) => {
-                                            log::warn!("插入 POI 失败: {}", e);
-                                        }
-                                    }
-                                }
-                                count
-                            } else {
-                                log::error!("无法获取数据库锁");
-                                0
-                            }
-                        };
-
-                        total_collected += saved;
-
-                        emit_log(
-                            &app,
-                            &format!(
-                                "[{}] {} 第{}页: 获取{}条, 新增{}条",
-                                platform,
-                                keyword,
-                                page,
-                                pois.len(),
-                                saved
-                            ),
-                        );
-
-                        update_status(&platform, |s| {
-                            s.total_collected = total_collected;
-                        });
-
-                        if !has_more {
-                            break;
-                        }
-                        page += 1;
+        // 多个工作线程按关键词并发采集，共享同一个请求限流器
+        let job = KeywordJob {
+            app: &app,
+            platform: &platform,
+            region_code: &region_code,
+            cat: &cat,
+            collector: &collector,
+            area: area.as_ref(),
+            exclusion: exclusion.as_ref(),
+            total_collected: &total_collected,
+            limiter: &limiter,
+            streak: &streak,
+            watched: &watched,
+            inserted_ids: enrich.then_some(&inserted_ids),
+            grid: grid_root.as_ref().map(|root| (root, result_cap)),
+            meter: &meter,
+            polygon: polygon.as_ref(),
+            polygon_ring: polygon_ring.as_deref(),
+            around: around.as_ref(),
+            around_search,
+        };
+        let next_keyword = AtomicUsize::new(0);
+        let stop_reason: Mutex<Option<StopReason>> = Mutex::new(None);
+        let workers = collector_concurrency(&platform).min(cat.keywords.len()).max(1);
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    if should_stop(&platform) || category_skipped(&platform) {
+                        return;
                     }
-                    Err(e) => {
-                        emit_log(&app, &format!("[{}] 采集错误: {}", platform, e));
-                        // 配额错误时停止
-                        if e.contains("配额") {
-                            update_status(&platform, |s| {
-                                s.status = "error".to_string();
-                                s.error_message = Some(e);
-                            });
-                            return;
+                    if stop_reason.lock().map(|r| r.is_some()).unwrap_or(true) {
+                        return;
+                    }
+                    let index = next_keyword.fetch_add(1, Ordering::Relaxed);
+                    let Some(keyword) = cat.keywords.get(index) else {
+                        return;
+                    };
+                    if let Err(reason) = collect_keyword(&job, keyword) {
+                        if let Ok(mut stop) = stop_reason.lock() {
+                            stop.get_or_insert(reason);
                         }
-                        break;
+                        return;
                     }
-                }
+                });
+            }
+        });
+
+        // 预算用尽时暂停，次日自动继续；IP 受限或疑似封禁时暂停并给出建议；Key 不可用时停止
+        match stop_reason.into_inner().ok().flatten() {
+            Some(StopReason::Budget) => {
+                emit_log(
+                    &app,
+                    &format!("[{}] {}，已暂停，次日零点后自动继续", platform, BUDGET_EXHAUSTED),
+                );
+                update_status(&platform, |s| {
+                    s.status = "paused".to_string();
+                    s.error_message = Some(BUDGET_EXHAUSTED.to_string());
+                });
+                resume_after_budget_reset(app.clone(), platform.clone());
+                return;
             }
+            Some(StopReason::Api(diagnosis)) => {
+                emit_log(&app, &format!("[{}] {}", platform, diagnosis.message));
+                update_status(&platform, |s| {
+                    s.status = if diagnosis.resumable { "paused" } else { "error" }.to_string();
+                    s.error_message = Some(diagnosis.message);
+                    s.error_kind = Some(diagnosis.kind.to_string());
+                    s.suggested_pause_secs = diagnosis.suggested_pause_secs;
+                });
+                return;
+            }
+            None => {}
+        }
+        if should_stop(&platform) {
+            return;
+        }
+
+        if category_skipped(&platform) {
+            emit_log(&app, &format!("[{}] 已跳过类别: {}", platform, cat.name));
+            continue;
         }
 
         completed_categories.push(cat.id.clone());
@@ -440,16 +1215,531 @@ fn run_collector(
 
     emit_log(
         &app,
-        &format!("[{}] 采集完成，共{}条", platform, total_collected),
+        &format!(
+            "[{}] 采集完成，共{}条",
+            platform,
+            total_collected.load(Ordering::Relaxed)
+        ),
     );
+    if enrich {
+        let ids = inserted_ids.into_inner().unwrap_or_default();
+        enrich_inserted(&app, &platform, &collector, &ids);
+    }
     update_status(&platform, |s| {
         s.status = "completed".to_string();
         s.current_category_id = String::new();
     });
 }
 
+/// 对本次新增的 POI 调用详情接口补全，失败只记录日志，不影响采集结果
+fn enrich_inserted(
+    app: &AppHandle,
+    platform: &str,
+    collector: &RwLock<Box<dyn Collector>>,
+    ids: &[i64],
+) {
+    let collector = collector.read();
+    let Some(id_key) = collector.detail_id_key() else {
+        return;
+    };
+    let pois = lock_db()
+        .map_err(String::from)
+        .and_then(|db| {
+            db.get_pois_to_enrich(platform, id_key, Some(ids), ids.len())
+                .map_err(|e| format!("读取待补全数据失败: {}", e))
+        })
+        .unwrap_or_else(|e| {
+            log::warn!("{}", e);
+            Vec::new()
+        });
+    if pois.is_empty() {
+        return;
+    }
+
+    emit_log(app, &format!("[{}] 开始补全详情，共{}条", platform, pois.len()));
+    let result = crate::enrichment::run_enrichment(collector.as_ref(), request_delay(platform), pois);
+    let mut message = format!(
+        "[{}] 详情补全完成：补全{}条，未查到{}条",
+        platform, result.enriched, result.not_found
+    );
+    if let Some(e) = result.error_message {
+        message.push_str(&format!("，提前结束: {}", e));
+    }
+    emit_log(app, &message);
+}
+
+/// 单个类别下按关键词采集时共享的上下文
+struct KeywordJob<'a> {
+    app: &'a AppHandle,
+    platform: &'a str,
+    region_code: &'a str,
+    cat: &'a Category,
+    collector: &'a RwLock<Box<dyn Collector>>,
+    area: Option<&'a SavedArea>,
+    /// 排除的下属区域
+    exclusion: Option<&'a regions::RegionExclusion>,
+    total_collected: &'a AtomicI64,
+    limiter: &'a RequestLimiter,
+    /// 连续错误跟踪，用于识别配额耗尽与 IP 封禁
+    streak: &'a Mutex<ErrorStreak>,
+    /// 关注区域，新增 POI 落入时发出提醒
+    watched: &'a [SavedArea],
+    /// 需要补全详情时收集新增 POI 的 id
+    inserted_ids: Option<&'a Mutex<Vec<i64>>>,
+    /// 网格模式的根范围与单个查询的结果上限
+    grid: Option<(&'a Bounds, usize)>,
+    /// 本次运行的速率统计
+    meter: &'a Mutex<RateMeter>,
+    /// 多边形范围
+    polygon: Option<&'a SearchPolygon>,
+    /// 按多边形检索时提交的顶点
+    polygon_ring: Option<&'a [(f64, f64)]>,
+    /// 周边范围
+    around: Option<&'a SearchCircle>,
+    /// 平台支持周边检索时按圆形范围检索
+    around_search: Option<&'a SearchCircle>,
+}
+
+/// 关注区域命中事件
+#[derive(Debug, Clone, Serialize)]
+pub struct GeofenceHit {
+    pub area_id: String,
+    pub area_name: String,
+    pub poi_id: i64,
+    pub name: String,
+    pub lon: f64,
+    pub lat: f64,
+    pub category: String,
+    pub address: String,
+    pub platform: String,
+}
+
+/// 新增 POI 落入关注区域时发出 geofence-hit 事件
+fn notify_geofence_hits(job: &KeywordJob, pois: &[POIData], inserted: &[(usize, i64)]) {
+    for &(index, poi_id) in inserted {
+        let Some(poi) = pois.get(index) else {
+            continue;
+        };
+        for area in job.watched.iter().filter(|a| a.contains(poi.lon, poi.lat)) {
+            emit_log(
+                job.app,
+                &format!("[{}] 关注区域「{}」新增: {}", job.platform, area.name, poi.name),
+            );
+            let hit = GeofenceHit {
+                area_id: area.id.clone(),
+                area_name: area.name.clone(),
+                poi_id,
+                name: poi.name.clone(),
+                lon: poi.lon,
+                lat: poi.lat,
+                category: job.cat.name.clone(),
+                address: poi.address.clone(),
+                platform: poi.platform.clone(),
+            };
+            let _ = job.app.emit("geofence-hit", &hit);
+        }
+    }
+}
+
+/// 逐页采集单个关键词，需要停止采集时返回停止原因
+fn collect_keyword(job: &KeywordJob, keyword: &str) -> Result<(), StopReason> {
+    if let Some((root, cap)) = job.grid {
+        return collect_keyword_grid(job, keyword, root, cap);
+    }
+
+    let platform = job.platform;
+    let cat = job.cat;
+    let mut page = 1;
+    loop {
+        if should_stop(platform) || category_skipped(platform) {
+            return Ok(());
+        }
+
+        // 优先复用未过期的分页缓存，避免重复消耗配额
+        let cache_key = ResponseCacheKey {
+            platform,
+            region_code: job.region_code,
+            keyword,
+            page,
+            category_id: &cat.id,
+        };
+        // 缓存按行政区划存放；限定了收藏范围、多边形或周边范围时请求范围不同，不使用分页缓存
+        let cacheable = job.area.is_none() && job.polygon.is_none() && job.around.is_none();
+        let cached = lock_db().ok().filter(|_| cacheable).and_then(|db| {
+            db.get_cached_response(&cache_key, RESPONSE_CACHE_TTL_SECS)
+                .ok()
+                .flatten()
+                .and_then(|(payload, has_more)| {
+                    serde_json::from_str::<Vec<POIData>>(&payload)
+                        .ok()
+                        .map(|pois| (pois, has_more))
+                })
+        });
+        let from_cache = cached.is_some();
+        // 本次请求使用的 Key，配额耗尽时据此判断是否已被其他线程换掉
+        let mut used_key = None;
+
+        let result = match cached {
+            Some(cached) => Ok(cached),
+            None => {
+                let (result, key) =
+                    send_request(job, |collector| match (job.polygon_ring, job.around_search) {
+                        (Some(ring), _) => {
+                            collector.search_poi_in_polygon(keyword, page, ring, &cat.name, &cat.id)
+                        }
+                        (None, Some(circle)) => {
+                            collector.search_poi_around(keyword, page, circle, &cat.name, &cat.id)
+                        }
+                        (None, None) => collector.search_poi(keyword, page, &cat.name, &cat.id),
+                    })?;
+                used_key = key;
+                if let (Ok((pois, has_more)), true) = (&result, cacheable) {
+                    if let (Ok(db), Ok(payload)) = (lock_db(), serde_json::to_string(pois)) {
+                        if let Err(e) = db.put_cached_response(&cache_key, &payload, *has_more) {
+                            log::warn!("写入响应缓存失败: {}", e);
+                        }
+                    }
+                }
+                result
+            }
+        };
+
+        match result {
+            Ok((pois, has_more)) => {
+                if pois.is_empty() {
+                    return Ok(());
+                }
+                save_pois(job, keyword, &format!("第{}页", page), pois, from_cache);
+                if !has_more {
+                    return Ok(());
+                }
+                page += 1;
+            }
+            Err(e) => {
+                if !handle_search_error(job, &e, used_key)? {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// 网格模式：按范围检索，结果总数达到上限的网格四等分后继续检索，直到低于上限或达到最大深度
+///
+/// 网格范围随细分变化，不使用分页缓存
+fn collect_keyword_grid(
+    job: &KeywordJob,
+    keyword: &str,
+    root: &Bounds,
+    cap: usize,
+) -> Result<(), StopReason> {
+    let platform = job.platform;
+    let cat = job.cat;
+    let mut cells = vec![grid::GridCell::root(root.clone())];
+    let mut seen = SeenPois::default();
+    while let Some(cell) = cells.pop() {
+        let mut page = 1;
+        loop {
+            if should_stop(platform) || category_skipped(platform) {
+                return Ok(());
+            }
+
+            let (result, used_key) = send_request(job, |collector| {
+                collector.search_poi_in_bounds(keyword, page, &cell.bounds, &cat.name, &cat.id)
+            })?;
+            let (mut pois, has_more, total) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    if handle_search_error(job, &e, used_key)? {
+                        continue;
+                    }
+                    return Ok(());
+                }
+            };
+
+            // 只看首页报告的总数，需要细分时不再翻页，首页结果照常入库
+            let split = page == 1 && cell.should_split(total, cap);
+            if split {
+                emit_log(
+                    job.app,
+                    &format!(
+                        "[{}] {} 网格 {} 共{}条，达到上限，细分检索",
+                        platform,
+                        keyword,
+                        cell.label(),
+                        total
+                    ),
+                );
+                cells.extend(cell.split());
+            } else if page == 1 && total >= cap {
+                emit_log(
+                    job.app,
+                    &format!(
+                        "[{}] {} 网格 {} 已达最大细分深度，部分结果可能无法取得",
+                        platform,
+                        keyword,
+                        cell.label()
+                    ),
+                );
+            }
+
+            seen.retain_new(&mut pois);
+            if !pois.is_empty() {
+                let location = format!("网格 {} 第{}页", cell.label(), page);
+                save_pois(job, keyword, &location, pois, false);
+            }
+            if split || !has_more {
+                break;
+            }
+            page += 1;
+        }
+    }
+    Ok(())
+}
+
+/// 发起一次检索请求：扣减当日预算、按 QPS 限流并应用手动切换的 Key，成功时重置连续错误
+///
+/// 返回检索结果与本次请求使用的 Key，配额耗尽时据此判断是否已被其他线程换掉
+fn send_request<T>(
+    job: &KeywordJob,
+    search: impl FnOnce(&dyn Collector) -> Result<T, String>,
+) -> Result<(Result<T, String>, Option<i64>), StopReason> {
+    let platform = job.platform;
+    match budget::consume(platform, BudgetKind::Requests) {
+        Consumption::Exhausted => return Err(StopReason::Budget),
+        Consumption::Warning { used, limit } => emit_log(
+            job.app,
+            &format!("[{}] 今日请求预算已用 {}/{}，即将达到上限", platform, used, limit),
+        ),
+        Consumption::Allowed => {}
+    }
+
+    // 限流：按 Key 的 QPS 设定请求间隔，运行中可调整
+    job.limiter.wait(request_delay(platform));
+
+    apply_key_switch(job);
+    let (result, used_key) = {
+        let collector = job.collector.read();
+        (search(collector.as_ref()), current_key_id(platform))
+    };
+    record_rate(job, RateMeter::record_request);
+    if result.is_ok() {
+        if let Ok(mut streak) = job.streak.lock() {
+            streak.reset();
+        }
+    }
+    Ok((result, used_key))
+}
+
+/// 处理检索错误：配额耗尽且换用其他 Key 后返回 true 以重试当前页，诊断为需要停止时返回停止原因
+fn handle_search_error(
+    job: &KeywordJob,
+    error: &str,
+    used_key: Option<i64>,
+) -> Result<bool, StopReason> {
+    emit_log(job.app, &format!("[{}] 采集错误: {}", job.platform, error));
+    if ApiErrorKind::classify(error) == ApiErrorKind::QuotaExhausted && rotate_key(job, used_key) {
+        return Ok(true);
+    }
+    let diagnosis = job.streak.lock().ok().and_then(|mut s| s.record(error));
+    match diagnosis {
+        Some(diagnosis) => Err(StopReason::Api(diagnosis)),
+        None => Ok(false),
+    }
+}
+
+/// 过滤一页检索结果并入库，location 为日志中的页码说明
+fn save_pois(
+    job: &KeywordJob,
+    keyword: &str,
+    location: &str,
+    mut pois: Vec<POIData>,
+    from_cache: bool,
+) {
+    let platform = job.platform;
+    let cat = job.cat;
+
+    // 收藏范围为多边形时剔除外接矩形内、多边形外的结果
+    if let Some(area) = job.area {
+        pois.retain(|p| area.contains(p.lon, p.lat));
+    }
+    if let Some(polygon) = job.polygon {
+        pois.retain(|p| polygon.contains(p.lon, p.lat));
+    }
+    if let Some(circle) = job.around {
+        pois.retain(|p| circle.contains(p.lon, p.lat));
+    }
+    // 剔除落在被排除区县的结果
+    if let Some(exclusion) = job.exclusion {
+        pois.retain(|p| {
+            let raw = serde_json::from_str(&p.raw_data).unwrap_or_default();
+            !exclusion.contains(&p.address, &raw)
+        });
+    }
+
+    // 保存到数据库（整页单事务提交）
+    let inserted = match lock_db() {
+        Ok(db) => db
+            .insert_poi_batch(&pois, &cat.name, &cat.id, job.region_code)
+            .unwrap_or_else(|e| {
+                log::warn!("批量保存 POI 失败: {}", e);
+                Vec::new()
+            }),
+        Err(_) => {
+            log::error!("无法获取数据库锁");
+            Vec::new()
+        }
+    };
+    let saved = inserted.len() as i64;
+    notify_geofence_hits(job, &pois, &inserted);
+    if let Some(Ok(mut ids)) = job.inserted_ids.map(|ids| ids.lock()) {
+        ids.extend(inserted.iter().map(|&(_, id)| id));
+    }
+
+    let total = job.total_collected.fetch_add(saved, Ordering::Relaxed) + saved;
+    record_rate(job, |meter| meter.record_pois(saved));
+
+    emit_log(
+        job.app,
+        &format!(
+            "[{}] {} {}: 获取{}条, 新增{}条{}",
+            platform,
+            keyword,
+            location,
+            pois.len(),
+            saved,
+            if from_cache { " (缓存)" } else { "" }
+        ),
+    );
+
+    update_status(platform, |s| {
+        s.total_collected = s.total_collected.max(total);
+    });
+}
+
+fn current_key_id(platform: &str) -> Option<i64> {
+    COLLECTOR_CONTROLS
+        .lock()
+        .ok()
+        .and_then(|controls| controls.get(platform).and_then(|c| c.key_id))
+}
+
+/// 替换采集器的 Key，调用方需持有采集器写锁；请求间隔按新 Key 的 QPS 重新设定
+fn switch_key(job: &KeywordJob, collector: &mut Box<dyn Collector>, key: ApiKey) {
+    let qps = key.qps.unwrap_or(DEFAULT_KEY_QPS);
+    collector.set_api_key(key.api_key);
+    if let Ok(mut controls) = COLLECTOR_CONTROLS.lock() {
+        if let Some(control) = controls.get_mut(job.platform) {
+            control.key_id = Some(key.id);
+            control.delay_ms = schedule_for_qps(qps).0;
+        }
+    }
+    if let Ok(mut streak) = job.streak.lock() {
+        streak.reset();
+    }
+    let name = if key.name.is_empty() {
+        format!("#{}", key.id)
+    } else {
+        key.name
+    };
+    emit_log(job.app, &format!("[{}] 已切换到 Key {}（QPS {}）", job.platform, name, qps));
+}
+
+/// 应用手动切换的 Key
+fn apply_key_switch(job: &KeywordJob) {
+    let key = COLLECTOR_CONTROLS
+        .lock()
+        .ok()
+        .and_then(|mut controls| controls.get_mut(job.platform)?.key_switch.take());
+    if let Some(key) = key {
+        switch_key(job, &mut job.collector.write(), key);
+    }
+}
+
+/// 当前 Key 配额耗尽：记为今日耗尽并重新查询 Key 表换用其他 Key，没有可用 Key 时返回 false
+///
+/// 多个工作线程同时遇到配额错误时只换一次，Key 已被换掉的线程直接重试
+fn rotate_key(job: &KeywordJob, failed: Option<i64>) -> bool {
+    let Some(failed) = failed else {
+        return false;
+    };
+    let mut collector = job.collector.write();
+    if current_key_id(job.platform) != Some(failed) {
+        return true;
+    }
+    if let Ok(mut exhausted) = EXHAUSTED_KEYS.lock() {
+        exhausted.insert(failed, today());
+    }
+    match pick_api_key(job.platform, None, None) {
+        Ok(key) => {
+            switch_key(job, &mut collector, key);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// 立即切换运行中采集器使用的 Key，不指定 key_id 时换用其他可用 Key 中 QPS 上限最高的
+///
+/// 新增的 Key 无需重启采集即可使用，工作线程在下次请求前生效
+#[tauri::command]
+pub fn switch_collector_key(platform: String, key_id: Option<i64>) -> CmdResult<ApiKey> {
+    if platform == "osm" {
+        return Err(AppError::invalid("OSM 不需要 API Key"));
+    }
+    let running = COLLECTOR_STATUSES
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(&platform)
+        .is_some_and(|s| s.status == "running");
+    if !running {
+        return Err(AppError::conflict("采集器未在运行"));
+    }
+
+    let current = current_key_id(&platform);
+    let key = pick_api_key(&platform, key_id, current)?;
+    let mut controls = COLLECTOR_CONTROLS.lock().map_err(|e| e.to_string())?;
+    let control = controls
+        .get_mut(&platform)
+        .ok_or_else(|| AppError::conflict("采集器未在运行"))?;
+    control.key_switch = Some(key.clone());
+    log::info!("{} 采集将切换到 Key {}", platform, key.id);
+    Ok(key)
+}
+
+/// 等待预算跨天恢复后继续采集，期间用户手动操作过则不再自动继续
+fn resume_after_budget_reset(app: AppHandle, platform: String) {
+    thread::spawn(move || loop {
+        thread::sleep(BUDGET_CHECK_INTERVAL);
+
+        let still_paused = COLLECTOR_STATUSES
+            .lock()
+            .map(|statuses| {
+                statuses.get(&platform).is_some_and(|s| {
+                    s.status == "paused" && s.error_message.as_deref() == Some(BUDGET_EXHAUSTED)
+                })
+            })
+            .unwrap_or(false);
+        if !still_paused {
+            return;
+        }
+        if budget::is_exhausted(&platform, BudgetKind::Requests) {
+            continue;
+        }
+
+        emit_log(&app, &format!("[{}] 预算已恢复，自动继续采集", platform));
+        if let Err(e) = resume_unfinished_collection(app.clone(), platform.clone()) {
+            emit_log(&app, &format!("[{}] 自动继续采集失败: {}", platform, e));
+        }
+        return;
+    });
+}
+
+/// 暂停采集：通知工作线程在当前请求后退出，采集会话保留已完成的类别
+///
+/// 暂停与停止相同，不保留线程；恢复由 resume_unfinished_collection 按会话中的进度重新启动
 #[tauri::command]
-pub fn stop_collector(platform: String) -> Result<(), String> {
+pub fn stop_collector(platform: String) -> CmdResult<()> {
     // 设置停止标志
     if let Ok(flags) = STOP_FLAGS.lock() {
         if let Some(flag) = flags.get(&platform) {
@@ -464,8 +1754,144 @@ pub fn stop_collector(platform: String) -> Result<(), String> {
     Ok(())
 }
 
+/// 调整采集请求间隔（毫秒），运行中立即生效
 #[tauri::command]
-pub fn reset_collector(platform: String) -> Result<(), String> {
+pub fn set_collector_delay(platform: String, delay_ms: u64) -> CmdResult<u64> {
+    let delay_ms = delay_ms.clamp(20, 60_000);
+    let mut controls = COLLECTOR_CONTROLS.lock().map_err(|e| e.to_string())?;
+    controls
+        .entry(platform.clone())
+        .or_insert_with(|| CollectorControl {
+            delay_ms,
+            concurrency: 1,
+            pending: vec![],
+            skip_current: false,
+            key_id: None,
+            key_switch: None,
+        })
+        .delay_ms = delay_ms;
+    log::info!("{} 采集请求间隔调整为 {}ms", platform, delay_ms);
+    Ok(delay_ms)
+}
+
+/// 追加或移除运行中采集器的待采类别，移除当前类别时跳过其剩余关键词，返回调整后的待采类别
+#[tauri::command]
+pub fn set_collector_categories(
+    platform: String,
+    add: Option<Vec<String>>,
+    remove: Option<Vec<String>>,
+) -> CmdResult<Vec<String>> {
+    let (current, completed) = {
+        let statuses = COLLECTOR_STATUSES.lock().map_err(|e| e.to_string())?;
+        match statuses.get(&platform) {
+            Some(s) if s.status == "running" => {
+                (s.current_category_id.clone(), s.completed_categories.clone())
+            }
+            _ => return Err(AppError::conflict("采集器未在运行")),
+        }
+    };
+
+    let (pending_ids, skipping) = {
+        let mut controls = COLLECTOR_CONTROLS.lock().map_err(|e| e.to_string())?;
+        let control = controls
+            .get_mut(&platform)
+            .ok_or_else(|| "采集器未在运行".to_string())?;
+
+        let remove = remove.unwrap_or_default();
+        control.pending.retain(|c| !remove.contains(&c.id));
+        if remove.contains(&current) {
+            control.skip_current = true;
+        }
+
+        for cat in get_poi_categories() {
+            let queued = control.pending.iter().any(|c| c.id == cat.id)
+                || (cat.id == current && !control.skip_current)
+                || completed.contains(&cat.id);
+            if add.as_ref().is_some_and(|ids| ids.contains(&cat.id)) && !queued {
+                control.pending.push(cat);
+            }
+        }
+
+        let ids: Vec<String> = control.pending.iter().map(|c| c.id.clone()).collect();
+        (ids, control.skip_current)
+    };
+
+    let in_progress = usize::from(!current.is_empty() && !skipping);
+    update_status(&platform, |s| {
+        s.total_categories = s.completed_categories.len() + in_progress + pending_ids.len();
+    });
+    Ok(pending_ids)
+}
+
+/// 全局控制事件
+#[derive(Debug, Clone, Serialize)]
+pub struct GlobalControlEvent {
+    pub action: String,
+    pub tile_tasks: Vec<String>,
+    pub collectors: Vec<String>,
+}
+
+/// 停止所有运行中的采集器，返回平台列表
+fn stop_running_collectors() -> Vec<String> {
+    let running: Vec<String> = COLLECTOR_STATUSES
+        .lock()
+        .map(|statuses| {
+            statuses
+                .values()
+                .filter(|s| s.status == "running")
+                .map(|s| s.platform.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for platform in &running {
+        let _ = stop_collector(platform.clone());
+    }
+    running
+}
+
+/// 暂停所有下载任务与采集器
+#[tauri::command]
+pub fn pause_all_tasks(app: AppHandle) -> CmdResult<GlobalControlEvent> {
+    let event = GlobalControlEvent {
+        action: "pause_all".to_string(),
+        tile_tasks: crate::tile_downloader::commands::pause_all_tile_tasks(&app)?,
+        collectors: stop_running_collectors(),
+    };
+
+    log::info!(
+        "全部暂停: {} 个下载任务, {} 个采集器",
+        event.tile_tasks.len(),
+        event.collectors.len()
+    );
+    let _ = app.emit("global-control", &event);
+    Ok(event)
+}
+
+/// 紧急停止所有下载任务与采集器
+#[tauri::command]
+pub fn stop_all(app: AppHandle) -> CmdResult<GlobalControlEvent> {
+    let event = GlobalControlEvent {
+        action: "stop_all".to_string(),
+        tile_tasks: crate::tile_downloader::commands::stop_all_tile_tasks(&app)?,
+        collectors: stop_running_collectors(),
+    };
+
+    log::info!(
+        "全部停止: {} 个下载任务, {} 个采集器",
+        event.tile_tasks.len(),
+        event.collectors.len()
+    );
+    let _ = app.emit("global-control", &event);
+    Ok(event)
+}
+
+#[tauri::command]
+pub fn reset_collector(platform: String) -> CmdResult<()> {
+    lock_db()?
+        .delete_collector_session(&platform)
+        .map_err(|e| format!("删除采集会话失败: {}", e))?;
+
     let mut statuses = COLLECTOR_STATUSES.lock().map_err(|e| e.to_string())?;
 
     statuses.insert(
@@ -477,29 +1903,295 @@ pub fn reset_collector(platform: String) -> Result<(), String> {
             completed_categories: vec![],
             current_category_id: String::new(),
             error_message: None,
+            total_categories: 0,
+            started_at: None,
+            error_kind: None,
+            suggested_pause_secs: None,
+            requests_per_minute: 0.0,
+            pois_per_minute: 0.0,
+            last_message: None,
         },
     );
 
     Ok(())
 }
 
+/// 获取上次未正常结束的采集会话（不含当前正在运行的）
+#[tauri::command]
+pub fn get_unfinished_collections() -> CmdResult<Vec<CollectorSession>> {
+    let running: Vec<String> = COLLECTOR_STATUSES
+        .lock()
+        .map_err(|e| e.to_string())?
+        .values()
+        .filter(|s| s.status == "running")
+        .map(|s| s.platform.clone())
+        .collect();
+
+    let db = lock_db()?;
+    let sessions = db
+        .get_collector_sessions()
+        .map_err(|e| format!("获取采集会话失败: {}", e))?;
+    Ok(sessions
+        .into_iter()
+        .filter(|s| !running.contains(&s.platform))
+        .collect())
+}
+
+/// 继续未完成的采集，跳过已完成的类别
+#[tauri::command]
+pub fn resume_unfinished_collection(app: AppHandle, platform: String) -> CmdResult<()> {
+    let session = {
+        let db = lock_db()?;
+        db.get_collector_sessions()
+            .map_err(|e| format!("获取采集会话失败: {}", e))?
+            .into_iter()
+            .find(|s| s.platform == platform)
+            .ok_or_else(|| AppError::not_found(format!("{}没有未完成的采集", platform)))?
+    };
+    let launch: CollectorLaunch =
+        serde_json::from_str(&session.launch).map_err(|e| format!("解析启动参数失败: {}", e))?;
+
+    let categories: Vec<String> = launch
+        .categories
+        .unwrap_or_else(|| get_poi_categories().into_iter().map(|c| c.id).collect())
+        .into_iter()
+        .filter(|id| !session.completed_categories.contains(id))
+        .collect();
+    if categories.is_empty() {
+        lock_db()?
+            .delete_collector_session(&platform)
+            .map_err(|e| format!("删除采集会话失败: {}", e))?;
+        return Err(AppError::conflict("所有类别均已采集完成"));
+    }
+
+    start_collector(
+        app,
+        platform,
+        Some(categories),
+        launch.regions,
+        launch.keywords,
+        launch.area_id,
+        launch.exclude_codes,
+        Some(launch.enrich),
+        Some(launch.grid),
+        launch.polygon,
+        launch.around,
+    )
+}
+
+/// 放弃未完成的采集
+#[tauri::command]
+pub fn discard_unfinished_collection(platform: String) -> CmdResult<bool> {
+    let db = lock_db()?;
+    db.delete_collector_session(&platform)
+        .map_err(|e| AppError::from(format!("删除采集会话失败: {}", e)))
+}
+
+/// 是否有运行中的采集器
+pub(crate) fn has_running_collectors() -> bool {
+    COLLECTOR_STATUSES
+        .lock()
+        .map(|statuses| statuses.values().any(|s| s.status == "running"))
+        .unwrap_or(false)
+}
+
+/// 将 POI 数据库快照写入指定路径
+pub(crate) fn snapshot_poi_db(dest: &std::path::Path) -> Result<(), String> {
+    let db = lock_db()?;
+    db.vacuum_into(dest)
+        .map_err(|e| format!("导出 POI 数据库失败: {}", e))
+}
+
+/// 以当前密码打开 POI 数据库；库已加密而尚未解锁时以内存库占位，并标记为锁定
+fn open_poi_db() -> Result<Database, String> {
+    match Database::open(POI_DB_PATH, db_crypto::current_key().as_deref()) {
+        Ok(db) => {
+            db_crypto::set_locked(false);
+            Ok(db)
+        }
+        Err(e) if db_crypto::is_not_a_database(&e) => {
+            log::warn!("POI 数据库已加密，等待输入密码解锁");
+            db_crypto::set_locked(true);
+            Database::new(":memory:").map_err(|e| e.to_string())
+        }
+        Err(e) => Err(format!("打开数据库失败: {}", e)),
+    }
+}
+
+/// 获取 POI 数据库锁，数据库未解锁时返回错误
+fn lock_db() -> CmdResult<MutexGuard<'static, Database>> {
+    let db = DB.lock().map_err(|e| e.to_string())?;
+    if db_crypto::is_locked() {
+        return Err(db_crypto::locked_error());
+    }
+    Ok(db)
+}
+
+/// 以当前密码重新打开 POI 数据库，返回是否已解锁
+pub(crate) fn reopen_poi_db() -> Result<bool, String> {
+    let mut db = DB.lock().map_err(|e| e.to_string())?;
+    *db = open_poi_db()?;
+    Ok(!db_crypto::is_locked())
+}
+
+/// 以新密码重写 POI 数据库（None 为明文），完成后以新密码重新打开
+pub(crate) fn rekey_poi_db(new_key: Option<String>) -> Result<(), String> {
+    let mut db = lock_db()?;
+    let temp = std::path::Path::new(POI_DB_PATH).with_extension("rekey");
+    let _ = std::fs::remove_file(&temp);
+    db.export_with_key(&temp, new_key.as_deref().unwrap_or(""))
+        .map_err(|e| format!("导出数据库失败: {}", e))?;
+
+    *db = Database::new(":memory:").map_err(|e| e.to_string())?;
+    let result = crate::workspace::replace_db_file(&temp, std::path::Path::new(POI_DB_PATH));
+    let _ = std::fs::remove_file(&temp);
+    if result.is_ok() {
+        db_crypto::set_key(new_key);
+    }
+    *db = open_poi_db()?;
+    result
+}
+
+/// 持有 POI 数据库锁执行只读查询
+pub(crate) fn with_poi_db<T>(f: impl FnOnce(&Database) -> Result<T, String>) -> CmdResult<T> {
+    let db = lock_db()?;
+    f(&db).map_err(AppError::from)
+}
+
+/// POI 数据库文件路径
+pub(crate) fn poi_db_path() -> &'static std::path::Path {
+    std::path::Path::new(POI_DB_PATH)
+}
+
+/// 在释放 POI 数据库文件句柄期间执行 f（用于替换数据库文件），完成后重新打开
+pub(crate) fn with_poi_db_closed<F>(f: F) -> Result<(), String>
+where
+    F: FnOnce(&std::path::Path) -> Result<(), String>,
+{
+    let mut db = DB.lock().map_err(|e| e.to_string())?;
+    *db = Database::new(":memory:").map_err(|e| e.to_string())?;
+    let result = f(std::path::Path::new(POI_DB_PATH));
+    *db = open_poi_db().map_err(|e| format!("重新打开数据库失败: {}", e))?;
+    result
+}
+
 #[tauri::command]
 pub fn search_poi(
     query: String,
     platform: Option<String>,
     mode: String,
     limit: Option<i64>,
-) -> Result<Vec<POI>, String> {
-    let db = DB.lock().map_err(|e| e.to_string())?;
+) -> CmdResult<Vec<POI>> {
+    let db = lock_db()?;
     let platform_filter = platform
         .as_ref()
         .filter(|p| p.as_str() != "all")
         .map(|s| s.as_str());
     db.search_poi(&query, platform_filter, &mode, limit.unwrap_or(100))
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
+}
+
+/// 获取搜索别名表
+#[tauri::command]
+pub fn list_poi_aliases() -> CmdResult<Vec<PoiAlias>> {
+    let db = lock_db()?;
+    db.list_poi_aliases().map_err(AppError::from)
+}
+
+/// 新增或修改搜索别名，如「一小」对应「第一小学」，搜索时双向命中
+#[tauri::command]
+pub fn set_poi_alias(alias: String, target: String) -> CmdResult<i64> {
+    let alias = alias.trim();
+    let target = target.trim();
+    if alias.is_empty() || target.is_empty() {
+        return Err(AppError::invalid("别名与全称均不能为空"));
+    }
+    if alias == target {
+        return Err(AppError::invalid("别名不能与全称相同"));
+    }
+    let db = lock_db()?;
+    db.upsert_poi_alias(alias, target).map_err(AppError::from)
+}
+
+/// 删除搜索别名
+#[tauri::command]
+pub fn delete_poi_alias(id: i64) -> CmdResult<bool> {
+    let db = lock_db()?;
+    db.delete_poi_alias(id).map_err(AppError::from)
+}
+
+/// 查询范围内的 POI（走空间索引），缺省最多 1000 条
+#[tauri::command]
+pub fn query_poi_bbox(
+    bounds: crate::config::Bounds,
+    limit: Option<i64>,
+) -> CmdResult<Vec<POI>> {
+    let db = lock_db()?;
+    db.query_poi_in_bounds(&bounds, limit.unwrap_or(1000).clamp(1, 100_000))
+        .map_err(|e| AppError::from(format!("范围查询失败: {}", e)))
+}
+
+/// 获取落在收藏范围内的全部 POI ID，配合 export_poi_to_file 导出红线内清单
+#[tauri::command]
+pub fn get_poi_ids_in_area(area_id: String) -> CmdResult<Vec<i64>> {
+    let area = find_saved_area(&area_id)?;
+    let db = lock_db()?;
+    Ok(db
+        .query_poi_in_bounds(&area.bounds, i64::MAX)
+        .map_err(|e| format!("范围查询失败: {}", e))?
+        .into_iter()
+        .filter(|poi| area.contains(poi.lon, poi.lat))
+        .map(|poi| poi.id)
+        .collect())
+}
+
+/// 查询指定点附近的 POI，按距离升序
+#[tauri::command]
+pub fn query_poi_nearby(
+    lon: f64,
+    lat: f64,
+    radius_m: f64,
+    limit: Option<usize>,
+) -> CmdResult<Vec<NearbyPOI>> {
+    if radius_m <= 0.0 {
+        return Err(AppError::invalid("查询半径必须大于 0"));
+    }
+    let db = lock_db()?;
+    db.query_poi_nearby(lon, lat, radius_m, limit.unwrap_or(100))
+        .map_err(|e| AppError::from(format!("附近查询失败: {}", e)))
+}
+
+/// 按经纬度网格聚合范围内的 POI 数量
+#[tauri::command]
+pub fn aggregate_poi_grid(
+    bounds: crate::config::Bounds,
+    cell_size: f64,
+) -> CmdResult<Vec<GridCell>> {
+    if cell_size <= 0.0 {
+        return Err(AppError::invalid("网格大小必须大于 0"));
+    }
+    let db = lock_db()?;
+    db.aggregate_poi_grid(&bounds, cell_size)
+        .map_err(|e| AppError::from(format!("网格聚合失败: {}", e)))
+}
+
+/// 重建空间索引
+#[tauri::command]
+pub fn rebuild_spatial_index() -> CmdResult<usize> {
+    let db = lock_db()?;
+    db.rebuild_spatial_index()
+        .map_err(|e| AppError::from(format!("重建空间索引失败: {}", e)))
 }
 
 // 行政区划相关命令
+use crate::coverage;
+use crate::dxf;
+use crate::geojsonl;
+use crate::masking::Masker;
+use crate::sql_export::{Column, SqlDialect, SqlType, SqlValue};
+use crate::parquet_export;
+use crate::poi_tags;
+use crate::provenance;
 use crate::regions;
 
 #[tauri::command]
@@ -528,30 +2220,319 @@ pub fn get_district_codes_for_region(code: String) -> Vec<String> {
 }
 
 // 导出相关命令
-use crate::database::ExportPOI;
+use crate::database::{ExportPOI, PoiTagLink};
 
 #[tauri::command]
-pub fn get_all_poi_data(platform: Option<String>) -> Result<Vec<ExportPOI>, String> {
-    let db = DB.lock().map_err(|e| e.to_string())?;
+pub fn get_all_poi_data(platform: Option<String>) -> CmdResult<Vec<ExportPOI>> {
+    let db = lock_db()?;
     let platform_filter = platform
         .as_ref()
         .filter(|p| p.as_str() != "all")
         .map(|s| s.as_str());
-    db.get_all_poi(platform_filter).map_err(|e| e.to_string())
+    db.get_all_poi(platform_filter).map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn get_export_settings() -> CmdResult<ExportSettings> {
+    crate::config::get_export_settings().map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn set_export_settings(settings: ExportSettings) -> CmdResult<()> {
+    if settings.filename_template.trim().is_empty() {
+        return Err(AppError::invalid("命名模板不能为空"));
+    }
+    crate::config::set_export_settings(&settings).map_err(AppError::from)
+}
+
+/// 按导出设置生成默认导出路径
+#[tauri::command]
+pub fn get_default_export_path(
+    format: String,
+    platform: Option<String>,
+    category: Option<String>,
+) -> CmdResult<String> {
+    let settings = crate::config::get_export_settings()?;
+    let dir = settings
+        .export_dir
+        .filter(|d| !d.trim().is_empty())
+        .ok_or_else(|| AppError::invalid("未配置默认导出目录"))?;
+
+    let ext = match format.as_str() {
+        "json" => "json",
+        "excel" => "csv",
+        "mysql" | "sqlite" | "dameng" | "kingbase" => "sql",
+        "dxf" => "dxf",
+        "parquet" => "parquet",
+        "geojsonl" => "geojsonl",
+        _ => return Err(AppError::invalid("不支持的导出格式")),
+    };
+
+    let now = chrono::Local::now();
+    let region = get_current_region().map(|r| r.name).unwrap_or_default();
+    let mut vars = HashMap::new();
+    vars.insert("region", region);
+    vars.insert("category", category.unwrap_or_else(|| "全部".to_string()));
+    vars.insert("platform", platform.unwrap_or_else(|| "all".to_string()));
+    vars.insert("format", format.clone());
+    vars.insert("date", now.format("%Y-%m-%d").to_string());
+    vars.insert("time", now.format("%H%M%S").to_string());
+
+    let filename = format!("{}.{}", render_filename(&settings.filename_template, &vars), ext);
+    let path = std::path::Path::new(&dir).join(filename);
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// 带区划名称的导出行
+#[derive(Debug, Serialize)]
+struct ExportRow<'a> {
+    #[serde(flatten)]
+    poi: &'a ExportPOI,
+    #[serde(flatten)]
+    region: regions::RegionNames,
+    tags: Vec<PoiTagLink>,
+    /// 导出时选定的 OSM 标签
+    #[serde(skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    osm_tags: std::collections::BTreeMap<String, String>,
+}
+
+/// OSM 标签对应的导出列名，如 contact:phone -> osm_contact_phone
+fn osm_column_name(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    format!("osm_{}", name)
+}
+
+/// 按选定的 OSM 标签展开为列：列名与各行取值，非 OSM 或缺少该标签的行为空
+fn osm_tag_columns(
+    data: &[ExportPOI],
+    tag_map: &HashMap<i64, HashMap<String, String>>,
+    keys: &[String],
+) -> Vec<(String, Vec<String>)> {
+    keys.iter()
+        .map(|key| {
+            let values = data
+                .iter()
+                .map(|poi| {
+                    tag_map
+                        .get(&poi.id)
+                        .and_then(|tags| tags.get(key))
+                        .cloned()
+                        .unwrap_or_default()
+                })
+                .collect();
+            (osm_column_name(key), values)
+        })
+        .collect()
+}
+
+/// 按区划层级聚合后的导出行
+#[derive(Debug, Serialize)]
+struct AggregatedRow {
+    region_code: String,
+    #[serde(flatten)]
+    region: regions::RegionNames,
+    category: String,
+    count: usize,
+}
+
+/// 将 region_code 截取到指定层级（省 2 位、市 4 位、区县 6 位）
+fn region_code_at_level(code: &str, level: &str) -> String {
+    let len = match level {
+        "province" => 2,
+        "city" => 4,
+        _ => 6,
+    };
+    code.chars().take(len).collect()
+}
+
+/// 按区划层级与类别聚合计数
+fn aggregate_by_region(data: &[ExportPOI], level: &str) -> Vec<AggregatedRow> {
+    let mut counts: std::collections::BTreeMap<(String, String), usize> =
+        std::collections::BTreeMap::new();
+    for poi in data {
+        let code = region_code_at_level(&poi.region_code, level);
+        *counts.entry((code, poi.category.clone())).or_default() += 1;
+    }
+
+    counts
+        .into_iter()
+        .map(|((region_code, category), count)| AggregatedRow {
+            region: regions::resolve_region_names(&region_code),
+            region_code,
+            category,
+            count,
+        })
+        .collect()
+}
+
+fn write_aggregated(path: &str, format: &str, rows: &[AggregatedRow]) -> Result<(), String> {
+    if let Some(dialect) = SqlDialect::from_format(format) {
+        let columns = [
+            Column::new("region_code", SqlType::Varchar(12)).not_null(),
+            Column::new("province", SqlType::Varchar(100)),
+            Column::new("city", SqlType::Varchar(100)),
+            Column::new("district", SqlType::Varchar(100)),
+            Column::new("category", SqlType::Varchar(100)),
+            Column::new("count", SqlType::Int).not_null(),
+        ];
+        let mut sql = String::new();
+        sql.push_str("-- POI 区划聚合统计导出\n");
+        sql.push_str("-- 生成时间: ");
+        sql.push_str(&chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
+        sql.push_str(&format!("\n-- 目标数据库: {}\n-- 编码: UTF-8\n\n", dialect.label()));
+        sql.push_str(dialect.begin());
+        sql.push_str(&dialect.create_table("poi_region_stats", &columns));
+        for row in rows {
+            sql.push_str(&dialect.insert(
+                "poi_region_stats",
+                &columns,
+                &[
+                    SqlValue::Text(&row.region_code),
+                    SqlValue::Text(&row.region.province),
+                    SqlValue::Text(&row.region.city),
+                    SqlValue::Text(&row.region.district),
+                    SqlValue::Text(&row.category),
+                    SqlValue::Int(row.count as i64),
+                ],
+            ));
+        }
+        sql.push_str(dialect.end());
+        return write_sql(path, dialect, &sql);
+    }
+
+    let mut bytes: Vec<u8> = vec![0xEF, 0xBB, 0xBF]; // UTF-8 BOM
+    match format {
+        "json" => {
+            let json = serde_json::to_string_pretty(rows).map_err(|e| e.to_string())?;
+            bytes.extend_from_slice(json.as_bytes());
+        }
+        "excel" => {
+            bytes.extend_from_slice("区划代码,省,市,区县,类别,数量\n".as_bytes());
+            for row in rows {
+                let line = format!(
+                    "{},\"{}\",\"{}\",\"{}\",\"{}\",{}\n",
+                    row.region_code,
+                    row.region.province,
+                    row.region.city,
+                    row.region.district,
+                    row.category.replace("\"", "\"\""),
+                    row.count
+                );
+                bytes.extend_from_slice(line.as_bytes());
+            }
+        }
+        _ => return Err("不支持的导出格式".to_string()),
+    }
+    std::fs::write(path, bytes).map_err(|e| e.to_string())
+}
+
+/// 写出 SQL 脚本，按方言决定是否添加 UTF-8 BOM
+fn write_sql(path: &str, dialect: SqlDialect, sql: &str) -> Result<(), String> {
+    let mut bytes: Vec<u8> = Vec::with_capacity(sql.len() + 3);
+    if dialect.with_bom() {
+        bytes.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+    }
+    bytes.extend_from_slice(sql.as_bytes());
+    std::fs::write(path, bytes).map_err(|e| e.to_string())
 }
 
+/// 导出 POI 数据
+///
+/// aggregate_level 为 province/city/district 时按该层级与类别聚合计数导出，否则逐条导出并附带省/市/区县名称列。
+/// DXF 格式可通过 projection 指定 wgs84/web_mercator/gauss_kruger 坐标；
+/// freshness 为 fresh/stale 时只导出按新鲜度设置判定为新鲜或过期的数据；
+/// category 为类别 ID 时只导出该类别的数据，同时用于默认文件名；
+/// tags 非空时只导出带有任一标签的数据。逐条导出的表格类格式附带标签列；
+/// with_metadata 为 true 时附带来源声明（各平台占比、采集时间、坐标系、许可提醒）
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn export_poi_to_file(
-    path: String,
+    path: Option<String>,
     format: String,
     platform: Option<String>,
     ids: Option<Vec<i64>>,
-) -> Result<usize, String> {
-    let db = DB.lock().map_err(|e| e.to_string())?;
+    category: Option<String>,
+    aggregate_level: Option<String>,
+    projection: Option<String>,
+    freshness: Option<Freshness>,
+    tags: Option<Vec<String>>,
+    osm_tags: Option<Vec<String>>,
+    with_metadata: Option<bool>,
+) -> CmdResult<usize> {
+    let with_metadata = with_metadata.unwrap_or(false);
+    let category = category.filter(|c| !c.trim().is_empty() && c != "all");
+    // 未指定路径时按导出设置生成
+    let path = match path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => path,
+        None => {
+            let path =
+                get_default_export_path(format.clone(), platform.clone(), category.clone())?;
+            if let Some(parent) = std::path::Path::new(&path).parent() {
+                std::fs::create_dir_all(parent).map_err(|e| format!("创建导出目录失败: {}", e))?;
+            }
+            path
+        }
+    };
+
+    let masker = Masker::new(&crate::config::get_export_settings()?.masking);
+    let masking = masker.as_ref().map(|m| m.policy());
+    let stale_days = crate::config::get_freshness_settings()?.stale_days;
+
+    let db = lock_db()?;
     let platform_filter = platform
         .as_ref()
         .filter(|p| p.as_str() != "all")
         .map(|s| s.as_str());
+
+    // 按新鲜度、类别或标签过滤时与指定的 IDs 取交集
+    let tags = tags.filter(|t| !t.is_empty());
+    let ids: Option<Vec<i64>> = if freshness.is_some() || tags.is_some() || category.is_some() {
+        let matched = db
+            .poi_ids_filtered(&PoiFilter {
+                category_ids: category.map(|c| vec![c]),
+                freshness,
+                stale_days: Some(stale_days),
+                tags,
+                ..Default::default()
+            })
+            .map_err(|e| e.to_string())?;
+        Some(match ids {
+            Some(list) => list.into_iter().filter(|id| matched.contains(id)).collect(),
+            None => matched.into_iter().collect(),
+        })
+    } else {
+        ids
+    };
+
+    // GeoJSON Lines 边查边写，不整体加载数据
+    if format == "geojsonl" && aggregate_level.is_none() {
+        let id_set: Option<std::collections::HashSet<i64>> =
+            ids.map(|list| list.into_iter().collect());
+        let count = db
+            .with_poi_rows(platform_filter, |rows| {
+                let mut rows = rows.map(|row| {
+                    row.map(|mut poi| {
+                        if let Some(masker) = &masker {
+                            masker.apply(&mut poi);
+                        }
+                        poi
+                    })
+                });
+                geojsonl::write_geojsonl(&path, &mut rows, id_set.as_ref())
+            })
+            .map_err(|e| format!("查询 POI 失败: {}", e))??;
+        write_masking_note(&path, masking.as_deref())?;
+        if with_metadata {
+            let statement =
+                export_statement(&db, platform_filter, id_set.as_ref(), WGS84_LABEL, masking.as_deref())?;
+            provenance::write_readme(&path, &statement)?;
+        }
+        return Ok(count);
+    }
+
     let mut data = db.get_all_poi(platform_filter).map_err(|e| e.to_string())?;
 
     // 如果指定了 IDs，只导出这些 IDs 的数据
@@ -560,12 +2541,122 @@ pub fn export_poi_to_file(
         data.retain(|poi| id_set.contains(&poi.id));
     }
 
+    if let Some(masker) = &masker {
+        data.iter_mut().for_each(|poi| masker.apply(poi));
+    }
+
     let count = data.len();
+    let statement_for = |coord_system: &str| -> Result<Option<provenance::SourceStatement>, String> {
+        if !with_metadata {
+            return Ok(None);
+        }
+        let id_set: std::collections::HashSet<i64> = data.iter().map(|poi| poi.id).collect();
+        export_statement(&db, platform_filter, Some(&id_set), coord_system, masking.as_deref()).map(Some)
+    };
+
+    if let Some(level) = aggregate_level.as_deref() {
+        if !matches!(level, "province" | "city" | "district") {
+            return Err(AppError::invalid(format!("不支持的聚合层级: {}", level)));
+        }
+        let rows = aggregate_by_region(&data, level);
+        write_aggregated(&path, &format, &rows)?;
+        if let Some(statement) = statement_for(WGS84_LABEL)? {
+            provenance::write_readme(&path, &statement)?;
+        }
+        return Ok(count);
+    }
+
+    if format == "dxf" {
+        let center_lon = if data.is_empty() {
+            0.0
+        } else {
+            data.iter().map(|poi| poi.lon).sum::<f64>() / data.len() as f64
+        };
+        let projection =
+            dxf::Projection::parse(projection.as_deref(), center_lon).map_err(AppError::invalid)?;
+        let points: Vec<dxf::DxfPoint> = data
+            .iter()
+            .map(|poi| dxf::DxfPoint {
+                name: &poi.name,
+                layer: &poi.category,
+                lon: poi.lon,
+                lat: poi.lat,
+            })
+            .collect();
+        std::fs::write(&path, dxf::write_dxf(&points, projection)).map_err(|e| e.to_string())?;
+        write_masking_note(&path, masking.as_deref())?;
+        if let Some(statement) = statement_for(&projection.label())? {
+            provenance::write_readme(&path, &statement)?;
+        }
+        return Ok(count);
+    }
+
+    // 按 region_code 解析区划名称，相同代码只解析一次
+    let mut name_cache: HashMap<String, regions::RegionNames> = HashMap::new();
+    let region_names: Vec<regions::RegionNames> = data
+        .iter()
+        .map(|poi| {
+            name_cache
+                .entry(poi.region_code.clone())
+                .or_insert_with(|| regions::resolve_region_names(&poi.region_code))
+                .clone()
+        })
+        .collect();
+    let mut tag_map = db.get_poi_tag_map(None).map_err(|e| e.to_string())?;
+    let tag_names: Vec<String> = data
+        .iter()
+        .map(|poi| poi_tags::join_tag_names(tag_map.get(&poi.id)))
+        .collect();
+    // OSM 标签扩展列
+    let osm_tags = osm_tags.unwrap_or_default();
+    let osm_columns = if osm_tags.is_empty() {
+        Vec::new()
+    } else {
+        let osm_tag_map = db.get_osm_tag_map().map_err(|e| e.to_string())?;
+        osm_tag_columns(&data, &osm_tag_map, &osm_tags)
+    };
+    let statement = statement_for(WGS84_LABEL)?;
 
     match format.as_str() {
+        "parquet" => parquet_export::write_parquet(
+            &path,
+            &data,
+            &region_names,
+            &tag_names,
+            &osm_columns,
+            masking.as_deref(),
+        )?,
         "json" => {
-            // JSON 导出，添加 UTF-8 BOM
-            let json = serde_json::to_string_pretty(&data).map_err(|e| e.to_string())?;
+            // JSON 导出，添加 UTF-8 BOM；脱敏时包一层对象注明策略
+            let rows: Vec<ExportRow> = data
+                .iter()
+                .zip(region_names)
+                .enumerate()
+                .map(|(row, (poi, region))| ExportRow {
+                    poi,
+                    region,
+                    tags: tag_map.remove(&poi.id).unwrap_or_default(),
+                    osm_tags: osm_columns
+                        .iter()
+                        .zip(&osm_tags)
+                        .filter(|((_, values), _)| !values[row].is_empty())
+                        .map(|((_, values), key)| (key.clone(), values[row].clone()))
+                        .collect(),
+                })
+                .collect();
+            // 附带来源声明时写入 metadata 段，其中已包含脱敏策略
+            let json = match (&statement, &masking) {
+                (Some(statement), _) => serde_json::to_string_pretty(&serde_json::json!({
+                    "metadata": statement,
+                    "data": rows,
+                })),
+                (None, Some(policy)) => serde_json::to_string_pretty(&serde_json::json!({
+                    "masking": policy,
+                    "data": rows,
+                })),
+                (None, None) => serde_json::to_string_pretty(&rows),
+            }
+            .map_err(|e| e.to_string())?;
             let mut json_bytes: Vec<u8> = vec![0xEF, 0xBB, 0xBF]; // UTF-8 BOM
             json_bytes.extend_from_slice(json.as_bytes());
             std::fs::write(&path, json_bytes).map_err(|e| e.to_string())?;
@@ -573,10 +2664,19 @@ pub fn export_poi_to_file(
         "excel" => {
             // CSV 导出，添加 UTF-8 BOM 以便 Excel 正确识别中文
             let mut csv_bytes: Vec<u8> = vec![0xEF, 0xBB, 0xBF]; // UTF-8 BOM
-            csv_bytes.extend_from_slice("ID,名称,经度,纬度,地址,电话,类别,平台\n".as_bytes());
-            for poi in &data {
-                let line = format!(
-                    "{},\"{}\",{},{},\"{}\",\"{}\",\"{}\",{}\n",
+            if let Some(policy) = &masking {
+                csv_bytes.extend_from_slice(format!("# 脱敏策略: {}\n", policy).as_bytes());
+            }
+            let mut header = "ID,名称,经度,纬度,地址,电话,类别,平台,省,市,区县,标签".to_string();
+            for (name, _) in &osm_columns {
+                header.push(',');
+                header.push_str(name);
+            }
+            header.push('\n');
+            csv_bytes.extend_from_slice(header.as_bytes());
+            for (row, ((poi, region), tags)) in data.iter().zip(&region_names).zip(&tag_names).enumerate() {
+                let mut line = format!(
+                    "{},\"{}\",{},{},\"{}\",\"{}\",\"{}\",{},\"{}\",\"{}\",\"{}\",\"{}\"",
                     poi.id,
                     poi.name.replace("\"", "\"\""),
                     poi.lon,
@@ -584,79 +2684,296 @@ pub fn export_poi_to_file(
                     poi.address.replace("\"", "\"\""),
                     poi.phone.replace("\"", "\"\""),
                     poi.category.replace("\"", "\"\""),
-                    poi.platform
+                    poi.platform,
+                    region.province,
+                    region.city,
+                    region.district,
+                    tags
                 );
+                for (_, values) in &osm_columns {
+                    line.push_str(&format!(",\"{}\"", values[row].replace("\"", "\"\"")));
+                }
+                line.push('\n');
                 csv_bytes.extend_from_slice(line.as_bytes());
             }
             std::fs::write(&path, csv_bytes).map_err(|e| e.to_string())?;
         }
-        "mysql" => {
-            // MySQL SQL 导出，添加 UTF-8 BOM
-            let mut sql_bytes: Vec<u8> = vec![0xEF, 0xBB, 0xBF]; // UTF-8 BOM
+        format => {
+            // SQL 导出，类型映射与转义按目标库方言处理
+            let dialect = SqlDialect::from_format(format)
+                .ok_or_else(|| AppError::invalid("不支持的导出格式"))?;
+            let mut columns = vec![
+                Column::new("id", SqlType::BigInt).primary_key(),
+                Column::new("name", SqlType::Varchar(255)).not_null(),
+                Column::new("lon", SqlType::Double).not_null(),
+                Column::new("lat", SqlType::Double).not_null(),
+                Column::new("address", SqlType::Varchar(500)),
+                Column::new("phone", SqlType::Varchar(100)),
+                Column::new("category", SqlType::Varchar(100)),
+                Column::new("platform", SqlType::Varchar(50)),
+                Column::new("province", SqlType::Varchar(100)),
+                Column::new("city", SqlType::Varchar(100)),
+                Column::new("district", SqlType::Varchar(100)),
+                Column::new("tags", SqlType::Varchar(500)),
+            ];
+            columns.extend(
+                osm_columns
+                    .iter()
+                    .map(|(name, _)| Column::named(name.clone(), SqlType::Varchar(500))),
+            );
             let mut sql = String::new();
             sql.push_str("-- POI 数据导出\n");
             sql.push_str("-- 生成时间: ");
             sql.push_str(&chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
-            sql.push_str("\n-- 编码: UTF-8\n\n");
-            sql.push_str("SET NAMES utf8mb4;\n\n");
-            sql.push_str("CREATE TABLE IF NOT EXISTS poi_data (\n");
-            sql.push_str("  id BIGINT PRIMARY KEY,\n");
-            sql.push_str("  name VARCHAR(255) NOT NULL,\n");
-            sql.push_str("  lon DOUBLE NOT NULL,\n");
-            sql.push_str("  lat DOUBLE NOT NULL,\n");
-            sql.push_str("  address VARCHAR(500),\n");
-            sql.push_str("  phone VARCHAR(100),\n");
-            sql.push_str("  category VARCHAR(100),\n");
-            sql.push_str("  platform VARCHAR(50)\n");
-            sql.push_str(") ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;\n\n");
-
-            for poi in &data {
-                sql.push_str(&format!(
-                    "INSERT INTO poi_data (id, name, lon, lat, address, phone, category, platform) VALUES ({}, '{}', {}, {}, '{}', '{}', '{}', '{}');\n",
-                    poi.id,
-                    poi.name.replace("'", "''"),
-                    poi.lon,
-                    poi.lat,
-                    poi.address.replace("'", "''"),
-                    poi.phone.replace("'", "''"),
-                    poi.category.replace("'", "''"),
-                    poi.platform
-                ));
+            sql.push_str(&format!("\n-- 目标数据库: {}\n-- 编码: UTF-8\n", dialect.label()));
+            if let Some(policy) = &masking {
+                sql.push_str(&format!("-- 脱敏策略: {}\n", policy));
+            }
+            sql.push('\n');
+            sql.push_str(dialect.begin());
+            sql.push_str(&dialect.create_table("poi_data", &columns));
+
+            for (row, ((poi, region), tags)) in data.iter().zip(&region_names).zip(&tag_names).enumerate() {
+                let mut values = vec![
+                        SqlValue::Int(poi.id),
+                        SqlValue::Text(&poi.name),
+                        SqlValue::Float(poi.lon),
+                        SqlValue::Float(poi.lat),
+                        SqlValue::Text(&poi.address),
+                        SqlValue::Text(&poi.phone),
+                        SqlValue::Text(&poi.category),
+                        SqlValue::Text(&poi.platform),
+                        SqlValue::Text(&region.province),
+                        SqlValue::Text(&region.city),
+                        SqlValue::Text(&region.district),
+                        SqlValue::Text(tags),
+                ];
+                values.extend(osm_columns.iter().map(|(_, v)| SqlValue::Text(&v[row])));
+                sql.push_str(&dialect.insert("poi_data", &columns, &values));
             }
-            sql_bytes.extend_from_slice(sql.as_bytes());
-            std::fs::write(&path, sql_bytes).map_err(|e| e.to_string())?;
+            sql.push_str(dialect.end());
+            write_sql(&path, dialect, &sql)?;
         }
-        _ => return Err("不支持的导出格式".to_string()),
+    }
+    if let Some(statement) = &statement {
+        provenance::write_readme(&path, statement)?;
     }
 
     Ok(count)
 }
 
+/// 导出数据未投影时的坐标系说明
+const WGS84_LABEL: &str = "WGS84 经纬度 (EPSG:4326)";
+
+/// 导出数据的来源声明，id_set 为空表示平台下的全部数据
+fn export_statement(
+    db: &Database,
+    platform: Option<&str>,
+    id_set: Option<&std::collections::HashSet<i64>>,
+    coord_system: &str,
+    masking: Option<&str>,
+) -> Result<provenance::SourceStatement, String> {
+    let records: Vec<(String, String)> = db
+        .get_poi_sources(platform)
+        .map_err(|e| format!("读取采集时间失败: {}", e))?
+        .into_iter()
+        .filter(|(id, _, _)| id_set.is_none_or(|set| set.contains(id)))
+        .map(|(_, platform, created_at)| (platform, created_at))
+        .collect();
+    Ok(provenance::build_statement(&records, coord_system, masking))
+}
+
+/// 无法在文件内注明脱敏策略的格式，在同目录写出说明文件
+fn write_masking_note(path: &str, masking: Option<&str>) -> Result<(), String> {
+    let Some(policy) = masking else {
+        return Ok(());
+    };
+    std::fs::write(format!("{}.masking.txt", path), format!("脱敏策略: {}\n", policy))
+        .map_err(|e| format!("写入脱敏说明失败: {}", e))
+}
+
 /// 修复缺失的 region_code 数据
 #[tauri::command]
-pub fn fix_region_codes() -> Result<(i64, i64), String> {
-    let db = DB.lock().map_err(|e| e.to_string())?;
-    db.fix_region_codes().map_err(|e| e.to_string())
+pub fn fix_region_codes() -> CmdResult<(i64, i64)> {
+    let db = lock_db()?;
+    db.fix_region_codes().map_err(AppError::from)
 }
 
 /// 获取按 region_code 分组的 POI 统计
 #[tauri::command]
-pub fn get_poi_stats_by_region() -> Result<Vec<(String, i64)>, String> {
-    let db = DB.lock().map_err(|e| e.to_string())?;
-    db.get_poi_stats_by_region().map_err(|e| e.to_string())
+pub fn get_poi_stats_by_region() -> CmdResult<Vec<(String, i64)>> {
+    let db = lock_db()?;
+    db.get_poi_stats_by_region().map_err(AppError::from)
+}
+
+/// 以 OSM 为基准按区县对比各平台采集量，标出可能漏采的区域
+#[tauri::command]
+pub async fn coverage_compare(
+    region_codes: Vec<String>,
+    category_ids: Option<Vec<String>>,
+    threshold: Option<f64>,
+) -> CmdResult<coverage::CoverageReport> {
+    let mut district_codes: Vec<String> = region_codes
+        .iter()
+        .flat_map(|code| regions::get_all_district_codes(code))
+        .collect();
+    district_codes.sort();
+    district_codes.dedup();
+    if district_codes.is_empty() {
+        return Err(AppError::invalid("未找到可对比的区县"));
+    }
+
+    let local_counts = {
+        let db = lock_db()?;
+        db.count_poi_by_region_platform_category(&district_codes)
+            .map_err(|e| e.to_string())?
+    };
+    let threshold = threshold.unwrap_or(coverage::DEFAULT_COVERAGE_THRESHOLD);
+
+    tokio::task::spawn_blocking(move || {
+        coverage::build_report(
+            &district_codes,
+            category_ids.as_deref(),
+            &local_counts,
+            threshold,
+        )
+    })
+    .await
+    .map_err(|e| AppError::from(format!("生成覆盖度报告失败: {}", e)))
 }
 
 /// 根据 region_code 列表删除 POI
 #[tauri::command]
-pub fn delete_poi_by_regions(codes: Vec<String>) -> Result<usize, String> {
-    let db = DB.lock().map_err(|e| e.to_string())?;
+pub fn delete_poi_by_regions(codes: Vec<String>) -> CmdResult<usize> {
+    let db = lock_db()?;
     db.delete_poi_by_region_codes(&codes)
-        .map_err(|e| e.to_string())
+        .map_err(AppError::from)
+}
+
+/// 组合条件删除结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FilteredDeleteResult {
+    /// 满足条件的条数（预估或实际删除数）
+    pub count: usize,
+    /// 是否已执行删除
+    pub deleted: bool,
+}
+
+/// 按平台、类别、地区、时间范围组合条件删除 POI
+///
+/// confirm 为 false 时仅返回预估条数，确认后再次调用并传 true 执行删除。
+/// 地区为省/市时包含其下属区县。
+#[tauri::command]
+pub fn delete_poi_filtered(
+    filter: PoiFilter,
+    confirm: Option<bool>,
+) -> CmdResult<FilteredDeleteResult> {
+    let mut filter = filter;
+    filter.resolve_stale_days();
+    if let Some(codes) = filter.region_codes.take() {
+        filter.region_codes = Some(regions::expand_region_codes(&codes));
+    }
+
+    let has_condition = [
+        &filter.platforms,
+        &filter.category_ids,
+        &filter.region_codes,
+        &filter.tags,
+    ]
+        .iter()
+        .any(|v| v.as_ref().is_some_and(|v| !v.is_empty()))
+        || filter.start_time.as_ref().is_some_and(|s| !s.is_empty())
+        || filter.end_time.as_ref().is_some_and(|s| !s.is_empty())
+        || filter.freshness.is_some();
+    if !has_condition {
+        return Err(AppError::invalid(
+            "请至少指定一个删除条件，清空全部数据请使用清空功能",
+        ));
+    }
+
+    let db = lock_db()?;
+    if confirm.unwrap_or(false) {
+        let count = db.delete_poi_filtered(&filter).map_err(|e| e.to_string())?;
+        log::info!("按条件删除 POI: {} 条", count);
+        Ok(FilteredDeleteResult {
+            count,
+            deleted: true,
+        })
+    } else {
+        let count = db.count_poi_filtered(&filter).map_err(|e| e.to_string())?;
+        Ok(FilteredDeleteResult {
+            count,
+            deleted: false,
+        })
+    }
+}
+
+/// 类别重建结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RebuildCategoriesResult {
+    /// 检查的记录数
+    pub scanned: usize,
+    /// 回填类别的记录数
+    pub updated: usize,
+    /// 无法识别类别的记录数
+    pub unmatched: usize,
+}
+
+/// 根据 raw_data 中的平台类型码/标签重建类别
+///
+/// overwrite 为 false 时只处理类别为空的记录，为 true 时重新计算全部记录
+#[tauri::command]
+pub fn rebuild_categories(overwrite: Option<bool>) -> CmdResult<RebuildCategoriesResult> {
+    let db = lock_db()?;
+    let rows = db
+        .get_poi_raw_for_categories(overwrite.unwrap_or(false))
+        .map_err(|e| format!("读取 POI 失败: {}", e))?;
+
+    let names: HashMap<String, String> = default_categories()
+        .into_iter()
+        .map(|c| (c.id, c.name))
+        .collect();
+
+    let mut updates = Vec::new();
+    for (id, platform, raw_data) in &rows {
+        let Ok(raw) = serde_json::from_str::<serde_json::Value>(raw_data) else {
+            continue;
+        };
+        let Some(category_id) = category_map::infer_category_id(platform, &raw) else {
+            continue;
+        };
+        if let Some(name) = names.get(category_id) {
+            updates.push((*id, name.clone(), category_id.to_string()));
+        }
+    }
+
+    let updated = db
+        .update_poi_categories(&updates)
+        .map_err(|e| format!("回填类别失败: {}", e))?;
+    log::info!("重建类别: 检查 {} 条，回填 {} 条", rows.len(), updated);
+
+    Ok(RebuildCategoriesResult {
+        scanned: rows.len(),
+        updated,
+        unmatched: rows.len() - updates.len(),
+    })
+}
+
+/// 清理采集响应缓存，expired_only 为 true 时仅清理过期条目
+#[tauri::command]
+pub fn clear_response_cache(expired_only: Option<bool>) -> CmdResult<usize> {
+    let db = lock_db()?;
+    let ttl = if expired_only.unwrap_or(false) {
+        RESPONSE_CACHE_TTL_SECS
+    } else {
+        0
+    };
+    db.purge_response_cache(ttl).map_err(AppError::from)
 }
 
 /// 清空所有 POI 数据
 #[tauri::command]
-pub fn clear_all_poi() -> Result<usize, String> {
-    let db = DB.lock().map_err(|e| e.to_string())?;
-    db.clear_all_poi().map_err(|e| e.to_string())
+pub fn clear_all_poi() -> CmdResult<usize> {
+    let db = lock_db()?;
+    db.clear_all_poi().map_err(AppError::from)
 }
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use once_cell::sync::Lazy;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,8 +99,87 @@ pub static PRESET_REGIONS: Lazy<HashMap<String, RegionConfig>> = Lazy::new(|| {
     m
 });
 
+const REGION_CONFIG_FILE: &str = "region_config.json";
+const EXPORT_SETTINGS_FILE: &str = "export_settings.json";
+const REGION_PROFILES_FILE: &str = "region_profiles.json";
+const FAVORITE_REGIONS_FILE: &str = "favorite_regions.json";
+const SAVED_AREAS_FILE: &str = "saved_areas.json";
+const DAILY_BUDGETS_FILE: &str = "daily_budgets.json";
+const BUDGET_USAGE_FILE: &str = "budget_usage.json";
+const TILE_OUTPUT_SETTINGS_FILE: &str = "tile_output_settings.json";
+const TILE_NETWORK_SETTINGS_FILE: &str = "tile_network_settings.json";
+const COLLECT_TEMPLATES_FILE: &str = "collect_templates.json";
+const EVENT_SETTINGS_FILE: &str = "event_settings.json";
+const FRESHNESS_SETTINGS_FILE: &str = "freshness_settings.json";
+const RAW_DATA_SETTINGS_FILE: &str = "raw_data_settings.json";
+const TILE_URL_RULE_SETTINGS_FILE: &str = "tile_url_rule_settings.json";
+const TILE_URL_RULES_FILE: &str = "tile_url_rules.json";
+const POWER_SETTINGS_FILE: &str = "power_settings.json";
+const OSM_SETTINGS_FILE: &str = "osm_settings.json";
+const RESTART_POLICY_FILE: &str = "restart_policy.json";
+
+/// 配置文件目录（应用数据目录），未初始化时使用工作目录
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// 设置配置文件目录，并将工作目录下的旧配置文件迁移过去
+pub fn init_config_dir(dir: PathBuf) {
+    if let Err(e) = fs::create_dir_all(&dir) {
+        log::warn!("创建配置目录失败: {}", e);
+        return;
+    }
+
+    for name in [REGION_CONFIG_FILE, EXPORT_SETTINGS_FILE] {
+        let legacy = PathBuf::from(name);
+        let target = dir.join(name);
+        if legacy.exists() && !target.exists() {
+            match fs::copy(&legacy, &target) {
+                Ok(_) => {
+                    let _ = fs::remove_file(&legacy);
+                    log::info!("迁移配置文件 {} 到 {}", name, target.display());
+                }
+                Err(e) => log::warn!("迁移配置文件 {} 失败: {}", name, e),
+            }
+        }
+    }
+
+    let _ = CONFIG_DIR.set(dir);
+}
+
+fn config_file(name: &str) -> PathBuf {
+    match CONFIG_DIR.get() {
+        Some(dir) => dir.join(name),
+        None => PathBuf::from(name),
+    }
+}
+
+/// 工作区打包时需要迁移的配置文件 (文件名, 路径)
+pub fn workspace_config_files() -> Vec<(&'static str, PathBuf)> {
+    [
+        REGION_CONFIG_FILE,
+        EXPORT_SETTINGS_FILE,
+        REGION_PROFILES_FILE,
+        FAVORITE_REGIONS_FILE,
+        SAVED_AREAS_FILE,
+        DAILY_BUDGETS_FILE,
+        TILE_OUTPUT_SETTINGS_FILE,
+        TILE_NETWORK_SETTINGS_FILE,
+        COLLECT_TEMPLATES_FILE,
+        EVENT_SETTINGS_FILE,
+        FRESHNESS_SETTINGS_FILE,
+        RAW_DATA_SETTINGS_FILE,
+        TILE_URL_RULE_SETTINGS_FILE,
+        TILE_URL_RULES_FILE,
+        POWER_SETTINGS_FILE,
+        RESTART_POLICY_FILE,
+        OSM_SETTINGS_FILE,
+    ]
+    .into_iter()
+    .map(|name| (name, config_file(name)))
+    .collect()
+}
+
 fn config_path() -> PathBuf {
-    PathBuf::from("region_config.json")
+    config_file(REGION_CONFIG_FILE)
 }
 
 pub fn get_current_region() -> Result<RegionConfig, String> {
@@ -119,3 +199,860 @@ pub fn set_region(config: RegionConfig) -> Result<(), String> {
     let content = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
     fs::write(&path, content).map_err(|e| e.to_string())
 }
+
+/// 默认导出文件命名模板
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "{region}_{category}_{date}";
+
+/// 导出设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportSettings {
+    /// 默认导出目录
+    #[serde(default)]
+    pub export_dir: Option<String>,
+    /// 文件命名模板，支持 {region} {category} {platform} {format} {date} {time}
+    #[serde(default = "default_filename_template")]
+    pub filename_template: String,
+    /// 脱敏导出选项
+    #[serde(default)]
+    pub masking: crate::masking::MaskOptions,
+}
+
+fn default_filename_template() -> String {
+    DEFAULT_FILENAME_TEMPLATE.to_string()
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            export_dir: None,
+            filename_template: default_filename_template(),
+            masking: Default::default(),
+        }
+    }
+}
+
+fn export_settings_path() -> PathBuf {
+    config_file(EXPORT_SETTINGS_FILE)
+}
+
+pub fn get_export_settings() -> Result<ExportSettings, String> {
+    let path = export_settings_path();
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(ExportSettings::default())
+    }
+}
+
+pub fn set_export_settings(settings: &ExportSettings) -> Result<(), String> {
+    let path = export_settings_path();
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 数据新鲜度设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreshnessSettings {
+    /// 超过该天数未复核的 POI 标记为过期
+    #[serde(default = "default_stale_days")]
+    pub stale_days: u32,
+}
+
+fn default_stale_days() -> u32 {
+    365
+}
+
+impl Default for FreshnessSettings {
+    fn default() -> Self {
+        Self {
+            stale_days: default_stale_days(),
+        }
+    }
+}
+
+pub fn get_freshness_settings() -> Result<FreshnessSettings, String> {
+    let path = config_file(FRESHNESS_SETTINGS_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(FreshnessSettings::default())
+    }
+}
+
+pub fn set_freshness_settings(settings: &FreshnessSettings) -> Result<(), String> {
+    let path = config_file(FRESHNESS_SETTINGS_FILE);
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 默认瓦片输出路径模板
+pub const DEFAULT_TILE_PATH_TEMPLATE: &str = "{platform}/{region}/{maptype}_{date}";
+
+/// 瓦片输出设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileOutputSettings {
+    /// 瓦片输出根目录
+    #[serde(default)]
+    pub output_root: Option<String>,
+    /// 相对根目录的路径模板，以 / 分隔子目录，支持 {platform} {region} {maptype} {name} {date} {time}
+    #[serde(default = "default_tile_path_template")]
+    pub path_template: String,
+}
+
+fn default_tile_path_template() -> String {
+    DEFAULT_TILE_PATH_TEMPLATE.to_string()
+}
+
+impl Default for TileOutputSettings {
+    fn default() -> Self {
+        Self {
+            output_root: None,
+            path_template: default_tile_path_template(),
+        }
+    }
+}
+
+pub fn get_tile_output_settings() -> Result<TileOutputSettings, String> {
+    let path = config_file(TILE_OUTPUT_SETTINGS_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(TileOutputSettings::default())
+    }
+}
+
+pub fn set_tile_output_settings(settings: &TileOutputSettings) -> Result<(), String> {
+    let path = config_file(TILE_OUTPUT_SETTINGS_FILE);
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 瓦片下载的 HTTP 连接设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileNetworkSettings {
+    /// 服务端支持时通过 ALPN 协商 HTTP/2，并启用自适应流控窗口
+    #[serde(default = "default_true")]
+    pub http2: bool,
+    /// 每个主机保留的空闲连接数，缺省与任务的单主机并发数一致
+    #[serde(default)]
+    pub pool_max_idle_per_host: Option<u32>,
+    /// 空闲连接保留时长（秒）
+    #[serde(default = "default_pool_idle_timeout")]
+    pub pool_idle_timeout_secs: u64,
+    /// TCP keepalive 间隔（秒），为空表示不启用
+    #[serde(default = "default_tcp_keepalive")]
+    pub tcp_keepalive_secs: Option<u64>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_pool_idle_timeout() -> u64 {
+    90
+}
+
+fn default_tcp_keepalive() -> Option<u64> {
+    Some(60)
+}
+
+impl Default for TileNetworkSettings {
+    fn default() -> Self {
+        Self {
+            http2: true,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout_secs: default_pool_idle_timeout(),
+            tcp_keepalive_secs: default_tcp_keepalive(),
+        }
+    }
+}
+
+pub fn get_tile_network_settings() -> Result<TileNetworkSettings, String> {
+    let path = config_file(TILE_NETWORK_SETTINGS_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(TileNetworkSettings::default())
+    }
+}
+
+pub fn set_tile_network_settings(settings: &TileNetworkSettings) -> Result<(), String> {
+    let path = config_file(TILE_NETWORK_SETTINGS_FILE);
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 前端事件订阅级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventLevel {
+    /// 逐条实时推送
+    Realtime,
+    /// 进度按间隔合并，日志按间隔批量推送
+    #[default]
+    Throttled,
+    /// 进度仅在状态变化时推送，日志批量推送且每批只保留最近若干条
+    Quiet,
+}
+
+/// 采集日志与下载进度事件的推送设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSettings {
+    #[serde(default)]
+    pub level: EventLevel,
+    /// 合并推送的间隔（毫秒）
+    #[serde(default = "default_event_interval")]
+    pub interval_ms: u64,
+}
+
+fn default_event_interval() -> u64 {
+    500
+}
+
+impl Default for EventSettings {
+    fn default() -> Self {
+        Self {
+            level: EventLevel::default(),
+            interval_ms: default_event_interval(),
+        }
+    }
+}
+
+pub fn get_event_settings() -> Result<EventSettings, String> {
+    let path = config_file(EVENT_SETTINGS_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(EventSettings::default())
+    }
+}
+
+pub fn set_event_settings(settings: &EventSettings) -> Result<(), String> {
+    let path = config_file(EVENT_SETTINGS_FILE);
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 原始响应（raw_data）的存储方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RawDataMode {
+    /// 原样保存文本
+    #[default]
+    Keep,
+    /// gzip 压缩后以 BLOB 保存
+    Compress,
+    /// 不保存
+    Discard,
+}
+
+/// 原始响应存储设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RawDataSettings {
+    #[serde(default)]
+    pub mode: RawDataMode,
+    /// 超过该字节数的原始响应截断保存，为空时不截断
+    #[serde(default)]
+    pub max_bytes: Option<usize>,
+}
+
+pub fn get_raw_data_settings() -> Result<RawDataSettings, String> {
+    let path = config_file(RAW_DATA_SETTINGS_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(RawDataSettings::default())
+    }
+}
+
+pub fn set_raw_data_settings(settings: &RawDataSettings) -> Result<(), String> {
+    let path = config_file(RAW_DATA_SETTINGS_FILE);
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// OSM 采集设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OsmSettings {
+    /// 采集时保留到 raw_data 的 OSM 标签，导出时可展开为列
+    #[serde(default = "default_osm_extra_tags")]
+    pub extra_tags: Vec<String>,
+}
+
+fn default_osm_extra_tags() -> Vec<String> {
+    ["opening_hours", "website", "wheelchair", "operator", "brand"]
+        .iter()
+        .map(|t| t.to_string())
+        .collect()
+}
+
+impl Default for OsmSettings {
+    fn default() -> Self {
+        Self {
+            extra_tags: default_osm_extra_tags(),
+        }
+    }
+}
+
+pub fn get_osm_settings() -> Result<OsmSettings, String> {
+    let path = config_file(OSM_SETTINGS_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(OsmSettings::default())
+    }
+}
+
+pub fn set_osm_settings(settings: &OsmSettings) -> Result<(), String> {
+    let path = config_file(OSM_SETTINGS_FILE);
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 瓦片 URL 规则更新设置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TileUrlRuleSettings {
+    /// 启动时自动检查更新
+    #[serde(default)]
+    pub auto_update: bool,
+    /// 规则文件地址，尚无官方发布的规则，缺省为空
+    #[serde(default)]
+    pub source_url: String,
+    /// 规则签名公钥（Ed25519，Base64），由规则发布者提供，为空时拒绝远程规则
+    #[serde(default)]
+    pub public_key: String,
+}
+
+pub fn get_tile_url_rule_settings() -> Result<TileUrlRuleSettings, String> {
+    let path = config_file(TILE_URL_RULE_SETTINGS_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(TileUrlRuleSettings::default())
+    }
+}
+
+pub fn set_tile_url_rule_settings(settings: &TileUrlRuleSettings) -> Result<(), String> {
+    let path = config_file(TILE_URL_RULE_SETTINGS_FILE);
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 电源设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerSettings {
+    /// 下载或采集运行期间阻止系统休眠
+    #[serde(default = "default_true")]
+    pub prevent_sleep: bool,
+}
+
+impl Default for PowerSettings {
+    fn default() -> Self {
+        Self {
+            prevent_sleep: true,
+        }
+    }
+}
+
+pub fn get_power_settings() -> Result<PowerSettings, String> {
+    let path = config_file(POWER_SETTINGS_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(PowerSettings::default())
+    }
+}
+
+pub fn set_power_settings(settings: &PowerSettings) -> Result<(), String> {
+    let path = config_file(POWER_SETTINGS_FILE);
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 采集失败自动重启策略
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartPolicy {
+    #[serde(default)]
+    pub enabled: bool,
+    /// 同一采集任务最多自动重启的次数，采集完成后清零
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+    /// 首次重启前等待的秒数，之后每次翻倍
+    #[serde(default = "default_restart_delay")]
+    pub base_delay_secs: u64,
+}
+
+fn default_max_restarts() -> u32 {
+    3
+}
+
+fn default_restart_delay() -> u64 {
+    300
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_restarts: default_max_restarts(),
+            base_delay_secs: default_restart_delay(),
+        }
+    }
+}
+
+pub fn get_restart_policy() -> Result<RestartPolicy, String> {
+    let path = config_file(RESTART_POLICY_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(RestartPolicy::default())
+    }
+}
+
+pub fn set_restart_policy(policy: &RestartPolicy) -> Result<(), String> {
+    let path = config_file(RESTART_POLICY_FILE);
+    let content = serde_json::to_string_pretty(policy).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 已下载的瓦片 URL 规则文件（保留签名，加载时重新校验）
+pub fn tile_url_rules_path() -> PathBuf {
+    config_file(TILE_URL_RULES_FILE)
+}
+
+/// 按路径模板逐级渲染目录与文件名，各级中的非法字符替换为下划线
+pub fn render_path(root: &Path, template: &str, vars: &HashMap<&str, String>) -> PathBuf {
+    template
+        .split(['/', '\\'])
+        .filter(|segment| !segment.trim().is_empty())
+        .fold(root.to_path_buf(), |path, segment| {
+            path.join(render_filename(segment, vars))
+        })
+}
+
+/// 用户收藏的预置区域，bounds 取自边界服务并缓存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavoriteRegion {
+    /// 行政区划代码（与 regions.json 一致）
+    pub code: String,
+    pub bounds: Bounds,
+}
+
+/// 将区划代码补全为 6 位行政区划代码
+fn pad_admin_code(code: &str) -> String {
+    format!("{:0<6}", code)
+}
+
+impl FavoriteRegion {
+    /// 由内置行政区划数据生成区域配置
+    pub fn to_region_config(&self) -> Option<RegionConfig> {
+        let region = crate::regions::get_region_by_code(&self.code)?;
+        let city_code = match region.level.as_str() {
+            "district" => region.parent_code.clone().unwrap_or_else(|| self.code.clone()),
+            _ => self.code.clone(),
+        };
+        let b = &self.bounds;
+        Some(RegionConfig {
+            name: region.name,
+            admin_code: pad_admin_code(&self.code),
+            city_code: pad_admin_code(&city_code),
+            bounds: b.clone(),
+            center: Some(((b.min_lon + b.max_lon) / 2.0, (b.min_lat + b.max_lat) / 2.0)),
+        })
+    }
+}
+
+pub fn list_favorite_regions() -> Result<Vec<FavoriteRegion>, String> {
+    let path = config_file(FAVORITE_REGIONS_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+fn write_favorite_regions(favorites: &[FavoriteRegion]) -> Result<(), String> {
+    let path = config_file(FAVORITE_REGIONS_FILE);
+    let content = serde_json::to_string_pretty(favorites).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 收藏区域为预置，已存在时更新 bounds
+pub fn add_favorite_region(favorite: FavoriteRegion) -> Result<(), String> {
+    let mut favorites = list_favorite_regions()?;
+    match favorites.iter_mut().find(|f| f.code == favorite.code) {
+        Some(existing) => *existing = favorite,
+        None => favorites.push(favorite),
+    }
+    write_favorite_regions(&favorites)
+}
+
+/// 取消收藏，返回是否存在
+pub fn remove_favorite_region(code: &str) -> Result<bool, String> {
+    let mut favorites = list_favorite_regions()?;
+    let before = favorites.len();
+    favorites.retain(|f| f.code != code);
+    if favorites.len() == before {
+        return Ok(false);
+    }
+    write_favorite_regions(&favorites)?;
+    Ok(true)
+}
+
+/// 按 ID 查找预置区域：内置预置使用拼音 ID，收藏区域使用区划代码
+pub fn find_preset(id: &str) -> Option<RegionConfig> {
+    if let Some(region) = PRESET_REGIONS.get(id) {
+        return Some(region.clone());
+    }
+    list_favorite_regions()
+        .ok()?
+        .into_iter()
+        .find(|f| f.code == id)
+        .and_then(|f| f.to_region_config())
+}
+
+/// 命名的地区配置（常用地区组合）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegionProfile {
+    pub name: String,
+    /// 选中的行政区划代码
+    pub region_codes: Vec<String>,
+    /// 保存时的当前区域配置
+    #[serde(default)]
+    pub region: Option<RegionConfig>,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+pub fn list_region_profiles() -> Result<Vec<RegionProfile>, String> {
+    let path = config_file(REGION_PROFILES_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+fn write_region_profiles(profiles: &[RegionProfile]) -> Result<(), String> {
+    let path = config_file(REGION_PROFILES_FILE);
+    let content = serde_json::to_string_pretty(profiles).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 保存地区配置，同名配置会被覆盖
+pub fn save_region_profile(profile: RegionProfile) -> Result<(), String> {
+    let mut profiles = list_region_profiles()?;
+    match profiles.iter_mut().find(|p| p.name == profile.name) {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+    write_region_profiles(&profiles)
+}
+
+pub fn get_region_profile(name: &str) -> Result<Option<RegionProfile>, String> {
+    Ok(list_region_profiles()?.into_iter().find(|p| p.name == name))
+}
+
+/// 删除地区配置，返回是否存在
+pub fn delete_region_profile(name: &str) -> Result<bool, String> {
+    let mut profiles = list_region_profiles()?;
+    let before = profiles.len();
+    profiles.retain(|p| p.name != name);
+    if profiles.len() == before {
+        return Ok(false);
+    }
+    write_region_profiles(&profiles)?;
+    Ok(true)
+}
+
+/// 收藏的范围（名称 + 矩形或多边形）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedArea {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// 外接矩形，设置了多边形时按多边形重新计算
+    pub bounds: Bounds,
+    /// 多边形顶点 (经度, 纬度)，为空时范围即 bounds 矩形
+    #[serde(default)]
+    pub polygon: Vec<(f64, f64)>,
+    /// 关注区域：采集到落入范围的新 POI 时发出提醒
+    #[serde(default)]
+    pub watch: bool,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+impl SavedArea {
+    /// 判断坐标是否在范围内
+    pub fn contains(&self, lon: f64, lat: f64) -> bool {
+        let in_bounds = lon >= self.bounds.min_lon
+            && lon <= self.bounds.max_lon
+            && lat >= self.bounds.min_lat
+            && lat <= self.bounds.max_lat;
+        if self.polygon.len() < 3 {
+            return in_bounds;
+        }
+        in_bounds && crate::tile_downloader::coverage::point_in_polygon(lon, lat, &self.polygon)
+    }
+}
+
+pub fn list_saved_areas() -> Result<Vec<SavedArea>, String> {
+    let path = config_file(SAVED_AREAS_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+fn write_saved_areas(areas: &[SavedArea]) -> Result<(), String> {
+    let path = config_file(SAVED_AREAS_FILE);
+    let content = serde_json::to_string_pretty(areas).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 保存范围收藏，id 相同时覆盖
+pub fn save_saved_area(area: SavedArea) -> Result<(), String> {
+    let mut areas = list_saved_areas()?;
+    match areas.iter_mut().find(|a| a.id == area.id) {
+        Some(existing) => *existing = area,
+        None => areas.push(area),
+    }
+    write_saved_areas(&areas)
+}
+
+pub fn get_saved_area(id: &str) -> Result<Option<SavedArea>, String> {
+    Ok(list_saved_areas()?.into_iter().find(|a| a.id == id))
+}
+
+/// 获取设为关注的范围
+pub fn list_watched_areas() -> Result<Vec<SavedArea>, String> {
+    Ok(list_saved_areas()?.into_iter().filter(|a| a.watch).collect())
+}
+
+/// 删除范围收藏，返回是否存在
+pub fn delete_saved_area(id: &str) -> Result<bool, String> {
+    let mut areas = list_saved_areas()?;
+    let before = areas.len();
+    areas.retain(|a| a.id != id);
+    if areas.len() == before {
+        return Ok(false);
+    }
+    write_saved_areas(&areas)?;
+    Ok(true)
+}
+
+/// 采集任务模板：平台、地区与类别的组合，可按周期自动运行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectTemplate {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// 依次启动的平台
+    pub platforms: Vec<String>,
+    /// 行政区划代码，为空时使用当前区域
+    #[serde(default)]
+    pub regions: Option<Vec<String>>,
+    /// 类别 ID，为空时采集全部类别
+    #[serde(default)]
+    pub categories: Option<Vec<String>>,
+    #[serde(default)]
+    pub keywords: Option<Vec<String>>,
+    /// 引用收藏的范围
+    #[serde(default)]
+    pub area_id: Option<String>,
+    /// 排除的下属区域
+    #[serde(default)]
+    pub exclude_codes: Option<Vec<String>>,
+    /// 在收藏范围内按网格自适应细分检索
+    #[serde(default)]
+    pub grid: bool,
+    /// 自动运行间隔（天），为空表示仅手动运行
+    #[serde(default)]
+    pub interval_days: Option<u32>,
+    #[serde(default)]
+    pub last_run_at: Option<String>,
+    #[serde(default)]
+    pub updated_at: String,
+}
+
+pub fn list_collect_templates() -> Result<Vec<CollectTemplate>, String> {
+    let path = config_file(COLLECT_TEMPLATES_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+fn write_collect_templates(templates: &[CollectTemplate]) -> Result<(), String> {
+    let path = config_file(COLLECT_TEMPLATES_FILE);
+    let content = serde_json::to_string_pretty(templates).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())
+}
+
+/// 保存采集模板，id 相同时覆盖
+pub fn save_collect_template(template: CollectTemplate) -> Result<(), String> {
+    let mut templates = list_collect_templates()?;
+    match templates.iter_mut().find(|t| t.id == template.id) {
+        Some(existing) => *existing = template,
+        None => templates.push(template),
+    }
+    write_collect_templates(&templates)
+}
+
+pub fn get_collect_template(id: &str) -> Result<Option<CollectTemplate>, String> {
+    Ok(list_collect_templates()?.into_iter().find(|t| t.id == id))
+}
+
+/// 删除采集模板，返回是否存在
+pub fn delete_collect_template(id: &str) -> Result<bool, String> {
+    let mut templates = list_collect_templates()?;
+    let before = templates.len();
+    templates.retain(|t| t.id != id);
+    if templates.len() == before {
+        return Ok(false);
+    }
+    write_collect_templates(&templates)?;
+    Ok(true)
+}
+
+/// 平台每日配额预算，上限为空表示不限制
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyBudget {
+    pub platform: String,
+    /// 每日 POI 接口请求数上限
+    #[serde(default)]
+    pub max_requests: Option<u64>,
+    /// 每日瓦片请求数上限
+    #[serde(default)]
+    pub max_tiles: Option<u64>,
+    /// 用量达到上限的该百分比时发出警告
+    #[serde(default = "default_warn_percent")]
+    pub warn_percent: u8,
+}
+
+fn default_warn_percent() -> u8 {
+    80
+}
+
+pub fn list_daily_budgets() -> Result<Vec<DailyBudget>, String> {
+    let path = config_file(DAILY_BUDGETS_FILE);
+
+    if path.exists() {
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// 保存平台预算，同平台覆盖，两项上限均为空时删除
+pub fn save_daily_budget(budget: DailyBudget) -> Result<Vec<DailyBudget>, String> {
+    let mut budgets = list_daily_budgets()?;
+    budgets.retain(|b| b.platform != budget.platform);
+    if budget.max_requests.is_some() || budget.max_tiles.is_some() {
+        budgets.push(budget);
+    }
+
+    let path = config_file(DAILY_BUDGETS_FILE);
+    let content = serde_json::to_string_pretty(&budgets).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())?;
+    Ok(budgets)
+}
+
+/// 当日预算用量的持久化文件
+pub fn budget_usage_path() -> PathBuf {
+    config_file(BUDGET_USAGE_FILE)
+}
+
+/// 按模板生成文件名（不含扩展名），变量缺失时保留原文，非法字符替换为下划线
+pub fn render_filename(template: &str, vars: &HashMap<&str, String>) -> String {
+    let mut name = template.to_string();
+    for (key, value) in vars {
+        name = name.replace(&format!("{{{}}}", key), value);
+    }
+
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '\\' | '/' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    let name = name.trim().trim_matches('.');
+    if name.is_empty() {
+        "poi_export".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_filename() {
+        let mut vars = HashMap::new();
+        vars.insert("region", "阜宁县".to_string());
+        vars.insert("category", "餐饮/美食".to_string());
+        vars.insert("date", "2024-05-01".to_string());
+
+        assert_eq!(
+            render_filename(DEFAULT_FILENAME_TEMPLATE, &vars),
+            "阜宁县_餐饮_美食_2024-05-01"
+        );
+        assert_eq!(render_filename("{unknown}", &vars), "{unknown}");
+        assert_eq!(render_filename("..", &vars), "poi_export");
+    }
+
+    #[test]
+    fn test_render_path() {
+        let mut vars = HashMap::new();
+        vars.insert("platform", "tianditu".to_string());
+        vars.insert("region", "盐城/阜宁".to_string());
+        vars.insert("maptype", "satellite".to_string());
+        vars.insert("date", "2024-05-01".to_string());
+
+        let path = render_path(Path::new("tiles"), DEFAULT_TILE_PATH_TEMPLATE, &vars);
+        assert_eq!(
+            path,
+            Path::new("tiles")
+                .join("tianditu")
+                .join("盐城_阜宁")
+                .join("satellite_2024-05-01")
+        );
+    }
+}
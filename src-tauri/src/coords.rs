@@ -1,5 +1,5 @@
 //! 坐标转换工具
-//! 支持 GCJ02 (高德) 和 BD09 (百度) 转 WGS84
+//! 支持 GCJ02 (高德) 和 BD09 (百度) 与 WGS84 互转
 
 use std::f64::consts::PI;
 
@@ -24,17 +24,20 @@ pub fn gcj02_to_wgs84(gcj_lon: f64, gcj_lat: f64) -> (f64, f64) {
         return (gcj_lon, gcj_lat);
     }
 
-    let dlat = transform_lat(gcj_lon - 105.0, gcj_lat - 35.0);
-    let dlon = transform_lon(gcj_lon - 105.0, gcj_lat - 35.0);
-    let radlat = gcj_lat / 180.0 * PI;
-    let magic = radlat.sin();
-    let magic = 1.0 - EE * magic * magic;
-    let sqrtmagic = magic.sqrt();
-    let dlat = (dlat * 180.0) / ((A * (1.0 - EE)) / (magic * sqrtmagic) * PI);
-    let dlon = (dlon * 180.0) / (A / sqrtmagic * radlat.cos() * PI);
+    let (dlon, dlat) = gcj02_offset(gcj_lon, gcj_lat);
     (gcj_lon - dlon, gcj_lat - dlat)
 }
 
+/// WGS84 坐标转 GCJ02
+pub fn wgs84_to_gcj02(wgs_lon: f64, wgs_lat: f64) -> (f64, f64) {
+    if out_of_china(wgs_lon, wgs_lat) {
+        return (wgs_lon, wgs_lat);
+    }
+
+    let (dlon, dlat) = gcj02_offset(wgs_lon, wgs_lat);
+    (wgs_lon + dlon, wgs_lat + dlat)
+}
+
 /// BD09 坐标转 WGS84
 pub fn bd09_to_wgs84(bd_lon: f64, bd_lat: f64) -> (f64, f64) {
     let (gcj_lon, gcj_lat) = bd09_to_gcj02(bd_lon, bd_lat);
@@ -46,6 +49,31 @@ pub fn amap_to_wgs84(gcj_lon: f64, gcj_lat: f64) -> (f64, f64) {
     gcj02_to_wgs84(gcj_lon, gcj_lat)
 }
 
+/// 地球平均半径（米）
+pub(crate) const EARTH_RADIUS_M: f64 = 6_371_008.8;
+
+/// 两点间的球面距离（米）
+pub fn haversine_distance(lon1: f64, lat1: f64, lon2: f64, lat2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let dphi = (lat2 - lat1).to_radians();
+    let dlambda = (lon2 - lon1).to_radians();
+    let a = (dphi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (dlambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+/// 计算某点处 GCJ02 相对 WGS84 的经纬度偏移
+fn gcj02_offset(lon: f64, lat: f64) -> (f64, f64) {
+    let dlat = transform_lat(lon - 105.0, lat - 35.0);
+    let dlon = transform_lon(lon - 105.0, lat - 35.0);
+    let radlat = lat / 180.0 * PI;
+    let magic = radlat.sin();
+    let magic = 1.0 - EE * magic * magic;
+    let sqrtmagic = magic.sqrt();
+    let dlat = (dlat * 180.0) / ((A * (1.0 - EE)) / (magic * sqrtmagic) * PI);
+    let dlon = (dlon * 180.0) / (A / sqrtmagic * radlat.cos() * PI);
+    (dlon, dlat)
+}
+
 fn out_of_china(lon: f64, lat: f64) -> bool {
     !(72.004..=137.8347).contains(&lon) || !(0.8293..=55.8271).contains(&lat)
 }
@@ -65,3 +93,27 @@ fn transform_lon(x: f64, y: f64) -> f64 {
     ret += (150.0 * (x / 12.0 * PI).sin() + 300.0 * (x / 30.0 * PI).sin()) * 2.0 / 3.0;
     ret
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wgs84_gcj02_roundtrip() {
+        let (lon, lat) = (119.8, 33.78);
+        let (gcj_lon, gcj_lat) = wgs84_to_gcj02(lon, lat);
+        assert!((gcj_lon - lon).abs() > 1e-4);
+
+        let (back_lon, back_lat) = gcj02_to_wgs84(gcj_lon, gcj_lat);
+        assert!((back_lon - lon).abs() < 1e-4);
+        assert!((back_lat - lat).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_haversine_distance() {
+        // 经度 1 度在赤道约 111.2 km
+        let d = haversine_distance(0.0, 0.0, 1.0, 0.0);
+        assert!((d - 111_195.0).abs() < 10.0);
+        assert_eq!(haversine_distance(118.8, 32.0, 118.8, 32.0), 0.0);
+    }
+}
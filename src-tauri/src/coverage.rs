@@ -0,0 +1,176 @@
+//! 采集覆盖度评估
+//!
+//! 以 OSM 对应类别的要素数量为基准，按区县对比本地各平台采集量
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::collectors::{default_categories, osm};
+use crate::regions;
+
+/// 默认漏采判定阈值：最佳平台采集量低于 OSM 基准的该比例时标记
+pub const DEFAULT_COVERAGE_THRESHOLD: f64 = 0.5;
+
+/// 连续 Overpass 查询之间的间隔，避免触发速率限制
+const QUERY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 类别对应的 OSM 标签过滤条件，无可靠对应关系的类别返回 None
+pub fn osm_filters(category_id: &str) -> Option<&'static [&'static str]> {
+    let filters: &'static [&'static str] = match category_id {
+        "residential" => &[r#"["landuse"="residential"]["name"]"#],
+        "commercial" => &[r#"["building"~"^(commercial|retail|office)$"]["name"]"#],
+        "school" => &[r#"["amenity"~"^(school|kindergarten|college|university)$"]"#],
+        "hospital" => &[r#"["amenity"~"^(hospital|clinic|doctors|pharmacy)$"]"#],
+        "government" => &[
+            r#"["office"="government"]"#,
+            r#"["amenity"~"^(townhall|police|courthouse)$"]"#,
+        ],
+        "transport" => &[
+            r#"["amenity"~"^(bus_station|parking|fuel)$"]"#,
+            r#"["railway"="station"]"#,
+        ],
+        "business" => &[
+            r#"["shop"~"^(supermarket|mall|convenience)$"]"#,
+            r#"["amenity"~"^(bank|restaurant|marketplace)$"]"#,
+            r#"["tourism"="hotel"]"#,
+        ],
+        "entertainment" => &[
+            r#"["amenity"~"^(cinema|cafe|internet_cafe)$"]"#,
+            r#"["leisure"~"^(fitness_centre|amusement_arcade)$"]"#,
+        ],
+        "nature" => &[
+            r#"["leisure"="park"]"#,
+            r#"["natural"~"^(water|wetland|wood)$"]["name"]"#,
+        ],
+        "admin" => &[r#"["place"~"^(town|village|suburb|neighbourhood)$"]"#],
+        "landmark" => &[
+            r#"["amenity"~"^(library|arts_centre)$"]"#,
+            r#"["tourism"~"^(museum|attraction)$"]"#,
+        ],
+        "industrial" => &[r#"["landuse"="industrial"]["name"]"#],
+        "agriculture" => &[r#"["landuse"~"^(farmland|orchard|greenhouse_horticulture)$"]["name"]"#],
+        "municipal" => &[
+            r#"["power"="substation"]["name"]"#,
+            r#"["amenity"~"^(fire_station|waste_transfer_station)$"]"#,
+            r#"["man_made"="wastewater_plant"]"#,
+        ],
+        "public_service" => &[r#"["amenity"~"^(post_office|community_centre)$"]"#],
+        "religious" => &[r#"["amenity"="place_of_worship"]"#],
+        _ => return None,
+    };
+    Some(filters)
+}
+
+/// 单个区县单个类别的对比结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverageEntry {
+    pub region_code: String,
+    pub region_name: String,
+    pub category_id: String,
+    pub category_name: String,
+    /// OSM 基准数量，查询失败时为 None
+    pub osm_count: Option<u64>,
+    pub osm_error: Option<String>,
+    /// 各平台采集数量
+    pub platform_counts: HashMap<String, i64>,
+    /// 采集量最多的平台（不含 OSM）
+    pub best_platform: Option<String>,
+    pub best_count: i64,
+    /// best_count / osm_count
+    pub ratio: Option<f64>,
+    /// 是否可能漏采严重
+    pub suspicious: bool,
+}
+
+/// 覆盖度对比报告
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverageReport {
+    pub generated_at: String,
+    pub threshold: f64,
+    pub entries: Vec<CoverageEntry>,
+    pub suspicious_count: usize,
+}
+
+/// 生成覆盖度报告
+///
+/// local_counts 为 (region_code, platform, category_id, count)，逐区县逐类别查询 OSM 基准，耗时较长
+pub fn build_report(
+    district_codes: &[String],
+    category_ids: Option<&[String]>,
+    local_counts: &[(String, String, String, i64)],
+    threshold: f64,
+) -> CoverageReport {
+    let categories: Vec<_> = default_categories()
+        .into_iter()
+        .filter(|c| category_ids.is_none_or(|ids| ids.contains(&c.id)))
+        .filter(|c| osm_filters(&c.id).is_some())
+        .collect();
+
+    let mut entries = Vec::new();
+    let mut first_query = true;
+
+    for code in district_codes {
+        let region_name = match regions::get_region_by_code(code) {
+            Some(region) => region.name,
+            None => continue,
+        };
+
+        for category in &categories {
+            let filters = osm_filters(&category.id).unwrap_or_default();
+
+            if !first_query {
+                std::thread::sleep(QUERY_INTERVAL);
+            }
+            first_query = false;
+
+            let (osm_count, osm_error) = match osm::count_features(code, &region_name, filters) {
+                Ok(count) => (Some(count), None),
+                Err(e) => {
+                    log::warn!("[覆盖度] {} {} 查询 OSM 失败: {}", region_name, category.name, e);
+                    (None, Some(e))
+                }
+            };
+
+            let platform_counts: HashMap<String, i64> = local_counts
+                .iter()
+                .filter(|(r, _, c, _)| r == code && c == &category.id)
+                .map(|(_, p, _, n)| (p.clone(), *n))
+                .collect();
+
+            let best = platform_counts
+                .iter()
+                .filter(|(p, _)| p.as_str() != "osm")
+                .max_by_key(|(_, n)| **n);
+            let best_platform = best.map(|(p, _)| p.clone());
+            let best_count = best.map(|(_, n)| *n).unwrap_or(0);
+
+            let ratio = osm_count
+                .filter(|n| *n > 0)
+                .map(|n| best_count as f64 / n as f64);
+
+            entries.push(CoverageEntry {
+                region_code: code.clone(),
+                region_name: region_name.clone(),
+                category_id: category.id.clone(),
+                category_name: category.name.clone(),
+                osm_count,
+                osm_error,
+                platform_counts,
+                best_platform,
+                best_count,
+                ratio,
+                suspicious: ratio.is_some_and(|r| r < threshold),
+            });
+        }
+    }
+
+    let suspicious_count = entries.iter().filter(|e| e.suspicious).count();
+
+    CoverageReport {
+        generated_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        threshold,
+        entries,
+        suspicious_count,
+    }
+}
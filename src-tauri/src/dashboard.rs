@@ -0,0 +1,111 @@
+//! 首页概要
+//!
+//! 一次性聚合 POI 总量、今日新增、运行中任务、磁盘占用与 Key 状态，
+//! 避免首页串行调用多个命令。
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::commands::{get_collector_statuses, poi_db_path, with_poi_db};
+use crate::tile_downloader::commands as tile_commands;
+use crate::error::CmdResult;
+
+/// 各数据文件的磁盘占用（字节）
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskUsage {
+    pub poi_db_bytes: u64,
+    pub tile_db_bytes: u64,
+    pub tile_cache_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// 单个平台的 API Key 状态
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyStatus {
+    pub platform: String,
+    pub total: usize,
+    pub active: usize,
+    /// 配额已用尽的数量
+    pub exhausted: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardSummary {
+    pub total_poi: i64,
+    pub today_added: i64,
+    pub by_platform: HashMap<String, i64>,
+    pub running_collectors: usize,
+    pub running_tile_tasks: usize,
+    pub tile_task_count: usize,
+    pub disk_usage: DiskUsage,
+    pub key_status: Vec<KeyStatus>,
+}
+
+/// SQLite 文件大小，包含 WAL 文件
+fn db_file_size(path: &Path) -> u64 {
+    let mut wal = path.as_os_str().to_os_string();
+    wal.push("-wal");
+    [path, Path::new(&wal)]
+        .iter()
+        .filter_map(|p| p.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// 获取首页概要数据
+#[tauri::command]
+pub async fn get_dashboard_summary(app: AppHandle) -> CmdResult<DashboardSummary> {
+    let stale_days = crate::config::get_freshness_settings()?.stale_days;
+    let (stats, today_added, keys) = with_poi_db(|db| {
+        let stats = db.get_stats(None, stale_days).map_err(|e| format!("获取统计失败: {}", e))?;
+        let today = db
+            .count_poi_added_today()
+            .map_err(|e| format!("获取今日新增失败: {}", e))?;
+        let keys = db
+            .get_all_api_keys()
+            .map_err(|e| format!("获取 API Key 失败: {}", e))?;
+        Ok((stats, today, keys))
+    })?;
+
+    let mut key_status: Vec<KeyStatus> = keys
+        .into_iter()
+        .map(|(platform, keys)| KeyStatus {
+            platform,
+            total: keys.len(),
+            active: keys.iter().filter(|k| k.is_active && !k.quota_exhausted).count(),
+            exhausted: keys.iter().filter(|k| k.quota_exhausted).count(),
+        })
+        .collect();
+    key_status.sort_by(|a, b| a.platform.cmp(&b.platform));
+
+    let running_collectors = get_collector_statuses()
+        .values()
+        .filter(|s| s.status == "running")
+        .count();
+
+    let tile_db_bytes = db_file_size(&tile_commands::tile_db_path(&app)?);
+    let tile_cache_bytes = db_file_size(&tile_commands::tile_cache_path(&app)?);
+    let tasks = tile_commands::get_tile_tasks(app).await?;
+    let running_tile_tasks = tasks.iter().filter(|t| t.status == "downloading").count();
+
+    let poi_db_bytes = db_file_size(poi_db_path());
+    let disk_usage = DiskUsage {
+        poi_db_bytes,
+        tile_db_bytes,
+        tile_cache_bytes,
+        total_bytes: poi_db_bytes + tile_db_bytes + tile_cache_bytes,
+    };
+
+    Ok(DashboardSummary {
+        total_poi: stats.total,
+        today_added,
+        by_platform: stats.by_platform,
+        running_collectors,
+        running_tile_tasks,
+        tile_task_count: tasks.len(),
+        disk_usage,
+        key_status,
+    })
+}
@@ -1,26 +1,111 @@
+use crate::collectors::{POIData, PoiDetail};
 use crate::commands::{ApiKey, Stats, POI};
-use rusqlite::{params, Connection, Result};
+use crate::config::Bounds;
+use crate::coords::haversine_distance;
+use crate::normalize::{normalize_name, search_variants};
+use rusqlite::{params, Connection, OptionalExtension, Result};
 use std::collections::HashMap;
 
+/// 搜索词展开后的最大变体数
+const MAX_SEARCH_VARIANTS: usize = 8;
+
+/// 已完成存量名称规范化的库版本（PRAGMA user_version）
+const NAMES_NORMALIZED_VERSION: i32 = 1;
+
+/// 数据库被其他连接锁定时的最长等待时间
+pub const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
 pub struct Database {
     conn: Connection,
 }
 
 impl Database {
     pub fn new(path: &str) -> Result<Self> {
+        Self::open(path, None)
+    }
+
+    /// 打开数据库，key 为 SQLCipher 加密密码
+    pub fn open(path: &str, key: Option<&str>) -> Result<Self> {
         let conn = Connection::open(path)?;
+        // 密码需在任何读写之前设置，密码错误时后续语句返回 NotADatabase
+        if let Some(key) = key {
+            conn.pragma_update(None, "key", key)?;
+        }
 
         // 启用 WAL 模式，避免 journal 文件频繁出现/消失
         conn.execute_batch("PRAGMA journal_mode=WAL;")?;
+        // 其他连接（备份、外部工具）持有写锁时等待而不是立即报 database is locked
+        conn.busy_timeout(BUSY_TIMEOUT)?;
 
         let db = Self { conn };
         db.migrate()?;
         db.init_tables()?;
+        db.normalize_existing_names()?;
         Ok(db)
     }
 
+    /// 规范化早于入库规范化采集的 POI 名称，完成后记录库版本，之后启动不再执行
+    ///
+    /// 规范化后与已有记录重名（同平台同坐标）的保留原名，不合并也不删除
+    fn normalize_existing_names(&self) -> Result<()> {
+        let version: i32 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version >= NAMES_NORMALIZED_VERSION {
+            return Ok(());
+        }
+
+        let mut changed: Vec<(i64, String)> = Vec::new();
+        {
+            let mut stmt = self.conn.prepare("SELECT id, name FROM poi_data")?;
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                let name: String = row.get(1)?;
+                let normalized = normalize_name(&name);
+                if normalized != name {
+                    changed.push((row.get(0)?, normalized));
+                }
+            }
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let mut updated = 0;
+        {
+            let mut stmt = tx.prepare("UPDATE OR IGNORE poi_data SET name = ?1 WHERE id = ?2")?;
+            for (id, name) in &changed {
+                updated += stmt.execute(params![name, id])?;
+            }
+        }
+        tx.pragma_update(None, "user_version", NAMES_NORMALIZED_VERSION)?;
+        tx.commit()?;
+
+        if !changed.is_empty() {
+            log::info!(
+                "迁移数据库：规范化 {} 条 POI 名称，{} 条与已有记录重名保留原名",
+                updated,
+                changed.len() - updated
+            );
+        }
+        Ok(())
+    }
+
     /// 数据库迁移：检查表结构版本并升级
     fn migrate(&self) -> Result<()> {
+        // api_keys 增加 QPS 上限字段（表不存在时忽略，由建表语句创建）
+        let has_qps: bool = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('api_keys') WHERE name = 'qps'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+        if !has_qps {
+            let _ = self
+                .conn
+                .execute("ALTER TABLE api_keys ADD COLUMN qps INTEGER", []);
+        }
+
         // 检查是否有旧版本的 poi_data 表（没有新字段）
         let has_category_id: bool = self
             .conn
@@ -71,6 +156,53 @@ impl Database {
             );
         }
 
+        // 复核时间：旧数据以入库时间作为最近一次复核时间
+        let has_verified_at: bool = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('poi_data') WHERE name = 'last_verified_at'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+        if !has_verified_at {
+            let added = self
+                .conn
+                .execute("ALTER TABLE poi_data ADD COLUMN last_verified_at TEXT", []);
+            if added.is_ok() {
+                log::info!("迁移数据库：添加 last_verified_at 字段");
+                let _ = self.conn.execute(
+                    "UPDATE poi_data SET last_verified_at = created_at WHERE last_verified_at IS NULL",
+                    [],
+                );
+            }
+        }
+
+        // 详情补全字段
+        for column in [
+            "opening_hours",
+            "platform_tags",
+            "price",
+            "rating",
+            "photos",
+            "enriched_at",
+        ] {
+            let exists: bool = self
+                .conn
+                .query_row(
+                    "SELECT COUNT(*) > 0 FROM pragma_table_info('poi_data') WHERE name = ?1",
+                    params![column],
+                    |row| row.get(0),
+                )
+                .unwrap_or(false);
+            if !exists {
+                let sql = format!("ALTER TABLE poi_data ADD COLUMN {} TEXT", column);
+                if self.conn.execute(&sql, []).is_ok() {
+                    log::info!("迁移数据库：添加 {} 字段", column);
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -84,6 +216,7 @@ impl Database {
                 name TEXT,
                 is_active INTEGER DEFAULT 1,
                 quota_exhausted INTEGER DEFAULT 0,
+                qps INTEGER,
                 created_at TEXT DEFAULT CURRENT_TIMESTAMP
             );
 
@@ -102,6 +235,13 @@ impl Database {
                 region_code TEXT,
                 raw_data TEXT,
                 created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                last_verified_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                opening_hours TEXT,
+                platform_tags TEXT,
+                price TEXT,
+                rating TEXT,
+                photos TEXT,
+                enriched_at TEXT,
                 UNIQUE(platform, name, lon, lat)
             );
 
@@ -109,22 +249,238 @@ impl Database {
             CREATE INDEX IF NOT EXISTS idx_poi_platform ON poi_data(platform);
             CREATE INDEX IF NOT EXISTS idx_poi_category ON poi_data(category);
             CREATE INDEX IF NOT EXISTS idx_poi_region ON poi_data(region_code);
+            CREATE INDEX IF NOT EXISTS idx_poi_verified ON poi_data(last_verified_at);
+
+            CREATE TABLE IF NOT EXISTS response_cache (
+                platform TEXT NOT NULL,
+                region_code TEXT NOT NULL,
+                keyword TEXT NOT NULL,
+                page INTEGER NOT NULL,
+                category_id TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                has_more INTEGER NOT NULL,
+                created_at INTEGER NOT NULL,
+                PRIMARY KEY (platform, region_code, keyword, page, category_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS poi_aliases (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                alias TEXT NOT NULL UNIQUE,
+                target TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS poi_snapshots (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                filter TEXT NOT NULL,
+                poi_count INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE TABLE IF NOT EXISTS poi_snapshot_items (
+                snapshot_id INTEGER NOT NULL,
+                poi_id INTEGER NOT NULL,
+                platform TEXT NOT NULL,
+                name TEXT NOT NULL,
+                lon REAL NOT NULL,
+                lat REAL NOT NULL,
+                address TEXT,
+                phone TEXT,
+                category TEXT,
+                region_code TEXT,
+                PRIMARY KEY (snapshot_id, poi_id)
+            );
+
+            CREATE TABLE IF NOT EXISTS collector_sessions (
+                platform TEXT PRIMARY KEY,
+                launch TEXT NOT NULL,
+                status TEXT NOT NULL,
+                total_collected INTEGER NOT NULL DEFAULT 0,
+                current_category_id TEXT,
+                completed_categories TEXT,
+                started_at TEXT,
+                heartbeat_at TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS tags (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                color TEXT,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE TABLE IF NOT EXISTS poi_tags (
+                poi_id INTEGER NOT NULL,
+                tag_id INTEGER NOT NULL,
+                note TEXT,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (poi_id, tag_id)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_poi_tags_tag ON poi_tags(tag_id);
+
+            CREATE TRIGGER IF NOT EXISTS poi_tags_poi_delete AFTER DELETE ON poi_data BEGIN
+                DELETE FROM poi_tags WHERE poi_id = old.id;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS poi_tags_tag_delete AFTER DELETE ON tags BEGIN
+                DELETE FROM poi_tags WHERE tag_id = old.id;
+            END;
+
+            -- 空间索引：点数据的外接矩形即为点本身（R*Tree 以单精度存储，查询时再按原坐标精确过滤）
+            CREATE VIRTUAL TABLE IF NOT EXISTS poi_rtree USING rtree(
+                id, min_lon, max_lon, min_lat, max_lat
+            );
+
+            CREATE TRIGGER IF NOT EXISTS poi_rtree_insert AFTER INSERT ON poi_data BEGIN
+                INSERT OR REPLACE INTO poi_rtree VALUES (new.id, new.lon, new.lon, new.lat, new.lat);
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS poi_rtree_update AFTER UPDATE OF lon, lat ON poi_data BEGIN
+                UPDATE poi_rtree SET min_lon = new.lon, max_lon = new.lon, min_lat = new.lat, max_lat = new.lat
+                WHERE id = new.id;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS poi_rtree_delete AFTER DELETE ON poi_data BEGIN
+                DELETE FROM poi_rtree WHERE id = old.id;
+            END;
         "#,
         )?;
+
+        // 旧数据库首次创建索引时回填已有数据
+        let indexed: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM poi_rtree", [], |row| row.get(0))?;
+        if indexed == 0 {
+            let count = self.rebuild_spatial_index()?;
+            if count > 0 {
+                log::info!("已为 {} 条 POI 建立空间索引", count);
+            }
+        }
         Ok(())
     }
 
-    pub fn get_stats(&self) -> Result<Stats> {
-        let total: i64 = self
-            .conn
-            .query_row("SELECT COUNT(*) FROM poi_data", [], |row| row.get(0))
-            .unwrap_or(0);
+    /// 重建空间索引，返回索引的记录数
+    pub fn rebuild_spatial_index(&self) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM poi_rtree", [])?;
+        let count = tx.execute(
+            "INSERT INTO poi_rtree SELECT id, lon, lon, lat, lat FROM poi_data",
+            [],
+        )?;
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// 查询范围内的 POI（走空间索引）
+    pub fn query_poi_in_bounds(&self, bounds: &Bounds, limit: i64) -> Result<Vec<POI>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT p.id, p.name, p.lon, p.lat, p.address, p.category, p.platform
+             FROM poi_rtree r JOIN poi_data p ON p.id = r.id
+             WHERE r.max_lon >= ?1 AND r.min_lon <= ?2 AND r.max_lat >= ?3 AND r.min_lat <= ?4
+               AND p.lon BETWEEN ?1 AND ?2 AND p.lat BETWEEN ?3 AND ?4
+             LIMIT ?5",
+        )?;
+        let rows = stmt.query_map(
+            params![bounds.min_lon, bounds.max_lon, bounds.min_lat, bounds.max_lat, limit],
+            |row| {
+                Ok(POI {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    lon: row.get(2)?,
+                    lat: row.get(3)?,
+                    address: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                    category: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+                    platform: row.get(6)?,
+                })
+            },
+        )?;
+        rows.collect()
+    }
+
+    /// 查询半径范围内的 POI，按距离升序返回
+    pub fn query_poi_nearby(
+        &self,
+        lon: f64,
+        lat: f64,
+        radius_m: f64,
+        limit: usize,
+    ) -> Result<Vec<NearbyPOI>> {
+        // 先用外接矩形在索引中粗筛，再按球面距离精确过滤
+        let dlat = radius_m / 111_320.0;
+        let dlon = radius_m / (111_320.0 * lat.to_radians().cos().max(1e-6));
+        let bounds = Bounds {
+            min_lon: lon - dlon,
+            max_lon: lon + dlon,
+            min_lat: lat - dlat,
+            max_lat: lat + dlat,
+        };
+
+        let mut results: Vec<NearbyPOI> = self
+            .query_poi_in_bounds(&bounds, i64::MAX)?
+            .into_iter()
+            .map(|poi| NearbyPOI {
+                distance_m: haversine_distance(lon, lat, poi.lon, poi.lat),
+                poi,
+            })
+            .filter(|p| p.distance_m <= radius_m)
+            .collect();
+        results.sort_by(|a, b| a.distance_m.total_cmp(&b.distance_m));
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    /// 按经纬度网格聚合范围内的 POI 数量，cell_size 为网格边长（度）
+    pub fn aggregate_poi_grid(&self, bounds: &Bounds, cell_size: f64) -> Result<Vec<GridCell>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT CAST((p.lon - ?1) / ?5 AS INTEGER) AS gx,
+                    CAST((p.lat - ?3) / ?5 AS INTEGER) AS gy,
+                    COUNT(*), AVG(p.lon), AVG(p.lat)
+             FROM poi_rtree r JOIN poi_data p ON p.id = r.id
+             WHERE r.max_lon >= ?1 AND r.min_lon <= ?2 AND r.max_lat >= ?3 AND r.min_lat <= ?4
+               AND p.lon BETWEEN ?1 AND ?2 AND p.lat BETWEEN ?3 AND ?4
+             GROUP BY gx, gy",
+        )?;
+        let rows = stmt.query_map(
+            params![bounds.min_lon, bounds.max_lon, bounds.min_lat, bounds.max_lat, cell_size],
+            |row| {
+                let gx: i64 = row.get(0)?;
+                let gy: i64 = row.get(1)?;
+                Ok(GridCell {
+                    min_lon: bounds.min_lon + gx as f64 * cell_size,
+                    min_lat: bounds.min_lat + gy as f64 * cell_size,
+                    count: row.get(2)?,
+                    center_lon: row.get(3)?,
+                    center_lat: row.get(4)?,
+                })
+            },
+        )?;
+        rows.collect()
+    }
+
+    /// 统计 POI 总量及按平台、类别的分布，region_codes 为空时统计全库
+    pub fn get_stats(&self, region_codes: Option<Vec<String>>, stale_days: u32) -> Result<Stats> {
+        let filter = PoiFilter {
+            region_codes,
+            ..Default::default()
+        };
+        let (clause, values) = Self::filter_clause(&filter);
+        let params: Vec<&dyn rusqlite::ToSql> =
+            values.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+        let region_where = if clause.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clause)
+        };
+
+        let total = self.count_poi_filtered(&filter).unwrap_or(0) as i64;
 
         let mut by_platform = HashMap::new();
-        let mut stmt = self
-            .conn
-            .prepare("SELECT platform, COUNT(*) FROM poi_data GROUP BY platform")?;
-        let rows = stmt.query_map([], |row| {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT platform, COUNT(*) FROM poi_data {} GROUP BY platform",
+            region_where
+        ))?;
+        let rows = stmt.query_map(params.as_slice(), |row| {
             Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
         })?;
         for row in rows {
@@ -133,10 +489,16 @@ impl Database {
         }
 
         let mut by_category = HashMap::new();
-        let mut stmt = self.conn.prepare(
-            "SELECT category, COUNT(*) FROM poi_data WHERE category IS NOT NULL GROUP BY category",
-        )?;
-        let rows = stmt.query_map([], |row| {
+        let category_where = if clause.is_empty() {
+            "WHERE category IS NOT NULL".to_string()
+        } else {
+            format!("WHERE category IS NOT NULL AND {}", clause)
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT category, COUNT(*) FROM poi_data {} GROUP BY category",
+            category_where
+        ))?;
+        let rows = stmt.query_map(params.as_slice(), |row| {
             Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
         })?;
         for row in rows {
@@ -144,18 +506,60 @@ impl Database {
             by_category.insert(category, count);
         }
 
+        // 超过 stale_days 天未复核的 POI 数量
+        let stale = self.count_poi_filtered(&PoiFilter {
+            freshness: Some(Freshness::Stale),
+            stale_days: Some(stale_days),
+            ..filter
+        })? as i64;
+
         Ok(Stats {
             total,
             by_platform,
             by_category,
+            stale,
         })
     }
 
+    /// 记录 POI 已通过平台复核
+    pub fn mark_poi_verified(&self, id: i64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE poi_data SET last_verified_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            params![id],
+        )?;
+        Ok(())
+    }
+
+    /// 读取满足过滤条件的 POI ID
+    pub fn poi_ids_filtered(&self, filter: &PoiFilter) -> Result<std::collections::HashSet<i64>> {
+        let (clause, values) = Self::filter_clause(filter);
+        let sql = if clause.is_empty() {
+            "SELECT id FROM poi_data".to_string()
+        } else {
+            format!("SELECT id FROM poi_data WHERE {}", clause)
+        };
+        let mut stmt = self.conn.prepare(&sql)?;
+        let params: Vec<&dyn rusqlite::ToSql> =
+            values.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+        let rows = stmt.query_map(params.as_slice(), |row| row.get(0))?;
+        rows.collect()
+    }
+
+    /// 统计本地时间今日新增的 POI 数量
+    pub fn count_poi_added_today(&self) -> Result<i64> {
+        // created_at 为 UTC 时间，将本地零点换算为 UTC 后比较
+        self.conn.query_row(
+            "SELECT COUNT(*) FROM poi_data WHERE created_at >= datetime('now', 'localtime', 'start of day', 'utc')",
+            [],
+            |row| row.get(0),
+        )
+    }
+
     pub fn get_all_api_keys(&self) -> Result<HashMap<String, Vec<ApiKey>>> {
         let mut result: HashMap<String, Vec<ApiKey>> = HashMap::new();
 
         let mut stmt = self.conn.prepare(
-            "SELECT id, platform, api_key, name, is_active, quota_exhausted FROM api_keys ORDER BY platform, id"
+            "SELECT id, platform, api_key, name, is_active, quota_exhausted, qps FROM api_keys ORDER BY platform, id"
         )?;
 
         let rows = stmt.query_map([], |row| {
@@ -167,6 +571,7 @@ impl Database {
                     api_key: row.get::<_, String>(2)?, // 返回完整的 key 给后端使用
                     is_active: row.get::<_, i64>(4)? == 1,
                     quota_exhausted: row.get::<_, i64>(5)? == 1,
+                    qps: row.get(6)?,
                 },
             ))
         })?;
@@ -179,20 +584,36 @@ impl Database {
         Ok(result)
     }
 
-    pub fn add_api_key(&self, platform: &str, api_key: &str, name: Option<&str>) -> Result<i64> {
+    pub fn add_api_key(
+        &self,
+        platform: &str,
+        api_key: &str,
+        name: Option<&str>,
+        qps: Option<u32>,
+    ) -> Result<i64> {
         self.conn.execute(
-            "INSERT INTO api_keys (platform, api_key, name) VALUES (?1, ?2, ?3)",
-            params![platform, api_key, name],
+            "INSERT INTO api_keys (platform, api_key, name, qps) VALUES (?1, ?2, ?3, ?4)",
+            params![platform, api_key, name, qps],
         )?;
         Ok(self.conn.last_insert_rowid())
     }
 
+    /// 设置 Key 的 QPS 上限，None 表示使用默认值
+    pub fn set_api_key_qps(&self, key_id: i64, qps: Option<u32>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE api_keys SET qps = ?1 WHERE id = ?2",
+            params![qps, key_id],
+        )?;
+        Ok(())
+    }
+
     pub fn delete_api_key(&self, key_id: i64) -> Result<()> {
         self.conn
             .execute("DELETE FROM api_keys WHERE id = ?1", params![key_id])?;
         Ok(())
     }
 
+    /// 搜索名称或地址，查询词按别名与繁简互转展开后任一命中即可
     pub fn search_poi(
         &self,
         query: &str,
@@ -200,54 +621,358 @@ impl Database {
         mode: &str,
         limit: i64,
     ) -> Result<Vec<POI>> {
-        let pattern = match mode {
-            "exact" => query.to_string(),
-            "prefix" => format!("{}%", query),
-            "contains" => format!("%{}%", query),
-            _ => format!("%{}%", query), // smart/fuzzy
-        };
+        // 名称入库时已规范化，查询词按同样规则处理；原始查询词保留，用于匹配未规范化的地址
+        let normalized = normalize_name(query);
+        let mut variants = search_variants(&normalized, &self.alias_pairs()?, MAX_SEARCH_VARIANTS);
+        let raw = query.trim();
+        if !raw.is_empty() && !variants.iter().any(|v| v == raw) {
+            variants.push(raw.to_string());
+        }
+        let mut values: Vec<String> = variants
+            .iter()
+            .map(|term| match mode {
+                "exact" => term.clone(),
+                "prefix" => format!("{}%", term),
+                "contains" => format!("%{}%", term),
+                _ => format!("%{}%", term), // smart/fuzzy
+            })
+            .collect();
+
+        let conditions = (1..=values.len())
+            .map(|i| format!("name LIKE ?{0} OR address LIKE ?{0}", i))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let mut sql = format!(
+            "SELECT id, name, lon, lat, address, category, platform FROM poi_data WHERE ({})",
+            conditions
+        );
+        if let Some(p) = platform {
+            values.push(p.to_string());
+            sql.push_str(&format!(" AND platform = ?{}", values.len()));
+        }
+        sql.push_str(&format!(" LIMIT {}", limit.max(0)));
+
+        let params: Vec<&dyn rusqlite::ToSql> =
+            values.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params.as_slice(), |row| {
+            Ok(POI {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                lon: row.get(2)?,
+                lat: row.get(3)?,
+                address: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                category: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+                platform: row.get(6)?,
+            })
+        })?;
 
         let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
 
-        if let Some(p) = platform {
-            let mut stmt = self.conn.prepare(
-                "SELECT id, name, lon, lat, address, category, platform FROM poi_data WHERE (name LIKE ?1 OR address LIKE ?1) AND platform = ?2 LIMIT ?3"
+    /// 别名与全称对照，供搜索展开使用
+    fn alias_pairs(&self) -> Result<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare("SELECT alias, target FROM poi_aliases")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
+
+    /// 获取全部别名
+    pub fn list_poi_aliases(&self) -> Result<Vec<PoiAlias>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, alias, target FROM poi_aliases ORDER BY alias")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(PoiAlias {
+                id: row.get(0)?,
+                alias: row.get(1)?,
+                target: row.get(2)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// 新增或更新别名，同一别名只对应一个全称
+    pub fn upsert_poi_alias(&self, alias: &str, target: &str) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO poi_aliases (alias, target) VALUES (?1, ?2)
+             ON CONFLICT(alias) DO UPDATE SET target = excluded.target",
+            params![alias, target],
+        )?;
+        self.conn.query_row(
+            "SELECT id FROM poi_aliases WHERE alias = ?1",
+            params![alias],
+            |row| row.get(0),
+        )
+    }
+
+    /// 删除别名
+    pub fn delete_poi_alias(&self, id: i64) -> Result<bool> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM poi_aliases WHERE id = ?1", params![id])?;
+        Ok(deleted > 0)
+    }
+
+    /// 全部标签及各标签下的 POI 数量
+    pub fn list_tags(&self) -> Result<Vec<PoiTag>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT t.id, t.name, t.color, t.created_at, COUNT(pt.poi_id)
+             FROM tags t LEFT JOIN poi_tags pt ON pt.tag_id = t.id
+             GROUP BY t.id ORDER BY t.name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(PoiTag {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                color: row.get(2)?,
+                created_at: row.get(3)?,
+                poi_count: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    pub fn get_tag_id(&self, name: &str) -> Result<Option<i64>> {
+        self.conn
+            .query_row("SELECT id FROM tags WHERE name = ?1", params![name], |row| {
+                row.get(0)
+            })
+            .optional()
+    }
+
+    /// 新增标签，同名标签已存在时更新颜色，返回标签 ID
+    pub fn upsert_tag(&self, name: &str, color: Option<&str>) -> Result<i64> {
+        self.conn.execute(
+            "INSERT INTO tags (name, color) VALUES (?1, ?2)
+             ON CONFLICT(name) DO UPDATE SET color = COALESCE(excluded.color, color)",
+            params![name, color],
+        )?;
+        self.conn
+            .query_row("SELECT id FROM tags WHERE name = ?1", params![name], |row| {
+                row.get(0)
+            })
+    }
+
+    /// 删除标签，关联记录由触发器一并删除
+    pub fn delete_tag(&self, id: i64) -> Result<bool> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM tags WHERE id = ?1", params![id])?;
+        Ok(deleted > 0)
+    }
+
+    /// 为 POI 打标签，已有该标签的 POI 只更新备注，返回新打标的数量
+    pub fn tag_pois(&self, ids: &[i64], tag_id: i64, note: Option<&str>) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut added = 0;
+        {
+            let mut insert = tx.prepare(
+                "INSERT OR IGNORE INTO poi_tags (poi_id, tag_id, note)
+                 SELECT id, ?2, ?3 FROM poi_data WHERE id = ?1",
             )?;
-            let rows = stmt.query_map(params![pattern, p, limit], |row| {
-                Ok(POI {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    lon: row.get(2)?,
-                    lat: row.get(3)?,
-                    address: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
-                    category: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
-                    platform: row.get(6)?,
-                })
+            let mut update_note =
+                tx.prepare("UPDATE poi_tags SET note = ?3 WHERE poi_id = ?1 AND tag_id = ?2")?;
+            for id in ids {
+                let inserted = insert.execute(params![id, tag_id, note])?;
+                if inserted == 0 && note.is_some() {
+                    update_note.execute(params![id, tag_id, note])?;
+                }
+                added += inserted;
+            }
+        }
+        tx.commit()?;
+        Ok(added)
+    }
+
+    /// 移除 POI 的标签，返回移除的数量
+    pub fn untag_pois(&self, ids: &[i64], tag_id: i64) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut removed = 0;
+        {
+            let mut stmt = tx.prepare("DELETE FROM poi_tags WHERE poi_id = ?1 AND tag_id = ?2")?;
+            for id in ids {
+                removed += stmt.execute(params![id, tag_id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// 读取 POI 的标签，ids 为空时读取全部已打标的 POI
+    pub fn get_poi_tag_map(&self, ids: Option<&[i64]>) -> Result<HashMap<i64, Vec<PoiTagLink>>> {
+        let mut map: HashMap<i64, Vec<PoiTagLink>> = HashMap::new();
+        let sql = "SELECT pt.poi_id, t.name, t.color, pt.note
+                   FROM poi_tags pt JOIN tags t ON t.id = pt.tag_id";
+        let mut collect = |sql: &str, params: &[&dyn rusqlite::ToSql]| -> Result<()> {
+            let mut stmt = self.conn.prepare(sql)?;
+            let rows = stmt.query_map(params, |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    PoiTagLink {
+                        name: row.get(1)?,
+                        color: row.get(2)?,
+                        note: row.get(3)?,
+                    },
+                ))
             })?;
             for row in rows {
-                results.push(row?);
+                let (poi_id, link) = row?;
+                map.entry(poi_id).or_default().push(link);
             }
-        } else {
-            let mut stmt = self.conn.prepare(
-                "SELECT id, name, lon, lat, address, category, platform FROM poi_data WHERE (name LIKE ?1 OR address LIKE ?1) LIMIT ?2"
-            )?;
-            let rows = stmt.query_map(params![pattern, limit], |row| {
-                Ok(POI {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    lon: row.get(2)?,
-                    lat: row.get(3)?,
-                    address: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
-                    category: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
-                    platform: row.get(6)?,
-                })
+            Ok(())
+        };
+        match ids {
+            None => collect(&format!("{} ORDER BY t.name", sql), &[])?,
+            Some(ids) => {
+                // 分批查询，避免超出 SQLite 单条语句的参数上限
+                for chunk in ids.chunks(500) {
+                    let placeholders: Vec<String> = chunk.iter().map(|_| "?".to_string()).collect();
+                    let params: Vec<&dyn rusqlite::ToSql> =
+                        chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+                    collect(
+                        &format!(
+                            "{} WHERE pt.poi_id IN ({}) ORDER BY t.name",
+                            sql,
+                            placeholders.join(",")
+                        ),
+                        &params,
+                    )?;
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    /// 读取 OSM POI 在 raw_data 中保留的标签：POI ID -> 标签名 -> 取值
+    pub fn get_osm_tag_map(&self) -> Result<HashMap<i64, HashMap<String, String>>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, raw_data FROM poi_data WHERE platform = 'osm' AND raw_data IS NOT NULL",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, crate::raw_data::decode(row.get_ref(1)?)))
+        })?;
+        let mut map = HashMap::new();
+        for row in rows {
+            let (id, Some(raw)) = row? else {
+                continue;
+            };
+            let Ok(serde_json::Value::Object(raw)) = serde_json::from_str(&raw) else {
+                continue;
+            };
+            let Some(serde_json::Value::Object(tags)) = raw.get("tags") else {
+                continue;
+            };
+            let tags: HashMap<String, String> = tags
+                .iter()
+                .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                .collect();
+            if !tags.is_empty() {
+                map.insert(id, tags);
+            }
+        }
+        Ok(map)
+    }
+
+    /// 读取 POI 的平台与入库时间：(id, 平台, 入库时间)，用于生成来源声明
+    pub fn get_poi_sources(&self, platform: Option<&str>) -> Result<Vec<(i64, String, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, platform, COALESCE(created_at, '') FROM poi_data
+             WHERE ?1 IS NULL OR platform = ?1",
+        )?;
+        let rows = stmt.query_map(params![platform], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?;
+        rows.collect()
+    }
+
+    /// 读取待补全详情的 POI：(id, 平台 id, raw_data)，平台 id 取自 raw_data 中的 id_key 字段，
+    /// 没有 raw_data 或其中没有平台 id 的跳过
+    ///
+    /// ids 为空时按 id 顺序取尚未补全的前 limit 条
+    pub fn get_pois_to_enrich(
+        &self,
+        platform: &str,
+        id_key: &str,
+        ids: Option<&[i64]>,
+        limit: usize,
+    ) -> Result<Vec<(i64, String, String)>> {
+        let sql = "SELECT id, raw_data FROM poi_data
+                   WHERE platform = ?1 AND enriched_at IS NULL AND raw_data IS NOT NULL";
+        let mut rows = Vec::new();
+        let mut collect = |sql: &str, params: &[&dyn rusqlite::ToSql]| -> Result<()> {
+            let mut stmt = self.conn.prepare(sql)?;
+            let found = stmt.query_map(params, |row| {
+                Ok((row.get::<_, i64>(0)?, crate::raw_data::decode(row.get_ref(1)?)))
             })?;
-            for row in rows {
-                results.push(row?);
+            for row in found {
+                let (id, Some(raw)) = row? else {
+                    continue;
+                };
+                let poi_id = serde_json::from_str::<serde_json::Value>(&raw)
+                    .ok()
+                    .and_then(|v| v.get(id_key)?.as_str().map(str::to_string));
+                if let Some(poi_id) = poi_id.filter(|p| !p.is_empty()) {
+                    rows.push((id, poi_id, raw));
+                }
+            }
+            Ok(())
+        };
+        match ids {
+            None => collect(
+                &format!("{} ORDER BY id LIMIT {}", sql, limit),
+                &[&platform as &dyn rusqlite::ToSql],
+            )?,
+            Some(ids) => {
+                for chunk in ids.chunks(500) {
+                    let placeholders: Vec<String> = chunk.iter().map(|_| "?".to_string()).collect();
+                    let mut params: Vec<&dyn rusqlite::ToSql> = vec![&platform];
+                    params.extend(chunk.iter().map(|id| id as &dyn rusqlite::ToSql));
+                    collect(
+                        &format!("{} AND id IN ({}) ORDER BY id", sql, placeholders.join(",")),
+                        &params,
+                    )?;
+                }
             }
         }
+        rows.truncate(limit);
+        Ok(rows)
+    }
 
-        Ok(results)
+    /// 写入详情补全结果，raw_data 按当前存储设置重新编码
+    ///
+    /// detail 为空表示详情接口查不到，只记录补全时间；照片 URL 以 JSON 数组保存
+    pub fn update_poi_detail(
+        &self,
+        id: i64,
+        detail: Option<&PoiDetail>,
+        raw_data: &str,
+    ) -> Result<()> {
+        let field = |get: fn(&PoiDetail) -> &str| {
+            detail.map(get).filter(|s| !s.is_empty()).map(str::to_string)
+        };
+        let photos = detail
+            .filter(|d| !d.photos.is_empty())
+            .map(|d| serde_json::to_string(&d.photos).unwrap_or_default());
+        self.conn.execute(
+            "UPDATE poi_data SET opening_hours = ?1, platform_tags = ?2, price = ?3, rating = ?4,
+                    photos = ?5, raw_data = ?6, enriched_at = CURRENT_TIMESTAMP
+             WHERE id = ?7",
+            params![
+                field(|d| &d.opening_hours),
+                field(|d| &d.tags),
+                field(|d| &d.price),
+                field(|d| &d.rating),
+                photos,
+                crate::raw_data::encode(raw_data),
+                id
+            ],
+        )?;
+        Ok(())
     }
 
     pub fn insert_poi(
@@ -265,13 +990,144 @@ impl Database {
         region_code: &str,
         raw_data: &str,
     ) -> Result<bool> {
-        let rows = self.conn.execute(
+        // 统一全角/半角、空白与括号，避免名称微差导致重复
+        let name = normalize_name(name);
+        // 复用预编译语句，避免每条记录重新解析 SQL
+        let mut stmt = self.conn.prepare_cached(
             "INSERT OR IGNORE INTO poi_data (name, lon, lat, original_lon, original_lat, category, category_id, address, phone, platform, region_code, raw_data) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
-            params![name, lon, lat, original_lon, original_lat, category, category_id, address, phone, platform, region_code, raw_data]
         )?;
+        // 按设置截断、压缩或丢弃原始响应
+        let raw_data = crate::raw_data::encode(raw_data);
+        let rows = stmt.execute(params![name, lon, lat, original_lon, original_lat, category, category_id, address, phone, platform, region_code, raw_data])?;
+        if rows == 0 {
+            // 已存在的 POI 被再次采集到，视为完成一次复核
+            let mut stmt = self.conn.prepare_cached(
+                "UPDATE poi_data SET last_verified_at = CURRENT_TIMESTAMP WHERE platform = ?1 AND name = ?2 AND lon = ?3 AND lat = ?4",
+            )?;
+            stmt.execute(params![platform, name, lon, lat])?;
+        }
         Ok(rows > 0) // 返回是否实际插入了行
     }
 
+    /// 在单个事务中批量插入一页 POI，返回新增记录的 (pois 下标, id)
+    ///
+    /// 单条插入失败只记录日志，不影响同批其他记录
+    pub fn insert_poi_batch(
+        &self,
+        pois: &[POIData],
+        category: &str,
+        category_id: &str,
+        region_code: &str,
+    ) -> Result<Vec<(usize, i64)>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut inserted = Vec::new();
+        for (index, poi) in pois.iter().enumerate() {
+            match self.insert_poi(
+                &poi.name,
+                poi.lon,
+                poi.lat,
+                poi.original_lon,
+                poi.original_lat,
+                category,
+                category_id,
+                &poi.address,
+                &poi.phone,
+                &poi.platform,
+                region_code,
+                &poi.raw_data,
+            ) {
+                Ok(true) => inserted.push((index, self.conn.last_insert_rowid())),
+                Ok(false) => {} // 重复数据，忽略
+                Err(e) => {
+                    log::warn!("插入 POI 失败: {}", e);
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    /// 获取需要重建类别的 POI (id, platform, raw_data)，all 为 false 时仅返回类别为空的记录
+    pub fn get_poi_raw_for_categories(&self, all: bool) -> Result<Vec<(i64, String, String)>> {
+        let sql = if all {
+            "SELECT id, platform, raw_data FROM poi_data WHERE raw_data IS NOT NULL"
+        } else {
+            "SELECT id, platform, raw_data FROM poi_data WHERE raw_data IS NOT NULL AND (category IS NULL OR category = '' OR category_id IS NULL OR category_id = '')"
+        };
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                crate::raw_data::decode(row.get_ref(2)?),
+            ))
+        })?;
+        let mut result = Vec::new();
+        for row in rows {
+            if let (id, platform, Some(raw)) = row? {
+                result.push((id, platform, raw));
+            }
+        }
+        Ok(result)
+    }
+
+    /// 按 ID 顺序转换一批 raw_data，返回本批处理情况
+    pub fn recode_raw_data(
+        &self,
+        after_id: i64,
+        limit: usize,
+        encode: impl Fn(&str) -> rusqlite::types::Value,
+    ) -> Result<RawDataBatch> {
+        let rows: Vec<(i64, rusqlite::types::Value)> = {
+            let mut stmt = self.conn.prepare(
+                "SELECT id, raw_data FROM poi_data WHERE id > ?1 AND raw_data IS NOT NULL ORDER BY id LIMIT ?2",
+            )?;
+            let rows = stmt.query_map(params![after_id, limit as i64], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?;
+            rows.collect::<Result<_>>()?
+        };
+
+        let mut batch = RawDataBatch {
+            last_id: rows.last().map(|(id, _)| *id),
+            ..Default::default()
+        };
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = self
+                .conn
+                .prepare_cached("UPDATE poi_data SET raw_data = ?1 WHERE id = ?2")?;
+            for (id, value) in &rows {
+                let Some(raw) = crate::raw_data::decode(value.into()) else {
+                    continue;
+                };
+                let encoded = encode(&raw);
+                batch.bytes_before += crate::raw_data::stored_len(value) as u64;
+                batch.bytes_after += crate::raw_data::stored_len(&encoded) as u64;
+                stmt.execute(params![encoded, id])?;
+                batch.rows += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(batch)
+    }
+
+    /// 批量回填类别，updates 为 (id, category, category_id)
+    pub fn update_poi_categories(&self, updates: &[(i64, String, String)]) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut count = 0;
+        {
+            let mut stmt = self
+                .conn
+                .prepare_cached("UPDATE poi_data SET category = ?1, category_id = ?2 WHERE id = ?3")?;
+            for (id, category, category_id) in updates {
+                count += stmt.execute(params![category, category_id, id])?;
+            }
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
     pub fn mark_key_exhausted(&self, key_id: i64) -> Result<()> {
         self.conn.execute(
             "UPDATE api_keys SET quota_exhausted = 1 WHERE id = ?1",
@@ -329,6 +1185,30 @@ impl Database {
         Ok(results)
     }
 
+    /// 逐行遍历 POI 数据（不整体加载到内存），支持平台过滤，用于流式导出
+    pub fn with_poi_rows<T>(
+        &self,
+        platform: Option<&str>,
+        f: impl FnOnce(&mut dyn Iterator<Item = Result<ExportPOI>>) -> T,
+    ) -> Result<T> {
+        let sql = "SELECT id, name, lon, lat, address, phone, category, platform, region_code FROM poi_data WHERE ?1 IS NULL OR platform = ?1 ORDER BY id";
+        let mut stmt = self.conn.prepare(sql)?;
+        let mut rows = stmt.query_map(params![platform], |row| {
+            Ok(ExportPOI {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                lon: row.get(2)?,
+                lat: row.get(3)?,
+                address: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                phone: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+                category: row.get::<_, Option<String>>(6)?.unwrap_or_default(),
+                platform: row.get(7)?,
+                region_code: row.get::<_, Option<String>>(8)?.unwrap_or_default(),
+            })
+        })?;
+        Ok(f(&mut rows))
+    }
+
     /// 修复缺失的 region_code：根据地址内容更新
     pub fn fix_region_codes(&self) -> Result<(i64, i64)> {
         // 获取修复前的空 region_code 数量
@@ -395,6 +1275,32 @@ impl Database {
         Ok(results)
     }
 
+    /// 按区划、平台、类别统计指定区划内的 POI 数量
+    pub fn count_poi_by_region_platform_category(
+        &self,
+        codes: &[String],
+    ) -> Result<Vec<(String, String, String, i64)>> {
+        if codes.is_empty() {
+            return Ok(Vec::new());
+        }
+        let placeholders: Vec<String> = codes.iter().map(|_| "?".to_string()).collect();
+        let sql = format!(
+            "SELECT region_code, platform, COALESCE(category_id, ''), COUNT(*) FROM poi_data WHERE region_code IN ({}) GROUP BY region_code, platform, category_id",
+            placeholders.join(",")
+        );
+        let params: Vec<&dyn rusqlite::ToSql> =
+            codes.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params.as_slice(), |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?;
+        let mut results = Vec::new();
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+
     /// 根据 region_code 列表删除 POI 数据
     pub fn delete_poi_by_region_codes(&self, codes: &[String]) -> Result<usize> {
         if codes.is_empty() {
@@ -411,6 +1317,370 @@ impl Database {
         Ok(count)
     }
 
+    /// 读取未过期的分页响应缓存，返回 (解析结果 JSON, 是否还有更多)
+    pub fn get_cached_response(
+        &self,
+        key: &ResponseCacheKey,
+        ttl_secs: i64,
+    ) -> Result<Option<(String, bool)>> {
+        let min_created = chrono::Utc::now().timestamp() - ttl_secs;
+        let result = self.conn.query_row(
+            "SELECT payload, has_more FROM response_cache WHERE platform = ?1 AND region_code = ?2 AND keyword = ?3 AND page = ?4 AND category_id = ?5 AND created_at >= ?6",
+            params![
+                key.platform,
+                key.region_code,
+                key.keyword,
+                key.page as i64,
+                key.category_id,
+                min_created
+            ],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? == 1)),
+        );
+        match result {
+            Ok(cached) => Ok(Some(cached)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 写入分页响应缓存
+    pub fn put_cached_response(
+        &self,
+        key: &ResponseCacheKey,
+        payload: &str,
+        has_more: bool,
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO response_cache (platform, region_code, keyword, page, category_id, payload, has_more, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                key.platform,
+                key.region_code,
+                key.keyword,
+                key.page as i64,
+                key.category_id,
+                payload,
+                has_more as i64,
+                chrono::Utc::now().timestamp()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 清理过期缓存，ttl_secs 为 0 时清空全部
+    pub fn purge_response_cache(&self, ttl_secs: i64) -> Result<usize> {
+        let min_created = chrono::Utc::now().timestamp() - ttl_secs;
+        let count = if ttl_secs <= 0 {
+            self.conn.execute("DELETE FROM response_cache", [])?
+        } else {
+            self.conn.execute(
+                "DELETE FROM response_cache WHERE created_at < ?1",
+                params![min_created],
+            )?
+        };
+        Ok(count)
+    }
+
+    /// 记录采集会话开始，launch 为启动参数 JSON
+    pub fn start_collector_session(&self, platform: &str, launch: &str, started_at: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO collector_sessions (platform, launch, status, total_collected, completed_categories, started_at, heartbeat_at) VALUES (?1, ?2, 'running', 0, '[]', ?3, ?3)",
+            params![platform, launch, started_at],
+        )?;
+        Ok(())
+    }
+
+    /// 写入采集进度心跳
+    pub fn heartbeat_collector_session(
+        &self,
+        platform: &str,
+        status: &str,
+        total_collected: i64,
+        current_category_id: &str,
+        completed_categories: &[String],
+    ) -> Result<()> {
+        let completed = serde_json::to_string(completed_categories).unwrap_or_default();
+        self.conn.execute(
+            "UPDATE collector_sessions SET status = ?1, total_collected = ?2, current_category_id = ?3, completed_categories = ?4, heartbeat_at = ?5 WHERE platform = ?6",
+            params![
+                status,
+                total_collected,
+                current_category_id,
+                completed,
+                chrono::Local::now().to_rfc3339(),
+                platform
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 获取所有未结束的采集会话
+    pub fn get_collector_sessions(&self) -> Result<Vec<CollectorSession>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT platform, launch, status, total_collected, current_category_id, completed_categories, started_at, heartbeat_at FROM collector_sessions ORDER BY heartbeat_at DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let completed: Option<String> = row.get(5)?;
+            Ok(CollectorSession {
+                platform: row.get(0)?,
+                launch: row.get(1)?,
+                status: row.get(2)?,
+                total_collected: row.get(3)?,
+                current_category_id: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                completed_categories: completed
+                    .and_then(|c| serde_json::from_str(&c).ok())
+                    .unwrap_or_default(),
+                started_at: row.get(6)?,
+                heartbeat_at: row.get(7)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// 删除采集会话（正常完成或放弃恢复）
+    pub fn delete_collector_session(&self, platform: &str) -> Result<bool> {
+        let count = self.conn.execute(
+            "DELETE FROM collector_sessions WHERE platform = ?1",
+            params![platform],
+        )?;
+        Ok(count > 0)
+    }
+
+    /// 将组合过滤条件转换为 WHERE 子句与参数
+    fn filter_clause(filter: &PoiFilter) -> (String, Vec<String>) {
+        let mut conditions = Vec::new();
+        let mut values: Vec<String> = Vec::new();
+
+        let mut push_in = |column: &str, items: &Option<Vec<String>>| {
+            if let Some(items) = items.as_ref().filter(|v| !v.is_empty()) {
+                let placeholders: Vec<String> = items.iter().map(|_| "?".to_string()).collect();
+                conditions.push(format!("{} IN ({})", column, placeholders.join(",")));
+                values.extend(items.iter().cloned());
+            }
+        };
+        push_in("platform", &filter.platforms);
+        push_in("category_id", &filter.category_ids);
+        push_in("region_code", &filter.region_codes);
+        if let Some(tags) = filter.tags.as_ref().filter(|v| !v.is_empty()) {
+            let placeholders: Vec<String> = tags.iter().map(|_| "?".to_string()).collect();
+            conditions.push(format!(
+                "id IN (SELECT pt.poi_id FROM poi_tags pt JOIN tags t ON t.id = pt.tag_id WHERE t.name IN ({}))",
+                placeholders.join(",")
+            ));
+            values.extend(tags.iter().cloned());
+        }
+
+        if let Some(start) = filter.start_time.as_ref().filter(|s| !s.is_empty()) {
+            conditions.push("created_at >= ?".to_string());
+            values.push(start.clone());
+        }
+        if let Some(end) = filter.end_time.as_ref().filter(|s| !s.is_empty()) {
+            conditions.push("created_at <= ?".to_string());
+            values.push(end.clone());
+        }
+        if let Some(freshness) = filter.freshness {
+            let op = match freshness {
+                Freshness::Fresh => ">=",
+                Freshness::Stale => "<",
+            };
+            conditions.push(format!("COALESCE(last_verified_at, created_at) {} ?", op));
+            values.push(stale_cutoff(filter.stale_days.unwrap_or(365)));
+        }
+
+        (conditions.join(" AND "), values)
+    }
+
+    /// 统计满足组合条件的 POI 数量
+    pub fn count_poi_filtered(&self, filter: &PoiFilter) -> Result<usize> {
+        let (clause, values) = Self::filter_clause(filter);
+        let sql = if clause.is_empty() {
+            "SELECT COUNT(*) FROM poi_data".to_string()
+        } else {
+            format!("SELECT COUNT(*) FROM poi_data WHERE {}", clause)
+        };
+        let params: Vec<&dyn rusqlite::ToSql> =
+            values.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+        let count: i64 = self
+            .conn
+            .query_row(&sql, params.as_slice(), |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// 按组合条件删除 POI 数据，条件为空时不删除任何数据
+    pub fn delete_poi_filtered(&self, filter: &PoiFilter) -> Result<usize> {
+        let (clause, values) = Self::filter_clause(filter);
+        if clause.is_empty() {
+            return Ok(0);
+        }
+        let sql = format!("DELETE FROM poi_data WHERE {}", clause);
+        let params: Vec<&dyn rusqlite::ToSql> =
+            values.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+        let count = self.conn.execute(&sql, params.as_slice())?;
+        Ok(count)
+    }
+
+    /// 按过滤条件将当前 POI 复制为数据快照，返回快照 ID
+    pub fn create_poi_snapshot(&self, name: &str, filter: &PoiFilter) -> Result<i64> {
+        let filter_json = serde_json::to_string(filter)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        let (clause, values) = Self::filter_clause(filter);
+        let where_sql = if clause.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", clause)
+        };
+        let params: Vec<&dyn rusqlite::ToSql> =
+            values.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO poi_snapshots (name, filter) VALUES (?1, ?2)",
+            params![name, filter_json],
+        )?;
+        let id = tx.last_insert_rowid();
+        let count = tx.execute(
+            &format!(
+                "INSERT INTO poi_snapshot_items
+                 (snapshot_id, poi_id, platform, name, lon, lat, address, phone, category, region_code)
+                 SELECT {}, id, platform, name, lon, lat, address, phone, category, region_code
+                 FROM poi_data {}",
+                id, where_sql
+            ),
+            params.as_slice(),
+        )?;
+        tx.execute(
+            "UPDATE poi_snapshots SET poi_count = ?1 WHERE id = ?2",
+            params![count as i64, id],
+        )?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    fn row_to_snapshot(row: &rusqlite::Row) -> Result<PoiSnapshot> {
+        let filter: String = row.get(2)?;
+        Ok(PoiSnapshot {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            filter: serde_json::from_str(&filter).unwrap_or_default(),
+            poi_count: row.get(3)?,
+            created_at: row.get(4)?,
+        })
+    }
+
+    pub fn list_poi_snapshots(&self) -> Result<Vec<PoiSnapshot>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, filter, poi_count, created_at FROM poi_snapshots ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map([], Self::row_to_snapshot)?;
+        rows.collect()
+    }
+
+    pub fn get_poi_snapshot(&self, id: i64) -> Result<Option<PoiSnapshot>> {
+        self.conn
+            .query_row(
+                "SELECT id, name, filter, poi_count, created_at FROM poi_snapshots WHERE id = ?1",
+                params![id],
+                Self::row_to_snapshot,
+            )
+            .optional()
+    }
+
+    pub fn delete_poi_snapshot(&self, id: i64) -> Result<bool> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM poi_snapshot_items WHERE snapshot_id = ?1", params![id])?;
+        let count = tx.execute("DELETE FROM poi_snapshots WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(count > 0)
+    }
+
+    /// 读取快照中的全部 POI，id 为快照时的 POI ID
+    pub fn get_poi_snapshot_items(&self, id: i64) -> Result<Vec<ExportPOI>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT poi_id, name, lon, lat, address, phone, category, platform, region_code
+             FROM poi_snapshot_items WHERE snapshot_id = ?1",
+        )?;
+        let rows = stmt.query_map(params![id], Self::row_to_export_poi)?;
+        rows.collect()
+    }
+
+    /// 按 ID 读取 POI，不存在的 ID 忽略
+    pub fn get_poi_by_ids(&self, ids: &[i64]) -> Result<Vec<ExportPOI>> {
+        let mut pois = Vec::with_capacity(ids.len());
+        // 分批查询，避免超出 SQLite 单条语句的参数上限
+        for chunk in ids.chunks(500) {
+            let placeholders: Vec<String> = chunk.iter().map(|_| "?".to_string()).collect();
+            let sql = format!(
+                "SELECT id, name, lon, lat, address, phone, category, platform, region_code
+                 FROM poi_data WHERE id IN ({})",
+                placeholders.join(",")
+            );
+            let params: Vec<&dyn rusqlite::ToSql> =
+                chunk.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+            let mut stmt = self.conn.prepare(&sql)?;
+            let rows = stmt.query_map(params.as_slice(), Self::row_to_export_poi)?;
+            for row in rows {
+                pois.push(row?);
+            }
+        }
+        Ok(pois)
+    }
+
+    /// 读取满足过滤条件的当前 POI
+    pub fn get_poi_filtered(&self, filter: &PoiFilter) -> Result<Vec<ExportPOI>> {
+        let (clause, values) = Self::filter_clause(filter);
+        let sql = format!(
+            "SELECT id, name, lon, lat, address, phone, category, platform, region_code FROM poi_data {}",
+            if clause.is_empty() {
+                String::new()
+            } else {
+                format!("WHERE {}", clause)
+            }
+        );
+        let params: Vec<&dyn rusqlite::ToSql> =
+            values.iter().map(|s| s as &dyn rusqlite::ToSql).collect();
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(params.as_slice(), Self::row_to_export_poi)?;
+        rows.collect()
+    }
+
+    fn row_to_export_poi(row: &rusqlite::Row) -> Result<ExportPOI> {
+        Ok(ExportPOI {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            lon: row.get(2)?,
+            lat: row.get(3)?,
+            address: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+            phone: row.get::<_, Option<String>>(5)?.unwrap_or_default(),
+            category: row.get::<_, Option<String>>(6)?.unwrap_or_default(),
+            platform: row.get(7)?,
+            region_code: row.get::<_, Option<String>>(8)?.unwrap_or_default(),
+        })
+    }
+
+    /// 回收已删除数据占用的空间
+    pub fn vacuum(&self) -> Result<()> {
+        self.conn.execute_batch("VACUUM")
+    }
+
+    /// 将数据库一致性快照写入新文件
+    pub fn vacuum_into(&self, dest: &std::path::Path) -> Result<()> {
+        self.conn
+            .execute("VACUUM INTO ?1", params![dest.to_string_lossy()])?;
+        Ok(())
+    }
+
+    /// 以 key 加密导出到新文件，key 为空时导出明文库（需 SQLCipher）
+    pub fn export_with_key(&self, dest: &std::path::Path, key: &str) -> Result<()> {
+        self.conn.execute(
+            "ATTACH DATABASE ?1 AS rekey KEY ?2",
+            params![dest.to_string_lossy(), key],
+        )?;
+        let result = self
+            .conn
+            .query_row("SELECT sqlcipher_export('rekey')", [], |_| Ok(()));
+        self.conn.execute("DETACH DATABASE rekey", [])?;
+        result
+    }
+
     /// 清空所有 POI 数据
     pub fn clear_all_poi(&self) -> Result<usize> {
         let count = self.conn.execute("DELETE FROM poi_data", [])?;
@@ -418,6 +1688,145 @@ impl Database {
     }
 }
 
+/// 一批 raw_data 转换的结果
+#[derive(Debug, Clone, Default)]
+pub struct RawDataBatch {
+    /// 本批最后一条记录的 ID，没有记录时为空
+    pub last_id: Option<i64>,
+    pub rows: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// POI 组合过滤条件，各条件之间为 AND 关系，未设置的条件不参与过滤
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct PoiFilter {
+    pub platforms: Option<Vec<String>>,
+    pub category_ids: Option<Vec<String>>,
+    pub region_codes: Option<Vec<String>>,
+    /// 入库时间下限（UTC），格式 YYYY-MM-DD HH:MM:SS
+    pub start_time: Option<String>,
+    /// 入库时间上限（UTC），格式 YYYY-MM-DD HH:MM:SS
+    pub end_time: Option<String>,
+    /// 按新鲜度过滤
+    pub freshness: Option<Freshness>,
+    /// 判定过期的天数，缺省使用新鲜度设置
+    pub stale_days: Option<u32>,
+    /// 带有任一标签的 POI
+    pub tags: Option<Vec<String>>,
+}
+
+impl PoiFilter {
+    /// 按新鲜度过滤且未指定过期天数时，取新鲜度设置中的天数
+    pub fn resolve_stale_days(&mut self) {
+        if self.freshness.is_some() && self.stale_days.is_none() {
+            self.stale_days = crate::config::get_freshness_settings()
+                .map(|s| s.stale_days)
+                .ok();
+        }
+    }
+}
+
+/// POI 新鲜度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Freshness {
+    /// 在过期天数内复核过
+    Fresh,
+    /// 超过过期天数未复核
+    Stale,
+}
+
+/// 过期判定的时间界限（UTC），早于该时间未复核的 POI 视为过期
+pub fn stale_cutoff(stale_days: u32) -> String {
+    (chrono::Utc::now() - chrono::Duration::days(stale_days as i64))
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// POI 数据快照
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PoiSnapshot {
+    pub id: i64,
+    pub name: String,
+    /// 快照范围（地区、类别、平台）
+    pub filter: PoiFilter,
+    pub poi_count: i64,
+    pub created_at: Option<String>,
+}
+
+/// 采集分页响应缓存键，类别参与解析结果，因此一并作为键的一部分
+#[derive(Debug, Clone)]
+pub struct ResponseCacheKey<'a> {
+    pub platform: &'a str,
+    pub region_code: &'a str,
+    pub keyword: &'a str,
+    pub page: usize,
+    pub category_id: &'a str,
+}
+
+/// 搜索别名，如「一小」对应「第一小学」
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PoiAlias {
+    pub id: i64,
+    pub alias: String,
+    pub target: String,
+}
+
+/// POI 标签，如「已实地核实」「重点对象」
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PoiTag {
+    pub id: i64,
+    pub name: String,
+    pub color: Option<String>,
+    pub created_at: Option<String>,
+    /// 带有该标签的 POI 数量
+    pub poi_count: i64,
+}
+
+/// POI 上的一个标签及打标时的备注
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PoiTagLink {
+    pub name: String,
+    pub color: Option<String>,
+    pub note: Option<String>,
+}
+
+/// 附近查询结果
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct NearbyPOI {
+    #[serde(flatten)]
+    pub poi: POI,
+    pub distance_m: f64,
+}
+
+/// 网格聚合单元，center 为格内 POI 的平均位置
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GridCell {
+    pub min_lon: f64,
+    pub min_lat: f64,
+    pub count: i64,
+    pub center_lon: f64,
+    pub center_lat: f64,
+}
+
+/// 持久化的采集会话，用于崩溃后恢复
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CollectorSession {
+    pub platform: String,
+    /// 启动参数 JSON
+    #[serde(skip)]
+    pub launch: String,
+    /// 最后一次心跳时的状态，running 表示未正常结束（如断电、崩溃）
+    pub status: String,
+    pub total_collected: i64,
+    pub current_category_id: String,
+    pub completed_categories: Vec<String>,
+    pub started_at: Option<String>,
+    pub heartbeat_at: Option<String>,
+}
+
 /// 导出用的 POI 结构体（包含更多字段）
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ExportPOI {
@@ -0,0 +1,159 @@
+//! POI 数据库加密
+//!
+//! 以 `sqlcipher` 特性构建时，poi_data.db 可设置密码加密。密码只保存在内存中：
+//! 启动时数据库处于锁定状态，需通过 unlock_database 输入密码后才能读写。
+
+use crate::commands::{rekey_poi_db, reopen_poi_db, with_poi_db};
+use crate::error::{AppError, CmdResult, ErrorCode as AppErrorCode};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use rusqlite::{Connection, ErrorCode};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 数据库未解锁时的错误信息
+pub const LOCKED_MESSAGE: &str = "数据库已加密，请先输入密码解锁";
+
+/// 密码最小长度
+const MIN_PASSWORD_LEN: usize = 6;
+
+static KEY: Lazy<Mutex<Option<String>>> = Lazy::new(|| Mutex::new(None));
+static LOCKED: AtomicBool = AtomicBool::new(false);
+
+pub fn current_key() -> Option<String> {
+    KEY.lock().clone()
+}
+
+pub fn set_key(key: Option<String>) {
+    *KEY.lock() = key;
+}
+
+pub fn is_locked() -> bool {
+    LOCKED.load(Ordering::SeqCst)
+}
+
+pub fn set_locked(locked: bool) {
+    LOCKED.store(locked, Ordering::SeqCst);
+}
+
+/// 数据库未解锁时返回的错误
+pub fn locked_error() -> AppError {
+    AppError::new(AppErrorCode::Locked, LOCKED_MESSAGE)
+}
+
+/// 打开加密库未提供密码或密码错误时，SQLite 报告 NotADatabase
+pub fn is_not_a_database(error: &rusqlite::Error) -> bool {
+    error.sqlite_error_code() == Some(ErrorCode::NotADatabase)
+}
+
+/// 当前构建是否链接了 SQLCipher
+fn cipher_supported() -> bool {
+    Connection::open_in_memory()
+        .and_then(|conn| conn.query_row("PRAGMA cipher_version", [], |row| row.get::<_, String>(0)))
+        .is_ok()
+}
+
+/// 数据库加密状态
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseEncryption {
+    /// 当前构建是否支持加密
+    pub supported: bool,
+    pub encrypted: bool,
+    /// 已加密但尚未输入密码
+    pub locked: bool,
+}
+
+fn status() -> DatabaseEncryption {
+    // 确保数据库已打开，锁定状态在首次打开时确定
+    let _ = with_poi_db(|_| Ok(()));
+    let locked = is_locked();
+    DatabaseEncryption {
+        supported: cipher_supported(),
+        encrypted: locked || KEY.lock().is_some(),
+        locked,
+    }
+}
+
+fn check_new_password(password: &str) -> CmdResult<()> {
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(AppError::invalid(format!(
+            "密码至少需要 {} 个字符",
+            MIN_PASSWORD_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// 校验输入的密码与当前密码一致
+fn check_current_password(password: &str) -> CmdResult<()> {
+    if is_locked() {
+        return Err(locked_error());
+    }
+    match KEY.lock().as_deref() {
+        Some(key) if key == password => Ok(()),
+        Some(_) => Err(AppError::invalid("密码错误")),
+        None => Err(AppError::invalid("数据库未加密")),
+    }
+}
+
+#[tauri::command]
+pub fn get_database_encryption() -> CmdResult<DatabaseEncryption> {
+    Ok(status())
+}
+
+/// 输入密码解锁数据库
+#[tauri::command]
+pub fn unlock_database(password: String) -> CmdResult<DatabaseEncryption> {
+    if !status().locked {
+        return Ok(status());
+    }
+    set_key(Some(password));
+    if !reopen_poi_db()? {
+        set_key(None);
+        return Err(AppError::invalid("密码错误"));
+    }
+    log::info!("POI 数据库已解锁");
+    Ok(status())
+}
+
+/// 设置密码：将当前明文库迁移为加密库
+#[tauri::command]
+pub fn encrypt_database(password: String) -> CmdResult<DatabaseEncryption> {
+    let current = status();
+    if !current.supported {
+        return Err(AppError::invalid(
+            "当前版本未启用 SQLCipher，不支持数据库加密",
+        ));
+    }
+    if current.encrypted {
+        return Err(AppError::conflict(
+            "数据库已加密，如需更换密码请使用修改密码",
+        ));
+    }
+    check_new_password(&password)?;
+    rekey_poi_db(Some(password))?;
+    log::info!("POI 数据库已迁移为加密库");
+    Ok(status())
+}
+
+/// 修改数据库密码
+#[tauri::command]
+pub fn change_database_password(
+    old_password: String,
+    new_password: String,
+) -> CmdResult<DatabaseEncryption> {
+    check_current_password(&old_password)?;
+    check_new_password(&new_password)?;
+    rekey_poi_db(Some(new_password))?;
+    log::info!("POI 数据库密码已修改");
+    Ok(status())
+}
+
+/// 取消加密，将数据库还原为明文库
+#[tauri::command]
+pub fn decrypt_database(password: String) -> CmdResult<DatabaseEncryption> {
+    check_current_password(&password)?;
+    rekey_poi_db(None)?;
+    log::info!("POI 数据库已还原为明文库");
+    Ok(status())
+}
@@ -0,0 +1,234 @@
+//! DXF (CAD) 导出
+//!
+//! 生成 R12 (AC1009) 格式的 ASCII DXF：每个 POI 输出一个 POINT 与一个名称 TEXT，
+//! 按类别分图层。非 ASCII 字符使用 \U+XXXX 转义，兼容各版本 CAD 软件。
+
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+/// CGCS2000 椭球长半轴
+const CGCS2000_A: f64 = 6378137.0;
+/// CGCS2000 椭球扁率
+const CGCS2000_F: f64 = 1.0 / 298.257222101;
+/// 高斯-克吕格投影东偏移（米）
+const FALSE_EASTING: f64 = 500000.0;
+
+/// 导出坐标的投影方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// 不投影，直接输出经纬度
+    Geographic,
+    /// Web 墨卡托 (EPSG:3857)
+    WebMercator,
+    /// 高斯-克吕格 3 度带 (CGCS2000)，不加带号
+    GaussKruger { central_meridian: f64 },
+}
+
+impl Projection {
+    /// 解析投影名称，高斯-克吕格投影按数据中心经度选择 3 度带中央经线
+    pub fn parse(name: Option<&str>, center_lon: f64) -> Result<Self, String> {
+        match name.unwrap_or("wgs84") {
+            "wgs84" => Ok(Self::Geographic),
+            "web_mercator" => Ok(Self::WebMercator),
+            "gauss_kruger" => Ok(Self::GaussKruger {
+                central_meridian: (center_lon / 3.0).round() * 3.0,
+            }),
+            other => Err(format!("不支持的投影方式: {}", other)),
+        }
+    }
+
+    /// 坐标系说明，写入导出的来源声明
+    pub fn label(&self) -> String {
+        match *self {
+            Self::Geographic => "WGS84 经纬度 (EPSG:4326)".to_string(),
+            Self::WebMercator => "Web 墨卡托 (EPSG:3857)".to_string(),
+            Self::GaussKruger { central_meridian } => {
+                format!("CGCS2000 高斯-克吕格 3 度带，中央经线 {}°", central_meridian)
+            }
+        }
+    }
+
+    /// WGS84 经纬度投影为平面坐标 (x 东向, y 北向)
+    pub fn project(&self, lon: f64, lat: f64) -> (f64, f64) {
+        match *self {
+            Self::Geographic => (lon, lat),
+            Self::WebMercator => {
+                let x = CGCS2000_A * lon.to_radians();
+                let y = CGCS2000_A * (PI / 4.0 + lat.to_radians() / 2.0).tan().ln();
+                (x, y)
+            }
+            Self::GaussKruger { central_meridian } => {
+                gauss_kruger(lon, lat, central_meridian)
+            }
+        }
+    }
+
+    /// 名称文字高度，与坐标单位匹配
+    fn text_height(&self) -> f64 {
+        match self {
+            Self::Geographic => 0.0001,
+            _ => 5.0,
+        }
+    }
+}
+
+/// 高斯-克吕格正算
+fn gauss_kruger(lon: f64, lat: f64, central_meridian: f64) -> (f64, f64) {
+    let e2 = CGCS2000_F * (2.0 - CGCS2000_F);
+    let e4 = e2 * e2;
+    let e6 = e4 * e2;
+    let ep2 = e2 / (1.0 - e2);
+
+    let phi = lat.to_radians();
+    let (sin, cos) = phi.sin_cos();
+    let n = CGCS2000_A / (1.0 - e2 * sin * sin).sqrt();
+    let t = phi.tan().powi(2);
+    let c = ep2 * cos * cos;
+    let a = (lon - central_meridian).to_radians() * cos;
+
+    // 子午线弧长
+    let m = CGCS2000_A
+        * ((1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0) * phi
+            - (3.0 * e2 / 8.0 + 3.0 * e4 / 32.0 + 45.0 * e6 / 1024.0) * (2.0 * phi).sin()
+            + (15.0 * e4 / 256.0 + 45.0 * e6 / 1024.0) * (4.0 * phi).sin()
+            - (35.0 * e6 / 3072.0) * (6.0 * phi).sin());
+
+    let x = n
+        * (a + (1.0 - t + c) * a.powi(3) / 6.0
+            + (5.0 - 18.0 * t + t * t + 72.0 * c - 58.0 * ep2) * a.powi(5) / 120.0);
+    let y = m
+        + n * phi.tan()
+            * (a * a / 2.0
+                + (5.0 - t + 9.0 * c + 4.0 * c * c) * a.powi(4) / 24.0
+                + (61.0 - 58.0 * t + t * t + 600.0 * c - 330.0 * ep2) * a.powi(6) / 720.0);
+
+    (x + FALSE_EASTING, y)
+}
+
+/// 导出到 DXF 的单个点
+pub struct DxfPoint<'a> {
+    pub name: &'a str,
+    pub layer: &'a str,
+    pub lon: f64,
+    pub lat: f64,
+}
+
+/// 非 ASCII 字符转为 \U+XXXX，并去掉换行
+fn escape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\r' | '\n' => out.push(' '),
+            c if c.is_ascii() => out.push(c),
+            c if (c as u32) <= 0xFFFF => out.push_str(&format!("\\U+{:04X}", c as u32)),
+            // 超出基本平面的字符 CAD 无法显示
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+/// 图层名：去掉 DXF 图层名中不允许的字符
+fn layer_name(category: &str) -> String {
+    let cleaned: String = category
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | '/' | '\\' | '"' | ':' | ';' | '?' | '*' | '|' | ',' | '=' | '`' => '_',
+            c => c,
+        })
+        .collect();
+    if cleaned.is_empty() {
+        "POI".to_string()
+    } else {
+        escape_text(&cleaned)
+    }
+}
+
+fn push_pair(out: &mut String, code: u32, value: &str) {
+    out.push_str(&format!("{:>3}\n{}\n", code, value));
+}
+
+/// 生成 DXF 文本
+pub fn write_dxf(points: &[DxfPoint], projection: Projection) -> String {
+    // 图层按名称排序，颜色在 1-7 号标准色中循环
+    let layers: BTreeMap<String, usize> = {
+        let mut names: Vec<String> = points.iter().map(|p| layer_name(p.layer)).collect();
+        names.sort();
+        names.dedup();
+        names.into_iter().enumerate().map(|(i, n)| (n, i % 7 + 1)).collect()
+    };
+
+    let mut out = String::new();
+    push_pair(&mut out, 0, "SECTION");
+    push_pair(&mut out, 2, "HEADER");
+    push_pair(&mut out, 9, "$ACADVER");
+    push_pair(&mut out, 1, "AC1009");
+    push_pair(&mut out, 0, "ENDSEC");
+
+    push_pair(&mut out, 0, "SECTION");
+    push_pair(&mut out, 2, "TABLES");
+    push_pair(&mut out, 0, "TABLE");
+    push_pair(&mut out, 2, "LAYER");
+    push_pair(&mut out, 70, &layers.len().to_string());
+    for (name, color) in &layers {
+        push_pair(&mut out, 0, "LAYER");
+        push_pair(&mut out, 2, name);
+        push_pair(&mut out, 70, "0");
+        push_pair(&mut out, 62, &color.to_string());
+        push_pair(&mut out, 6, "CONTINUOUS");
+    }
+    push_pair(&mut out, 0, "ENDTAB");
+    push_pair(&mut out, 0, "ENDSEC");
+
+    let height = projection.text_height();
+    push_pair(&mut out, 0, "SECTION");
+    push_pair(&mut out, 2, "ENTITIES");
+    for point in points {
+        let layer = layer_name(point.layer);
+        let (x, y) = projection.project(point.lon, point.lat);
+
+        push_pair(&mut out, 0, "POINT");
+        push_pair(&mut out, 8, &layer);
+        push_pair(&mut out, 10, &x.to_string());
+        push_pair(&mut out, 20, &y.to_string());
+        push_pair(&mut out, 30, "0.0");
+
+        // 名称标注在点的右上方
+        push_pair(&mut out, 0, "TEXT");
+        push_pair(&mut out, 8, &layer);
+        push_pair(&mut out, 10, &(x + height * 0.5).to_string());
+        push_pair(&mut out, 20, &(y + height * 0.5).to_string());
+        push_pair(&mut out, 30, "0.0");
+        push_pair(&mut out, 40, &height.to_string());
+        push_pair(&mut out, 1, &escape_text(point.name));
+    }
+    push_pair(&mut out, 0, "ENDSEC");
+    push_pair(&mut out, 0, "EOF");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projects_to_plane() {
+        let (x, y) = Projection::WebMercator.project(180.0, 0.0);
+        assert!((x - 20037508.34).abs() < 0.01);
+        assert!(y.abs() < 1e-6);
+
+        let gk = Projection::parse(Some("gauss_kruger"), 117.4).unwrap();
+        assert_eq!(gk, Projection::GaussKruger { central_meridian: 117.0 });
+        let (x, y) = gk.project(117.0, 30.0);
+        assert!((x - 500000.0).abs() < 1e-6);
+        assert!((y - 3320113.4).abs() < 1.0);
+    }
+
+    #[test]
+    fn escapes_non_ascii() {
+        assert_eq!(escape_text("A店\n1"), "A\\U+5E97 1");
+        assert_eq!(layer_name(" 餐饮/美食 "), "\\U+9910\\U+996E_\\U+7F8E\\U+98DF");
+        assert_eq!(layer_name(""), "POI");
+    }
+}
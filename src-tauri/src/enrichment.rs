@@ -0,0 +1,168 @@
+//! POI 详情补全
+//!
+//! 检索结果只有名称、地址、电话，按 raw_data 中的平台 id（百度 uid、高德 id）批量调用详情接口，
+//! 将营业时间、标签、价格、评分、照片写入独立字段，并把详情原始字段合并回 raw_data。
+//! 详情请求计入当日请求预算，查不到详情的 POI 同样记录补全时间，避免重复消耗额度。
+
+use serde::Serialize;
+use serde_json::Value;
+use std::thread;
+use std::time::Duration;
+
+use crate::budget::{self, BudgetKind, Consumption};
+use crate::collectors::{Collector, PoiDetail, DETAIL_BATCH};
+use crate::commands::{create_collector, select_api_key, with_poi_db};
+use crate::error::{AppError, CmdResult};
+
+/// 单次补全缺省处理的 POI 数
+const DEFAULT_LIMIT: usize = 200;
+
+/// 补全结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EnrichResult {
+    /// 本次处理的 POI 数
+    pub total: usize,
+    /// 取得详情并写入的数量
+    pub enriched: usize,
+    /// 详情接口查不到的数量
+    pub not_found: usize,
+    /// 请求失败提前结束时的原因
+    pub error_message: Option<String>,
+}
+
+/// 将详情合并进原始响应，raw_data 无法解析时只保留详情
+fn merge_detail(raw: &str, detail: &PoiDetail) -> String {
+    let mut value: Value =
+        serde_json::from_str(raw).unwrap_or_else(|_| Value::Object(Default::default()));
+    if let Value::Object(map) = &mut value {
+        map.extend(detail.extra.clone());
+    }
+    value.to_string()
+}
+
+/// 调用平台详情接口补全 POI，ids 为空时处理尚未补全的前 limit 条
+#[tauri::command]
+pub async fn enrich_poi_details(
+    platform: String,
+    ids: Option<Vec<i64>>,
+    limit: Option<usize>,
+) -> CmdResult<EnrichResult> {
+    let name = crate::provenance::platform_name(&platform).to_string();
+    let unsupported = || AppError::invalid(format!("{} 不支持详情补全", name));
+    let id_key = create_collector(&platform, String::new())
+        .and_then(|c| c.detail_id_key())
+        .ok_or_else(unsupported)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    let pois = with_poi_db(|db| {
+        db.get_pois_to_enrich(&platform, id_key, ids.as_deref(), limit)
+            .map_err(|e| format!("读取待补全数据失败: {}", e))
+    })?;
+    if pois.is_empty() {
+        return Err(AppError::invalid(format!(
+            "没有需要补全详情的{} POI（需保留原始响应）",
+            name
+        )));
+    }
+    let (api_key, (delay_ms, _)) = select_api_key(&platform)?;
+    if api_key.is_empty() {
+        return Err(AppError::invalid(format!("请先配置{} API Key", name)));
+    }
+
+    let collector = create_collector(&platform, api_key).ok_or_else(unsupported)?;
+    tokio::task::spawn_blocking(move || {
+        run_enrichment(
+            collector.as_ref(),
+            Duration::from_millis(delay_ms),
+            pois,
+        )
+    })
+    .await
+    .map_err(|e| AppError::from(format!("补全详情失败: {}", e)))
+}
+
+/// 逐批查询详情并写入，pois 为 get_pois_to_enrich 的结果
+pub(crate) fn run_enrichment(
+    collector: &dyn Collector,
+    delay: Duration,
+    pois: Vec<(i64, String, String)>,
+) -> EnrichResult {
+    let platform = collector.platform();
+    let name = crate::provenance::platform_name(platform);
+    let mut result = EnrichResult {
+        total: pois.len(),
+        ..Default::default()
+    };
+    for batch in pois.chunks(DETAIL_BATCH) {
+        if budget::consume(platform, BudgetKind::Requests) == Consumption::Exhausted {
+            result.error_message = Some("今日请求预算已用尽".to_string());
+            break;
+        }
+        let poi_ids: Vec<String> = batch.iter().map(|(_, poi_id, _)| poi_id.clone()).collect();
+        let details = match collector.fetch_details(&poi_ids) {
+            Ok(details) => details,
+            Err(e) => {
+                log::warn!("{}详情补全失败: {}", name, e);
+                result.error_message = Some(e);
+                break;
+            }
+        };
+
+        let saved = with_poi_db(|db| {
+            for (id, poi_id, raw) in batch {
+                let updated = match details.iter().find(|d| &d.id == poi_id) {
+                    Some(detail) => {
+                        db.update_poi_detail(*id, Some(detail), &merge_detail(raw, detail))
+                    }
+                    None => db.update_poi_detail(*id, None, raw),
+                };
+                updated.map_err(|e| format!("保存详情失败: {}", e))?;
+            }
+            Ok(())
+        });
+        if let Err(e) = saved {
+            result.error_message = Some(e.message);
+            break;
+        }
+        let found = batch
+            .iter()
+            .filter(|(_, poi_id, _)| details.iter().any(|d| &d.id == poi_id))
+            .count();
+        result.enriched += found;
+        result.not_found += batch.len() - found;
+        thread::sleep(delay);
+    }
+
+    log::info!(
+        "{}详情补全：共 {} 条，补全 {} 条，未查到 {} 条",
+        name,
+        result.total,
+        result.enriched,
+        result.not_found
+    );
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_detail() {
+        let mut extra = serde_json::Map::new();
+        extra.insert(
+            "detail_info".to_string(),
+            serde_json::json!({"shop_hours": "10:00-22:00"}),
+        );
+        let detail = PoiDetail {
+            id: "u1".to_string(),
+            opening_hours: "10:00-22:00".to_string(),
+            extra,
+            ..Default::default()
+        };
+        let merged: Value =
+            serde_json::from_str(&merge_detail(r#"{"uid":"u1","name":"老正兴"}"#, &detail))
+                .unwrap();
+        assert_eq!(merged["name"], "老正兴");
+        assert_eq!(merged["detail_info"]["shop_hours"], "10:00-22:00");
+    }
+}
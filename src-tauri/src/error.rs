@@ -0,0 +1,128 @@
+//! 命令统一错误类型
+//!
+//! 命令返回 `{ code, message }`，前端按错误码做差异化提示。错误码在出错处显式指定，
+//! 底层模块仍返回 `Result<_, String>`，未指定错误码的信息经 `?` 转换后一律视为内部错误。
+
+use serde::Serialize;
+use std::fmt;
+
+/// 错误码
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// 参数错误
+    InvalidArgument,
+    /// 目标不存在
+    NotFound,
+    /// 状态冲突，如任务正在运行、路径被占用
+    Conflict,
+    /// 配额或每日预算用尽
+    Quota,
+    /// 网络请求失败
+    Network,
+    /// 文件读写失败
+    Io,
+    /// 数据库操作失败
+    Database,
+    /// 数据库已加密且尚未解锁
+    Locked,
+    Internal,
+}
+
+/// 命令错误
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+/// 命令返回值
+pub type CmdResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidArgument, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Conflict, message)
+    }
+
+    pub fn quota(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Quota, message)
+    }
+
+    pub fn network(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Network, message)
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+/// 供仍返回 `Result<_, String>` 的函数调用命令层辅助函数
+impl From<AppError> for String {
+    fn from(e: AppError) -> Self {
+        e.message
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        Self::new(ErrorCode::Database, format!("数据库操作失败: {}", e))
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        Self::new(ErrorCode::Io, format!("文件操作失败: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code() {
+        // 未显式指定错误码的信息不按内容猜测
+        let e: AppError = "请先停止所有瓦片下载任务".into();
+        assert_eq!(e.code, ErrorCode::Internal);
+        let e: AppError = "数据库已加密，请先输入密码解锁".to_string().into();
+        assert_eq!(e.code, ErrorCode::Internal);
+
+        let e = AppError::invalid("无效的区域边界");
+        assert_eq!(
+            serde_json::to_string(&e).unwrap(),
+            r#"{"code":"invalid_argument","message":"无效的区域边界"}"#
+        );
+    }
+}
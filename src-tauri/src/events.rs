@@ -0,0 +1,226 @@
+//! 前端事件节流与合并
+//!
+//! 采集日志与瓦片下载进度的推送频率很高，逐条 emit 会拖慢前端大屏。这里按订阅级别
+//! 缓冲事件：进度按任务只保留最新一条，日志攒成一批，由后台线程按间隔统一推送；
+//! 任务状态变化或带提示信息的进度仍立即推送。
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::config::{self, EventLevel, EventSettings};
+use crate::error::{AppError, CmdResult};
+use crate::tile_downloader::types::ProgressEvent;
+
+pub const COLLECTOR_LOG: &str = "collector-log";
+/// 批量日志事件，载荷为日志数组
+pub const COLLECTOR_LOG_BATCH: &str = "collector-log-batch";
+pub const TILE_PROGRESS: &str = "tile-download-progress";
+
+/// 推送间隔的取值范围（毫秒）
+const MIN_INTERVAL_MS: u64 = 100;
+const MAX_INTERVAL_MS: u64 = 10_000;
+/// 安静模式下每批保留的日志条数
+const QUIET_LOG_BATCH: usize = 20;
+/// 日志缓冲上限，前端长时间未取走时丢弃最早的日志
+const MAX_LOG_BUFFER: usize = 500;
+
+static HUB: Lazy<Mutex<EventHub>> = Lazy::new(|| {
+    let settings = config::get_event_settings().unwrap_or_default();
+    Mutex::new(EventHub::new(settings))
+});
+
+/// 事件缓冲区
+struct EventHub {
+    settings: EventSettings,
+    logs: Vec<String>,
+    dropped_logs: usize,
+    /// 各任务待推送的最新进度
+    progress: HashMap<String, ProgressEvent>,
+    /// 各任务最近一次推送的状态
+    last_status: HashMap<String, String>,
+}
+
+impl EventHub {
+    fn new(settings: EventSettings) -> Self {
+        Self {
+            settings,
+            logs: Vec::new(),
+            dropped_logs: 0,
+            progress: HashMap::new(),
+            last_status: HashMap::new(),
+        }
+    }
+
+    /// 缓冲一条日志，实时模式下原样返回由调用方直接推送
+    fn push_log(&mut self, message: String) -> Option<String> {
+        let cap = match self.settings.level {
+            EventLevel::Realtime => return Some(message),
+            EventLevel::Throttled => MAX_LOG_BUFFER,
+            EventLevel::Quiet => QUIET_LOG_BATCH,
+        };
+        self.logs.push(message);
+        if self.logs.len() > cap {
+            let excess = self.logs.len() - cap;
+            self.logs.drain(..excess);
+            self.dropped_logs += excess;
+        }
+        None
+    }
+
+    /// 取出待推送的日志，被丢弃的条数以一条提示放在最前
+    fn take_logs(&mut self) -> Vec<String> {
+        let mut logs = std::mem::take(&mut self.logs);
+        if self.dropped_logs > 0 {
+            logs.insert(0, format!("…省略 {} 条日志", self.dropped_logs));
+            self.dropped_logs = 0;
+        }
+        logs
+    }
+
+    /// 缓冲一条进度，返回需要立即推送的事件
+    fn push_progress(&mut self, event: ProgressEvent) -> Option<ProgressEvent> {
+        let urgent = self.settings.level == EventLevel::Realtime
+            || event.message.is_some()
+            || self.last_status.get(&event.task_id) != Some(&event.status);
+        if urgent {
+            self.progress.remove(&event.task_id);
+            if event.status == "downloading" {
+                self.last_status
+                    .insert(event.task_id.clone(), event.status.clone());
+            } else {
+                // 任务结束或暂停后不再有后续进度，清理记录
+                self.last_status.remove(&event.task_id);
+            }
+            return Some(event);
+        }
+        // 安静模式只推送状态变化
+        if self.settings.level == EventLevel::Throttled {
+            self.progress.insert(event.task_id.clone(), event);
+        }
+        None
+    }
+
+    fn take_progress(&mut self) -> Vec<ProgressEvent> {
+        self.progress.drain().map(|(_, event)| event).collect()
+    }
+}
+
+/// 推送采集日志
+pub fn emit_log(app: &AppHandle, message: impl Into<String>) {
+    if let Some(message) = HUB.lock().push_log(message.into()) {
+        let _ = app.emit(COLLECTOR_LOG, message);
+    }
+}
+
+/// 推送瓦片下载进度
+pub fn emit_tile_progress(app: &AppHandle, event: ProgressEvent) {
+    if let Some(event) = HUB.lock().push_progress(event) {
+        let _ = app.emit(TILE_PROGRESS, &event);
+    }
+}
+
+/// 将缓冲的事件全部推送出去
+fn flush(app: &AppHandle) {
+    let (logs, progress) = {
+        let mut hub = HUB.lock();
+        (hub.take_logs(), hub.take_progress())
+    };
+    if !logs.is_empty() {
+        let _ = app.emit(COLLECTOR_LOG_BATCH, logs);
+    }
+    for event in progress {
+        let _ = app.emit(TILE_PROGRESS, &event);
+    }
+}
+
+/// 启动事件推送线程，按设置的间隔推送缓冲的事件
+pub fn start_flusher(app: AppHandle) {
+    thread::spawn(move || loop {
+        let interval = HUB.lock().settings.interval_ms;
+        thread::sleep(Duration::from_millis(interval));
+        flush(&app);
+    });
+}
+
+#[tauri::command]
+pub fn get_event_settings() -> CmdResult<EventSettings> {
+    Ok(HUB.lock().settings.clone())
+}
+
+/// 修改事件订阅级别与推送间隔，立即生效
+#[tauri::command]
+pub fn set_event_settings(app: AppHandle, settings: EventSettings) -> CmdResult<EventSettings> {
+    if !(MIN_INTERVAL_MS..=MAX_INTERVAL_MS).contains(&settings.interval_ms) {
+        return Err(AppError::invalid(format!(
+            "推送间隔需在 {}~{} 毫秒之间",
+            MIN_INTERVAL_MS, MAX_INTERVAL_MS
+        )));
+    }
+    config::set_event_settings(&settings)?;
+    HUB.lock().settings = settings.clone();
+    // 切换级别前缓冲的事件按旧级别推送完
+    flush(&app);
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(completed: u64, status: &str) -> ProgressEvent {
+        ProgressEvent {
+            task_id: "t".to_string(),
+            completed,
+            failed: 0,
+            total: 100,
+            speed: 0.0,
+            current_zoom: 10,
+            status: status.to_string(),
+            message: None,
+        }
+    }
+
+    #[test]
+    fn test_progress_coalesced() {
+        let mut hub = EventHub::new(EventSettings::default());
+        assert!(hub.push_progress(progress(1, "downloading")).is_some());
+        assert!(hub.push_progress(progress(2, "downloading")).is_none());
+        assert!(hub.push_progress(progress(3, "downloading")).is_none());
+        let pending = hub.take_progress();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].completed, 3);
+
+        assert!(hub.push_progress(progress(4, "downloading")).is_none());
+        let done = hub.push_progress(progress(100, "completed")).unwrap();
+        assert_eq!(done.completed, 100);
+        assert!(hub.take_progress().is_empty());
+
+        hub.settings.level = EventLevel::Quiet;
+        assert!(hub.push_progress(progress(1, "downloading")).is_some());
+        assert!(hub.push_progress(progress(2, "downloading")).is_none());
+        assert!(hub.take_progress().is_empty());
+    }
+
+    #[test]
+    fn test_quiet_logs_capped() {
+        let mut hub = EventHub::new(EventSettings {
+            level: EventLevel::Quiet,
+            interval_ms: 500,
+        });
+        for i in 0..25 {
+            assert!(hub.push_log(format!("日志 {}", i)).is_none());
+        }
+        let logs = hub.take_logs();
+        assert_eq!(logs.len(), QUIET_LOG_BATCH + 1);
+        assert_eq!(logs[0], "…省略 5 条日志");
+        assert_eq!(logs[1], "日志 5");
+        assert!(hub.take_logs().is_empty());
+
+        hub.settings.level = EventLevel::Realtime;
+        assert_eq!(hub.push_log("即时".to_string()).as_deref(), Some("即时"));
+    }
+}
@@ -0,0 +1,71 @@
+//! GeoJSON Lines 导出
+//!
+//! 每行一个 Feature（换行分隔，无外层 FeatureCollection），边查边写，
+//! 内存占用与数据量无关，可直接交给 tippecanoe 等工具处理。
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use serde_json::json;
+
+use crate::database::ExportPOI;
+use crate::regions::{self, RegionNames};
+
+/// 将单个 POI 转换为 GeoJSON Feature
+fn feature(poi: &ExportPOI, region: &RegionNames) -> serde_json::Value {
+    json!({
+        "type": "Feature",
+        "id": poi.id,
+        "geometry": {
+            "type": "Point",
+            "coordinates": [poi.lon, poi.lat],
+        },
+        "properties": {
+            "id": poi.id,
+            "name": poi.name,
+            "address": poi.address,
+            "phone": poi.phone,
+            "category": poi.category,
+            "platform": poi.platform,
+            "region_code": poi.region_code,
+            "province": region.province,
+            "city": region.city,
+            "district": region.district,
+        },
+    })
+}
+
+/// 逐行写出 POI，ids 指定时只导出其中的记录，返回写出条数
+pub fn write_geojsonl(
+    path: &str,
+    rows: &mut dyn Iterator<Item = rusqlite::Result<ExportPOI>>,
+    ids: Option<&HashSet<i64>>,
+) -> Result<usize, String> {
+    let file = File::create(path).map_err(|e| format!("创建文件失败: {}", e))?;
+    let mut writer = BufWriter::new(file);
+    // 区划名称按代码缓存，条目数只与区县数量有关
+    let mut name_cache: HashMap<String, RegionNames> = HashMap::new();
+    let mut count = 0;
+
+    for row in rows {
+        let poi = row.map_err(|e| format!("读取 POI 失败: {}", e))?;
+        if ids.is_some_and(|ids| !ids.contains(&poi.id)) {
+            continue;
+        }
+        let region = name_cache
+            .entry(poi.region_code.clone())
+            .or_insert_with(|| regions::resolve_region_names(&poi.region_code));
+
+        serde_json::to_writer(&mut writer, &feature(&poi, region))
+            .map_err(|e| format!("写入失败: {}", e))?;
+        writer
+            .write_all(b"\n")
+            .map_err(|e| format!("写入失败: {}", e))?;
+        count += 1;
+    }
+
+    writer.flush().map_err(|e| format!("写入失败: {}", e))?;
+    Ok(count)
+}
@@ -0,0 +1,43 @@
+//! 单实例检测
+//!
+//! 两个实例同时写同一个 SQLite 数据库时会因锁冲突相互报错。启动时对数据库旁的锁文件加排他锁，
+//! 加锁失败说明已有实例在运行，提示后退出；锁随进程退出自动释放，异常退出也不会残留。
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::Path;
+use std::sync::OnceLock;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+/// 持有到进程结束的锁文件
+static INSTANCE_LOCK: OnceLock<File> = OnceLock::new();
+
+/// 尝试获取实例锁，已有实例持有时返回 false
+pub fn acquire(path: &Path) -> Result<bool, String> {
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("打开实例锁文件失败: {}", e))?;
+    match file.try_lock() {
+        Ok(()) => {
+            let _ = INSTANCE_LOCK.set(file);
+            Ok(true)
+        }
+        Err(TryLockError::WouldBlock) => Ok(false),
+        Err(TryLockError::Error(e)) => Err(format!("锁定实例锁文件失败: {}", e)),
+    }
+}
+
+/// 隐藏窗口并提示已有实例在运行，确认后退出
+pub fn exit_as_duplicate(app: &AppHandle) {
+    for window in app.webview_windows().values() {
+        let _ = window.hide();
+    }
+    app.dialog()
+        .message("POI 采集器已在运行，请切换到已打开的窗口。同时运行多个实例会导致数据库写入冲突。")
+        .kind(MessageDialogKind::Warning)
+        .title("程序已在运行")
+        .show(|_| std::process::exit(0));
+}
@@ -0,0 +1,135 @@
+//! 统一任务中心
+//!
+//! 聚合采集任务与瓦片下载任务，提供统一的数据结构与暂停/恢复/取消入口。
+//! 采集任务暂停后工作线程即退出，恢复时按数据库中持久化的采集会话跳过已完成的类别，
+//! 暂停时进行中的类别从第一个关键词重新采集，已入库的 POI 按唯一约束去重。
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::commands::{
+    get_collector_statuses, resume_unfinished_collection, stop_collector, CollectorStatus,
+};
+use crate::tile_downloader::commands as tile_commands;
+use crate::tile_downloader::types::TaskInfo;
+use crate::error::{AppError, CmdResult};
+
+/// 统一任务信息
+#[derive(Debug, Clone, Serialize)]
+pub struct Job {
+    /// 采集任务为平台标识，瓦片任务为任务 ID
+    pub id: String,
+    /// collector / tile
+    pub kind: String,
+    pub name: String,
+    /// idle / running / paused / completed / failed / cancelled
+    pub status: String,
+    pub completed: u64,
+    pub total: Option<u64>,
+    /// 进度 0.0 - 1.0
+    pub progress: Option<f64>,
+    /// 采集为条/秒，瓦片为个/秒
+    pub speed: f64,
+    pub started_at: Option<String>,
+    pub error_message: Option<String>,
+}
+
+pub(crate) fn collector_job(status: CollectorStatus) -> Job {
+    let completed = status.total_collected.max(0) as u64;
+
+    let speed = status
+        .started_at
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|start| (chrono::Local::now().fixed_offset() - start).num_milliseconds() as f64 / 1000.0)
+        .filter(|elapsed| *elapsed > 0.0 && status.status == "running")
+        .map(|elapsed| completed as f64 / elapsed)
+        .unwrap_or(0.0);
+
+    let progress = if status.status == "completed" {
+        Some(1.0)
+    } else if status.total_categories > 0 {
+        Some(status.completed_categories.len() as f64 / status.total_categories as f64)
+    } else {
+        None
+    };
+
+    let normalized = match status.status.as_str() {
+        "error" => "failed",
+        other => other,
+    };
+
+    Job {
+        id: status.platform.clone(),
+        kind: "collector".to_string(),
+        name: format!("{} 采集", status.platform),
+        status: normalized.to_string(),
+        completed,
+        total: None,
+        progress,
+        speed,
+        started_at: status.started_at,
+        error_message: status.error_message,
+    }
+}
+
+fn tile_job(task: TaskInfo) -> Job {
+    let progress = if task.total_tiles > 0 {
+        Some((task.completed_tiles + task.failed_tiles) as f64 / task.total_tiles as f64)
+    } else {
+        None
+    };
+
+    let normalized = match task.status.as_str() {
+        "downloading" => "running",
+        "pending" => "idle",
+        other => other,
+    };
+
+    Job {
+        started_at: tile_commands::tile_task_started_at(&task.id),
+        id: task.id,
+        kind: "tile".to_string(),
+        name: task.name,
+        status: normalized.to_string(),
+        completed: task.completed_tiles,
+        total: Some(task.total_tiles),
+        progress,
+        speed: task.download_speed,
+        error_message: task.error_message,
+    }
+}
+
+/// 获取所有采集与瓦片下载任务
+#[tauri::command]
+pub async fn get_all_jobs(app: AppHandle) -> CmdResult<Vec<Job>> {
+    let mut jobs: Vec<Job> = get_collector_statuses()
+        .into_values()
+        .filter(|s| s.status != "idle")
+        .map(collector_job)
+        .collect();
+    jobs.sort_by(|a, b| a.id.cmp(&b.id));
+
+    let tasks = tile_commands::get_tile_tasks(app).await?;
+    jobs.extend(tasks.into_iter().map(tile_job));
+
+    Ok(jobs)
+}
+
+/// 统一的任务控制入口，action 为 pause / resume / cancel
+#[tauri::command]
+pub async fn control_job(
+    app: AppHandle,
+    kind: String,
+    id: String,
+    action: String,
+) -> CmdResult<()> {
+    match (kind.as_str(), action.as_str()) {
+        ("collector", "pause") | ("collector", "cancel") => stop_collector(id),
+        ("collector", "resume") => resume_unfinished_collection(app, id),
+        ("tile", "pause") => tile_commands::pause_tile_download(app, id).await,
+        ("tile", "resume") => tile_commands::start_tile_download(app, id).await,
+        ("tile", "cancel") => tile_commands::cancel_tile_download(app, id).await,
+        _ => Err(AppError::invalid(format!("不支持的操作: {} {}", kind, action))),
+    }
+}
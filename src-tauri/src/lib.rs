@@ -1,15 +1,46 @@
+mod benchmark;
+mod budget;
 mod collectors;
 mod commands;
 mod config;
 mod coords;
+mod coverage;
+mod dashboard;
 mod database;
+mod db_crypto;
+mod dxf;
+mod enrichment;
+mod error;
+mod events;
+mod geojsonl;
+mod instance;
+mod jobs;
+mod masking;
+mod metrics;
+mod normalize;
+mod parquet_export;
+mod poi_matching;
+mod poi_snapshots;
+mod poi_tags;
+mod polygon_stats;
+mod power;
+mod provenance;
+mod raw_data;
 mod regions;
+mod restart;
+mod sql_export;
+mod templates;
 mod tile_downloader;
+mod verification;
+mod workspace;
 
 use commands::*;
+use tauri::Manager;
 use tile_downloader::boundaries;
 use tile_downloader::commands as tile_commands;
+use tile_downloader::snapshot;
 use tile_downloader::tile_proxy;
+use tile_downloader::url_rules;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -18,25 +49,139 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .setup(|app| {
+            // 配置文件存放在应用数据目录
+            match app.path().app_data_dir() {
+                Ok(dir) => config::init_config_dir(dir),
+                Err(e) => log::warn!("获取应用数据目录失败: {}", e),
+            }
+            // 同一数据库只允许一个实例写入
+            let lock_path = commands::poi_db_path().with_extension("lock");
+            match instance::acquire(&lock_path) {
+                Ok(true) => {}
+                Ok(false) => {
+                    instance::exit_as_duplicate(app.handle());
+                    return Ok(());
+                }
+                Err(e) => log::warn!("{}", e),
+            }
+            templates::start_scheduler(app.handle().clone());
+            events::start_flusher(app.handle().clone());
+            url_rules::start();
+            power::start();
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             // Stats
             get_stats,
+            get_freshness_settings,
+            set_freshness_settings,
+            get_osm_settings,
+            set_osm_settings,
+            dashboard::get_dashboard_summary,
             // Region (legacy)
             get_region_config,
             get_region_presets,
             set_region_by_preset,
+            add_region_preset,
+            remove_region_preset,
+            list_region_profiles,
+            save_region_profile,
+            load_region_profile,
+            delete_region_profile,
+            list_saved_areas,
+            save_area,
+            set_area_watch,
+            delete_saved_area,
+            // Collect templates
+            templates::list_collect_templates,
+            templates::save_collect_template,
+            templates::delete_collect_template,
+            templates::run_template,
+            // POI snapshots
+            poi_snapshots::create_poi_snapshot,
+            poi_snapshots::list_poi_snapshots,
+            poi_snapshots::delete_poi_snapshot,
+            poi_snapshots::compare_snapshots,
+            poi_snapshots::export_snapshot_diff,
+            // 跨平台 POI 对齐
+            poi_matching::get_poi_matches,
+            // POI 标签
+            poi_tags::list_poi_tags,
+            poi_tags::save_poi_tag,
+            poi_tags::delete_poi_tag,
+            poi_tags::tag_pois,
+            poi_tags::untag_pois,
+            poi_tags::get_poi_tags,
+            poi_tags::list_tagged_poi,
+            // 多边形范围统计
+            polygon_stats::stats_in_polygon,
+            polygon_stats::export_polygon_stats,
+            verification::start_poi_verification,
+            verification::stop_poi_verification,
+            verification::get_poi_verification,
+            verification::export_verification_report,
+            enrichment::enrich_poi_details,
+            raw_data::get_raw_data_settings,
+            raw_data::set_raw_data_settings,
+            raw_data::compact_raw_data,
+            // 休眠阻止
+            power::get_power_settings,
+            power::set_power_settings,
+            restart::get_restart_policy,
+            restart::set_restart_policy,
+            power::get_pending_completion_action,
+            power::cancel_completion_action,
+            // 数据库加密
+            db_crypto::get_database_encryption,
+            db_crypto::unlock_database,
+            db_crypto::encrypt_database,
+            db_crypto::change_database_password,
+            db_crypto::decrypt_database,
+            // Events
+            events::get_event_settings,
+            events::set_event_settings,
             // API Keys
             get_api_keys,
             add_api_key,
             delete_api_key,
+            set_api_key_qps,
+            // 每日预算
+            budget::get_daily_budgets,
+            budget::set_daily_budget,
+            // 采集速度基准测试
+            benchmark::benchmark_platform,
             // Collector
             get_categories,
             get_collector_statuses,
             start_collector,
             stop_collector,
+            set_collector_delay,
+            switch_collector_key,
+            set_collector_categories,
             reset_collector,
+            get_unfinished_collections,
+            resume_unfinished_collection,
+            discard_unfinished_collection,
+            pause_all_tasks,
+            stop_all,
+            // 任务中心
+            jobs::get_all_jobs,
+            metrics::get_metrics_prometheus,
+            jobs::control_job,
+            // 工作区迁移
+            workspace::export_workspace,
+            workspace::import_workspace,
             // Search
             search_poi,
+            list_poi_aliases,
+            set_poi_alias,
+            delete_poi_alias,
+            query_poi_bbox,
+            query_poi_nearby,
+            get_poi_ids_in_area,
+            aggregate_poi_grid,
+            rebuild_spatial_index,
             // 行政区划
             get_regions,
             get_provinces,
@@ -46,27 +191,59 @@ pub fn run() {
             // 导出
             get_all_poi_data,
             export_poi_to_file,
+            get_export_settings,
+            set_export_settings,
+            get_default_export_path,
             fix_region_codes,
             // 数据管理
             get_poi_stats_by_region,
+            coverage_compare,
+            clear_response_cache,
+            delete_poi_filtered,
+            rebuild_categories,
             delete_poi_by_regions,
             clear_all_poi,
             // 瓦片下载
             tile_commands::get_tile_platforms,
             tile_commands::calculate_tiles_count,
+            tile_commands::load_clip_geojson,
+            tile_commands::check_tile_coverage,
+            tile_commands::probe_tile_coverage,
             tile_commands::create_tile_task,
+            tile_commands::get_tile_output_settings,
+            tile_commands::set_tile_output_settings,
+            tile_commands::get_tile_network_settings,
+            tile_commands::set_tile_network_settings,
             tile_commands::get_tile_tasks,
             tile_commands::get_tile_task,
+            tile_commands::get_recent_tile_errors,
+            tile_commands::regenerate_manifest,
+            tile_commands::get_tile_cache_stats,
+            tile_commands::clear_tile_cache,
             tile_commands::start_tile_download,
             tile_commands::pause_tile_download,
             tile_commands::cancel_tile_download,
             tile_commands::delete_tile_task,
             tile_commands::set_tile_thread_count,
+            tile_commands::update_task_runtime_config,
+            tile_commands::set_tile_task_completion_action,
             tile_commands::retry_failed_tiles,
+            tile_commands::set_tile_task_list,
             tile_commands::convert_tile_file,
+            tile_commands::package_offline_map,
+            tile_commands::get_local_tile,
+            tile_commands::generate_task_thumbnail,
             tile_proxy::proxy_tile_request,
+            tile_proxy::prefetch_tiles,
+            snapshot::export_map_snapshot,
             boundaries::get_region_boundary,
             boundaries::clear_boundary_cache,
+            url_rules::get_tile_url_rules,
+            url_rules::update_tile_url_rules,
+            url_rules::reset_tile_url_rules,
+            url_rules::get_tile_url_rule_settings,
+            url_rules::set_tile_url_rule_settings,
+            url_rules::check_tile_urls,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
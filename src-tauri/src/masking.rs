@@ -0,0 +1,146 @@
+//! 导出脱敏
+//!
+//! 对外共享数据时对电话打码、对坐标做随机抖动并降低精度。抖动以每次导出随机生成的
+//! 盐值和 POI ID 计算，同一文件内结果稳定，不同导出之间无法相互对照还原。
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crate::database::ExportPOI;
+
+/// 脱敏选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MaskOptions {
+    /// 启用脱敏导出
+    #[serde(default)]
+    pub enabled: bool,
+    /// 电话打码，保留前 3 位与后 2 位
+    #[serde(default)]
+    pub mask_phone: bool,
+    /// 坐标随机抖动半径（米）
+    #[serde(default)]
+    pub jitter_m: Option<f64>,
+    /// 坐标保留的小数位数（3 位约 100 米）
+    #[serde(default)]
+    pub coord_decimals: Option<u32>,
+}
+
+/// 单次导出的脱敏器
+pub struct Masker {
+    options: MaskOptions,
+    salt: u128,
+}
+
+impl Masker {
+    /// 未启用脱敏时返回 None
+    pub fn new(options: &MaskOptions) -> Option<Self> {
+        options.enabled.then(|| Self {
+            options: options.clone(),
+            salt: uuid::Uuid::new_v4().as_u128(),
+        })
+    }
+
+    /// 脱敏策略说明，写入导出文件头
+    pub fn policy(&self) -> String {
+        let mut parts = vec!["不含原始响应数据".to_string()];
+        if self.options.mask_phone {
+            parts.push("电话已打码".to_string());
+        }
+        if let Some(radius) = self.options.jitter_m.filter(|r| *r > 0.0) {
+            parts.push(format!("坐标随机偏移 {} 米以内", radius));
+        }
+        if let Some(decimals) = self.options.coord_decimals {
+            parts.push(format!("坐标保留 {} 位小数", decimals));
+        }
+        parts.join("；")
+    }
+
+    pub fn apply(&self, poi: &mut ExportPOI) {
+        if self.options.mask_phone {
+            poi.phone = mask_phone(&poi.phone);
+        }
+        if let Some(radius) = self.options.jitter_m.filter(|r| *r > 0.0) {
+            let (lon, lat) = jitter(
+                poi.lon,
+                poi.lat,
+                radius,
+                self.unit(poi.id, 0),
+                self.unit(poi.id, 1),
+            );
+            poi.lon = lon;
+            poi.lat = lat;
+        }
+        if let Some(decimals) = self.options.coord_decimals {
+            poi.lon = round_to(poi.lon, decimals);
+            poi.lat = round_to(poi.lat, decimals);
+        }
+    }
+
+    /// 由盐值与 ID 派生的 [0, 1) 伪随机数
+    fn unit(&self, id: i64, stream: u8) -> f64 {
+        let mut hasher = DefaultHasher::new();
+        (self.salt, id, stream).hash(&mut hasher);
+        (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// 电话打码，多个号码分别处理；7 位以上保留前 3 后 2 位，较短的全部打码
+pub fn mask_phone(phone: &str) -> String {
+    phone
+        .split(';')
+        .map(|number| {
+            let chars: Vec<char> = number.trim().chars().collect();
+            if chars.len() >= 7 {
+                let mut masked: String = chars[..3].iter().collect();
+                masked.push_str(&"*".repeat(chars.len() - 5));
+                masked.extend(&chars[chars.len() - 2..]);
+                masked
+            } else {
+                "*".repeat(chars.len())
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// 在半径 radius_m 的圆内均匀偏移坐标，u1/u2 为 [0, 1) 随机数
+fn jitter(lon: f64, lat: f64, radius_m: f64, u1: f64, u2: f64) -> (f64, f64) {
+    let angle = u1 * std::f64::consts::TAU;
+    let distance = radius_m * u2.sqrt();
+    let dlat = distance * angle.cos() / 111_320.0;
+    let dlon = distance * angle.sin() / (111_320.0 * lat.to_radians().cos().max(1e-6));
+    (lon + dlon, lat + dlat)
+}
+
+fn round_to(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals.min(12) as i32);
+    (value * factor).round() / factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_phone() {
+        assert_eq!(mask_phone("13812345678"), "138******78");
+        assert_eq!(
+            mask_phone("0515-88886666;13812345678"),
+            "051********66;138******78"
+        );
+        assert_eq!(mask_phone("12345"), "*****");
+        assert_eq!(mask_phone(""), "");
+    }
+
+    #[test]
+    fn test_jitter_within_radius() {
+        let (lon, lat) = (120.0, 33.0);
+        for (u1, u2) in [(0.0, 0.99), (0.25, 0.5), (0.7, 0.999)] {
+            let (jlon, jlat) = jitter(lon, lat, 100.0, u1, u2);
+            let distance = crate::coords::haversine_distance(lon, lat, jlon, jlat);
+            assert!(distance <= 100.5, "{}", distance);
+        }
+        assert_eq!(round_to(120.123456, 3), 120.123);
+    }
+}
@@ -0,0 +1,215 @@
+//! 运行指标导出
+//!
+//! 以 Prometheus 文本格式输出采集与瓦片下载的运行指标，供监控平台通过 textfile 等方式接入。
+//! 指标均为采样时刻的瞬时值，速率取本次运行的平均速率。
+
+use std::fmt::Write;
+use tauri::AppHandle;
+
+use crate::budget;
+use crate::commands::{get_collector_statuses, with_poi_db};
+use crate::error::CmdResult;
+use crate::jobs::collector_job;
+use crate::tile_downloader::commands as tile_commands;
+
+/// 指标名前缀
+const PREFIX: &str = "poi_collector";
+
+/// Prometheus 文本格式构建器
+struct MetricsWriter {
+    out: String,
+}
+
+impl MetricsWriter {
+    fn new() -> Self {
+        Self { out: String::new() }
+    }
+
+    /// 开始一个指标族，写入 HELP 与 TYPE
+    fn family(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.out, "# HELP {}_{} {}", PREFIX, name, help);
+        let _ = writeln!(self.out, "# TYPE {}_{} {}", PREFIX, name, kind);
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) {
+        let _ = write!(self.out, "{}_{}", PREFIX, name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(k, v)| format!("{}=\"{}\"", k, escape_label(v)))
+                .collect();
+            let _ = write!(self.out, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.out, " {}", format_value(value));
+    }
+
+    fn finish(self) -> String {
+        self.out
+    }
+}
+
+/// 转义标签值中的反斜杠、双引号与换行
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// 以 Prometheus 文本格式输出当前运行指标
+#[tauri::command]
+pub async fn get_metrics_prometheus(app: AppHandle) -> CmdResult<String> {
+    let mut m = MetricsWriter::new();
+
+    let stale_days = crate::config::get_freshness_settings()?.stale_days;
+    let stats = with_poi_db(|db| {
+        db.get_stats(None, stale_days)
+            .map_err(|e| format!("获取统计失败: {}", e))
+    })?;
+    let mut by_platform: Vec<_> = stats.by_platform.into_iter().collect();
+    by_platform.sort();
+    m.family("poi_total", "gauge", "本地库中的 POI 数量");
+    for (platform, count) in &by_platform {
+        m.sample("poi_total", &[("platform", platform)], *count as f64);
+    }
+    m.family("poi_stale", "gauge", "超过过期天数未复核的 POI 数量");
+    m.sample("poi_stale", &[], stats.stale as f64);
+
+    let mut statuses: Vec<_> = get_collector_statuses().into_values().collect();
+    statuses.sort_by(|a, b| a.platform.cmp(&b.platform));
+    m.family("collector_running", "gauge", "采集器是否在运行");
+    for s in &statuses {
+        let running = if s.status == "running" { 1.0 } else { 0.0 };
+        m.sample("collector_running", &[("platform", &s.platform)], running);
+    }
+    m.family("collector_collected", "gauge", "本次采集已入库的 POI 数");
+    for s in &statuses {
+        m.sample(
+            "collector_collected",
+            &[("platform", &s.platform)],
+            s.total_collected as f64,
+        );
+    }
+    m.family("collector_rate", "gauge", "本次采集的平均速率（条/秒）");
+    for s in &statuses {
+        let speed = collector_job(s.clone()).speed;
+        m.sample("collector_rate", &[("platform", &s.platform)], speed);
+    }
+    m.family("collector_queue_length", "gauge", "待采集的类别数");
+    for s in &statuses {
+        let pending = s
+            .total_categories
+            .saturating_sub(s.completed_categories.len());
+        m.sample(
+            "collector_queue_length",
+            &[("platform", &s.platform)],
+            pending as f64,
+        );
+    }
+    m.family("collector_error", "gauge", "采集器是否因错误停止");
+    for s in &statuses {
+        let error = if s.status == "error" { 1.0 } else { 0.0 };
+        m.sample("collector_error", &[("platform", &s.platform)], error);
+    }
+
+    let tasks = tile_commands::get_tile_tasks(app).await?;
+    m.family("tile_tasks", "gauge", "各状态的瓦片下载任务数");
+    let mut task_statuses: Vec<&str> = tasks.iter().map(|t| t.status.as_str()).collect();
+    task_statuses.sort();
+    task_statuses.dedup();
+    for status in task_statuses {
+        let count = tasks.iter().filter(|t| t.status == status).count();
+        m.sample("tile_tasks", &[("status", status)], count as f64);
+    }
+    let active: Vec<_> = tasks
+        .iter()
+        .filter(|t| matches!(t.status.as_str(), "downloading" | "paused"))
+        .collect();
+    m.family(
+        "tile_task_tiles",
+        "gauge",
+        "进行中瓦片任务按状态统计的瓦片数",
+    );
+    for t in &active {
+        let pending = t
+            .total_tiles
+            .saturating_sub(t.completed_tiles + t.failed_tiles);
+        for (state, value) in [
+            ("completed", t.completed_tiles),
+            ("failed", t.failed_tiles),
+            ("pending", pending),
+        ] {
+            m.sample(
+                "tile_task_tiles",
+                &[("task", &t.id), ("name", &t.name), ("state", state)],
+                value as f64,
+            );
+        }
+    }
+    m.family("tile_download_rate", "gauge", "瓦片下载速率（个/秒）");
+    for t in &active {
+        m.sample(
+            "tile_download_rate",
+            &[("task", &t.id), ("name", &t.name)],
+            t.download_speed,
+        );
+    }
+
+    let budgets = budget::get_daily_budgets()?;
+    m.family("budget_used", "gauge", "今日已用的请求预算");
+    for b in &budgets {
+        for (kind, used) in [("requests", b.used_requests), ("tiles", b.used_tiles)] {
+            m.sample(
+                "budget_used",
+                &[("platform", &b.platform), ("kind", kind)],
+                used as f64,
+            );
+        }
+    }
+    m.family("budget_limit", "gauge", "今日预算上限，未设置上限的不输出");
+    for b in &budgets {
+        for (kind, limit) in [("requests", b.max_requests), ("tiles", b.max_tiles)] {
+            if let Some(limit) = limit {
+                m.sample(
+                    "budget_limit",
+                    &[("platform", &b.platform), ("kind", kind)],
+                    limit as f64,
+                );
+            }
+        }
+    }
+
+    Ok(m.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_writer() {
+        let mut m = MetricsWriter::new();
+        m.family("poi_total", "gauge", "POI 数量");
+        m.sample("poi_total", &[("platform", "a\"b\\c")], 12.0);
+        m.sample("poi_total", &[], 0.5);
+        m.sample("poi_total", &[("platform", "x")], f64::INFINITY);
+        assert_eq!(
+            m.finish(),
+            "# HELP poi_collector_poi_total POI 数量\n\
+             # TYPE poi_collector_poi_total gauge\n\
+             poi_collector_poi_total{platform=\"a\\\"b\\\\c\"} 12\n\
+             poi_collector_poi_total 0.5\n\
+             poi_collector_poi_total{platform=\"x\"} +Inf\n"
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! POI 名称规范化
+//!
+//! 入库前统一全角/半角、空白与括号，减少名称微差导致的重复数据；
+//! 搜索时按繁简互转与别名展开查询词。
+
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+/// 是否为中日韩文字或全角标点
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{2E80}'..='\u{9FFF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FF00}'..='\u{FFEF}'
+        | '\u{20000}'..='\u{2FA1F}')
+}
+
+/// 全角字符转半角，并统一各类括号为半角圆括号
+fn to_halfwidth(c: char) -> char {
+    let c = match c {
+        '\u{3000}' => ' ',
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        _ => c,
+    };
+    match c {
+        '[' | '【' | '〔' | '〖' | '﹙' => '(',
+        ']' | '】' | '〕' | '〗' | '﹚' => ')',
+        _ => c,
+    }
+}
+
+/// 规范化 POI 名称
+///
+/// - 全角字母数字与符号转半角，全角空格转半角空格
+/// - 【】〔〕[] 等括号统一为 ()
+/// - 去除首尾空白，中文与其他字符之间的空白直接去除，其余连续空白合并为一个空格
+pub fn normalize_name(name: &str) -> String {
+    let chars: Vec<char> = name.chars().map(to_halfwidth).collect();
+    let mut out = String::with_capacity(name.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if !c.is_whitespace() {
+            out.push(c);
+            i += 1;
+            continue;
+        }
+
+        // 跳过整段空白
+        let start = i;
+        while i < chars.len() && chars[i].is_whitespace() {
+            i += 1;
+        }
+
+        let prev = start.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i).copied();
+        match (prev, next) {
+            (Some(p), Some(n)) if !is_cjk(p) && !is_cjk(n) && p != '(' && n != ')' => {
+                out.push(' ')
+            }
+            _ => {}
+        }
+    }
+
+    out
+}
+
+/// 常用繁体字与简体字对照，每两个字符为一组（繁、简）
+const TRAD_SIMP_PAIRS: &str = "
+學学醫医園园廣广東东門门車车館馆飯饭樓楼廳厅場场鎮镇鄉乡區区縣县號号銀银電电話话
+業业貨货樂乐藥药診诊療疗衛卫機机關关華华國国際际灣湾臺台島岛嶺岭陽阳陰阴長长條条
+橋桥廟庙會会議议廠厂鐵铁錶表鐘钟書书報报開开發发體体遊游戲戏劇剧畫画藝艺術术舊旧
+亞亚歐欧萬万億亿個个們们來来時时間间問问題题頭头買买賣卖價价貴贵錢钱網网絡络線线
+經经營营專专務务員员產产農农漁渔礦矿運运輸输動动氣气燈灯燒烧雞鸡鴨鸭魚鱼蝦虾麵面
+餅饼餃饺飲饮湯汤點点廚厨衝冲涼凉熱热溫温潔洁淨净髮发紅红綠绿藍蓝黃黄龍龙鳳凤鶴鹤
+馬马驛驿滬沪蘇苏閩闽粵粤瓊琼遼辽齊齐魯鲁贛赣晉晋陝陕隴陇寧宁濱滨灘滩澤泽濟济劉刘
+陳陈張张楊杨趙赵吳吴鄭郑孫孙馮冯許许鄧邓蕭萧韓韩賈贾葉叶盧卢嚴严莊庄錦锦繡绣豐丰
+順顺達达興兴聯联協协記记總总證证據据檢检測测驗验質质監监處处辦办層层棟栋單单雙双
+鄰邻裡里裏里壩坝鋪铺舖铺攤摊櫃柜對对為为爲为與与於于後后從从眾众衆众無无戶户廬庐
+禮礼儀仪紀纪環环護护養养兒儿婦妇夥伙饒饶歲岁壽寿齡龄靈灵聖圣觀观寶宝輛辆駕驾駛驶
+練练訓训試试課课講讲讀读職职師师範范導导創创圖图維维設设計计裝装飾饰傢家腦脑數数
+碼码訊讯視视聽听響响紡纺織织綢绸緞缎襪袜廁厕掛挂圍围壇坛壯壮攝摄彙汇匯汇廈厦濕湿
+燦灿爐炉窯窑壓压蘭兰蓮莲葦苇參参讓让邊边遠远這这過过還还進进選选遷迁適适連连錄录
+銷销鋼钢鋁铝鏈链鑽钻閣阁闆板閘闸隊队陸陆險险隨随離离難难雲云霧雾靜静韻韵頂顶項项
+預预領领頻频額额顏颜顧顾風风飛飞驚惊髒脏鬆松鬥斗鹽盐麥麦黨党齒齿蔣蒋漢汉滿满湧涌
+溝沟瀋沈澗涧礎础祿禄樹树棧栈楓枫檔档橫横歡欢殯殡漿浆烏乌煙烟燁烨獅狮獎奖瑪玛璽玺
+瑤瑶畢毕盤盘碩硕確确種种穩稳竊窃筆笔築筑簡简糧粮紙纸組组結结給给統统繼继續续羅罗
+義义習习聲声腸肠膚肤臨临舉举艦舰蘋苹蟲虫補补製制複复覽览討讨評评諮咨詢询誠诚說说
+請请謝谢豬猪貓猫負负財财貿贸費费資资賓宾賽赛購购贈赠趕赶躍跃軍军軟软載载輕轻較较
+轉转郵邮醬酱釣钓鈴铃鉛铅錫锡鍋锅鏡镜閃闪閱阅陣阵階阶隱隐雜杂頁页飄飘餘余饅馒駐驻
+騎骑鬧闹鮮鲜鳥鸟鴻鸿鵬鹏麗丽黴霉齋斋龜龟
+";
+
+/// 简体对应多个繁体或本身也是常用繁体的字，不做简转繁
+const AMBIGUOUS_SIMPLIFIED: &str = "面里发后台表板松斗叶家范云于冲汇丰制复余沈霉干只";
+
+/// 解析对照表为 (繁, 简) 列表
+fn trad_simp_pairs() -> Vec<(char, char)> {
+    let chars: Vec<char> = TRAD_SIMP_PAIRS
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    chars.chunks(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+static TRAD_TO_SIMP: Lazy<HashMap<char, char>> =
+    Lazy::new(|| trad_simp_pairs().into_iter().collect());
+
+static SIMP_TO_TRAD: Lazy<HashMap<char, char>> = Lazy::new(|| {
+    let mut map = HashMap::new();
+    for (trad, simp) in trad_simp_pairs() {
+        if !AMBIGUOUS_SIMPLIFIED.contains(simp) {
+            map.entry(simp).or_insert(trad);
+        }
+    }
+    map
+});
+
+/// 繁体转简体，仅覆盖 POI 名称中的常用字
+pub fn to_simplified(text: &str) -> String {
+    text.chars()
+        .map(|c| TRAD_TO_SIMP.get(&c).copied().unwrap_or(c))
+        .collect()
+}
+
+/// 简体转繁体，一简对多繁的字保持不变
+pub fn to_traditional(text: &str) -> String {
+    text.chars()
+        .map(|c| SIMP_TO_TRAD.get(&c).copied().unwrap_or(c))
+        .collect()
+}
+
+/// 展开搜索词：先按别名双向替换（别名 -> 全称），再对每个结果做繁简互转，去重后最多返回 max 个
+pub fn search_variants(query: &str, aliases: &[(String, String)], max: usize) -> Vec<String> {
+    let mut terms = vec![query.to_string()];
+    for (alias, target) in aliases {
+        if alias.is_empty() || target.is_empty() {
+            continue;
+        }
+        if query.contains(alias.as_str()) && !query.contains(target.as_str()) {
+            terms.push(query.replace(alias.as_str(), target));
+        } else if query.contains(target.as_str()) {
+            terms.push(query.replace(target.as_str(), alias));
+        }
+    }
+
+    let mut variants: Vec<String> = Vec::new();
+    for term in terms {
+        for variant in [to_simplified(&term), to_traditional(&term), term] {
+            if !variants.contains(&variant) {
+                variants.push(variant);
+            }
+        }
+    }
+    variants.truncate(max.max(1));
+    variants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("ＸＸ 小区"), "XX小区");
+        assert_eq!(normalize_name("  阳光花园　二期 "), "阳光花园二期");
+        assert_eq!(normalize_name("万达广场【东门】"), "万达广场(东门)");
+        assert_eq!(normalize_name("中国银行（阜宁支行）"), "中国银行(阜宁支行)");
+        assert_eq!(normalize_name("KFC   Drive  Thru"), "KFC Drive Thru");
+        assert_eq!(normalize_name("7 天酒店 ( 南门 )"), "7天酒店(南门)");
+    }
+
+    #[test]
+    fn test_search_variants() {
+        assert_eq!(to_simplified("臺灣銀行"), "台湾银行");
+        assert_eq!(to_traditional("湾仔码头"), "灣仔碼頭");
+        // 一简对多繁的字不转换
+        assert_eq!(to_traditional("面馆"), "面館");
+
+        let aliases = vec![("一小".to_string(), "第一小学".to_string())];
+        let variants = search_variants("阜宁一小", &aliases, 8);
+        assert_eq!(variants[0], "阜宁一小");
+        assert!(variants.contains(&"阜宁第一小学".to_string()));
+        assert!(variants.contains(&"阜寧第一小學".to_string()));
+
+        let variants = search_variants("第一小学", &aliases, 8);
+        assert!(variants.contains(&"一小".to_string()));
+        assert_eq!(search_variants("abc", &aliases, 8), vec!["abc"]);
+    }
+}
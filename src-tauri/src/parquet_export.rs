@@ -0,0 +1,108 @@
+//! Parquet 导出
+//!
+//! 按列类型写出 POI 数据（坐标为 DOUBLE、ID 为 INT64），使用 Snappy 压缩，
+//! 便于 pandas / polars 直接读取百万级数据。
+
+use std::fs::File;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+
+use crate::database::ExportPOI;
+use crate::regions::RegionNames;
+
+/// 每个 RecordBatch 的行数
+const BATCH_ROWS: usize = 65536;
+
+fn schema(extra: &[(String, Vec<String>)]) -> Schema {
+    let text = |name: &str| Field::new(name, DataType::Utf8, false);
+    let mut fields = vec![
+        Field::new("id", DataType::Int64, false),
+        text("name"),
+        Field::new("lon", DataType::Float64, false),
+        Field::new("lat", DataType::Float64, false),
+        text("address"),
+        text("phone"),
+        text("category"),
+        text("platform"),
+        text("region_code"),
+        text("province"),
+        text("city"),
+        text("district"),
+        text("tags"),
+    ];
+    fields.extend(extra.iter().map(|(name, _)| text(name)));
+    Schema::new(fields)
+}
+
+fn string_column<'a>(values: impl Iterator<Item = &'a str>) -> ArrayRef {
+    Arc::new(StringArray::from_iter_values(values))
+}
+
+/// 将 POI 及其区划名称、标签写入 Parquet 文件，masking 为脱敏策略说明，写入文件元数据
+///
+/// extra 为追加的扩展列：列名与各行取值
+pub fn write_parquet(
+    path: &str,
+    data: &[ExportPOI],
+    regions: &[RegionNames],
+    tags: &[String],
+    extra: &[(String, Vec<String>)],
+    masking: Option<&str>,
+) -> Result<(), String> {
+    let schema = Arc::new(schema(extra));
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_key_value_metadata(
+            masking.map(|policy| vec![KeyValue::new("masking".to_string(), policy.to_string())]),
+        )
+        .build();
+
+    let file = File::create(path).map_err(|e| format!("创建文件失败: {}", e))?;
+    let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(props))
+        .map_err(|e| format!("创建 Parquet 写入器失败: {}", e))?;
+
+    let batches = data
+        .chunks(BATCH_ROWS)
+        .zip(regions.chunks(BATCH_ROWS))
+        .zip(tags.chunks(BATCH_ROWS))
+        .enumerate();
+    for (index, ((pois, names), tags)) in batches {
+        let rows = index * BATCH_ROWS..index * BATCH_ROWS + pois.len();
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter_values(pois.iter().map(|p| p.id))),
+            string_column(pois.iter().map(|p| p.name.as_str())),
+            Arc::new(Float64Array::from_iter_values(pois.iter().map(|p| p.lon))),
+            Arc::new(Float64Array::from_iter_values(pois.iter().map(|p| p.lat))),
+            string_column(pois.iter().map(|p| p.address.as_str())),
+            string_column(pois.iter().map(|p| p.phone.as_str())),
+            string_column(pois.iter().map(|p| p.category.as_str())),
+            string_column(pois.iter().map(|p| p.platform.as_str())),
+            string_column(pois.iter().map(|p| p.region_code.as_str())),
+            string_column(names.iter().map(|n| n.province.as_str())),
+            string_column(names.iter().map(|n| n.city.as_str())),
+            string_column(names.iter().map(|n| n.district.as_str())),
+            string_column(tags.iter().map(|t| t.as_str())),
+        ];
+        columns.extend(
+            extra
+                .iter()
+                .map(|(_, values)| string_column(values[rows.clone()].iter().map(|v| v.as_str()))),
+        );
+        let batch = RecordBatch::try_new(schema.clone(), columns)
+            .map_err(|e| format!("构建数据批次失败: {}", e))?;
+        writer
+            .write(&batch)
+            .map_err(|e| format!("写入 Parquet 失败: {}", e))?;
+    }
+
+    writer
+        .close()
+        .map_err(|e| format!("写入 Parquet 失败: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,230 @@
+//! 跨平台 POI 对齐
+//!
+//! 同一地点在各平台的名称写法与坐标都略有差异。给定一条 POI，在附近查找其他平台中
+//! 名称相似的记录，按名称相似度与距离综合打分后并列返回，辅助人工确认跨平台匹配。
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::commands::{with_poi_db, COLLECTOR_PLATFORMS};
+use crate::database::ExportPOI;
+use crate::error::{AppError, CmdResult};
+use crate::normalize::normalize_name;
+
+/// 缺省搜索半径（米）
+const DEFAULT_RADIUS_M: f64 = 200.0;
+/// 最大搜索半径（米）
+const MAX_RADIUS_M: f64 = 2000.0;
+/// 缺省名称相似度下限
+const DEFAULT_MIN_SIMILARITY: f64 = 0.3;
+/// 每个平台缺省返回的候选数
+const DEFAULT_PER_PLATFORM: usize = 3;
+/// 综合得分中名称相似度的权重，其余为距离
+const NAME_WEIGHT: f64 = 0.7;
+/// 名称互相包含时的最低相似度，如「第一人民医院」与「上海市第一人民医院」
+const CONTAINS_SIMILARITY: f64 = 0.8;
+
+/// 候选匹配记录
+#[derive(Debug, Clone, Serialize)]
+pub struct PoiMatch {
+    #[serde(flatten)]
+    pub poi: ExportPOI,
+    pub distance_m: f64,
+    pub name_similarity: f64,
+    /// 综合得分，0~1
+    pub score: f64,
+}
+
+/// 单个平台的候选，按得分降序
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformMatches {
+    pub platform: String,
+    pub candidates: Vec<PoiMatch>,
+}
+
+/// 对齐结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PoiMatches {
+    pub poi: ExportPOI,
+    pub radius_m: f64,
+    /// 其他平台的候选，没有候选的平台 candidates 为空
+    pub platforms: Vec<PlatformMatches>,
+}
+
+/// 用于比较的名称：规范化后去掉空白与括号，字母转小写
+fn comparable_name(name: &str) -> Vec<char> {
+    normalize_name(name)
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '(' && *c != ')')
+        .collect()
+}
+
+fn bigrams(chars: &[char]) -> Vec<(char, char)> {
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+/// 名称相似度（0~1）：字符二元组的 Dice 系数，互相包含时不低于 CONTAINS_SIMILARITY
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (comparable_name(a), comparable_name(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+
+    let (a_grams, mut b_grams) = (bigrams(&a), bigrams(&b));
+    let total = a_grams.len() + b_grams.len();
+    let mut common = 0;
+    for gram in &a_grams {
+        if let Some(pos) = b_grams.iter().position(|g| g == gram) {
+            b_grams.swap_remove(pos);
+            common += 1;
+        }
+    }
+    let dice = if total == 0 {
+        0.0
+    } else {
+        2.0 * common as f64 / total as f64
+    };
+
+    let (short, long) = if a.len() <= b.len() {
+        (&a, &b)
+    } else {
+        (&b, &a)
+    };
+    let contains = long.windows(short.len()).any(|w| w == short.as_slice());
+    if contains {
+        dice.max(CONTAINS_SIMILARITY)
+    } else {
+        dice
+    }
+}
+
+/// 综合得分：名称相似度为主，距离越近得分越高
+fn match_score(similarity: f64, distance_m: f64, radius_m: f64) -> f64 {
+    let closeness = (1.0 - distance_m / radius_m).clamp(0.0, 1.0);
+    NAME_WEIGHT * similarity + (1.0 - NAME_WEIGHT) * closeness
+}
+
+/// 查找其他平台在附近的对应记录
+#[tauri::command]
+pub fn get_poi_matches(
+    id: i64,
+    radius_m: Option<f64>,
+    min_similarity: Option<f64>,
+    per_platform: Option<usize>,
+) -> CmdResult<PoiMatches> {
+    let radius_m = radius_m.unwrap_or(DEFAULT_RADIUS_M);
+    if radius_m <= 0.0 || radius_m > MAX_RADIUS_M {
+        return Err(AppError::invalid(format!(
+            "搜索半径需在 0~{} 米之间",
+            MAX_RADIUS_M
+        )));
+    }
+    let min_similarity = min_similarity
+        .unwrap_or(DEFAULT_MIN_SIMILARITY)
+        .clamp(0.0, 1.0);
+    let per_platform = per_platform.unwrap_or(DEFAULT_PER_PLATFORM).max(1);
+
+    let (poi, nearby, details) = with_poi_db(|db| {
+        let poi = db
+            .get_poi_by_ids(&[id])
+            .map_err(|e| format!("读取 POI 失败: {}", e))?
+            .pop();
+        let Some(poi) = poi else {
+            return Ok((None, Vec::new(), Vec::new()));
+        };
+        let nearby = db
+            .query_poi_nearby(poi.lon, poi.lat, radius_m, usize::MAX)
+            .map_err(|e| format!("附近查询失败: {}", e))?;
+        // 只读取名称足够相似的候选的完整字段
+        let ids: Vec<i64> = nearby
+            .iter()
+            .filter(|n| n.poi.platform != poi.platform)
+            .filter(|n| name_similarity(&poi.name, &n.poi.name) >= min_similarity)
+            .map(|n| n.poi.id)
+            .collect();
+        let details = db
+            .get_poi_by_ids(&ids)
+            .map_err(|e| format!("读取 POI 失败: {}", e))?;
+        Ok((Some(poi), nearby, details))
+    })?;
+    let poi = poi.ok_or_else(|| AppError::not_found(format!("POI {} 不存在", id)))?;
+
+    let mut matches: Vec<PoiMatch> = details
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance_m = nearby.iter().find(|n| n.poi.id == candidate.id)?.distance_m;
+            let similarity = name_similarity(&poi.name, &candidate.name);
+            Some(PoiMatch {
+                score: match_score(similarity, distance_m, radius_m),
+                name_similarity: similarity,
+                distance_m,
+                poi: candidate,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    // 内置平台在前，其余平台按出现顺序排在后面
+    let mut platforms: Vec<String> = COLLECTOR_PLATFORMS.iter().map(|p| p.to_string()).collect();
+    let mut seen: HashSet<String> = platforms.iter().cloned().collect();
+    for m in &matches {
+        if seen.insert(m.poi.platform.clone()) {
+            platforms.push(m.poi.platform.clone());
+        }
+    }
+    let platforms = platforms
+        .into_iter()
+        .filter(|p| *p != poi.platform)
+        .map(|platform| PlatformMatches {
+            candidates: matches
+                .iter()
+                .filter(|m| m.poi.platform == platform)
+                .take(per_platform)
+                .cloned()
+                .collect(),
+            platform,
+        })
+        .collect();
+
+    Ok(PoiMatches {
+        poi,
+        radius_m,
+        platforms,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_similarity() {
+        assert_eq!(
+            name_similarity("上海市第一人民医院", "上海市第一人民医院"),
+            1.0
+        );
+        // 全角、空白与括号差异视为相同
+        assert_eq!(
+            name_similarity("肯德基（人民广场店）", "肯德基 (人民广场店)"),
+            1.0
+        );
+        assert_eq!(name_similarity("KFC", "kfc"), 1.0);
+        // 互相包含
+        assert_eq!(name_similarity("第一人民医院", "上海市第一人民医院"), 0.8);
+        let partial = name_similarity("上海市第一人民医院", "上海市第一人民医院南院");
+        assert!((0.8..1.0).contains(&partial));
+        assert!(name_similarity("星巴克", "人民医院") < 0.1);
+        assert_eq!(name_similarity("", "医院"), 0.0);
+    }
+
+    #[test]
+    fn test_match_score() {
+        assert_eq!(match_score(1.0, 0.0, 200.0), 1.0);
+        assert!((match_score(1.0, 100.0, 200.0) - 0.85).abs() < 1e-9);
+        assert!((match_score(0.5, 300.0, 200.0) - 0.35).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,304 @@
+//! POI 数据快照与对比
+//!
+//! 按地区、类别将当前 POI 复制为快照，周期性采集后对比两次快照（或快照与当前数据），
+//! 得到新增、消失与属性变化的 POI 清单。平台与名称相同且距离在阈值内的视为同一 POI，
+//! 因此重新采集导致的 ID 变化不会被误判为增删。
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::commands::with_poi_db;
+use crate::coords::haversine_distance;
+use crate::database::{ExportPOI, PoiFilter, PoiSnapshot};
+use crate::error::{AppError, CmdResult};
+
+/// 同名 POI 视为同一地点的最大距离（米）
+const MATCH_DISTANCE_M: f64 = 200.0;
+/// 坐标偏移超过该距离时记为位置变化（米）
+const MOVE_THRESHOLD_M: f64 = 20.0;
+
+/// 单个字段的变化
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// 属性发生变化的 POI
+#[derive(Debug, Clone, Serialize)]
+pub struct PoiChange {
+    pub before: ExportPOI,
+    pub after: ExportPOI,
+    pub changes: Vec<FieldChange>,
+}
+
+/// 快照对比结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotDiff {
+    pub base: PoiSnapshot,
+    /// 对比目标，为空表示与当前数据对比
+    pub target: Option<PoiSnapshot>,
+    pub added: Vec<ExportPOI>,
+    pub removed: Vec<ExportPOI>,
+    pub changed: Vec<PoiChange>,
+    pub unchanged: usize,
+}
+
+/// 比较两个 POI 的属性
+fn field_changes(before: &ExportPOI, after: &ExportPOI) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+    let mut compare = |field: &str, a: &str, b: &str| {
+        if a.trim() != b.trim() {
+            changes.push(FieldChange {
+                field: field.to_string(),
+                before: a.to_string(),
+                after: b.to_string(),
+            });
+        }
+    };
+    compare("地址", &before.address, &after.address);
+    compare("电话", &before.phone, &after.phone);
+    compare("类别", &before.category, &after.category);
+    compare("区域编码", &before.region_code, &after.region_code);
+
+    if haversine_distance(before.lon, before.lat, after.lon, after.lat) > MOVE_THRESHOLD_M {
+        changes.push(FieldChange {
+            field: "位置".to_string(),
+            before: format!("{:.6},{:.6}", before.lon, before.lat),
+            after: format!("{:.6},{:.6}", after.lon, after.lat),
+        });
+    }
+    changes
+}
+
+/// 对比两组 POI，返回 (新增, 消失, 变化, 未变化数量)
+fn diff_pois(
+    base: Vec<ExportPOI>,
+    target: Vec<ExportPOI>,
+) -> (Vec<ExportPOI>, Vec<ExportPOI>, Vec<PoiChange>, usize) {
+    // 按平台与名称分组，组内按距离就近匹配
+    let mut groups: HashMap<(String, String), Vec<usize>> = HashMap::new();
+    for (i, poi) in target.iter().enumerate() {
+        groups
+            .entry((poi.platform.clone(), poi.name.trim().to_string()))
+            .or_default()
+            .push(i);
+    }
+
+    let mut matched = vec![false; target.len()];
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+    let mut unchanged = 0;
+    for poi in base {
+        let key = (poi.platform.clone(), poi.name.trim().to_string());
+        let nearest = groups.get(&key).and_then(|candidates| {
+            candidates
+                .iter()
+                .filter(|&&i| !matched[i])
+                .map(|&i| {
+                    let other = &target[i];
+                    (
+                        i,
+                        haversine_distance(poi.lon, poi.lat, other.lon, other.lat),
+                    )
+                })
+                .filter(|(_, distance)| *distance <= MATCH_DISTANCE_M)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+        });
+
+        match nearest {
+            Some((i, _)) => {
+                matched[i] = true;
+                let changes = field_changes(&poi, &target[i]);
+                if changes.is_empty() {
+                    unchanged += 1;
+                } else {
+                    changed.push(PoiChange {
+                        before: poi,
+                        after: target[i].clone(),
+                        changes,
+                    });
+                }
+            }
+            None => removed.push(poi),
+        }
+    }
+
+    let added = target
+        .into_iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(poi, _)| poi)
+        .collect();
+    (added, removed, changed, unchanged)
+}
+
+/// 按地区、类别等条件创建当前数据的快照
+#[tauri::command]
+pub fn create_poi_snapshot(name: String, filter: PoiFilter) -> CmdResult<PoiSnapshot> {
+    let mut filter = filter;
+    filter.resolve_stale_days();
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid("请输入快照名称"));
+    }
+    with_poi_db(|db| {
+        let id = db
+            .create_poi_snapshot(name, &filter)
+            .map_err(|e| format!("创建快照失败: {}", e))?;
+        db.get_poi_snapshot(id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "快照不存在".to_string())
+    })
+}
+
+#[tauri::command]
+pub fn list_poi_snapshots() -> CmdResult<Vec<PoiSnapshot>> {
+    with_poi_db(|db| db.list_poi_snapshots().map_err(|e| e.to_string()))
+}
+
+#[tauri::command]
+pub fn delete_poi_snapshot(id: i64) -> CmdResult<bool> {
+    with_poi_db(|db| db.delete_poi_snapshot(id).map_err(|e| e.to_string()))
+}
+
+/// 对比两个快照；未指定 target_id 时按基准快照的范围与当前数据对比
+#[tauri::command]
+pub fn compare_snapshots(base_id: i64, target_id: Option<i64>) -> CmdResult<SnapshotDiff> {
+    let find = |id: i64| -> CmdResult<PoiSnapshot> {
+        with_poi_db(|db| db.get_poi_snapshot(id).map_err(|e| e.to_string()))?
+            .ok_or_else(|| AppError::not_found(format!("快照 {} 不存在", id)))
+    };
+    let base = find(base_id)?;
+    let target = target_id.map(find).transpose()?;
+
+    let (base_items, target_items) = with_poi_db(|db| {
+        let items = |id: i64| -> Result<Vec<ExportPOI>, String> {
+            db.get_poi_snapshot_items(id)
+                .map_err(|e| format!("读取快照数据失败: {}", e))
+        };
+        let target_items = match &target {
+            Some(target) => items(target.id)?,
+            None => db
+                .get_poi_filtered(&base.filter)
+                .map_err(|e| format!("读取当前数据失败: {}", e))?,
+        };
+        Ok((items(base.id)?, target_items))
+    })?;
+
+    let (added, removed, changed, unchanged) = diff_pois(base_items, target_items);
+    Ok(SnapshotDiff {
+        base,
+        target,
+        added,
+        removed,
+        changed,
+        unchanged,
+    })
+}
+
+fn csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// 将对比结果导出为 CSV 差异报告，返回写入的行数
+#[tauri::command]
+pub fn export_snapshot_diff(
+    base_id: i64,
+    target_id: Option<i64>,
+    output_path: String,
+) -> CmdResult<usize> {
+    let diff = compare_snapshots(base_id, target_id)?;
+
+    let mut csv = String::from("\u{feff}");
+    csv.push_str(&format!(
+        "# 基准快照: {}（{}）；对比目标: {}\n",
+        diff.base.name,
+        diff.base.created_at.as_deref().unwrap_or(""),
+        diff.target
+            .as_ref()
+            .map(|t| format!("{}（{}）", t.name, t.created_at.as_deref().unwrap_or("")))
+            .unwrap_or_else(|| "当前数据".to_string())
+    ));
+    csv.push_str("变化类型,平台,名称,经度,纬度,区域编码,字段,原值,新值\n");
+
+    let mut rows = 0;
+    let mut push_row = |kind: &str, poi: &ExportPOI, field: &str, before: &str, after: &str| {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            kind,
+            poi.platform,
+            csv_field(&poi.name),
+            poi.lon,
+            poi.lat,
+            poi.region_code,
+            field,
+            csv_field(before),
+            csv_field(after)
+        ));
+        rows += 1;
+    };
+    for poi in &diff.added {
+        push_row("新增", poi, "", "", "");
+    }
+    for poi in &diff.removed {
+        push_row("消失", poi, "", "", "");
+    }
+    for change in &diff.changed {
+        for field in &change.changes {
+            push_row(
+                "变化",
+                &change.after,
+                &field.field,
+                &field.before,
+                &field.after,
+            );
+        }
+    }
+
+    std::fs::write(&output_path, csv)?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poi(id: i64, name: &str, lon: f64, phone: &str) -> ExportPOI {
+        ExportPOI {
+            id,
+            name: name.to_string(),
+            lon,
+            lat: 33.0,
+            address: "人民路 1 号".to_string(),
+            phone: phone.to_string(),
+            category: "餐饮".to_string(),
+            platform: "amap".to_string(),
+            region_code: "320902".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff_pois() {
+        let base = vec![
+            poi(1, "老街面馆", 120.0, "0515-1"),
+            poi(2, "加油站", 120.0, ""),
+            poi(3, "加油站", 120.1, ""),
+            poi(4, "已关闭的店", 120.2, ""),
+        ];
+        // 重新采集后 ID 全部变化；一个加油站消失，面馆换了电话
+        let target = vec![
+            poi(11, "老街面馆", 120.0, "0515-2"),
+            poi(13, "加油站", 120.1001, ""),
+            poi(15, "新开的店", 120.3, ""),
+        ];
+
+        let (added, removed, changed, unchanged) = diff_pois(base, target);
+        assert_eq!(added.iter().map(|p| p.id).collect::<Vec<_>>(), vec![15]);
+        assert_eq!(removed.iter().map(|p| p.id).collect::<Vec<_>>(), vec![2, 4]);
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].changes[0].field, "电话");
+        assert_eq!(unchanged, 1);
+    }
+}
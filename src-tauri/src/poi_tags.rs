@@ -0,0 +1,190 @@
+//! POI 标签
+//!
+//! 为 POI 批量打上「已实地核实」「重点对象」等标签，打标时可附带备注。标签与 POI 为多对多关系，
+//! 可按标签筛选、导出，导出文件中以「、」连接的标签列呈现。
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::commands::with_poi_db;
+use crate::database::{ExportPOI, PoiFilter, PoiTag, PoiTagLink};
+use crate::error::{AppError, CmdResult};
+
+/// 标签名称的最大长度（字符）
+const MAX_TAG_LEN: usize = 32;
+/// 导出时连接多个标签的分隔符，标签名称中不允许出现
+pub const TAG_SEPARATOR: &str = "、";
+/// 按标签筛选缺省返回的条数
+const DEFAULT_LIMIT: usize = 1000;
+
+/// 带标签的 POI
+#[derive(Debug, Clone, Serialize)]
+pub struct TaggedPoi {
+    #[serde(flatten)]
+    pub poi: ExportPOI,
+    pub tags: Vec<PoiTagLink>,
+}
+
+/// 校验并规范化标签名称
+fn normalize_tag_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid("标签名称不能为空"));
+    }
+    if name.chars().count() > MAX_TAG_LEN {
+        return Err(AppError::invalid(format!(
+            "标签名称不能超过 {} 个字符",
+            MAX_TAG_LEN
+        )));
+    }
+    if name.contains(TAG_SEPARATOR) || name.contains(',') {
+        return Err(AppError::invalid(format!(
+            "标签名称不能包含「{}」或逗号",
+            TAG_SEPARATOR
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// 导出时的标签列
+pub fn join_tag_names(tags: Option<&Vec<PoiTagLink>>) -> String {
+    tags.map(|tags| {
+        tags.iter()
+            .map(|t| t.name.as_str())
+            .collect::<Vec<_>>()
+            .join(TAG_SEPARATOR)
+    })
+    .unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn list_poi_tags() -> CmdResult<Vec<PoiTag>> {
+    with_poi_db(|db| db.list_tags().map_err(|e| e.to_string()))
+}
+
+/// 新增标签，同名标签已存在时更新颜色
+#[tauri::command]
+pub fn save_poi_tag(name: String, color: Option<String>) -> CmdResult<i64> {
+    let name = normalize_tag_name(&name)?;
+    let color = color.filter(|c| !c.trim().is_empty());
+    with_poi_db(|db| {
+        db.upsert_tag(&name, color.as_deref())
+            .map_err(|e| format!("保存标签失败: {}", e))
+    })
+}
+
+/// 删除标签，同时移除所有 POI 上的该标签
+#[tauri::command]
+pub fn delete_poi_tag(id: i64) -> CmdResult<()> {
+    let deleted = with_poi_db(|db| db.delete_tag(id).map_err(|e| e.to_string()))?;
+    if !deleted {
+        return Err(AppError::not_found(format!("标签 {} 不存在", id)));
+    }
+    Ok(())
+}
+
+/// 为一批 POI 打标签，标签不存在时自动创建；已有该标签的 POI 只更新备注。返回新打标的数量
+#[tauri::command]
+pub fn tag_pois(ids: Vec<i64>, tag: String, note: Option<String>) -> CmdResult<usize> {
+    let tag = normalize_tag_name(&tag)?;
+    if ids.is_empty() {
+        return Err(AppError::invalid("请选择要打标的 POI"));
+    }
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    let count = with_poi_db(|db| {
+        let tag_id = db
+            .upsert_tag(&tag, None)
+            .map_err(|e| format!("保存标签失败: {}", e))?;
+        db.tag_pois(&ids, tag_id, note.as_deref())
+            .map_err(|e| format!("打标签失败: {}", e))
+    })?;
+    log::info!("已为 {} 条 POI 打上标签「{}」", count, tag);
+    Ok(count)
+}
+
+/// 移除一批 POI 上的标签，返回移除的数量
+#[tauri::command]
+pub fn untag_pois(ids: Vec<i64>, tag: String) -> CmdResult<usize> {
+    let tag = tag.trim().to_string();
+    with_poi_db(|db| {
+        let Some(tag_id) = db.get_tag_id(&tag).map_err(|e| e.to_string())? else {
+            return Ok(0);
+        };
+        db.untag_pois(&ids, tag_id)
+            .map_err(|e| format!("移除标签失败: {}", e))
+    })
+}
+
+/// 读取一批 POI 的标签，没有标签的 POI 不出现在结果中
+#[tauri::command]
+pub fn get_poi_tags(ids: Vec<i64>) -> CmdResult<HashMap<i64, Vec<PoiTagLink>>> {
+    with_poi_db(|db| db.get_poi_tag_map(Some(&ids)).map_err(|e| e.to_string()))
+}
+
+/// 按标签筛选 POI，带有任一指定标签即命中，可再按平台过滤
+#[tauri::command]
+pub fn list_tagged_poi(
+    tags: Vec<String>,
+    platform: Option<String>,
+    limit: Option<usize>,
+) -> CmdResult<Vec<TaggedPoi>> {
+    if tags.is_empty() {
+        return Err(AppError::invalid("请至少选择一个标签"));
+    }
+    let filter = PoiFilter {
+        tags: Some(tags),
+        platforms: platform
+            .filter(|p| !p.is_empty() && p != "all")
+            .map(|p| vec![p]),
+        ..Default::default()
+    };
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+
+    let (pois, mut tag_map) = with_poi_db(|db| {
+        let mut pois = db
+            .get_poi_filtered(&filter)
+            .map_err(|e| format!("查询 POI 失败: {}", e))?;
+        pois.truncate(limit);
+        let ids: Vec<i64> = pois.iter().map(|p| p.id).collect();
+        let tag_map = db
+            .get_poi_tag_map(Some(&ids))
+            .map_err(|e| format!("读取标签失败: {}", e))?;
+        Ok((pois, tag_map))
+    })?;
+
+    Ok(pois
+        .into_iter()
+        .map(|poi| TaggedPoi {
+            tags: tag_map.remove(&poi.id).unwrap_or_default(),
+            poi,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tag_name() {
+        assert_eq!(normalize_tag_name("  已实地核实 ").unwrap(), "已实地核实");
+        assert!(normalize_tag_name("   ").is_err());
+        assert!(normalize_tag_name("重点、对象").is_err());
+        assert!(normalize_tag_name("a,b").is_err());
+        assert!(normalize_tag_name(&"长".repeat(MAX_TAG_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_join_tag_names() {
+        let link = |name: &str| PoiTagLink {
+            name: name.to_string(),
+            color: None,
+            note: None,
+        };
+        assert_eq!(join_tag_names(None), "");
+        assert_eq!(
+            join_tag_names(Some(&vec![link("已实地核实"), link("重点对象")])),
+            "已实地核实、重点对象"
+        );
+    }
+}
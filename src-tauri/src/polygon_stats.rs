@@ -0,0 +1,226 @@
+//! 多边形范围内 POI 统计报表
+//!
+//! 以收藏范围或 GeoJSON 面要素作为统计范围（如项目红线），按类别汇总范围内的 POI 数量并列出明细，
+//! 可直接导出为 CSV 报表。多个面与内环（洞）按奇偶规则判断，与瓦片裁剪一致。
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::commands::with_poi_db;
+use crate::config::Bounds;
+use crate::database::ExportPOI;
+use crate::error::{AppError, CmdResult};
+use crate::tile_downloader::clip::{parse_geojson, polygon_bounds};
+use crate::tile_downloader::coverage::point_in_polygon;
+use crate::tile_downloader::types::ClipPolygon;
+
+/// 单个类别的统计
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CategoryCount {
+    pub category: String,
+    pub count: usize,
+    /// 各平台的数量
+    pub platforms: BTreeMap<String, usize>,
+}
+
+/// 多边形统计结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PolygonStats {
+    pub total: usize,
+    /// 按数量降序
+    pub categories: Vec<CategoryCount>,
+    pub pois: Vec<ExportPOI>,
+}
+
+/// 按奇偶规则判断点是否落在多边形（可含多个面与洞）内
+pub(crate) fn polygon_contains(polygon: &ClipPolygon, lon: f64, lat: f64) -> bool {
+    polygon
+        .iter()
+        .filter(|ring| point_in_polygon(lon, lat, ring))
+        .count()
+        % 2
+        == 1
+}
+
+/// 按类别汇总，类别为空的归为「未分类」
+fn count_by_category(pois: &[ExportPOI]) -> Vec<CategoryCount> {
+    let mut counts: HashMap<&str, CategoryCount> = HashMap::new();
+    for poi in pois {
+        let category = match poi.category.trim() {
+            "" => "未分类",
+            c => c,
+        };
+        let entry = counts.entry(category).or_insert_with(|| CategoryCount {
+            category: category.to_string(),
+            count: 0,
+            platforms: BTreeMap::new(),
+        });
+        entry.count += 1;
+        *entry.platforms.entry(poi.platform.clone()).or_default() += 1;
+    }
+    let mut categories: Vec<CategoryCount> = counts.into_values().collect();
+    categories.sort_by(|a, b| b.count.cmp(&a.count).then(a.category.cmp(&b.category)));
+    categories
+}
+
+/// 解析统计范围：收藏范围与 GeoJSON 二选一
+fn resolve_polygon(area_id: Option<String>, geojson: Option<Value>) -> CmdResult<ClipPolygon> {
+    match (area_id.filter(|id| !id.trim().is_empty()), geojson) {
+        (Some(_), Some(_)) => Err(AppError::invalid("收藏范围与 GeoJSON 只能指定一个")),
+        (Some(id), None) => {
+            let area = crate::commands::find_saved_area(&id)?;
+            if area.polygon.len() >= 3 {
+                return Ok(vec![area.polygon]);
+            }
+            let b = area.bounds;
+            Ok(vec![vec![
+                (b.min_lon, b.min_lat),
+                (b.max_lon, b.min_lat),
+                (b.max_lon, b.max_lat),
+                (b.min_lon, b.max_lat),
+            ]])
+        }
+        (None, Some(value)) => parse_geojson(&value).map_err(AppError::invalid),
+        (None, None) => Err(AppError::invalid("请指定收藏范围或 GeoJSON 多边形")),
+    }
+}
+
+fn collect_stats(polygon: &ClipPolygon, platform: Option<&str>) -> CmdResult<PolygonStats> {
+    let extent = polygon_bounds(polygon);
+    let bounds = Bounds {
+        min_lon: extent.west,
+        max_lon: extent.east,
+        min_lat: extent.south,
+        max_lat: extent.north,
+    };
+
+    let mut pois = with_poi_db(|db| {
+        let ids: Vec<i64> = db
+            .query_poi_in_bounds(&bounds, i64::MAX)
+            .map_err(|e| format!("范围查询失败: {}", e))?
+            .into_iter()
+            .filter(|poi| platform.is_none_or(|p| poi.platform == p))
+            .filter(|poi| polygon_contains(polygon, poi.lon, poi.lat))
+            .map(|poi| poi.id)
+            .collect();
+        db.get_poi_by_ids(&ids)
+            .map_err(|e| format!("读取 POI 失败: {}", e))
+    })?;
+    pois.sort_by(|a, b| a.category.cmp(&b.category).then(a.name.cmp(&b.name)));
+
+    Ok(PolygonStats {
+        total: pois.len(),
+        categories: count_by_category(&pois),
+        pois,
+    })
+}
+
+/// 统计多边形范围内的 POI：按类别汇总并返回明细
+///
+/// area_id 指定收藏范围，geojson 传入 GeoJSON 面要素（WGS84 经纬度），二选一
+#[tauri::command]
+pub fn stats_in_polygon(
+    area_id: Option<String>,
+    geojson: Option<Value>,
+    platform: Option<String>,
+) -> CmdResult<PolygonStats> {
+    let polygon = resolve_polygon(area_id, geojson)?;
+    let platform = platform.filter(|p| p != "all");
+    collect_stats(&polygon, platform.as_deref())
+}
+
+fn csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// 将多边形统计导出为 CSV 报表（类别汇总 + 明细），返回明细条数
+#[tauri::command]
+pub fn export_polygon_stats(
+    area_id: Option<String>,
+    geojson: Option<Value>,
+    platform: Option<String>,
+    output_path: String,
+) -> CmdResult<usize> {
+    let stats = stats_in_polygon(area_id, geojson, platform)?;
+
+    let mut csv = String::from("\u{feff}");
+    csv.push_str("类别,数量\n");
+    for category in &stats.categories {
+        csv.push_str(&format!(
+            "{},{}\n",
+            csv_field(&category.category),
+            category.count
+        ));
+    }
+    csv.push_str(&format!("合计,{}\n\n", stats.total));
+
+    csv.push_str("名称,类别,平台,地址,电话,经度,纬度,区域编码\n");
+    for poi in &stats.pois {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            csv_field(&poi.name),
+            csv_field(&poi.category),
+            poi.platform,
+            csv_field(&poi.address),
+            csv_field(&poi.phone),
+            poi.lon,
+            poi.lat,
+            poi.region_code
+        ));
+    }
+
+    std::fs::write(&output_path, csv)?;
+    Ok(stats.total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poi(category: &str, platform: &str) -> ExportPOI {
+        ExportPOI {
+            id: 0,
+            name: "测试".to_string(),
+            lon: 120.0,
+            lat: 30.0,
+            address: String::new(),
+            phone: String::new(),
+            category: category.to_string(),
+            platform: platform.to_string(),
+            region_code: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_polygon_contains() {
+        let outer = vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        let hole = vec![(4.0, 4.0), (6.0, 4.0), (6.0, 6.0), (4.0, 6.0)];
+        let other = vec![(20.0, 0.0), (30.0, 0.0), (30.0, 10.0), (20.0, 10.0)];
+        let polygon = vec![outer, hole, other];
+        assert!(polygon_contains(&polygon, 2.0, 2.0));
+        assert!(!polygon_contains(&polygon, 5.0, 5.0));
+        assert!(polygon_contains(&polygon, 25.0, 5.0));
+        assert!(!polygon_contains(&polygon, 15.0, 5.0));
+    }
+
+    #[test]
+    fn test_count_by_category() {
+        let pois = vec![
+            poi("学校", "amap"),
+            poi("医院", "amap"),
+            poi("医院", "baidu"),
+            poi(" ", "osm"),
+        ];
+        let categories = count_by_category(&pois);
+        assert_eq!(categories.len(), 3);
+        assert_eq!(categories[0].category, "医院");
+        assert_eq!(categories[0].count, 2);
+        assert_eq!(
+            categories[0].platforms,
+            BTreeMap::from([("amap".to_string(), 1), ("baidu".to_string(), 1)])
+        );
+        assert_eq!(categories[1].category, "学校");
+        assert_eq!(categories[2].category, "未分类");
+    }
+}
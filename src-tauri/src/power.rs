@@ -0,0 +1,377 @@
+//! 任务运行期间阻止系统休眠
+//!
+//! 后台线程定期检查是否有运行中的采集或瓦片下载任务，有任务时通过系统接口阻止休眠，
+//! 全部结束（或暂停）后释放。Windows 使用 SetThreadExecutionState，macOS 使用 caffeinate，
+//! Linux 使用 systemd-inhibit。只阻止系统休眠，不阻止关闭显示器。
+//!
+//! 下载任务可配置完成后动作（退出应用/系统休眠/关机）：任务完成且没有其他运行中的任务后
+//! 开始倒计时，倒计时期间可取消，结束后执行。
+
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::config::{self, PowerSettings};
+use crate::error::{AppError, CmdResult};
+
+/// 检查任务状态的间隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+static SETTINGS: Lazy<RwLock<PowerSettings>> =
+    Lazy::new(|| RwLock::new(config::get_power_settings().unwrap_or_default()));
+
+/// 当前是否正在阻止休眠
+static INHIBITED: AtomicBool = AtomicBool::new(false);
+
+/// 完成后动作倒计时事件，载荷为 PendingAction，为 null 表示已取消或已执行
+pub const COMPLETION_ACTION_EVENT: &str = "completion-action";
+/// 执行完成后动作前的倒计时
+const COUNTDOWN_SECS: u64 = 60;
+
+/// 待执行的完成后动作
+static PENDING: Lazy<Mutex<Option<PendingAction>>> = Lazy::new(|| Mutex::new(None));
+/// 倒计时代数，取消或重新安排时递增，旧的倒计时线程随之退出
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(windows)]
+mod imp {
+    use windows_sys::Win32::System::Power::{
+        SetThreadExecutionState, ES_CONTINUOUS, ES_SYSTEM_REQUIRED,
+    };
+
+    /// 执行状态按线程生效，需在同一线程上获取与释放
+    pub struct Inhibitor;
+
+    impl Inhibitor {
+        pub fn acquire() -> Result<Self, String> {
+            if unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) } == 0 {
+                return Err("SetThreadExecutionState 调用失败".to_string());
+            }
+            Ok(Inhibitor)
+        }
+
+        pub fn is_alive(&mut self) -> bool {
+            true
+        }
+    }
+
+    impl Drop for Inhibitor {
+        fn drop(&mut self) {
+            unsafe {
+                SetThreadExecutionState(ES_CONTINUOUS);
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod imp {
+    use std::process::{Child, Command, Stdio};
+
+    /// 持有阻止休眠的子进程，结束子进程即释放；子进程随本进程退出而退出
+    pub struct Inhibitor(Child);
+
+    impl Inhibitor {
+        pub fn acquire() -> Result<Self, String> {
+            let pid = std::process::id().to_string();
+            #[cfg(target_os = "macos")]
+            let mut command = {
+                let mut command = Command::new("caffeinate");
+                command.args(["-i", "-w", pid.as_str()]);
+                command
+            };
+            #[cfg(not(target_os = "macos"))]
+            let mut command = {
+                let tail_pid = format!("--pid={}", pid);
+                let mut command = Command::new("systemd-inhibit");
+                command.args([
+                    "--what=sleep:idle",
+                    "--who=POI Collector",
+                    "--why=下载或采集任务运行中",
+                    "--mode=block",
+                    "tail",
+                    tail_pid.as_str(),
+                    "-f",
+                    "/dev/null",
+                ]);
+                command
+            };
+            command
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map(Inhibitor)
+                .map_err(|e| format!("启动休眠阻止进程失败: {}", e))
+        }
+
+        /// 子进程已退出（如系统不支持）时返回 false
+        pub fn is_alive(&mut self) -> bool {
+            matches!(self.0.try_wait(), Ok(None))
+        }
+    }
+
+    impl Drop for Inhibitor {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+}
+
+/// 是否有运行中的采集或瓦片下载任务
+fn has_active_tasks() -> bool {
+    crate::commands::get_collector_statuses()
+        .values()
+        .any(|s| s.status == "running")
+        || crate::tile_downloader::commands::has_active_tile_tasks()
+}
+
+/// 启动休眠阻止监视线程
+pub fn start() {
+    thread::spawn(|| {
+        let mut inhibitor: Option<imp::Inhibitor> = None;
+        // 获取失败后不再重试，直到任务全部结束
+        let mut failed = false;
+        loop {
+            let wanted = SETTINGS.read().prevent_sleep && has_active_tasks();
+            if !wanted {
+                if inhibitor.take().is_some() {
+                    log::info!("任务已结束，允许系统休眠");
+                }
+                failed = false;
+            } else if let Some(current) = inhibitor.as_mut() {
+                if !current.is_alive() {
+                    log::warn!("休眠阻止已失效，当前系统可能不支持");
+                    inhibitor = None;
+                    failed = true;
+                }
+            } else if !failed {
+                match imp::Inhibitor::acquire() {
+                    Ok(acquired) => {
+                        log::info!("任务运行中，已阻止系统休眠");
+                        inhibitor = Some(acquired);
+                    }
+                    Err(e) => {
+                        log::warn!("{}", e);
+                        failed = true;
+                    }
+                }
+            }
+            INHIBITED.store(inhibitor.is_some(), Ordering::Relaxed);
+            thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+/// 电源设置与当前状态
+#[derive(Debug, Clone, Serialize)]
+pub struct PowerStatus {
+    #[serde(flatten)]
+    pub settings: PowerSettings,
+    /// 当前是否正在阻止休眠
+    pub inhibiting: bool,
+}
+
+#[tauri::command]
+pub fn get_power_settings() -> CmdResult<PowerStatus> {
+    Ok(PowerStatus {
+        settings: SETTINGS.read().clone(),
+        inhibiting: INHIBITED.load(Ordering::Relaxed),
+    })
+}
+
+/// 修改电源设置，下次检查时生效
+#[tauri::command]
+pub fn set_power_settings(settings: PowerSettings) -> CmdResult<PowerStatus> {
+    config::set_power_settings(&settings)?;
+    *SETTINGS.write() = settings;
+    get_power_settings()
+}
+
+/// 任务完成后的动作
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionAction {
+    #[default]
+    None,
+    /// 退出应用
+    Exit,
+    /// 系统休眠
+    Sleep,
+    /// 关机
+    Shutdown,
+}
+
+impl CompletionAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompletionAction::None => "none",
+            CompletionAction::Exit => "exit",
+            CompletionAction::Sleep => "sleep",
+            CompletionAction::Shutdown => "shutdown",
+        }
+    }
+
+    /// 由存储的取值解析，无法识别时视为无操作
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "exit" => CompletionAction::Exit,
+            "sleep" => CompletionAction::Sleep,
+            "shutdown" => CompletionAction::Shutdown,
+            _ => CompletionAction::None,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            CompletionAction::None => "无操作",
+            CompletionAction::Exit => "退出应用",
+            CompletionAction::Sleep => "系统休眠",
+            CompletionAction::Shutdown => "关机",
+        }
+    }
+}
+
+/// 倒计时中的完成后动作
+#[derive(Debug, Clone, Serialize)]
+pub struct PendingAction {
+    pub action: CompletionAction,
+    /// 触发动作的任务名称
+    pub task_name: String,
+    /// 剩余秒数
+    pub remaining_secs: u64,
+    /// 仍有其他任务运行，等待其结束后再开始倒计时
+    pub waiting: bool,
+}
+
+/// 休眠与关机使用的系统命令
+fn system_command(action: CompletionAction) -> Option<(&'static str, &'static [&'static str])> {
+    #[cfg(windows)]
+    let command: Option<(&str, &[&str])> = match action {
+        CompletionAction::Sleep => {
+            Some(("rundll32.exe", &["powrprof.dll,SetSuspendState", "0,1,0"]))
+        }
+        CompletionAction::Shutdown => Some(("shutdown", &["/s", "/t", "0"])),
+        _ => None,
+    };
+    #[cfg(target_os = "macos")]
+    let command: Option<(&str, &[&str])> = match action {
+        CompletionAction::Sleep => Some(("pmset", &["sleepnow"])),
+        CompletionAction::Shutdown => Some((
+            "osascript",
+            &["-e", "tell application \"System Events\" to shut down"],
+        )),
+        _ => None,
+    };
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let command: Option<(&str, &[&str])> = match action {
+        CompletionAction::Sleep => Some(("systemctl", &["suspend"])),
+        CompletionAction::Shutdown => Some(("systemctl", &["poweroff"])),
+        _ => None,
+    };
+    command
+}
+
+fn execute(app: &AppHandle, action: CompletionAction) -> Result<(), String> {
+    if action == CompletionAction::Exit {
+        app.exit(0);
+        return Ok(());
+    }
+    let Some((program, args)) = system_command(action) else {
+        return Ok(());
+    };
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .map_err(|e| format!("执行{}失败: {}", action.label(), e))?;
+    if !status.success() {
+        return Err(format!("执行{}失败: {}", action.label(), status));
+    }
+    Ok(())
+}
+
+/// 更新待执行动作并通知前端，倒计时已被取消或覆盖时返回 false
+fn publish(app: &AppHandle, generation: u64, pending: Option<PendingAction>) -> bool {
+    let mut current = PENDING.lock();
+    if GENERATION.load(Ordering::SeqCst) != generation {
+        return false;
+    }
+    *current = pending.clone();
+    let _ = app.emit(COMPLETION_ACTION_EVENT, pending);
+    true
+}
+
+/// 任务完成后安排执行完成后动作，覆盖之前未执行的动作
+///
+/// 仍有其他运行中的任务时先等待其结束，倒计时期间有新任务开始也会重新等待
+pub fn schedule_completion_action(app: &AppHandle, action: CompletionAction, task_name: &str) {
+    if action == CompletionAction::None {
+        return;
+    }
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    let task_name = task_name.to_string();
+    log::info!("任务 {} 已完成，准备{}", task_name, action.label());
+
+    thread::spawn(move || {
+        let mut remaining = COUNTDOWN_SECS;
+        loop {
+            let waiting = has_active_tasks();
+            if waiting {
+                remaining = COUNTDOWN_SECS;
+            } else if remaining == 0 {
+                break;
+            }
+            let pending = PendingAction {
+                action,
+                task_name: task_name.clone(),
+                remaining_secs: remaining,
+                waiting,
+            };
+            if !publish(&app, generation, Some(pending)) {
+                return;
+            }
+            thread::sleep(Duration::from_secs(1));
+            if !waiting {
+                remaining -= 1;
+            }
+        }
+
+        if !publish(&app, generation, None) {
+            return;
+        }
+        log::info!("倒计时结束，执行{}", action.label());
+        if let Err(e) = execute(&app, action) {
+            log::error!("{}", e);
+        }
+    });
+}
+
+/// 获取倒计时中的完成后动作
+#[tauri::command]
+pub fn get_pending_completion_action() -> Option<PendingAction> {
+    PENDING.lock().clone()
+}
+
+/// 取消倒计时中的完成后动作
+#[tauri::command]
+pub fn cancel_completion_action(app: AppHandle) -> CmdResult<()> {
+    let mut current = PENDING.lock();
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+    let Some(pending) = current.take() else {
+        return Err(AppError::not_found("没有待执行的完成后动作"));
+    };
+    let _ = app.emit(COMPLETION_ACTION_EVENT, None::<PendingAction>);
+    log::info!(
+        "已取消任务 {} 完成后的{}",
+        pending.task_name,
+        pending.action.label()
+    );
+    Ok(())
+}
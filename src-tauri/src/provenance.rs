@@ -0,0 +1,193 @@
+//! 数据来源声明
+//!
+//! 对外交付时随导出文件注明数据来源：各平台数据量与占比、采集时间范围、坐标系以及各平台的使用许可提醒。
+//! JSON 导出将声明写入 metadata 段，所有格式都在同目录写出「<导出文件>.README.txt」。
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 单个平台的数据来源
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformSource {
+    pub platform: String,
+    pub name: String,
+    pub count: usize,
+    /// 占导出总数的比例 (0-1)
+    pub ratio: f64,
+    pub first_collected: String,
+    pub last_collected: String,
+    pub license: String,
+}
+
+/// 导出数据的来源声明
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceStatement {
+    pub generated_at: String,
+    pub total: usize,
+    pub coord_system: String,
+    /// 采集时间范围 (UTC)，没有数据时为空
+    pub collected_from: Option<String>,
+    pub collected_to: Option<String>,
+    /// 按数据量降序
+    pub platforms: Vec<PlatformSource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub masking: Option<String>,
+}
+
+/// 平台显示名称
+pub fn platform_name(platform: &str) -> &str {
+    match platform {
+        "tianditu" => "天地图",
+        "amap" => "高德地图",
+        "baidu" => "百度地图",
+        "osm" => "OpenStreetMap",
+        "google" => "Google Maps",
+        "bing" => "Bing Maps",
+        "here" => "HERE",
+        "mapbox" => "Mapbox",
+        other => other,
+    }
+}
+
+/// 各平台的使用许可提醒，仅作提示，具体以平台协议为准
+fn license_notice(platform: &str) -> &'static str {
+    match platform {
+        "tianditu" => "天地图服务条款：使用时须标注「数据来源：天地图」，不得用于违法用途",
+        "amap" => "高德开放平台服务协议：数据仅限在授权范围内使用，不得转售、批量分发或建立独立的 POI 数据库",
+        "baidu" => "百度地图开放平台服务条款：数据仅限在授权范围内使用，不得转售、批量分发或建立独立的 POI 数据库",
+        "osm" => "© OpenStreetMap contributors，ODbL 1.0 许可：再分发须署名，衍生数据库须以相同许可共享",
+        "google" => "Google Maps Platform 服务条款：Places 内容不得离线存储或在 Google 地图之外使用",
+        "bing" => "Microsoft Bing Maps 使用条款：数据仅限在授权应用内使用，不得批量导出分发",
+        "here" => "HERE 服务条款：数据仅限在授权范围内使用，展示时须注明 HERE 来源",
+        "mapbox" => "Mapbox 服务条款：临时搜索结果不得永久存储，永久存储需使用 permanent 接口",
+        _ => "请确认该数据来源的使用许可",
+    }
+}
+
+/// 根据导出数据的 (平台, 入库时间) 生成来源声明
+pub fn build_statement(
+    records: &[(String, String)],
+    coord_system: &str,
+    masking: Option<&str>,
+) -> SourceStatement {
+    // 平台 -> (数量, 最早, 最晚)
+    let mut by_platform: BTreeMap<&str, (usize, &str, &str)> = BTreeMap::new();
+    for (platform, created_at) in records {
+        let entry = by_platform.entry(platform.as_str()).or_insert((
+            0,
+            created_at.as_str(),
+            created_at.as_str(),
+        ));
+        entry.0 += 1;
+        entry.1 = entry.1.min(created_at.as_str());
+        entry.2 = entry.2.max(created_at.as_str());
+    }
+
+    let total = records.len();
+    let mut platforms: Vec<PlatformSource> = by_platform
+        .into_iter()
+        .map(|(platform, (count, first, last))| PlatformSource {
+            platform: platform.to_string(),
+            name: platform_name(platform).to_string(),
+            count,
+            ratio: count as f64 / total as f64,
+            first_collected: first.to_string(),
+            last_collected: last.to_string(),
+            license: license_notice(platform).to_string(),
+        })
+        .collect();
+    platforms.sort_by_key(|p| std::cmp::Reverse(p.count));
+
+    SourceStatement {
+        generated_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        total,
+        coord_system: coord_system.to_string(),
+        collected_from: platforms.iter().map(|p| p.first_collected.clone()).min(),
+        collected_to: platforms.iter().map(|p| p.last_collected.clone()).max(),
+        platforms,
+        masking: masking.map(str::to_string),
+    }
+}
+
+/// 来源声明的纯文本说明
+pub fn render_readme(statement: &SourceStatement) -> String {
+    let mut text = String::from("数据来源声明\n============\n\n");
+    text.push_str(&format!("导出时间: {}\n", statement.generated_at));
+    text.push_str(&format!("数据总数: {}\n", statement.total));
+    text.push_str(&format!("坐标系: {}\n", statement.coord_system));
+    if let (Some(from), Some(to)) = (&statement.collected_from, &statement.collected_to) {
+        text.push_str(&format!("采集时间: {} 至 {} (UTC)\n", from, to));
+    }
+    if let Some(policy) = &statement.masking {
+        text.push_str(&format!("脱敏策略: {}\n", policy));
+    }
+
+    text.push_str("\n各平台数据\n----------\n");
+    for source in &statement.platforms {
+        text.push_str(&format!(
+            "{}: {} 条 ({:.1}%)，采集于 {} 至 {}\n",
+            source.name,
+            source.count,
+            source.ratio * 100.0,
+            source.first_collected,
+            source.last_collected
+        ));
+    }
+
+    text.push_str("\n使用许可提醒\n------------\n");
+    for source in &statement.platforms {
+        text.push_str(&format!("- {}\n", source.license));
+    }
+    text.push_str("\n以上提醒仅供参考，对外提供数据前请以各平台最新的服务协议为准。\n");
+    text
+}
+
+/// 在导出文件同目录写出来源说明
+pub fn write_readme(path: &str, statement: &SourceStatement) -> Result<(), String> {
+    std::fs::write(format!("{}.README.txt", path), render_readme(statement))
+        .map_err(|e| format!("写入来源说明失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(platform: &str, created_at: &str) -> (String, String) {
+        (platform.to_string(), created_at.to_string())
+    }
+
+    #[test]
+    fn test_build_statement() {
+        let records = [
+            record("osm", "2024-03-02 08:00:00"),
+            record("amap", "2024-03-05 10:00:00"),
+            record("amap", "2024-03-01 09:30:00"),
+            record("amap", "2024-03-03 12:00:00"),
+        ];
+        let statement = build_statement(&records, "WGS84", None);
+
+        assert_eq!(statement.total, 4);
+        assert_eq!(
+            statement.collected_from.as_deref(),
+            Some("2024-03-01 09:30:00")
+        );
+        assert_eq!(
+            statement.collected_to.as_deref(),
+            Some("2024-03-05 10:00:00")
+        );
+        let amap = &statement.platforms[0];
+        assert_eq!(
+            (amap.name.as_str(), amap.count, amap.ratio),
+            ("高德地图", 3, 0.75)
+        );
+        assert_eq!(amap.last_collected, "2024-03-05 10:00:00");
+        assert_eq!(statement.platforms[1].platform, "osm");
+
+        let readme = render_readme(&statement);
+        assert!(readme.contains("高德地图: 3 条 (75.0%)"));
+        assert!(readme.contains("© OpenStreetMap contributors"));
+
+        let empty = build_statement(&[], "WGS84", None);
+        assert!(empty.platforms.is_empty() && empty.collected_from.is_none());
+    }
+}
@@ -0,0 +1,187 @@
+//! 原始响应存储
+//!
+//! raw_data 保存平台返回的原始 JSON，用于事后按平台分类重建类别，每条有几 KB，
+//! 百万级数据时是数据库体积的主要来源。按设置原样保存、截断、gzip 压缩为 BLOB 或不保存，
+//! 读取时自动识别文本与压缩格式。截断后的 JSON 无法再用于重建类别。
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use rusqlite::types::{Value, ValueRef};
+use serde::Serialize;
+use std::io::{Read, Write};
+
+use crate::commands::with_poi_db;
+use crate::config::{self, RawDataMode, RawDataSettings};
+use crate::error::{AppError, CmdResult};
+
+/// 截断上限的最小值（字节）
+const MIN_MAX_BYTES: usize = 256;
+/// 迁移时每批处理的记录数，批次之间释放数据库锁，不阻塞采集写入
+const COMPACT_BATCH: usize = 2000;
+
+static SETTINGS: Lazy<RwLock<RawDataSettings>> =
+    Lazy::new(|| RwLock::new(config::get_raw_data_settings().unwrap_or_default()));
+
+/// 在不超过 max_bytes 的字符边界处截断
+fn truncate(raw: &str, max_bytes: Option<usize>) -> &str {
+    let Some(max) = max_bytes.filter(|max| raw.len() > *max) else {
+        return raw;
+    };
+    let mut end = max;
+    while !raw.is_char_boundary(end) {
+        end -= 1;
+    }
+    &raw[..end]
+}
+
+fn encode_with(settings: &RawDataSettings, raw: &str) -> Value {
+    let raw = truncate(raw, settings.max_bytes);
+    match settings.mode {
+        RawDataMode::Keep => Value::Text(raw.to_string()),
+        RawDataMode::Discard => Value::Null,
+        RawDataMode::Compress => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            match encoder
+                .write_all(raw.as_bytes())
+                .and_then(|_| encoder.finish())
+            {
+                Ok(bytes) => Value::Blob(bytes),
+                Err(e) => {
+                    log::warn!("压缩原始响应失败: {}", e);
+                    Value::Text(raw.to_string())
+                }
+            }
+        }
+    }
+}
+
+/// 按当前设置转换原始响应，用于写入 raw_data 列
+pub fn encode(raw: &str) -> Value {
+    encode_with(&SETTINGS.read(), raw)
+}
+
+/// 读取 raw_data 列，自动解压 BLOB
+pub fn decode(value: ValueRef) -> Option<String> {
+    match value {
+        ValueRef::Text(text) => Some(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(bytes) => {
+            let mut text = String::new();
+            match GzDecoder::new(bytes).read_to_string(&mut text) {
+                Ok(_) => Some(text),
+                Err(e) => {
+                    log::warn!("解压原始响应失败: {}", e);
+                    None
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+/// 列值占用的字节数
+pub fn stored_len(value: &Value) -> usize {
+    match value {
+        Value::Text(text) => text.len(),
+        Value::Blob(bytes) => bytes.len(),
+        _ => 0,
+    }
+}
+
+#[tauri::command]
+pub fn get_raw_data_settings() -> CmdResult<RawDataSettings> {
+    Ok(SETTINGS.read().clone())
+}
+
+/// 修改原始响应存储方式，只影响之后写入的数据，存量数据通过 compact_raw_data 转换
+#[tauri::command]
+pub fn set_raw_data_settings(settings: RawDataSettings) -> CmdResult<RawDataSettings> {
+    if settings.max_bytes.is_some_and(|max| max < MIN_MAX_BYTES) {
+        return Err(AppError::invalid(format!(
+            "截断上限不能小于 {} 字节",
+            MIN_MAX_BYTES
+        )));
+    }
+    config::set_raw_data_settings(&settings)?;
+    *SETTINGS.write() = settings.clone();
+    Ok(settings)
+}
+
+/// 存量 raw_data 转换结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CompactResult {
+    pub processed: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+/// 按当前设置转换存量 raw_data，vacuum 为 true 时完成后回收数据库空间
+#[tauri::command]
+pub async fn compact_raw_data(vacuum: Option<bool>) -> CmdResult<CompactResult> {
+    let settings = SETTINGS.read().clone();
+    let mut result = CompactResult {
+        processed: 0,
+        bytes_before: 0,
+        bytes_after: 0,
+    };
+    let mut after_id = 0;
+    loop {
+        let batch = with_poi_db(|db| {
+            db.recode_raw_data(after_id, COMPACT_BATCH, |raw| encode_with(&settings, raw))
+                .map_err(|e| format!("转换原始响应失败: {}", e))
+        })?;
+        let Some(last_id) = batch.last_id else {
+            break;
+        };
+        after_id = last_id;
+        result.processed += batch.rows;
+        result.bytes_before += batch.bytes_before;
+        result.bytes_after += batch.bytes_after;
+    }
+
+    if vacuum.unwrap_or(false) {
+        with_poi_db(|db| {
+            db.vacuum()
+                .map_err(|e| format!("回收数据库空间失败: {}", e))
+        })?;
+    }
+    log::info!(
+        "原始响应转换完成: {} 条，{} -> {} 字节",
+        result.processed,
+        result.bytes_before,
+        result.bytes_after
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode() {
+        let raw = r#"{"name":"老街面馆","type":"餐饮服务"}"#;
+        let mut settings = RawDataSettings {
+            mode: RawDataMode::Compress,
+            max_bytes: None,
+        };
+        let Value::Blob(bytes) = encode_with(&settings, raw) else {
+            panic!("应压缩为 BLOB");
+        };
+        assert_eq!(decode(ValueRef::Blob(&bytes)).as_deref(), Some(raw));
+
+        // 截断不会切开多字节字符
+        settings.mode = RawDataMode::Keep;
+        settings.max_bytes = Some(13);
+        assert_eq!(
+            encode_with(&settings, raw),
+            Value::Text(r#"{"name":"老"#.to_string())
+        );
+
+        settings.mode = RawDataMode::Discard;
+        assert_eq!(encode_with(&settings, raw), Value::Null);
+        assert_eq!(decode(ValueRef::Null), None);
+    }
+}
@@ -3,7 +3,7 @@
 //! 从内置 JSON 文件加载省市区数据，支持按层级查询
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::OnceLock;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -124,6 +124,96 @@ pub fn get_all_district_codes(code: &str) -> Vec<String> {
     }
 }
 
+/// 展开区划代码列表，包含各区划自身及其下属区县，结果已去重
+pub fn expand_region_codes(codes: &[String]) -> Vec<String> {
+    let mut expanded: Vec<String> = codes
+        .iter()
+        .flat_map(|code| std::iter::once(code.clone()).chain(get_all_district_codes(code)))
+        .collect();
+    expanded.sort();
+    expanded.dedup();
+    expanded
+}
+
+/// code 是否为 ancestor 的下属区划（不含自身）
+fn is_descendant(code: &str, ancestor: &str) -> bool {
+    let mut current = get_region_by_code(code).and_then(|r| r.parent_code);
+    while let Some(parent) = current {
+        if parent == ancestor {
+            return true;
+        }
+        current = get_region_by_code(&parent).and_then(|r| r.parent_code);
+    }
+    false
+}
+
+/// 采集时排除的下属区域，如采集盐城市但排除亭湖区
+#[derive(Debug, Clone)]
+pub struct RegionExclusion {
+    /// 被排除的区划及其下属区县代码
+    codes: HashSet<String>,
+    /// 被排除区县的名称，原始数据中没有区划代码时按名称匹配
+    names: Vec<String>,
+}
+
+impl RegionExclusion {
+    /// 校验排除的区域均为 region_code 的下属区划并展开到区县，没有排除项时返回 None
+    pub fn new(region_code: &str, exclude_codes: &[String]) -> Result<Option<Self>, String> {
+        let mut codes = HashSet::new();
+        for code in exclude_codes {
+            if !is_descendant(code, region_code) {
+                let name = get_region_by_code(code).map(|r| r.name).unwrap_or_else(|| code.clone());
+                return Err(format!("排除的区域 {} 不属于所选地区", name));
+            }
+            codes.insert(code.clone());
+            codes.extend(get_all_district_codes(code));
+        }
+        if codes.is_empty() {
+            return Ok(None);
+        }
+        let names = codes
+            .iter()
+            .filter_map(|code| get_region_by_code(code))
+            .filter(|r| r.level == "district")
+            .map(|r| r.name)
+            .collect();
+        Ok(Some(Self { codes, names }))
+    }
+
+    /// region_code 下排除后剩余的区县代码
+    pub fn remaining_districts(&self, region_code: &str) -> Vec<String> {
+        get_all_district_codes(region_code)
+            .into_iter()
+            .filter(|code| !self.codes.contains(code))
+            .collect()
+    }
+
+    /// POI 是否落在被排除的区域
+    ///
+    /// 原始数据带区划代码（高德、百度的 adcode）时以代码为准，否则按区县名称（adname、area）
+    /// 或地址中的区县全称匹配。过短的名称（如「城区」）容易误伤，不参与地址匹配。
+    pub fn contains(&self, address: &str, raw: &serde_json::Value) -> bool {
+        let adcode = match raw.get("adcode") {
+            Some(serde_json::Value::String(code)) => Some(code.clone()),
+            Some(serde_json::Value::Number(code)) => Some(code.to_string()),
+            _ => None,
+        };
+        if let Some(adcode) = adcode.filter(|c| !c.is_empty()) {
+            return self.codes.contains(&adcode);
+        }
+
+        let district = ["adname", "area"]
+            .iter()
+            .find_map(|key| raw.get(*key).and_then(|v| v.as_str()));
+        if let Some(district) = district.filter(|d| !d.is_empty()) {
+            return self.names.iter().any(|name| name == district);
+        }
+        self.names
+            .iter()
+            .any(|name| name.chars().count() >= 3 && address.contains(name.as_str()))
+    }
+}
+
 /// 按名称模糊搜索区划
 pub fn search_regions(query: &str) -> Vec<Region> {
     get_all_regions()
@@ -134,6 +224,32 @@ pub fn search_regions(query: &str) -> Vec<Region> {
         .collect()
 }
 
+/// 区划完整名称（省/市/区县）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegionNames {
+    pub province: String,
+    pub city: String,
+    pub district: String,
+}
+
+/// 沿上级链解析区划代码对应的省/市/区县名称
+pub fn resolve_region_names(code: &str) -> RegionNames {
+    let mut names = RegionNames::default();
+    let mut current = get_region_by_code(code);
+
+    while let Some(region) = current {
+        match region.level.as_str() {
+            "province" => names.province = region.name.clone(),
+            "city" => names.city = region.name.clone(),
+            "district" => names.district = region.name.clone(),
+            _ => {}
+        }
+        current = region.parent_code.as_deref().and_then(get_region_by_code);
+    }
+
+    names
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -151,4 +267,31 @@ mod tests {
         assert!(!provinces.is_empty());
         println!("Found {} provinces", provinces.len());
     }
+
+    #[test]
+    fn test_region_exclusion() {
+        // 盐城市排除亭湖区
+        let exclusion = RegionExclusion::new("3209", &["320902".to_string()]).unwrap().unwrap();
+        let remaining = exclusion.remaining_districts("3209");
+        assert!(!remaining.contains(&"320902".to_string()));
+        assert!(remaining.contains(&"320903".to_string()));
+
+        assert!(exclusion.contains("", &serde_json::json!({"adcode": "320902"})));
+        assert!(!exclusion.contains("亭湖区", &serde_json::json!({"adcode": "320903"})));
+        assert!(exclusion.contains("", &serde_json::json!({"area": "亭湖区"})));
+        assert!(exclusion.contains("江苏省盐城市亭湖区解放南路", &serde_json::Value::Null));
+        assert!(!exclusion.contains("江苏省盐城市盐都区", &serde_json::Value::Null));
+
+        assert!(RegionExclusion::new("3209", &[]).unwrap().is_none());
+        // 不属于所选地区的区县
+        assert!(RegionExclusion::new("3209", &["320102".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_region_names() {
+        let names = resolve_region_names("320923");
+        assert_eq!(names.province, "江苏省");
+        assert_eq!(names.city, "盐城市");
+        assert_eq!(names.district, "阜宁县");
+    }
 }
@@ -0,0 +1,192 @@
+//! 采集失败自动重启
+//!
+//! 夜间无人值守时，采集常因临时故障停下：接口诊断为限流、IP 受限时暂停，Key 配额用尽等出错停止。
+//! 启用重启策略后，模板调度线程每次检查时发现这类采集器，按递增间隔（首次为基础间隔，之后逐次翻倍，
+//! 不短于诊断建议的暂停时长）从未完成的类别继续采集，同一任务最多重启 N 次，采集完成后计数清零。
+//! 每次安排与执行重启都写入采集日志。
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use crate::commands::{
+    emit_log, get_collector_statuses, resume_unfinished_collection, CollectorStatus,
+};
+use crate::config::{self, RestartPolicy};
+use crate::error::{AppError, CmdResult};
+
+/// 重启间隔上限
+const MAX_DELAY_SECS: u64 = 6 * 3600;
+/// 调度线程按分钟检查，更短的间隔没有意义
+const MIN_DELAY_SECS: u64 = 60;
+const MAX_RESTARTS: u32 = 20;
+
+/// 各平台的重启记录
+static STATE: Lazy<Mutex<HashMap<String, RestartState>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Default)]
+struct RestartState {
+    /// 已自动重启的次数
+    attempts: u32,
+    /// 最近一次处理过的失败运行，以启动时间标识
+    handled_run: Option<String>,
+    /// 已安排的重启时间
+    due: Option<Instant>,
+}
+
+/// 因故障停止、需要自动重启的采集器：出错停止，或经接口诊断暂停（用户暂停与预算暂停不含诊断类别）
+fn is_failed(status: &CollectorStatus) -> bool {
+    status.status == "error" || (status.status == "paused" && status.error_kind.is_some())
+}
+
+/// 第 attempt 次重启前等待的秒数
+fn restart_delay(policy: &RestartPolicy, attempt: u32, suggested_secs: Option<u64>) -> u64 {
+    let factor = 1u64 << attempt.saturating_sub(1).min(16);
+    policy
+        .base_delay_secs
+        .saturating_mul(factor)
+        .min(MAX_DELAY_SECS)
+        .max(suggested_secs.unwrap_or(0))
+}
+
+/// 检查失败的采集器并按策略安排或执行重启，由模板调度线程定期调用
+pub fn check_failed(app: &AppHandle) {
+    let policy = match config::get_restart_policy() {
+        Ok(policy) if policy.enabled => policy,
+        Ok(_) => {
+            STATE.lock().clear();
+            return;
+        }
+        Err(e) => {
+            log::warn!("读取重启策略失败: {}", e);
+            return;
+        }
+    };
+
+    let mut due_platforms = Vec::new();
+    {
+        let mut state = STATE.lock();
+        for status in get_collector_statuses().into_values() {
+            if status.status == "completed" {
+                state.remove(&status.platform);
+                continue;
+            }
+            if !is_failed(&status) {
+                continue;
+            }
+
+            let entry = state.entry(status.platform.clone()).or_default();
+            let run = status.started_at.clone().unwrap_or_default();
+            if entry.handled_run.as_deref() != Some(run.as_str()) {
+                // 新的一次失败
+                entry.handled_run = Some(run);
+                entry.due = None;
+                if entry.attempts >= policy.max_restarts {
+                    emit_log(
+                        app,
+                        &format!(
+                            "[{}] 已自动重启 {} 次仍失败，不再重启",
+                            status.platform, entry.attempts
+                        ),
+                    );
+                    continue;
+                }
+                let delay = restart_delay(&policy, entry.attempts + 1, status.suggested_pause_secs);
+                entry.due = Some(Instant::now() + Duration::from_secs(delay));
+                emit_log(
+                    app,
+                    &format!(
+                        "[{}] 采集已停止（{}），{} 分钟后第 {}/{} 次自动重启",
+                        status.platform,
+                        status.error_message.as_deref().unwrap_or("未知错误"),
+                        delay.div_ceil(60),
+                        entry.attempts + 1,
+                        policy.max_restarts
+                    ),
+                );
+            } else if entry.due.is_some_and(|due| Instant::now() >= due) {
+                entry.due = None;
+                entry.attempts += 1;
+                due_platforms.push((status.platform.clone(), entry.attempts));
+            }
+        }
+    }
+
+    // 重启会读取采集器状态，在释放记录锁后执行
+    for (platform, attempt) in due_platforms {
+        emit_log(
+            app,
+            &format!(
+                "[{}] 第 {}/{} 次自动重启，继续未完成的类别",
+                platform, attempt, policy.max_restarts
+            ),
+        );
+        if let Err(e) = resume_unfinished_collection(app.clone(), platform.clone()) {
+            emit_log(app, &format!("[{}] 自动重启失败: {}", platform, e));
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_restart_policy() -> CmdResult<RestartPolicy> {
+    config::get_restart_policy().map_err(AppError::from)
+}
+
+/// 修改重启策略，下次检查时生效
+#[tauri::command]
+pub fn set_restart_policy(policy: RestartPolicy) -> CmdResult<RestartPolicy> {
+    if policy.base_delay_secs < MIN_DELAY_SECS {
+        return Err(AppError::invalid("重启间隔不能小于 60 秒"));
+    }
+    if policy.max_restarts == 0 || policy.max_restarts > MAX_RESTARTS {
+        return Err(AppError::invalid(format!(
+            "重启次数须在 1 到 {} 之间",
+            MAX_RESTARTS
+        )));
+    }
+    config::set_restart_policy(&policy)?;
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_delay() {
+        let policy = RestartPolicy {
+            enabled: true,
+            max_restarts: 3,
+            base_delay_secs: 300,
+        };
+        assert_eq!(restart_delay(&policy, 1, None), 300);
+        assert_eq!(restart_delay(&policy, 2, None), 600);
+        assert_eq!(restart_delay(&policy, 3, None), 1200);
+        // 不短于诊断建议的暂停时长
+        assert_eq!(restart_delay(&policy, 1, Some(3600)), 3600);
+        assert_eq!(restart_delay(&policy, 30, None), MAX_DELAY_SECS);
+
+        let mut status = CollectorStatus {
+            platform: "amap".to_string(),
+            status: "paused".to_string(),
+            total_collected: 0,
+            completed_categories: vec![],
+            current_category_id: String::new(),
+            error_message: None,
+            total_categories: 0,
+            started_at: None,
+            error_kind: None,
+            suggested_pause_secs: None,
+            requests_per_minute: 0.0,
+            pois_per_minute: 0.0,
+            last_message: None,
+        };
+        assert!(!is_failed(&status));
+        status.error_kind = Some("rate_limited".to_string());
+        assert!(is_failed(&status));
+        status.status = "running".to_string();
+        assert!(!is_failed(&status));
+    }
+}
@@ -0,0 +1,238 @@
+//! 多方言 SQL 导出
+//!
+//! 按目标库生成建表语句与 INSERT 语句，类型映射、字符串转义与事务包装随方言变化。
+//! 达梦按 Oracle 兼容语法生成，不使用 IF NOT EXISTS；人大金仓按 PostgreSQL 兼容语法生成。
+
+use std::borrow::Cow;
+
+/// 目标数据库
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlDialect {
+    Mysql,
+    Sqlite,
+    /// 达梦 DM8
+    Dameng,
+    /// 人大金仓 KingbaseES
+    Kingbase,
+}
+
+/// 列类型
+#[derive(Debug, Clone, Copy)]
+pub enum SqlType {
+    BigInt,
+    Int,
+    Double,
+    /// 可变长字符串，长度按字符计
+    Varchar(u32),
+}
+
+/// 列定义
+pub struct Column {
+    pub name: Cow<'static, str>,
+    pub ty: SqlType,
+    pub not_null: bool,
+    pub primary_key: bool,
+}
+
+impl Column {
+    pub const fn new(name: &'static str, ty: SqlType) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            ty,
+            not_null: false,
+            primary_key: false,
+        }
+    }
+
+    /// 列名在运行时确定（如导出的扩展列）
+    pub fn named(name: String, ty: SqlType) -> Self {
+        Self {
+            name: Cow::Owned(name),
+            ty,
+            not_null: false,
+            primary_key: false,
+        }
+    }
+
+    pub const fn not_null(mut self) -> Self {
+        self.not_null = true;
+        self
+    }
+
+    pub const fn primary_key(mut self) -> Self {
+        self.primary_key = true;
+        self
+    }
+}
+
+/// 列值
+pub enum SqlValue<'a> {
+    Int(i64),
+    Float(f64),
+    Text(&'a str),
+}
+
+impl SqlDialect {
+    /// 由导出格式解析方言，非 SQL 格式返回 None
+    pub fn from_format(format: &str) -> Option<Self> {
+        match format {
+            "mysql" => Some(Self::Mysql),
+            "sqlite" => Some(Self::Sqlite),
+            "dameng" => Some(Self::Dameng),
+            "kingbase" => Some(Self::Kingbase),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Mysql => "MySQL",
+            Self::Sqlite => "SQLite",
+            Self::Dameng => "达梦 DM8",
+            Self::Kingbase => "人大金仓 KingbaseES",
+        }
+    }
+
+    /// 是否在文件头写入 UTF-8 BOM；命令行客户端导入带 BOM 的脚本会报错，只有 MySQL 保留
+    pub fn with_bom(self) -> bool {
+        self == Self::Mysql
+    }
+
+    fn type_name(self, ty: SqlType) -> String {
+        match (self, ty) {
+            (Self::Sqlite, SqlType::BigInt | SqlType::Int) => "INTEGER".to_string(),
+            (Self::Sqlite, SqlType::Double) => "REAL".to_string(),
+            (Self::Sqlite, SqlType::Varchar(_)) => "TEXT".to_string(),
+            (Self::Kingbase, SqlType::Double) => "DOUBLE PRECISION".to_string(),
+            (Self::Kingbase, SqlType::Int) => "INTEGER".to_string(),
+            // 达梦默认按字节计长度，UTF-8 下一个汉字占 3 字节，显式按字符计
+            (Self::Dameng, SqlType::Varchar(len)) => format!("VARCHAR({} CHAR)", len),
+            (_, SqlType::BigInt) => "BIGINT".to_string(),
+            (_, SqlType::Int) => "INT".to_string(),
+            (_, SqlType::Double) => "DOUBLE".to_string(),
+            (_, SqlType::Varchar(len)) => format!("VARCHAR({})", len),
+        }
+    }
+
+    /// 字符串字面量；MySQL 默认把反斜杠当作转义符，需额外转义
+    pub fn quote(self, value: &str) -> String {
+        let escaped = value.replace('\'', "''");
+        let escaped = match self {
+            Self::Mysql => escaped.replace('\\', "\\\\"),
+            _ => escaped,
+        };
+        format!("'{}'", escaped)
+    }
+
+    fn value(self, value: &SqlValue) -> String {
+        match value {
+            SqlValue::Int(v) => v.to_string(),
+            SqlValue::Float(v) if v.is_finite() => v.to_string(),
+            SqlValue::Float(_) => "NULL".to_string(),
+            SqlValue::Text(v) => self.quote(v),
+        }
+    }
+
+    /// 脚本开头：字符集设置与事务开始
+    pub fn begin(self) -> &'static str {
+        match self {
+            Self::Mysql => "SET NAMES utf8mb4;\n\n",
+            Self::Sqlite => "BEGIN TRANSACTION;\n\n",
+            Self::Dameng => "",
+            Self::Kingbase => "SET client_encoding = 'UTF8';\nBEGIN;\n\n",
+        }
+    }
+
+    /// 脚本结尾：提交事务
+    pub fn end(self) -> &'static str {
+        match self {
+            Self::Mysql => "",
+            Self::Sqlite | Self::Dameng | Self::Kingbase => "\nCOMMIT;\n",
+        }
+    }
+
+    pub fn create_table(self, table: &str, columns: &[Column]) -> String {
+        let defs: Vec<String> = columns
+            .iter()
+            .map(|column| {
+                let mut def = format!("  {} {}", column.name, self.type_name(column.ty));
+                if column.primary_key {
+                    def.push_str(" PRIMARY KEY");
+                } else if column.not_null {
+                    def.push_str(" NOT NULL");
+                }
+                def
+            })
+            .collect();
+        let (create, suffix) = match self {
+            Self::Mysql => (
+                "CREATE TABLE IF NOT EXISTS",
+                " ENGINE=InnoDB DEFAULT CHARSET=utf8mb4",
+            ),
+            Self::Dameng => ("CREATE TABLE", ""),
+            Self::Sqlite | Self::Kingbase => ("CREATE TABLE IF NOT EXISTS", ""),
+        };
+        format!(
+            "{} {} (\n{}\n){};\n\n",
+            create,
+            table,
+            defs.join(",\n"),
+            suffix
+        )
+    }
+
+    pub fn insert(self, table: &str, columns: &[Column], values: &[SqlValue]) -> String {
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_ref()).collect();
+        let values: Vec<String> = values.iter().map(|v| self.value(v)).collect();
+        format!(
+            "INSERT INTO {} ({}) VALUES ({});\n",
+            table,
+            names.join(", "),
+            values.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dialects() {
+        let columns = [
+            Column::new("id", SqlType::BigInt).primary_key(),
+            Column::new("name", SqlType::Varchar(255)).not_null(),
+            Column::new("lon", SqlType::Double).not_null(),
+        ];
+        let row = [
+            SqlValue::Int(1),
+            SqlValue::Text(r"O'Neil\店"),
+            SqlValue::Float(120.5),
+        ];
+
+        let mysql = SqlDialect::Mysql;
+        assert!(mysql
+            .create_table("poi", &columns)
+            .contains("name VARCHAR(255) NOT NULL"));
+        assert_eq!(
+            mysql.insert("poi", &columns, &row),
+            "INSERT INTO poi (id, name, lon) VALUES (1, 'O''Neil\\\\店', 120.5);\n"
+        );
+
+        let dameng = SqlDialect::Dameng;
+        let create = dameng.create_table("poi", &columns);
+        assert!(create.starts_with("CREATE TABLE poi ("));
+        assert!(create.contains("VARCHAR(255 CHAR)"));
+        assert!(dameng
+            .insert("poi", &columns, &row)
+            .contains(r"'O''Neil\店'"));
+
+        assert!(SqlDialect::Kingbase
+            .create_table("poi", &columns)
+            .contains("lon DOUBLE PRECISION NOT NULL"));
+        assert!(SqlDialect::Sqlite
+            .create_table("poi", &columns)
+            .contains("id INTEGER PRIMARY KEY"));
+        assert_eq!(SqlDialect::from_format("excel"), None);
+    }
+}
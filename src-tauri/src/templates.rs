@@ -0,0 +1,206 @@
+//! 采集任务模板
+//!
+//! 模板保存平台、地区与类别的组合，可一键运行；设置了运行间隔的模板由后台调度线程
+//! 到期自动运行，实现周期性采集。调度线程同时按重启策略重启失败的采集，见 restart 模块。
+
+use chrono::{Duration as ChronoDuration, Local, NaiveDateTime};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::commands::{get_collector_statuses, start_collector};
+use crate::config::{self, CollectTemplate};
+use crate::error::{AppError, CmdResult};
+use crate::events;
+
+/// 调度线程检查到期模板的间隔
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(60);
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// 模板运行结果
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateRunResult {
+    pub template_id: String,
+    /// 已启动的平台
+    pub started: Vec<String>,
+    /// 启动失败的平台及原因
+    pub failed: Vec<(String, String)>,
+}
+
+/// 模板是否到期：以上次运行时间（未运行过时为保存时间）加运行间隔判断
+fn is_due(template: &CollectTemplate, now: NaiveDateTime) -> bool {
+    let Some(days) = template.interval_days.filter(|d| *d > 0) else {
+        return false;
+    };
+    let base = template
+        .last_run_at
+        .as_deref()
+        .unwrap_or(&template.updated_at);
+    match NaiveDateTime::parse_from_str(base, TIME_FORMAT) {
+        Ok(base) => now >= base + ChronoDuration::days(days as i64),
+        Err(_) => true,
+    }
+}
+
+#[tauri::command]
+pub fn list_collect_templates() -> CmdResult<Vec<CollectTemplate>> {
+    config::list_collect_templates().map_err(AppError::from)
+}
+
+/// 新增或修改采集模板，id 为空时新建
+#[tauri::command]
+pub fn save_collect_template(template: CollectTemplate) -> CmdResult<CollectTemplate> {
+    let mut template = template;
+    template.name = template.name.trim().to_string();
+    if template.name.is_empty() {
+        return Err(AppError::invalid("请输入模板名称"));
+    }
+    let mut seen = HashSet::new();
+    template
+        .platforms
+        .retain(|p| !p.trim().is_empty() && seen.insert(p.clone()));
+    if template.platforms.is_empty() {
+        return Err(AppError::invalid("请至少选择一个平台"));
+    }
+    if template.id.is_empty() {
+        template.id = uuid::Uuid::new_v4().to_string();
+    } else if let Ok(Some(existing)) = config::get_collect_template(&template.id) {
+        // 运行记录由后端维护，编辑模板时保留
+        template.last_run_at = existing.last_run_at;
+    }
+
+    template.updated_at = Local::now().format(TIME_FORMAT).to_string();
+    config::save_collect_template(template.clone())?;
+    Ok(template)
+}
+
+#[tauri::command]
+pub fn delete_collect_template(id: String) -> CmdResult<bool> {
+    config::delete_collect_template(&id).map_err(AppError::from)
+}
+
+/// 按模板依次启动各平台采集，单个平台启动失败不影响其他平台
+#[tauri::command]
+pub fn run_template(app: AppHandle, id: String) -> CmdResult<TemplateRunResult> {
+    let mut template = config::get_collect_template(&id)?
+        .ok_or_else(|| AppError::not_found(format!("未找到采集模板: {}", id)))?;
+
+    let mut result = TemplateRunResult {
+        template_id: template.id.clone(),
+        started: Vec::new(),
+        failed: Vec::new(),
+    };
+    for platform in &template.platforms {
+        match start_collector(
+            app.clone(),
+            platform.clone(),
+            template.categories.clone(),
+            template.regions.clone(),
+            template.keywords.clone(),
+            template.area_id.clone(),
+            template.exclude_codes.clone(),
+            None,
+            Some(template.grid),
+            None,
+            None,
+        ) {
+            Ok(()) => result.started.push(platform.clone()),
+            Err(e) => result.failed.push((platform.clone(), e.message)),
+        }
+    }
+
+    // 无论成败都记录运行时间，避免调度线程对持续失败的模板反复重试
+    template.last_run_at = Some(Local::now().format(TIME_FORMAT).to_string());
+    config::save_collect_template(template.clone())?;
+
+    if result.started.is_empty() {
+        let reasons: Vec<String> = result
+            .failed
+            .iter()
+            .map(|(platform, e)| format!("{}: {}", platform, e))
+            .collect();
+        return Err(AppError::conflict(format!(
+            "模板「{}」未能启动任何平台（{}）",
+            template.name,
+            reasons.join("；")
+        )));
+    }
+    Ok(result)
+}
+
+/// 启动模板调度线程，定期运行到期的模板并重启失败的采集
+pub fn start_scheduler(app: AppHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(SCHEDULER_INTERVAL);
+        crate::restart::check_failed(&app);
+
+        let templates = match config::list_collect_templates() {
+            Ok(templates) => templates,
+            Err(e) => {
+                log::warn!("读取采集模板失败: {}", e);
+                continue;
+            }
+        };
+
+        let now = Local::now().naive_local();
+        for template in templates.iter().filter(|t| is_due(t, now)) {
+            // 模板中的平台仍在采集时顺延到下次检查
+            let statuses = get_collector_statuses();
+            let busy = template
+                .platforms
+                .iter()
+                .any(|p| statuses.get(p).is_some_and(|s| s.status == "running"));
+            if busy {
+                continue;
+            }
+
+            log::info!("定时运行采集模板: {}", template.name);
+            let message = match run_template(app.clone(), template.id.clone()) {
+                Ok(result) => format!(
+                    "定时运行采集模板「{}」，已启动: {}",
+                    template.name,
+                    result.started.join("、")
+                ),
+                Err(e) => format!("定时运行采集模板「{}」失败: {}", template.name, e),
+            };
+            events::emit_log(&app, message);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_due() {
+        let mut template = CollectTemplate {
+            id: "t".to_string(),
+            name: "月度采集".to_string(),
+            platforms: vec!["amap".to_string()],
+            regions: None,
+            categories: None,
+            keywords: None,
+            area_id: None,
+            exclude_codes: None,
+            grid: false,
+            interval_days: Some(30),
+            last_run_at: None,
+            updated_at: "2024-05-01 08:00:00".to_string(),
+        };
+        let at = |s: &str| NaiveDateTime::parse_from_str(s, TIME_FORMAT).unwrap();
+
+        assert!(!is_due(&template, at("2024-05-30 08:00:00")));
+        assert!(is_due(&template, at("2024-05-31 08:00:00")));
+
+        template.last_run_at = Some("2024-05-31 09:00:00".to_string());
+        assert!(!is_due(&template, at("2024-06-01 09:00:00")));
+        assert!(is_due(&template, at("2024-06-30 09:00:00")));
+
+        template.interval_days = None;
+        assert!(!is_due(&template, at("2025-01-01 00:00:00")));
+    }
+}
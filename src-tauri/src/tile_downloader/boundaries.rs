@@ -1,3 +1,4 @@
+use crate::error::{AppError, CmdResult, ErrorCode};
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
 use reqwest::Client;
@@ -17,6 +18,13 @@ static HTTP_CLIENT: Lazy<Client> = Lazy::new(|| {
 static BOUNDARY_CACHE: Lazy<RwLock<HashMap<String, Value>>> =
     Lazy::new(|| RwLock::new(HashMap::new()));
 
+/// 简化容差分级（度），请求的容差向下取到最近的一级，同一级共用缓存
+const SIMPLIFY_LEVELS: [f64; 5] = [0.0001, 0.0005, 0.001, 0.005, 0.01];
+
+// 简化后的边界缓存，键为 (区划代码, 容差级别)
+static SIMPLIFIED_CACHE: Lazy<RwLock<HashMap<(String, usize), Value>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegionBounds {
     pub north: f64,
@@ -28,23 +36,59 @@ pub struct RegionBounds {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BoundaryResult {
     pub geojson: Value,
+    /// 按原始边界计算，不受简化影响
     pub bounds: RegionBounds,
+    /// 实际使用的简化容差（度），未简化时为空
+    #[serde(default)]
+    pub tolerance: Option<f64>,
+}
+
+/// 获取行政区边界，tolerance 为 Douglas-Peucker 简化容差（度），为空或小于最低一级时返回原始边界
+///
+/// DataV 边界点数极多，地图渲染与点内判断都慢；简化结果按容差分级缓存。
+#[tauri::command]
+pub async fn get_region_boundary(
+    region_code: String,
+    tolerance: Option<f64>,
+) -> CmdResult<BoundaryResult> {
+    let geojson = load_boundary(&region_code).await?;
+    let bounds = extract_bounds(&geojson);
+
+    let Some(level) = tolerance.and_then(simplify_level) else {
+        return Ok(BoundaryResult {
+            geojson,
+            bounds,
+            tolerance: None,
+        });
+    };
+    let tolerance = SIMPLIFY_LEVELS[level];
+    let key = (region_code, level);
+
+    if let Some(simplified) = SIMPLIFIED_CACHE.read().get(&key) {
+        return Ok(BoundaryResult {
+            geojson: simplified.clone(),
+            bounds,
+            tolerance: Some(tolerance),
+        });
+    }
+
+    let mut simplified = geojson;
+    simplify_geojson(&mut simplified, tolerance);
+    SIMPLIFIED_CACHE.write().insert(key, simplified.clone());
+
+    Ok(BoundaryResult {
+        geojson: simplified,
+        bounds,
+        tolerance: Some(tolerance),
+    })
 }
 
 /// 从阿里云 DataV.GeoAtlas 获取行政区边界
 /// API: https://geo.datav.aliyun.com/areas_v3/bound/{code}_full.json
-#[tauri::command]
-pub async fn get_region_boundary(region_code: String) -> Result<BoundaryResult, String> {
+async fn load_boundary(region_code: &str) -> CmdResult<Value> {
     // 检查缓存
-    {
-        let cache = BOUNDARY_CACHE.read();
-        if let Some(geojson) = cache.get(&region_code) {
-            let bounds = extract_bounds(geojson);
-            return Ok(BoundaryResult {
-                geojson: geojson.clone(),
-                bounds,
-            });
-        }
+    if let Some(geojson) = BOUNDARY_CACHE.read().get(region_code) {
+        return Ok(geojson.clone());
     }
 
     // 根据代码长度补全并确定 URL
@@ -54,7 +98,7 @@ pub async fn get_region_boundary(region_code: String) -> Result<BoundaryResult,
     let (padded_code, use_full) = match region_code.len() {
         2 => (format!("{}0000", region_code), true), // 省级: 11 -> 110000
         4 => (format!("{}00", region_code), true),   // 市级: 1101 -> 110100
-        _ => (region_code.clone(), false),           // 区县级: 110101
+        _ => (region_code.to_string(), false),       // 区县级: 110101
     };
 
     let url = if use_full {
@@ -79,7 +123,10 @@ pub async fn get_region_boundary(region_code: String) -> Result<BoundaryResult,
         .map_err(|e| format!("请求边界数据失败: {}", e))?;
 
     if !response.status().is_success() {
-        return Err(format!("获取边界失败: HTTP {}", response.status()));
+        return Err(AppError::new(
+            ErrorCode::Network,
+            format!("获取边界失败: HTTP {}", response.status()),
+        ));
     }
 
     let geojson: Value = response
@@ -87,16 +134,121 @@ pub async fn get_region_boundary(region_code: String) -> Result<BoundaryResult,
         .await
         .map_err(|e| format!("解析边界数据失败: {}", e))?;
 
-    // 计算边界框
-    let bounds = extract_bounds(&geojson);
-
     // 存入缓存
-    {
-        let mut cache = BOUNDARY_CACHE.write();
-        cache.insert(region_code, geojson.clone());
+    BOUNDARY_CACHE
+        .write()
+        .insert(region_code.to_string(), geojson.clone());
+
+    Ok(geojson)
+}
+
+/// 容差对应的级别：不超过容差的最大一级
+fn simplify_level(tolerance: f64) -> Option<usize> {
+    SIMPLIFY_LEVELS.iter().rposition(|level| *level <= tolerance)
+}
+
+/// 原地简化 GeoJSON 中的线与面，面的环至少保留 4 个点，简化后点数不足时保留原环
+fn simplify_geojson(value: &mut Value, tolerance: f64) {
+    let Value::Object(obj) = value else {
+        return;
+    };
+
+    let geometry_type = obj.get("type").and_then(|t| t.as_str()).map(str::to_string);
+    if let (Some(geometry_type), Some(coordinates)) = (geometry_type, obj.get_mut("coordinates")) {
+        match geometry_type.as_str() {
+            "LineString" => simplify_ring(coordinates, tolerance, 2),
+            "MultiLineString" => for_each_item(coordinates, |line| simplify_ring(line, tolerance, 2)),
+            "Polygon" => for_each_item(coordinates, |ring| simplify_ring(ring, tolerance, 4)),
+            "MultiPolygon" => for_each_item(coordinates, |polygon| {
+                for_each_item(polygon, |ring| simplify_ring(ring, tolerance, 4))
+            }),
+            _ => {}
+        }
     }
 
-    Ok(BoundaryResult { geojson, bounds })
+    for key in ["features", "geometries"] {
+        if let Some(items) = obj.get_mut(key) {
+            for_each_item(items, |item| simplify_geojson(item, tolerance));
+        }
+    }
+    if let Some(geometry) = obj.get_mut("geometry") {
+        simplify_geojson(geometry, tolerance);
+    }
+}
+
+fn for_each_item(value: &mut Value, mut f: impl FnMut(&mut Value)) {
+    if let Value::Array(items) = value {
+        items.iter_mut().for_each(&mut f);
+    }
+}
+
+/// 简化 [[lon, lat], ...] 坐标串，无法解析时保持不变
+fn simplify_ring(value: &mut Value, tolerance: f64, min_points: usize) {
+    let Some(points) = value.as_array().and_then(|items| {
+        items
+            .iter()
+            .map(|p| Some((p.get(0)?.as_f64()?, p.get(1)?.as_f64()?)))
+            .collect::<Option<Vec<_>>>()
+    }) else {
+        return;
+    };
+
+    let simplified = douglas_peucker(&points, tolerance);
+    if simplified.len() < min_points || simplified.len() == points.len() {
+        return;
+    }
+    *value = Value::Array(
+        simplified
+            .into_iter()
+            .map(|(lon, lat)| serde_json::json!([lon, lat]))
+            .collect(),
+    );
+}
+
+/// 点到线段的距离（按经纬度平面计算）
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len_sq = dx * dx + dy * dy;
+    let t = if len_sq == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len_sq).clamp(0.0, 1.0)
+    };
+    ((p.0 - a.0 - t * dx).powi(2) + (p.1 - a.1 - t * dy).powi(2)).sqrt()
+}
+
+/// Douglas-Peucker 抽稀，保留首尾点；用显式栈避免长边界递归过深
+fn douglas_peucker(points: &[(f64, f64)], tolerance: f64) -> Vec<(f64, f64)> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let last = points.len() - 1;
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[last] = true;
+
+    let mut stack = vec![(0, last)];
+    while let Some((start, end)) = stack.pop() {
+        let mut farthest = (start, 0.0);
+        for i in start + 1..end {
+            let distance = segment_distance(points[i], points[start], points[end]);
+            if distance > farthest.1 {
+                farthest = (i, distance);
+            }
+        }
+        if farthest.1 > tolerance {
+            keep[farthest.0] = true;
+            stack.push((start, farthest.0));
+            stack.push((farthest.0, end));
+        }
+    }
+
+    points
+        .iter()
+        .zip(keep)
+        .filter_map(|(point, kept)| kept.then_some(*point))
+        .collect()
 }
 
 /// 从 GeoJSON 提取边界框
@@ -162,7 +314,34 @@ fn extract_bounds(geojson: &Value) -> RegionBounds {
 /// 清除边界缓存
 #[tauri::command]
 pub fn clear_boundary_cache() {
-    let mut cache = BOUNDARY_CACHE.write();
-    cache.clear();
+    BOUNDARY_CACHE.write().clear();
+    SIMPLIFIED_CACHE.write().clear();
     log::info!("边界缓存已清除");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simplify() {
+        let line = [(0.0, 0.0), (1.0, 0.0005), (2.0, 0.0), (3.0, 1.0), (4.0, 0.0)];
+        assert_eq!(
+            douglas_peucker(&line, 0.001),
+            vec![(0.0, 0.0), (2.0, 0.0), (3.0, 1.0), (4.0, 0.0)]
+        );
+
+        // 面的环点数不足时保留原环
+        let mut polygon = serde_json::json!({
+            "type": "Polygon",
+            "coordinates": [[[0.0, 0.0], [1.0, 0.0001], [2.0, 0.0], [0.0, 0.0]]]
+        });
+        let original = polygon.clone();
+        simplify_geojson(&mut polygon, 0.001);
+        assert_eq!(polygon, original);
+
+        assert_eq!(simplify_level(0.00005), None);
+        assert_eq!(simplify_level(0.002), Some(2));
+        assert_eq!(simplify_level(1.0), Some(4));
+    }
+}
@@ -0,0 +1,94 @@
+//! 跨任务共享的瓦片缓存
+//!
+//! 按平台/地图类型/z/x/y 索引原始瓦片数据。开启共享缓存的任务先查缓存，
+//! 缺失的瓦片下载后写回，相邻范围的任务不再重复下载重叠瓦片。
+
+use super::types::TileCoord;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension, Result};
+use serde::Serialize;
+use std::path::Path;
+
+/// 共享缓存统计
+#[derive(Debug, Clone, Serialize)]
+pub struct TileCacheStats {
+    pub tile_count: u64,
+    pub total_size_bytes: u64,
+}
+
+pub struct TileCache {
+    conn: Mutex<Connection>,
+}
+
+impl TileCache {
+    pub fn new(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.busy_timeout(crate::database::BUSY_TIMEOUT)?;
+        conn.execute_batch(
+            r#"
+            PRAGMA journal_mode=WAL;
+
+            CREATE TABLE IF NOT EXISTS tiles (
+                platform TEXT NOT NULL,
+                map_type TEXT NOT NULL,
+                z INTEGER NOT NULL,
+                x INTEGER NOT NULL,
+                y INTEGER NOT NULL,
+                data BLOB NOT NULL,
+                created_at TEXT NOT NULL,
+                PRIMARY KEY (platform, map_type, z, x, y)
+            );
+            "#,
+        )?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// 读取缓存的瓦片
+    pub fn get(&self, platform: &str, map_type: &str, tile: &TileCoord) -> Result<Option<Vec<u8>>> {
+        self.conn
+            .lock()
+            .prepare_cached(
+                "SELECT data FROM tiles WHERE platform = ?1 AND map_type = ?2 AND z = ?3 AND x = ?4 AND y = ?5",
+            )?
+            .query_row(params![platform, map_type, tile.z, tile.x, tile.y], |row| row.get(0))
+            .optional()
+    }
+
+    /// 写入瓦片，已存在时覆盖
+    pub fn put(&self, platform: &str, map_type: &str, tile: &TileCoord, data: &[u8]) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn
+            .lock()
+            .prepare_cached(
+                "INSERT OR REPLACE INTO tiles (platform, map_type, z, x, y, data, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?
+            .execute(params![platform, map_type, tile.z, tile.x, tile.y, data, now])?;
+        Ok(())
+    }
+
+    pub fn stats(&self) -> Result<TileCacheStats> {
+        self.conn.lock().query_row(
+            "SELECT COUNT(*), COALESCE(SUM(LENGTH(data)), 0) FROM tiles",
+            [],
+            |row| {
+                Ok(TileCacheStats {
+                    tile_count: row.get::<_, i64>(0)? as u64,
+                    total_size_bytes: row.get::<_, i64>(1)? as u64,
+                })
+            },
+        )
+    }
+
+    /// 清空缓存，platform 指定时只清理该平台，返回删除条数
+    pub fn clear(&self, platform: Option<&str>) -> Result<usize> {
+        let conn = self.conn.lock();
+        let count = match platform {
+            Some(platform) => conn.execute("DELETE FROM tiles WHERE platform = ?1", params![platform])?,
+            None => conn.execute("DELETE FROM tiles", [])?,
+        };
+        conn.execute_batch("VACUUM")?;
+        Ok(count)
+    }
+}
@@ -0,0 +1,248 @@
+//! 按多边形裁剪下载范围
+//!
+//! 从 GeoJSON 读取面要素作为下载范围，以外接矩形作为任务边界，只下载与多边形相交的瓦片。
+//! 多边形顶点投影到瓦片坐标后逐行扫描：边界穿过的瓦片与扫描线落在面内的瓦片都保留，
+//! 内环（洞）按奇偶规则自然排除。
+
+use super::downloader::build_estimate;
+use super::types::{Bounds, ClipPolygon, TileCoord, TileEstimate};
+use serde_json::Value;
+use std::path::Path;
+
+/// Web Mercator 可表示的纬度范围
+const MAX_LAT: f64 = 85.0511;
+
+/// 读取本地 GeoJSON 文件中的面要素
+pub fn load_geojson_file(path: &Path) -> Result<ClipPolygon, String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("读取 GeoJSON 文件失败: {}", e))?;
+    let value: Value =
+        serde_json::from_str(&content).map_err(|e| format!("解析 GeoJSON 失败: {}", e))?;
+    parse_geojson(&value)
+}
+
+/// 提取 GeoJSON 中所有 Polygon/MultiPolygon 的环，忽略点线要素
+pub fn parse_geojson(value: &Value) -> Result<ClipPolygon, String> {
+    let mut rings = Vec::new();
+    collect_rings(value, &mut rings)?;
+    if rings.is_empty() {
+        return Err("GeoJSON 中没有面要素".to_string());
+    }
+    Ok(rings)
+}
+
+fn collect_rings(value: &Value, rings: &mut ClipPolygon) -> Result<(), String> {
+    match value.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => {
+            for feature in value["features"].as_array().into_iter().flatten() {
+                collect_rings(feature, rings)?;
+            }
+        }
+        Some("Feature") => collect_rings(&value["geometry"], rings)?,
+        Some("GeometryCollection") => {
+            for geometry in value["geometries"].as_array().into_iter().flatten() {
+                collect_rings(geometry, rings)?;
+            }
+        }
+        Some("Polygon") => push_polygon(&value["coordinates"], rings)?,
+        Some("MultiPolygon") => {
+            for polygon in value["coordinates"].as_array().into_iter().flatten() {
+                push_polygon(polygon, rings)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn push_polygon(coordinates: &Value, rings: &mut ClipPolygon) -> Result<(), String> {
+    for ring in coordinates.as_array().into_iter().flatten() {
+        let mut points = Vec::new();
+        for point in ring.as_array().into_iter().flatten() {
+            let (Some(lon), Some(lat)) = (point[0].as_f64(), point[1].as_f64()) else {
+                return Err("GeoJSON 坐标格式无效".to_string());
+            };
+            if !(-180.0..=180.0).contains(&lon) || !(-90.0..=90.0).contains(&lat) {
+                return Err("GeoJSON 坐标超出经纬度范围，请先转换为 WGS84 经纬度坐标".to_string());
+            }
+            points.push((lon, lat));
+        }
+        if points.len() >= 3 {
+            rings.push(points);
+        }
+    }
+    Ok(())
+}
+
+/// 多边形的外接矩形
+pub fn polygon_bounds(polygon: &ClipPolygon) -> Bounds {
+    let mut bounds = Bounds::new(-90.0, 90.0, -180.0, 180.0);
+    for &(lon, lat) in polygon.iter().flatten() {
+        bounds.north = bounds.north.max(lat);
+        bounds.south = bounds.south.min(lat);
+        bounds.east = bounds.east.max(lon);
+        bounds.west = bounds.west.min(lon);
+    }
+    bounds.north = bounds.north.min(MAX_LAT);
+    bounds.south = bounds.south.max(-MAX_LAT);
+    bounds
+}
+
+/// 经纬度转为指定层级的浮点瓦片坐标
+fn to_tile_space(lon: f64, lat: f64, n: f64) -> (f64, f64) {
+    let lat = lat.clamp(-MAX_LAT, MAX_LAT).to_radians();
+    let x = (lon + 180.0) / 360.0 * n;
+    let y = (1.0 - lat.tan().asinh() / std::f64::consts::PI) / 2.0 * n;
+    (x, y)
+}
+
+/// 逐行计算与多边形相交的瓦片列区间，区间已合并且按列排序
+fn for_each_row(polygon: &ClipPolygon, z: u32, mut f: impl FnMut(u32, &[(u32, u32)])) {
+    let n = 2u32.pow(z);
+    let nf = n as f64;
+    let rings: Vec<Vec<(f64, f64)>> = polygon
+        .iter()
+        .map(|ring| {
+            ring.iter()
+                .map(|&(lon, lat)| to_tile_space(lon, lat, nf))
+                .collect()
+        })
+        .collect();
+    let edges: Vec<((f64, f64), (f64, f64))> = rings
+        .iter()
+        .flat_map(|ring| (0..ring.len()).map(move |i| (ring[i], ring[(i + 1) % ring.len()])))
+        .collect();
+
+    if edges.is_empty() {
+        return;
+    }
+    let (min_y, max_y) = edges.iter().fold((f64::MAX, f64::MIN), |(lo, hi), (p, _)| {
+        (lo.min(p.1), hi.max(p.1))
+    });
+    let last = n - 1;
+    let col = |x: f64| (x.floor().max(0.0) as u32).min(last);
+    // 区间右端恰好落在瓦片边线上时不计入右侧瓦片
+    let col_end = |x: f64| ((x.ceil() - 1.0).max(0.0) as u32).min(last);
+
+    let mut ranges = Vec::new();
+    let mut crossings = Vec::new();
+    for y in col(min_y)..=col(max_y) {
+        let (top, bottom) = (y as f64, y as f64 + 1.0);
+        ranges.clear();
+        crossings.clear();
+
+        for &((x0, y0), (x1, y1)) in &edges {
+            // 边界穿过本行的部分
+            if y0.max(y1) >= top && y0.min(y1) <= bottom {
+                let (a, b) = if y0 == y1 {
+                    (x0, x1)
+                } else {
+                    let t0 = ((top - y0) / (y1 - y0)).clamp(0.0, 1.0);
+                    let t1 = ((bottom - y0) / (y1 - y0)).clamp(0.0, 1.0);
+                    (x0 + t0 * (x1 - x0), x0 + t1 * (x1 - x0))
+                };
+                let (a, b) = (a.min(b), a.max(b));
+                ranges.push((col(a), col_end(b).max(col(a))));
+            }
+            // 行中线与边的交点，用于判断面内部
+            let center = top + 0.5;
+            if (y0 > center) != (y1 > center) {
+                crossings.push(x0 + (center - y0) / (y1 - y0) * (x1 - x0));
+            }
+        }
+
+        crossings.sort_by(|a, b| a.total_cmp(b));
+        for pair in crossings.chunks_exact(2) {
+            ranges.push((col(pair[0]), col_end(pair[1]).max(col(pair[0]))));
+        }
+
+        ranges.sort_unstable();
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+        for &(start, end) in &ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        if !merged.is_empty() {
+            f(y, &merged);
+        }
+    }
+}
+
+/// 计算与多边形相交的所有瓦片
+pub fn tiles_in_polygon(polygon: &ClipPolygon, zoom_levels: &[u32]) -> Vec<TileCoord> {
+    let mut tiles = Vec::new();
+    for &z in zoom_levels {
+        for_each_row(polygon, z, |y, ranges| {
+            for &(start, end) in ranges {
+                tiles.extend((start..=end).map(|x| TileCoord::new(z, x, y)));
+            }
+        });
+    }
+    tiles
+}
+
+/// 按多边形估算瓦片数量
+pub fn estimate_tiles_in_polygon(polygon: &ClipPolygon, zoom_levels: &[u32]) -> TileEstimate {
+    let tiles_per_level = zoom_levels
+        .iter()
+        .map(|&z| {
+            let mut count = 0u64;
+            for_each_row(polygon, z, |_, ranges| {
+                count += ranges.iter().map(|(s, e)| (e - s + 1) as u64).sum::<u64>();
+            });
+            (z, count)
+        })
+        .collect();
+    build_estimate(tiles_per_level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tiles_in_polygon() {
+        // 层级 1 下覆盖西北、东北、西南三个瓦片的 L 形区域
+        let polygon = parse_geojson(&json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [[
+                    [-170.0, 80.0], [170.0, 80.0], [170.0, 10.0],
+                    [-10.0, 10.0], [-10.0, -80.0], [-170.0, -80.0], [-170.0, 80.0]
+                ]]
+            }
+        }))
+        .unwrap();
+        let mut tiles: Vec<(u32, u32)> = tiles_in_polygon(&polygon, &[1])
+            .into_iter()
+            .map(|t| (t.x, t.y))
+            .collect();
+        tiles.sort_unstable();
+        assert_eq!(tiles, vec![(0, 0), (0, 1), (1, 0)]);
+
+        // 层级 4 下外接矩形 16×16，L 形应明显少于矩形
+        let estimate = estimate_tiles_in_polygon(&polygon, &[4]);
+        assert!(estimate.total_tiles < 16 * 16);
+        assert_eq!(
+            estimate.total_tiles,
+            tiles_in_polygon(&polygon, &[4]).len() as u64
+        );
+
+        let bounds = polygon_bounds(&polygon);
+        assert_eq!((bounds.north, bounds.west), (80.0, -170.0));
+    }
+
+    #[test]
+    fn test_parse_geojson_rejects_projected() {
+        let value = json!({
+            "type": "Polygon",
+            "coordinates": [[[500000.0, 3500000.0], [500100.0, 3500000.0], [500100.0, 3500100.0]]]
+        });
+        assert!(parse_geojson(&value).is_err());
+        assert!(parse_geojson(&json!({ "type": "Point", "coordinates": [120.0, 30.0] })).is_err());
+    }
+}
@@ -1,15 +1,33 @@
+use super::cache::{TileCache, TileCacheStats};
+use super::clip::{
+    estimate_tiles_in_polygon, load_geojson_file, polygon_bounds, tiles_in_polygon,
+};
+use super::coverage::{CoverageArea, CoverageCheck};
 use super::database::TileDatabase;
-use super::downloader::{calculate_tiles, estimate_tiles, TileDownloader};
+use super::downloader::{
+    calculate_tiles, estimate_tiles, TileDownloader, DEFAULT_MAX_CONNECTIONS_PER_HOST,
+};
+use super::manifest::{manifest_path, write_manifest, MANIFEST_FILE};
+use super::package::{build_package, PackageResult, TargetApp};
 use super::platforms::{create_platform, get_all_platforms};
-use super::storage::create_storage;
+use super::probe::{probe_coverage, ProbeConfig, ProbeResult};
+use super::storage::{
+    create_storage, is_tile_tree, read_tile, strip_tile_extension, tms_to_xyz, TileFormat,
+};
+use super::thumbnail::{generate_thumbnail, TaskThumbnail};
+use super::tile_list::{
+    load_tile_list_file, normalize_tile_list, tile_list_bounds, tile_list_zooms,
+};
 use super::types::*;
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Manager};
 use tokio::sync::mpsc;
 use uuid::Uuid;
+use crate::error::{AppError, CmdResult, ErrorCode};
+use crate::power::CompletionAction;
 
 // 全局下载器实例
 static TILE_DOWNLOADER: Lazy<TileDownloader> = Lazy::new(TileDownloader::new);
@@ -17,55 +35,272 @@ static TILE_DOWNLOADER: Lazy<TileDownloader> = Lazy::new(TileDownloader::new);
 // 全局数据库实例
 static TILE_DB: Lazy<RwLock<Option<Arc<TileDatabase>>>> = Lazy::new(|| RwLock::new(None));
 
+/// 瓦片数据库文件路径
+pub(crate) fn tile_db_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let app_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("获取应用目录失败: {}", e))?;
+    std::fs::create_dir_all(&app_dir).ok();
+    Ok(app_dir.join("tile_data.db"))
+}
+
+/// 共享瓦片缓存文件路径
+pub(crate) fn tile_cache_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(tile_db_path(app)?.with_file_name("tile_cache.db"))
+}
+
+/// 共享瓦片缓存（延迟打开）
+static TILE_CACHE: Lazy<RwLock<Option<Arc<TileCache>>>> = Lazy::new(|| RwLock::new(None));
+
+/// 打开共享瓦片缓存
+pub(crate) fn get_tile_cache(app: &AppHandle) -> Result<Arc<TileCache>, String> {
+    let mut cache_guard = TILE_CACHE.write();
+    if cache_guard.is_none() {
+        let path = tile_cache_path(app)?;
+        let cache = TileCache::new(&path).map_err(|e| format!("打开瓦片缓存失败: {}", e))?;
+        *cache_guard = Some(Arc::new(cache));
+    }
+    Ok(cache_guard.as_ref().unwrap().clone())
+}
+
+/// 获取共享瓦片缓存的统计
+#[tauri::command]
+pub async fn get_tile_cache_stats(app: AppHandle) -> CmdResult<TileCacheStats> {
+    get_tile_cache(&app)?
+        .stats()
+        .map_err(|e| AppError::from(format!("获取缓存统计失败: {}", e)))
+}
+
+/// 清空共享瓦片缓存，platform 指定时只清理该平台
+#[tauri::command]
+pub async fn clear_tile_cache(app: AppHandle, platform: Option<String>) -> CmdResult<usize> {
+    get_tile_cache(&app)?
+        .clear(platform.as_deref())
+        .map_err(|e| AppError::from(format!("清理缓存失败: {}", e)))
+}
+
 /// 初始化瓦片数据库
 fn get_tile_db(app: &AppHandle) -> Result<Arc<TileDatabase>, String> {
     let mut db_guard = TILE_DB.write();
     if db_guard.is_none() {
-        let app_dir = app
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("获取应用目录失败: {}", e))?;
-        std::fs::create_dir_all(&app_dir).ok();
-        let db_path = app_dir.join("tile_data.db");
+        let db_path = tile_db_path(app)?;
         let db = TileDatabase::new(&db_path).map_err(|e| format!("初始化数据库失败: {}", e))?;
         *db_guard = Some(Arc::new(db));
     }
     Ok(db_guard.as_ref().unwrap().clone())
 }
 
+/// 将瓦片数据库快照写入指定路径
+pub(crate) fn snapshot_tile_db(app: &AppHandle, dest: &Path) -> Result<(), String> {
+    get_tile_db(app)?
+        .vacuum_into(dest)
+        .map_err(|e| format!("导出瓦片数据库失败: {}", e))
+}
+
+/// 是否有正在下载（未暂停）的瓦片任务
+pub(crate) fn has_active_tile_tasks() -> bool {
+    TILE_DOWNLOADER.running_task_ids().iter().any(|id| {
+        TILE_DOWNLOADER
+            .get_state(id)
+            .is_some_and(|state| !state.is_paused.load(std::sync::atomic::Ordering::Relaxed))
+    })
+}
+
+/// 是否有正在运行的瓦片下载任务
+pub(crate) fn has_running_tile_tasks() -> bool {
+    !TILE_DOWNLOADER.running_task_ids().is_empty()
+}
+
+/// 在释放瓦片数据库期间执行 f（用于替换数据库文件），要求没有运行中的下载任务
+pub(crate) fn with_tile_db_closed<F>(app: &AppHandle, f: F) -> CmdResult<()>
+where
+    F: FnOnce(&Path) -> Result<(), String>,
+{
+    if has_running_tile_tasks() {
+        return Err(AppError::conflict("请先停止所有瓦片下载任务"));
+    }
+
+    let db_path = tile_db_path(app)?;
+    let mut db_guard = TILE_DB.write();
+    *db_guard = None;
+    f(&db_path).map_err(AppError::from)
+}
+
 /// 获取所有支持的平台
 #[tauri::command]
 pub fn get_tile_platforms() -> Vec<PlatformInfo> {
     get_all_platforms()
 }
 
-/// 计算瓦片数量
+/// 计算瓦片数量，指定 GeoJSON 文件时按多边形裁剪后计数
+#[tauri::command]
+pub fn calculate_tiles_count(
+    bounds: Bounds,
+    zoom_levels: Vec<u32>,
+    geojson_path: Option<String>,
+) -> CmdResult<TileEstimate> {
+    match geojson_path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => {
+            let polygon = load_geojson_file(Path::new(&path)).map_err(AppError::invalid)?;
+            Ok(estimate_tiles_in_polygon(&polygon, &zoom_levels))
+        }
+        None => Ok(estimate_tiles(&bounds, &zoom_levels)),
+    }
+}
+
+/// GeoJSON 范围文件概要
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ClipGeojsonInfo {
+    /// 面要素的外接矩形
+    pub bounds: Bounds,
+    pub rings: usize,
+    pub vertices: usize,
+}
+
+/// 读取本地 GeoJSON 文件，返回外接矩形供前端定位与预览
 #[tauri::command]
-pub fn calculate_tiles_count(bounds: Bounds, zoom_levels: Vec<u32>) -> TileEstimate {
-    estimate_tiles(&bounds, &zoom_levels)
+pub fn load_clip_geojson(path: String) -> CmdResult<ClipGeojsonInfo> {
+    let polygon = load_geojson_file(Path::new(&path)).map_err(AppError::invalid)?;
+    Ok(ClipGeojsonInfo {
+        bounds: polygon_bounds(&polygon),
+        rings: polygon.len(),
+        vertices: polygon.iter().map(Vec::len).sum(),
+    })
 }
 
 /// 创建下载任务
 #[tauri::command]
-pub async fn create_tile_task(app: AppHandle, config: TaskConfig) -> Result<String, String> {
+pub async fn create_tile_task(app: AppHandle, config: TaskConfig) -> CmdResult<String> {
     let db = get_tile_db(&app)?;
 
+    let mut config = config;
+    config.geojson_path = config.geojson_path.filter(|p| !p.trim().is_empty());
+    if config.area_id.is_some() && config.geojson_path.is_some() {
+        return Err(AppError::invalid("收藏范围与 GeoJSON 文件不能同时指定"));
+    }
+
+    // 按坐标列表下载时，以列表中瓦片的外接矩形为边界、出现的层级为下载层级
+    let tile_list_path = config.tile_list_path.take().filter(|p| !p.trim().is_empty());
+    let tile_list = match (config.tile_list.take(), &tile_list_path) {
+        (Some(_), Some(_)) => {
+            return Err(AppError::invalid("瓦片列表与瓦片列表文件不能同时指定"));
+        }
+        (Some(list), None) => Some(normalize_tile_list(list).map_err(AppError::invalid)?),
+        (None, Some(path)) => {
+            Some(load_tile_list_file(Path::new(path)).map_err(AppError::invalid)?)
+        }
+        (None, None) => None,
+    };
+    if let Some(list) = &tile_list {
+        if config.area_id.is_some() || config.geojson_path.is_some() {
+            return Err(AppError::invalid("瓦片列表不能与收藏范围或 GeoJSON 文件同时指定"));
+        }
+        config.bounds = tile_list_bounds(list);
+        config.zoom_levels = tile_list_zooms(list);
+        if config.region_name.is_none() {
+            config.region_name = tile_list_path
+                .as_deref()
+                .and_then(|p| Path::new(p).file_stem())
+                .map(|stem| stem.to_string_lossy().to_string());
+        }
+    }
+
+    // 以 GeoJSON 面要素的外接矩形为边界，下载时按多边形裁剪
+    let clip_polygon = match &config.geojson_path {
+        Some(path) => {
+            let path = Path::new(path);
+            let polygon = load_geojson_file(path).map_err(AppError::invalid)?;
+            config.bounds = polygon_bounds(&polygon);
+            if config.region_name.is_none() {
+                config.region_name = path
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string());
+            }
+            Some(polygon)
+        }
+        None => None,
+    };
+
+    if let Some(area_id) = &config.area_id {
+        let area = crate::commands::find_saved_area(area_id)?;
+        config.bounds = Bounds {
+            north: area.bounds.max_lat,
+            south: area.bounds.min_lat,
+            east: area.bounds.max_lon,
+            west: area.bounds.min_lon,
+        };
+        if config.region_name.is_none() {
+            config.region_name = Some(area.name);
+        }
+    }
+
     // 验证参数
     if !config.bounds.is_valid() {
-        return Err("无效的区域边界".to_string());
+        return Err(AppError::invalid("无效的区域边界"));
     }
 
     if config.zoom_levels.is_empty() {
-        return Err("请至少选择一个层级".to_string());
+        return Err(AppError::invalid("请至少选择一个层级"));
     }
 
     if config.name.trim().is_empty() {
-        return Err("请输入任务名称".to_string());
+        return Err(AppError::invalid("请输入任务名称"));
+    }
+
+    // 验证层级与地图类型是否被平台支持
+    let platforms = get_all_platforms();
+    validate_platform_capabilities(&platforms, &config).map_err(AppError::invalid)?;
+
+    // 检查区域是否超出平台覆盖范围
+    let coverage = check_tile_coverage(
+        config.platform.clone(),
+        config.map_type.clone(),
+        config.bounds.clone(),
+    );
+    if let Some(warning) = &coverage.warning {
+        if coverage.covered_ratio <= 0.0 && config.fallback_platforms.is_empty() {
+            return Err(AppError::invalid(warning.clone()));
+        }
+        log::warn!("{}", warning);
+    }
+
+    // 验证备选平台
+    for fallback in &config.fallback_platforms {
+        let info = platforms
+            .iter()
+            .find(|p| &p.id == fallback)
+            .ok_or_else(|| AppError::invalid(format!("未知的备选平台: {}", fallback)))?;
+        if info.id == config.platform {
+            return Err(AppError::invalid("备选平台不能与主平台相同"));
+        }
+        if info.requires_key {
+            return Err(AppError::invalid(format!(
+                "备选平台 {} 需要 API Key，暂不支持作为备选源",
+                info.name
+            )));
+        }
+    }
+
+    // 未填写输出路径时按输出设置自动生成
+    if config.output_path.trim().is_empty() {
+        config.output_path = default_tile_output_path(&config)?;
+        log::info!("自动生成输出路径: {}", config.output_path);
     }
 
+    // 检查输出路径冲突
+    check_output_conflict(&db, &config)?;
+
     // 计算瓦片总数
-    let tiles = calculate_tiles(&config.bounds, &config.zoom_levels);
+    let tiles = match (&tile_list, &clip_polygon) {
+        (Some(list), _) => list.clone(),
+        (None, Some(polygon)) => tiles_in_polygon(polygon, &config.zoom_levels),
+        (None, None) => calculate_tiles(&config.bounds, &config.zoom_levels),
+    };
     let total_tiles = tiles.len() as u64;
+    if total_tiles == 0 {
+        return Err(AppError::invalid("范围内没有需要下载的瓦片"));
+    }
 
     // 生成任务ID
     let task_id = Uuid::new_v4().to_string();
@@ -84,17 +319,323 @@ pub async fn create_tile_task(app: AppHandle, config: TaskConfig) -> Result<Stri
         config.thread_count,
         config.retry_count,
         config.api_key.as_deref(),
+        config
+            .max_connections_per_host
+            .unwrap_or(DEFAULT_MAX_CONNECTIONS_PER_HOST)
+            .clamp(1, 32),
+        &config.headers,
+        config.coord_correction,
+        &config.fallback_platforms,
+        config.use_shared_cache,
+        config.fill_no_data,
+        normalize_rate_limit(config.rate_limit).map_err(AppError::invalid)?,
+        clip_polygon.as_ref(),
+        config.on_complete,
     )
     .map_err(|e| format!("创建任务失败: {}", e))?;
+    if let Some(list) = &tile_list {
+        db.set_tile_list(&task_id, list)
+            .map_err(|e| format!("保存瓦片列表失败: {}", e))?;
+        db.close_progress(&task_id);
+    }
 
     log::info!("创建下载任务: {} ({}), 共 {} 个瓦片", config.name, task_id, total_tiles);
 
     Ok(task_id)
 }
 
+/// 检测区域是否超出平台覆盖范围，并给出可替代的全球覆盖平台
+#[tauri::command]
+pub fn check_tile_coverage(platform: String, map_type: String, bounds: Bounds) -> CoverageCheck {
+    let tile_platform = create_platform(&platform, None);
+    let covered_ratio = tile_platform.coverage().covered_ratio(&bounds);
+
+    let map_type = map_type.to_lowercase();
+    let suggested_platforms: Vec<String> = get_all_platforms()
+        .into_iter()
+        .filter(|p| p.id != platform && !p.requires_key && p.map_types.contains(&map_type))
+        .filter(|p| create_platform(&p.id, None).coverage() == CoverageArea::Global)
+        .map(|p| p.id)
+        .collect();
+
+    let warning = if covered_ratio >= 1.0 {
+        None
+    } else {
+        let extent = if covered_ratio <= 0.0 {
+            "完全位于".to_string()
+        } else {
+            format!("约 {:.0}% 位于", (1.0 - covered_ratio) * 100.0)
+        };
+        let mut message = format!(
+            "所选区域{} {} 覆盖范围（中国大陆）之外，境外及港澳台区域可能没有瓦片",
+            extent,
+            tile_platform.name()
+        );
+        if !suggested_platforms.is_empty() {
+            message.push_str(&format!(
+                "，建议改用或添加备选平台: {}",
+                suggested_platforms.join("、")
+            ));
+        }
+        Some(message)
+    };
+
+    CoverageCheck {
+        platform,
+        covered_ratio,
+        warning,
+        suggested_platforms,
+    }
+}
+
+/// 抽样请求部分瓦片，统计各平台各层级的有图比例与平均大小，不写入任何文件
+#[tauri::command]
+pub async fn probe_tile_coverage(config: ProbeConfig) -> CmdResult<Vec<ProbeResult>> {
+    probe_coverage(config).await.map_err(AppError::from)
+}
+
+/// 校验所选层级与地图类型是否在平台能力范围内，不满足时给出修正建议
+fn validate_platform_capabilities(
+    platforms: &[PlatformInfo],
+    config: &TaskConfig,
+) -> Result<(), String> {
+    let info = platforms
+        .iter()
+        .find(|p| p.id == config.platform)
+        .ok_or_else(|| format!("未知的平台: {}", config.platform))?;
+
+    let map_type = config.map_type.to_lowercase();
+    if !info.map_types.contains(&map_type) {
+        let alternatives: Vec<&str> = platforms
+            .iter()
+            .filter(|p| p.map_types.contains(&map_type))
+            .map(|p| p.name.as_str())
+            .collect();
+        let mut message = format!(
+            "{} 不支持地图类型 {}，可选类型: {}",
+            info.name,
+            config.map_type,
+            info.map_types.join("、")
+        );
+        if !alternatives.is_empty() {
+            message.push_str(&format!("；支持该类型的平台: {}", alternatives.join("、")));
+        }
+        return Err(message);
+    }
+
+    let mut invalid: Vec<u32> = config
+        .zoom_levels
+        .iter()
+        .copied()
+        .filter(|z| *z < info.min_zoom || *z > info.max_zoom)
+        .collect();
+    if !invalid.is_empty() {
+        invalid.sort_unstable();
+        invalid.dedup();
+        let max_requested = config.zoom_levels.iter().copied().max().unwrap_or(0);
+        let alternatives: Vec<String> = platforms
+            .iter()
+            .filter(|p| p.id != info.id && p.map_types.contains(&map_type))
+            .filter(|p| config.zoom_levels.iter().all(|z| *z >= p.min_zoom && *z <= p.max_zoom))
+            .map(|p| format!("{}({}-{})", p.name, p.min_zoom, p.max_zoom))
+            .collect();
+
+        let mut message = format!(
+            "{} 支持层级 {}-{}，以下层级超出范围: {}。建议移除这些层级",
+            info.name,
+            info.min_zoom,
+            info.max_zoom,
+            invalid
+                .iter()
+                .map(|z| z.to_string())
+                .collect::<Vec<_>>()
+                .join("、")
+        );
+        if max_requested > info.max_zoom && !alternatives.is_empty() {
+            message.push_str(&format!("，或改用 {}", alternatives.join("、")));
+        }
+        return Err(message);
+    }
+
+    Ok(())
+}
+
+/// 按瓦片输出设置生成输出路径，并创建所需的上级目录
+fn default_tile_output_path(config: &TaskConfig) -> Result<String, String> {
+    let settings = crate::config::get_tile_output_settings()?;
+    let root = settings
+        .output_root
+        .filter(|d| !d.trim().is_empty())
+        .ok_or_else(|| "请选择输出路径或在设置中配置瓦片输出根目录".to_string())?;
+
+    let now = chrono::Local::now();
+    let region = config.region_name.clone().unwrap_or_else(|| {
+        crate::config::get_current_region()
+            .map(|r| r.name)
+            .unwrap_or_default()
+    });
+    let mut vars = std::collections::HashMap::new();
+    vars.insert("platform", config.platform.clone());
+    vars.insert("region", region);
+    vars.insert("maptype", config.map_type.clone());
+    vars.insert("name", config.name.trim().to_string());
+    vars.insert("date", now.format("%Y-%m-%d").to_string());
+    vars.insert("time", now.format("%H%M%S").to_string());
+
+    let mut path = crate::config::render_path(Path::new(&root), &settings.path_template, &vars);
+    // MBTiles 与 ZIP 输出为单个文件，其余格式输出为目录
+    let dir = match config.output_format.as_str() {
+        "mbtiles" | "zip" => {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            path = path.with_file_name(format!("{}.{}", file_name, config.output_format));
+            path.parent().map(Path::to_path_buf)
+        }
+        _ => Some(path.clone()),
+    };
+    if let Some(dir) = dir {
+        std::fs::create_dir_all(dir).map_err(|e| format!("创建输出目录失败: {}", e))?;
+    }
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// 获取瓦片输出设置
+#[tauri::command]
+pub fn get_tile_output_settings() -> CmdResult<crate::config::TileOutputSettings> {
+    crate::config::get_tile_output_settings().map_err(AppError::from)
+}
+
+/// 保存瓦片输出设置
+#[tauri::command]
+pub fn set_tile_output_settings(settings: crate::config::TileOutputSettings) -> CmdResult<()> {
+    if settings.path_template.trim().is_empty() {
+        return Err(AppError::invalid("路径模板不能为空"));
+    }
+    crate::config::set_tile_output_settings(&settings).map_err(AppError::from)
+}
+
+/// 获取瓦片下载连接设置
+#[tauri::command]
+pub fn get_tile_network_settings() -> CmdResult<crate::config::TileNetworkSettings> {
+    crate::config::get_tile_network_settings().map_err(AppError::from)
+}
+
+/// 保存瓦片下载连接设置，对之后启动的任务生效
+#[tauri::command]
+pub fn set_tile_network_settings(
+    settings: crate::config::TileNetworkSettings,
+) -> CmdResult<()> {
+    if settings.pool_idle_timeout_secs == 0 {
+        return Err(AppError::invalid("空闲连接保留时长需大于 0"));
+    }
+    if settings.tcp_keepalive_secs == Some(0) {
+        return Err(AppError::invalid("TCP keepalive 间隔需大于 0"));
+    }
+    crate::config::set_tile_network_settings(&settings).map_err(AppError::from)
+}
+
+/// 检查输出路径是否被其他任务占用或已存在文件，并按冲突策略处理
+fn check_output_conflict(db: &TileDatabase, config: &TaskConfig) -> CmdResult<()> {
+    let strategy = config.conflict_strategy.as_deref().unwrap_or("error");
+    if !matches!(strategy, "overwrite" | "resume" | "error") {
+        return Err(AppError::invalid(format!("不支持的冲突策略: {}", strategy)));
+    }
+
+    let output = Path::new(&config.output_path);
+    let tasks = db
+        .get_all_tasks()
+        .map_err(|e| format!("获取任务列表失败: {}", e))?;
+
+    for task in tasks.iter().filter(|t| Path::new(&t.output_path) == output) {
+        // 正在下载的任务无论何种策略都不允许共用输出
+        let running = TILE_DOWNLOADER
+            .get_state(&task.id)
+            .map(|s| s.is_running.load(std::sync::atomic::Ordering::Relaxed))
+            .unwrap_or(false);
+        if running {
+            return Err(AppError::conflict(format!(
+                "输出路径正被任务「{}」使用，请等待其结束",
+                task.name
+            )));
+        }
+        if strategy == "error" {
+            return Err(AppError::conflict(format!(
+                "输出路径已被任务「{}」占用",
+                task.name
+            )));
+        }
+    }
+
+    // 不存在或为空目录时视为无冲突
+    let is_empty_dir = output.is_dir()
+        && std::fs::read_dir(output)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+    if !output.exists() || is_empty_dir {
+        return Ok(());
+    }
+
+    match strategy {
+        "overwrite" => {
+            remove_task_output(output, &config.output_format)?;
+            log::info!("覆盖已有输出: {}", config.output_path);
+        }
+        "resume" => {
+            // ZIP 无法在已有归档上续写
+            if config.output_format == "zip" {
+                return Err(AppError::invalid(
+                    "ZIP 输出不支持续写，请选择覆盖或更换路径",
+                ));
+            }
+        }
+        _ => return Err(AppError::conflict("输出路径已存在文件")),
+    }
+
+    Ok(())
+}
+
+/// 覆盖前删除已有的瓦片输出：与输出格式一致的 MBTiles/ZIP 文件，或只含 z/x/y 瓦片与清单的目录；
+/// 其他文件与目录不是下载任务的产物，拒绝删除
+fn remove_task_output(output: &Path, output_format: &str) -> CmdResult<()> {
+    let removed = if output.is_dir() {
+        if output_format != "folder" || !is_tile_tree(output, &[MANIFEST_FILE])? {
+            return Err(AppError::conflict(
+                "输出目录中有瓦片以外的文件，不能覆盖，请更换为空目录",
+            ));
+        }
+        std::fs::read_dir(output)
+            .map_err(|e| format!("读取目录失败: {}", e))?
+            .flatten()
+            .try_for_each(|entry| {
+                if entry.path().is_dir() {
+                    std::fs::remove_dir_all(entry.path())
+                } else {
+                    std::fs::remove_file(entry.path())
+                }
+            })
+    } else {
+        let extension = output
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if output_format == "folder" || extension != output_format {
+            return Err(AppError::conflict(format!(
+                "输出路径是已有文件且不是 .{} 瓦片文件，不能覆盖",
+                output_format
+            )));
+        }
+        let manifest = manifest_path(output, output_format);
+        if manifest.is_file() {
+            std::fs::remove_file(&manifest).ok();
+        }
+        std::fs::remove_file(output)
+    };
+    removed.map_err(|e| AppError::new(ErrorCode::Io, format!("删除已有输出失败: {}", e)))
+}
+
 /// 获取所有任务
 #[tauri::command]
-pub async fn get_tile_tasks(app: AppHandle) -> Result<Vec<TaskInfo>, String> {
+pub async fn get_tile_tasks(app: AppHandle) -> CmdResult<Vec<TaskInfo>> {
     let db = get_tile_db(&app)?;
 
     let mut tasks = db
@@ -119,9 +660,16 @@ pub async fn get_tile_tasks(app: AppHandle) -> Result<Vec<TaskInfo>, String> {
     Ok(tasks)
 }
 
+/// 获取运行中任务本次的开始时间
+pub(crate) fn tile_task_started_at(task_id: &str) -> Option<String> {
+    TILE_DOWNLOADER
+        .get_state(task_id)
+        .and_then(|state| state.started_at.read().clone())
+}
+
 /// 获取单个任务
 #[tauri::command]
-pub async fn get_tile_task(app: AppHandle, task_id: String) -> Result<Option<TaskInfo>, String> {
+pub async fn get_tile_task(app: AppHandle, task_id: String) -> CmdResult<Option<TaskInfo>> {
     let db = get_tile_db(&app)?;
 
     let mut task = db
@@ -130,6 +678,44 @@ pub async fn get_tile_task(app: AppHandle, task_id: String) -> Result<Option<Tas
 
     // 更新运行中任务的实时状态
     if let Some(ref mut t) = task {
+        t.zoom_progress = db
+            .get_zoom_stats(&t.id)
+            .map_err(|e| format!("获取层级进度失败: {}", e))?;
+        t.source_stats = db
+            .get_source_stats(&t.id)
+            .map_err(|e| format!("获取来源统计失败: {}", e))?;
+        t.no_data_tiles = db
+            .count_no_data_tiles(&t.id)
+            .map_err(|e| format!("获取无数据瓦片数失败: {}", e))?;
+
+        // 尚未开始下载的任务没有进度记录，按估算结果全部视为待下载
+        if t.zoom_progress.is_empty() {
+            let tile_list = db
+                .get_tile_list(&t.id)
+                .map_err(|e| format!("读取瓦片列表失败: {}", e))?;
+            let tiles_per_level = match (tile_list, &t.clip_polygon) {
+                (Some(list), _) => tile_list_zooms(&list)
+                    .into_iter()
+                    .map(|z| (z, list.iter().filter(|t| t.z == z).count() as u64))
+                    .collect(),
+                (None, Some(polygon)) => {
+                    estimate_tiles_in_polygon(polygon, &t.zoom_levels).tiles_per_level
+                }
+                (None, None) => estimate_tiles(&t.bounds, &t.zoom_levels).tiles_per_level,
+            };
+            t.zoom_progress = tiles_per_level
+                .into_iter()
+                .map(|(zoom, count)| ZoomProgress {
+                    zoom,
+                    total: count,
+                    pending: count,
+                    completed: 0,
+                    failed: 0,
+                    no_data: 0,
+                })
+                .collect();
+        }
+
         if let Some(state) = TILE_DOWNLOADER.get_state(&t.id) {
             t.completed_tiles = state.completed.load(std::sync::atomic::Ordering::Relaxed);
             t.failed_tiles = state.failed.load(std::sync::atomic::Ordering::Relaxed);
@@ -146,16 +732,40 @@ pub async fn get_tile_task(app: AppHandle, task_id: String) -> Result<Option<Tas
     Ok(task)
 }
 
+/// 默认返回的失败记录条数
+const DEFAULT_RECENT_ERROR_LIMIT: u32 = 50;
+
+/// 获取任务最近的瓦片失败记录，下载过程中即可查看
+#[tauri::command]
+pub async fn get_recent_tile_errors(
+    app: AppHandle,
+    task_id: String,
+    limit: Option<u32>,
+) -> CmdResult<Vec<TileError>> {
+    let db = get_tile_db(&app)?;
+    let limit = limit.unwrap_or(DEFAULT_RECENT_ERROR_LIMIT).clamp(1, 1000);
+    db.get_recent_tile_errors(&task_id, limit)
+        .map_err(|e| AppError::from(format!("获取失败记录失败: {}", e)))
+}
+
+/// 重新生成任务的离线包清单，返回清单文件路径
+#[tauri::command]
+pub async fn regenerate_manifest(app: AppHandle, task_id: String) -> CmdResult<String> {
+    let db = get_tile_db(&app)?;
+    let path = write_manifest(&db, &task_id)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
 /// 开始/恢复下载任务
 #[tauri::command]
-pub async fn start_tile_download(app: AppHandle, task_id: String) -> Result<(), String> {
+pub async fn start_tile_download(app: AppHandle, task_id: String) -> CmdResult<()> {
     let db = get_tile_db(&app)?;
 
     // 获取任务信息
     let task = db
         .get_task(&task_id)
         .map_err(|e| format!("获取任务失败: {}", e))?
-        .ok_or("任务不存在")?;
+        .ok_or_else(|| AppError::not_found("任务不存在"))?;
 
     // 检查是否已在运行
     if let Some(state) = TILE_DOWNLOADER.get_state(&task_id) {
@@ -165,13 +775,22 @@ pub async fn start_tile_download(app: AppHandle, task_id: String) -> Result<(),
                 TILE_DOWNLOADER.resume(&task_id);
                 return Ok(());
             }
-            return Err("任务已在运行中".to_string());
+            return Err(AppError::conflict("任务已在运行中"));
         }
     }
 
     // 创建平台
     let platform = create_platform(&task.platform, task.api_key.as_deref());
-    let map_type = MapType::from(task.map_type.as_str());
+    let fallbacks = task
+        .fallback_platforms
+        .iter()
+        .map(|id| create_platform(id, None))
+        .collect();
+    let cache = if task.use_shared_cache {
+        Some(get_tile_cache(&app)?)
+    } else {
+        None
+    };
 
     // 创建进度通道
     let (progress_tx, mut progress_rx) = mpsc::channel::<ProgressEvent>(100);
@@ -180,7 +799,7 @@ pub async fn start_tile_download(app: AppHandle, task_id: String) -> Result<(),
     let app_handle = app.clone();
     tokio::spawn(async move {
         while let Some(event) = progress_rx.recv().await {
-            let _ = app_handle.emit("tile-download-progress", &event);
+            crate::events::emit_tile_progress(&app_handle, event);
         }
     });
 
@@ -189,23 +808,22 @@ pub async fn start_tile_download(app: AppHandle, task_id: String) -> Result<(),
     let task_id_clone = task_id.clone();
 
     tokio::spawn(async move {
-        if let Err(e) = TILE_DOWNLOADER
-            .start_download(
-                db_clone,
-                task_id_clone.clone(),
-                platform,
-                map_type,
-                task.bounds,
-                task.zoom_levels,
-                task.output_path,
-                task.output_format,
-                task.thread_count,
-                task.retry_count,
-                progress_tx,
-            )
-            .await
-        {
-            log::error!("下载任务 {} 失败: {}", task_id_clone, e);
+        let result = TILE_DOWNLOADER
+            .start_download(db_clone.clone(), cache, task, platform, fallbacks, progress_tx)
+            .await;
+        // 下载结束（含暂停、取消）后关闭进度库，之后的查询临时打开
+        db_clone.close_progress(&task_id_clone);
+        let outcome = match result {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                log::error!("下载任务 {} 失败: {}", task_id_clone, e);
+                return;
+            }
+        };
+        // 只有下载完成才执行完成后动作，停止或取消不执行；动作以库中最新设置为准
+        if let Ok(Some(task)) = db_clone.get_task(&task_id_clone) {
+            let action = outcome.completion_action(&task);
+            crate::power::schedule_completion_action(&app, action, &task.name);
         }
     });
 
@@ -214,20 +832,20 @@ pub async fn start_tile_download(app: AppHandle, task_id: String) -> Result<(),
 
 /// 暂停下载任务
 #[tauri::command]
-pub async fn pause_tile_download(app: AppHandle, task_id: String) -> Result<(), String> {
+pub async fn pause_tile_download(app: AppHandle, task_id: String) -> CmdResult<()> {
     let db = get_tile_db(&app)?;
 
     if TILE_DOWNLOADER.pause(&task_id) {
         db.update_task_status(&task_id, "paused").ok();
         Ok(())
     } else {
-        Err("任务不存在或未运行".to_string())
+        Err(AppError::not_found("任务不存在或未运行"))
     }
 }
 
 /// 停止/取消下载任务
 #[tauri::command]
-pub async fn cancel_tile_download(app: AppHandle, task_id: String) -> Result<(), String> {
+pub async fn cancel_tile_download(app: AppHandle, task_id: String) -> CmdResult<()> {
     let db = get_tile_db(&app)?;
 
     TILE_DOWNLOADER.stop(&task_id);
@@ -236,13 +854,41 @@ pub async fn cancel_tile_download(app: AppHandle, task_id: String) -> Result<(),
     Ok(())
 }
 
+/// 暂停所有运行中的下载任务，返回被暂停的任务 ID
+pub(crate) fn pause_all_tile_tasks(app: &AppHandle) -> Result<Vec<String>, String> {
+    let db = get_tile_db(app)?;
+
+    let mut paused = Vec::new();
+    for task_id in TILE_DOWNLOADER.running_task_ids() {
+        if TILE_DOWNLOADER.pause(&task_id) {
+            db.update_task_status(&task_id, "paused").ok();
+            paused.push(task_id);
+        }
+    }
+    Ok(paused)
+}
+
+/// 停止所有运行中的下载任务，返回被停止的任务 ID
+pub(crate) fn stop_all_tile_tasks(app: &AppHandle) -> Result<Vec<String>, String> {
+    let db = get_tile_db(app)?;
+
+    let mut stopped = Vec::new();
+    for task_id in TILE_DOWNLOADER.running_task_ids() {
+        if TILE_DOWNLOADER.stop(&task_id) {
+            db.update_task_status(&task_id, "cancelled").ok();
+            stopped.push(task_id);
+        }
+    }
+    Ok(stopped)
+}
+
 /// 删除任务
 #[tauri::command]
 pub async fn delete_tile_task(
     app: AppHandle,
     task_id: String,
     delete_files: bool,
-) -> Result<(), String> {
+) -> CmdResult<()> {
     let db = get_tile_db(&app)?;
 
     // 先停止任务
@@ -262,6 +908,10 @@ pub async fn delete_tile_task(
         }
     }
 
+    // 删除缓存的缩略图
+    let thumbnail = thumbnail_dir(&app)?.join(format!("{}.png", task_id));
+    std::fs::remove_file(thumbnail).ok();
+
     // 删除数据库记录
     db.delete_task(&task_id)
         .map_err(|e| format!("删除任务失败: {}", e))?;
@@ -275,7 +925,7 @@ pub async fn set_tile_thread_count(
     app: AppHandle,
     task_id: String,
     count: u32,
-) -> Result<(), String> {
+) -> CmdResult<()> {
     let db = get_tile_db(&app)?;
 
     let count = count.max(1).min(32);
@@ -285,33 +935,237 @@ pub async fn set_tile_thread_count(
     Ok(())
 }
 
+/// 校验限速参数，非正数视为不限速
+fn normalize_rate_limit(rate_limit: Option<f64>) -> Result<Option<f64>, String> {
+    match rate_limit {
+        Some(rate) if !rate.is_finite() => Err("无效的限速参数".to_string()),
+        Some(rate) if rate > 0.0 => Ok(Some(rate)),
+        _ => Ok(None),
+    }
+}
+
+/// 修改任务的线程数、重试次数与限速，任意状态下均可修改并写库
+///
+/// 运行中的任务立即应用线程数与限速，重试次数在下次启动时生效
+#[tauri::command]
+pub async fn update_task_runtime_config(
+    app: AppHandle,
+    task_id: String,
+    config: RuntimeConfig,
+) -> CmdResult<()> {
+    let db = get_tile_db(&app)?;
+
+    let thread_count = config.thread_count.clamp(1, 32);
+    if config.retry_count > 10 {
+        return Err(AppError::invalid("重试次数不能超过 10 次"));
+    }
+    let rate_limit = normalize_rate_limit(config.rate_limit).map_err(AppError::invalid)?;
+
+    let updated = db
+        .update_runtime_config(&task_id, thread_count, config.retry_count, rate_limit)
+        .map_err(|e| format!("保存运行参数失败: {}", e))?;
+    if !updated {
+        return Err(AppError::not_found("任务不存在"));
+    }
+
+    if let Some(state) = TILE_DOWNLOADER.get_state(&task_id) {
+        state.thread_count.store(thread_count, std::sync::atomic::Ordering::SeqCst);
+        state.set_rate_limit(rate_limit);
+    }
+    log::info!(
+        "任务 {} 运行参数: 线程数 {}, 重试 {} 次, 限速 {:?}",
+        task_id,
+        thread_count,
+        config.retry_count,
+        rate_limit
+    );
+    Ok(())
+}
+
+/// 修改任务下载完成后的动作，运行中的任务同样生效
+#[tauri::command]
+pub async fn set_tile_task_completion_action(
+    app: AppHandle,
+    task_id: String,
+    action: CompletionAction,
+) -> CmdResult<()> {
+    let db = get_tile_db(&app)?;
+    let updated = db
+        .update_on_complete(&task_id, action)
+        .map_err(|e| format!("保存完成后动作失败: {}", e))?;
+    if !updated {
+        return Err(AppError::not_found("任务不存在"));
+    }
+    Ok(())
+}
+
 /// 重试失败的瓦片
 #[tauri::command]
-pub async fn retry_failed_tiles(app: AppHandle, task_id: String) -> Result<u64, String> {
+pub async fn retry_failed_tiles(app: AppHandle, task_id: String) -> CmdResult<u64> {
     let db = get_tile_db(&app)?;
 
     let count = db
         .reset_failed_tiles(&task_id)
         .map_err(|e| format!("重置失败瓦片失败: {}", e))?;
+    if TILE_DOWNLOADER.get_state(&task_id).is_none() {
+        db.close_progress(&task_id);
+    }
 
-    // 更新任务状态
+    // 更新任务状态，清除上次汇总的失败原因
     db.update_task_status(&task_id, "pending").ok();
+    db.set_task_error_message(&task_id, None).ok();
 
     Ok(count)
 }
 
+/// 限定已有任务只下载指定的瓦片坐标列表（如失败清单），返回列表中的瓦片数
+///
+/// 列表中的层级须为任务已选择的层级，设置后任务重置为待下载，下次启动时只下载列表中的瓦片
+#[tauri::command]
+pub async fn set_tile_task_list(
+    app: AppHandle,
+    task_id: String,
+    tiles: Option<Vec<TileCoord>>,
+    path: Option<String>,
+) -> CmdResult<u64> {
+    let db = get_tile_db(&app)?;
+
+    let task = db
+        .get_task(&task_id)
+        .map_err(|e| format!("获取任务失败: {}", e))?
+        .ok_or_else(|| AppError::not_found("任务不存在"))?;
+    if TILE_DOWNLOADER
+        .get_state(&task_id)
+        .is_some_and(|state| state.is_running.load(std::sync::atomic::Ordering::Relaxed))
+    {
+        return Err(AppError::conflict("任务运行中，请先取消下载"));
+    }
+
+    let path = path.filter(|p| !p.trim().is_empty());
+    let tiles = match (tiles, path) {
+        (Some(tiles), None) => normalize_tile_list(tiles).map_err(AppError::invalid)?,
+        (None, Some(path)) => load_tile_list_file(Path::new(&path)).map_err(AppError::invalid)?,
+        _ => return Err(AppError::invalid("请指定瓦片列表或瓦片列表文件其中之一")),
+    };
+    if let Some(tile) = tiles.iter().find(|t| !task.zoom_levels.contains(&t.z)) {
+        return Err(AppError::invalid(format!(
+            "瓦片 {}/{}/{} 的层级不在任务层级范围内",
+            tile.z, tile.x, tile.y
+        )));
+    }
+
+    db.set_tile_list(&task_id, &tiles)
+        .map_err(|e| format!("保存瓦片列表失败: {}", e))?;
+    db.init_tile_progress(&task_id, &tiles)
+        .map_err(|e| format!("初始化进度失败: {}", e))?;
+    db.close_progress(&task_id);
+    let total_tiles = tiles.len() as u64;
+    db.reset_task_total(&task_id, total_tiles)
+        .map_err(|e| format!("更新任务失败: {}", e))?;
+    db.update_task_status(&task_id, "pending").ok();
+    db.set_task_error_message(&task_id, None).ok();
+
+    log::info!("任务 {} 限定下载 {} 个指定瓦片", task_id, total_tiles);
+    Ok(total_tiles)
+}
+
+/// 读取任务已下载的本地瓦片，用于离线预览
+#[tauri::command]
+pub async fn get_local_tile(
+    app: AppHandle,
+    task_id: String,
+    z: u32,
+    x: u32,
+    y: u32,
+) -> CmdResult<Option<Vec<u8>>> {
+    let db = get_tile_db(&app)?;
+
+    let task = db
+        .get_task(&task_id)
+        .map_err(|e| format!("获取任务失败: {}", e))?
+        .ok_or_else(|| AppError::not_found("任务不存在"))?;
+
+    let coord = TileCoord::new(z, x, y);
+    if !coord.is_valid() {
+        return Err(AppError::invalid(format!("瓦片坐标超出范围: {}/{}/{}", z, x, y)));
+    }
+    read_tile(
+        &task.output_format,
+        Path::new(&task.output_path),
+        &coord,
+    ).map_err(AppError::from)
+}
+
+/// 任务缩略图缓存目录
+fn thumbnail_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(tile_db_path(app)?.with_file_name("thumbnails"))
+}
+
+/// 生成任务缩略图并返回实际覆盖范围，缩略图缓存在应用目录，force 为 true 时重新生成
+#[tauri::command]
+pub async fn generate_task_thumbnail(
+    app: AppHandle,
+    task_id: String,
+    force: Option<bool>,
+) -> CmdResult<TaskThumbnail> {
+    let db = get_tile_db(&app)?;
+    let cache_dir = thumbnail_dir(&app)?;
+    tokio::task::spawn_blocking(move || {
+        generate_thumbnail(&db, &task_id, &cache_dir, force.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| format!("生成缩略图失败: {}", e))?
+}
+
+/// 按目标 App 将任务输出打包为可直接拷贝到设备的离线地图
+#[tauri::command]
+pub async fn package_offline_map(
+    app: AppHandle,
+    task_id: String,
+    target: TargetApp,
+    output_dir: String,
+    name: Option<String>,
+    overwrite: Option<bool>,
+) -> CmdResult<PackageResult> {
+    let db = get_tile_db(&app)?;
+    let task = db
+        .get_task(&task_id)
+        .map_err(|e| format!("获取任务失败: {}", e))?
+        .ok_or_else(|| AppError::not_found("任务不存在"))?;
+
+    let running = TILE_DOWNLOADER
+        .get_state(&task_id)
+        .map(|s| s.is_running.load(std::sync::atomic::Ordering::Relaxed))
+        .unwrap_or(false);
+    if running {
+        return Err(AppError::conflict("任务正在下载，请等待完成后再打包"));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        build_package(
+            &task,
+            target,
+            Path::new(&output_dir),
+            name.as_deref().filter(|n| !n.trim().is_empty()),
+            overwrite.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| format!("打包离线地图失败: {}", e))?
+}
+
 /// 解压/转换瓦片文件
 #[tauri::command]
 pub async fn convert_tile_file(
     input_path: String,
     output_path: String,
     output_format: String,
-) -> Result<(), String> {
+) -> CmdResult<()> {
     let input = Path::new(&input_path);
     let output = Path::new(&output_path);
 
     if !input.exists() {
-        return Err("输入文件不存在".to_string());
+        return Err(AppError::not_found("输入文件不存在"));
     }
 
     // 检测输入格式
@@ -348,8 +1202,11 @@ pub async fn convert_tile_file(
 
                     if file.is_file() {
                         let name = file.name().to_string();
-                        // 解析 z/x/y.png
-                        let parts: Vec<&str> = name.trim_end_matches(".png").split('/').collect();
+                        // 解析 z/x/y.{扩展名}
+                        let Some(stem) = strip_tile_extension(&name) else {
+                            continue;
+                        };
+                        let parts: Vec<&str> = stem.split('/').collect();
                         if parts.len() >= 3 {
                             if let (Ok(z), Ok(x), Ok(y)) = (
                                 parts[parts.len() - 3].parse::<u32>(),
@@ -393,12 +1250,15 @@ pub async fn convert_tile_file(
 
                 for row in rows {
                     let (z, x, tms_y, data) = row.map_err(|e| format!("读取行失败: {}", e))?;
-                    // TMS Y 翻转
-                    let y = (1u32 << z) - 1 - tms_y;
+                    // TMS Y 翻转，跳过坐标超出范围的行
+                    let Some(TileCoord { y, .. }) = tms_to_xyz(z, x, tms_y) else {
+                        continue;
+                    };
 
                     let tile_dir = output.join(z.to_string()).join(x.to_string());
                     std::fs::create_dir_all(&tile_dir).ok();
-                    let tile_path = tile_dir.join(format!("{}.png", y));
+                    let extension = TileFormat::detect(&data).extension();
+                    let tile_path = tile_dir.join(format!("{}.{}", y, extension));
                     std::fs::write(&tile_path, &data).ok();
                 }
             } else if output_format == "zip" {
@@ -424,15 +1284,16 @@ pub async fn convert_tile_file(
 
                 for row in rows {
                     let (z, x, tms_y, data) = row.map_err(|e| format!("读取行失败: {}", e))?;
-                    let y = (1u32 << z) - 1 - tms_y;
-                    storage.save_tile(&TileCoord::new(z, x, y), &data)?;
+                    if let Some(coord) = tms_to_xyz(z, x, tms_y) {
+                        storage.save_tile(&coord, &data)?;
+                    }
                 }
 
                 storage.finalize()?;
             }
         }
         _ => {
-            return Err(format!("不支持的输入格式: {}", input_ext));
+            return Err(AppError::invalid(format!("不支持的输入格式: {}", input_ext)));
         }
     }
 
@@ -0,0 +1,59 @@
+//! GCJ02 瓦片纠偏
+//!
+//! GCJ02 相对 WGS84 的偏移在单个瓦片范围内变化极小，因此以瓦片中心的偏移量
+//! 对整张瓦片做平移重采样，得到与 WGS84 对齐的瓦片。
+
+use super::imaging::{lonlat_to_pixel, pixel_to_lonlat, RgbaImage, TILE_SIZE};
+use super::types::TileCoord;
+use crate::coords::wgs84_to_gcj02;
+
+/// WGS84 瓦片在 GCJ02 源瓦片中对应的像素窗口
+pub struct SourceWindow {
+    /// 窗口左上角在源瓦片全局像素坐标中的位置
+    pub left: i64,
+    pub top: i64,
+    /// 覆盖该窗口的源瓦片（最多 2x2 个）
+    pub sources: Vec<TileCoord>,
+}
+
+/// 计算 WGS84 瓦片所需的源瓦片窗口
+pub fn source_window(tile: &TileCoord) -> SourceWindow {
+    let size = TILE_SIZE as f64;
+    let center_x = (tile.x as f64 + 0.5) * size;
+    let center_y = (tile.y as f64 + 0.5) * size;
+
+    // 瓦片中心处 WGS84 -> GCJ02 的像素偏移
+    let (lon, lat) = pixel_to_lonlat(center_x, center_y, tile.z);
+    let (gcj_lon, gcj_lat) = wgs84_to_gcj02(lon, lat);
+    let (gcj_x, gcj_y) = lonlat_to_pixel(gcj_lon, gcj_lat, tile.z);
+
+    let left = (tile.x as f64 * size + gcj_x - center_x).round() as i64;
+    let top = (tile.y as f64 * size + gcj_y - center_y).round() as i64;
+
+    let tile_size = TILE_SIZE as i64;
+    let max_index = (1i64 << tile.z) - 1;
+    let mut sources = Vec::new();
+    for sx in left.div_euclid(tile_size)..=(left + tile_size - 1).div_euclid(tile_size) {
+        for sy in top.div_euclid(tile_size)..=(top + tile_size - 1).div_euclid(tile_size) {
+            if (0..=max_index).contains(&sx) && (0..=max_index).contains(&sy) {
+                sources.push(TileCoord::new(tile.z, sx as u32, sy as u32));
+            }
+        }
+    }
+
+    SourceWindow { left, top, sources }
+}
+
+impl SourceWindow {
+    /// 将源瓦片拼接后裁剪出纠偏后的瓦片
+    pub fn compose(&self, sources: &[(TileCoord, RgbaImage)]) -> RgbaImage {
+        let tile_size = TILE_SIZE as i64;
+        let mut out = RgbaImage::filled(TILE_SIZE, TILE_SIZE, [0, 0, 0, 0]);
+        for (coord, image) in sources {
+            let offset_x = coord.x as i64 * tile_size - self.left;
+            let offset_y = coord.y as i64 * tile_size - self.top;
+            out.blit(image, offset_x, offset_y);
+        }
+        out
+    }
+}
@@ -0,0 +1,181 @@
+//! 瓦片平台覆盖范围
+//!
+//! 国内平台在境外（含港澳台）通常没有瓦片，使用粗略的覆盖多边形在创建任务前检测
+
+use super::types::Bounds;
+use serde::Serialize;
+
+/// 平台覆盖范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverageArea {
+    /// 全球覆盖
+    Global,
+    /// 中国大陆（不含港澳台）
+    ChinaMainland,
+}
+
+/// 中国大陆粗略轮廓 (经度, 纬度)，沿边境外扩少许，包含海南岛与近海
+const CHINA_MAINLAND: &[(f64, f64)] = &[
+    (123.5, 53.6),
+    (125.5, 53.2),
+    (127.5, 50.3),
+    (130.6, 48.9),
+    (135.1, 48.4),
+    (133.1, 45.1),
+    (131.0, 42.6),
+    (129.0, 41.9),
+    (125.0, 40.0),
+    (121.1, 38.6),
+    (122.8, 37.4),
+    (120.3, 35.8),
+    (121.2, 32.5),
+    (122.3, 31.2),
+    (122.5, 30.0),
+    (121.0, 27.8),
+    (120.0, 26.2),
+    (118.2, 24.3),
+    (117.2, 23.5),
+    (116.0, 22.7),
+    (114.5, 22.3),
+    (113.5, 21.9),
+    (111.0, 21.3),
+    (111.3, 19.5),
+    (110.0, 18.0),
+    (108.5, 18.2),
+    (108.6, 19.3),
+    (108.0, 21.5),
+    (106.7, 22.0),
+    (105.5, 23.2),
+    (103.9, 22.5),
+    (102.2, 22.4),
+    (101.6, 21.2),
+    (100.2, 21.5),
+    (99.2, 22.1),
+    (98.7, 24.0),
+    (97.5, 24.8),
+    (98.5, 27.5),
+    (97.4, 28.3),
+    (96.0, 29.4),
+    (94.6, 29.2),
+    (92.0, 27.8),
+    (89.6, 28.1),
+    (88.0, 27.9),
+    (86.0, 27.9),
+    (84.0, 28.6),
+    (81.2, 30.1),
+    (78.7, 32.6),
+    (77.8, 35.5),
+    (75.8, 36.8),
+    (74.5, 37.4),
+    (73.5, 39.5),
+    (75.5, 40.6),
+    (76.8, 41.0),
+    (80.2, 42.2),
+    (80.8, 45.1),
+    (82.5, 45.2),
+    (83.0, 47.2),
+    (85.5, 47.0),
+    (87.3, 49.1),
+    (90.0, 47.9),
+    (91.0, 46.0),
+    (90.9, 45.2),
+    (95.3, 44.3),
+    (96.4, 42.8),
+    (100.8, 42.6),
+    (105.0, 41.6),
+    (110.4, 42.7),
+    (111.9, 43.7),
+    (111.4, 44.4),
+    (113.6, 44.8),
+    (116.7, 46.4),
+    (119.9, 46.7),
+    (115.6, 47.8),
+    (117.8, 49.5),
+    (119.3, 50.3),
+    (120.8, 52.6),
+];
+
+/// 轮廓内需要排除的区域（香港、澳门），(西, 南, 东, 北)
+const CHINA_MAINLAND_EXCLUDED: &[(f64, f64, f64, f64)] = &[
+    (113.82, 22.13, 114.45, 22.50),
+    (113.52, 22.10, 113.60, 22.22),
+];
+
+/// 射线法判断点是否在多边形内
+pub fn point_in_polygon(lon: f64, lat: f64, polygon: &[(f64, f64)]) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+    for i in 0..polygon.len() {
+        let (xi, yi) = polygon[i];
+        let (xj, yj) = polygon[j];
+        if (yi > lat) != (yj > lat) && lon < (xj - xi) * (lat - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+impl CoverageArea {
+    /// 判断坐标是否在覆盖范围内
+    pub fn contains(&self, lon: f64, lat: f64) -> bool {
+        match self {
+            CoverageArea::Global => true,
+            CoverageArea::ChinaMainland => {
+                point_in_polygon(lon, lat, CHINA_MAINLAND)
+                    && !CHINA_MAINLAND_EXCLUDED
+                        .iter()
+                        .any(|(w, s, e, n)| lon >= *w && lon <= *e && lat >= *s && lat <= *n)
+            }
+        }
+    }
+
+    /// 采样统计区域落在覆盖范围内的比例
+    pub fn covered_ratio(&self, bounds: &Bounds) -> f64 {
+        if *self == CoverageArea::Global {
+            return 1.0;
+        }
+
+        const STEPS: usize = 8;
+        let mut inside = 0;
+        for i in 0..=STEPS {
+            for j in 0..=STEPS {
+                let lon = bounds.west + (bounds.east - bounds.west) * i as f64 / STEPS as f64;
+                let lat = bounds.south + (bounds.north - bounds.south) * j as f64 / STEPS as f64;
+                if self.contains(lon, lat) {
+                    inside += 1;
+                }
+            }
+        }
+        inside as f64 / ((STEPS + 1) * (STEPS + 1)) as f64
+    }
+}
+
+/// 覆盖范围检测结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverageCheck {
+    pub platform: String,
+    /// 区域落在平台覆盖范围内的比例 0.0 - 1.0
+    pub covered_ratio: f64,
+    pub warning: Option<String>,
+    /// 建议改用的全球覆盖平台
+    pub suggested_platforms: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_china_mainland_coverage() {
+        let china = CoverageArea::ChinaMainland;
+        assert!(china.contains(116.40, 39.90)); // 北京
+        assert!(china.contains(119.80, 33.78)); // 阜宁
+        assert!(china.contains(91.13, 29.65)); // 拉萨
+        assert!(china.contains(110.35, 20.02)); // 海口
+        assert!(!china.contains(114.17, 22.30)); // 香港
+        assert!(!china.contains(113.55, 22.19)); // 澳门
+        assert!(!china.contains(121.56, 25.03)); // 台北
+        assert!(!china.contains(139.69, 35.69)); // 东京
+    }
+}
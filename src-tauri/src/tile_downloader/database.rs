@@ -1,23 +1,400 @@
 use parking_lot::Mutex;
-use rusqlite::{params, Connection, Result};
-use std::path::Path;
+use rusqlite::{ffi, params, Connection, OpenFlags, OptionalExtension, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use super::types::{Bounds, TaskInfo, TileCoord};
+use crate::power::CompletionAction;
 
+use super::types::{
+    Bounds, ClipPolygon, HeaderOptions, SourceStat, TaskInfo, TileCoord, TileError, TileExtent, ZoomProgress,
+};
+
+/// 任务查询的列顺序，与 row_to_task 的下标一一对应
+const TASK_COLUMNS: &str = "id, name, platform, map_type, bounds_north, bounds_south, bounds_east, bounds_west, \
+     zoom_levels, status, total_tiles, completed_tiles, failed_tiles, output_path, \
+     output_format, thread_count, retry_count, api_key, created_at, updated_at, completed_at, error_message, \
+     max_connections_per_host, user_agent, referer, accept, random_user_agent, \
+     coord_correction, fallback_platforms, use_shared_cache, fill_no_data, rate_limit, clip_polygon, \
+     on_complete";
+
+/// 将查询行转换为任务信息
+fn row_to_task(row: &rusqlite::Row) -> Result<TaskInfo> {
+    let zoom_str: String = row.get(8)?;
+    let clip_polygon: Option<ClipPolygon> = row
+        .get::<_, Option<String>>(32)?
+        .and_then(|json| serde_json::from_str(&json).ok());
+    let zoom_levels: Vec<u32> = zoom_str
+        .split(',')
+        .filter_map(|s| s.trim().parse().ok())
+        .collect();
+
+    Ok(TaskInfo {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        platform: row.get(2)?,
+        map_type: row.get(3)?,
+        bounds: Bounds {
+            north: row.get(4)?,
+            south: row.get(5)?,
+            east: row.get(6)?,
+            west: row.get(7)?,
+        },
+        zoom_levels,
+        status: row.get(9)?,
+        total_tiles: row.get::<_, i64>(10)? as u64,
+        completed_tiles: row.get::<_, i64>(11)? as u64,
+        failed_tiles: row.get::<_, i64>(12)? as u64,
+        output_path: row.get(13)?,
+        output_format: row.get(14)?,
+        thread_count: row.get(15)?,
+        retry_count: row.get(16)?,
+        api_key: row.get(17)?,
+        created_at: row.get(18)?,
+        updated_at: row.get(19)?,
+        completed_at: row.get(20)?,
+        error_message: row.get(21)?,
+        max_connections_per_host: row.get(22)?,
+        headers: HeaderOptions {
+            user_agent: row.get(23)?,
+            referer: row.get(24)?,
+            accept: row.get(25)?,
+            random_user_agent: row.get::<_, i64>(26)? == 1,
+        },
+        coord_correction: row.get::<_, i64>(27)? == 1,
+        fallback_platforms: row
+            .get::<_, Option<String>>(28)?
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+        use_shared_cache: row.get::<_, i64>(29)? == 1,
+        fill_no_data: row.get::<_, i64>(30)? == 1,
+        rate_limit: row.get(31)?,
+        clipped: clip_polygon.is_some(),
+        clip_polygon,
+        on_complete: row
+            .get::<_, Option<String>>(33)?
+            .map(|value| CompletionAction::parse(&value))
+            .unwrap_or_default(),
+        download_speed: 0.0,
+        zoom_progress: Vec::new(),
+        source_stats: Vec::new(),
+        no_data_tiles: 0,
+    })
+}
+
+/// 瓦片任务数据库
+///
+/// 主库只存任务元数据；每个任务的瓦片进度存放在 tile_progress 目录下的独立 SQLite 文件中，
+/// 千万级任务之间互不拖慢查询，删除任务时直接删除文件。
 pub struct TileDatabase {
     conn: Mutex<Connection>,
+    path: PathBuf,
+    /// 各任务进度库所在目录
+    progress_dir: PathBuf,
+    /// 下载中的任务已打开的进度库，任务结束时关闭
+    progress: Mutex<HashMap<String, Arc<Mutex<Connection>>>>,
+}
+
+/// 进度库表结构
+const PROGRESS_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS tile_progress (
+        z INTEGER NOT NULL,
+        x INTEGER NOT NULL,
+        y INTEGER NOT NULL,
+        status TEXT NOT NULL DEFAULT 'pending',
+        retry_count INTEGER NOT NULL DEFAULT 0,
+        error_message TEXT,
+        downloaded_at TEXT,
+        source TEXT,
+        url TEXT,
+        failed_at TEXT,
+        PRIMARY KEY (z, x, y)
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_tile_progress_status ON tile_progress(status);
+
+    -- 按坐标列表下载的任务，只下载其中的瓦片
+    CREATE TABLE IF NOT EXISTS tile_list (
+        z INTEGER NOT NULL,
+        x INTEGER NOT NULL,
+        y INTEGER NOT NULL,
+        PRIMARY KEY (z, x, y)
+    );
+"#;
+
+/// 去掉错误信息中的请求地址，便于同类错误归并
+fn error_reason(message: &str) -> String {
+    let reason = match message.find(" for url (") {
+        Some(start) => {
+            let end = message[start..]
+                .find(')')
+                .map(|i| start + i + 1)
+                .unwrap_or(message.len());
+            format!("{}{}", &message[..start], &message[end..])
+        }
+        None => message.to_string(),
+    };
+    match reason.trim() {
+        "" => "未知错误".to_string(),
+        reason => reason.to_string(),
+    }
 }
 
 impl TileDatabase {
     pub fn new(path: &Path) -> Result<Self> {
         let conn = Connection::open(path)?;
         conn.execute_batch("PRAGMA journal_mode=WAL;")?;
+        conn.busy_timeout(crate::database::BUSY_TIMEOUT)?;
 
-        let db = Self { conn: Mutex::new(conn) };
+        let db = Self {
+            conn: Mutex::new(conn),
+            path: path.to_path_buf(),
+            progress_dir: path.with_file_name("tile_progress"),
+            progress: Mutex::new(HashMap::new()),
+        };
+        if let Err(e) = std::fs::create_dir_all(&db.progress_dir) {
+            log::warn!("创建瓦片进度目录失败: {}", e);
+        }
         db.init_tables()?;
+        db.migrate()?;
+        db.remove_orphan_progress();
         Ok(db)
     }
 
+    /// 进度库路径；任务 ID 来自前端，不是 UUID 时返回 None，避免拼出进度目录以外的路径
+    fn progress_path(&self, task_id: &str) -> Option<PathBuf> {
+        uuid::Uuid::parse_str(task_id)
+            .ok()
+            .map(|_| self.progress_dir.join(format!("{}.db", task_id)))
+    }
+
+    fn task_exists(&self, task_id: &str) -> Result<bool> {
+        let found: Option<i64> = self
+            .conn
+            .lock()
+            .query_row(
+                "SELECT 1 FROM tile_download_tasks WHERE id = ?1",
+                params![task_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    /// 获取任务的进度库，首次打开时创建，并迁入旧版主库中该任务的进度；只用于写入
+    fn progress(&self, task_id: &str) -> Result<Arc<Mutex<Connection>>> {
+        let mut opened = self.progress.lock();
+        if let Some(conn) = opened.get(task_id) {
+            return Ok(conn.clone());
+        }
+
+        let path = match self.progress_path(task_id) {
+            Some(path) if self.task_exists(task_id)? => path,
+            _ => {
+                return Err(rusqlite::Error::SqliteFailure(
+                    ffi::Error::new(ffi::SQLITE_NOTFOUND),
+                    Some(format!("瓦片任务不存在: {}", task_id)),
+                ))
+            }
+        };
+        let conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA journal_mode=WAL;")?;
+        conn.busy_timeout(crate::database::BUSY_TIMEOUT)?;
+        conn.execute_batch(PROGRESS_SCHEMA)?;
+        self.migrate_legacy_progress(task_id, &conn)?;
+
+        let conn = Arc::new(Mutex::new(conn));
+        opened.insert(task_id.to_string(), conn.clone());
+        Ok(conn)
+    }
+
+    /// 读取用的进度库：下载中的任务复用已打开的连接，否则临时打开、用完即关，
+    /// 不创建文件；任务不存在或还没有进度时返回 None
+    fn read_progress(&self, task_id: &str) -> Result<Option<Arc<Mutex<Connection>>>> {
+        if let Some(conn) = self.progress.lock().get(task_id) {
+            return Ok(Some(conn.clone()));
+        }
+        let Some(path) = self.progress_path(task_id) else {
+            return Ok(None);
+        };
+        if !self.task_exists(task_id)? {
+            return Ok(None);
+        }
+        if !path.exists() {
+            // 旧版进度还在主库时先迁入独立进度库
+            if self.has_legacy_progress()? {
+                let conn = self.progress(task_id)?;
+                self.close_progress(task_id);
+                return Ok(Some(conn));
+            }
+            return Ok(None);
+        }
+
+        let conn = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_WRITE
+                | OpenFlags::SQLITE_OPEN_NO_MUTEX
+                | OpenFlags::SQLITE_OPEN_URI,
+        )?;
+        conn.busy_timeout(crate::database::BUSY_TIMEOUT)?;
+        Ok(Some(Arc::new(Mutex::new(conn))))
+    }
+
+    /// 关闭任务的进度库连接，任务结束下载时调用
+    pub fn close_progress(&self, task_id: &str) {
+        self.progress.lock().remove(task_id);
+    }
+
+    fn has_legacy_progress(&self) -> Result<bool> {
+        self.conn.lock().query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'tile_progress'",
+            [],
+            |row| row.get(0),
+        )
+    }
+
+    /// 旧版本所有任务的进度都在主库 tile_progress 表中，按任务迁入独立进度库，全部迁完后删除该表
+    fn migrate_legacy_progress(&self, task_id: &str, progress: &Connection) -> Result<()> {
+        if !self.has_legacy_progress()? {
+            return Ok(());
+        }
+        let main = self.conn.lock();
+
+        progress.execute(
+            "ATTACH DATABASE ?1 AS legacy",
+            params![self.path.to_string_lossy()],
+        )?;
+        let copied = progress.execute(
+            "INSERT OR IGNORE INTO tile_progress
+             (z, x, y, status, retry_count, error_message, downloaded_at, source, url, failed_at)
+             SELECT z, x, y, status, retry_count, error_message, downloaded_at, source, url, failed_at
+             FROM legacy.tile_progress WHERE task_id = ?1",
+            params![task_id],
+        );
+        progress.execute("DETACH DATABASE legacy", [])?;
+        let copied = copied?;
+
+        if copied > 0 {
+            log::info!("迁移任务 {} 的 {} 条瓦片进度到独立进度库", task_id, copied);
+            main.execute("DELETE FROM tile_progress WHERE task_id = ?1", params![task_id])?;
+        }
+        // 已删除任务遗留的进度不再迁移
+        let remaining: Option<i64> = main
+            .query_row(
+                "SELECT 1 FROM tile_progress WHERE task_id IN (SELECT id FROM tile_download_tasks) LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if remaining.is_none() {
+            main.execute_batch("DROP TABLE tile_progress;")?;
+            log::info!("旧版瓦片进度已全部迁移");
+        }
+        Ok(())
+    }
+
+    /// 关闭并删除任务的进度库
+    fn remove_progress(&self, task_id: &str) {
+        self.close_progress(task_id);
+        let Some(path) = self.progress_path(task_id) else {
+            return;
+        };
+        for suffix in ["", "-wal", "-shm"] {
+            let mut file = path.as_os_str().to_owned();
+            file.push(suffix);
+            let _ = std::fs::remove_file(PathBuf::from(file));
+        }
+    }
+
+    /// 删除主库中已不存在的任务的进度库（如导入工作区后）
+    fn remove_orphan_progress(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.progress_dir) else {
+            return;
+        };
+        let task_ids: Vec<String> = match self.get_all_tasks() {
+            Ok(tasks) => tasks.into_iter().map(|t| t.id).collect(),
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("db") {
+                continue;
+            }
+            let Some(task_id) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if !task_ids.iter().any(|id| id == task_id) {
+                log::info!("删除已不存在任务的瓦片进度库: {}", task_id);
+                self.remove_progress(task_id);
+            }
+        }
+    }
+
+    /// 数据库迁移：为旧版本的表补充新增字段
+    fn migrate(&self) -> Result<()> {
+        let conn = self.conn.lock();
+        let columns = [
+            ("tile_download_tasks", "max_connections_per_host", "INTEGER NOT NULL DEFAULT 6"),
+            ("tile_download_tasks", "user_agent", "TEXT"),
+            ("tile_download_tasks", "referer", "TEXT"),
+            ("tile_download_tasks", "accept", "TEXT"),
+            ("tile_download_tasks", "random_user_agent", "INTEGER NOT NULL DEFAULT 0"),
+            ("tile_download_tasks", "coord_correction", "INTEGER NOT NULL DEFAULT 0"),
+            ("tile_download_tasks", "fallback_platforms", "TEXT"),
+            ("tile_download_tasks", "use_shared_cache", "INTEGER NOT NULL DEFAULT 0"),
+            ("tile_download_tasks", "fill_no_data", "INTEGER NOT NULL DEFAULT 0"),
+            ("tile_download_tasks", "rate_limit", "REAL"),
+            ("tile_download_tasks", "clip_polygon", "TEXT"),
+            ("tile_download_tasks", "on_complete", "TEXT"),
+            ("tile_progress", "source", "TEXT"),
+            ("tile_progress", "url", "TEXT"),
+            ("tile_progress", "failed_at", "TEXT"),
+        ];
+
+        for (table, name, definition) in columns {
+            // 旧版进度表迁移完成后已删除
+            let table_exists: bool = conn
+                .query_row(
+                    "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                    params![table],
+                    |row| row.get(0),
+                )
+                .unwrap_or(false);
+            if !table_exists {
+                continue;
+            }
+
+            let exists: bool = conn
+                .query_row(
+                    "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+                    params![table, name],
+                    |row| row.get(0),
+                )
+                .unwrap_or(false);
+
+            if !exists {
+                log::info!("迁移瓦片数据库：{} 添加 {} 字段", table, name);
+                conn.execute(
+                    &format!("ALTER TABLE {} ADD COLUMN {} {}", table, name, definition),
+                    [],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 将数据库一致性快照写入新文件
+    pub fn vacuum_into(&self, dest: &Path) -> Result<()> {
+        self.conn
+            .lock()
+            .execute("VACUUM INTO ?1", params![dest.to_string_lossy()])?;
+        Ok(())
+    }
+
     fn init_tables(&self) -> Result<()> {
         self.conn.lock().execute_batch(
             r#"
@@ -44,26 +421,22 @@ impl TileDatabase {
                 created_at TEXT DEFAULT CURRENT_TIMESTAMP,
                 updated_at TEXT DEFAULT CURRENT_TIMESTAMP,
                 completed_at TEXT,
-                error_message TEXT
-            );
-
-            CREATE INDEX IF NOT EXISTS idx_tile_task_status ON tile_download_tasks(status);
-
-            -- 瓦片进度表
-            CREATE TABLE IF NOT EXISTS tile_progress (
-                task_id TEXT NOT NULL,
-                z INTEGER NOT NULL,
-                x INTEGER NOT NULL,
-                y INTEGER NOT NULL,
-                status TEXT NOT NULL DEFAULT 'pending',
-                retry_count INTEGER NOT NULL DEFAULT 0,
                 error_message TEXT,
-                downloaded_at TEXT,
-                PRIMARY KEY (task_id, z, x, y)
+                max_connections_per_host INTEGER NOT NULL DEFAULT 6,
+                user_agent TEXT,
+                referer TEXT,
+                accept TEXT,
+                random_user_agent INTEGER NOT NULL DEFAULT 0,
+                coord_correction INTEGER NOT NULL DEFAULT 0,
+                fallback_platforms TEXT,
+                use_shared_cache INTEGER NOT NULL DEFAULT 0,
+                fill_no_data INTEGER NOT NULL DEFAULT 0,
+                rate_limit REAL,
+                clip_polygon TEXT,
+                on_complete TEXT
             );
 
-            CREATE INDEX IF NOT EXISTS idx_tile_progress_task ON tile_progress(task_id);
-            CREATE INDEX IF NOT EXISTS idx_tile_progress_status ON tile_progress(task_id, status);
+            CREATE INDEX IF NOT EXISTS idx_tile_task_status ON tile_download_tasks(status);
             "#,
         )?;
         Ok(())
@@ -84,7 +457,20 @@ impl TileDatabase {
         thread_count: u32,
         retry_count: u32,
         api_key: Option<&str>,
+        max_connections_per_host: u32,
+        headers: &HeaderOptions,
+        coord_correction: bool,
+        fallback_platforms: &[String],
+        use_shared_cache: bool,
+        fill_no_data: bool,
+        rate_limit: Option<f64>,
+        clip_polygon: Option<&ClipPolygon>,
+        on_complete: CompletionAction,
     ) -> Result<()> {
+        let clip_json = clip_polygon
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
         let zoom_str = zoom_levels
             .iter()
             .map(|z| z.to_string())
@@ -94,8 +480,10 @@ impl TileDatabase {
         self.conn.lock().execute(
             r#"INSERT INTO tile_download_tasks
                (id, name, platform, map_type, bounds_north, bounds_south, bounds_east, bounds_west,
-                zoom_levels, total_tiles, output_path, output_format, thread_count, retry_count, api_key)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)"#,
+                zoom_levels, total_tiles, output_path, output_format, thread_count, retry_count, api_key,
+                max_connections_per_host, user_agent, referer, accept, random_user_agent, coord_correction,
+                fallback_platforms, use_shared_cache, fill_no_data, rate_limit, clip_polygon, on_complete)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27)"#,
             params![
                 id,
                 name,
@@ -112,6 +500,18 @@ impl TileDatabase {
                 thread_count,
                 retry_count,
                 api_key,
+                max_connections_per_host,
+                headers.user_agent,
+                headers.referer,
+                headers.accept,
+                headers.random_user_agent as i64,
+                coord_correction as i64,
+                fallback_platforms.join(","),
+                use_shared_cache as i64,
+                fill_no_data as i64,
+                rate_limit,
+                clip_json,
+                on_complete.as_str(),
             ],
         )?;
         Ok(())
@@ -120,48 +520,12 @@ impl TileDatabase {
     /// 获取所有任务
     pub fn get_all_tasks(&self) -> Result<Vec<TaskInfo>> {
         let conn = self.conn.lock();
-        let mut stmt = conn.prepare(
-            r#"SELECT id, name, platform, map_type, bounds_north, bounds_south, bounds_east, bounds_west,
-                      zoom_levels, status, total_tiles, completed_tiles, failed_tiles, output_path,
-                      output_format, thread_count, retry_count, api_key, created_at, updated_at, completed_at, error_message
-               FROM tile_download_tasks ORDER BY created_at DESC"#,
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tile_download_tasks ORDER BY created_at DESC",
+            TASK_COLUMNS
+        ))?;
 
-        let rows = stmt.query_map([], |row| {
-            let zoom_str: String = row.get(8)?;
-            let zoom_levels: Vec<u32> = zoom_str
-                .split(',')
-                .filter_map(|s| s.trim().parse().ok())
-                .collect();
-
-            Ok(TaskInfo {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                platform: row.get(2)?,
-                map_type: row.get(3)?,
-                bounds: Bounds {
-                    north: row.get(4)?,
-                    south: row.get(5)?,
-                    east: row.get(6)?,
-                    west: row.get(7)?,
-                },
-                zoom_levels,
-                status: row.get(9)?,
-                total_tiles: row.get::<_, i64>(10)? as u64,
-                completed_tiles: row.get::<_, i64>(11)? as u64,
-                failed_tiles: row.get::<_, i64>(12)? as u64,
-                output_path: row.get(13)?,
-                output_format: row.get(14)?,
-                thread_count: row.get(15)?,
-                retry_count: row.get(16)?,
-                api_key: row.get(17)?,
-                created_at: row.get(18)?,
-                updated_at: row.get(19)?,
-                completed_at: row.get(20)?,
-                error_message: row.get(21)?,
-                download_speed: 0.0,
-            })
-        })?;
+        let rows = stmt.query_map([], row_to_task)?;
 
         let mut tasks = Vec::new();
         for row in rows {
@@ -173,48 +537,12 @@ impl TileDatabase {
     /// 获取单个任务
     pub fn get_task(&self, task_id: &str) -> Result<Option<TaskInfo>> {
         let conn = self.conn.lock();
-        let mut stmt = conn.prepare(
-            r#"SELECT id, name, platform, map_type, bounds_north, bounds_south, bounds_east, bounds_west,
-                      zoom_levels, status, total_tiles, completed_tiles, failed_tiles, output_path,
-                      output_format, thread_count, retry_count, api_key, created_at, updated_at, completed_at, error_message
-               FROM tile_download_tasks WHERE id = ?1"#,
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM tile_download_tasks WHERE id = ?1",
+            TASK_COLUMNS
+        ))?;
 
-        let result = stmt.query_row(params![task_id], |row| {
-            let zoom_str: String = row.get(8)?;
-            let zoom_levels: Vec<u32> = zoom_str
-                .split(',')
-                .filter_map(|s| s.trim().parse().ok())
-                .collect();
-
-            Ok(TaskInfo {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                platform: row.get(2)?,
-                map_type: row.get(3)?,
-                bounds: Bounds {
-                    north: row.get(4)?,
-                    south: row.get(5)?,
-                    east: row.get(6)?,
-                    west: row.get(7)?,
-                },
-                zoom_levels,
-                status: row.get(9)?,
-                total_tiles: row.get::<_, i64>(10)? as u64,
-                completed_tiles: row.get::<_, i64>(11)? as u64,
-                failed_tiles: row.get::<_, i64>(12)? as u64,
-                output_path: row.get(13)?,
-                output_format: row.get(14)?,
-                thread_count: row.get(15)?,
-                retry_count: row.get(16)?,
-                api_key: row.get(17)?,
-                created_at: row.get(18)?,
-                updated_at: row.get(19)?,
-                completed_at: row.get(20)?,
-                error_message: row.get(21)?,
-                download_speed: 0.0,
-            })
-        });
+        let result = stmt.query_row(params![task_id], row_to_task);
 
         match result {
             Ok(task) => Ok(Some(task)),
@@ -268,6 +596,100 @@ impl TileDatabase {
         Ok(())
     }
 
+    /// 替换瓦片列表后重置任务的瓦片总数与已完成、失败计数
+    pub fn reset_task_total(&self, task_id: &str, total_tiles: u64) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let updated = self.conn.lock().execute(
+            "UPDATE tile_download_tasks SET total_tiles = ?1, completed_tiles = 0, failed_tiles = 0, updated_at = ?2 WHERE id = ?3",
+            params![total_tiles as i64, now, task_id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// 设置任务级错误信息，None 表示清除
+    pub fn set_task_error_message(&self, task_id: &str, error: Option<&str>) -> Result<()> {
+        self.conn.lock().execute(
+            "UPDATE tile_download_tasks SET error_message = ?1 WHERE id = ?2",
+            params![error, task_id],
+        )?;
+        Ok(())
+    }
+
+    /// 汇总失败瓦片的主要原因，返回前 top 项及其占比，无失败瓦片时返回 None
+    pub fn summarize_tile_errors(&self, task_id: &str, top: usize) -> Result<Option<String>> {
+        let Some(progress) = self.read_progress(task_id)? else {
+            return Ok(None);
+        };
+        let conn = progress.lock();
+        let mut stmt = conn.prepare(
+            r#"SELECT error_message, COUNT(*) FROM tile_progress
+               WHERE status = 'failed'
+               GROUP BY error_message"#,
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                row.get::<_, i64>(1)? as u64,
+            ))
+        })?;
+
+        // 同类错误可能因请求地址不同而文本不同，归并后再统计
+        let mut reasons: Vec<(String, u64)> = Vec::new();
+        for row in rows {
+            let (message, count) = row?;
+            let reason = error_reason(&message);
+            match reasons.iter_mut().find(|(r, _)| *r == reason) {
+                Some((_, c)) => *c += count,
+                None => reasons.push((reason, count)),
+            }
+        }
+
+        let total: u64 = reasons.iter().map(|(_, c)| c).sum();
+        if total == 0 {
+            return Ok(None);
+        }
+        reasons.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let parts: Vec<String> = reasons
+            .iter()
+            .take(top)
+            .map(|(reason, count)| {
+                format!(
+                    "{} {} 个（{:.0}%）",
+                    reason,
+                    count,
+                    *count as f64 * 100.0 / total as f64
+                )
+            })
+            .collect();
+        Ok(Some(format!("{} 个瓦片失败，主要原因：{}", total, parts.join("；"))))
+    }
+
+    /// 更新运行参数（线程数、重试次数、限速），下次启动任务时生效
+    pub fn update_runtime_config(
+        &self,
+        task_id: &str,
+        thread_count: u32,
+        retry_count: u32,
+        rate_limit: Option<f64>,
+    ) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let updated = self.conn.lock().execute(
+            "UPDATE tile_download_tasks SET thread_count = ?1, retry_count = ?2, rate_limit = ?3, updated_at = ?4 WHERE id = ?5",
+            params![thread_count, retry_count, rate_limit, now, task_id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// 更新下载完成后的动作
+    pub fn update_on_complete(&self, task_id: &str, action: CompletionAction) -> Result<bool> {
+        let updated = self.conn.lock().execute(
+            "UPDATE tile_download_tasks SET on_complete = ?1 WHERE id = ?2",
+            params![action.as_str(), task_id],
+        )?;
+        Ok(updated > 0)
+    }
+
     /// 更新线程数
     pub fn update_thread_count(&self, task_id: &str, count: u32) -> Result<()> {
         self.conn.lock().execute(
@@ -277,35 +699,32 @@ impl TileDatabase {
         Ok(())
     }
 
-    /// 删除任务
+    /// 删除任务及其进度库
     pub fn delete_task(&self, task_id: &str) -> Result<()> {
-        let conn = self.conn.lock();
-        conn.execute(
-            "DELETE FROM tile_progress WHERE task_id = ?1",
-            params![task_id],
-        )?;
-        conn.execute(
+        self.conn.lock().execute(
             "DELETE FROM tile_download_tasks WHERE id = ?1",
             params![task_id],
         )?;
+        self.remove_progress(task_id);
         Ok(())
     }
 
     /// 初始化任务的瓦片列表
     pub fn init_tile_progress(&self, task_id: &str, tiles: &[TileCoord]) -> Result<()> {
-        let mut conn = self.conn.lock();
+        let progress = self.progress(task_id)?;
+        let mut conn = progress.lock();
         let tx = conn.transaction()?;
 
         // 先删除旧的进度记录
-        tx.execute("DELETE FROM tile_progress WHERE task_id = ?1", params![task_id])?;
+        tx.execute("DELETE FROM tile_progress", [])?;
 
         // 批量插入
         let mut stmt = tx.prepare(
-            "INSERT INTO tile_progress (task_id, z, x, y, status) VALUES (?1, ?2, ?3, ?4, 'pending')",
+            "INSERT INTO tile_progress (z, x, y, status) VALUES (?1, ?2, ?3, 'pending')",
         )?;
 
         for tile in tiles {
-            stmt.execute(params![task_id, tile.z, tile.x, tile.y])?;
+            stmt.execute(params![tile.z, tile.x, tile.y])?;
         }
 
         drop(stmt);
@@ -313,14 +732,76 @@ impl TileDatabase {
         Ok(())
     }
 
+    /// 保存任务的瓦片坐标列表，替换已有列表
+    pub fn set_tile_list(&self, task_id: &str, tiles: &[TileCoord]) -> Result<()> {
+        let progress = self.progress(task_id)?;
+        let mut conn = progress.lock();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM tile_list", [])?;
+        {
+            let mut stmt = tx.prepare("INSERT OR IGNORE INTO tile_list (z, x, y) VALUES (?1, ?2, ?3)")?;
+            for tile in tiles {
+                stmt.execute(params![tile.z, tile.x, tile.y])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// 读取任务的瓦片坐标列表，按范围下载的任务返回 None
+    pub fn get_tile_list(&self, task_id: &str) -> Result<Option<Vec<TileCoord>>> {
+        let Some(progress) = self.read_progress(task_id)? else {
+            return Ok(None);
+        };
+        let conn = progress.lock();
+        let mut stmt = conn.prepare("SELECT z, x, y FROM tile_list ORDER BY z, x, y")?;
+        let tiles = stmt
+            .query_map([], |row| {
+                Ok(TileCoord {
+                    z: row.get(0)?,
+                    x: row.get(1)?,
+                    y: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(if tiles.is_empty() { None } else { Some(tiles) })
+    }
+
     /// 获取待下载的瓦片
     pub fn get_pending_tiles(&self, task_id: &str, limit: usize) -> Result<Vec<TileCoord>> {
-        let conn = self.conn.lock();
+        let Some(progress) = self.read_progress(task_id)? else {
+            return Ok(Vec::new());
+        };
+        let conn = progress.lock();
+        let mut stmt =
+            conn.prepare("SELECT z, x, y FROM tile_progress WHERE status = 'pending' LIMIT ?1")?;
+
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            Ok(TileCoord {
+                z: row.get(0)?,
+                x: row.get(1)?,
+                y: row.get(2)?,
+            })
+        })?;
+
+        let mut tiles = Vec::new();
+        for row in rows {
+            tiles.push(row?);
+        }
+        Ok(tiles)
+    }
+
+    /// 随机抽取已完成的瓦片
+    pub fn sample_completed_tiles(&self, task_id: &str, limit: u32) -> Result<Vec<TileCoord>> {
+        let Some(progress) = self.read_progress(task_id)? else {
+            return Ok(Vec::new());
+        };
+        let conn = progress.lock();
         let mut stmt = conn.prepare(
-            "SELECT z, x, y FROM tile_progress WHERE task_id = ?1 AND status = 'pending' LIMIT ?2",
+            "SELECT z, x, y FROM tile_progress WHERE status = 'completed' ORDER BY RANDOM() LIMIT ?1",
         )?;
 
-        let rows = stmt.query_map(params![task_id, limit as i64], |row| {
+        let rows = stmt.query_map(params![limit], |row| {
             Ok(TileCoord {
                 z: row.get(0)?,
                 x: row.get(1)?,
@@ -337,12 +818,13 @@ impl TileDatabase {
 
     /// 获取失败的瓦片
     pub fn get_failed_tiles(&self, task_id: &str) -> Result<Vec<TileCoord>> {
-        let conn = self.conn.lock();
-        let mut stmt = conn.prepare(
-            "SELECT z, x, y FROM tile_progress WHERE task_id = ?1 AND status = 'failed'",
-        )?;
+        let Some(progress) = self.read_progress(task_id)? else {
+            return Ok(Vec::new());
+        };
+        let conn = progress.lock();
+        let mut stmt = conn.prepare("SELECT z, x, y FROM tile_progress WHERE status = 'failed'")?;
 
-        let rows = stmt.query_map(params![task_id], |row| {
+        let rows = stmt.query_map([], |row| {
             Ok(TileCoord {
                 z: row.get(0)?,
                 x: row.get(1)?,
@@ -358,54 +840,207 @@ impl TileDatabase {
     }
 
     /// 标记瓦片完成
-    pub fn mark_tile_completed(&self, task_id: &str, tile: &TileCoord) -> Result<()> {
+    pub fn mark_tile_completed(&self, task_id: &str, tile: &TileCoord, source: &str) -> Result<()> {
         let now = chrono::Utc::now().to_rfc3339();
-        self.conn.lock().execute(
-            "UPDATE tile_progress SET status = 'completed', downloaded_at = ?1 WHERE task_id = ?2 AND z = ?3 AND x = ?4 AND y = ?5",
-            params![now, task_id, tile.z, tile.x, tile.y],
+        self.progress(task_id)?.lock().execute(
+            "UPDATE tile_progress SET status = 'completed', downloaded_at = ?1, source = ?2 WHERE z = ?3 AND x = ?4 AND y = ?5",
+            params![now, source, tile.z, tile.x, tile.y],
         )?;
         Ok(())
     }
 
-    /// 标记瓦片失败
-    pub fn mark_tile_failed(&self, task_id: &str, tile: &TileCoord, error: &str) -> Result<()> {
-        self.conn.lock().execute(
-            "UPDATE tile_progress SET status = 'failed', error_message = ?1, retry_count = retry_count + 1 WHERE task_id = ?2 AND z = ?3 AND x = ?4 AND y = ?5",
-            params![error, task_id, tile.z, tile.x, tile.y],
+    /// 标记瓦片失败，url 为出错的请求地址
+    pub fn mark_tile_failed(
+        &self,
+        task_id: &str,
+        tile: &TileCoord,
+        url: Option<&str>,
+        error: &str,
+    ) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.progress(task_id)?.lock().execute(
+            "UPDATE tile_progress SET status = 'failed', error_message = ?1, url = ?2, failed_at = ?3, retry_count = retry_count + 1 WHERE z = ?4 AND x = ?5 AND y = ?6",
+            params![error, url, now, tile.z, tile.x, tile.y],
         )?;
         Ok(())
     }
 
+    /// 标记瓦片为无数据（所有来源 404/空响应），计入已完成但不写入存储
+    pub fn mark_tile_no_data(&self, task_id: &str, tile: &TileCoord, url: Option<&str>) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.progress(task_id)?.lock().execute(
+            "UPDATE tile_progress SET status = 'no_data', url = ?1, downloaded_at = ?2 WHERE z = ?3 AND x = ?4 AND y = ?5",
+            params![url, now, tile.z, tile.x, tile.y],
+        )?;
+        Ok(())
+    }
+
+    /// 统计无数据的瓦片数
+    pub fn count_no_data_tiles(&self, task_id: &str) -> Result<u64> {
+        let Some(progress) = self.read_progress(task_id)? else {
+            return Ok(0);
+        };
+        let count: i64 = progress.lock().query_row(
+            "SELECT COUNT(*) FROM tile_progress WHERE status = 'no_data'",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as u64)
+    }
+
+    /// 获取最近失败的瓦片记录，按失败时间倒序
+    pub fn get_recent_tile_errors(&self, task_id: &str, limit: u32) -> Result<Vec<TileError>> {
+        let Some(progress) = self.read_progress(task_id)? else {
+            return Ok(Vec::new());
+        };
+        let conn = progress.lock();
+        let mut stmt = conn.prepare(
+            r#"SELECT z, x, y, url, error_message, failed_at FROM tile_progress
+               WHERE status = 'failed'
+               ORDER BY failed_at DESC LIMIT ?1"#,
+        )?;
+
+        let rows = stmt.query_map(params![limit], |row| {
+            Ok(TileError {
+                z: row.get(0)?,
+                x: row.get(1)?,
+                y: row.get(2)?,
+                url: row.get(3)?,
+                error: row.get::<_, Option<String>>(4)?.unwrap_or_default(),
+                failed_at: row.get(5)?,
+            })
+        })?;
+
+        let mut errors = Vec::new();
+        for row in rows {
+            errors.push(row?);
+        }
+        Ok(errors)
+    }
+
     /// 重置失败瓦片为待下载
     pub fn reset_failed_tiles(&self, task_id: &str) -> Result<u64> {
-        let count = self.conn.lock().execute(
-            "UPDATE tile_progress SET status = 'pending', error_message = NULL WHERE task_id = ?1 AND status = 'failed'",
-            params![task_id],
+        let count = self.progress(task_id)?.lock().execute(
+            "UPDATE tile_progress SET status = 'pending', error_message = NULL WHERE status = 'failed'",
+            [],
         )?;
         Ok(count as u64)
     }
 
-    /// 获取任务统计
+    /// 获取任务统计 (待下载, 已完成, 失败)，无数据的瓦片计入已完成
     pub fn get_tile_stats(&self, task_id: &str) -> Result<(u64, u64, u64)> {
-        let conn = self.conn.lock();
+        let Some(progress) = self.read_progress(task_id)? else {
+            return Ok((0, 0, 0));
+        };
+        let conn = progress.lock();
         let pending: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM tile_progress WHERE task_id = ?1 AND status = 'pending'",
-            params![task_id],
+            "SELECT COUNT(*) FROM tile_progress WHERE status = 'pending'",
+            [],
             |row| row.get(0),
         )?;
 
         let completed: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM tile_progress WHERE task_id = ?1 AND status = 'completed'",
-            params![task_id],
+            "SELECT COUNT(*) FROM tile_progress WHERE status IN ('completed', 'no_data')",
+            [],
             |row| row.get(0),
         )?;
 
         let failed: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM tile_progress WHERE task_id = ?1 AND status = 'failed'",
-            params![task_id],
+            "SELECT COUNT(*) FROM tile_progress WHERE status = 'failed'",
+            [],
             |row| row.get(0),
         )?;
 
         Ok((pending as u64, completed as u64, failed as u64))
     }
+
+    /// 按层级分组统计任务进度
+    pub fn get_zoom_stats(&self, task_id: &str) -> Result<Vec<ZoomProgress>> {
+        let Some(progress) = self.read_progress(task_id)? else {
+            return Ok(Vec::new());
+        };
+        let conn = progress.lock();
+        let mut stmt = conn.prepare(
+            r#"SELECT z,
+                      COUNT(*),
+                      SUM(CASE WHEN status = 'pending' THEN 1 ELSE 0 END),
+                      SUM(CASE WHEN status IN ('completed', 'no_data') THEN 1 ELSE 0 END),
+                      SUM(CASE WHEN status = 'failed' THEN 1 ELSE 0 END),
+                      SUM(CASE WHEN status = 'no_data' THEN 1 ELSE 0 END)
+               FROM tile_progress GROUP BY z ORDER BY z"#,
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(ZoomProgress {
+                zoom: row.get(0)?,
+                total: row.get::<_, i64>(1)? as u64,
+                pending: row.get::<_, i64>(2)? as u64,
+                completed: row.get::<_, i64>(3)? as u64,
+                failed: row.get::<_, i64>(4)? as u64,
+                no_data: row.get::<_, i64>(5)? as u64,
+            })
+        })?;
+
+        let mut stats = Vec::new();
+        for row in rows {
+            stats.push(row?);
+        }
+        Ok(stats)
+    }
+
+    /// 按层级统计已完成瓦片的行列范围
+    pub fn get_completed_extents(&self, task_id: &str) -> Result<Vec<TileExtent>> {
+        let Some(progress) = self.read_progress(task_id)? else {
+            return Ok(Vec::new());
+        };
+        let conn = progress.lock();
+        let mut stmt = conn.prepare(
+            r#"SELECT z, MIN(x), MAX(x), MIN(y), MAX(y), COUNT(*)
+               FROM tile_progress WHERE status = 'completed'
+               GROUP BY z ORDER BY z"#,
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(TileExtent {
+                zoom: row.get(0)?,
+                min_x: row.get(1)?,
+                max_x: row.get(2)?,
+                min_y: row.get(3)?,
+                max_y: row.get(4)?,
+                count: row.get::<_, i64>(5)? as u64,
+            })
+        })?;
+
+        let mut extents = Vec::new();
+        for row in rows {
+            extents.push(row?);
+        }
+        Ok(extents)
+    }
+
+    /// 按来源平台统计已完成瓦片
+    pub fn get_source_stats(&self, task_id: &str) -> Result<Vec<SourceStat>> {
+        let Some(progress) = self.read_progress(task_id)? else {
+            return Ok(Vec::new());
+        };
+        let conn = progress.lock();
+        let mut stmt = conn.prepare(
+            r#"SELECT source, COUNT(*) FROM tile_progress
+               WHERE status = 'completed' AND source IS NOT NULL
+               GROUP BY source ORDER BY COUNT(*) DESC"#,
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            Ok(SourceStat {
+                platform: row.get(0)?,
+                count: row.get::<_, i64>(1)? as u64,
+            })
+        })?;
+
+        let mut stats = Vec::new();
+        for row in rows {
+            stats.push(row?);
+        }
+        Ok(stats)
+    }
 }
@@ -1,14 +1,41 @@
+use super::clip::tiles_in_polygon;
+use super::correction;
+use super::cache::TileCache;
 use super::database::TileDatabase;
+use super::imaging::{decode_image, encode_jpeg, encode_png, is_jpeg, RgbaImage, TILE_SIZE};
+use super::manifest::write_manifest;
 use super::platforms::TilePlatform;
 use super::storage::{create_storage, TileStorage};
 use super::types::*;
-use parking_lot::RwLock;
-use std::collections::HashMap;
+use super::verify::{self, verify_output};
+use crate::budget::{self, BudgetKind, Consumption};
+use crate::config::TileNetworkSettings;
+use crate::power::CompletionAction;
+use parking_lot::{Mutex, RwLock};
+use std::collections::{HashMap, VecDeque};
 use std::path::Path;
 use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot, OwnedSemaphorePermit, Semaphore};
+
+/// 每个主机默认的最大并发连接数
+pub const DEFAULT_MAX_CONNECTIONS_PER_HOST: u32 = 6;
+
+/// 连续网络错误达到该次数时判定为断网，自动暂停任务
+const NETWORK_ERROR_THRESHOLD: u32 = 20;
+
+/// 断网暂停期间探测网络的间隔
+const NETWORK_PROBE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 预算暂停期间检查是否跨天的间隔
+const BUDGET_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 收到 429 且无 Retry-After 时的全局冷却时间
+const RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(5);
+
+/// Retry-After 冷却时间上限
+const MAX_RATE_LIMIT_COOLDOWN: Duration = Duration::from_secs(60);
 
 /// 计算经纬度边界内指定层级的所有瓦片坐标
 pub fn calculate_tiles(bounds: &Bounds, zoom_levels: &[u32]) -> Vec<TileCoord> {
@@ -42,7 +69,6 @@ pub fn calculate_tiles(bounds: &Bounds, zoom_levels: &[u32]) -> Vec<TileCoord> {
 
 /// 计算瓦片数量估算
 pub fn estimate_tiles(bounds: &Bounds, zoom_levels: &[u32]) -> TileEstimate {
-    let mut total_tiles = 0u64;
     let mut tiles_per_level = Vec::new();
 
     for &z in zoom_levels {
@@ -64,9 +90,15 @@ pub fn estimate_tiles(bounds: &Bounds, zoom_levels: &[u32]) -> TileEstimate {
         let count = x_count * y_count;
 
         tiles_per_level.push((z, count));
-        total_tiles += count;
     }
 
+    build_estimate(tiles_per_level)
+}
+
+/// 由各层级瓦片数汇总估算结果
+pub fn build_estimate(tiles_per_level: Vec<(u32, u64)>) -> TileEstimate {
+    let total_tiles: u64 = tiles_per_level.iter().map(|(_, count)| count).sum();
+
     // 估算大小：假设每个瓦片平均 20KB
     let estimated_size_mb = (total_tiles as f64 * 20.0) / 1024.0;
 
@@ -86,6 +118,23 @@ pub struct DownloaderState {
     pub thread_count: AtomicU32,
     pub current_zoom: AtomicU32,
     pub start_time: RwLock<Option<Instant>>,
+    pub started_at: RwLock<Option<String>>,
+    /// 连续网络错误次数，任意一次请求得到响应即清零
+    pub network_errors: AtomicU32,
+    /// 是否因断网自动暂停，网络恢复后自动继续
+    pub network_paused: AtomicBool,
+    /// 被限流（429）后的全局冷却截止时间，期间所有请求等待
+    pub throttled_until: RwLock<Option<Instant>>,
+    /// 本批有瓦片因今日预算用尽而未下载
+    pub budget_exhausted: AtomicBool,
+    /// 是否因预算用尽自动暂停，跨天后自动继续
+    pub budget_paused: AtomicBool,
+    /// 预算接近上限的提示，随下一次进度事件发出
+    pub budget_notice: RwLock<Option<String>>,
+    /// 限速时相邻请求的最小间隔（微秒），0 表示不限速
+    request_interval_us: AtomicU64,
+    /// 限速时下一个请求可发出的时间
+    next_request_at: parking_lot::Mutex<Option<Instant>>,
 }
 
 impl DownloaderState {
@@ -98,9 +147,60 @@ impl DownloaderState {
             thread_count: AtomicU32::new(thread_count),
             current_zoom: AtomicU32::new(0),
             start_time: RwLock::new(None),
+            started_at: RwLock::new(None),
+            network_errors: AtomicU32::new(0),
+            network_paused: AtomicBool::new(false),
+            throttled_until: RwLock::new(None),
+            budget_exhausted: AtomicBool::new(false),
+            budget_paused: AtomicBool::new(false),
+            budget_notice: RwLock::new(None),
+            request_interval_us: AtomicU64::new(0),
+            next_request_at: parking_lot::Mutex::new(None),
         }
     }
 
+    /// 设置每秒请求数上限，None 表示不限速
+    pub fn set_rate_limit(&self, rate_limit: Option<f64>) {
+        let interval = rate_limit
+            .filter(|r| *r > 0.0)
+            .map(|r| (1_000_000.0 / r) as u64)
+            .unwrap_or(0);
+        self.request_interval_us.store(interval, Ordering::SeqCst);
+    }
+
+    /// 按限速预约下一个请求时间，返回需要等待的时长
+    fn reserve_request_slot(&self) -> Option<Duration> {
+        let interval = self.request_interval_us.load(Ordering::Relaxed);
+        if interval == 0 {
+            return None;
+        }
+        let now = Instant::now();
+        let mut next = self.next_request_at.lock();
+        let slot = next.map_or(now, |t| t.max(now));
+        *next = Some(slot + Duration::from_micros(interval));
+        slot.checked_duration_since(now).filter(|d| !d.is_zero())
+    }
+
+    /// 被限流时全局降速：设置冷却时间，并在未处于冷却期时将线程数减半
+    fn throttle(&self, cooldown: Duration) {
+        let now = Instant::now();
+        let mut until = self.throttled_until.write();
+        let cooling = until.is_some_and(|t| t > now);
+        *until = Some(until.map_or(now + cooldown, |t| t.max(now + cooldown)));
+        if !cooling {
+            let threads = self.thread_count.load(Ordering::Relaxed);
+            let reduced = (threads / 2).max(1);
+            self.thread_count.store(reduced, Ordering::Relaxed);
+            log::warn!("请求被限流，冷却 {:?}，线程数 {} -> {}", cooldown, threads, reduced);
+        }
+    }
+
+    /// 限流冷却期内的剩余等待时间
+    fn throttle_remaining(&self) -> Option<Duration> {
+        let until = (*self.throttled_until.read())?;
+        until.checked_duration_since(Instant::now())
+    }
+
     pub fn calculate_speed(&self) -> f64 {
         if let Some(start) = *self.start_time.read() {
             let elapsed = start.elapsed().as_secs_f64();
@@ -112,6 +212,85 @@ impl DownloaderState {
     }
 }
 
+/// 按连接设置构建下载用 HTTP 客户端
+///
+/// 同一平台的海量小请求依赖连接复用：保留足够的空闲连接避免反复握手，
+/// 服务端支持 HTTP/2 时多路复用到少量连接上。
+pub fn build_http_client(
+    settings: &TileNetworkSettings,
+    max_connections_per_host: u32,
+) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .pool_max_idle_per_host(
+            settings
+                .pool_max_idle_per_host
+                .unwrap_or(max_connections_per_host) as usize,
+        )
+        .pool_idle_timeout(Duration::from_secs(settings.pool_idle_timeout_secs))
+        .tcp_keepalive(settings.tcp_keepalive_secs.map(Duration::from_secs))
+        .tcp_nodelay(true);
+
+    builder = if settings.http2 {
+        builder.http2_adaptive_window(true)
+    } else {
+        builder.http1_only()
+    };
+
+    builder
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))
+}
+
+/// 按主机名限制并发连接数
+///
+/// 同一平台的多个子域往往指向同一个后端，全部线程同时请求容易触发 403，
+/// 因此每个主机名共享一个信号量。
+pub struct HostLimiter {
+    limit: usize,
+    semaphores: parking_lot::Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+impl HostLimiter {
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit: limit.max(1) as usize,
+            semaphores: parking_lot::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 获取目标 URL 所在主机的连接许可，URL 无法解析时不做限制
+    pub async fn acquire(&self, url: &str) -> Option<OwnedSemaphorePermit> {
+        let host = reqwest::Url::parse(url).ok()?.host_str()?.to_string();
+        let semaphore = self
+            .semaphores
+            .lock()
+            .entry(host)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.limit)))
+            .clone();
+        semaphore.acquire_owned().await.ok()
+    }
+}
+
+/// 下载循环的结束原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadOutcome {
+    /// 所有瓦片处理完毕
+    Finished,
+    /// 被停止或取消
+    Stopped,
+}
+
+impl DownloadOutcome {
+    /// 任务结束后要执行的完成后动作，停止或取消的任务不执行
+    pub fn completion_action(self, task: &TaskInfo) -> CompletionAction {
+        match self {
+            DownloadOutcome::Finished => task.on_complete,
+            DownloadOutcome::Stopped => CompletionAction::None,
+        }
+    }
+}
+
 /// 瓦片下载器
 pub struct TileDownloader {
     states: RwLock<HashMap<String, Arc<DownloaderState>>>,
@@ -136,37 +315,51 @@ impl TileDownloader {
         state
     }
 
+    /// 获取所有运行中的任务 ID
+    pub fn running_task_ids(&self) -> Vec<String> {
+        self.states
+            .read()
+            .iter()
+            .filter(|(_, state)| state.is_running.load(Ordering::Relaxed))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
     /// 移除任务状态
     pub fn remove_state(&self, task_id: &str) {
         self.states.write().remove(task_id);
     }
 
-    /// 开始下载任务
+    /// 开始下载任务，返回下载循环的结束原因
     pub async fn start_download(
         &self,
         db: Arc<TileDatabase>,
-        task_id: String,
+        cache: Option<Arc<TileCache>>,
+        task: TaskInfo,
         platform: Box<dyn TilePlatform>,
-        map_type: MapType,
-        bounds: Bounds,
-        zoom_levels: Vec<u32>,
-        output_path: String,
-        output_format: String,
-        thread_count: u32,
-        retry_count: u32,
+        fallbacks: Vec<Box<dyn TilePlatform>>,
         progress_tx: mpsc::Sender<ProgressEvent>,
-    ) -> Result<(), String> {
-        let state = self.create_state(&task_id, thread_count);
+    ) -> Result<DownloadOutcome, String> {
+        let task_id = task.id.clone();
+        let state = self.create_state(&task_id, task.thread_count);
+        state.set_rate_limit(task.rate_limit);
 
-        // 计算所有瓦片
-        let tiles = calculate_tiles(&bounds, &zoom_levels);
+        // 计算所有瓦片，指定了坐标列表时只下载列表中的瓦片
+        let tile_list = db
+            .get_tile_list(&task_id)
+            .map_err(|e| format!("读取瓦片列表失败: {}", e))?;
+        let tiles = match (tile_list, &task.clip_polygon) {
+            (Some(list), _) => list,
+            (None, Some(polygon)) => tiles_in_polygon(polygon, &task.zoom_levels),
+            (None, None) => calculate_tiles(&task.bounds, &task.zoom_levels),
+        };
         let total_tiles = tiles.len() as u64;
 
         log::info!(
             "任务 {} 开始下载，共 {} 个瓦片，线程数 {}",
             task_id,
             total_tiles,
-            thread_count
+            task.thread_count
         );
 
         // 初始化进度到数据库
@@ -176,29 +369,147 @@ impl TileDownloader {
         // 更新任务状态
         db.update_task_status(&task_id, "downloading").ok();
 
-        // 创建存储
-        let storage = Arc::new(parking_lot::Mutex::new(create_storage(&output_format)));
-        {
-            let mut s = storage.lock();
-            s.init(Path::new(&output_path), &bounds, &zoom_levels)?;
+        // 纠偏仅对 GCJ02 平台生效
+        let coord_correction = task.coord_correction && platform.coord_system() == "GCJ02";
+        if task.coord_correction && !coord_correction {
+            log::warn!(
+                "平台 {} 坐标系为 {}，不支持纠偏输出",
+                platform.id(),
+                platform.coord_system()
+            );
         }
+        // 坐标系不一致的备选源会导致拼接错位，直接跳过
+        let fallbacks: Vec<_> = fallbacks
+            .into_iter()
+            .filter(|f| {
+                let same_crs = f.coord_system() == platform.coord_system();
+                if !same_crs {
+                    log::warn!(
+                        "备选平台 {} 坐标系为 {}，与主源 {} 不一致，已忽略",
+                        f.id(),
+                        f.coord_system(),
+                        platform.coord_system()
+                    );
+                }
+                same_crs
+            })
+            .collect();
+
+        let output_crs = if coord_correction {
+            "WGS84"
+        } else {
+            platform.coord_system()
+        };
+
+        // 创建 HTTP 客户端
+        let network = crate::config::get_tile_network_settings().unwrap_or_else(|e| {
+            log::warn!("读取连接设置失败，使用默认设置: {}", e);
+            Default::default()
+        });
+        let client = build_http_client(&network, task.max_connections_per_host)?;
+
+        // 创建存储，交由专用写线程串行写入
+        let mut storage = create_storage(&task.output_format);
+        storage.init(Path::new(&task.output_path), &task.bounds, &task.zoom_levels)?;
+        storage.set_metadata("crs", output_crs)?;
+
+        let (writer, write_rx) = mpsc::channel::<WriteJob>(WRITE_QUEUE_SIZE);
+        let writer_handle = {
+            let db = db.clone();
+            let task_id = task_id.clone();
+            let state = state.clone();
+            tokio::task::spawn_blocking(move || {
+                run_storage_writer(storage, write_rx, &db, &task_id, &state)
+            })
+        };
 
         // 设置运行状态
         state.is_running.store(true, Ordering::SeqCst);
         *state.start_time.write() = Some(Instant::now());
+        *state.started_at.write() = Some(chrono::Local::now().to_rfc3339());
 
-        // 创建 HTTP 客户端
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
-            .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
-
-        let platform = Arc::new(platform);
-        let db = db.clone();
+        let ctx = Arc::new(DownloadContext {
+            client,
+            platform,
+            fallbacks,
+            map_type: MapType::from(task.map_type.as_str()),
+            map_type_key: task.map_type.to_lowercase(),
+            header_options: task.headers.clone(),
+            db: db.clone(),
+            cache,
+            writer,
+            task_id: task_id.clone(),
+            state: state.clone(),
+            host_limiter: HostLimiter::new(task.max_connections_per_host),
+            max_retries: task.retry_count,
+            coord_correction,
+            fill_no_data: task.fill_no_data,
+            source_tiles: Mutex::new(SourceTileCache::default()),
+        });
         let task_id_clone = task_id.clone();
 
-        // 下载循环
+        // 下载循环，出错时跳出循环走统一的收尾流程，避免写线程和运行状态残留
+        let mut loop_error: Option<String> = None;
+        let mut outcome = DownloadOutcome::Stopped;
+        let mut last_probe: Option<Instant> = None;
+        let mut last_budget_check: Option<Instant> = None;
         loop {
+            // 断网暂停期间定期探测，网络恢复后自动继续
+            if state.network_paused.load(Ordering::Relaxed)
+                && last_probe.is_none_or(|t| t.elapsed() >= NETWORK_PROBE_INTERVAL)
+            {
+                last_probe = Some(Instant::now());
+                if probe_network(&ctx).await {
+                    state.network_errors.store(0, Ordering::SeqCst);
+                    state.network_paused.store(false, Ordering::SeqCst);
+                    state.is_paused.store(false, Ordering::SeqCst);
+                    db.update_task_status(&task_id_clone, "downloading").ok();
+                    log::info!("任务 {} 网络已恢复，自动继续下载", task_id_clone);
+
+                    let _ = progress_tx
+                        .send(ProgressEvent {
+                            task_id: task_id_clone.clone(),
+                            completed: state.completed.load(Ordering::Relaxed),
+                            failed: state.failed.load(Ordering::Relaxed),
+                            total: total_tiles,
+                            speed: state.calculate_speed(),
+                            current_zoom: state.current_zoom.load(Ordering::Relaxed),
+                            status: "downloading".to_string(),
+                            message: Some("网络已恢复，自动继续下载".to_string()),
+                        })
+                        .await;
+                }
+            }
+
+            // 预算暂停期间定期检查，跨天后自动继续
+            if state.budget_paused.load(Ordering::Relaxed)
+                && last_budget_check.is_none_or(|t| t.elapsed() >= BUDGET_CHECK_INTERVAL)
+            {
+                last_budget_check = Some(Instant::now());
+                let available = std::iter::once(&ctx.platform)
+                    .chain(ctx.fallbacks.iter())
+                    .all(|p| !budget::is_exhausted(p.id(), BudgetKind::Tiles));
+                if available {
+                    state.budget_paused.store(false, Ordering::SeqCst);
+                    state.is_paused.store(false, Ordering::SeqCst);
+                    db.update_task_status(&task_id_clone, "downloading").ok();
+                    log::info!("任务 {} 预算已恢复，自动继续下载", task_id_clone);
+
+                    let _ = progress_tx
+                        .send(ProgressEvent {
+                            task_id: task_id_clone.clone(),
+                            completed: state.completed.load(Ordering::Relaxed),
+                            failed: state.failed.load(Ordering::Relaxed),
+                            total: total_tiles,
+                            speed: state.calculate_speed(),
+                            current_zoom: state.current_zoom.load(Ordering::Relaxed),
+                            status: "downloading".to_string(),
+                            message: Some("预算已恢复，自动继续下载".to_string()),
+                        })
+                        .await;
+                }
+            }
+
             // 检查是否暂停
             if state.is_paused.load(Ordering::Relaxed) {
                 tokio::time::sleep(Duration::from_millis(100)).await;
@@ -212,18 +523,27 @@ impl TileDownloader {
 
             // 获取待下载瓦片
             let current_thread_count = state.thread_count.load(Ordering::Relaxed) as usize;
-            let pending = db
-                .get_pending_tiles(&task_id_clone, current_thread_count * 2)
-                .map_err(|e| format!("获取待下载瓦片失败: {}", e))?;
+            let pending = match db.get_pending_tiles(&task_id_clone, current_thread_count * 2) {
+                Ok(pending) => pending,
+                Err(e) => {
+                    loop_error = Some(format!("获取待下载瓦片失败: {}", e));
+                    break;
+                }
+            };
 
             if pending.is_empty() {
                 // 没有待下载的瓦片，检查是否有失败的需要重试
-                let (_, completed, failed) = db
-                    .get_tile_stats(&task_id_clone)
-                    .map_err(|e| format!("获取统计失败: {}", e))?;
+                let (_, completed, failed) = match db.get_tile_stats(&task_id_clone) {
+                    Ok(stats) => stats,
+                    Err(e) => {
+                        loop_error = Some(format!("获取统计失败: {}", e));
+                        break;
+                    }
+                };
 
                 if completed + failed >= total_tiles {
                     // 所有瓦片都已处理完成
+                    outcome = DownloadOutcome::Finished;
                     break;
                 }
             }
@@ -236,29 +556,8 @@ impl TileDownloader {
             // 并发下载
             let mut handles = Vec::new();
             for tile in pending.into_iter().take(current_thread_count) {
-                let client = client.clone();
-                let db = db.clone();
-                let storage = storage.clone();
-                let task_id = task_id_clone.clone();
-                let state = state.clone();
-                let retry_count = retry_count;
-                let url = platform.get_tile_url(tile.z, tile.x, tile.y, &map_type);
-                let headers = platform.get_headers();
-
-                let handle = tokio::spawn(async move {
-                    download_tile_with_url(
-                        &client,
-                        url,
-                        headers,
-                        &tile,
-                        &db,
-                        &storage,
-                        &task_id,
-                        &state,
-                        retry_count,
-                    )
-                    .await
-                });
+                let ctx = ctx.clone();
+                let handle = tokio::spawn(async move { download_tile(&ctx, &tile).await });
                 handles.push(handle);
             }
 
@@ -267,11 +566,54 @@ impl TileDownloader {
                 let _ = handle.await;
             }
 
+            // 等待写线程落盘本批瓦片，确保下一批不会重复取到仍在队列中的瓦片
+            let (flushed_tx, flushed_rx) = oneshot::channel();
+            if ctx.writer.send(WriteJob::Flush(flushed_tx)).await.is_ok() {
+                let _ = flushed_rx.await;
+            }
+
+            // 连续网络错误过多，判定为断网并自动暂停，避免剩余瓦片快速失败
+            let network_lost = state.network_errors.load(Ordering::Relaxed)
+                >= NETWORK_ERROR_THRESHOLD
+                && state.is_running.load(Ordering::Relaxed)
+                && !state.is_paused.swap(true, Ordering::SeqCst);
+            if network_lost {
+                state.network_paused.store(true, Ordering::SeqCst);
+                last_probe = Some(Instant::now());
+                db.update_task_status(&task_id_clone, "paused").ok();
+                log::warn!("任务 {} 连续网络错误，已自动暂停", task_id_clone);
+            }
+
+            // 今日瓦片预算用尽，未下载的瓦片保留为待下载并自动暂停
+            let budget_exhausted = state.budget_exhausted.swap(false, Ordering::SeqCst)
+                && state.is_running.load(Ordering::Relaxed)
+                && !state.is_paused.swap(true, Ordering::SeqCst);
+            if budget_exhausted {
+                state.budget_paused.store(true, Ordering::SeqCst);
+                last_budget_check = Some(Instant::now());
+                db.update_task_status(&task_id_clone, "paused").ok();
+                log::warn!("任务 {} 今日瓦片预算已用尽，已自动暂停", task_id_clone);
+            }
+
             // 发送进度事件
             let completed = state.completed.load(Ordering::Relaxed);
             let failed = state.failed.load(Ordering::Relaxed);
             let speed = state.calculate_speed();
 
+            let (status, message) = if network_lost {
+                (
+                    "network_paused",
+                    Some("检测到网络中断，已自动暂停，网络恢复后将自动继续".to_string()),
+                )
+            } else if budget_exhausted {
+                (
+                    "budget_paused",
+                    Some("今日瓦片预算已用尽，已自动暂停，次日零点后将自动继续".to_string()),
+                )
+            } else {
+                ("downloading", state.budget_notice.write().take())
+            };
+
             let _ = progress_tx
                 .send(ProgressEvent {
                     task_id: task_id_clone.clone(),
@@ -280,8 +622,8 @@ impl TileDownloader {
                     total: total_tiles,
                     speed,
                     current_zoom: state.current_zoom.load(Ordering::Relaxed),
-                    status: "downloading".to_string(),
-                    message: None,
+                    status: status.to_string(),
+                    message,
                 })
                 .await;
 
@@ -292,24 +634,96 @@ impl TileDownloader {
             tokio::time::sleep(Duration::from_millis(10)).await;
         }
 
-        // 完成存储
-        {
-            let mut s = storage.lock();
-            s.finalize()?;
+        // 关闭写入通道，等待写线程写完剩余瓦片并完成存储
+        drop(ctx);
+        let written = writer_handle
+            .await
+            .map_err(|e| format!("存储写线程异常退出: {}", e))
+            .and_then(|result| result);
+        if let Some(e) = loop_error.or(written.err()) {
+            state.is_running.store(false, Ordering::SeqCst);
+            db.update_task_status(&task_id_clone, "failed").ok();
+            db.set_task_error_message(&task_id_clone, Some(&e)).ok();
+            self.remove_state(&task_id);
+            return Err(e);
         }
 
-        // 更新最终状态
         let completed = state.completed.load(Ordering::Relaxed);
         let failed = state.failed.load(Ordering::Relaxed);
 
+        // 停止或取消时保留调用方写入的状态，不做完成校验和清单
+        if outcome == DownloadOutcome::Stopped {
+            db.update_task_progress(&task_id_clone, completed, failed).ok();
+            let _ = progress_tx
+                .send(ProgressEvent {
+                    task_id: task_id_clone.clone(),
+                    completed,
+                    failed,
+                    total: total_tiles,
+                    speed: 0.0,
+                    current_zoom: 0,
+                    status: "cancelled".to_string(),
+                    message: Some(format!(
+                        "下载已停止，成功 {} 个，失败 {} 个",
+                        completed, failed
+                    )),
+                })
+                .await;
+            self.remove_state(&task_id);
+            log::info!(
+                "任务 {} 已停止，成功 {}，失败 {}",
+                task_id,
+                completed,
+                failed
+            );
+            return Ok(outcome);
+        }
+
+        // 更新最终状态
+
         if failed == 0 {
             db.set_task_completed(&task_id_clone).ok();
         } else {
             db.update_task_status(&task_id_clone, "completed").ok();
         }
 
+        // 抽样校验瓦片能否解码，识别整批下载到劫持页面的情况
+        let suspect = match verify_output(
+            &db,
+            &task_id_clone,
+            &task.output_format,
+            &task.output_path,
+            verify::SAMPLE_SIZE,
+        ) {
+            Ok(check) if check.is_suspect() => {
+                log::warn!("任务 {} {}", task_id_clone, check.message());
+                db.update_task_status(&task_id_clone, "suspect").ok();
+                Some(check.message())
+            }
+            Ok(_) => None,
+            Err(e) => {
+                log::warn!("任务 {} 抽样校验失败: {}", task_id_clone, e);
+                None
+            }
+        };
+
+        // 将失败原因汇总到任务级错误信息
+        match db.summarize_tile_errors(&task_id_clone, 3) {
+            Ok(summary) => {
+                let error: Vec<String> = suspect.iter().cloned().chain(summary).collect();
+                let error = (!error.is_empty()).then(|| error.join("；"));
+                db.set_task_error_message(&task_id_clone, error.as_deref()).ok();
+            }
+            Err(e) => log::warn!("任务 {} 汇总失败原因失败: {}", task_id_clone, e),
+        }
+
         db.update_task_progress(&task_id_clone, completed, failed).ok();
 
+        // 生成离线包清单
+        if let Err(e) = write_manifest(&db, &task_id_clone) {
+            log::warn!("任务 {} 生成清单失败: {}", task_id_clone, e);
+        }
+
         // 发送完成事件
         let _ = progress_tx
             .send(ProgressEvent {
@@ -319,11 +733,14 @@ impl TileDownloader {
                 total: total_tiles,
                 speed: 0.0,
                 current_zoom: 0,
-                status: "completed".to_string(),
-                message: Some(format!(
-                    "下载完成，成功 {} 个，失败 {} 个",
-                    completed, failed
-                )),
+                status: if suspect.is_some() { "suspect" } else { "completed" }.to_string(),
+                message: Some(match &suspect {
+                    Some(warning) => format!(
+                        "下载完成，成功 {} 个，失败 {} 个；{}",
+                        completed, failed, warning
+                    ),
+                    None => format!("下载完成，成功 {} 个，失败 {} 个", completed, failed),
+                }),
             })
             .await;
 
@@ -337,12 +754,14 @@ impl TileDownloader {
             failed
         );
 
-        Ok(())
+        Ok(outcome)
     }
 
-    /// 暂停任务
+    /// 暂停任务，手动暂停后不再因网络恢复自动继续
     pub fn pause(&self, task_id: &str) -> bool {
         if let Some(state) = self.get_state(task_id) {
+            state.network_paused.store(false, Ordering::SeqCst);
+            state.budget_paused.store(false, Ordering::SeqCst);
             state.is_paused.store(true, Ordering::SeqCst);
             true
         } else {
@@ -353,6 +772,9 @@ impl TileDownloader {
     /// 恢复任务
     pub fn resume(&self, task_id: &str) -> bool {
         if let Some(state) = self.get_state(task_id) {
+            state.network_paused.store(false, Ordering::SeqCst);
+            state.budget_paused.store(false, Ordering::SeqCst);
+            state.network_errors.store(0, Ordering::SeqCst);
             state.is_paused.store(false, Ordering::SeqCst);
             true
         } else {
@@ -365,6 +787,7 @@ impl TileDownloader {
         if let Some(state) = self.get_state(task_id) {
             state.is_running.store(false, Ordering::SeqCst);
             state.is_paused.store(false, Ordering::SeqCst);
+            state.network_paused.store(false, Ordering::SeqCst);
             true
         } else {
             false
@@ -382,88 +805,797 @@ impl TileDownloader {
     }
 }
 
-/// 下载单个瓦片（使用预先生成的URL）
-async fn download_tile_with_url(
-    client: &reqwest::Client,
+/// 单个任务内各下载协程共享的上下文
+struct DownloadContext {
+    client: reqwest::Client,
+    platform: Box<dyn TilePlatform>,
+    fallbacks: Vec<Box<dyn TilePlatform>>,
+    map_type: MapType,
+    /// 共享缓存中的地图类型键
+    map_type_key: String,
+    header_options: HeaderOptions,
+    db: Arc<TileDatabase>,
+    cache: Option<Arc<TileCache>>,
+    writer: mpsc::Sender<WriteJob>,
+    task_id: String,
+    state: Arc<DownloaderState>,
+    host_limiter: HostLimiter,
+    max_retries: u32,
+    coord_correction: bool,
+    /// 无数据瓦片写入透明占位瓦片
+    fill_no_data: bool,
+    /// 纠偏时已拉取的源瓦片
+    source_tiles: Mutex<SourceTileCache>,
+}
+
+impl DownloadContext {
+    /// 按平台 ID 取回主源或备选源的 ID
+    fn source_platform(&self, id: &str) -> &str {
+        std::iter::once(&self.platform)
+            .chain(self.fallbacks.iter())
+            .map(|platform| platform.id())
+            .find(|platform| *platform == id)
+            .unwrap_or(self.platform.id())
+    }
+
+    /// 获取瓦片 URL 与请求头
+    fn tile_request(
+        &self,
+        platform: &dyn TilePlatform,
+        tile: &TileCoord,
+    ) -> Option<(String, HashMap<String, String>)> {
+        let url = platform.get_tile_url(tile.z, tile.x, tile.y, &self.map_type)?;
+        let mut headers = platform.get_headers();
+        self.header_options.apply(&mut headers);
+        Some((url, headers))
+    }
+}
+
+/// 下载单个瓦片并投递给存储写线程
+async fn download_tile(ctx: &DownloadContext, tile: &TileCoord) {
+    let result = if ctx.coord_correction {
+        download_corrected_tile(ctx, tile).await
+    } else {
+        fetch_tile_with_fallback(ctx, tile).await
+    };
+
+    let error = match result {
+        Ok((data, source)) => {
+            let job = WriteJob::Tile {
+                tile: *tile,
+                data,
+                source: source.to_string(),
+            };
+            ctx.state.network_errors.store(0, Ordering::Relaxed);
+            match ctx.writer.send(job).await {
+                Ok(()) => return,
+                Err(_) => FetchError::from("存储写线程已退出".to_string()),
+            }
+        }
+        Err(e) if e.kind == FetchErrorKind::NoData => {
+            // 所有来源均无数据，单独标记为无数据，不计入失败
+            ctx.state.network_errors.store(0, Ordering::Relaxed);
+            if ctx.fill_no_data {
+                match placeholder_tile() {
+                    Ok(data) => {
+                        let job = WriteJob::Placeholder {
+                            tile: *tile,
+                            data,
+                            url: e.url,
+                        };
+                        match ctx.writer.send(job).await {
+                            Ok(()) => return,
+                            Err(_) => FetchError::from("存储写线程已退出".to_string()),
+                        }
+                    }
+                    Err(err) => FetchError::from(err),
+                }
+            } else {
+                ctx.db.mark_tile_no_data(&ctx.task_id, tile, e.url.as_deref()).ok();
+                ctx.state.completed.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        }
+        Err(e) if e.kind == FetchErrorKind::Budget => {
+            // 预算用尽时保留为待下载，由下载循环统一暂停
+            ctx.state.budget_exhausted.store(true, Ordering::SeqCst);
+            return;
+        }
+        Err(e) if e.kind == FetchErrorKind::Network => {
+            // 已判定断网时保留为待下载，网络恢复后重新下载，不消耗失败配额
+            let errors = ctx.state.network_errors.fetch_add(1, Ordering::Relaxed) + 1;
+            if errors >= NETWORK_ERROR_THRESHOLD {
+                return;
+            }
+            e
+        }
+        Err(e) => {
+            ctx.state.network_errors.store(0, Ordering::Relaxed);
+            e
+        }
+    };
+
+    log::debug!("下载瓦片失败 {}/{}/{}: {}", tile.z, tile.x, tile.y, error.message);
+    ctx.db
+        .mark_tile_failed(&ctx.task_id, tile, error.url.as_deref(), &error.message)
+        .ok();
+    ctx.state.failed.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 瓦片获取失败的分类，决定重试策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FetchErrorKind {
+    /// 网络层错误（连接失败、超时等），退避重试并用于断网检测
+    Network,
+    /// 5xx 服务端错误，退避重试
+    Server,
+    /// 429 限流，全局降速后重试
+    RateLimited,
+    /// 401/403 鉴权失败，不重试
+    Auth,
+    /// 所有来源均无该瓦片（404/204/空响应）
+    NoData,
+    /// 今日瓦片预算已用尽，不发起请求
+    Budget,
+    Other,
+}
+
+/// 瓦片获取失败的原因，url 为出错的请求地址
+struct FetchError {
     url: Option<String>,
-    headers: std::collections::HashMap<String, String>,
-    tile: &TileCoord,
+    message: String,
+    kind: FetchErrorKind,
+}
+
+impl From<String> for FetchError {
+    fn from(message: String) -> Self {
+        Self {
+            url: None,
+            message,
+            kind: FetchErrorKind::Other,
+        }
+    }
+}
+
+/// 解析 Retry-After（秒数形式），缺省或无法解析时使用默认冷却时间
+fn retry_after(response: &reqwest::Response) -> Duration {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(RATE_LIMIT_COOLDOWN)
+        .min(MAX_RATE_LIMIT_COOLDOWN)
+}
+
+/// 探测网络是否恢复：请求一个待下载瓦片，收到任意 HTTP 响应即视为恢复
+async fn probe_network(ctx: &DownloadContext) -> bool {
+    let tile = match ctx.db.get_pending_tiles(&ctx.task_id, 1) {
+        Ok(tiles) => tiles.into_iter().next().unwrap_or(TileCoord::new(0, 0, 0)),
+        Err(_) => return false,
+    };
+    let Some((url, headers)) = ctx.tile_request(ctx.platform.as_ref(), &tile) else {
+        return false;
+    };
+
+    let mut request = ctx.client.get(&url).timeout(Duration::from_secs(10));
+    for (key, value) in &headers {
+        request = request.header(key, value);
+    }
+    request.send().await.is_ok()
+}
+
+/// 存储写入队列容量，队列满时下载协程等待，形成背压
+const WRITE_QUEUE_SIZE: usize = 256;
+
+/// 存储写线程消息
+enum WriteJob {
+    Tile {
+        tile: TileCoord,
+        data: Vec<u8>,
+        source: String,
+    },
+    /// 无数据瓦片的透明占位，url 为最后请求的地址
+    Placeholder {
+        tile: TileCoord,
+        data: Vec<u8>,
+        url: Option<String>,
+    },
+    /// 此前投递的瓦片全部写完后回复
+    Flush(oneshot::Sender<()>),
+}
+
+/// 存储写线程：串行写入瓦片并更新进度，通道关闭后完成存储
+fn run_storage_writer(
+    mut storage: Box<dyn TileStorage>,
+    mut jobs: mpsc::Receiver<WriteJob>,
     db: &TileDatabase,
-    storage: &parking_lot::Mutex<Box<dyn TileStorage>>,
     task_id: &str,
     state: &DownloaderState,
-    max_retries: u32,
-) {
-    let url = match url {
-        Some(url) => url,
-        None => {
-            db.mark_tile_failed(task_id, tile, "不支持的地图类型").ok();
-            state.failed.fetch_add(1, Ordering::Relaxed);
-            return;
+) -> Result<(), String> {
+    while let Some(job) = jobs.blocking_recv() {
+        match job {
+            WriteJob::Tile { tile, data, source } => match storage.save_tile(&tile, &data) {
+                Ok(()) => {
+                    db.mark_tile_completed(task_id, &tile, &source).ok();
+                    state.completed.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) => {
+                    log::debug!("保存瓦片失败 {}/{}/{}: {}", tile.z, tile.x, tile.y, e);
+                    db.mark_tile_failed(task_id, &tile, None, &e).ok();
+                    state.failed.fetch_add(1, Ordering::Relaxed);
+                }
+            },
+            WriteJob::Placeholder { tile, data, url } => match storage.save_tile(&tile, &data) {
+                Ok(()) => {
+                    db.mark_tile_no_data(task_id, &tile, url.as_deref()).ok();
+                    state.completed.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) => {
+                    log::debug!("保存占位瓦片失败 {}/{}/{}: {}", tile.z, tile.x, tile.y, e);
+                    db.mark_tile_failed(task_id, &tile, url.as_deref(), &e).ok();
+                    state.failed.fetch_add(1, Ordering::Relaxed);
+                }
+            },
+            WriteJob::Flush(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+
+    storage.finalize()
+}
+
+/// 透明占位瓦片（PNG）
+fn placeholder_tile() -> Result<Vec<u8>, String> {
+    encode_png(&RgbaImage::filled(TILE_SIZE, TILE_SIZE, [0, 0, 0, 0]))
+}
+
+/// 依次从主源与备选源获取瓦片，仅在 404/无数据时切换到下一个来源
+///
+/// 返回瓦片数据与实际来源平台 ID
+async fn fetch_tile_with_fallback<'a>(
+    ctx: &'a DownloadContext,
+    tile: &TileCoord,
+) -> Result<(Vec<u8>, &'a str), FetchError> {
+    let platforms = std::iter::once(&ctx.platform).chain(ctx.fallbacks.iter());
+    let mut supported = false;
+
+    for platform in platforms {
+        let Some((url, headers)) = ctx.tile_request(platform.as_ref(), tile) else {
+            continue;
+        };
+        supported = true;
+
+        if let Some(cache) = &ctx.cache {
+            if let Ok(Some(data)) = cache.get(platform.id(), &ctx.map_type_key, tile) {
+                return Ok((data, platform.id()));
+            }
+        }
+
+        match budget::consume(platform.id(), BudgetKind::Tiles) {
+            Consumption::Exhausted => {
+                return Err(FetchError {
+                    url: None,
+                    message: format!("{} 今日瓦片预算已用尽", platform.id()),
+                    kind: FetchErrorKind::Budget,
+                });
+            }
+            Consumption::Warning { used, limit } => {
+                *ctx.state.budget_notice.write() = Some(format!(
+                    "{} 今日瓦片预算已用 {}/{}，即将达到上限",
+                    platform.id(),
+                    used,
+                    limit
+                ));
+            }
+            Consumption::Allowed => {}
+        }
+
+        if let Some(data) = fetch_tile_bytes(ctx, &url, &headers).await? {
+            if let Some(cache) = &ctx.cache {
+                if let Err(e) = cache.put(platform.id(), &ctx.map_type_key, tile, &data) {
+                    log::debug!("写入瓦片缓存失败: {}", e);
+                }
+            }
+            return Ok((data, platform.id()));
+        }
+    }
+
+    if supported {
+        Err(FetchError {
+            url: None,
+            message: "所有来源均无该瓦片数据".to_string(),
+            kind: FetchErrorKind::NoData,
+        })
+    } else {
+        Err(FetchError::from("不支持的地图类型".to_string()))
+    }
+}
+
+/// 纠偏源瓦片缓存的容量
+const SOURCE_TILE_CACHE_SIZE: usize = 1024;
+
+/// 纠偏输出 JPEG 的质量
+const CORRECTED_JPEG_QUALITY: u8 = 90;
+
+/// 纠偏源瓦片缓存
+///
+/// 每个输出瓦片由 2~4 个源瓦片拼成，相邻输出瓦片共用源瓦片；缓存最近拉取的源瓦片原始数据，
+/// 避免重复请求和消耗瓦片预算。按写入顺序淘汰。
+#[derive(Default)]
+struct SourceTileCache {
+    tiles: HashMap<TileCoord, (Arc<Vec<u8>>, String)>,
+    order: VecDeque<TileCoord>,
+}
+
+impl SourceTileCache {
+    fn get(&self, coord: &TileCoord) -> Option<(Arc<Vec<u8>>, String)> {
+        self.tiles.get(coord).cloned()
+    }
+
+    fn put(&mut self, coord: TileCoord, data: Arc<Vec<u8>>, source: &str) {
+        if self
+            .tiles
+            .insert(coord, (data, source.to_string()))
+            .is_none()
+        {
+            self.order.push_back(coord);
         }
+        while self.order.len() > SOURCE_TILE_CACHE_SIZE {
+            if let Some(oldest) = self.order.pop_front() {
+                self.tiles.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// 下载纠偏后的瓦片：拉取覆盖该 WGS84 瓦片的 GCJ02 源瓦片并平移重采样
+///
+/// 源瓦片均为 JPEG（如卫星影像）时输出 JPEG，否则输出 PNG
+async fn download_corrected_tile<'a>(
+    ctx: &'a DownloadContext,
+    tile: &TileCoord,
+) -> Result<(Vec<u8>, &'a str), FetchError> {
+    let window = correction::source_window(tile);
+
+    // 任一源瓦片来自备选平台时，以该备选平台作为记录来源
+    let mut tile_source = ctx.platform.id();
+    let mut sources = Vec::new();
+    let mut all_jpeg = true;
+    for source in &window.sources {
+        let cached = ctx.source_tiles.lock().get(source);
+        let (data, from) = match cached {
+            Some((data, from)) => (data, ctx.source_platform(&from)),
+            None => {
+                let (data, from) = fetch_tile_with_fallback(ctx, source).await?;
+                let data = Arc::new(data);
+                ctx.source_tiles.lock().put(*source, data.clone(), from);
+                (data, from)
+            }
+        };
+        all_jpeg &= is_jpeg(&data);
+        if from != ctx.platform.id() {
+            tile_source = from;
+        }
+        sources.push((*source, decode_image(&data)?));
+    }
+
+    let image = window.compose(&sources);
+    let data = if all_jpeg {
+        encode_jpeg(&image, CORRECTED_JPEG_QUALITY)?
+    } else {
+        encode_png(&image)?
     };
+    Ok((data, tile_source))
+}
+
+/// 已接收的部分响应体
+///
+/// 地形、矢量包等大瓦片可达数 MB，弱网下整包下载容易中途断开。服务端声明支持 Range 时，
+/// 重试以 `Range: bytes=N-` 从断点续传，而不是整个请求重来。
+#[derive(Default)]
+struct PartialBody {
+    data: Vec<u8>,
+    /// ETag 或 Last-Modified，续传时作为 If-Range，资源已变化时服务端返回完整响应
+    validator: Option<String>,
+    /// 服务端是否支持 Range 请求
+    resumable: bool,
+}
 
+impl PartialBody {
+    /// 是否应以 Range 请求续传
+    fn resume_from(&self) -> Option<usize> {
+        (self.resumable && !self.data.is_empty()).then_some(self.data.len())
+    }
+
+    /// 收到完整响应时重新开始接收
+    fn restart(&mut self, response: &reqwest::Response) {
+        let headers = response.headers();
+        self.data.clear();
+        if let Some(len) = response.content_length() {
+            self.data.reserve(len as usize);
+        }
+        self.resumable = headers
+            .get(reqwest::header::ACCEPT_RANGES)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.eq_ignore_ascii_case("bytes"));
+        self.validator = headers
+            .get(reqwest::header::ETAG)
+            .filter(|v| !v.as_bytes().starts_with(b"W/"))
+            .or_else(|| headers.get(reqwest::header::LAST_MODIFIED))
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+    }
+
+    /// 放弃已接收的数据，后续重试请求完整响应
+    fn discard(&mut self) {
+        self.data.clear();
+        self.resumable = false;
+    }
+}
+
+/// 解析 Content-Range 的起始偏移，如 `bytes 1024-2047/4096` 返回 1024
+fn content_range_start(value: &str) -> Option<usize> {
+    let range = value.trim().strip_prefix("bytes ")?;
+    let (start, _) = range.split_once('-')?;
+    start.trim().parse().ok()
+}
+
+/// 逐块读取响应体追加到 buf，中途断开时已读取的部分保留在 buf 中
+async fn read_body(mut response: reqwest::Response, buf: &mut Vec<u8>) -> reqwest::Result<()> {
+    while let Some(chunk) = response.chunk().await? {
+        buf.extend_from_slice(&chunk);
+    }
+    Ok(())
+}
+
+/// 请求瓦片数据，按错误类型决定重试策略：
+/// 超时/网络错误与 5xx 指数退避重试，429 全局降速后重试，401/403 及其余 4xx 直接失败
+///
+/// 响应体读取中断且服务端支持 Range 时从断点续传，有新数据到达的续传不计入重试次数。
+/// 来源明确没有该瓦片（404、204 或空响应）时返回 Ok(None)
+async fn fetch_tile_bytes(
+    ctx: &DownloadContext,
+    url: &str,
+    headers: &HashMap<String, String>,
+) -> Result<Option<Vec<u8>>, FetchError> {
     let mut retries = 0;
+    let mut partial = PartialBody::default();
 
     loop {
-        let mut request = client.get(&url);
-        for (key, value) in &headers {
+        // 限流冷却期内暂停发起请求
+        if let Some(remaining) = ctx.state.throttle_remaining() {
+            tokio::time::sleep(remaining).await;
+        }
+
+        // 按任务限速排队
+        if let Some(wait) = ctx.state.reserve_request_slot() {
+            tokio::time::sleep(wait).await;
+        }
+
+        // 请求与读取响应期间持有主机连接许可，退避等待前释放
+        let permit = ctx.host_limiter.acquire(url).await;
+
+        let mut request = ctx.client.get(url);
+        for (key, value) in headers {
             request = request.header(key, value);
         }
+        let resume_from = partial.resume_from();
+        if let Some(offset) = resume_from {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+            if let Some(validator) = &partial.validator {
+                request = request.header(reqwest::header::IF_RANGE, validator);
+            }
+        }
+        let received = partial.data.len();
 
-        match request.send().await {
-            Ok(response) => {
-                if response.status().is_success() {
-                    match response.bytes().await {
-                        Ok(data) => {
-                            // 保存瓦片
-                            let mut s = storage.lock();
-                            if let Err(e) = s.save_tile(tile, &data) {
-                                log::warn!("保存瓦片失败 {}/{}/{}: {}", tile.z, tile.x, tile.y, e);
-                                db.mark_tile_failed(task_id, tile, &e).ok();
-                                state.failed.fetch_add(1, Ordering::Relaxed);
-                            } else {
-                                db.mark_tile_completed(task_id, tile).ok();
-                                state.completed.fetch_add(1, Ordering::Relaxed);
-                            }
-                            return;
+        let fail = |message: String, kind: FetchErrorKind| FetchError {
+            url: Some(url.to_string()),
+            message,
+            kind,
+        };
+        let (message, kind) = match request.send().await {
+            Ok(response) => match response.status() {
+                reqwest::StatusCode::NO_CONTENT | reqwest::StatusCode::NOT_FOUND => {
+                    return Ok(None);
+                }
+                reqwest::StatusCode::PARTIAL_CONTENT
+                    if response
+                        .headers()
+                        .get(reqwest::header::CONTENT_RANGE)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(content_range_start)
+                        != resume_from =>
+                {
+                    partial.discard();
+                    ("续传响应的范围与请求不一致".to_string(), FetchErrorKind::Server)
+                }
+                reqwest::StatusCode::RANGE_NOT_SATISFIABLE if resume_from.is_some() => {
+                    partial.discard();
+                    ("续传范围无效".to_string(), FetchErrorKind::Server)
+                }
+                status if status.is_success() => {
+                    if status != reqwest::StatusCode::PARTIAL_CONTENT {
+                        partial.restart(&response);
+                    }
+                    match read_body(response, &mut partial.data).await {
+                        Ok(()) if partial.data.is_empty() => return Ok(None),
+                        Ok(()) => return Ok(Some(std::mem::take(&mut partial.data))),
+                        Err(e) => (e.to_string(), FetchErrorKind::Network),
+                    }
+                }
+                reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                    ctx.state.throttle(retry_after(&response));
+                    (format!("HTTP {}", response.status()), FetchErrorKind::RateLimited)
+                }
+                reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                    return Err(fail(
+                        format!(
+                            "HTTP {}，请检查 API Key 是否有效以及 Referer 设置",
+                            response.status()
+                        ),
+                        FetchErrorKind::Auth,
+                    ));
+                }
+                // 其余 4xx 错误不重试
+                status if status.is_client_error() => {
+                    return Err(fail(format!("HTTP {}", status), FetchErrorKind::Other));
+                }
+                status => (format!("HTTP {}", status), FetchErrorKind::Server),
+            },
+            Err(e) => (e.to_string(), FetchErrorKind::Network),
+        };
+
+        // 已判定断网时不再重试，避免白白消耗重试次数
+        let network_paused =
+            kind == FetchErrorKind::Network && ctx.state.network_paused.load(Ordering::Relaxed);
+        // 续传有进展时立即继续，不消耗重试次数
+        let progressed = partial.resume_from().is_some_and(|len| len > received);
+        if network_paused || (retries >= ctx.max_retries && !progressed) {
+            return Err(fail(message, kind));
+        }
+
+        drop(permit);
+        if progressed {
+            log::debug!("瓦片响应中断，已接收 {} 字节，续传: {}", partial.data.len(), url);
+            continue;
+        }
+        retries += 1;
+        // 限流时由冷却期控制等待，其余错误指数退避
+        if kind != FetchErrorKind::RateLimited {
+            let delay = Duration::from_millis(1000 * 2u64.pow(retries.min(4)));
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::stream::{self, StreamExt};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 本地 HTTP/1.1 keep-alive 服务，每个请求返回固定大小的响应
+    async fn spawn_tile_server(body_size: usize) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    break;
+                };
+                tokio::spawn(async move {
+                    let response = [
+                        format!(
+                            "HTTP/1.1 200 OK\r\nContent-Type: image/png\r\nContent-Length: {}\r\n\r\n",
+                            body_size
+                        )
+                        .into_bytes(),
+                        vec![0u8; body_size],
+                    ]
+                    .concat();
+                    let mut buf = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    while let Ok(n) = socket.read(&mut chunk).await {
+                        if n == 0 {
+                            break;
                         }
-                        Err(e) => {
-                            if retries >= max_retries {
-                                db.mark_tile_failed(task_id, tile, &e.to_string()).ok();
-                                state.failed.fetch_add(1, Ordering::Relaxed);
+                        buf.extend_from_slice(&chunk[..n]);
+                        while let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                            buf.drain(..end + 4);
+                            if socket.write_all(&response).await.is_err() {
                                 return;
                             }
                         }
                     }
-                } else if response.status().is_client_error() {
-                    // 4xx 错误不重试
-                    let error = format!("HTTP {}", response.status());
-                    db.mark_tile_failed(task_id, tile, &error).ok();
-                    state.failed.fetch_add(1, Ordering::Relaxed);
-                    return;
-                } else {
-                    // 5xx 错误重试
-                    if retries >= max_retries {
-                        let error = format!("HTTP {}", response.status());
-                        db.mark_tile_failed(task_id, tile, &error).ok();
-                        state.failed.fetch_add(1, Ordering::Relaxed);
-                        return;
-                    }
-                }
+                });
             }
-            Err(e) => {
-                if retries >= max_retries {
-                    db.mark_tile_failed(task_id, tile, &e.to_string()).ok();
-                    state.failed.fetch_add(1, Ordering::Relaxed);
-                    return;
+        });
+        format!("http://{}", addr)
+    }
+
+    async fn measure(client: &reqwest::Client, url: &str, requests: usize) -> f64 {
+        let start = Instant::now();
+        stream::iter(0..requests)
+            .map(|i| {
+                let url = format!("{}/{}.png", url, i);
+                async move {
+                    let response = client.get(&url).send().await.unwrap();
+                    response.bytes().await.unwrap().len()
                 }
+            })
+            .buffer_unordered(16)
+            .collect::<Vec<_>>()
+            .await;
+        requests as f64 / start.elapsed().as_secs_f64()
+    }
+
+    #[test]
+    fn test_content_range_start() {
+        assert_eq!(content_range_start("bytes 1024-2047/4096"), Some(1024));
+        assert_eq!(content_range_start("bytes 0-99/*"), Some(0));
+        assert_eq!(content_range_start("bytes */4096"), None);
+        assert_eq!(content_range_start("items 0-9/10"), None);
+    }
+
+    /// 不提供任何瓦片地址的平台，下载时瓦片立即失败，不访问网络
+    struct OfflinePlatform;
+
+    impl TilePlatform for OfflinePlatform {
+        fn id(&self) -> &str {
+            "offline"
+        }
+
+        fn name(&self) -> &str {
+            "离线测试"
+        }
+
+        fn get_tile_url(&self, _z: u32, _x: u32, _y: u32, _map_type: &MapType) -> Option<String> {
+            None
+        }
+
+        fn max_zoom(&self) -> u32 {
+            18
+        }
+
+        fn min_zoom(&self) -> u32 {
+            0
+        }
+
+        fn supported_map_types(&self) -> Vec<MapType> {
+            vec![MapType::Street]
+        }
+
+        fn requires_api_key(&self) -> bool {
+            false
+        }
+
+        fn set_api_key(&mut self, _key: &str) {}
+    }
+
+    #[tokio::test]
+    async fn test_cancel_skips_completion_action() {
+        let dir = std::env::temp_dir().join(format!("tile_cancel_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db = Arc::new(TileDatabase::new(&dir.join("tiles.db")).unwrap());
+        let task_id = uuid::Uuid::new_v4().to_string();
+        let bounds = Bounds {
+            north: 85.0,
+            south: -85.0,
+            east: 179.9,
+            west: -179.9,
+        };
+        let output = dir.join("tiles");
+        db.create_task(
+            &task_id,
+            "取消测试",
+            "offline",
+            "street",
+            &bounds,
+            &[6],
+            estimate_tiles(&bounds, &[6]).total_tiles,
+            &output.to_string_lossy(),
+            "folder",
+            1,
+            0,
+            None,
+            DEFAULT_MAX_CONNECTIONS_PER_HOST,
+            &HeaderOptions::default(),
+            false,
+            &[],
+            false,
+            false,
+            None,
+            None,
+            CompletionAction::Shutdown,
+        )
+        .unwrap();
+        let task = db.get_task(&task_id).unwrap().unwrap();
+
+        let downloader = Arc::new(TileDownloader::new());
+        let (progress_tx, mut progress_rx) = mpsc::channel(100);
+        let events = tokio::spawn(async move {
+            let mut last = None;
+            while let Some(event) = progress_rx.recv().await {
+                last = Some(event);
             }
+            last
+        });
+        let download = {
+            let downloader = downloader.clone();
+            let db = db.clone();
+            tokio::spawn(async move {
+                downloader
+                    .start_download(
+                        db,
+                        None,
+                        task,
+                        Box::new(OfflinePlatform),
+                        Vec::new(),
+                        progress_tx,
+                    )
+                    .await
+            })
+        };
+
+        // 等第一批瓦片处理完后按取消命令的方式停止任务
+        loop {
+            let started = downloader.get_state(&task_id).is_some_and(|state| {
+                state.completed.load(Ordering::Relaxed) + state.failed.load(Ordering::Relaxed) > 0
+            });
+            if started {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
         }
+        downloader.stop(&task_id);
+        db.update_task_status(&task_id, "cancelled").unwrap();
 
-        retries += 1;
-        // 指数退避
-        let delay = Duration::from_millis(1000 * 2u64.pow(retries.min(4)));
-        tokio::time::sleep(delay).await;
+        let outcome = download.await.unwrap().unwrap();
+        assert_eq!(outcome, DownloadOutcome::Stopped);
+        assert!(downloader.get_state(&task_id).is_none());
+        assert_eq!(events.await.unwrap().unwrap().status, "cancelled");
+
+        let task = db.get_task(&task_id).unwrap().unwrap();
+        assert_eq!(task.status, "cancelled");
+        assert_eq!(task.on_complete, CompletionAction::Shutdown);
+        assert_eq!(outcome.completion_action(&task), CompletionAction::None);
+        assert_eq!(
+            DownloadOutcome::Finished.completion_action(&task),
+            CompletionAction::Shutdown
+        );
+
+        db.close_progress(&task_id);
+        drop(db);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// 连接复用吞吐基准：cargo test --release bench_connection_reuse -- --ignored --nocapture
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn bench_connection_reuse() {
+        let url = spawn_tile_server(20 * 1024).await;
+        let requests = 5000;
+
+        let tuned = build_http_client(&TileNetworkSettings::default(), 16).unwrap();
+        let no_reuse = build_http_client(
+            &TileNetworkSettings {
+                pool_max_idle_per_host: Some(0),
+                ..Default::default()
+            },
+            16,
+        )
+        .unwrap();
+
+        let reuse_rps = measure(&tuned, &url, requests).await;
+        let no_reuse_rps = measure(&no_reuse, &url, requests).await;
+        println!(
+            "连接复用 {:.0} req/s，不复用 {:.0} req/s，提升 {:.1} 倍",
+            reuse_rps,
+            no_reuse_rps,
+            reuse_rps / no_reuse_rps
+        );
     }
 }
@@ -0,0 +1,276 @@
+//! 瓦片图像处理工具
+//!
+//! 支持 PNG 与 JPEG 解码/编码，所有图像统一为 8 位 RGBA 像素缓冲区
+
+/// 标准瓦片尺寸（像素）
+pub const TILE_SIZE: u32 = 256;
+
+/// RGBA 图像
+#[derive(Debug, Clone)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// 创建指定颜色填充的图像
+    pub fn filled(width: u32, height: u32, color: [u8; 4]) -> Self {
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        for _ in 0..width * height {
+            pixels.extend_from_slice(&color);
+        }
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// 读取像素，越界返回 None
+    pub fn get_pixel(&self, x: i64, y: i64) -> Option<[u8; 4]> {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return None;
+        }
+        let i = ((y as usize) * self.width as usize + x as usize) * 4;
+        Some([
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ])
+    }
+
+    /// 写入像素，越界忽略
+    pub fn put_pixel(&mut self, x: i64, y: i64, color: [u8; 4]) {
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return;
+        }
+        let i = ((y as usize) * self.width as usize + x as usize) * 4;
+        self.pixels[i..i + 4].copy_from_slice(&color);
+    }
+
+    /// 将另一张图像绘制到 (left, top) 位置
+    pub fn blit(&mut self, src: &RgbaImage, left: i64, top: i64) {
+        for sy in 0..src.height as i64 {
+            for sx in 0..src.width as i64 {
+                if let Some(color) = src.get_pixel(sx, sy) {
+                    self.put_pixel(left + sx, top + sy, color);
+                }
+            }
+        }
+    }
+
+    /// 裁剪出子区域
+    pub fn crop(&self, left: u32, top: u32, width: u32, height: u32) -> RgbaImage {
+        let mut out = RgbaImage::filled(width, height, [0, 0, 0, 0]);
+        for y in 0..height as i64 {
+            for x in 0..width as i64 {
+                if let Some(color) = self.get_pixel(left as i64 + x, top as i64 + y) {
+                    out.put_pixel(x, y, color);
+                }
+            }
+        }
+        out
+    }
+
+    /// 最近邻缩放到指定尺寸
+    pub fn resize(&self, width: u32, height: u32) -> RgbaImage {
+        let mut out = RgbaImage::filled(width, height, [0, 0, 0, 0]);
+        for y in 0..height {
+            for x in 0..width {
+                let sx = (x as u64 * self.width as u64 / width as u64) as i64;
+                let sy = (y as u64 * self.height as u64 / height as u64) as i64;
+                if let Some(color) = self.get_pixel(sx, sy) {
+                    out.put_pixel(x as i64, y as i64, color);
+                }
+            }
+        }
+        out
+    }
+
+    /// 绘制实心圆
+    pub fn fill_circle(&mut self, cx: i64, cy: i64, radius: i64, color: [u8; 4]) {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if dx * dx + dy * dy <= radius * radius {
+                    self.put_pixel(cx + dx, cy + dy, color);
+                }
+            }
+        }
+    }
+}
+
+/// 判断数据是否为 PNG
+pub fn is_png(data: &[u8]) -> bool {
+    data.starts_with(&[0x89, b'P', b'N', b'G'])
+}
+
+/// 解码 PNG 为 RGBA
+pub fn decode_png(data: &[u8]) -> Result<RgbaImage, String> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder
+        .read_info()
+        .map_err(|e| format!("解析 PNG 失败: {}", e))?;
+
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buf)
+        .map_err(|e| format!("解码 PNG 失败: {}", e))?;
+    buf.truncate(info.buffer_size());
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buf,
+        png::ColorType::Rgb => buf
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        png::ColorType::Indexed => return Err("不支持的 PNG 调色板格式".to_string()),
+    };
+
+    Ok(RgbaImage {
+        width: info.width,
+        height: info.height,
+        pixels,
+    })
+}
+
+/// 判断数据是否为 JPEG
+pub fn is_jpeg(data: &[u8]) -> bool {
+    data.starts_with(&[0xFF, 0xD8, 0xFF])
+}
+
+/// 解码 JPEG 为 RGBA
+pub fn decode_jpeg(data: &[u8]) -> Result<RgbaImage, String> {
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    let buf = decoder
+        .decode()
+        .map_err(|e| format!("解码 JPEG 失败: {}", e))?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| "解析 JPEG 失败".to_string())?;
+
+    let pixels = match info.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => buf
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::L8 => buf.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        // 16 位灰度为大端序，取高字节
+        jpeg_decoder::PixelFormat::L16 => buf
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::CMYK32 => buf
+            .chunks_exact(4)
+            .flat_map(|p| {
+                let k = p[3] as u16;
+                let channel = |c: u8| (c as u16 * k / 255) as u8;
+                [channel(p[0]), channel(p[1]), channel(p[2]), 255]
+            })
+            .collect(),
+    };
+
+    Ok(RgbaImage {
+        width: info.width as u32,
+        height: info.height as u32,
+        pixels,
+    })
+}
+
+/// 按数据头解码 PNG 或 JPEG 瓦片
+pub fn decode_image(data: &[u8]) -> Result<RgbaImage, String> {
+    if is_png(data) {
+        decode_png(data)
+    } else if is_jpeg(data) {
+        decode_jpeg(data)
+    } else {
+        Err("不支持的瓦片图片格式，仅支持 PNG 与 JPEG".to_string())
+    }
+}
+
+/// 编码 RGBA 为 PNG
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, image.width, image.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| format!("写入 PNG 头失败: {}", e))?;
+        writer
+            .write_image_data(&image.pixels)
+            .map_err(|e| format!("编码 PNG 失败: {}", e))?;
+    }
+    Ok(out)
+}
+
+/// 编码 RGBA 为 JPEG，透明部分按白色背景合成
+pub fn encode_jpeg(image: &RgbaImage, quality: u8) -> Result<Vec<u8>, String> {
+    let (width, height) = match (u16::try_from(image.width), u16::try_from(image.height)) {
+        (Ok(w), Ok(h)) => (w, h),
+        _ => return Err("图片尺寸超出 JPEG 上限".to_string()),
+    };
+    let rgb: Vec<u8> = image
+        .pixels
+        .chunks_exact(4)
+        .flat_map(|p| {
+            let alpha = p[3] as u16;
+            let blend = |c: u8| ((c as u16 * alpha + 255 * (255 - alpha)) / 255) as u8;
+            [blend(p[0]), blend(p[1]), blend(p[2])]
+        })
+        .collect();
+
+    let mut out = Vec::new();
+    jpeg_encoder::Encoder::new(&mut out, quality)
+        .encode(&rgb, width, height, jpeg_encoder::ColorType::Rgb)
+        .map_err(|e| format!("编码 JPEG 失败: {}", e))?;
+    Ok(out)
+}
+
+/// 经纬度转指定层级下的全局像素坐标 (Web Mercator)
+pub fn lonlat_to_pixel(lon: f64, lat: f64, zoom: u32) -> (f64, f64) {
+    let size = TILE_SIZE as f64 * 2f64.powi(zoom as i32);
+    let x = (lon + 180.0) / 360.0 * size;
+    let lat_rad = lat.to_radians();
+    let y = (1.0 - lat_rad.tan().asinh() / std::f64::consts::PI) / 2.0 * size;
+    (x, y)
+}
+
+/// 全局像素坐标转经纬度 (Web Mercator)
+pub fn pixel_to_lonlat(x: f64, y: f64, zoom: u32) -> (f64, f64) {
+    let size = TILE_SIZE as f64 * 2f64.powi(zoom as i32);
+    let lon = x / size * 360.0 - 180.0;
+    let n = std::f64::consts::PI * (1.0 - 2.0 * y / size);
+    let lat = n.sinh().atan().to_degrees();
+    (lon, lat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jpeg_round_trip() {
+        let mut image = RgbaImage::filled(16, 16, [200, 40, 40, 255]);
+        image.put_pixel(0, 0, [0, 0, 0, 0]);
+        let data = encode_jpeg(&image, 90).unwrap();
+        assert!(is_jpeg(&data));
+
+        let decoded = decode_image(&data).unwrap();
+        assert_eq!((decoded.width, decoded.height), (16, 16));
+        let [r, g, b, a] = decoded.get_pixel(8, 8).unwrap();
+        assert!(r > 180 && g < 70 && b < 70 && a == 255);
+        // 透明像素按白色背景合成
+        assert!(decoded.get_pixel(0, 0).unwrap()[0] > 150);
+
+        assert!(decode_image(b"<html>").is_err());
+    }
+}
@@ -0,0 +1,113 @@
+//! 下载任务清单文件
+//!
+//! 任务完成后在输出旁生成 manifest.json，记录平台、范围、层级、瓦片数、总大小、
+//! 坐标系与失败列表，随离线包一并交付。
+
+use super::database::TileDatabase;
+use super::platforms::create_platform;
+use super::types::{Bounds, SourceStat, TileError};
+use crate::error::AppError;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// 清单文件名
+pub(crate) const MANIFEST_FILE: &str = "manifest.json";
+
+/// 离线包清单
+#[derive(Debug, Clone, Serialize)]
+pub struct TileManifest {
+    pub task_id: String,
+    pub name: String,
+    pub platform: String,
+    pub map_type: String,
+    pub bounds: Bounds,
+    pub zoom_levels: Vec<u32>,
+    pub output_format: String,
+    /// 瓦片坐标系
+    pub crs: String,
+    pub total_tiles: u64,
+    pub completed_tiles: u64,
+    pub failed_tiles: u64,
+    /// 输出总大小（字节）
+    pub total_size_bytes: u64,
+    pub sources: Vec<SourceStat>,
+    pub generated_at: String,
+    pub failed: Vec<TileError>,
+}
+
+/// 清单路径：目录输出写在目录内，文件输出写在同目录的 <文件名>.manifest.json
+pub fn manifest_path(output_path: &Path, output_format: &str) -> PathBuf {
+    if output_format.eq_ignore_ascii_case("folder") || output_path.is_dir() {
+        output_path.join(MANIFEST_FILE)
+    } else {
+        let mut name = output_path.file_name().unwrap_or_default().to_os_string();
+        name.push(".");
+        name.push(MANIFEST_FILE);
+        output_path.with_file_name(name)
+    }
+}
+
+/// 统计文件或目录的总大小，跳过清单文件本身
+fn total_size(path: &Path) -> u64 {
+    if path.is_file() {
+        return path.metadata().map(|m| m.len()).unwrap_or(0);
+    }
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|e| e.file_name() != MANIFEST_FILE)
+        .map(|e| total_size(&e.path()))
+        .sum()
+}
+
+/// 根据任务当前状态生成并写入清单，返回清单路径
+pub fn write_manifest(db: &TileDatabase, task_id: &str) -> Result<PathBuf, AppError> {
+    let task = db
+        .get_task(task_id)
+        .map_err(|e| format!("获取任务失败: {}", e))?
+        .ok_or_else(|| AppError::not_found("任务不存在"))?;
+
+    let output_path = Path::new(&task.output_path);
+    if !output_path.exists() {
+        return Err(AppError::not_found("输出文件不存在"));
+    }
+
+    let (_, completed, failed) = db
+        .get_tile_stats(task_id)
+        .map_err(|e| format!("获取统计失败: {}", e))?;
+    let platform = create_platform(&task.platform, None);
+    let crs = if task.coord_correction && platform.coord_system() == "GCJ02" {
+        "WGS84"
+    } else {
+        platform.coord_system()
+    };
+
+    let manifest = TileManifest {
+        task_id: task.id.clone(),
+        name: task.name.clone(),
+        platform: task.platform.clone(),
+        map_type: task.map_type.clone(),
+        bounds: task.bounds.clone(),
+        zoom_levels: task.zoom_levels.clone(),
+        output_format: task.output_format.clone(),
+        crs: crs.to_string(),
+        total_tiles: task.total_tiles,
+        completed_tiles: completed,
+        failed_tiles: failed,
+        total_size_bytes: total_size(output_path),
+        sources: db
+            .get_source_stats(task_id)
+            .map_err(|e| format!("获取来源统计失败: {}", e))?,
+        generated_at: chrono::Local::now().to_rfc3339(),
+        failed: db
+            .get_recent_tile_errors(task_id, u32::MAX)
+            .map_err(|e| format!("获取失败记录失败: {}", e))?,
+    };
+
+    let path = manifest_path(output_path, &task.output_format);
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("写入清单失败: {}", e))?;
+    Ok(path)
+}
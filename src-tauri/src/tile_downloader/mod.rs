@@ -1,8 +1,21 @@
 pub mod boundaries;
+pub mod cache;
+pub mod clip;
 pub mod commands;
+pub mod correction;
+pub mod coverage;
 pub mod database;
 pub mod downloader;
+pub mod imaging;
+pub mod manifest;
+pub mod package;
 pub mod platforms;
+pub mod probe;
+pub mod snapshot;
 pub mod storage;
+pub mod thumbnail;
+pub mod tile_list;
 pub mod tile_proxy;
 pub mod types;
+pub mod url_rules;
+pub mod verify;
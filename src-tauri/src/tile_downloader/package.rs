@@ -0,0 +1,207 @@
+//! 按目标 App 打包离线地图
+//!
+//! 奥维、两步路、OruxMaps、Locus 等移动端 App 只认各自支持的离线格式与目录结构。
+//! 按目标 App 选择兼容格式，从任务输出转换生成可直接拷贝到设备的离线包，并给出拷贝位置说明。
+
+use super::storage::{
+    for_each_tile, MbtilesStorage, OruxMapsStorage, SqlitedbStorage, TileStorage,
+};
+use super::types::TaskInfo;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 目标 App
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetApp {
+    /// 奥维互动地图
+    Ovital,
+    /// 两步路
+    Liangbulu,
+    #[serde(rename = "oruxmaps")]
+    OruxMaps,
+    Locus,
+    /// 其他支持 MBTiles 的 App
+    Generic,
+}
+
+/// 离线包格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageFormat {
+    Mbtiles,
+    Sqlitedb,
+    #[serde(rename = "oruxmaps")]
+    OruxMaps,
+}
+
+impl TargetApp {
+    pub fn format(self) -> PackageFormat {
+        match self {
+            Self::Ovital | Self::Generic => PackageFormat::Mbtiles,
+            Self::Liangbulu | Self::Locus => PackageFormat::Sqlitedb,
+            Self::OruxMaps => PackageFormat::OruxMaps,
+        }
+    }
+
+    /// 拷贝到设备的位置说明
+    fn copy_hint(self, file_name: &str) -> String {
+        match self {
+            Self::OruxMaps => format!(
+                "将整个「{}」文件夹拷贝到设备的 oruxmaps/mapfiles/ 目录，在 OruxMaps 中刷新离线地图列表",
+                file_name
+            ),
+            Self::Locus => format!(
+                "将 {} 拷贝到设备的 Locus/maps/ 目录，在 Locus 离线地图列表中选择",
+                file_name
+            ),
+            Self::Ovital => format!("将 {} 拷贝到设备存储，在奥维中通过导入地图选择该文件", file_name),
+            Self::Liangbulu => format!("将 {} 拷贝到设备存储，在两步路中通过导入离线地图选择该文件", file_name),
+            Self::Generic => format!("将 {} 拷贝到设备存储，在支持 MBTiles 的 App 中导入", file_name),
+        }
+    }
+}
+
+impl PackageFormat {
+    /// 输出文件名（OruxMaps 为目录名）
+    fn file_name(self, name: &str) -> String {
+        match self {
+            Self::Mbtiles => format!("{}.mbtiles", name),
+            Self::Sqlitedb => format!("{}.sqlitedb", name),
+            Self::OruxMaps => name.to_string(),
+        }
+    }
+
+    fn create_storage(self) -> Box<dyn TileStorage> {
+        match self {
+            Self::Mbtiles => Box::new(MbtilesStorage::new()),
+            Self::Sqlitedb => Box::new(SqlitedbStorage::new()),
+            Self::OruxMaps => Box::new(OruxMapsStorage::new()),
+        }
+    }
+}
+
+/// 打包结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageResult {
+    pub target: TargetApp,
+    pub format: PackageFormat,
+    /// 离线包路径（OruxMaps 为目录）
+    pub path: String,
+    pub tile_count: u64,
+    pub size_bytes: u64,
+    /// 拷贝到设备的说明
+    pub copy_hint: String,
+}
+
+/// 将包名整理为各平台文件系统都可用的文件名
+fn sanitize_name(name: &str) -> String {
+    let cleaned: String = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_whitespace() || c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim_matches(|c| c == '.' || c == '_');
+    if cleaned.is_empty() {
+        "offline_map".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// 文件或目录占用的字节数
+fn path_size(path: &Path) -> u64 {
+    if path.is_dir() {
+        std::fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| path_size(&entry.path()))
+                    .sum()
+            })
+            .unwrap_or(0)
+    } else {
+        std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+    }
+}
+
+/// 从任务输出生成目标 App 的离线包，name 为空时取任务名称
+pub fn build_package(
+    task: &TaskInfo,
+    target: TargetApp,
+    output_dir: &Path,
+    name: Option<&str>,
+    overwrite: bool,
+) -> Result<PackageResult, AppError> {
+    let format = target.format();
+    let file_name = format.file_name(&sanitize_name(name.unwrap_or(&task.name)));
+    let path: PathBuf = output_dir.join(&file_name);
+
+    if path.exists() {
+        if !overwrite {
+            return Err(AppError::conflict(format!(
+                "离线包 {} 已存在",
+                path.display()
+            )));
+        }
+        remove_path(&path).map_err(|e| format!("删除已有离线包失败: {}", e))?;
+    }
+
+    let mut storage = format.create_storage();
+    storage.init(&path, &task.bounds, &task.zoom_levels)?;
+    storage.set_metadata("name", &task.name)?;
+
+    let mut tile_count = 0u64;
+    for_each_tile(
+        &task.output_format,
+        Path::new(&task.output_path),
+        &mut |coord, data| {
+            storage.save_tile(&coord, &data)?;
+            tile_count += 1;
+            Ok(())
+        },
+    )?;
+    storage.finalize()?;
+
+    if tile_count == 0 {
+        let _ = remove_path(&path);
+        return Err(AppError::invalid("任务输出中没有瓦片"));
+    }
+    log::info!("离线包已生成: {}，{} 个瓦片", path.display(), tile_count);
+
+    Ok(PackageResult {
+        target,
+        format,
+        path: path.to_string_lossy().to_string(),
+        tile_count,
+        size_bytes: path_size(&path),
+        copy_hint: target.copy_hint(&file_name),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_file_name() {
+        assert_eq!(sanitize_name(" 上海 市区/卫星图 "), "上海_市区_卫星图");
+        assert_eq!(sanitize_name("..."), "offline_map");
+        assert_eq!(TargetApp::Locus.format().file_name("上海"), "上海.sqlitedb");
+        assert_eq!(TargetApp::OruxMaps.format().file_name("上海"), "上海");
+        assert_eq!(TargetApp::Ovital.format().file_name("上海"), "上海.mbtiles");
+    }
+}
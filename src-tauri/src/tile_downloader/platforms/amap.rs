@@ -1,4 +1,5 @@
 use super::TilePlatform;
+use crate::tile_downloader::coverage::CoverageArea;
 use crate::tile_downloader::types::MapType;
 
 pub struct AmapPlatform {
@@ -42,6 +43,27 @@ impl TilePlatform for AmapPlatform {
                     s, x, y, z
                 ))
             }
+            // 纯注记（不含道路线），可叠加在影像或其他底图上
+            MapType::Annotation => {
+                Some(format!(
+                    "http://wprd0{}.is.autonavi.com/appmaptile?lang=zh_cn&size=1&scl=1&style=8&ltype=4&x={}&y={}&z={}",
+                    s, x, y, z
+                ))
+            }
+            MapType::AnnotationEn => {
+                Some(format!(
+                    "http://wprd0{}.is.autonavi.com/appmaptile?lang=en&size=1&scl=1&style=8&ltype=4&x={}&y={}&z={}",
+                    s, x, y, z
+                ))
+            }
+            // 路况服务的层级从 17 倒数，最高支持到 17 级
+            MapType::Traffic => {
+                let zoom = 17u32.checked_sub(z)?;
+                Some(format!(
+                    "http://tm.amap.com/trafficengine/mapabc/traffictile?v=1.0&t=1&x={}&y={}&zoom={}",
+                    x, y, zoom
+                ))
+            }
             _ => None,
         }
     }
@@ -55,7 +77,14 @@ impl TilePlatform for AmapPlatform {
     }
 
     fn supported_map_types(&self) -> Vec<MapType> {
-        vec![MapType::Street, MapType::Satellite, MapType::Roadnet]
+        vec![
+            MapType::Street,
+            MapType::Satellite,
+            MapType::Roadnet,
+            MapType::Annotation,
+            MapType::AnnotationEn,
+            MapType::Traffic,
+        ]
     }
 
     fn requires_api_key(&self) -> bool {
@@ -66,6 +95,14 @@ impl TilePlatform for AmapPlatform {
         self.api_key = Some(key.to_string());
     }
 
+    fn coord_system(&self) -> &str {
+        "GCJ02"
+    }
+
+    fn coverage(&self) -> CoverageArea {
+        CoverageArea::ChinaMainland
+    }
+
     fn subdomains(&self) -> Vec<&str> {
         vec!["1", "2", "3", "4"]
     }
@@ -1,13 +1,13 @@
 use super::TilePlatform;
+use crate::tile_downloader::coverage::CoverageArea;
 use crate::tile_downloader::types::MapType;
 
-pub struct BaiduPlatform {
-    api_key: Option<String>,
-}
+/// 百度地图公开瓦片。开放平台没有提供以 AK 鉴权的栅格瓦片（XYZ/WMTS）接口，因此不接受 Key
+pub struct BaiduPlatform;
 
 impl BaiduPlatform {
     pub fn new() -> Self {
-        Self { api_key: None }
+        Self
     }
 
     /// 将标准 WGS84/GCJ02 坐标的瓦片坐标转换为百度坐标系
@@ -79,8 +79,12 @@ impl TilePlatform for BaiduPlatform {
         false
     }
 
-    fn set_api_key(&mut self, key: &str) {
-        self.api_key = Some(key.to_string());
+    fn coord_system(&self) -> &str {
+        "BD09"
+    }
+
+    fn coverage(&self) -> CoverageArea {
+        CoverageArea::ChinaMainland
     }
 
     fn subdomains(&self) -> Vec<&str> {
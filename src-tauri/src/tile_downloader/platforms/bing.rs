@@ -9,23 +9,23 @@ impl BingPlatform {
     pub fn new() -> Self {
         Self { api_key: None }
     }
+}
 
-    /// 将XYZ坐标转换为Bing的QuadKey
-    fn tile_to_quadkey(&self, z: u32, x: u32, y: u32) -> String {
-        let mut quadkey = String::with_capacity(z as usize);
-        for i in (1..=z).rev() {
-            let mut digit = 0u8;
-            let mask = 1u32 << (i - 1);
-            if (x & mask) != 0 {
-                digit += 1;
-            }
-            if (y & mask) != 0 {
-                digit += 2;
-            }
-            quadkey.push((b'0' + digit) as char);
+/// 将XYZ坐标转换为Bing的QuadKey
+pub(super) fn tile_to_quadkey(z: u32, x: u32, y: u32) -> String {
+    let mut quadkey = String::with_capacity(z as usize);
+    for i in (1..=z).rev() {
+        let mut digit = 0u8;
+        let mask = 1u32 << (i - 1);
+        if (x & mask) != 0 {
+            digit += 1;
+        }
+        if (y & mask) != 0 {
+            digit += 2;
         }
-        quadkey
+        quadkey.push((b'0' + digit) as char);
     }
+    quadkey
 }
 
 impl TilePlatform for BingPlatform {
@@ -39,7 +39,7 @@ impl TilePlatform for BingPlatform {
 
     fn get_tile_url(&self, z: u32, x: u32, y: u32, map_type: &MapType) -> Option<String> {
         let s = self.get_subdomain(x, y);
-        let quadkey = self.tile_to_quadkey(z, x, y);
+        let quadkey = tile_to_quadkey(z, x, y);
 
         let (url_type, suffix) = match map_type {
             MapType::Street => ("r", "png"),      // 街道图
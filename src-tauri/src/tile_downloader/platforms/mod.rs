@@ -6,6 +6,7 @@ mod tianditu;
 mod osm;
 mod arcgis;
 mod bing;
+mod ruled;
 
 pub use google::GooglePlatform;
 pub use baidu::BaiduPlatform;
@@ -16,8 +17,14 @@ pub use osm::OsmPlatform;
 pub use arcgis::ArcGisPlatform;
 pub use bing::BingPlatform;
 
+use super::coverage::CoverageArea;
+use super::url_rules;
 use super::types::{MapType, PlatformInfo};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// 子域名轮询计数器
+static SUBDOMAIN_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 /// 瓦片平台 trait
 pub trait TilePlatform: Send + Sync {
@@ -42,8 +49,28 @@ pub trait TilePlatform: Send + Sync {
     /// 是否需要API Key
     fn requires_api_key(&self) -> bool;
 
-    /// 设置API Key
-    fn set_api_key(&mut self, key: &str);
+    /// 是否可填写 API Key，Key 为可选的平台也返回 true
+    fn accepts_api_key(&self) -> bool {
+        self.requires_api_key()
+    }
+
+    /// 申请 API Key 的入口
+    fn key_apply_url(&self) -> Option<&str> {
+        None
+    }
+
+    /// 设置API Key，不接受 Key 的平台忽略
+    fn set_api_key(&mut self, _key: &str) {}
+
+    /// 瓦片坐标系：WGS84 / GCJ02 / BD09
+    fn coord_system(&self) -> &str {
+        "WGS84"
+    }
+
+    /// 瓦片覆盖范围
+    fn coverage(&self) -> CoverageArea {
+        CoverageArea::Global
+    }
 
     /// 获取请求头
     fn get_headers(&self) -> HashMap<String, String> {
@@ -55,13 +82,13 @@ pub trait TilePlatform: Send + Sync {
         headers
     }
 
-    /// 获取子域名
-    fn get_subdomain(&self, x: u32, y: u32) -> String {
+    /// 获取子域名（轮询分配，使并发请求均匀落到各个子域）
+    fn get_subdomain(&self, _x: u32, _y: u32) -> String {
         let subdomains = self.subdomains();
         if subdomains.is_empty() {
             return String::new();
         }
-        let index = ((x + y) as usize) % subdomains.len();
+        let index = SUBDOMAIN_COUNTER.fetch_add(1, Ordering::Relaxed) % subdomains.len();
         subdomains[index].to_string()
     }
 
@@ -80,6 +107,8 @@ pub trait TilePlatform: Send + Sync {
             max_zoom: self.max_zoom(),
             map_types: self.supported_map_types().iter().map(|t| t.to_string()).collect(),
             requires_key: self.requires_api_key(),
+            accepts_key: self.accepts_api_key(),
+            key_apply_url: self.key_apply_url().map(str::to_string),
         }
     }
 }
@@ -98,6 +127,12 @@ pub fn create_platform(platform: &str, api_key: Option<&str>) -> Box<dyn TilePla
         _ => Box::new(OsmPlatform::new()),
     };
 
+    // 远程规则覆盖内置 URL 模板
+    let rules = url_rules::rules_for(p.id());
+    if !rules.is_empty() {
+        p = Box::new(ruled::RuledPlatform::new(p, rules));
+    }
+
     if let Some(key) = api_key {
         p.set_api_key(key);
     }
@@ -0,0 +1,124 @@
+//! 按远程 URL 规则覆盖平台内置模板
+
+use super::bing::tile_to_quadkey;
+use super::TilePlatform;
+use crate::tile_downloader::coverage::CoverageArea;
+use crate::tile_downloader::types::MapType;
+use crate::tile_downloader::url_rules::{render, UrlRule};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub struct RuledPlatform {
+    inner: Box<dyn TilePlatform>,
+    rules: Vec<UrlRule>,
+    api_key: Option<String>,
+    counter: AtomicUsize,
+}
+
+impl RuledPlatform {
+    pub fn new(inner: Box<dyn TilePlatform>, rules: Vec<UrlRule>) -> Self {
+        Self {
+            inner,
+            rules,
+            api_key: None,
+            counter: AtomicUsize::new(0),
+        }
+    }
+
+    /// 当前 Key 状态下适用的规则
+    fn rule_for(&self, map_type: &MapType) -> Option<&UrlRule> {
+        let map_type = map_type.to_string();
+        let has_key = self.api_key.is_some();
+        self.rules
+            .iter()
+            .find(|r| r.map_type == map_type && r.uses_key() == has_key)
+    }
+
+    fn subdomain_for(&self, rule: &UrlRule, x: u32, y: u32) -> String {
+        if rule.subdomains.is_empty() {
+            return self.inner.get_subdomain(x, y);
+        }
+        let index = self.counter.fetch_add(1, Ordering::Relaxed) % rule.subdomains.len();
+        rule.subdomains[index].clone()
+    }
+}
+
+impl TilePlatform for RuledPlatform {
+    fn id(&self) -> &str {
+        self.inner.id()
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn get_tile_url(&self, z: u32, x: u32, y: u32, map_type: &MapType) -> Option<String> {
+        let Some(rule) = self.rule_for(map_type) else {
+            return self.inner.get_tile_url(z, x, y, map_type);
+        };
+
+        // 百度瓦片坐标：原点在经纬度 (0, 0)，Y 向上增加
+        let center = (1i64 << z) / 2;
+        let vars = [
+            ("s", self.subdomain_for(rule, x, y)),
+            ("x", x.to_string()),
+            ("y", y.to_string()),
+            ("z", z.to_string()),
+            ("-y", ((1u32 << z) - 1 - y).to_string()),
+            ("q", tile_to_quadkey(z, x, y)),
+            ("bx", (x as i64 - center).to_string()),
+            ("by", (center - 1 - y as i64).to_string()),
+            ("key", self.api_key.clone().unwrap_or_default()),
+        ];
+        Some(render(&rule.template, &vars))
+    }
+
+    fn max_zoom(&self) -> u32 {
+        self.inner.max_zoom()
+    }
+
+    fn min_zoom(&self) -> u32 {
+        self.inner.min_zoom()
+    }
+
+    fn supported_map_types(&self) -> Vec<MapType> {
+        self.inner.supported_map_types()
+    }
+
+    fn requires_api_key(&self) -> bool {
+        self.inner.requires_api_key()
+    }
+
+    fn accepts_api_key(&self) -> bool {
+        self.inner.accepts_api_key()
+    }
+
+    fn key_apply_url(&self) -> Option<&str> {
+        self.inner.key_apply_url()
+    }
+
+    fn set_api_key(&mut self, key: &str) {
+        self.api_key = Some(key.trim().to_string()).filter(|k| !k.is_empty());
+        self.inner.set_api_key(key);
+    }
+
+    fn coord_system(&self) -> &str {
+        self.inner.coord_system()
+    }
+
+    fn coverage(&self) -> CoverageArea {
+        self.inner.coverage()
+    }
+
+    fn get_headers(&self) -> HashMap<String, String> {
+        self.inner.get_headers()
+    }
+
+    fn get_subdomain(&self, x: u32, y: u32) -> String {
+        self.inner.get_subdomain(x, y)
+    }
+
+    fn subdomains(&self) -> Vec<&str> {
+        self.inner.subdomains()
+    }
+}
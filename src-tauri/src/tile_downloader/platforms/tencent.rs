@@ -1,13 +1,13 @@
 use super::TilePlatform;
+use crate::tile_downloader::coverage::CoverageArea;
 use crate::tile_downloader::types::MapType;
 
-pub struct TencentPlatform {
-    api_key: Option<String>,
-}
+/// 腾讯地图公开瓦片。腾讯位置服务没有提供以 Key 鉴权的栅格瓦片（XYZ/WMTS）接口，因此不接受 Key
+pub struct TencentPlatform;
 
 impl TencentPlatform {
     pub fn new() -> Self {
-        Self { api_key: None }
+        Self
     }
 
     /// 腾讯地图Y坐标需要翻转
@@ -71,8 +71,12 @@ impl TilePlatform for TencentPlatform {
         false
     }
 
-    fn set_api_key(&mut self, key: &str) {
-        self.api_key = Some(key.to_string());
+    fn coord_system(&self) -> &str {
+        "GCJ02"
+    }
+
+    fn coverage(&self) -> CoverageArea {
+        CoverageArea::ChinaMainland
     }
 
     fn subdomains(&self) -> Vec<&str> {
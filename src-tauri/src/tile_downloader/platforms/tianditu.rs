@@ -29,6 +29,10 @@ impl TilePlatform for TiandituPlatform {
             MapType::Satellite => ("img", "default"),  // 影像底图
             MapType::Terrain => ("ter", "default"),    // 地形底图
             MapType::Annotation => ("cva", "default"), // 矢量注记
+            MapType::AnnotationEn => ("eva", "default"), // 矢量英文注记
+            MapType::SatelliteAnnotation => ("cia", "default"), // 影像注记
+            MapType::TerrainAnnotation => ("cta", "default"), // 地形注记
+            MapType::Boundary => ("ibo", "default"), // 全球境界
             _ => return None,
         };
 
@@ -47,13 +51,26 @@ impl TilePlatform for TiandituPlatform {
     }
 
     fn supported_map_types(&self) -> Vec<MapType> {
-        vec![MapType::Street, MapType::Satellite, MapType::Terrain, MapType::Annotation]
+        vec![
+            MapType::Street,
+            MapType::Satellite,
+            MapType::Terrain,
+            MapType::Annotation,
+            MapType::AnnotationEn,
+            MapType::SatelliteAnnotation,
+            MapType::TerrainAnnotation,
+            MapType::Boundary,
+        ]
     }
 
     fn requires_api_key(&self) -> bool {
         true
     }
 
+    fn key_apply_url(&self) -> Option<&str> {
+        Some("https://console.tianditu.gov.cn/api/key")
+    }
+
     fn set_api_key(&mut self, key: &str) {
         self.api_key = Some(key.to_string());
     }
@@ -0,0 +1,217 @@
+//! 瓦片可用率抽样探测
+//!
+//! 只请求少量抽样瓦片、不写入存储，统计各平台各层级的有图比例与平均大小，
+//! 用于创建下载任务前选择平台与层级。
+
+use super::platforms::{create_platform, TilePlatform};
+use super::types::{Bounds, MapType, TileCoord};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// 缺省抽样比例
+const DEFAULT_SAMPLE_RATIO: f64 = 0.01;
+/// 每个层级缺省最多抽样的瓦片数
+const DEFAULT_MAX_SAMPLES: u32 = 50;
+/// 探测并发请求数
+const PROBE_CONCURRENCY: usize = 8;
+
+/// 探测配置
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProbeConfig {
+    pub platforms: Vec<String>,
+    pub map_type: String,
+    pub bounds: Bounds,
+    pub zoom_levels: Vec<u32>,
+    /// 抽样比例 0.0 - 1.0，缺省 1%
+    #[serde(default)]
+    pub sample_ratio: Option<f64>,
+    /// 每个层级最多抽样数，缺省 50
+    #[serde(default)]
+    pub max_samples_per_level: Option<u32>,
+    /// 各平台的 API Key，键为平台 ID
+    #[serde(default)]
+    pub api_keys: HashMap<String, String>,
+}
+
+/// 单个平台单个层级的探测结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeResult {
+    pub platform: String,
+    pub zoom: u32,
+    /// 该层级瓦片总数
+    pub total_tiles: u64,
+    pub sampled: u32,
+    /// 返回瓦片数据的数量
+    pub available: u32,
+    /// 404/204/空响应的数量
+    pub missing: u32,
+    /// 其他 HTTP 错误或网络错误的数量
+    pub errors: u32,
+    /// 有图比例 0.0 - 1.0
+    pub availability: f64,
+    /// 有图瓦片的平均大小（字节）
+    pub avg_size_bytes: u64,
+}
+
+/// 单次请求结果
+enum ProbeOutcome {
+    Available(usize),
+    Missing,
+    Error,
+}
+
+/// 计算边界在指定层级的瓦片行列范围 (x_min, x_max, y_min, y_max)
+fn tile_range(bounds: &Bounds, z: u32) -> (u32, u32, u32, u32) {
+    let n = 2u32.pow(z);
+    let to_y = |lat: f64| {
+        ((1.0 - lat.to_radians().tan().asinh() / std::f64::consts::PI) / 2.0 * n as f64).floor()
+            as u32
+    };
+    let x_min = ((bounds.west + 180.0) / 360.0 * n as f64).floor() as u32;
+    let x_max = ((bounds.east + 180.0) / 360.0 * n as f64).floor() as u32;
+    (x_min, x_max.min(n - 1), to_y(bounds.north), to_y(bounds.south).min(n - 1))
+}
+
+/// 在层级范围内按比例等间隔抽样（至少 1 个，至多 max 个），避免高层级时展开全部瓦片
+///
+/// 返回该层级瓦片总数与抽样结果
+fn sample_tiles(bounds: &Bounds, z: u32, ratio: f64, max: u64) -> (u64, Vec<TileCoord>) {
+    let (x_min, x_max, y_min, y_max) = tile_range(bounds, z);
+    if x_max < x_min || y_max < y_min {
+        return (0, Vec::new());
+    }
+    let width = (x_max - x_min + 1) as u64;
+    let total = width * (y_max - y_min + 1) as u64;
+    let count = ((total as f64 * ratio).ceil() as u64).clamp(1, max.min(total));
+    let step = total as f64 / count as f64;
+    let tiles = (0..count)
+        .map(|i| {
+            let index = ((i as f64 + 0.5) * step) as u64;
+            TileCoord::new(z, x_min + (index % width) as u32, y_min + (index / width) as u32)
+        })
+        .collect();
+    (total, tiles)
+}
+
+async fn probe_tile(client: &reqwest::Client, platform: &dyn TilePlatform, url: &str) -> ProbeOutcome {
+    let mut request = client.get(url);
+    for (key, value) in platform.get_headers() {
+        request = request.header(key, value);
+    }
+
+    match request.send().await {
+        Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => ProbeOutcome::Missing,
+        Ok(response) if response.status() == reqwest::StatusCode::NO_CONTENT => ProbeOutcome::Missing,
+        Ok(response) if response.status().is_success() => match response.bytes().await {
+            Ok(data) if data.is_empty() => ProbeOutcome::Missing,
+            Ok(data) => ProbeOutcome::Available(data.len()),
+            Err(_) => ProbeOutcome::Error,
+        },
+        _ => ProbeOutcome::Error,
+    }
+}
+
+/// 按配置抽样探测各平台各层级的瓦片可用率
+pub async fn probe_coverage(config: ProbeConfig) -> Result<Vec<ProbeResult>, String> {
+    if config.platforms.is_empty() {
+        return Err("请至少选择一个平台".to_string());
+    }
+    let ratio = config.sample_ratio.unwrap_or(DEFAULT_SAMPLE_RATIO).clamp(0.0, 1.0);
+    let max_samples = config.max_samples_per_level.unwrap_or(DEFAULT_MAX_SAMPLES).max(1) as u64;
+    let map_type = MapType::from(config.map_type.as_str());
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+
+    let mut results = Vec::new();
+    for platform_id in &config.platforms {
+        let platform = create_platform(
+            platform_id,
+            config.api_keys.get(platform_id).map(|k| k.as_str()),
+        );
+
+        for &zoom in &config.zoom_levels {
+            let (total, tiles) = sample_tiles(&config.bounds, zoom, ratio, max_samples);
+            let urls: Vec<String> = tiles
+                .iter()
+                .filter_map(|t| platform.get_tile_url(t.z, t.x, t.y, &map_type))
+                .collect();
+
+            let mut outcomes = Vec::with_capacity(urls.len());
+            for chunk in urls.chunks(PROBE_CONCURRENCY) {
+                let requests = chunk
+                    .iter()
+                    .map(|url| probe_tile(&client, platform.as_ref(), url));
+                outcomes.extend(join_all(requests).await);
+            }
+
+            let mut result = ProbeResult {
+                platform: platform_id.clone(),
+                zoom,
+                total_tiles: total,
+                sampled: outcomes.len() as u32,
+                available: 0,
+                missing: 0,
+                errors: 0,
+                availability: 0.0,
+                avg_size_bytes: 0,
+            };
+            let mut total_size = 0u64;
+            for outcome in outcomes {
+                match outcome {
+                    ProbeOutcome::Available(size) => {
+                        result.available += 1;
+                        total_size += size as u64;
+                    }
+                    ProbeOutcome::Missing => result.missing += 1,
+                    ProbeOutcome::Error => result.errors += 1,
+                }
+            }
+            if result.sampled > 0 {
+                result.availability = result.available as f64 / result.sampled as f64;
+            }
+            if result.available > 0 {
+                result.avg_size_bytes = total_size / result.available as u64;
+            }
+
+            log::info!(
+                "探测 {} z{}: 抽样 {}，有图 {}，无图 {}，错误 {}",
+                platform_id,
+                zoom,
+                result.sampled,
+                result.available,
+                result.missing,
+                result.errors
+            );
+            results.push(result);
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tile_downloader::downloader::calculate_tiles;
+
+    #[test]
+    fn samples_spread_within_range() {
+        let bounds = Bounds {
+            north: 32.1,
+            south: 31.9,
+            east: 118.9,
+            west: 118.6,
+        };
+        let all = calculate_tiles(&bounds, &[14]);
+        let (total, tiles) = sample_tiles(&bounds, 14, 0.5, 10);
+        assert_eq!(total, all.len() as u64);
+        assert_eq!(tiles.len(), 10);
+        assert!(tiles.iter().all(|t| all.contains(t)));
+        assert_ne!(tiles.first(), tiles.last());
+    }
+}
@@ -0,0 +1,223 @@
+//! 地图截图导出：按范围拉取瓦片拼接裁剪为单张图片
+
+use super::imaging::{
+    decode_image, encode_jpeg, encode_png, lonlat_to_pixel, RgbaImage, TILE_SIZE,
+};
+use super::platforms::create_platform;
+use super::types::{Bounds, HeaderOptions, MapType};
+use crate::error::{AppError, CmdResult, ErrorCode};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 截图最大边长（像素），避免内存占用过大
+const MAX_SNAPSHOT_SIZE: u32 = 8192;
+
+/// 并发拉取瓦片数
+const FETCH_CONCURRENCY: usize = 8;
+
+/// JPEG 输出质量
+const JPEG_QUALITY: u8 = 90;
+
+/// 截图上的标注点
+#[derive(Debug, Clone, Deserialize)]
+pub struct SnapshotMarker {
+    pub lon: f64,
+    pub lat: f64,
+    /// 颜色，格式 #rrggbb，缺省为红色
+    pub color: Option<String>,
+}
+
+/// 截图请求
+#[derive(Debug, Deserialize)]
+pub struct SnapshotRequest {
+    pub bounds: Bounds,
+    pub zoom: u32,
+    pub platform: String,
+    pub map_type: String,
+    pub api_key: Option<String>,
+    pub output_path: String,
+    /// 输出格式 png 或 jpg，缺省为 png
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub markers: Vec<SnapshotMarker>,
+    #[serde(flatten)]
+    pub headers: HeaderOptions,
+}
+
+/// 截图结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotResult {
+    pub width: u32,
+    pub height: u32,
+    pub tiles_total: usize,
+    pub tiles_failed: usize,
+}
+
+/// 解析 #rrggbb 颜色
+fn parse_color(color: Option<&str>) -> [u8; 4] {
+    let default = [229, 57, 53, 255];
+    let hex = match color.map(|c| c.trim_start_matches('#')) {
+        Some(hex) if hex.len() == 6 => hex,
+        _ => return default,
+    };
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match (channel(0), channel(2), channel(4)) {
+        (Some(r), Some(g), Some(b)) => [r, g, b, 255],
+        _ => default,
+    }
+}
+
+/// 导出指定范围与层级的地图截图
+#[tauri::command]
+pub async fn export_map_snapshot(request: SnapshotRequest) -> CmdResult<SnapshotResult> {
+    let format = request.format.as_deref().unwrap_or("png").to_lowercase();
+    let jpeg = match format.as_str() {
+        "png" => false,
+        "jpg" | "jpeg" => true,
+        _ => return Err(AppError::invalid("截图仅支持导出 PNG 或 JPEG 格式")),
+    };
+
+    if !request.bounds.is_valid() {
+        return Err(AppError::invalid("无效的区域边界"));
+    }
+
+    let platform = create_platform(&request.platform, request.api_key.as_deref());
+    if request.zoom < platform.min_zoom() || request.zoom > platform.max_zoom() {
+        return Err(AppError::invalid(format!(
+            "层级超出平台支持范围 ({}-{})",
+            platform.min_zoom(),
+            platform.max_zoom()
+        )));
+    }
+    let map_type = MapType::from(request.map_type.as_str());
+
+    // 计算范围在全局像素坐标中的位置
+    let bounds = &request.bounds;
+    let (left, top) = lonlat_to_pixel(bounds.west, bounds.north, request.zoom);
+    let (right, bottom) = lonlat_to_pixel(bounds.east, bounds.south, request.zoom);
+    let (left, top) = (left.floor() as u64, top.floor() as u64);
+    let width = (right.ceil() as u64).saturating_sub(left).max(1);
+    let height = (bottom.ceil() as u64).saturating_sub(top).max(1);
+
+    if width > MAX_SNAPSHOT_SIZE as u64 || height > MAX_SNAPSHOT_SIZE as u64 {
+        return Err(AppError::invalid(format!(
+            "截图尺寸 {}x{} 超过上限 {}，请缩小范围或降低层级",
+            width, height, MAX_SNAPSHOT_SIZE
+        )));
+    }
+
+    // 覆盖范围的瓦片
+    let tile = TILE_SIZE as u64;
+    let max_index = (1u64 << request.zoom) - 1;
+    let (tx_min, ty_min) = (left / tile, top / tile);
+    let tx_max = ((left + width - 1) / tile).min(max_index);
+    let ty_max = ((top + height - 1) / tile).min(max_index);
+
+    let mut jobs = Vec::new();
+    for tx in tx_min..=tx_max {
+        for ty in ty_min..=ty_max {
+            let url = platform.get_tile_url(request.zoom, tx as u32, ty as u32, &map_type);
+            let mut headers = platform.get_headers();
+            request.headers.apply(&mut headers);
+            jobs.push((tx, ty, url, headers));
+        }
+    }
+    let tiles_total = jobs.len();
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+
+    let results: Vec<_> = stream::iter(jobs)
+        .map(|(tx, ty, url, headers)| {
+            let client = client.clone();
+            async move {
+                let url = url.ok_or("此平台不支持该地图类型".to_string())?;
+                let mut req = client.get(&url);
+                for (key, value) in &headers {
+                    req = req.header(key, value);
+                }
+                let response = req.send().await.map_err(|e| format!("请求失败: {}", e))?;
+                if !response.status().is_success() {
+                    return Err(format!("HTTP {}", response.status()));
+                }
+                let data = response
+                    .bytes()
+                    .await
+                    .map_err(|e| format!("读取响应失败: {}", e))?;
+                Ok((tx, ty, decode_image(&data)?))
+            }
+        })
+        .buffer_unordered(FETCH_CONCURRENCY)
+        .collect()
+        .await;
+
+    // 拼接到画布
+    let canvas_width = ((tx_max - tx_min + 1) * tile) as u32;
+    let canvas_height = ((ty_max - ty_min + 1) * tile) as u32;
+    let mut canvas = RgbaImage::filled(canvas_width, canvas_height, [240, 240, 240, 255]);
+    let mut tiles_failed = 0;
+
+    for result in results {
+        match result {
+            Ok((tx, ty, image)) => {
+                canvas.blit(
+                    &image,
+                    ((tx - tx_min) * tile) as i64,
+                    ((ty - ty_min) * tile) as i64,
+                );
+            }
+            Err(e) => {
+                log::warn!("截图瓦片获取失败: {}", e);
+                tiles_failed += 1;
+            }
+        }
+    }
+
+    if tiles_failed == tiles_total {
+        return Err(AppError::new(
+            ErrorCode::Network,
+            "所有瓦片均获取失败".to_string(),
+        ));
+    }
+
+    // 裁剪到请求范围
+    let offset_x = (left - tx_min * tile) as u32;
+    let offset_y = (top - ty_min * tile) as u32;
+    let mut image = canvas.crop(offset_x, offset_y, width as u32, height as u32);
+
+    // 叠加 POI 标注
+    for marker in &request.markers {
+        let (px, py) = lonlat_to_pixel(marker.lon, marker.lat, request.zoom);
+        let x = px as i64 - left as i64;
+        let y = py as i64 - top as i64;
+        image.fill_circle(x, y, 6, [255, 255, 255, 255]);
+        image.fill_circle(x, y, 4, parse_color(marker.color.as_deref()));
+    }
+
+    let data = if jpeg {
+        encode_jpeg(&image, JPEG_QUALITY)?
+    } else {
+        encode_png(&image)?
+    };
+    std::fs::write(&request.output_path, data).map_err(|e| format!("保存截图失败: {}", e))?;
+
+    log::info!(
+        "导出地图截图: {} ({}x{}), 瓦片 {} 个, 失败 {} 个",
+        request.output_path,
+        image.width,
+        image.height,
+        tiles_total,
+        tiles_failed
+    );
+
+    Ok(SnapshotResult {
+        width: image.width,
+        height: image.height,
+        tiles_total,
+        tiles_failed,
+    })
+}
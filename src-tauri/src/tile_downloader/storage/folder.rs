@@ -1,4 +1,4 @@
-use super::TileStorage;
+use super::{strip_tile_extension, TileFormat, TileStorage};
 use crate::tile_downloader::types::{Bounds, TileCoord};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -13,6 +13,94 @@ impl FolderStorage {
             base_path: PathBuf::new(),
         }
     }
+
+    /// 读取 z/x/y.{png,jpg,webp,gif} 瓦片文件
+    pub fn read_tile(base_path: &Path, coord: &TileCoord) -> Result<Option<Vec<u8>>, String> {
+        let tile_dir = base_path.join(coord.z.to_string()).join(coord.x.to_string());
+        let Some(tile_path) = TileFormat::ALL
+            .iter()
+            .map(|format| tile_dir.join(format!("{}.{}", coord.y, format.extension())))
+            .find(|path| path.exists())
+        else {
+            return Ok(None);
+        };
+
+        fs::read(&tile_path)
+            .map(Some)
+            .map_err(|e| format!("读取瓦片失败: {}", e))
+    }
+
+    /// 遍历 z/x/y.{扩展名} 目录结构中的全部瓦片
+    pub fn for_each_tile(
+        base_path: &Path,
+        f: &mut dyn FnMut(TileCoord, Vec<u8>) -> Result<(), String>,
+    ) -> Result<(), String> {
+        for (z, z_dir) in numbered_entries(base_path, false)? {
+            for (x, x_dir) in numbered_entries(&z_dir, false)? {
+                for (y, tile_path) in numbered_entries(&x_dir, true)? {
+                    let data = fs::read(&tile_path)
+                        .map_err(|e| format!("读取瓦片失败: {}", e))?;
+                    f(TileCoord::new(z, x, y), data)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 目录是否只包含 z/x/y.{扩展名} 瓦片树与 sidecars 中的文件，覆盖输出前据此确认不会误删其他文件
+pub fn is_tile_tree(base_path: &Path, sidecars: &[&str]) -> Result<bool, String> {
+    let read = |dir: &Path| -> Result<Vec<fs::DirEntry>, String> {
+        fs::read_dir(dir)
+            .map_err(|e| format!("读取目录失败: {}", e))?
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("读取目录失败: {}", e))
+    };
+    let numbered_dir = |entry: &fs::DirEntry| {
+        entry.path().is_dir() && entry.file_name().to_str().is_some_and(|n| n.parse::<u32>().is_ok())
+    };
+    for z in read(base_path)? {
+        let name = z.file_name();
+        if z.path().is_file() && sidecars.iter().any(|s| name == *s) {
+            continue;
+        }
+        if !numbered_dir(&z) {
+            return Ok(false);
+        }
+        for x in read(&z.path())? {
+            if !numbered_dir(&x) {
+                return Ok(false);
+            }
+            for y in read(&x.path())? {
+                let is_tile = y.path().is_file()
+                    && y.file_name()
+                        .to_str()
+                        .and_then(strip_tile_extension)
+                        .is_some_and(|stem| stem.parse::<u32>().is_ok());
+                if !is_tile {
+                    return Ok(false);
+                }
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// 列出目录下以数字命名的条目，files 为 true 时只列图片文件（去掉扩展名后为数字），
+/// 否则只列子目录，忽略清单等其他文件
+fn numbered_entries(dir: &Path, files: bool) -> Result<Vec<(u32, PathBuf)>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("读取目录失败: {}", e))?;
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir() != files)
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let name = name.to_str()?;
+            let stem = if files { strip_tile_extension(name)? } else { name };
+            let number = stem.parse().ok()?;
+            Some((number, entry.path()))
+        })
+        .collect())
 }
 
 impl TileStorage for FolderStorage {
@@ -32,8 +120,9 @@ impl TileStorage for FolderStorage {
         fs::create_dir_all(&tile_dir)
             .map_err(|e| format!("创建瓦片目录失败: {}", e))?;
 
-        // 保存瓦片文件 y.png
-        let tile_path = tile_dir.join(format!("{}.png", coord.y));
+        // 保存瓦片文件 y.{扩展名}，扩展名按实际图片格式
+        let extension = TileFormat::detect(data).extension();
+        let tile_path = tile_dir.join(format!("{}.{}", coord.y, extension));
         fs::write(&tile_path, data)
             .map_err(|e| format!("保存瓦片失败: {}", e))?;
 
@@ -49,3 +138,27 @@ impl TileStorage for FolderStorage {
         "folder"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_tile_tree() {
+        let dir = std::env::temp_dir().join(format!("tile_tree_{}", std::process::id()));
+        fs::create_dir_all(dir.join("12/3421")).unwrap();
+        fs::write(dir.join("12/3421/1564.png"), b"").unwrap();
+        fs::write(dir.join("manifest.json"), b"{}").unwrap();
+        assert!(is_tile_tree(&dir, &["manifest.json"]).unwrap());
+        assert!(!is_tile_tree(&dir, &[]).unwrap());
+
+        // 混有其他文件的目录不是瓦片输出
+        fs::write(dir.join("12/3421/notes.txt"), b"").unwrap();
+        assert!(!is_tile_tree(&dir, &["manifest.json"]).unwrap());
+        fs::remove_file(dir.join("12/3421/notes.txt")).unwrap();
+        fs::create_dir_all(dir.join("照片")).unwrap();
+        assert!(!is_tile_tree(&dir, &["manifest.json"]).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
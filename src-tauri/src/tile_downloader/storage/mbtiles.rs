@@ -1,7 +1,8 @@
-use super::TileStorage;
+use super::{TileFormat, TileStorage};
 use crate::tile_downloader::types::{Bounds, TileCoord};
 use parking_lot::Mutex;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OpenFlags};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 pub struct MbtilesStorage {
@@ -9,6 +10,8 @@ pub struct MbtilesStorage {
     conn: Mutex<Option<Connection>>,
     bounds: Option<Bounds>,
     zoom_levels: Vec<u32>,
+    /// 本次写入的各格式瓦片数，完成时据此写入 format 元数据
+    format_counts: HashMap<TileFormat, u64>,
 }
 
 impl MbtilesStorage {
@@ -18,6 +21,7 @@ impl MbtilesStorage {
             conn: Mutex::new(None),
             bounds: None,
             zoom_levels: Vec::new(),
+            format_counts: HashMap::new(),
         }
     }
 
@@ -25,6 +29,66 @@ impl MbtilesStorage {
     fn flip_y(&self, z: u32, y: u32) -> u32 {
         (1u32 << z) - 1 - y
     }
+
+    /// 从 MBTiles 文件读取瓦片（输入为 XYZ 坐标），坐标超出范围时返回 None
+    pub fn read_tile(db_path: &Path, coord: &TileCoord) -> Result<Option<Vec<u8>>, String> {
+        if !coord.is_valid() {
+            return Ok(None);
+        }
+        let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| format!("打开 MBTiles 失败: {}", e))?;
+
+        let tms_y = (1u32 << coord.z) - 1 - coord.y;
+        let result = conn.query_row(
+            "SELECT tile_data FROM tiles WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+            params![coord.z, coord.x, tms_y],
+            |row| row.get::<_, Vec<u8>>(0),
+        );
+
+        match result {
+            Ok(data) => Ok(Some(data)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(format!("读取瓦片失败: {}", e)),
+        }
+    }
+
+    /// 遍历 MBTiles 中的全部瓦片（回调坐标为 XYZ），跳过坐标超出范围的行
+    pub fn for_each_tile(
+        db_path: &Path,
+        f: &mut dyn FnMut(TileCoord, Vec<u8>) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| format!("打开 MBTiles 失败: {}", e))?;
+        let mut stmt = conn
+            .prepare("SELECT zoom_level, tile_column, tile_row, tile_data FROM tiles")
+            .map_err(|e| format!("查询失败: {}", e))?;
+        let mut rows = stmt.query([]).map_err(|e| format!("读取瓦片失败: {}", e))?;
+        while let Some(row) = rows.next().map_err(|e| format!("读取行失败: {}", e))? {
+            let read = || -> rusqlite::Result<_> {
+                Ok((
+                    row.get::<_, u32>(0)?,
+                    row.get::<_, u32>(1)?,
+                    row.get::<_, u32>(2)?,
+                    row.get::<_, Vec<u8>>(3)?,
+                ))
+            };
+            let (z, x, tms_y, data) = read().map_err(|e| format!("读取行失败: {}", e))?;
+            match tms_to_xyz(z, x, tms_y) {
+                Some(coord) => f(coord, data)?,
+                None => log::warn!("跳过坐标超出范围的瓦片: {}/{}/{}", z, x, tms_y),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// TMS 行号转 XYZ 坐标，坐标超出范围时返回 None
+pub fn tms_to_xyz(z: u32, x: u32, tms_y: u32) -> Option<TileCoord> {
+    if z > 30 {
+        return None;
+    }
+    let y = ((1u32 << z) - 1).checked_sub(tms_y)?;
+    Some(TileCoord::new(z, x, y)).filter(TileCoord::is_valid)
 }
 
 impl TileStorage for MbtilesStorage {
@@ -77,7 +141,6 @@ impl TileStorage for MbtilesStorage {
             ("type", "baselayer"),
             ("version", "1.0"),
             ("description", "Downloaded tiles"),
-            ("format", "png"),
         ];
 
         for (name, value) in metadata {
@@ -88,6 +151,13 @@ impl TileStorage for MbtilesStorage {
             .map_err(|e| format!("插入元数据失败: {}", e))?;
         }
 
+        // 续传时保留已写入的格式，完成时按实际瓦片格式更新
+        conn.execute(
+            "INSERT OR IGNORE INTO metadata (name, value) VALUES ('format', 'png')",
+            [],
+        )
+        .map_err(|e| format!("插入元数据失败: {}", e))?;
+
         conn.execute(
             "INSERT OR REPLACE INTO metadata (name, value) VALUES ('bounds', ?1)",
             params![bounds_str],
@@ -125,11 +195,34 @@ impl TileStorage for MbtilesStorage {
         )
         .map_err(|e| format!("保存瓦片失败: {}", e))?;
 
+        *self.format_counts.entry(TileFormat::detect(data)).or_default() += 1;
+        Ok(())
+    }
+
+    fn set_metadata(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let conn_guard = self.conn.lock();
+        let conn = conn_guard.as_ref().ok_or("数据库未初始化")?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO metadata (name, value) VALUES (?1, ?2)",
+            params![name, value],
+        )
+        .map_err(|e| format!("插入元数据失败: {}", e))?;
+
         Ok(())
     }
 
     fn finalize(&mut self) -> Result<(), String> {
         if let Some(conn) = self.conn.lock().take() {
+            // 占位瓦片固定为 PNG，以数量最多的格式作为整个文件的格式
+            if let Some((format, _)) = self.format_counts.iter().max_by_key(|(_, count)| **count) {
+                conn.execute(
+                    "INSERT OR REPLACE INTO metadata (name, value) VALUES ('format', ?1)",
+                    params![format.extension()],
+                )
+                .map_err(|e| format!("插入元数据失败: {}", e))?;
+            }
+
             // 优化数据库
             conn.execute("VACUUM", [])
                 .map_err(|e| format!("优化数据库失败: {}", e))?;
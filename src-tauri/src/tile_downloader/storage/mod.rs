@@ -1,9 +1,15 @@
 mod folder;
 mod mbtiles;
+mod oruxmaps;
+mod sqlitedb;
+mod tile_format;
 mod zip_storage;
 
-pub use folder::FolderStorage;
-pub use mbtiles::MbtilesStorage;
+pub use folder::{is_tile_tree, FolderStorage};
+pub use mbtiles::{tms_to_xyz, MbtilesStorage};
+pub use oruxmaps::OruxMapsStorage;
+pub use sqlitedb::SqlitedbStorage;
+pub use tile_format::{strip_tile_extension, TileFormat};
 pub use zip_storage::ZipStorage;
 
 use super::types::{Bounds, TileCoord};
@@ -17,6 +23,11 @@ pub trait TileStorage: Send + Sync {
     /// 保存瓦片
     fn save_tile(&mut self, coord: &TileCoord, data: &[u8]) -> Result<(), String>;
 
+    /// 写入元数据（如坐标系），不支持元数据的存储忽略
+    fn set_metadata(&mut self, _name: &str, _value: &str) -> Result<(), String> {
+        Ok(())
+    }
+
     /// 完成存储（清理、压缩等）
     fn finalize(&mut self) -> Result<(), String>;
 
@@ -32,3 +43,33 @@ pub fn create_storage(format: &str) -> Box<dyn TileStorage> {
         _ => Box::new(FolderStorage::new()),
     }
 }
+
+/// 从已下载的输出中读取单个瓦片，瓦片不存在时返回 None
+pub fn read_tile(format: &str, output_path: &Path, coord: &TileCoord) -> Result<Option<Vec<u8>>, String> {
+    if !output_path.exists() {
+        return Err("输出文件不存在".to_string());
+    }
+
+    match format.to_lowercase().as_str() {
+        "mbtiles" => MbtilesStorage::read_tile(output_path, coord),
+        "zip" => ZipStorage::read_tile(output_path, coord),
+        _ => FolderStorage::read_tile(output_path, coord),
+    }
+}
+
+/// 逐个读取已下载输出中的全部瓦片
+pub fn for_each_tile(
+    format: &str,
+    output_path: &Path,
+    f: &mut dyn FnMut(TileCoord, Vec<u8>) -> Result<(), String>,
+) -> Result<(), String> {
+    if !output_path.exists() {
+        return Err("输出文件不存在".to_string());
+    }
+
+    match format.to_lowercase().as_str() {
+        "mbtiles" => MbtilesStorage::for_each_tile(output_path, f),
+        "zip" => ZipStorage::for_each_tile(output_path, f),
+        _ => FolderStorage::for_each_tile(output_path, f),
+    }
+}
@@ -0,0 +1,226 @@
+//! OruxMaps 离线地图格式
+//!
+//! 输出为以地图名命名的目录，包含 OruxMapsImages.db 与 <地图名>.otrk2.xml。
+//! 每个层级单独标定，瓦片行列号相对该层级范围左上角的瓦片计数。
+
+use super::TileStorage;
+use crate::tile_downloader::imaging::{lonlat_to_pixel, pixel_to_lonlat, TILE_SIZE};
+use crate::tile_downloader::types::{Bounds, TileCoord};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// 瓦片数据库文件名
+const IMAGES_DB: &str = "OruxMapsImages.db";
+
+/// 单个层级的瓦片行列范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LevelExtent {
+    min_x: u32,
+    min_y: u32,
+    max_x: u32,
+    max_y: u32,
+}
+
+impl LevelExtent {
+    /// 覆盖边界的瓦片范围
+    fn covering(bounds: &Bounds, zoom: u32) -> Self {
+        let last = (1u32 << zoom) - 1;
+        let tile = |v: f64| ((v / TILE_SIZE as f64).floor().max(0.0) as u32).min(last);
+        let (west, north) = lonlat_to_pixel(bounds.west, bounds.north, zoom);
+        let (east, south) = lonlat_to_pixel(bounds.east, bounds.south, zoom);
+        Self {
+            min_x: tile(west),
+            min_y: tile(north),
+            max_x: tile(east),
+            max_y: tile(south),
+        }
+    }
+
+    fn columns(&self) -> u32 {
+        self.max_x - self.min_x + 1
+    }
+
+    fn rows(&self) -> u32 {
+        self.max_y - self.min_y + 1
+    }
+
+    /// 瓦片在该层级内的相对行列号，超出范围时返回 None
+    fn relative(&self, coord: &TileCoord) -> Option<(u32, u32)> {
+        let x = coord
+            .x
+            .checked_sub(self.min_x)
+            .filter(|x| *x < self.columns())?;
+        let y = coord
+            .y
+            .checked_sub(self.min_y)
+            .filter(|y| *y < self.rows())?;
+        Some((x, y))
+    }
+}
+
+pub struct OruxMapsStorage {
+    map_dir: PathBuf,
+    conn: Mutex<Option<Connection>>,
+    levels: BTreeMap<u32, LevelExtent>,
+}
+
+impl OruxMapsStorage {
+    pub fn new() -> Self {
+        Self {
+            map_dir: PathBuf::new(),
+            conn: Mutex::new(None),
+            levels: BTreeMap::new(),
+        }
+    }
+
+    /// 地图名取输出目录名
+    fn map_name(&self) -> String {
+        self.map_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "map".to_string())
+    }
+
+    /// 生成 otrk2.xml 标定文件
+    fn calibration_xml(&self) -> String {
+        let name = self.map_name();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(
+            "<OruxTracker xmlns=\"http://oruxtracker.com/app/res/calibration\" versionCode=\"3.0\">\n",
+        );
+        xml.push_str("<MapCalibration layers=\"true\" layerLevel=\"0\">\n");
+        xml.push_str(&format!("<MapName><![CDATA[{}]]></MapName>\n", name));
+        for (&zoom, extent) in &self.levels {
+            let (min_lon, max_lat) = pixel_to_lonlat(
+                (extent.min_x * TILE_SIZE) as f64,
+                (extent.min_y * TILE_SIZE) as f64,
+                zoom,
+            );
+            let (max_lon, min_lat) = pixel_to_lonlat(
+                ((extent.max_x + 1) * TILE_SIZE) as f64,
+                ((extent.max_y + 1) * TILE_SIZE) as f64,
+                zoom,
+            );
+            xml.push_str(&format!(
+                "<OruxTracker xmlns=\"http://oruxtracker.com/app/res/calibration\" versionCode=\"2.1\">\n\
+                 <MapCalibration layers=\"false\" layerLevel=\"{z}\">\n\
+                 <MapName><![CDATA[{name} {z}]]></MapName>\n\
+                 <MapChunks xMax=\"{cols}\" yMax=\"{rows}\" datum=\"WGS84\" projection=\"Mercator\" \
+                 img_height=\"{size}\" img_width=\"{size}\" file_name=\"{name}\" />\n\
+                 <MapDimensions height=\"{height}\" width=\"{width}\" />\n\
+                 <MapBounds minLat=\"{min_lat}\" maxLat=\"{max_lat}\" minLon=\"{min_lon}\" maxLon=\"{max_lon}\" />\n\
+                 <CalibrationPoints>\n\
+                 <CalibrationPoint corner=\"TL\" lon=\"{min_lon}\" lat=\"{max_lat}\" />\n\
+                 <CalibrationPoint corner=\"BR\" lon=\"{max_lon}\" lat=\"{min_lat}\" />\n\
+                 <CalibrationPoint corner=\"TR\" lon=\"{max_lon}\" lat=\"{max_lat}\" />\n\
+                 <CalibrationPoint corner=\"BL\" lon=\"{min_lon}\" lat=\"{min_lat}\" />\n\
+                 </CalibrationPoints>\n</MapCalibration>\n</OruxTracker>\n",
+                z = zoom,
+                name = name,
+                cols = extent.columns(),
+                rows = extent.rows(),
+                size = TILE_SIZE,
+                height = extent.rows() * TILE_SIZE,
+                width = extent.columns() * TILE_SIZE,
+                min_lat = min_lat,
+                max_lat = max_lat,
+                min_lon = min_lon,
+                max_lon = max_lon,
+            ));
+        }
+        xml.push_str("</MapCalibration>\n</OruxTracker>\n");
+        xml
+    }
+}
+
+impl TileStorage for OruxMapsStorage {
+    fn init(
+        &mut self,
+        output_path: &Path,
+        bounds: &Bounds,
+        zoom_levels: &[u32],
+    ) -> Result<(), String> {
+        std::fs::create_dir_all(output_path).map_err(|e| format!("创建目录失败: {}", e))?;
+        self.map_dir = output_path.to_path_buf();
+        self.levels = zoom_levels
+            .iter()
+            .map(|&zoom| (zoom, LevelExtent::covering(bounds, zoom)))
+            .collect();
+
+        let conn = Connection::open(self.map_dir.join(IMAGES_DB))
+            .map_err(|e| format!("创建 OruxMaps 数据库失败: {}", e))?;
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS tiles (
+                x INT,
+                y INT,
+                z INT,
+                image BLOB,
+                PRIMARY KEY (x, y, z)
+            );
+            CREATE TABLE IF NOT EXISTS android_metadata (locale TEXT);
+            DELETE FROM android_metadata;
+            INSERT INTO android_metadata (locale) VALUES ('zh_CN');
+            BEGIN;
+            "#,
+        )
+        .map_err(|e| format!("创建表结构失败: {}", e))?;
+        *self.conn.lock() = Some(conn);
+        Ok(())
+    }
+
+    fn save_tile(&mut self, coord: &TileCoord, data: &[u8]) -> Result<(), String> {
+        let (x, y) = self
+            .levels
+            .get(&coord.z)
+            .and_then(|extent| extent.relative(coord))
+            .ok_or_else(|| format!("瓦片 {}/{}/{} 超出地图范围", coord.z, coord.x, coord.y))?;
+        let conn_guard = self.conn.lock();
+        let conn = conn_guard.as_ref().ok_or("数据库未初始化")?;
+        conn.execute(
+            "INSERT OR REPLACE INTO tiles (x, y, z, image) VALUES (?1, ?2, ?3, ?4)",
+            params![x, y, coord.z, data],
+        )
+        .map_err(|e| format!("保存瓦片失败: {}", e))?;
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<(), String> {
+        if let Some(conn) = self.conn.lock().take() {
+            conn.execute_batch("COMMIT")
+                .map_err(|e| format!("提交瓦片失败: {}", e))?;
+        }
+        let xml_path = self.map_dir.join(format!("{}.otrk2.xml", self.map_name()));
+        std::fs::write(&xml_path, self.calibration_xml())
+            .map_err(|e| format!("写入标定文件失败: {}", e))
+    }
+
+    fn storage_type(&self) -> &str {
+        "oruxmaps"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_extent() {
+        let bounds = Bounds::new(31.5, 30.7, 122.0, 121.0);
+        let extent = LevelExtent::covering(&bounds, 10);
+        assert_eq!(
+            extent,
+            LevelExtent {
+                min_x: 856,
+                min_y: 417,
+                max_x: 859,
+                max_y: 420,
+            }
+        );
+        assert_eq!(extent.relative(&TileCoord::new(10, 857, 418)), Some((1, 1)));
+        assert_eq!(extent.relative(&TileCoord::new(10, 860, 418)), None);
+        assert_eq!(extent.relative(&TileCoord::new(10, 855, 418)), None);
+    }
+}
@@ -0,0 +1,100 @@
+//! RMaps sqlitedb 格式，Locus、两步路等移动端 App 可直接加载
+//!
+//! 采用 BigPlanet 层级编号：z 存储为 17 - 层级，info 表中的 minzoom/maxzoom 同样取反。
+
+use super::TileStorage;
+use crate::tile_downloader::types::{Bounds, TileCoord};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+/// BigPlanet 层级编号的基准
+const ZOOM_BASE: i32 = 17;
+
+pub struct SqlitedbStorage {
+    conn: Mutex<Option<Connection>>,
+}
+
+impl SqlitedbStorage {
+    pub fn new() -> Self {
+        Self {
+            conn: Mutex::new(None),
+        }
+    }
+}
+
+/// 层级转 BigPlanet 编号
+fn inverted_zoom(zoom: u32) -> i32 {
+    ZOOM_BASE - zoom as i32
+}
+
+impl TileStorage for SqlitedbStorage {
+    fn init(
+        &mut self,
+        output_path: &Path,
+        _bounds: &Bounds,
+        zoom_levels: &[u32],
+    ) -> Result<(), String> {
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+
+        let conn = Connection::open(output_path)
+            .map_err(|e| format!("创建 sqlitedb 数据库失败: {}", e))?;
+        conn.execute_batch(
+            r#"
+            CREATE TABLE IF NOT EXISTS tiles (
+                x INT,
+                y INT,
+                z INT,
+                s INT,
+                image BLOB,
+                PRIMARY KEY (x, y, z, s)
+            );
+            CREATE TABLE IF NOT EXISTS info (maxzoom INT, minzoom INT);
+            CREATE TABLE IF NOT EXISTS android_metadata (locale TEXT);
+            DELETE FROM info;
+            DELETE FROM android_metadata;
+            INSERT INTO android_metadata (locale) VALUES ('zh_CN');
+            "#,
+        )
+        .map_err(|e| format!("创建表结构失败: {}", e))?;
+
+        let min_zoom = zoom_levels.iter().min().copied().unwrap_or(0);
+        let max_zoom = zoom_levels.iter().max().copied().unwrap_or(18);
+        conn.execute(
+            "INSERT INTO info (maxzoom, minzoom) VALUES (?1, ?2)",
+            params![inverted_zoom(min_zoom), inverted_zoom(max_zoom)],
+        )
+        .map_err(|e| format!("写入层级信息失败: {}", e))?;
+
+        // 批量写入放在一个事务中，finalize 时提交
+        conn.execute_batch("BEGIN")
+            .map_err(|e| format!("开始事务失败: {}", e))?;
+        *self.conn.lock() = Some(conn);
+        Ok(())
+    }
+
+    fn save_tile(&mut self, coord: &TileCoord, data: &[u8]) -> Result<(), String> {
+        let conn_guard = self.conn.lock();
+        let conn = conn_guard.as_ref().ok_or("数据库未初始化")?;
+        conn.execute(
+            "INSERT OR REPLACE INTO tiles (x, y, z, s, image) VALUES (?1, ?2, ?3, 0, ?4)",
+            params![coord.x, coord.y, inverted_zoom(coord.z), data],
+        )
+        .map_err(|e| format!("保存瓦片失败: {}", e))?;
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<(), String> {
+        if let Some(conn) = self.conn.lock().take() {
+            conn.execute_batch("COMMIT")
+                .map_err(|e| format!("提交瓦片失败: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn storage_type(&self) -> &str {
+        "sqlitedb"
+    }
+}
@@ -0,0 +1,96 @@
+//! 瓦片图片格式识别
+//!
+//! 各平台返回的瓦片格式不同（谷歌卫星、Bing 为 JPEG，部分平台为 WebP），
+//! 按文件头魔数识别格式，决定文件扩展名与 MBTiles 的 format 元数据。
+
+/// 瓦片图片格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TileFormat {
+    Png,
+    Jpeg,
+    Webp,
+    Gif,
+}
+
+impl TileFormat {
+    /// 全部格式，读取时按此顺序尝试扩展名
+    pub const ALL: [TileFormat; 4] = [
+        TileFormat::Png,
+        TileFormat::Jpeg,
+        TileFormat::Webp,
+        TileFormat::Gif,
+    ];
+
+    /// 按魔数识别格式，无法识别时按 PNG 处理
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            TileFormat::Jpeg
+        } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+            TileFormat::Webp
+        } else if data.starts_with(b"GIF8") {
+            TileFormat::Gif
+        } else {
+            TileFormat::Png
+        }
+    }
+
+    /// 文件扩展名，同时也是 MBTiles 规范中的 format 取值
+    pub fn extension(&self) -> &'static str {
+        match self {
+            TileFormat::Png => "png",
+            TileFormat::Jpeg => "jpg",
+            TileFormat::Webp => "webp",
+            TileFormat::Gif => "gif",
+        }
+    }
+
+    /// 由扩展名识别格式
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "png" => Some(TileFormat::Png),
+            "jpg" | "jpeg" => Some(TileFormat::Jpeg),
+            "webp" => Some(TileFormat::Webp),
+            "gif" => Some(TileFormat::Gif),
+            _ => None,
+        }
+    }
+}
+
+/// 去掉瓦片文件名中的图片扩展名，如 `12.jpg` 返回 `12`，非图片扩展名返回 None
+pub fn strip_tile_extension(name: &str) -> Option<&str> {
+    let (stem, ext) = name.rsplit_once('.')?;
+    TileFormat::from_extension(ext).map(|_| stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            TileFormat::detect(&[0x89, b'P', b'N', b'G', 0x0D]),
+            TileFormat::Png
+        );
+        assert_eq!(
+            TileFormat::detect(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            TileFormat::Jpeg
+        );
+        assert_eq!(
+            TileFormat::detect(b"RIFF\x10\0\0\0WEBPVP8 "),
+            TileFormat::Webp
+        );
+        assert_eq!(TileFormat::detect(b"GIF89a"), TileFormat::Gif);
+        assert_eq!(TileFormat::detect(b""), TileFormat::Png);
+        assert_eq!(TileFormat::detect(b"RIFF"), TileFormat::Png);
+    }
+
+    #[test]
+    fn test_strip_tile_extension() {
+        assert_eq!(strip_tile_extension("12.png"), Some("12"));
+        assert_eq!(strip_tile_extension("3/5/12.JPG"), Some("3/5/12"));
+        assert_eq!(strip_tile_extension("12.webp"), Some("12"));
+        assert_eq!(strip_tile_extension("manifest.json"), None);
+        assert_eq!(strip_tile_extension("12"), None);
+    }
+}
@@ -1,7 +1,7 @@
-use super::TileStorage;
+use super::{strip_tile_extension, TileFormat, TileStorage};
 use crate::tile_downloader::types::{Bounds, TileCoord};
 use std::fs::File;
-use std::io::Write;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use zip::write::{FileOptions, ZipWriter};
 use zip::CompressionMethod;
@@ -18,6 +18,64 @@ impl ZipStorage {
             writer: None,
         }
     }
+
+    /// 从 ZIP 归档读取瓦片（归档需已写入完成）
+    pub fn read_tile(zip_path: &Path, coord: &TileCoord) -> Result<Option<Vec<u8>>, String> {
+        let file = File::open(zip_path).map_err(|e| format!("打开 ZIP 文件失败: {}", e))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| format!("读取 ZIP 文件失败（下载中的归档尚不可读）: {}", e))?;
+
+        let Some(tile_path) = TileFormat::ALL
+            .iter()
+            .map(|format| format!("{}/{}/{}.{}", coord.z, coord.x, coord.y, format.extension()))
+            .find(|path| archive.index_for_name(path).is_some())
+        else {
+            return Ok(None);
+        };
+        let mut entry = archive
+            .by_name(&tile_path)
+            .map_err(|e| format!("读取瓦片失败: {}", e))?;
+
+        let mut data = Vec::new();
+        entry
+            .read_to_end(&mut data)
+            .map_err(|e| format!("读取瓦片失败: {}", e))?;
+        Ok(Some(data))
+    }
+
+    /// 遍历归档中 z/x/y.{扩展名} 形式的全部瓦片
+    pub fn for_each_tile(
+        zip_path: &Path,
+        f: &mut dyn FnMut(TileCoord, Vec<u8>) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let file = File::open(zip_path).map_err(|e| format!("打开 ZIP 文件失败: {}", e))?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| format!("读取 ZIP 文件失败（下载中的归档尚不可读）: {}", e))?;
+
+        for i in 0..archive.len() {
+            let mut entry = archive
+                .by_index(i)
+                .map_err(|e| format!("读取文件失败: {}", e))?;
+            let Some(coord) = entry.is_file().then(|| parse_tile_path(entry.name())).flatten() else {
+                continue;
+            };
+            let mut data = Vec::new();
+            entry
+                .read_to_end(&mut data)
+                .map_err(|e| format!("读取瓦片失败: {}", e))?;
+            f(coord, data)?;
+        }
+        Ok(())
+    }
+}
+
+/// 解析 z/x/y.{扩展名} 形式的条目路径
+fn parse_tile_path(name: &str) -> Option<TileCoord> {
+    let mut parts = strip_tile_extension(name)?.rsplit('/');
+    let y = parts.next()?.parse().ok()?;
+    let x = parts.next()?.parse().ok()?;
+    let z = parts.next()?.parse().ok()?;
+    Some(TileCoord::new(z, x, y))
 }
 
 impl TileStorage for ZipStorage {
@@ -41,8 +99,9 @@ impl TileStorage for ZipStorage {
     fn save_tile(&mut self, coord: &TileCoord, data: &[u8]) -> Result<(), String> {
         let writer = self.writer.as_mut().ok_or("ZIP writer 未初始化")?;
 
-        // 瓦片路径 z/x/y.png
-        let tile_path = format!("{}/{}/{}.png", coord.z, coord.x, coord.y);
+        // 瓦片路径 z/x/y.{扩展名}，扩展名按实际图片格式
+        let extension = TileFormat::detect(data).extension();
+        let tile_path = format!("{}/{}/{}.{}", coord.z, coord.x, coord.y, extension);
 
         let options = FileOptions::<()>::default()
             .compression_method(CompressionMethod::Deflated)
@@ -0,0 +1,163 @@
+//! 离线包缩略图
+//!
+//! 从输出中取低层级瓦片拼成小图并缓存到应用目录，同时按已完成瓦片
+//! 计算实际覆盖范围，供任务列表展示。
+
+use super::database::TileDatabase;
+use super::imaging::{decode_png, encode_png, is_png, pixel_to_lonlat, RgbaImage, TILE_SIZE};
+use super::storage::read_tile;
+use super::types::{TileCoord, TileExtent};
+use crate::error::AppError;
+use serde::Serialize;
+use serde_json::json;
+use std::path::Path;
+
+/// 缩略图最大边长（像素）
+const THUMBNAIL_SIZE: u32 = 256;
+/// 优先选择每边不超过该瓦片数的最高层级
+const PREFERRED_SPAN: u32 = 4;
+/// 最低层级每边瓦片数超过该值时放弃生成
+const MAX_SPAN: u32 = 16;
+
+/// 任务缩略图
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskThumbnail {
+    /// 缩略图文件路径
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    /// 拼图所用层级
+    pub zoom: u32,
+    /// 实际覆盖范围（GeoJSON Feature），按最高层级已完成瓦片计算
+    pub coverage: serde_json::Value,
+}
+
+fn span(extent: &TileExtent) -> u32 {
+    (extent.max_x - extent.min_x + 1).max(extent.max_y - extent.min_y + 1)
+}
+
+/// 瓦片范围转为 GeoJSON 多边形
+fn extent_to_geojson(extent: &TileExtent) -> serde_json::Value {
+    let tile = TILE_SIZE as f64;
+    let (west, north) =
+        pixel_to_lonlat(extent.min_x as f64 * tile, extent.min_y as f64 * tile, extent.zoom);
+    let (east, south) = pixel_to_lonlat(
+        (extent.max_x + 1) as f64 * tile,
+        (extent.max_y + 1) as f64 * tile,
+        extent.zoom,
+    );
+    json!({
+        "type": "Feature",
+        "properties": {
+            "zoom": extent.zoom,
+            "tiles": extent.count,
+        },
+        "geometry": {
+            "type": "Polygon",
+            "coordinates": [[
+                [west, south],
+                [east, south],
+                [east, north],
+                [west, north],
+                [west, south],
+            ]],
+        },
+    })
+}
+
+/// 缓存是否仍有效：缩略图不早于输出文件的修改时间
+fn cache_fresh(thumbnail: &Path, output: &Path) -> bool {
+    let modified = |p: &Path| p.metadata().and_then(|m| m.modified()).ok();
+    match (modified(thumbnail), modified(output)) {
+        (Some(thumb), Some(out)) => thumb >= out,
+        _ => false,
+    }
+}
+
+/// 生成（或读取缓存的）任务缩略图，缓存文件为 cache_dir/<task_id>.png
+pub fn generate_thumbnail(
+    db: &TileDatabase,
+    task_id: &str,
+    cache_dir: &Path,
+    force: bool,
+) -> Result<TaskThumbnail, AppError> {
+    let task = db
+        .get_task(task_id)
+        .map_err(|e| format!("获取任务失败: {}", e))?
+        .ok_or_else(|| AppError::not_found("任务不存在"))?;
+    let output_path = Path::new(&task.output_path);
+    if !output_path.exists() {
+        return Err(AppError::not_found("输出文件不存在"));
+    }
+
+    let extents = db
+        .get_completed_extents(task_id)
+        .map_err(|e| format!("获取瓦片范围失败: {}", e))?;
+    let (Some(lowest), Some(highest)) = (extents.first(), extents.last()) else {
+        return Err(AppError::conflict("任务尚无已完成的瓦片"));
+    };
+    let coverage = extent_to_geojson(highest);
+
+    // 选每边不超过 PREFERRED_SPAN 的最高层级，都超过时退回最低层级
+    let extent = extents
+        .iter()
+        .rev()
+        .find(|e| span(e) <= PREFERRED_SPAN)
+        .unwrap_or(lowest);
+    if span(extent) > MAX_SPAN {
+        return Err(AppError::invalid(format!(
+            "最低层级 z{} 瓦片过多，无法生成缩略图",
+            extent.zoom
+        )));
+    }
+
+    let tiles_x = extent.max_x - extent.min_x + 1;
+    let tiles_y = extent.max_y - extent.min_y + 1;
+    let cell = THUMBNAIL_SIZE / tiles_x.max(tiles_y);
+    let (width, height) = (tiles_x * cell, tiles_y * cell);
+
+    std::fs::create_dir_all(cache_dir).map_err(|e| format!("创建缩略图目录失败: {}", e))?;
+    let thumbnail_path = cache_dir.join(format!("{}.png", task_id));
+    let result = TaskThumbnail {
+        path: thumbnail_path.to_string_lossy().to_string(),
+        width,
+        height,
+        zoom: extent.zoom,
+        coverage,
+    };
+    if !force && cache_fresh(&thumbnail_path, output_path) {
+        return Ok(result);
+    }
+
+    let mut canvas = RgbaImage::filled(width, height, [240, 240, 240, 255]);
+    let mut drawn = 0;
+    for x in extent.min_x..=extent.max_x {
+        for y in extent.min_y..=extent.max_y {
+            let coord = TileCoord::new(extent.zoom, x, y);
+            let data = match read_tile(&task.output_format, output_path, &coord) {
+                Ok(Some(data)) if is_png(&data) => data,
+                Ok(_) => continue,
+                Err(e) => {
+                    log::warn!("读取缩略图瓦片 {}/{}/{} 失败: {}", coord.z, x, y, e);
+                    continue;
+                }
+            };
+            match decode_png(&data) {
+                Ok(image) => {
+                    let left = ((x - extent.min_x) * cell) as i64;
+                    let top = ((y - extent.min_y) * cell) as i64;
+                    canvas.blit(&image.resize(cell, cell), left, top);
+                    drawn += 1;
+                }
+                Err(e) => log::warn!("解码缩略图瓦片 {}/{}/{} 失败: {}", coord.z, x, y, e),
+            }
+        }
+    }
+    if drawn == 0 {
+        return Err(AppError::invalid("没有可用的 PNG 瓦片，暂不支持其他格式"));
+    }
+
+    let png = encode_png(&canvas)?;
+    std::fs::write(&thumbnail_path, png).map_err(|e| format!("保存缩略图失败: {}", e))?;
+    Ok(result)
+}
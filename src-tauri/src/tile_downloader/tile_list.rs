@@ -0,0 +1,132 @@
+//! 按瓦片坐标列表下载
+//!
+//! 从失败清单等 CSV/文本文件读取瓦片坐标，跳过 bbox 枚举只下载列表中的瓦片。
+//! 每行前三列依次为 z、x、y，可用逗号、制表符、空格或斜杠分隔，多余的列忽略；
+//! 表头、注释等无法解析为坐标的行直接跳过。
+
+use super::types::{Bounds, TileCoord};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Web Mercator 可表示的纬度范围
+const MAX_LAT: f64 = 85.0511;
+
+/// 读取本地瓦片坐标列表文件
+pub fn load_tile_list_file(path: &Path) -> Result<Vec<TileCoord>, String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("读取瓦片列表文件失败: {}", e))?;
+    parse_tile_list(&content)
+}
+
+/// 解析瓦片坐标列表
+pub fn parse_tile_list(text: &str) -> Result<Vec<TileCoord>, String> {
+    normalize_tile_list(text.lines().filter_map(parse_line).collect())
+}
+
+/// 校验坐标范围并按出现顺序去重
+pub fn normalize_tile_list(tiles: Vec<TileCoord>) -> Result<Vec<TileCoord>, String> {
+    let mut seen = HashSet::new();
+    let mut result = Vec::new();
+    for tile in tiles {
+        if !tile.is_valid() {
+            return Err(format!(
+                "瓦片坐标超出范围: {}/{}/{}",
+                tile.z, tile.x, tile.y
+            ));
+        }
+        if seen.insert(tile) {
+            result.push(tile);
+        }
+    }
+    if result.is_empty() {
+        return Err("瓦片列表中没有有效的坐标".to_string());
+    }
+    Ok(result)
+}
+
+fn parse_line(line: &str) -> Option<TileCoord> {
+    let mut fields = line
+        .split(|c: char| c == ',' || c == '/' || c == ';' || c.is_whitespace())
+        .filter(|f| !f.is_empty())
+        .map(|f| f.trim_matches('"').parse::<u32>());
+    let z = fields.next()?.ok()?;
+    let x = fields.next()?.ok()?;
+    let y = fields.next()?.ok()?;
+    Some(TileCoord::new(z, x, y))
+}
+
+/// 列表中出现的层级，升序
+pub fn tile_list_zooms(tiles: &[TileCoord]) -> Vec<u32> {
+    let mut zooms: Vec<u32> = tiles.iter().map(|t| t.z).collect();
+    zooms.sort_unstable();
+    zooms.dedup();
+    zooms
+}
+
+/// 列表中所有瓦片的外接矩形
+pub fn tile_list_bounds(tiles: &[TileCoord]) -> Bounds {
+    let mut bounds = Bounds::new(-MAX_LAT, MAX_LAT, -180.0, 180.0);
+    for tile in tiles {
+        let (west, north) = tile_corner(tile.z, tile.x, tile.y);
+        let (east, south) = tile_corner(tile.z, tile.x + 1, tile.y + 1);
+        bounds.north = bounds.north.max(north);
+        bounds.south = bounds.south.min(south);
+        bounds.east = bounds.east.max(east);
+        bounds.west = bounds.west.min(west);
+    }
+    bounds.north = bounds.north.min(MAX_LAT);
+    bounds.south = bounds.south.max(-MAX_LAT);
+    bounds
+}
+
+/// 瓦片左上角经纬度
+fn tile_corner(z: u32, x: u32, y: u32) -> (f64, f64) {
+    let n = 2f64.powi(z as i32);
+    let lon = x as f64 / n * 360.0 - 180.0;
+    let lat = (std::f64::consts::PI * (1.0 - 2.0 * y as f64 / n))
+        .sinh()
+        .atan()
+        .to_degrees();
+    (lon, lat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tile_list() {
+        let text = "z,x,y,error\n\
+                    12,3421,1564,timeout\n\
+                    12\t3422\t1564\n\
+                    # 补下\n\
+                    13/6843/3128\n\
+                    12,3421,1564\n\
+                    \n";
+        let tiles = parse_tile_list(text).unwrap();
+        assert_eq!(
+            tiles,
+            vec![
+                TileCoord::new(12, 3421, 1564),
+                TileCoord::new(12, 3422, 1564),
+                TileCoord::new(13, 6843, 3128),
+            ]
+        );
+        assert_eq!(tile_list_zooms(&tiles), vec![12, 13]);
+
+        assert_eq!(
+            parse_tile_list("z,x,y\n2,4,1").unwrap_err(),
+            "瓦片坐标超出范围: 2/4/1"
+        );
+        assert!(parse_tile_list("z,x,y\n").is_err());
+    }
+
+    #[test]
+    fn test_tile_list_bounds() {
+        let bounds = tile_list_bounds(&[TileCoord::new(1, 1, 0), TileCoord::new(2, 2, 2)]);
+        assert_eq!((bounds.west, bounds.east), (0.0, 180.0));
+        assert_eq!(bounds.north, MAX_LAT);
+        assert!((bounds.south + 66.5133).abs() < 1e-4);
+        assert!(bounds.is_valid());
+    }
+}
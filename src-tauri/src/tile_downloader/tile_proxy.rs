@@ -1,9 +1,22 @@
-use super::platforms::create_platform;
-use super::types::MapType;
+use super::commands::get_tile_cache;
+use super::downloader::calculate_tiles;
+use super::platforms::{create_platform, TilePlatform};
+use super::types::{Bounds, HeaderOptions, MapType, TileCoord};
+use crate::budget::{self, BudgetKind, Consumption};
+use futures::stream::{self, StreamExt};
 use once_cell::sync::Lazy;
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
+use tauri::AppHandle;
+use crate::error::{AppError, CmdResult};
+
+/// 单次预取的瓦片数上限
+const MAX_PREFETCH_TILES: usize = 2000;
+
+/// 预取并发数
+const PREFETCH_CONCURRENCY: usize = 8;
 
 static HTTP_CLIENT: Lazy<Client> = Lazy::new(|| {
     Client::builder()
@@ -20,38 +33,181 @@ pub struct TileRequest {
     pub x: u32,
     pub y: u32,
     pub api_key: Option<String>,
+    /// 与下载任务一致的请求头伪装配置
+    #[serde(flatten)]
+    pub headers: HeaderOptions,
 }
 
-/// 代理瓦片请求，避免浏览器 CORS 限制
-#[tauri::command]
-pub async fn proxy_tile_request(request: TileRequest) -> Result<Vec<u8>, String> {
-    let platform = create_platform(&request.platform, request.api_key.as_deref());
-    let map_type = MapType::from(request.map_type.as_str());
-
+/// 生成瓦片请求地址与请求头
+fn upstream_request(
+    platform: &dyn TilePlatform,
+    map_type: &MapType,
+    header_options: &HeaderOptions,
+    tile: &TileCoord,
+) -> CmdResult<(String, HashMap<String, String>)> {
     let url = platform
-        .get_tile_url(request.z, request.x, request.y, &map_type)
-        .ok_or("此平台不支持该地图类型")?;
+        .get_tile_url(tile.z, tile.x, tile.y, map_type)
+        .ok_or_else(|| AppError::invalid("此平台不支持该地图类型"))?;
 
-    let headers = platform.get_headers();
+    let mut headers = platform.get_headers();
+    header_options.apply(&mut headers);
+    Ok((url, headers))
+}
+
+/// 从上游拉取瓦片，计入平台每日瓦片预算
+async fn fetch_upstream(
+    platform_id: &str,
+    url: &str,
+    headers: &HashMap<String, String>,
+) -> CmdResult<Vec<u8>> {
+    if budget::consume(platform_id, BudgetKind::Tiles) == Consumption::Exhausted {
+        return Err(AppError::quota("今日瓦片预算已用尽"));
+    }
 
-    let mut req = HTTP_CLIENT.get(&url);
+    let mut req = HTTP_CLIENT.get(url);
     for (key, value) in headers {
-        req = req.header(&key, &value);
+        req = req.header(key, value);
     }
 
     let response = req
         .send()
         .await
-        .map_err(|e| format!("请求失败: {}", e))?;
+        .map_err(|e| AppError::network(format!("请求失败: {}", e)))?;
 
     if !response.status().is_success() {
-        return Err(format!("HTTP 错误: {}", response.status()));
+        return Err(AppError::network(format!(
+            "HTTP 错误: {}",
+            response.status()
+        )));
     }
 
     let bytes = response
         .bytes()
         .await
-        .map_err(|e| format!("读取响应失败: {}", e))?;
+        .map_err(|e| AppError::network(format!("读取响应失败: {}", e)))?;
 
     Ok(bytes.to_vec())
 }
+
+/// 代理瓦片请求，避免浏览器 CORS 限制；优先读取共享瓦片缓存，下载后写回
+#[tauri::command]
+pub async fn proxy_tile_request(app: AppHandle, request: TileRequest) -> CmdResult<Vec<u8>> {
+    let platform = create_platform(&request.platform, request.api_key.as_deref());
+    let map_type = MapType::from(request.map_type.as_str());
+    let map_type_key = request.map_type.to_lowercase();
+    let tile = TileCoord::new(request.z, request.x, request.y);
+
+    // 缓存不可用时直接走网络，不影响预览
+    let cache = get_tile_cache(&app)
+        .inspect_err(|e| log::debug!("{}", e))
+        .ok();
+    if let Some(data) = cache
+        .as_ref()
+        .and_then(|c| c.get(platform.id(), &map_type_key, &tile).ok().flatten())
+    {
+        return Ok(data);
+    }
+
+    let (url, headers) = upstream_request(platform.as_ref(), &map_type, &request.headers, &tile)?;
+    let data = fetch_upstream(platform.id(), &url, &headers).await?;
+
+    if let Some(cache) = &cache {
+        if let Err(e) = cache.put(platform.id(), &map_type_key, &tile, &data) {
+            log::debug!("写入瓦片缓存失败: {}", e);
+        }
+    }
+    Ok(data)
+}
+
+/// 批量预取请求
+#[derive(Debug, Deserialize)]
+pub struct PrefetchRequest {
+    pub platform: String,
+    pub map_type: String,
+    pub bounds: Bounds,
+    pub zoom: u32,
+    pub api_key: Option<String>,
+    #[serde(flatten)]
+    pub headers: HeaderOptions,
+}
+
+/// 批量预取结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PrefetchResult {
+    pub total: usize,
+    /// 已在缓存中的瓦片数
+    pub cached: usize,
+    /// 本次下载并写入缓存的瓦片数
+    pub downloaded: usize,
+    pub failed: usize,
+}
+
+/// 按范围批量拉取瓦片写入共享缓存，供切换离线预览前预热
+#[tauri::command]
+pub async fn prefetch_tiles(app: AppHandle, request: PrefetchRequest) -> CmdResult<PrefetchResult> {
+    let platform = create_platform(&request.platform, request.api_key.as_deref());
+    let map_type = MapType::from(request.map_type.as_str());
+    let map_type_key = request.map_type.to_lowercase();
+    let cache = get_tile_cache(&app)?;
+
+    let tiles = calculate_tiles(&request.bounds, &[request.zoom]);
+    if tiles.len() > MAX_PREFETCH_TILES {
+        return Err(AppError::invalid(format!(
+            "范围内共 {} 个瓦片，超过单次预取上限 {}，请缩小范围",
+            tiles.len(),
+            MAX_PREFETCH_TILES
+        )));
+    }
+
+    let mut result = PrefetchResult {
+        total: tiles.len(),
+        cached: 0,
+        downloaded: 0,
+        failed: 0,
+    };
+
+    let mut jobs = Vec::new();
+    for tile in tiles {
+        match cache.get(platform.id(), &map_type_key, &tile) {
+            Ok(Some(_)) => result.cached += 1,
+            _ => {
+                let (url, headers) =
+                    upstream_request(platform.as_ref(), &map_type, &request.headers, &tile)?;
+                jobs.push((tile, url, headers));
+            }
+        }
+    }
+
+    let platform_id = platform.id();
+    let fetched: Vec<_> = stream::iter(jobs)
+        .map(|(tile, url, headers)| async move {
+            (tile, fetch_upstream(platform_id, &url, &headers).await)
+        })
+        .buffer_unordered(PREFETCH_CONCURRENCY)
+        .collect()
+        .await;
+
+    for (tile, data) in fetched {
+        let saved = data.and_then(|data| {
+            cache
+                .put(platform_id, &map_type_key, &tile, &data)
+                .map_err(|e| AppError::from(format!("写入瓦片缓存失败: {}", e)))
+        });
+        match saved {
+            Ok(()) => result.downloaded += 1,
+            Err(e) => {
+                log::debug!("预取瓦片 {}/{}/{} 失败: {}", tile.z, tile.x, tile.y, e);
+                result.failed += 1;
+            }
+        }
+    }
+
+    log::info!(
+        "预取瓦片 {} 个: 缓存命中 {}, 下载 {}, 失败 {}",
+        result.total,
+        result.cached,
+        result.downloaded,
+        result.failed
+    );
+    Ok(result)
+}
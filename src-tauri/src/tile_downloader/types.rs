@@ -1,4 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::power::CompletionAction;
 
 /// 下载任务状态
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -10,6 +13,8 @@ pub enum TaskStatus {
     Completed,
     Failed,
     Cancelled,
+    /// 已完成但抽样校验发现瓦片无法解码
+    Suspect,
 }
 
 impl ToString for TaskStatus {
@@ -21,6 +26,7 @@ impl ToString for TaskStatus {
             TaskStatus::Completed => "completed".to_string(),
             TaskStatus::Failed => "failed".to_string(),
             TaskStatus::Cancelled => "cancelled".to_string(),
+            TaskStatus::Suspect => "suspect".to_string(),
         }
     }
 }
@@ -34,6 +40,7 @@ impl From<&str> for TaskStatus {
             "completed" => TaskStatus::Completed,
             "failed" => TaskStatus::Failed,
             "cancelled" => TaskStatus::Cancelled,
+            "suspect" => TaskStatus::Suspect,
             _ => TaskStatus::Pending,
         }
     }
@@ -94,6 +101,16 @@ pub enum MapType {
     Terrain,
     Roadnet,
     Annotation,
+    /// 英文注记
+    AnnotationEn,
+    /// 实时路况
+    Traffic,
+    /// 影像注记
+    SatelliteAnnotation,
+    /// 地形注记
+    TerrainAnnotation,
+    /// 境界线
+    Boundary,
 }
 
 impl ToString for MapType {
@@ -105,6 +122,11 @@ impl ToString for MapType {
             MapType::Terrain => "terrain".to_string(),
             MapType::Roadnet => "roadnet".to_string(),
             MapType::Annotation => "annotation".to_string(),
+            MapType::AnnotationEn => "annotation_en".to_string(),
+            MapType::Traffic => "traffic".to_string(),
+            MapType::SatelliteAnnotation => "satellite_annotation".to_string(),
+            MapType::TerrainAnnotation => "terrain_annotation".to_string(),
+            MapType::Boundary => "boundary".to_string(),
         }
     }
 }
@@ -118,6 +140,11 @@ impl From<&str> for MapType {
             "terrain" => MapType::Terrain,
             "roadnet" => MapType::Roadnet,
             "annotation" => MapType::Annotation,
+            "annotation_en" => MapType::AnnotationEn,
+            "traffic" => MapType::Traffic,
+            "satellite_annotation" => MapType::SatelliteAnnotation,
+            "terrain_annotation" => MapType::TerrainAnnotation,
+            "boundary" => MapType::Boundary,
             _ => MapType::Street,
         }
     }
@@ -175,6 +202,9 @@ impl Bounds {
     }
 }
 
+/// 裁剪多边形，每个环为一组 (经度, 纬度)，内环与外环同样存放，按奇偶规则判断内外
+pub type ClipPolygon = Vec<Vec<(f64, f64)>>;
+
 /// 下载任务配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskConfig {
@@ -188,6 +218,89 @@ pub struct TaskConfig {
     pub thread_count: u32,
     pub retry_count: u32,
     pub api_key: Option<String>,
+    /// 每个主机的最大并发连接数，缺省为 6
+    #[serde(default)]
+    pub max_connections_per_host: Option<u32>,
+    /// 输出路径冲突策略: overwrite(覆盖) / resume(续写) / error(报错，缺省)
+    #[serde(default)]
+    pub conflict_strategy: Option<String>,
+    /// 请求头伪装配置
+    #[serde(flatten)]
+    pub headers: HeaderOptions,
+    /// GCJ02 平台纠偏输出为 WGS84 对齐的瓦片
+    #[serde(default)]
+    pub coord_correction: bool,
+    /// 备选平台列表，主源 404/无数据时按顺序尝试
+    #[serde(default)]
+    pub fallback_platforms: Vec<String>,
+    /// 引用收藏的范围，设置后以其外接矩形作为下载边界
+    #[serde(default)]
+    pub area_id: Option<String>,
+    /// 区域名称，用于按模板生成输出路径，缺省取收藏范围名称
+    #[serde(default)]
+    pub region_name: Option<String>,
+    /// 使用跨任务共享的瓦片缓存，已缓存的瓦片直接复制不再下载
+    #[serde(default)]
+    pub use_shared_cache: bool,
+    /// 无数据瓦片以透明占位瓦片填充输出
+    #[serde(default)]
+    pub fill_no_data: bool,
+    /// 每秒请求数上限，为空表示不限速
+    #[serde(default)]
+    pub rate_limit: Option<f64>,
+    /// 本地 GeoJSON 文件路径，设置后以其面要素的外接矩形为边界并按多边形裁剪瓦片
+    #[serde(default)]
+    pub geojson_path: Option<String>,
+    /// 瓦片坐标列表，设置后跳过范围枚举只下载列表中的瓦片，层级与边界取自列表
+    #[serde(default)]
+    pub tile_list: Option<Vec<TileCoord>>,
+    /// 瓦片坐标列表文件（CSV/文本，每行 z,x,y），与 tile_list 二选一
+    #[serde(default)]
+    pub tile_list_path: Option<String>,
+    /// 下载完成后的动作：none / exit / sleep / shutdown
+    #[serde(default)]
+    pub on_complete: CompletionAction,
+}
+
+/// 请求头伪装配置，覆盖平台默认的请求头
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeaderOptions {
+    pub user_agent: Option<String>,
+    pub referer: Option<String>,
+    pub accept: Option<String>,
+    /// 每次请求从内置 UA 池中随机选择 User-Agent
+    pub random_user_agent: bool,
+}
+
+/// 常见浏览器 User-Agent 池
+const USER_AGENT_POOL: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 Safari/605.1.15",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0",
+    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+];
+
+impl HeaderOptions {
+    /// 将配置应用到平台默认请求头上，空值表示沿用默认
+    pub fn apply(&self, headers: &mut HashMap<String, String>) {
+        if self.random_user_agent {
+            let index = (uuid::Uuid::new_v4().as_u128() % USER_AGENT_POOL.len() as u128) as usize;
+            headers.insert("User-Agent".to_string(), USER_AGENT_POOL[index].to_string());
+        } else if let Some(ua) = self.user_agent.as_deref().filter(|s| !s.trim().is_empty()) {
+            headers.insert("User-Agent".to_string(), ua.trim().to_string());
+        }
+
+        if let Some(referer) = self.referer.as_deref().filter(|s| !s.trim().is_empty()) {
+            headers.insert("Referer".to_string(), referer.trim().to_string());
+        }
+
+        if let Some(accept) = self.accept.as_deref().filter(|s| !s.trim().is_empty()) {
+            headers.insert("Accept".to_string(), accept.trim().to_string());
+        }
+    }
 }
 
 /// 下载任务信息
@@ -212,7 +325,90 @@ pub struct TaskInfo {
     pub updated_at: String,
     pub completed_at: Option<String>,
     pub error_message: Option<String>,
+    pub max_connections_per_host: u32,
+    #[serde(flatten)]
+    pub headers: HeaderOptions,
+    pub coord_correction: bool,
+    #[serde(default)]
+    pub fallback_platforms: Vec<String>,
+    #[serde(default)]
+    pub use_shared_cache: bool,
+    #[serde(default)]
+    pub fill_no_data: bool,
+    #[serde(default)]
+    pub rate_limit: Option<f64>,
+    /// 裁剪多边形，为空时下载整个矩形范围；数据量可能较大，不返回给前端
+    #[serde(default, skip_serializing)]
+    pub clip_polygon: Option<ClipPolygon>,
+    /// 是否按多边形裁剪
+    #[serde(default)]
+    pub clipped: bool,
+    /// 下载完成后的动作
+    #[serde(default)]
+    pub on_complete: CompletionAction,
     pub download_speed: f64,
+    /// 按层级统计的进度（仅 get_tile_task 返回）
+    #[serde(default)]
+    pub zoom_progress: Vec<ZoomProgress>,
+    /// 已完成瓦片按来源平台统计（仅 get_tile_task 返回）
+    #[serde(default)]
+    pub source_stats: Vec<SourceStat>,
+    /// 所有来源均无数据的瓦片数，已计入 completed_tiles，不参与失败重试（仅 get_tile_task 返回）
+    #[serde(default)]
+    pub no_data_tiles: u64,
+}
+
+/// 可在任意状态下修改的任务运行参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeConfig {
+    pub thread_count: u32,
+    pub retry_count: u32,
+    /// 每秒请求数上限，为空表示不限速
+    #[serde(default)]
+    pub rate_limit: Option<f64>,
+}
+
+/// 瓦片来源统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceStat {
+    pub platform: String,
+    pub count: u64,
+}
+
+/// 瓦片失败记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileError {
+    pub z: u32,
+    pub x: u32,
+    pub y: u32,
+    /// 出错的请求地址，写入存储失败时为空
+    pub url: Option<String>,
+    pub error: String,
+    pub failed_at: Option<String>,
+}
+
+/// 单个层级的瓦片进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZoomProgress {
+    pub zoom: u32,
+    pub total: u64,
+    pub pending: u64,
+    /// 含无数据瓦片
+    pub completed: u64,
+    pub failed: u64,
+    #[serde(default)]
+    pub no_data: u64,
+}
+
+/// 单个层级已完成瓦片的行列范围
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileExtent {
+    pub zoom: u32,
+    pub min_x: u32,
+    pub max_x: u32,
+    pub min_y: u32,
+    pub max_y: u32,
+    pub count: u64,
 }
 
 /// 瓦片进度状态
@@ -246,6 +442,11 @@ impl TileCoord {
     pub fn new(z: u32, x: u32, y: u32) -> Self {
         Self { z, x, y }
     }
+
+    /// 坐标是否在范围内；层级超过 30 时 2^z 溢出，平台也不提供如此高的层级
+    pub fn is_valid(&self) -> bool {
+        self.z <= 30 && self.x < 1 << self.z && self.y < 1 << self.z
+    }
 }
 
 /// 瓦片数量估算结果
@@ -279,4 +480,10 @@ pub struct PlatformInfo {
     pub max_zoom: u32,
     pub map_types: Vec<String>,
     pub requires_key: bool,
+    /// 可填写 Key，包括 Key 为可选的平台
+    #[serde(default)]
+    pub accepts_key: bool,
+    /// 申请 Key 的入口
+    #[serde(default)]
+    pub key_apply_url: Option<String>,
 }
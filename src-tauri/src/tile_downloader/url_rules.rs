@@ -0,0 +1,670 @@
+//! 瓦片 URL 规则远程更新
+//!
+//! 平台瓦片地址变更后，可通过远程规则文件覆盖内置的 URL 模板，无需等待新版本。
+//! 规则文件带版本号与 Ed25519 签名，签名与自检都通过且版本高于本地时才替换；
+//! 规则缺失、校验失败或手动重置时回退到内置模板。
+//!
+//! 目前还没有发布官方规则文件：更新地址与签名公钥缺省为空、不自动更新。
+//! 发布者生成密钥对、私钥离线保管，用户在规则设置中填写更新地址与对应的公钥；
+//! 公钥为空时拒绝所有远程规则。
+
+use super::platforms::{create_platform, get_all_platforms, TilePlatform};
+use super::types::MapType;
+use crate::config::{self, TileUrlRuleSettings};
+use crate::error::{AppError, CmdResult};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use ring::signature::{UnparsedPublicKey, ED25519, ED25519_PUBLIC_KEY_LEN};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 模板支持的占位符
+const PLACEHOLDERS: [&str; 9] = ["s", "x", "y", "z", "-y", "q", "bx", "by", "key"];
+
+/// 自检时请求的瓦片（z10 上海），各平台均有覆盖
+const CHECK_TILE: (u32, u32, u32) = (10, 857, 418);
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 单条 URL 规则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlRule {
+    pub platform: String,
+    pub map_type: String,
+    /// URL 模板。占位符：{s} 子域名，{x}/{y}/{z} 瓦片坐标，{-y} TMS 行号，
+    /// {q} Bing QuadKey，{bx}/{by} 百度瓦片坐标，{key} API Key
+    pub template: String,
+    /// 子域名列表，为空时沿用平台内置的子域名
+    #[serde(default)]
+    pub subdomains: Vec<String>,
+}
+
+impl UrlRule {
+    /// 模板含 {key} 的规则只在填写了 Key 时生效，其余规则只在未填写 Key 时生效
+    pub fn uses_key(&self) -> bool {
+        self.template.contains("{key}")
+    }
+}
+
+/// 规则集
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlRuleSet {
+    /// 规则版本，只接受高于本地的版本
+    pub version: u64,
+    #[serde(default)]
+    pub published_at: Option<String>,
+    pub rules: Vec<UrlRule>,
+}
+
+/// 规则文件：rules 为规则集的 JSON 文本，signature 为其 UTF-8 字节的 Ed25519 签名（Base64）
+#[derive(Debug, Deserialize)]
+struct SignedRules {
+    rules: String,
+    signature: String,
+}
+
+/// 当前生效的规则
+#[derive(Debug, Clone, Serialize)]
+pub struct UrlRuleStatus {
+    /// builtin: 内置模板；remote: 远程规则
+    pub source: String,
+    pub version: u64,
+    pub published_at: Option<String>,
+    pub rules: Vec<UrlRule>,
+    pub last_checked_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// URL 自检结果
+#[derive(Debug, Clone, Serialize)]
+pub struct UrlCheckResult {
+    pub platform: String,
+    pub map_type: String,
+    pub url: String,
+    /// 是否来自远程规则
+    pub from_rule: bool,
+    pub ok: bool,
+    pub http_status: Option<u16>,
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct RulesState {
+    active: Option<Arc<UrlRuleSet>>,
+    last_checked_at: Option<String>,
+    last_error: Option<String>,
+}
+
+static STATE: Lazy<RwLock<RulesState>> = Lazy::new(Default::default);
+
+/// 指定平台当前生效的远程规则
+pub fn rules_for(platform: &str) -> Vec<UrlRule> {
+    STATE
+        .read()
+        .active
+        .as_ref()
+        .map(|set| {
+            set.rules
+                .iter()
+                .filter(|r| r.platform == platform)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 替换模板中的占位符
+pub fn render(template: &str, vars: &[(&str, String)]) -> String {
+    let mut url = template.to_string();
+    for (name, value) in vars {
+        url = url.replace(&format!("{{{}}}", name), value);
+    }
+    url
+}
+
+/// 模板中出现的占位符名称
+fn placeholders(template: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            break;
+        };
+        names.push(&after[..end]);
+        rest = &after[end + 1..];
+    }
+    names
+}
+
+/// 规则自检：平台与地图类型存在，模板为 http(s) 地址且含定位瓦片所需的占位符
+fn check_rule(rule: &UrlRule) -> Result<(), String> {
+    let label = format!("{}/{}", rule.platform, rule.map_type);
+    let info = get_all_platforms()
+        .into_iter()
+        .find(|p| p.id == rule.platform)
+        .ok_or_else(|| format!("未知平台: {}", rule.platform))?;
+    if !info.map_types.contains(&rule.map_type) {
+        return Err(format!("{} 不是该平台支持的地图类型", label));
+    }
+    if !rule.template.starts_with("http://") && !rule.template.starts_with("https://") {
+        return Err(format!("{} 的模板不是 http(s) 地址", label));
+    }
+
+    let names = placeholders(&rule.template);
+    if let Some(unknown) = names.iter().find(|n| !PLACEHOLDERS.contains(n)) {
+        return Err(format!("{} 的模板含未知占位符 {{{}}}", label, unknown));
+    }
+    let has = |name: &str| names.contains(&name);
+    let xyz = has("z") && has("x") && (has("y") || has("-y"));
+    let baidu = has("z") && has("bx") && has("by");
+    if !(xyz || baidu || has("q")) {
+        return Err(format!("{} 的模板缺少瓦片坐标占位符", label));
+    }
+    if has("s")
+        && rule.subdomains.is_empty()
+        && create_platform(&rule.platform, None)
+            .subdomains()
+            .is_empty()
+    {
+        return Err(format!("{} 的模板使用了 {{s}}，但未提供子域名", label));
+    }
+    Ok(())
+}
+
+fn check_rule_set(set: &UrlRuleSet) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for rule in &set.rules {
+        check_rule(rule)?;
+        if !seen.insert((&rule.platform, &rule.map_type, rule.uses_key())) {
+            return Err(format!("{}/{} 的规则重复", rule.platform, rule.map_type));
+        }
+    }
+    Ok(())
+}
+
+/// 校验签名并解析规则文件
+fn parse_signed(text: &str, public_key: &[u8]) -> Result<UrlRuleSet, String> {
+    let signed: SignedRules =
+        serde_json::from_str(text).map_err(|e| format!("规则文件格式错误: {}", e))?;
+    let signature = BASE64
+        .decode(signed.signature.trim())
+        .map_err(|_| "规则签名格式错误".to_string())?;
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(signed.rules.as_bytes(), &signature)
+        .map_err(|_| "规则签名校验失败".to_string())?;
+
+    let set: UrlRuleSet =
+        serde_json::from_str(&signed.rules).map_err(|e| format!("规则内容格式错误: {}", e))?;
+    check_rule_set(&set)?;
+    Ok(set)
+}
+
+/// 解析 Base64 编码的 Ed25519 公钥
+fn decode_public_key(text: &str) -> Result<Vec<u8>, String> {
+    let key = BASE64
+        .decode(text.trim())
+        .map_err(|e| format!("规则签名公钥无效: {}", e))?;
+    if key.len() != ED25519_PUBLIC_KEY_LEN {
+        return Err(format!(
+            "规则签名公钥无效: 长度应为 {} 字节",
+            ED25519_PUBLIC_KEY_LEN
+        ));
+    }
+    Ok(key)
+}
+
+/// 设置中配置的规则签名公钥
+fn public_key(settings: &TileUrlRuleSettings) -> Result<Vec<u8>, String> {
+    if settings.public_key.trim().is_empty() {
+        return Err("尚未配置规则签名公钥，不能使用远程规则".to_string());
+    }
+    decode_public_key(&settings.public_key)
+}
+
+fn now() -> String {
+    chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// 加载本地保存的规则，无效时回退内置模板
+fn load_saved() {
+    let path = config::tile_url_rules_path();
+    if !path.exists() {
+        return;
+    }
+    let result = std::fs::read_to_string(&path)
+        .map_err(|e| format!("读取规则文件失败: {}", e))
+        .and_then(|text| {
+            let settings = config::get_tile_url_rule_settings()?;
+            parse_signed(&text, &public_key(&settings)?)
+        });
+
+    let mut state = STATE.write();
+    match result {
+        Ok(set) => {
+            log::info!(
+                "已加载瓦片 URL 规则 v{}，共 {} 条",
+                set.version,
+                set.rules.len()
+            );
+            state.active = Some(Arc::new(set));
+        }
+        Err(e) => {
+            log::warn!("本地瓦片 URL 规则无效，使用内置模板: {}", e);
+            state.active = None;
+            state.last_error = Some(e);
+        }
+    }
+}
+
+/// 拉取远程规则，版本高于本地时替换并保存，返回是否有更新
+fn fetch_update(settings: &TileUrlRuleSettings) -> Result<bool, String> {
+    let source_url = settings.source_url.trim();
+    if source_url.is_empty() {
+        return Err("未配置规则更新地址".to_string());
+    }
+    let public_key = public_key(settings)?;
+    let client = reqwest::blocking::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let response = client
+        .get(source_url)
+        .send()
+        .map_err(|e| format!("下载规则失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("下载规则失败: HTTP {}", response.status().as_u16()));
+    }
+    let text = response
+        .text()
+        .map_err(|e| format!("读取规则失败: {}", e))?;
+    let set = parse_signed(&text, &public_key)?;
+
+    let current = STATE.read().active.as_ref().map_or(0, |s| s.version);
+    if set.version <= current {
+        return Ok(false);
+    }
+    std::fs::write(config::tile_url_rules_path(), &text)
+        .map_err(|e| format!("保存规则文件失败: {}", e))?;
+    log::info!(
+        "瓦片 URL 规则已更新: v{} -> v{}，共 {} 条",
+        current,
+        set.version,
+        set.rules.len()
+    );
+    STATE.write().active = Some(Arc::new(set));
+    Ok(true)
+}
+
+/// 检查更新并记录结果
+fn check_for_update(settings: &TileUrlRuleSettings) -> Result<bool, String> {
+    let result = fetch_update(settings);
+    let mut state = STATE.write();
+    state.last_checked_at = Some(now());
+    state.last_error = result.as_ref().err().cloned();
+    result
+}
+
+/// 启动时加载本地规则，开启自动更新时在后台检查更新
+pub fn start() {
+    load_saved();
+    let settings = config::get_tile_url_rule_settings().unwrap_or_default();
+    if !settings.auto_update || settings.source_url.trim().is_empty() {
+        return;
+    }
+    std::thread::spawn(move || {
+        if let Err(e) = check_for_update(&settings) {
+            log::warn!("瓦片 URL 规则更新失败: {}", e);
+        }
+    });
+}
+
+fn status() -> UrlRuleStatus {
+    let state = STATE.read();
+    UrlRuleStatus {
+        source: if state.active.is_some() {
+            "remote"
+        } else {
+            "builtin"
+        }
+        .to_string(),
+        version: state.active.as_ref().map_or(0, |s| s.version),
+        published_at: state.active.as_ref().and_then(|s| s.published_at.clone()),
+        rules: state
+            .active
+            .as_ref()
+            .map(|s| s.rules.clone())
+            .unwrap_or_default(),
+        last_checked_at: state.last_checked_at.clone(),
+        last_error: state.last_error.clone(),
+    }
+}
+
+/// 获取当前生效的瓦片 URL 规则
+#[tauri::command]
+pub fn get_tile_url_rules() -> CmdResult<UrlRuleStatus> {
+    Ok(status())
+}
+
+/// 立即检查规则更新
+#[tauri::command]
+pub async fn update_tile_url_rules() -> CmdResult<UrlRuleStatus> {
+    let settings = config::get_tile_url_rule_settings()?;
+    tokio::task::spawn_blocking(move || check_for_update(&settings))
+        .await
+        .map_err(|e| format!("检查规则更新失败: {}", e))??;
+    Ok(status())
+}
+
+/// 删除远程规则，回退内置模板；开启自动更新时下次启动会重新拉取
+#[tauri::command]
+pub fn reset_tile_url_rules() -> CmdResult<UrlRuleStatus> {
+    let path = config::tile_url_rules_path();
+    if path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    let mut state = STATE.write();
+    state.active = None;
+    state.last_error = None;
+    drop(state);
+    log::info!("瓦片 URL 规则已重置为内置模板");
+    Ok(status())
+}
+
+#[tauri::command]
+pub fn get_tile_url_rule_settings() -> CmdResult<TileUrlRuleSettings> {
+    config::get_tile_url_rule_settings().map_err(AppError::from)
+}
+
+#[tauri::command]
+pub fn set_tile_url_rule_settings(settings: TileUrlRuleSettings) -> CmdResult<()> {
+    let url = settings.source_url.trim();
+    if url.is_empty() {
+        if settings.auto_update {
+            return Err(AppError::invalid("开启自动更新需填写规则更新地址"));
+        }
+    } else if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(AppError::invalid("规则更新地址需为 http(s) 地址"));
+    }
+    let public_key = settings.public_key.trim();
+    if public_key.is_empty() {
+        if settings.auto_update {
+            return Err(AppError::invalid("开启自动更新需填写规则签名公钥"));
+        }
+    } else {
+        decode_public_key(public_key).map_err(AppError::invalid)?;
+    }
+    let settings = TileUrlRuleSettings {
+        source_url: url.to_string(),
+        public_key: public_key.to_string(),
+        ..settings
+    };
+    config::set_tile_url_rule_settings(&settings).map_err(AppError::from)
+}
+
+async fn check_url(
+    client: &reqwest::Client,
+    platform: &dyn TilePlatform,
+    map_type: &MapType,
+) -> UrlCheckResult {
+    let (z, x, y) = CHECK_TILE;
+    let mut result = UrlCheckResult {
+        platform: platform.id().to_string(),
+        map_type: map_type.to_string(),
+        url: String::new(),
+        from_rule: false,
+        ok: false,
+        http_status: None,
+        error: None,
+    };
+    let Some(url) = platform.get_tile_url(z, x, y, map_type) else {
+        result.error = Some("该层级无瓦片地址".to_string());
+        return result;
+    };
+    result.url = url.clone();
+
+    let mut request = client.get(&url);
+    for (key, value) in platform.get_headers() {
+        request = request.header(key, value);
+    }
+    match request.send().await {
+        Ok(response) => {
+            let status = response.status();
+            result.http_status = Some(status.as_u16());
+            match response.bytes().await {
+                Ok(data) if status.is_success() && !data.is_empty() => result.ok = true,
+                Ok(_) if status.is_success() => result.error = Some("返回空瓦片".to_string()),
+                Ok(_) => result.error = Some(format!("HTTP {}", status.as_u16())),
+                Err(e) => result.error = Some(format!("读取响应失败: {}", e)),
+            }
+        }
+        Err(e) => result.error = Some(format!("请求失败: {}", e)),
+    }
+    result
+}
+
+/// 自检各平台当前生效的 URL：每种地图类型请求一个瓦片，需要 Key 而未提供的平台跳过
+#[tauri::command]
+pub async fn check_tile_urls(
+    platforms: Option<Vec<String>>,
+    api_keys: Option<HashMap<String, String>>,
+) -> CmdResult<Vec<UrlCheckResult>> {
+    let api_keys = api_keys.unwrap_or_default();
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+
+    let mut targets = Vec::new();
+    for info in get_all_platforms() {
+        if platforms
+            .as_ref()
+            .is_some_and(|ids| !ids.contains(&info.id))
+        {
+            continue;
+        }
+        let key = api_keys.get(&info.id).map(|k| k.as_str());
+        if info.requires_key && key.is_none() {
+            continue;
+        }
+        let rules = rules_for(&info.id);
+        for map_type in &info.map_types {
+            let from_rule = rules
+                .iter()
+                .any(|r| &r.map_type == map_type && r.uses_key() == key.is_some());
+            targets.push((
+                create_platform(&info.id, key),
+                MapType::from(map_type.as_str()),
+                from_rule,
+            ));
+        }
+    }
+
+    let checks = targets
+        .iter()
+        .map(|(platform, map_type, _)| check_url(&client, platform.as_ref(), map_type));
+    let mut results = join_all(checks).await;
+    for (result, (_, _, from_rule)) in results.iter_mut().zip(&targets) {
+        result.from_rule = *from_rule;
+    }
+
+    let failed = results.iter().filter(|r| !r.ok).count();
+    log::info!(
+        "瓦片 URL 自检完成: {} 项，失败 {} 项",
+        results.len(),
+        failed
+    );
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+    use std::io::{Read, Write};
+
+    fn generate_key_pair() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    /// 本地 HTTP 服务，只响应一次请求
+    fn serve_once(body: String) -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut socket, _)) = listener.accept() {
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf);
+                let _ = write!(
+                    socket,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+        format!("http://{}/rules.json", addr)
+    }
+
+    fn rule(platform: &str, map_type: &str, template: &str) -> UrlRule {
+        UrlRule {
+            platform: platform.to_string(),
+            map_type: map_type.to_string(),
+            template: template.to_string(),
+            subdomains: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_check_rule() {
+        assert!(check_rule(&rule(
+            "osm",
+            "street",
+            "https://{s}.tile.example.org/{z}/{x}/{y}.png"
+        ))
+        .is_ok());
+        assert!(check_rule(&rule(
+            "baidu",
+            "street",
+            "https://maponline{s}.bdimg.com/tile/?x={bx}&y={by}&z={z}"
+        ))
+        .is_ok());
+        assert!(check_rule(&rule("tianditu", "street", "http://t{s}.tianditu.gov.cn/vec_w/wmts?TILECOL={x}&TILEROW={y}&TILEMATRIX={z}&tk={key}")).is_ok());
+
+        assert!(check_rule(&rule("nokia", "street", "https://a/{z}/{x}/{y}")).is_err());
+        assert!(check_rule(&rule("osm", "traffic", "https://a/{z}/{x}/{y}")).is_err());
+        assert!(check_rule(&rule("osm", "street", "ftp://a/{z}/{x}/{y}")).is_err());
+        assert!(check_rule(&rule("osm", "street", "https://a/{z}/{x}/{row}")).is_err());
+        assert!(check_rule(&rule("osm", "street", "https://a/{z}/{x}")).is_err());
+    }
+
+    #[test]
+    fn test_parse_signed() {
+        let key_pair = generate_key_pair();
+        let public_key = key_pair.public_key().as_ref();
+
+        let rules = serde_json::json!({
+            "version": 3,
+            "rules": [{"platform": "osm", "map_type": "street", "template": "https://a/{z}/{x}/{y}.png"}]
+        })
+        .to_string();
+        let signed = |rules: &str, signature: &[u8]| {
+            serde_json::json!({"rules": rules, "signature": BASE64.encode(signature)}).to_string()
+        };
+
+        let set = parse_signed(
+            &signed(&rules, key_pair.sign(rules.as_bytes()).as_ref()),
+            public_key,
+        )
+        .unwrap();
+        assert_eq!(set.version, 3);
+        assert_eq!(set.rules[0].platform, "osm");
+
+        // 内容被改动后签名失效
+        let tampered = rules.replace("https://a", "https://b");
+        let err = parse_signed(
+            &signed(&tampered, key_pair.sign(rules.as_bytes()).as_ref()),
+            public_key,
+        )
+        .unwrap_err();
+        assert_eq!(err, "规则签名校验失败");
+    }
+
+    #[test]
+    fn test_signed_update() {
+        let dir = std::env::temp_dir().join(format!("tile_rules_{}", uuid::Uuid::new_v4()));
+        config::init_config_dir(dir.clone());
+
+        let key_pair = generate_key_pair();
+        let rules = serde_json::json!({
+            "version": 5,
+            "rules": [{"platform": "osm", "map_type": "street", "template": "https://a/{z}/{x}/{y}.png"}]
+        })
+        .to_string();
+        let text = serde_json::json!({
+            "rules": rules,
+            "signature": BASE64.encode(key_pair.sign(rules.as_bytes()).as_ref()),
+        })
+        .to_string();
+
+        // 未配置公钥时拒绝远程规则
+        let mut settings = TileUrlRuleSettings {
+            auto_update: false,
+            source_url: serve_once(text.clone()),
+            public_key: String::new(),
+        };
+        assert_eq!(
+            fetch_update(&settings).unwrap_err(),
+            "尚未配置规则签名公钥，不能使用远程规则"
+        );
+
+        // 公钥与签名不匹配时不替换
+        settings.public_key = BASE64.encode(generate_key_pair().public_key().as_ref());
+        assert_eq!(fetch_update(&settings).unwrap_err(), "规则签名校验失败");
+        assert!(rules_for("osm").is_empty());
+
+        settings.public_key = BASE64.encode(key_pair.public_key().as_ref());
+        settings.source_url = serve_once(text.clone());
+        assert!(fetch_update(&settings).unwrap());
+        assert_eq!(rules_for("osm")[0].template, "https://a/{z}/{x}/{y}.png");
+        assert_eq!(
+            std::fs::read_to_string(config::tile_url_rules_path()).unwrap(),
+            text
+        );
+
+        // 版本未升高时不重复替换
+        settings.source_url = serve_once(text);
+        assert!(!fetch_update(&settings).unwrap());
+
+        assert!(decode_public_key("not base64").is_err());
+        assert!(decode_public_key(&BASE64.encode([0u8; 16])).is_err());
+
+        STATE.write().active = None;
+        let _ = std::fs::remove_file(config::tile_url_rules_path());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_render() {
+        let vars = [
+            ("z", "3".to_string()),
+            ("-y", "1".to_string()),
+            ("s", "a".to_string()),
+        ];
+        assert_eq!(
+            render("https://{s}.example/{z}/{-y}", &vars),
+            "https://a.example/3/1"
+        );
+        assert_eq!(
+            placeholders("https://{s}/{z}/{-y}?k={key"),
+            vec!["s", "z", "-y"]
+        );
+    }
+}
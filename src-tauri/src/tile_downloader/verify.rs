@@ -0,0 +1,127 @@
+//! 下载结果抽样校验
+//!
+//! 运营商劫持或代理拦截时服务器照常返回 200，整批瓦片都“下载成功”却是网页内容。
+//! 任务完成后随机抽取若干已完成瓦片尝试按图片解码，有瓦片无法解码时将任务标记为可疑。
+
+use super::database::TileDatabase;
+use super::imaging::{decode_png, is_png};
+use super::storage::read_tile;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 每个任务抽样校验的瓦片数
+pub const SAMPLE_SIZE: u32 = 20;
+
+/// 抽样校验结果
+#[derive(Debug, Clone, Default)]
+pub struct SampleCheck {
+    pub sampled: usize,
+    pub invalid: usize,
+    /// 出现最多的失败原因
+    pub reason: Option<String>,
+}
+
+impl SampleCheck {
+    pub fn is_suspect(&self) -> bool {
+        self.invalid > 0
+    }
+
+    /// 面向用户的提示
+    pub fn message(&self) -> String {
+        format!(
+            "抽样校验 {} 个瓦片中有 {} 个无法解码（{}），请检查网络环境是否存在劫持或代理拦截",
+            self.sampled,
+            self.invalid,
+            self.reason.as_deref().unwrap_or("未知原因")
+        )
+    }
+}
+
+/// 校验瓦片数据是否为可解码的图片
+pub fn check_tile_image(data: &[u8]) -> Result<(), String> {
+    if data.is_empty() {
+        return Err("瓦片为空".to_string());
+    }
+    if is_png(data) {
+        return decode_png(data).map(|_| ());
+    }
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        // JPEG 以 EOI 标记结尾，部分服务端会在其后补齐少量字节
+        let tail = &data[data.len().saturating_sub(16)..];
+        return if tail.windows(2).any(|w| w == [0xFF, 0xD9]) {
+            Ok(())
+        } else {
+            Err("JPEG 数据不完整".to_string())
+        };
+    }
+    if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        let size = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        return if data.len() >= size + 8 {
+            Ok(())
+        } else {
+            Err("WebP 数据不完整".to_string())
+        };
+    }
+    if data.starts_with(b"GIF8") {
+        return Ok(());
+    }
+
+    let head = String::from_utf8_lossy(&data[..data.len().min(512)]).to_lowercase();
+    if head.trim_start().starts_with('<') || head.contains("<html") {
+        Err("返回的是网页而非图片".to_string())
+    } else {
+        Err("无法识别的图片格式".to_string())
+    }
+}
+
+/// 随机抽样已完成瓦片并逐个从输出中读取校验
+pub fn verify_output(
+    db: &TileDatabase,
+    task_id: &str,
+    output_format: &str,
+    output_path: &str,
+    sample_size: u32,
+) -> Result<SampleCheck, String> {
+    let tiles = db
+        .sample_completed_tiles(task_id, sample_size)
+        .map_err(|e| format!("读取已完成瓦片失败: {}", e))?;
+
+    let mut check = SampleCheck::default();
+    let mut reasons: HashMap<String, usize> = HashMap::new();
+    for coord in &tiles {
+        let result = match read_tile(output_format, Path::new(output_path), coord)? {
+            Some(data) => check_tile_image(&data),
+            None => Err("瓦片未写入输出".to_string()),
+        };
+        check.sampled += 1;
+        if let Err(e) = result {
+            check.invalid += 1;
+            *reasons.entry(e).or_default() += 1;
+        }
+    }
+    check.reason = reasons
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(reason, _)| reason);
+    Ok(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_tile_image() {
+        assert!(check_tile_image(&[0xFF, 0xD8, 0xFF, 0xE0, 0, 0, 0xFF, 0xD9]).is_ok());
+        assert_eq!(
+            check_tile_image(&[0xFF, 0xD8, 0xFF, 0xE0, 0, 0]).unwrap_err(),
+            "JPEG 数据不完整"
+        );
+        assert_eq!(
+            check_tile_image(b"\n<!DOCTYPE html><html><body>redirect</body></html>").unwrap_err(),
+            "返回的是网页而非图片"
+        );
+        assert!(check_tile_image(&[0x89, b'P', b'N', b'G', 0, 0]).is_err());
+        assert_eq!(check_tile_image(b"").unwrap_err(), "瓦片为空");
+    }
+}
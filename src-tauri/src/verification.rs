@@ -0,0 +1,448 @@
+//! POI 复核
+//!
+//! 不重新全量采集，而是对库内已有 POI 逐个以名称在其坐标附近向平台检索：
+//! 找到同名地点即视为仍然存在并更新复核时间，名称或地址变化、找不到的 POI 记入变更报告。
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::budget::{self, BudgetKind, Consumption};
+use crate::collectors::{Bounds, Collector, POIData, RegionConfig};
+use crate::commands::{collector_region, create_collector, select_api_key, with_poi_db};
+use crate::coords::haversine_distance;
+use crate::database::{ExportPOI, PoiFilter};
+use crate::error::{AppError, CmdResult};
+use crate::events::emit_log;
+use crate::normalize::normalize_name;
+use crate::poi_snapshots::FieldChange;
+
+/// 同名结果与库内坐标的最大距离（米）
+const MATCH_DISTANCE_M: f64 = 200.0;
+/// 名称互相包含的结果视为改名的最大距离（米）
+const RENAME_DISTANCE_M: f64 = 50.0;
+/// 检索范围：以 POI 为中心向四周扩展的经纬度
+const SEARCH_SPAN_DEG: f64 = 0.005;
+/// 连续请求失败达到该次数时停止复核
+const MAX_CONSECUTIVE_FAILURES: usize = 5;
+
+static STATUS: Lazy<Mutex<VerificationStatus>> = Lazy::new(|| {
+    Mutex::new(VerificationStatus {
+        status: "idle".to_string(),
+        ..Default::default()
+    })
+});
+static STOP_FLAG: AtomicBool = AtomicBool::new(false);
+
+/// 名称或地址发生变化的 POI
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifiedChange {
+    pub poi: ExportPOI,
+    pub changes: Vec<FieldChange>,
+}
+
+/// 复核进度与变更报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerificationStatus {
+    /// idle / running / stopped / completed / error
+    pub status: String,
+    pub platform: String,
+    pub total: usize,
+    pub checked: usize,
+    /// 仍然存在且信息未变的数量
+    pub confirmed: usize,
+    pub changed: Vec<VerifiedChange>,
+    /// 平台上已检索不到的 POI
+    pub missing: Vec<ExportPOI>,
+    /// 请求失败未能复核的数量
+    pub failed: usize,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub error_message: Option<String>,
+}
+
+/// 单个 POI 的复核结果
+#[derive(Debug, Clone, PartialEq)]
+enum Outcome {
+    Confirmed,
+    Changed(Vec<FieldChange>),
+    Missing,
+}
+
+/// 在平台检索结果中查找库内 POI
+///
+/// 优先匹配距离内的同名结果；没有同名结果时，近距离内名称互相包含的结果视为改名
+fn match_poi(poi: &ExportPOI, results: &[POIData]) -> Outcome {
+    let name = normalize_name(&poi.name);
+    let nearby: Vec<(&POIData, String, f64)> = results
+        .iter()
+        .map(|r| {
+            let distance = haversine_distance(poi.lon, poi.lat, r.lon, r.lat);
+            (r, normalize_name(&r.name), distance)
+        })
+        .filter(|(_, _, distance)| *distance <= MATCH_DISTANCE_M)
+        .collect();
+
+    let nearest = |renamed: bool| {
+        nearby
+            .iter()
+            .filter(|(_, other, distance)| {
+                if renamed {
+                    *distance <= RENAME_DISTANCE_M
+                        && !other.is_empty()
+                        && (other.contains(&name) || name.contains(other.as_str()))
+                } else {
+                    *other == name
+                }
+            })
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+    };
+    let (found, renamed) = match nearest(false) {
+        Some(found) => (found, false),
+        None => match nearest(true) {
+            Some(found) => (found, true),
+            None => return Outcome::Missing,
+        },
+    };
+
+    let (result, new_name, _) = found;
+    let mut changes = Vec::new();
+    if renamed {
+        changes.push(FieldChange {
+            field: "名称".to_string(),
+            before: poi.name.clone(),
+            after: new_name.clone(),
+        });
+    }
+    // 平台未返回地址时不视为变化
+    let address = result.address.trim();
+    if !address.is_empty() && address != poi.address.trim() {
+        changes.push(FieldChange {
+            field: "地址".to_string(),
+            before: poi.address.clone(),
+            after: address.to_string(),
+        });
+    }
+    if changes.is_empty() {
+        Outcome::Confirmed
+    } else {
+        Outcome::Changed(changes)
+    }
+}
+
+/// POI 周边的检索区域
+fn region_around(poi: &ExportPOI) -> RegionConfig {
+    let bounds = Bounds {
+        min_lon: poi.lon - SEARCH_SPAN_DEG,
+        max_lon: poi.lon + SEARCH_SPAN_DEG,
+        min_lat: poi.lat - SEARCH_SPAN_DEG,
+        max_lat: poi.lat + SEARCH_SPAN_DEG,
+    };
+    collector_region(&poi.region_code, bounds.clone()).unwrap_or(RegionConfig {
+        name: String::new(),
+        admin_code: String::new(),
+        city_code: String::new(),
+        bounds,
+    })
+}
+
+fn update_status(f: impl FnOnce(&mut VerificationStatus)) {
+    if let Ok(mut status) = STATUS.lock() {
+        f(&mut status);
+    }
+}
+
+fn finish(status: &str, error_message: Option<String>) {
+    update_status(|s| {
+        s.status = status.to_string();
+        s.finished_at = Some(chrono::Local::now().to_rfc3339());
+        s.error_message = error_message;
+    });
+}
+
+/// 按条件复核指定平台的库内 POI，limit 限制本次复核的数量以节省配额
+#[tauri::command]
+pub fn start_poi_verification(
+    app: AppHandle,
+    platform: String,
+    filter: PoiFilter,
+    limit: Option<usize>,
+) -> CmdResult<usize> {
+    if STATUS.lock().map_err(|e| e.to_string())?.status == "running" {
+        return Err(AppError::conflict("复核任务已在运行中"));
+    }
+    let (api_key, (delay_ms, _)) = select_api_key(&platform)?;
+    let Some(collector) = create_collector(&platform, api_key) else {
+        return Err(AppError::invalid(format!("不支持的平台: {}", platform)));
+    };
+
+    let mut filter = filter;
+    filter.platforms = Some(vec![platform.clone()]);
+    filter.resolve_stale_days();
+    let mut pois = with_poi_db(|db| {
+        db.get_poi_filtered(&filter)
+            .map_err(|e| format!("读取待复核数据失败: {}", e))
+    })?;
+    if let Some(limit) = limit {
+        pois.truncate(limit);
+    }
+    if pois.is_empty() {
+        return Err(AppError::invalid("没有符合条件的 POI"));
+    }
+
+    let total = pois.len();
+    STOP_FLAG.store(false, Ordering::SeqCst);
+    *STATUS.lock().map_err(|e| e.to_string())? = VerificationStatus {
+        status: "running".to_string(),
+        platform: platform.clone(),
+        total,
+        started_at: Some(chrono::Local::now().to_rfc3339()),
+        ..Default::default()
+    };
+    thread::spawn(move || {
+        run_verification(
+            app,
+            platform,
+            collector,
+            Duration::from_millis(delay_ms),
+            pois,
+        )
+    });
+    Ok(total)
+}
+
+fn run_verification(
+    app: AppHandle,
+    platform: String,
+    mut collector: Box<dyn Collector>,
+    delay: Duration,
+    pois: Vec<ExportPOI>,
+) {
+    emit_log(
+        &app,
+        format!("[{}] 开始复核 {} 个 POI", platform, pois.len()),
+    );
+
+    let mut failures = 0;
+    for poi in pois {
+        if STOP_FLAG.load(Ordering::SeqCst) {
+            emit_log(&app, format!("[{}] 复核已停止", platform));
+            finish("stopped", None);
+            return;
+        }
+        match budget::consume(&platform, BudgetKind::Requests) {
+            Consumption::Exhausted => {
+                emit_log(
+                    &app,
+                    format!("[{}] 今日请求预算已用尽，复核已停止", platform),
+                );
+                finish("stopped", Some("今日请求预算已用尽".to_string()));
+                return;
+            }
+            Consumption::Warning { used, limit } => emit_log(
+                &app,
+                format!(
+                    "[{}] 今日请求预算已用 {}/{}，即将达到上限",
+                    platform, used, limit
+                ),
+            ),
+            Consumption::Allowed => {}
+        }
+
+        thread::sleep(delay);
+        collector.set_region(region_around(&poi));
+        let outcome = match collector.search_poi(&poi.name, 1, &poi.category, "") {
+            Ok((results, _)) => {
+                failures = 0;
+                Some(match_poi(&poi, &results))
+            }
+            Err(e) => {
+                failures += 1;
+                emit_log(
+                    &app,
+                    format!("[{}] 复核 {} 失败: {}", platform, poi.name, e),
+                );
+                if failures >= MAX_CONSECUTIVE_FAILURES {
+                    finish(
+                        "error",
+                        Some(format!("连续 {} 次请求失败: {}", failures, e)),
+                    );
+                    return;
+                }
+                None
+            }
+        };
+
+        if matches!(outcome, Some(Outcome::Confirmed | Outcome::Changed(_))) {
+            if let Err(e) =
+                with_poi_db(|db| db.mark_poi_verified(poi.id).map_err(|e| e.to_string()))
+            {
+                log::warn!("更新复核时间失败: {}", e);
+            }
+        }
+        update_status(|s| {
+            s.checked += 1;
+            match outcome {
+                Some(Outcome::Confirmed) => s.confirmed += 1,
+                Some(Outcome::Changed(changes)) => s.changed.push(VerifiedChange { poi, changes }),
+                Some(Outcome::Missing) => s.missing.push(poi),
+                None => s.failed += 1,
+            }
+        });
+    }
+
+    let summary = STATUS
+        .lock()
+        .map(|s| {
+            format!(
+                "未变 {}，变化 {}，消失 {}，失败 {}",
+                s.confirmed,
+                s.changed.len(),
+                s.missing.len(),
+                s.failed
+            )
+        })
+        .unwrap_or_default();
+    emit_log(&app, format!("[{}] 复核完成：{}", platform, summary));
+    finish("completed", None);
+}
+
+#[tauri::command]
+pub fn stop_poi_verification() -> CmdResult<()> {
+    STOP_FLAG.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// 获取复核进度与变更报告
+#[tauri::command]
+pub fn get_poi_verification() -> CmdResult<VerificationStatus> {
+    Ok(STATUS.lock().map_err(|e| e.to_string())?.clone())
+}
+
+fn csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// 将最近一次复核的变更报告导出为 CSV，返回写入的行数
+#[tauri::command]
+pub fn export_verification_report(output_path: String) -> CmdResult<usize> {
+    let status = get_poi_verification()?;
+    if status.started_at.is_none() {
+        return Err(AppError::not_found("还没有复核记录"));
+    }
+
+    let mut csv = String::from("\u{feff}");
+    csv.push_str(&format!(
+        "# 平台: {}；开始时间: {}；已复核 {}/{}，未变 {}，失败 {}\n",
+        status.platform,
+        status.started_at.as_deref().unwrap_or(""),
+        status.checked,
+        status.total,
+        status.confirmed,
+        status.failed
+    ));
+    csv.push_str("变化类型,ID,名称,经度,纬度,区域编码,字段,原值,新值\n");
+
+    let mut rows = 0;
+    let mut push_row = |kind: &str, poi: &ExportPOI, field: &str, before: &str, after: &str| {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            kind,
+            poi.id,
+            csv_field(&poi.name),
+            poi.lon,
+            poi.lat,
+            poi.region_code,
+            field,
+            csv_field(before),
+            csv_field(after)
+        ));
+        rows += 1;
+    };
+    for change in &status.changed {
+        for field in &change.changes {
+            push_row(
+                "变化",
+                &change.poi,
+                &field.field,
+                &field.before,
+                &field.after,
+            );
+        }
+    }
+    for poi in &status.missing {
+        push_row("消失", poi, "", "", "");
+    }
+
+    std::fs::write(&output_path, csv)?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poi(name: &str, lon: f64, address: &str) -> ExportPOI {
+        ExportPOI {
+            id: 1,
+            name: name.to_string(),
+            lon,
+            lat: 33.0,
+            address: address.to_string(),
+            phone: String::new(),
+            category: "餐饮".to_string(),
+            platform: "amap".to_string(),
+            region_code: "320902".to_string(),
+        }
+    }
+
+    fn result(name: &str, lon: f64, address: &str) -> POIData {
+        POIData {
+            name: name.to_string(),
+            lon,
+            lat: 33.0,
+            original_lon: lon,
+            original_lat: 33.0,
+            category: "餐饮".to_string(),
+            category_id: String::new(),
+            address: address.to_string(),
+            phone: String::new(),
+            platform: "amap".to_string(),
+            raw_data: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_match_poi() {
+        let base = poi("老街面馆", 120.0, "人民路 1 号");
+
+        // 同名且地址一致
+        let found = [result("老街面馆", 120.0005, "人民路 1 号")];
+        assert_eq!(match_poi(&base, &found), Outcome::Confirmed);
+
+        // 地址变化，平台未返回地址时不算变化
+        let moved = [result("老街面馆", 120.0005, "人民路 9 号")];
+        match match_poi(&base, &moved) {
+            Outcome::Changed(changes) => assert_eq!(changes[0].field, "地址"),
+            other => panic!("{:?}", other),
+        }
+        let no_address = [result("老街面馆", 120.0005, "")];
+        assert_eq!(match_poi(&base, &no_address), Outcome::Confirmed);
+
+        // 近处名称互相包含视为改名，远处同名或无关名称视为消失
+        let renamed = [result("老街面馆(人民路店)", 120.0002, "人民路 1 号")];
+        match match_poi(&base, &renamed) {
+            Outcome::Changed(changes) => assert_eq!(changes[0].after, "老街面馆(人民路店)"),
+            other => panic!("{:?}", other),
+        }
+        let far = [
+            result("老街面馆", 120.01, "人民路 1 号"),
+            result("便利店", 120.0, ""),
+        ];
+        assert_eq!(match_poi(&base, &far), Outcome::Missing);
+    }
+}
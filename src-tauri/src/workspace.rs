@@ -0,0 +1,232 @@
+//! 工作区打包迁移
+//!
+//! 将 POI 数据库、瓦片任务数据库与各项设置打包为单个 ZIP 归档，换机后可一键导入恢复。
+//! 瓦片下载的输出文件与各任务的瓦片进度库体积较大，不包含在归档内，导入后重新开始任务时会重建进度。
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use zip::write::{FileOptions, ZipWriter};
+use zip::CompressionMethod;
+
+use crate::commands::{has_running_collectors, snapshot_poi_db, with_poi_db_closed};
+use crate::config::workspace_config_files;
+use crate::error::{AppError, CmdResult};
+use crate::tile_downloader::commands::{
+    has_running_tile_tasks, snapshot_tile_db, with_tile_db_closed,
+};
+
+/// 归档格式版本
+const WORKSPACE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const POI_DB_FILE: &str = "poi_data.db";
+const TILE_DB_FILE: &str = "tile_data.db";
+const CONFIG_PREFIX: &str = "config/";
+
+/// 归档清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    /// 归档内的文件列表
+    pub files: Vec<String>,
+}
+
+/// 临时工作目录，离开作用域时删除
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Result<Self, String> {
+        let dir = std::env::temp_dir().join(format!("poi-workspace-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).map_err(|e| format!("创建临时目录失败: {}", e))?;
+        Ok(Self(dir))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// 校验清单中的文件名，只接受两个数据库与白名单内的配置文件
+fn check_entry_name(name: &str, config_names: &[&str]) -> Result<(), String> {
+    let known = match name {
+        POI_DB_FILE | TILE_DB_FILE => true,
+        _ => name
+            .strip_prefix(CONFIG_PREFIX)
+            .is_some_and(|config_name| config_names.contains(&config_name)),
+    };
+    if known {
+        Ok(())
+    } else {
+        Err(format!("归档包含未知文件 {}，不是有效的工作区归档", name))
+    }
+}
+
+/// 用 src 覆盖数据库文件，并清除旧的 WAL 日志
+pub(crate) fn replace_db_file(src: &Path, dest: &Path) -> Result<(), String> {
+    for suffix in ["-wal", "-shm"] {
+        let mut journal = dest.as_os_str().to_owned();
+        journal.push(suffix);
+        let _ = std::fs::remove_file(PathBuf::from(journal));
+    }
+    std::fs::copy(src, dest).map_err(|e| format!("替换数据库文件失败: {}", e))?;
+    Ok(())
+}
+
+/// 导出工作区归档
+#[tauri::command]
+pub async fn export_workspace(app: AppHandle, path: String) -> CmdResult<WorkspaceManifest> {
+    let temp = TempDir::new()?;
+
+    // 数据库使用 VACUUM INTO 生成一致性快照，避免复制写入中的文件
+    let mut entries: Vec<(String, PathBuf)> = Vec::new();
+    let poi_snapshot = temp.0.join(POI_DB_FILE);
+    snapshot_poi_db(&poi_snapshot)?;
+    entries.push((POI_DB_FILE.to_string(), poi_snapshot));
+
+    let tile_snapshot = temp.0.join(TILE_DB_FILE);
+    snapshot_tile_db(&app, &tile_snapshot)?;
+    entries.push((TILE_DB_FILE.to_string(), tile_snapshot));
+
+    for (name, file) in workspace_config_files() {
+        if file.exists() {
+            entries.push((format!("{}{}", CONFIG_PREFIX, name), file));
+        }
+    }
+
+    let manifest = WorkspaceManifest {
+        format_version: WORKSPACE_FORMAT_VERSION,
+        app_version: app.package_info().version.to_string(),
+        created_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        files: entries.iter().map(|(name, _)| name.clone()).collect(),
+    };
+
+    let file = File::create(&path).map_err(|e| format!("创建归档文件失败: {}", e))?;
+    let mut writer = ZipWriter::new(file);
+    let options = FileOptions::<()>::default().compression_method(CompressionMethod::Deflated);
+
+    for (name, source) in &entries {
+        let data = std::fs::read(source).map_err(|e| format!("读取 {} 失败: {}", name, e))?;
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(|e| format!("写入归档失败: {}", e))?;
+        writer
+            .write_all(&data)
+            .map_err(|e| format!("写入归档失败: {}", e))?;
+    }
+
+    let manifest_json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    writer
+        .start_file(MANIFEST_FILE, options)
+        .map_err(|e| format!("写入归档失败: {}", e))?;
+    writer
+        .write_all(manifest_json.as_bytes())
+        .map_err(|e| format!("写入归档失败: {}", e))?;
+    writer
+        .finish()
+        .map_err(|e| format!("完成归档失败: {}", e))?;
+
+    log::info!("导出工作区: {} ({} 个文件)", path, manifest.files.len());
+    Ok(manifest)
+}
+
+/// 从归档导入工作区，覆盖当前的数据库与设置
+#[tauri::command]
+pub async fn import_workspace(app: AppHandle, path: String) -> CmdResult<WorkspaceManifest> {
+    if has_running_collectors() {
+        return Err(AppError::conflict("请先停止所有采集任务"));
+    }
+    if has_running_tile_tasks() {
+        return Err(AppError::conflict("请先停止所有瓦片下载任务"));
+    }
+
+    let file = File::open(&path).map_err(|e| format!("打开归档文件失败: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("读取归档失败: {}", e))?;
+
+    let manifest: WorkspaceManifest = {
+        let mut entry = archive
+            .by_name(MANIFEST_FILE)
+            .map_err(|_| "归档缺少清单文件，不是有效的工作区归档".to_string())?;
+        let mut content = String::new();
+        entry
+            .read_to_string(&mut content)
+            .map_err(|e| format!("读取清单失败: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("解析清单失败: {}", e))?
+    };
+
+    if manifest.format_version > WORKSPACE_FORMAT_VERSION {
+        return Err(AppError::invalid(format!(
+            "归档格式版本 {} 高于当前支持的版本 {}，请升级应用后再导入",
+            manifest.format_version, WORKSPACE_FORMAT_VERSION
+        )));
+    }
+
+    // 所有检查都在替换任何文件之前完成
+    let config_files = workspace_config_files();
+    let config_names: Vec<&str> = config_files.iter().map(|(name, _)| *name).collect();
+    for name in &manifest.files {
+        check_entry_name(name, &config_names)?;
+    }
+
+    // 先解压到临时目录，全部成功后再替换；临时文件按序号命名，不使用归档内的文件名
+    let temp = TempDir::new()?;
+    let mut extracted: Vec<(String, PathBuf)> = Vec::new();
+    for (index, name) in manifest.files.iter().enumerate() {
+        let mut entry = archive
+            .by_name(name)
+            .map_err(|e| format!("归档缺少文件 {}: {}", name, e))?;
+        let mut data = Vec::new();
+        entry
+            .read_to_end(&mut data)
+            .map_err(|e| format!("读取 {} 失败: {}", name, e))?;
+
+        let target = temp.0.join(format!("entry-{}", index));
+        std::fs::write(&target, data).map_err(|e| format!("解压 {} 失败: {}", name, e))?;
+        extracted.push((name.clone(), target));
+    }
+
+    for (name, source) in &extracted {
+        match name.as_str() {
+            POI_DB_FILE => with_poi_db_closed(|dest| replace_db_file(source, dest))?,
+            TILE_DB_FILE => with_tile_db_closed(&app, |dest| replace_db_file(source, dest))?,
+            _ => {
+                let config_name = name.trim_start_matches(CONFIG_PREFIX);
+                if let Some((_, dest)) = config_files.iter().find(|(n, _)| *n == config_name) {
+                    std::fs::copy(source, dest)
+                        .map_err(|e| format!("恢复配置 {} 失败: {}", config_name, e))?;
+                }
+            }
+        }
+    }
+
+    log::info!(
+        "导入工作区: {} (创建于 {}, 版本 {})",
+        path,
+        manifest.created_at,
+        manifest.app_version
+    );
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_entry_name() {
+        let config_names = ["region_config.json"];
+        assert!(check_entry_name(POI_DB_FILE, &config_names).is_ok());
+        assert!(check_entry_name(TILE_DB_FILE, &config_names).is_ok());
+        assert!(check_entry_name("config/region_config.json", &config_names).is_ok());
+        assert!(check_entry_name("config/other.json", &config_names).is_err());
+        assert!(check_entry_name("config/../poi_data.db", &config_names).is_err());
+        assert!(check_entry_name("..\\..\\x", &config_names).is_err());
+        assert!(check_entry_name("region_config.json", &config_names).is_err());
+    }
+}
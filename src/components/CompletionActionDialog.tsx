@@ -0,0 +1,87 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { Power } from 'lucide-react';
+import { Button } from '@/components/ui/button';
+import {
+    Dialog,
+    DialogContent,
+    DialogDescription,
+    DialogFooter,
+    DialogHeader,
+    DialogTitle,
+} from '@/components/ui/dialog';
+
+export type CompletionAction = 'none' | 'exit' | 'sleep' | 'shutdown';
+
+export const completionActionNames: Record<CompletionAction, string> = {
+    none: '无操作',
+    exit: '退出应用',
+    sleep: '系统休眠',
+    shutdown: '关机',
+};
+
+interface PendingAction {
+    action: CompletionAction;
+    task_name: string;
+    remaining_secs: number;
+    waiting: boolean;
+}
+
+/** 任务完成后动作的倒计时提示，可在倒计时结束前取消 */
+export function CompletionActionDialog() {
+    const [pending, setPending] = useState<PendingAction | null>(null);
+
+    useEffect(() => {
+        invoke<PendingAction | null>('get_pending_completion_action').then(setPending);
+        const unlisten = listen<PendingAction | null>('completion-action', (event) => {
+            setPending(event.payload);
+        });
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, []);
+
+    const handleCancel = async () => {
+        try {
+            await invoke('cancel_completion_action');
+        } catch (e) {
+            console.error('取消失败:', e);
+        }
+        setPending(null);
+    };
+
+    if (!pending) return null;
+    const actionName = completionActionNames[pending.action];
+
+    return (
+        <Dialog open onOpenChange={(open) => !open && handleCancel()}>
+            <DialogContent className="max-w-sm">
+                <DialogHeader>
+                    <DialogTitle className="flex items-center gap-2">
+                        <Power className="w-5 h-5 text-primary" />
+                        即将{actionName}
+                    </DialogTitle>
+                    <DialogDescription>任务「{pending.task_name}」已完成</DialogDescription>
+                </DialogHeader>
+                <div className="py-4 text-center">
+                    {pending.waiting ? (
+                        <p className="text-sm text-muted-foreground">
+                            仍有其他任务运行中，全部结束后开始倒计时
+                        </p>
+                    ) : (
+                        <>
+                            <div className="text-4xl font-bold tabular-nums">{pending.remaining_secs}</div>
+                            <p className="text-sm text-muted-foreground mt-1">秒后{actionName}</p>
+                        </>
+                    )}
+                </div>
+                <DialogFooter>
+                    <Button variant="outline" onClick={handleCancel}>
+                        取消{actionName}
+                    </Button>
+                </DialogFooter>
+            </DialogContent>
+        </Dialog>
+    );
+}
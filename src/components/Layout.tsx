@@ -12,6 +12,7 @@ import {
 import { Button } from '@/components/ui/button';
 import { ThemeToggle } from '@/components/theme-toggle';
 import { SettingsDialog } from '@/components/SettingsDialog';
+import { CompletionActionDialog } from '@/components/CompletionActionDialog';
 import { cn } from '@/lib/utils';
 
 const navItems = [
@@ -108,6 +109,9 @@ export default function Layout() {
 
             {/* Settings Dialog */}
             <SettingsDialog open={settingsOpen} onOpenChange={setSettingsOpen} />
+
+            {/* 任务完成后动作倒计时 */}
+            <CompletionActionDialog />
         </div>
     );
 }
@@ -90,6 +90,10 @@ const platformColors: Record<string, string> = {
     tianditu: '#06b6d4', // cyan
     amap: '#6366f1', // indigo
     baidu: '#ef4444', // red
+    google: '#f59e0b', // amber
+    bing: '#14b8a6', // teal
+    here: '#0ea5e9', // sky
+    mapbox: '#8b5cf6', // violet
 };
 
 // 创建自定义彩色图标
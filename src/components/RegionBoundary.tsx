@@ -2,6 +2,7 @@ import { useEffect, useState, useCallback } from 'react';
 import { GeoJSON, useMap } from 'react-leaflet';
 import { invoke } from '@tauri-apps/api/core';
 import L from 'leaflet';
+import { errorMessage } from '@/lib/utils';
 
 interface RegionBounds {
     north: number;
@@ -20,6 +21,8 @@ interface RegionBoundaryProps {
     onBoundsExtracted?: (bounds: RegionBounds) => void;
     fitBounds?: boolean;
     color?: string;
+    /** 边界简化容差（度），缺省约 50 米，传 0 使用原始边界 */
+    tolerance?: number;
 }
 
 export function RegionBoundary({
@@ -27,6 +30,7 @@ export function RegionBoundary({
     onBoundsExtracted,
     fitBounds = true,
     color = '#ef4444',
+    tolerance = 0.0005,
 }: RegionBoundaryProps) {
     const map = useMap();
     const [geoJson, setGeoJson] = useState<GeoJSON.GeoJsonObject | null>(null);
@@ -41,6 +45,7 @@ export function RegionBoundary({
         try {
             const result = await invoke<BoundaryResult>('get_region_boundary', {
                 regionCode: code,
+                tolerance: tolerance > 0 ? tolerance : null,
             });
 
             setGeoJson(result.geojson);
@@ -60,12 +65,12 @@ export function RegionBoundary({
             }
         } catch (err) {
             console.error('加载行政区边界失败:', err);
-            setError(err as string);
+            setError(errorMessage(err));
             setGeoJson(null);
         } finally {
             setLoading(false);
         }
-    }, [map, fitBounds, onBoundsExtracted]);
+    }, [map, fitBounds, onBoundsExtracted, tolerance]);
 
     useEffect(() => {
         if (regionCode) {
@@ -36,6 +36,10 @@ const platforms = [
     { id: 'tianditu', name: '天地图', url: 'https://console.tianditu.gov.cn' },
     { id: 'amap', name: '高德地图', url: 'https://console.amap.com' },
     { id: 'baidu', name: '百度地图', url: 'https://lbsyun.baidu.com' },
+    { id: 'google', name: 'Google Places', url: 'https://console.cloud.google.com/google/maps-apis' },
+    { id: 'bing', name: 'Bing Maps', url: 'https://www.bingmapsportal.com' },
+    { id: 'here', name: 'HERE', url: 'https://platform.here.com' },
+    { id: 'mapbox', name: 'Mapbox', url: 'https://account.mapbox.com' },
 ];
 
 const menuItems = [
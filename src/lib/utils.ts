@@ -4,3 +4,34 @@ import { twMerge } from "tailwind-merge"
 export function cn(...inputs: ClassValue[]) {
     return twMerge(clsx(inputs))
 }
+
+/** 后端命令返回的错误 */
+export interface AppError {
+    code: "invalid_argument" | "not_found" | "conflict" | "quota" | "network" | "io" | "database" | "locked" | "internal"
+    message: string
+}
+
+const ERROR_HINTS: Record<AppError["code"], string> = {
+    invalid_argument: "参数有误，请检查输入",
+    not_found: "目标不存在或已被删除",
+    conflict: "当前状态不允许该操作",
+    quota: "配额或今日预算已用尽，请更换 Key 或次日再试",
+    network: "网络请求失败，请检查网络或稍后重试",
+    io: "文件读写失败，请检查路径与权限",
+    database: "数据库操作失败",
+    locked: "请在设置中输入数据库密码解锁",
+    internal: "操作失败",
+}
+
+export function isAppError(e: unknown): e is AppError {
+    return typeof e === "object" && e !== null && "code" in e && "message" in e
+}
+
+/** 提取错误信息，后端错误按错误码附带处理建议 */
+export function errorMessage(e: unknown): string {
+    if (isAppError(e)) {
+        const hint = ERROR_HINTS[e.code]
+        return hint && e.code !== "internal" ? `${e.message}（${hint}）` : e.message
+    }
+    return e instanceof Error ? e.message : String(e)
+}
@@ -1,13 +1,14 @@
-import { useEffect, useState, useMemo } from 'react';
+import { useEffect, useState, useMemo, useRef, type ChangeEvent } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import { Play, Pause, Square, RotateCcw, Loader2, MapPin, Settings2, Globe, Map, Navigation, MapPinned, Terminal } from 'lucide-react';
+import { Play, Pause, Square, RotateCcw, Loader2, MapPin, Settings2, Globe, Map, Navigation, MapPinned, Terminal, Compass, KeyRound, Locate, Radar, Layers } from 'lucide-react';
 import { Button } from '@/components/ui/button';
 import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
 import { SettingsDialog } from '@/components/SettingsDialog';
 import { CategoryConfigDialog } from '@/components/CategoryConfigDialog';
 import { useToast } from '@/components/ui/toast';
 import SimpleBar from 'simplebar-react';
+import { errorMessage } from '@/lib/utils';
 
 interface SelectedRegion {
     code: string;
@@ -22,6 +23,19 @@ interface CollectorStatus {
     completed_categories: string[];
     current_category_id: string;
     error_message?: string;
+    error_kind?: string | null;
+    suggested_pause_secs?: number | null;
+    requests_per_minute?: number;
+    pois_per_minute?: number;
+    last_message?: string | null;
+}
+
+interface UnfinishedCollection {
+    platform: string;
+    status: string;
+    total_collected: number;
+    completed_categories: string[];
+    heartbeat_at?: string;
 }
 
 interface Category {
@@ -33,9 +47,13 @@ interface Category {
 // Platform configuration with metadata
 const platforms = [
     { id: 'tianditu', name: '天地图', needsApiKey: true, icon: MapPinned, gradient: 'from-cyan-500 to-cyan-600', bgGradient: 'from-cyan-500/10 to-cyan-600/5' },
-    { id: 'amap', name: '高德地图', needsApiKey: true, icon: Map, gradient: 'from-indigo-500 to-indigo-600', bgGradient: 'from-indigo-500/10 to-indigo-600/5' },
-    { id: 'baidu', name: '百度地图', needsApiKey: true, icon: Navigation, gradient: 'from-red-500 to-red-600', bgGradient: 'from-red-500/10 to-red-600/5' },
+    { id: 'amap', name: '高德地图', needsApiKey: true, supportsDetail: true, icon: Map, gradient: 'from-indigo-500 to-indigo-600', bgGradient: 'from-indigo-500/10 to-indigo-600/5' },
+    { id: 'baidu', name: '百度地图', needsApiKey: true, supportsDetail: true, icon: Navigation, gradient: 'from-red-500 to-red-600', bgGradient: 'from-red-500/10 to-red-600/5' },
     { id: 'osm', name: 'OpenStreetMap', needsApiKey: false, icon: Globe, gradient: 'from-emerald-500 to-emerald-600', bgGradient: 'from-emerald-500/10 to-emerald-600/5' },
+    { id: 'google', name: 'Google Places', needsApiKey: true, icon: Compass, gradient: 'from-amber-500 to-amber-600', bgGradient: 'from-amber-500/10 to-amber-600/5' },
+    { id: 'bing', name: 'Bing Maps', needsApiKey: true, icon: Locate, gradient: 'from-teal-500 to-teal-600', bgGradient: 'from-teal-500/10 to-teal-600/5' },
+    { id: 'here', name: 'HERE', needsApiKey: true, icon: Radar, gradient: 'from-sky-500 to-sky-600', bgGradient: 'from-sky-500/10 to-sky-600/5' },
+    { id: 'mapbox', name: 'Mapbox', needsApiKey: true, icon: Layers, gradient: 'from-violet-500 to-violet-600', bgGradient: 'from-violet-500/10 to-violet-600/5' },
 ];
 
 const platformNames: Record<string, string> = Object.fromEntries(
@@ -56,6 +74,16 @@ export default function Collector() {
     const [selectedCategories, setSelectedCategories] = useState<Record<string, string[]>>({});
     const [logs, setLogs] = useState<string[]>([]);
     const [selectedRegions, setSelectedRegions] = useState<SelectedRegion[]>([]);
+    // 采集第一个地区时排除的下属区域
+    const [childRegions, setChildRegions] = useState<{ code: string; name: string }[]>([]);
+    const [excludedCodes, setExcludedCodes] = useState<string[]>([]);
+    // 采集完成后补全详情（营业时间、评分、照片）的平台
+    const [enrichPlatforms, setEnrichPlatforms] = useState<Record<string, boolean>>({});
+    // 多边形采集范围（GeoJSON 面要素，WGS84），范围外的结果不入库
+    const [searchPolygon, setSearchPolygon] = useState<{ name: string; geojson: unknown } | null>(null);
+    const polygonInputRef = useRef<HTMLInputElement>(null);
+    // 周边范围（中心点 WGS84 与半径），三项都填写时生效
+    const [around, setAround] = useState({ lon: '', lat: '', radius: '' });
     const [categoryDialogPlatform, setCategoryDialogPlatform] = useState<string | null>(null);
     const [showSettings, setShowSettings] = useState(false);
     const [apiKeys, setApiKeys] = useState<Record<string, { id: number; api_key: string }[]>>({});
@@ -68,15 +96,36 @@ export default function Collector() {
         } catch (e) { console.error(e); }
     }, []);
 
+    useEffect(() => {
+        checkUnfinished();
+    }, []);
+
+    useEffect(() => {
+        setExcludedCodes([]);
+        const first = selectedRegions[0];
+        if (!first || first.level === 'district') {
+            setChildRegions([]);
+            return;
+        }
+        invoke<{ code: string; name: string }[]>('get_region_children', { parentCode: first.code })
+            .then(setChildRegions)
+            .catch((e) => console.error('加载下属区域失败:', e));
+    }, [selectedRegions[0]?.code]);
+
     useEffect(() => {
         loadData();
         const interval = setInterval(loadStatuses, 2000);
         const unlisten = listen<string>('collector-log', (event) => {
             setLogs(prev => [...prev.slice(-99), event.payload]);
         });
+        // 节流模式下日志按批推送
+        const unlistenBatch = listen<string[]>('collector-log-batch', (event) => {
+            setLogs(prev => [...prev, ...event.payload].slice(-100));
+        });
         return () => {
             clearInterval(interval);
             unlisten.then(fn => fn());
+            unlistenBatch.then(fn => fn());
         };
     }, []);
 
@@ -98,6 +147,28 @@ export default function Collector() {
         } catch (e) { console.error(e); }
     };
 
+    // 崩溃或异常退出后提示继续上次未完成的采集
+    const checkUnfinished = async () => {
+        try {
+            const sessions = await invoke<UnfinishedCollection[]>('get_unfinished_collections');
+            for (const s of sessions) {
+                const name = platformNames[s.platform] || s.platform;
+                const resume = confirm(
+                    `检测到未完成的采集：${name}（已采集 ${s.total_collected} 条，完成 ${s.completed_categories.length} 个类别），是否继续？`
+                );
+                if (resume) {
+                    await invoke('resume_unfinished_collection', { platform: s.platform });
+                    success('继续采集', `${name} 已从上次进度继续采集`);
+                } else {
+                    await invoke('discard_unfinished_collection', { platform: s.platform });
+                }
+            }
+            loadStatuses();
+        } catch (e: unknown) {
+            showError('恢复采集失败', errorMessage(e));
+        }
+    };
+
     const loadStatuses = async () => {
         try {
             const data = await invoke<Record<string, CollectorStatus>>('get_collector_statuses');
@@ -135,11 +206,28 @@ export default function Collector() {
                 platform,
                 categories: selectedCategories[platform],
                 regions: selectedRegions.map(r => r.code),
+                excludeCodes: excludedCodes.length > 0 ? excludedCodes : null,
+                enrich: enrichPlatforms[platform] ?? null,
+                polygon: searchPolygon?.geojson ?? null,
+                around: around.lon && around.lat && around.radius
+                    ? { lon: Number(around.lon), lat: Number(around.lat), radius: Number(around.radius) }
+                    : null,
             });
             success('开始采集', `${platformNames[platform]} 已开始采集`);
             loadStatuses();
         } catch (e: unknown) {
-            showError('采集失败', String(e));
+            showError('采集失败', errorMessage(e));
+        }
+    };
+
+    const importPolygon = async (e: ChangeEvent<HTMLInputElement>) => {
+        const file = e.target.files?.[0];
+        e.target.value = '';
+        if (!file) return;
+        try {
+            setSearchPolygon({ name: file.name, geojson: JSON.parse(await file.text()) });
+        } catch (err) {
+            showError('导入多边形失败', errorMessage(err));
         }
     };
 
@@ -151,6 +239,16 @@ export default function Collector() {
         } catch (e) { console.error(e); }
     };
 
+    // 立即换用其他可用 Key，采集中新增的 Key 无需重启即可使用
+    const switchKey = async (platform: string) => {
+        try {
+            const key = await invoke<{ id: number; name: string }>('switch_collector_key', { platform });
+            success('已切换 Key', `${platformNames[platform]} 将改用 Key ${key.name || `#${key.id}`}`);
+        } catch (e) {
+            showError('切换失败', errorMessage(e));
+        }
+    };
+
     const fullStopCollector = async (platform: string) => {
         if (!confirm('停止后需要从头开始采集，确定要停止吗？')) return;
         try {
@@ -243,6 +341,80 @@ export default function Collector() {
                                     管理地区
                                 </Button>
                             </div>
+                            {childRegions.length > 0 && (
+                                <div className="mt-3 flex flex-wrap items-center gap-1.5 text-xs">
+                                    <span className="text-muted-foreground mr-1">排除 {selectedRegions[0].name} 下属区域</span>
+                                    {childRegions.map(child => {
+                                        const excluded = excludedCodes.includes(child.code);
+                                        return (
+                                            <button
+                                                key={child.code}
+                                                onClick={() => setExcludedCodes(prev =>
+                                                    excluded ? prev.filter(c => c !== child.code) : [...prev, child.code]
+                                                )}
+                                                className={`px-2 py-0.5 rounded-full border transition-colors ${excluded
+                                                    ? 'border-destructive/50 bg-destructive/10 text-destructive line-through'
+                                                    : 'border-border text-muted-foreground hover:border-primary/50'
+                                                    }`}
+                                            >
+                                                {child.name}
+                                            </button>
+                                        );
+                                    })}
+                                </div>
+                            )}
+                            <div className="mt-3 flex items-center gap-2 text-xs">
+                                <span className="text-muted-foreground">多边形范围</span>
+                                {searchPolygon ? (
+                                    <>
+                                        <span className="px-2 py-0.5 rounded-full border border-primary/50 bg-primary/10 text-primary">
+                                            {searchPolygon.name}
+                                        </span>
+                                        <button
+                                            onClick={() => setSearchPolygon(null)}
+                                            className="text-muted-foreground hover:text-destructive"
+                                        >
+                                            清除
+                                        </button>
+                                    </>
+                                ) : (
+                                    <button
+                                        onClick={() => polygonInputRef.current?.click()}
+                                        className="px-2 py-0.5 rounded-full border border-border text-muted-foreground hover:border-primary/50"
+                                        title="导入 GeoJSON 面要素（WGS84），只采集多边形内的 POI"
+                                    >
+                                        导入 GeoJSON
+                                    </button>
+                                )}
+                                <input
+                                    ref={polygonInputRef}
+                                    type="file"
+                                    accept=".geojson,.json"
+                                    className="hidden"
+                                    onChange={importPolygon}
+                                />
+                            </div>
+                            <div className="mt-2 flex flex-wrap items-center gap-2 text-xs">
+                                <span className="text-muted-foreground" title="采集中心点周边指定半径内的 POI，坐标为 WGS84">周边范围</span>
+                                {(['lon', 'lat', 'radius'] as const).map(field => (
+                                    <input
+                                        key={field}
+                                        type="number"
+                                        placeholder={{ lon: '经度', lat: '纬度', radius: '半径（米）' }[field]}
+                                        value={around[field]}
+                                        onChange={e => setAround(prev => ({ ...prev, [field]: e.target.value }))}
+                                        className="w-24 h-7 px-2 rounded-md border border-input bg-background text-foreground"
+                                    />
+                                ))}
+                                {(around.lon || around.lat || around.radius) && (
+                                    <button
+                                        onClick={() => setAround({ lon: '', lat: '', radius: '' })}
+                                        className="text-muted-foreground hover:text-destructive"
+                                    >
+                                        清除
+                                    </button>
+                                )}
+                            </div>
                         </CardContent>
                     </Card>
 
@@ -302,6 +474,14 @@ export default function Collector() {
                                                 <span>{status.completed_categories?.length || 0} / {categories.length} 类别</span>
                                                 <span>已采集: {status.total_collected?.toLocaleString() || 0}</span>
                                             </div>
+                                            {isRunning && (
+                                                <div className="mt-1 space-y-0.5 text-xs text-muted-foreground">
+                                                    <div>{status.requests_per_minute ?? 0} 次请求/分 · 新增 {status.pois_per_minute ?? 0} 条/分</div>
+                                                    {status.last_message && (
+                                                        <div className="truncate" title={status.last_message}>{status.last_message}</div>
+                                                    )}
+                                                </div>
+                                            )}
                                         </div>
 
                                         {/* 类别配置 */}
@@ -318,6 +498,22 @@ export default function Collector() {
                                             </span>
                                         </button>
 
+                                        {/* 详情补全 */}
+                                        {platformConfig.supportsDetail && (
+                                            <label
+                                                className="flex items-center gap-2 text-xs text-muted-foreground cursor-pointer"
+                                                title="采集完成后调用详情接口补全营业时间、评分与照片，额外消耗请求额度"
+                                            >
+                                                <input
+                                                    type="checkbox"
+                                                    checked={enrichPlatforms[platform] || false}
+                                                    disabled={isRunning}
+                                                    onChange={e => setEnrichPlatforms(prev => ({ ...prev, [platform]: e.target.checked }))}
+                                                />
+                                                补全详情
+                                            </label>
+                                        )}
+
                                         {/* 操作按钮 */}
                                         <div className="flex gap-2">
                                             {status.status === 'running' ? (
@@ -331,6 +527,16 @@ export default function Collector() {
                                                         <Pause className="w-4 h-4 mr-2" />
                                                         暂停
                                                     </Button>
+                                                    {platformConfig.needsApiKey && (
+                                                        <Button
+                                                            variant="outline"
+                                                            size="icon"
+                                                            title="立即切换 Key"
+                                                            onClick={() => switchKey(platform)}
+                                                        >
+                                                            <KeyRound className="w-4 h-4" />
+                                                        </Button>
+                                                    )}
                                                     <Button
                                                         variant="destructive"
                                                         onClick={() => fullStopCollector(platform)}
@@ -382,6 +588,11 @@ export default function Collector() {
                                         {status.error_message && (
                                             <div className="p-3 bg-destructive/10 border border-destructive/30 rounded-xl text-destructive text-sm">
                                                 {status.error_message}
+                                                {status.suggested_pause_secs && (
+                                                    <div className="mt-1 text-xs opacity-80">
+                                                        建议约 {Math.ceil(status.suggested_pause_secs / 60)} 分钟后点击继续
+                                                    </div>
+                                                )}
                                             </div>
                                         )}
                                     </CardContent>
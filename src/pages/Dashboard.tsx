@@ -2,6 +2,7 @@ import { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { Database, MapPin, Globe, BarChart3, Loader2, Map, TrendingUp } from 'lucide-react';
 import { Card, CardContent, CardHeader, CardTitle, CardDescription } from '@/components/ui/card';
+import { Button } from '@/components/ui/button';
 import SimpleBar from 'simplebar-react';
 
 interface Stats {
@@ -10,6 +11,11 @@ interface Stats {
     by_category: Record<string, number>;
 }
 
+interface SelectedRegion {
+    code: string;
+    name: string;
+}
+
 interface Region {
     code: string;
     name: string;
@@ -22,6 +28,10 @@ const platformConfig: Record<string, { name: string; color: string; gradient: st
     amap: { name: '高德地图', color: 'text-indigo-400', gradient: 'from-indigo-500 to-indigo-600' },
     baidu: { name: '百度地图', color: 'text-red-400', gradient: 'from-red-500 to-red-600' },
     osm: { name: 'OSM', color: 'text-emerald-400', gradient: 'from-emerald-500 to-emerald-600' },
+    google: { name: 'Google', color: 'text-amber-400', gradient: 'from-amber-500 to-amber-600' },
+    bing: { name: 'Bing', color: 'text-teal-400', gradient: 'from-teal-500 to-teal-600' },
+    here: { name: 'HERE', color: 'text-sky-400', gradient: 'from-sky-500 to-sky-600' },
+    mapbox: { name: 'Mapbox', color: 'text-violet-400', gradient: 'from-violet-500 to-violet-600' },
 };
 
 export default function Dashboard() {
@@ -29,15 +39,28 @@ export default function Dashboard() {
     const [regionStats, setRegionStats] = useState<[string, number][]>([]);
     const [regionNames, setRegionNames] = useState<Record<string, string>>({});
     const [loading, setLoading] = useState(true);
+    // 工作地区：在地区页选择的地区，开启后只统计这些地区
+    const [workingRegions, setWorkingRegions] = useState<SelectedRegion[]>([]);
+    const [regionOnly, setRegionOnly] = useState(false);
 
     useEffect(() => {
-        loadStats();
+        try {
+            const saved = localStorage.getItem('poi_selected_regions');
+            if (saved) setWorkingRegions(JSON.parse(saved));
+        } catch (e) { console.error(e); }
         loadRegionStats();
     }, []);
 
+    useEffect(() => {
+        loadStats();
+    }, [regionOnly, workingRegions]);
+
     const loadStats = async () => {
         try {
-            const data = await invoke<Stats>('get_stats');
+            const regionCodes = regionOnly && workingRegions.length > 0
+                ? workingRegions.map(r => r.code)
+                : null;
+            const data = await invoke<Stats>('get_stats', { regionCodes });
             setStats(data);
         } catch (e) {
             console.error('加载统计失败:', e);
@@ -106,14 +129,27 @@ export default function Dashboard() {
                     <h1 className="text-2xl font-bold text-foreground">数据概览</h1>
                     <p className="text-muted-foreground">查看已采集的 POI 数据统计</p>
                 </div>
-                {stats && stats.total > 0 && (
-                    <div className="flex items-center gap-2 px-4 py-2 rounded-xl bg-primary/10 border border-primary/20">
-                        <TrendingUp className="w-4 h-4 text-primary" />
-                        <span className="text-sm font-medium text-primary">
-                            共 {stats.total.toLocaleString()} 条数据
-                        </span>
-                    </div>
-                )}
+                <div className="flex items-center gap-2">
+                    {workingRegions.length > 0 && (
+                        <Button
+                            variant={regionOnly ? 'default' : 'outline'}
+                            size="sm"
+                            onClick={() => setRegionOnly(!regionOnly)}
+                            title={workingRegions.map(r => r.name).join('、')}
+                        >
+                            <MapPin className="w-4 h-4 mr-1" />
+                            {regionOnly ? `仅看已选地区（${workingRegions.length}）` : '全部地区'}
+                        </Button>
+                    )}
+                    {stats && stats.total > 0 && (
+                        <div className="flex items-center gap-2 px-4 py-2 rounded-xl bg-primary/10 border border-primary/20">
+                            <TrendingUp className="w-4 h-4 text-primary" />
+                            <span className="text-sm font-medium text-primary">
+                                共 {stats.total.toLocaleString()} 条数据
+                            </span>
+                        </div>
+                    )}
+                </div>
             </div>
 
             {/* Scrollable content */}
@@ -5,6 +5,7 @@ import { Button } from '@/components/ui/button';
 import { Card, CardContent, CardHeader, CardTitle, CardDescription } from '@/components/ui/card';
 import { useToast } from '@/components/ui/toast';
 import SimpleBar from 'simplebar-react';
+import { errorMessage } from '@/lib/utils';
 
 interface Region {
     code: string;
@@ -90,7 +91,7 @@ export default function DataManagement() {
             setSelected(new Set());
             loadStats();
         } catch (e) {
-            showError('删除失败', String(e));
+            showError('删除失败', errorMessage(e));
         }
     };
 
@@ -105,7 +106,7 @@ export default function DataManagement() {
             success('删除成功', `已删除 ${count.toLocaleString()} 条数据`);
             loadStats();
         } catch (e) {
-            showError('删除失败', String(e));
+            showError('删除失败', errorMessage(e));
         }
     };
 
@@ -122,7 +123,7 @@ export default function DataManagement() {
             success('清空成功', `已删除 ${count.toLocaleString()} 条数据`);
             loadStats();
         } catch (e) {
-            showError('清空失败', String(e));
+            showError('清空失败', errorMessage(e));
         }
     };
 
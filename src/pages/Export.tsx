@@ -14,6 +14,8 @@ import {
   MapPin,
   Search,
   FolderTree,
+  PenTool,
+  Table2,
 } from "lucide-react";
 import SimpleBar from "simplebar-react";
 import { Button } from "@/components/ui/button";
@@ -27,6 +29,7 @@ import {
   DialogFooter,
 } from "@/components/ui/dialog";
 import { useToast } from "@/components/ui/toast";
+import { errorMessage } from "@/lib/utils";
 
 interface ExportPOI {
   id: number;
@@ -52,12 +55,20 @@ const platformNames: Record<string, string> = {
   tianditu: "天地图",
   amap: "高德地图",
   baidu: "百度地图",
+  google: "Google",
+  bing: "Bing",
+  here: "HERE",
+  mapbox: "Mapbox",
 };
 
 const platformColors: Record<string, string> = {
   tianditu: 'bg-cyan-500/20 text-cyan-500',
   amap: 'bg-indigo-500/20 text-indigo-500',
   baidu: 'bg-red-500/20 text-red-500',
+  google: 'bg-amber-500/20 text-amber-500',
+  bing: 'bg-teal-500/20 text-teal-500',
+  here: 'bg-sky-500/20 text-sky-500',
+  mapbox: 'bg-violet-500/20 text-violet-500',
 };
 
 const formats = [
@@ -71,6 +82,12 @@ const formats = [
   },
   { id: "json", icon: FileJson, label: "JSON", desc: ".json", ext: "json", gradient: "from-amber-500 to-amber-600" },
   { id: "mysql", icon: Database, label: "MySQL", desc: ".sql", ext: "sql", gradient: "from-blue-500 to-blue-600" },
+  { id: "sqlite", icon: Database, label: "SQLite", desc: ".sql", ext: "sql", gradient: "from-sky-500 to-sky-600" },
+  { id: "dameng", icon: Database, label: "达梦 DM8", desc: ".sql", ext: "sql", gradient: "from-red-500 to-red-600" },
+  { id: "kingbase", icon: Database, label: "人大金仓", desc: ".sql", ext: "sql", gradient: "from-indigo-500 to-indigo-600" },
+  { id: "parquet", icon: Table2, label: "Parquet", desc: ".parquet", ext: "parquet", gradient: "from-teal-500 to-teal-600" },
+  { id: "geojsonl", icon: MapPin, label: "GeoJSONL", desc: ".geojsonl", ext: "geojsonl", gradient: "from-lime-500 to-lime-600" },
+  { id: "dxf", icon: PenTool, label: "DXF (CAD)", desc: ".dxf", ext: "dxf", gradient: "from-violet-500 to-violet-600" },
 ];
 
 export default function Export() {
@@ -81,6 +98,16 @@ export default function Export() {
   const [showExportDialog, setShowExportDialog] = useState(false);
   const [format, setFormat] = useState("excel");
   const [exporting, setExporting] = useState(false);
+  // 新鲜度过滤：按最近复核时间区分新鲜/过期数据
+  const [freshness, setFreshness] = useState<"all" | "fresh" | "stale">("all");
+  // 标签过滤：只导出带有该标签的数据
+  const [tagNames, setTagNames] = useState<string[]>([]);
+  const [tagFilter, setTagFilter] = useState("");
+  // OSM 标签扩展列：采集时保留的标签，勾选的导出为列
+  const [osmTagOptions, setOsmTagOptions] = useState<string[]>([]);
+  const [osmColumns, setOsmColumns] = useState<string[]>([]);
+  // 附带来源声明（平台占比、采集时间、坐标系、许可提醒）
+  const [withMetadata, setWithMetadata] = useState(false);
 
   // 地区筛选
   const [provinces, setProvinces] = useState<Region[]>([]);
@@ -108,6 +135,12 @@ export default function Export() {
 
   useEffect(() => {
     loadProvinces();
+    invoke<{ name: string }[]>("list_poi_tags")
+      .then((tags) => setTagNames(tags.map((t) => t.name)))
+      .catch((e) => console.error("加载标签失败:", e));
+    invoke<{ extra_tags: string[] }>("get_osm_settings")
+      .then((settings) => setOsmTagOptions(settings.extra_tags))
+      .catch((e) => console.error("加载 OSM 设置失败:", e));
   }, []);
 
   // 当选择地区后加载数据
@@ -302,12 +335,16 @@ export default function Export() {
         format: format,
         platform: platform === "all" ? null : platform,
         ids: filteredIds,
+        freshness: freshness === "all" ? null : freshness,
+        tags: tagFilter ? [tagFilter] : null,
+        osmTags: osmColumns.length > 0 ? osmColumns : null,
+        withMetadata,
       });
 
       showSuccess("导出成功", `已导出 ${count.toLocaleString()} 条数据`);
       setShowExportDialog(false);
     } catch (e) {
-      showError("导出失败", String(e));
+      showError("导出失败", errorMessage(e));
     } finally {
       setExporting(false);
     }
@@ -638,6 +675,69 @@ export default function Export() {
             })}
           </div>
 
+          <div className="flex items-center gap-2 text-sm">
+            <span className="text-muted-foreground shrink-0">数据新鲜度</span>
+            <select
+              value={freshness}
+              onChange={(e) => setFreshness(e.target.value as "all" | "fresh" | "stale")}
+              className="flex-1 px-3 py-1.5 text-sm border border-input bg-background rounded-lg cursor-pointer focus:outline-none focus:ring-2 focus:ring-primary/50"
+            >
+              <option value="all">全部</option>
+              <option value="fresh">仅新鲜（过期天数内复核过）</option>
+              <option value="stale">仅过期（待复核）</option>
+            </select>
+          </div>
+
+          {tagNames.length > 0 && (
+            <div className="flex items-center gap-2 text-sm">
+              <span className="text-muted-foreground shrink-0">标签</span>
+              <select
+                value={tagFilter}
+                onChange={(e) => setTagFilter(e.target.value)}
+                className="flex-1 px-3 py-1.5 text-sm border border-input bg-background rounded-lg cursor-pointer focus:outline-none focus:ring-2 focus:ring-primary/50"
+              >
+                <option value="">全部</option>
+                {tagNames.map((name) => (
+                  <option key={name} value={name}>
+                    仅带「{name}」标签
+                  </option>
+                ))}
+              </select>
+            </div>
+          )}
+
+          {osmTagOptions.length > 0 && (platform === "all" || platform === "osm") && (
+            <div className="flex items-start gap-2 text-sm">
+              <span className="text-muted-foreground shrink-0 pt-0.5">OSM 标签列</span>
+              <div className="flex flex-wrap gap-x-3 gap-y-1">
+                {osmTagOptions.map((tag) => (
+                  <label key={tag} className="flex items-center gap-1 cursor-pointer">
+                    <input
+                      type="checkbox"
+                      checked={osmColumns.includes(tag)}
+                      onChange={(e) =>
+                        setOsmColumns((prev) =>
+                          e.target.checked ? [...prev, tag] : prev.filter((t) => t !== tag)
+                        )
+                      }
+                    />
+                    {tag}
+                  </label>
+                ))}
+              </div>
+            </div>
+          )}
+
+          <label className="flex items-center gap-2 text-sm cursor-pointer">
+            <input
+              type="checkbox"
+              checked={withMetadata}
+              onChange={(e) => setWithMetadata(e.target.checked)}
+            />
+            附带数据来源声明
+            <span className="text-xs text-muted-foreground">（平台占比、采集时间、坐标系与使用许可，另存为 README）</span>
+          </label>
+
           <DialogFooter>
             <Button
               variant="outline"
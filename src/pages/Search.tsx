@@ -1,18 +1,62 @@
-import { useState } from 'react';
+import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { Search as SearchIcon, MapPin, List, Columns, Loader2 } from 'lucide-react';
+import { Search as SearchIcon, MapPin, List, Columns, Loader2, GitCompare, Tag } from 'lucide-react';
 import { Button } from '@/components/ui/button';
 import { Card, CardContent } from '@/components/ui/card';
+import {
+    Dialog,
+    DialogContent,
+    DialogHeader,
+    DialogTitle,
+    DialogDescription,
+} from '@/components/ui/dialog';
 import POIMap, { POI } from '@/components/POIMap';
 import SimpleBar from 'simplebar-react';
 
 type ViewMode = 'list' | 'map' | 'split';
 
+interface MatchedPOI {
+    id: number;
+    name: string;
+    lon: number;
+    lat: number;
+    address: string;
+    phone: string;
+    category: string;
+    platform: string;
+    distance_m: number;
+    name_similarity: number;
+    score: number;
+}
+
+interface PoiMatches {
+    poi: Omit<MatchedPOI, 'distance_m' | 'name_similarity' | 'score'>;
+    radius_m: number;
+    platforms: { platform: string; candidates: MatchedPOI[] }[];
+}
+
+interface PoiTag {
+    id: number;
+    name: string;
+    color: string | null;
+    poi_count: number;
+}
+
+interface PoiTagLink {
+    name: string;
+    color: string | null;
+    note: string | null;
+}
+
 const platformNames: Record<string, string> = {
     all: '全部平台',
     tianditu: '天地图',
     amap: '高德',
     baidu: '百度',
+    google: 'Google',
+    bing: 'Bing',
+    here: 'HERE',
+    mapbox: 'Mapbox',
 };
 
 const platformColors: Record<string, string> = {
@@ -20,6 +64,10 @@ const platformColors: Record<string, string> = {
     amap: 'bg-indigo-500/20 text-indigo-500',
     baidu: 'bg-red-500/20 text-red-500',
     osm: 'bg-emerald-500/20 text-emerald-500',
+    google: 'bg-amber-500/20 text-amber-500',
+    bing: 'bg-teal-500/20 text-teal-500',
+    here: 'bg-sky-500/20 text-sky-500',
+    mapbox: 'bg-violet-500/20 text-violet-500',
 };
 
 const modeOptions = [
@@ -36,18 +84,66 @@ export default function Search() {
     const [loading, setLoading] = useState(false);
     const [viewMode, setViewMode] = useState<ViewMode>('split');
     const [selectedId, setSelectedId] = useState<number | null>(null);
+    const [matches, setMatches] = useState<PoiMatches | null>(null);
+    const [matchError, setMatchError] = useState<string | null>(null);
+    const [matchingId, setMatchingId] = useState<number | null>(null);
+    const [tags, setTags] = useState<PoiTag[]>([]);
+    const [tagFilter, setTagFilter] = useState('');
+    const [poiTags, setPoiTags] = useState<Record<number, PoiTagLink[]>>({});
+    const [tagInput, setTagInput] = useState('');
+    const [tagNote, setTagNote] = useState('');
+    const [tagging, setTagging] = useState(false);
+    const [tagMessage, setTagMessage] = useState<string | null>(null);
+
+    const loadTags = async () => {
+        try {
+            setTags(await invoke<PoiTag[]>('list_poi_tags'));
+        } catch (e) {
+            console.error('加载标签失败:', e);
+        }
+    };
+
+    const loadPoiTags = async (pois: POI[]) => {
+        if (pois.length === 0) {
+            setPoiTags({});
+            return;
+        }
+        try {
+            setPoiTags(await invoke<Record<number, PoiTagLink[]>>('get_poi_tags', {
+                ids: pois.map((p) => p.id),
+            }));
+        } catch (e) {
+            console.error('加载 POI 标签失败:', e);
+        }
+    };
+
+    useEffect(() => {
+        loadTags();
+    }, []);
 
     const handleSearch = async () => {
-        if (!query.trim()) return;
+        // 选择标签时可不输入关键词，列出带该标签的全部 POI
+        if (!query.trim() && !tagFilter) return;
 
         setLoading(true);
+        setTagMessage(null);
         try {
-            const data = await invoke<POI[]>('search_poi', {
-                query: query.trim(),
-                platform: platform === 'all' ? null : platform,
-                mode,
-            });
+            let data: POI[];
+            if (tagFilter) {
+                const keyword = query.trim().toLowerCase();
+                data = (await invoke<POI[]>('list_tagged_poi', {
+                    tags: [tagFilter],
+                    platform: platform === 'all' ? null : platform,
+                })).filter((poi) => !keyword || poi.name.toLowerCase().includes(keyword));
+            } else {
+                data = await invoke<POI[]>('search_poi', {
+                    query: query.trim(),
+                    platform: platform === 'all' ? null : platform,
+                    mode,
+                });
+            }
             setResults(data);
+            await loadPoiTags(data);
         } catch (e) {
             console.error('搜索失败:', e);
         } finally {
@@ -55,6 +151,38 @@ export default function Search() {
         }
     };
 
+    const handleTag = async (remove: boolean) => {
+        const tag = tagInput.trim();
+        if (!tag || results.length === 0) return;
+        setTagging(true);
+        setTagMessage(null);
+        try {
+            const ids = results.map((p) => p.id);
+            const count = remove
+                ? await invoke<number>('untag_pois', { ids, tag })
+                : await invoke<number>('tag_pois', { ids, tag, note: tagNote.trim() || null });
+            setTagMessage(remove ? `已移除 ${count} 条的标签「${tag}」` : `已为 ${count} 条新打上标签「${tag}」`);
+            await Promise.all([loadTags(), loadPoiTags(results)]);
+        } catch (e) {
+            setTagMessage(String(e));
+        } finally {
+            setTagging(false);
+        }
+    };
+
+    const handleCompare = async (id: number) => {
+        setMatchingId(id);
+        setMatchError(null);
+        try {
+            setMatches(await invoke<PoiMatches>('get_poi_matches', { id }));
+        } catch (e) {
+            setMatches(null);
+            setMatchError(String(e));
+        } finally {
+            setMatchingId(null);
+        }
+    };
+
     const handleMarkerClick = (poi: POI) => {
         setSelectedId(poi.id);
     };
@@ -113,6 +241,18 @@ export default function Search() {
                             ))}
                         </select>
 
+                        <select
+                            value={tagFilter}
+                            onChange={(e) => setTagFilter(e.target.value)}
+                            className="px-4 py-2.5 border border-input bg-background rounded-xl text-foreground
+                                     focus:outline-none focus:ring-2 focus:ring-primary/50 focus:border-primary cursor-pointer transition-all"
+                        >
+                            <option value="">全部标签</option>
+                            {tags.map((tag) => (
+                                <option key={tag.id} value={tag.name}>{tag.name} ({tag.poi_count})</option>
+                            ))}
+                        </select>
+
                         <Button onClick={handleSearch} disabled={loading} className="gradient-primary text-white border-0 hover:opacity-90 px-6">
                             {loading ? (
                                 <Loader2 className="w-4 h-4 animate-spin" />
@@ -156,10 +296,44 @@ export default function Search() {
                     </div>
 
                     {results.length > 0 && (
-                        <div className="mt-3 text-sm text-muted-foreground">
-                            找到 <span className="font-medium text-primary">{results.length}</span> 条结果
+                        <div className="mt-3 flex items-center gap-3 text-sm text-muted-foreground">
+                            <span>
+                                找到 <span className="font-medium text-primary">{results.length}</span> 条结果
+                            </span>
+                            <div className="ml-auto flex items-center gap-2">
+                                <Tag className="w-4 h-4" />
+                                <input
+                                    type="text"
+                                    list="poi-tag-options"
+                                    value={tagInput}
+                                    onChange={(e) => setTagInput(e.target.value)}
+                                    placeholder="标签，如 已实地核实"
+                                    className="w-40 px-3 py-1.5 border border-input bg-background rounded-lg text-foreground
+                                             placeholder:text-muted-foreground focus:outline-none focus:ring-2 focus:ring-primary/50"
+                                />
+                                <datalist id="poi-tag-options">
+                                    {tags.map((tag) => (
+                                        <option key={tag.id} value={tag.name} />
+                                    ))}
+                                </datalist>
+                                <input
+                                    type="text"
+                                    value={tagNote}
+                                    onChange={(e) => setTagNote(e.target.value)}
+                                    placeholder="备注（可选）"
+                                    className="w-40 px-3 py-1.5 border border-input bg-background rounded-lg text-foreground
+                                             placeholder:text-muted-foreground focus:outline-none focus:ring-2 focus:ring-primary/50"
+                                />
+                                <Button size="sm" variant="outline" disabled={tagging || !tagInput.trim()} onClick={() => handleTag(false)}>
+                                    为结果打标
+                                </Button>
+                                <Button size="sm" variant="ghost" disabled={tagging || !tagInput.trim()} onClick={() => handleTag(true)}>
+                                    移除
+                                </Button>
+                            </div>
                         </div>
                     )}
+                    {tagMessage && <div className="mt-2 text-xs text-muted-foreground">{tagMessage}</div>}
                 </CardContent>
             </Card>
 
@@ -200,8 +374,33 @@ export default function Search() {
                                                                 {poi.category}
                                                             </span>
                                                         )}
+                                                        {poiTags[poi.id]?.map((tag) => (
+                                                            <span
+                                                                key={tag.name}
+                                                                className="text-xs px-2 py-0.5 rounded-full bg-primary/10 text-primary"
+                                                                style={tag.color ? { backgroundColor: `${tag.color}22`, color: tag.color } : undefined}
+                                                                title={tag.note || undefined}
+                                                            >
+                                                                {tag.name}
+                                                            </span>
+                                                        ))}
                                                     </div>
                                                 </div>
+                                                <button
+                                                    onClick={(e) => {
+                                                        e.stopPropagation();
+                                                        handleCompare(poi.id);
+                                                    }}
+                                                    disabled={matchingId !== null}
+                                                    className="p-1.5 rounded-lg text-muted-foreground hover:text-primary hover:bg-accent transition-all cursor-pointer shrink-0"
+                                                    title="跨平台对比"
+                                                >
+                                                    {matchingId === poi.id ? (
+                                                        <Loader2 className="w-4 h-4 animate-spin" />
+                                                    ) : (
+                                                        <GitCompare className="w-4 h-4" />
+                                                    )}
+                                                </button>
                                             </div>
                                         </div>
                                     ))}
@@ -234,6 +433,75 @@ export default function Search() {
                     </Card>
                 )}
             </div>
+
+            {/* 跨平台对比 */}
+            <Dialog
+                open={matches !== null || matchError !== null}
+                onOpenChange={(open) => {
+                    if (!open) {
+                        setMatches(null);
+                        setMatchError(null);
+                    }
+                }}
+            >
+                <DialogContent className="max-w-4xl">
+                    <DialogHeader>
+                        <DialogTitle className="flex items-center gap-2">
+                            <GitCompare className="w-5 h-5" />
+                            跨平台对比
+                        </DialogTitle>
+                        <DialogDescription>
+                            {matches
+                                ? `「${matches.poi.name}」（${platformNames[matches.poi.platform] || matches.poi.platform}）周边 ${matches.radius_m} 米内名称相似的记录`
+                                : '查询失败'}
+                        </DialogDescription>
+                    </DialogHeader>
+
+                    {matchError && <p className="text-sm text-destructive">{matchError}</p>}
+
+                    {matches && (
+                        <div className="grid gap-3" style={{ gridTemplateColumns: `repeat(${matches.platforms.length + 1}, minmax(0, 1fr))` }}>
+                            <div className="space-y-2">
+                                <div className={`text-xs px-2 py-0.5 rounded-full inline-block ${platformColors[matches.poi.platform] || 'bg-muted text-muted-foreground'}`}>
+                                    {platformNames[matches.poi.platform] || matches.poi.platform}
+                                </div>
+                                <div className="p-3 rounded-lg border border-primary/50 bg-primary/5 text-sm space-y-1">
+                                    <div className="font-medium text-foreground">{matches.poi.name}</div>
+                                    <div className="text-muted-foreground">{matches.poi.address || '无地址'}</div>
+                                    {matches.poi.phone && <div className="text-muted-foreground">{matches.poi.phone}</div>}
+                                    <div className="text-xs text-muted-foreground">{matches.poi.category}</div>
+                                </div>
+                            </div>
+                            {matches.platforms.map(({ platform, candidates }) => (
+                                <div key={platform} className="space-y-2">
+                                    <div className={`text-xs px-2 py-0.5 rounded-full inline-block ${platformColors[platform] || 'bg-muted text-muted-foreground'}`}>
+                                        {platformNames[platform] || platform}
+                                    </div>
+                                    {candidates.length === 0 ? (
+                                        <div className="p-3 rounded-lg border border-dashed border-border text-sm text-muted-foreground">
+                                            无匹配记录
+                                        </div>
+                                    ) : (
+                                        candidates.map((c, index) => (
+                                            <div
+                                                key={c.id}
+                                                className={`p-3 rounded-lg border text-sm space-y-1 ${index === 0 ? 'border-border' : 'border-border/50 opacity-70'}`}
+                                            >
+                                                <div className="font-medium text-foreground">{c.name}</div>
+                                                <div className="text-muted-foreground">{c.address || '无地址'}</div>
+                                                {c.phone && <div className="text-muted-foreground">{c.phone}</div>}
+                                                <div className="text-xs text-muted-foreground">
+                                                    距离 {c.distance_m.toFixed(0)} 米 · 名称相似度 {(c.name_similarity * 100).toFixed(0)}%
+                                                </div>
+                                            </div>
+                                        ))
+                                    )}
+                                </div>
+                            ))}
+                        </div>
+                    )}
+                </DialogContent>
+            </Dialog>
         </div>
     );
 }
@@ -1,8 +1,9 @@
 import { useEffect, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { Key, Plus, Trash2, Eye, EyeOff, Loader2, Shield, ExternalLink } from 'lucide-react';
+import { Key, Plus, Trash2, Eye, EyeOff, Loader2, Shield, ExternalLink, Activity, Archive, Lock, Moon, Globe, RotateCcw } from 'lucide-react';
 import { Button } from '@/components/ui/button';
 import { Card, CardContent, CardHeader, CardTitle, CardDescription } from '@/components/ui/card';
+import { errorMessage } from '@/lib/utils';
 
 interface ApiKey {
     id: number;
@@ -10,20 +11,93 @@ interface ApiKey {
     api_key: string;
     is_active: boolean;
     quota_exhausted: boolean;
+    qps?: number | null;
 }
 
+type EventLevel = 'realtime' | 'throttled' | 'quiet';
+
+interface EventSettings {
+    level: EventLevel;
+    interval_ms: number;
+}
+
+const eventLevels: { id: EventLevel; name: string; hint: string }[] = [
+    { id: 'realtime', name: '实时', hint: '逐条推送，事件多时界面可能卡顿' },
+    { id: 'throttled', name: '节流', hint: '进度按间隔合并，日志批量推送' },
+    { id: 'quiet', name: '安静', hint: '仅推送状态变化，日志只保留最近 20 条' },
+];
+
+type RawDataMode = 'keep' | 'compress' | 'discard';
+
+interface RawDataSettings {
+    mode: RawDataMode;
+    max_bytes?: number | null;
+}
+
+interface CompactResult {
+    processed: number;
+    bytes_before: number;
+    bytes_after: number;
+}
+
+interface PowerStatus {
+    prevent_sleep: boolean;
+    inhibiting: boolean;
+}
+
+interface RestartPolicy {
+    enabled: boolean;
+    max_restarts: number;
+    base_delay_secs: number;
+}
+
+interface OsmSettings {
+    extra_tags: string[];
+}
+
+interface DatabaseEncryption {
+    supported: boolean;
+    encrypted: boolean;
+    locked: boolean;
+}
+
+const rawDataModes: { id: RawDataMode; name: string; hint: string }[] = [
+    { id: 'keep', name: '原样保存', hint: '保存平台返回的完整 JSON，便于事后重建类别' },
+    { id: 'compress', name: '压缩保存', hint: 'gzip 压缩后保存，体积约为原来的 1/4' },
+    { id: 'discard', name: '不保存', hint: '不保存原始响应，无法再按平台分类重建类别' },
+];
+
+const formatBytes = (bytes: number) => {
+    const mb = bytes / 1024 / 1024;
+    return mb < 1024 ? `${mb.toFixed(1)} MB` : `${(mb / 1024).toFixed(2)} GB`;
+};
+
 const platforms = [
     { id: 'tianditu', name: '天地图', hint: 'console.tianditu.gov.cn', gradient: 'from-cyan-500 to-cyan-600' },
     { id: 'amap', name: '高德地图', hint: 'console.amap.com', gradient: 'from-indigo-500 to-indigo-600' },
     { id: 'baidu', name: '百度地图', hint: 'lbsyun.baidu.com', gradient: 'from-red-500 to-red-600' },
+    { id: 'google', name: 'Google Places', hint: 'console.cloud.google.com', gradient: 'from-amber-500 to-amber-600' },
+    { id: 'bing', name: 'Bing Maps', hint: 'www.bingmapsportal.com', gradient: 'from-teal-500 to-teal-600' },
+    { id: 'here', name: 'HERE', hint: 'platform.here.com', gradient: 'from-sky-500 to-sky-600' },
+    { id: 'mapbox', name: 'Mapbox', hint: 'account.mapbox.com', gradient: 'from-violet-500 to-violet-600' },
 ];
 
 export default function Settings() {
     const [keys, setKeys] = useState<Record<string, ApiKey[]>>({});
     const [showKeys, setShowKeys] = useState<Record<string, boolean>>({});
-    const [newKey, setNewKey] = useState<Record<string, { name: string; key: string }>>({});
+    const [newKey, setNewKey] = useState<Record<string, { name: string; key: string; qps?: string }>>({});
     const [loading, setLoading] = useState(true);
     const [addingKey, setAddingKey] = useState<string | null>(null);
+    const [eventSettings, setEventSettings] = useState<EventSettings | null>(null);
+    const [rawDataSettings, setRawDataSettings] = useState<RawDataSettings | null>(null);
+    const [compacting, setCompacting] = useState(false);
+    const [power, setPower] = useState<PowerStatus | null>(null);
+    const [restartPolicy, setRestartPolicy] = useState<RestartPolicy | null>(null);
+    const [osmSettings, setOsmSettings] = useState<OsmSettings | null>(null);
+    const [encryption, setEncryption] = useState<DatabaseEncryption | null>(null);
+    const [password, setPassword] = useState('');
+    const [newPassword, setNewPassword] = useState('');
+    const [encrypting, setEncrypting] = useState(false);
 
     useEffect(() => {
         loadData();
@@ -31,8 +105,24 @@ export default function Settings() {
 
     const loadData = async () => {
         try {
-            const keysData = await invoke<Record<string, ApiKey[]>>('get_api_keys');
+            const encryptionData = await invoke<DatabaseEncryption>('get_database_encryption');
+            setEncryption(encryptionData);
+            if (encryptionData.locked) return;
+
+            const [keysData, eventData, rawData, powerData, osmData, restartData] = await Promise.all([
+                invoke<Record<string, ApiKey[]>>('get_api_keys'),
+                invoke<EventSettings>('get_event_settings'),
+                invoke<RawDataSettings>('get_raw_data_settings'),
+                invoke<PowerStatus>('get_power_settings'),
+                invoke<OsmSettings>('get_osm_settings'),
+                invoke<RestartPolicy>('get_restart_policy'),
+            ]);
             setKeys(keysData);
+            setEventSettings(eventData);
+            setRawDataSettings(rawData);
+            setPower(powerData);
+            setOsmSettings(osmData);
+            setRestartPolicy(restartData);
         } catch (e) {
             console.error('加载设置失败:', e);
         } finally {
@@ -40,6 +130,76 @@ export default function Settings() {
         }
     };
 
+    const saveEventSettings = async (settings: EventSettings) => {
+        try {
+            setEventSettings(await invoke<EventSettings>('set_event_settings', { settings }));
+        } catch (e) {
+            alert(errorMessage(e));
+        }
+    };
+
+    const saveRawDataSettings = async (settings: RawDataSettings) => {
+        try {
+            setRawDataSettings(await invoke<RawDataSettings>('set_raw_data_settings', { settings }));
+        } catch (e) {
+            alert(errorMessage(e));
+        }
+    };
+
+    const savePowerSettings = async (prevent_sleep: boolean) => {
+        try {
+            setPower(await invoke<PowerStatus>('set_power_settings', { settings: { prevent_sleep } }));
+        } catch (e) {
+            alert(errorMessage(e));
+        }
+    };
+
+    const saveRestartPolicy = async (policy: RestartPolicy) => {
+        try {
+            setRestartPolicy(await invoke<RestartPolicy>('set_restart_policy', { policy }));
+        } catch (e) {
+            alert(errorMessage(e));
+        }
+    };
+
+    const saveOsmTags = async (value: string) => {
+        const extra_tags = value.split(/[,，\s]+/).filter(Boolean);
+        try {
+            await invoke('set_osm_settings', { settings: { extra_tags } });
+            setOsmSettings(await invoke<OsmSettings>('get_osm_settings'));
+        } catch (e) {
+            alert(errorMessage(e));
+        }
+    };
+
+    const compactRawData = async () => {
+        if (!confirm('将按当前设置转换已有数据的原始响应，并回收数据库空间，数据量大时耗时较长，是否继续？')) return;
+        setCompacting(true);
+        try {
+            const result = await invoke<CompactResult>('compact_raw_data', { vacuum: true });
+            alert(`已处理 ${result.processed.toLocaleString()} 条，原始响应 ${formatBytes(result.bytes_before)} → ${formatBytes(result.bytes_after)}`);
+        } catch (e) {
+            alert(errorMessage(e));
+        } finally {
+            setCompacting(false);
+        }
+    };
+
+    const runEncryption = async (command: string, args: Record<string, string>, confirmText?: string) => {
+        if (confirmText && !confirm(confirmText)) return;
+        setEncrypting(true);
+        try {
+            setEncryption(await invoke<DatabaseEncryption>(command, args));
+            setPassword('');
+            setNewPassword('');
+            if (command === 'unlock_database') await loadData();
+        } catch (e) {
+            alert(errorMessage(e));
+        } finally {
+            setEncrypting(false);
+        }
+    };
+
     const addKey = async (platform: string) => {
         const data = newKey[platform];
         if (!data?.key) return;
@@ -49,9 +209,10 @@ export default function Settings() {
             await invoke('add_api_key', {
                 platform,
                 apiKey: data.key,
-                name: data.name || undefined
+                name: data.name || undefined,
+                qps: data.qps ? Number(data.qps) : undefined
             });
-            setNewKey({ ...newKey, [platform]: { name: '', key: '' } });
+            setNewKey({ ...newKey, [platform]: { name: '', key: '', qps: '' } });
             loadData();
         } catch (e) {
             console.error('添加Key失败:', e);
@@ -133,6 +294,9 @@ export default function Settings() {
                                                 <div className="flex-1 min-w-0">
                                                     <div className="font-medium text-foreground truncate">
                                                         {k.name || `Key ${k.id}`}
+                                                        <span className="ml-2 text-xs font-normal text-muted-foreground">
+                                                            {k.qps ? `${k.qps} QPS` : '默认 3 QPS'}
+                                                        </span>
                                                     </div>
                                                     <div className="text-xs text-muted-foreground font-mono truncate">
                                                         {showKeys[`${platform.id}-${k.id}`]
@@ -175,18 +339,34 @@ export default function Settings() {
 
                                 {/* 添加新 Key */}
                                 <div className="pt-4 border-t border-border/50 space-y-2">
-                                    <input
-                                        type="text"
-                                        placeholder="备注名称（可选）"
-                                        className="w-full px-3 py-2 border border-input bg-background rounded-lg 
-                                                 text-foreground placeholder:text-muted-foreground text-sm 
-                                                 focus:outline-none focus:ring-2 focus:ring-primary/50 transition-all"
-                                        value={newKey[platform.id]?.name || ''}
-                                        onChange={(e) => setNewKey({
-                                            ...newKey,
-                                            [platform.id]: { ...newKey[platform.id], name: e.target.value }
-                                        })}
-                                    />
+                                    <div className="flex gap-2">
+                                        <input
+                                            type="text"
+                                            placeholder="备注名称（可选）"
+                                            className="flex-1 min-w-0 px-3 py-2 border border-input bg-background rounded-lg 
+                                                     text-foreground placeholder:text-muted-foreground text-sm 
+                                                     focus:outline-none focus:ring-2 focus:ring-primary/50 transition-all"
+                                            value={newKey[platform.id]?.name || ''}
+                                            onChange={(e) => setNewKey({
+                                                ...newKey,
+                                                [platform.id]: { ...newKey[platform.id], name: e.target.value }
+                                            })}
+                                        />
+                                        <input
+                                            type="number"
+                                            min={1}
+                                            placeholder="QPS"
+                                            title="Key 的 QPS 上限，企业 Key 可填 30，留空按个人 Key 3 处理"
+                                            className="w-20 px-3 py-2 border border-input bg-background rounded-lg 
+                                                     text-foreground placeholder:text-muted-foreground text-sm 
+                                                     focus:outline-none focus:ring-2 focus:ring-primary/50 transition-all"
+                                            value={newKey[platform.id]?.qps || ''}
+                                            onChange={(e) => setNewKey({
+                                                ...newKey,
+                                                [platform.id]: { ...newKey[platform.id], qps: e.target.value }
+                                            })}
+                                        />
+                                    </div>
                                     <div className="flex gap-2">
                                         <div className="flex-1 relative">
                                             <input
@@ -235,6 +415,289 @@ export default function Settings() {
                 })}
             </div>
 
+            {eventSettings && (
+                <Card className="overflow-hidden">
+                    <CardHeader className="border-b border-border/50 bg-gradient-to-r from-muted/50 to-transparent">
+                        <CardTitle className="text-sm flex items-center gap-2">
+                            <div className="w-6 h-6 rounded-lg bg-primary/20 flex items-center justify-center">
+                                <Activity className="w-3 h-3 text-primary" />
+                            </div>
+                            实时事件推送
+                        </CardTitle>
+                        <CardDescription>控制采集日志与瓦片下载进度推送到界面的频率</CardDescription>
+                    </CardHeader>
+                    <CardContent className="pt-4 space-y-3">
+                        <div className="flex gap-2">
+                            {eventLevels.map(level => (
+                                <Button
+                                    key={level.id}
+                                    size="sm"
+                                    variant={eventSettings.level === level.id ? 'default' : 'outline'}
+                                    onClick={() => saveEventSettings({ ...eventSettings, level: level.id })}
+                                    title={level.hint}
+                                >
+                                    {level.name}
+                                </Button>
+                            ))}
+                        </div>
+                        <p className="text-xs text-muted-foreground">
+                            {eventLevels.find(l => l.id === eventSettings.level)?.hint}
+                        </p>
+                        {eventSettings.level !== 'realtime' && (
+                            <label className="flex items-center gap-2 text-sm">
+                                推送间隔
+                                <input
+                                    type="number"
+                                    min={100}
+                                    max={10000}
+                                    step={100}
+                                    defaultValue={eventSettings.interval_ms}
+                                    onBlur={(e) => {
+                                        const interval_ms = Number(e.target.value);
+                                        if (interval_ms !== eventSettings.interval_ms) {
+                                            saveEventSettings({ ...eventSettings, interval_ms });
+                                        }
+                                    }}
+                                    className="w-24 h-8 px-2 rounded-md border border-input bg-background"
+                                />
+                                毫秒
+                            </label>
+                        )}
+                    </CardContent>
+                </Card>
+            )}
+
+            {rawDataSettings && (
+                <Card className="overflow-hidden">
+                    <CardHeader className="border-b border-border/50 bg-gradient-to-r from-muted/50 to-transparent">
+                        <CardTitle className="text-sm flex items-center gap-2">
+                            <div className="w-6 h-6 rounded-lg bg-primary/20 flex items-center justify-center">
+                                <Archive className="w-3 h-3 text-primary" />
+                            </div>
+                            原始响应存储
+                        </CardTitle>
+                        <CardDescription>控制每条 POI 附带的平台原始 JSON 占用的空间</CardDescription>
+                    </CardHeader>
+                    <CardContent className="pt-4 space-y-3">
+                        <div className="flex gap-2">
+                            {rawDataModes.map(mode => (
+                                <Button
+                                    key={mode.id}
+                                    size="sm"
+                                    variant={rawDataSettings.mode === mode.id ? 'default' : 'outline'}
+                                    onClick={() => saveRawDataSettings({ ...rawDataSettings, mode: mode.id })}
+                                    title={mode.hint}
+                                >
+                                    {mode.name}
+                                </Button>
+                            ))}
+                        </div>
+                        <p className="text-xs text-muted-foreground">
+                            {rawDataModes.find(m => m.id === rawDataSettings.mode)?.hint}
+                        </p>
+                        {rawDataSettings.mode !== 'discard' && (
+                            <label className="flex items-center gap-2 text-sm">
+                                截断上限
+                                <input
+                                    type="number"
+                                    min={256}
+                                    step={256}
+                                    placeholder="不截断"
+                                    defaultValue={rawDataSettings.max_bytes ?? ''}
+                                    onBlur={(e) => {
+                                        const max_bytes = e.target.value ? Number(e.target.value) : null;
+                                        if (max_bytes !== (rawDataSettings.max_bytes ?? null)) {
+                                            saveRawDataSettings({ ...rawDataSettings, max_bytes });
+                                        }
+                                    }}
+                                    className="w-24 h-8 px-2 rounded-md border border-input bg-background"
+                                />
+                                字节
+                            </label>
+                        )}
+                        <Button size="sm" variant="outline" onClick={compactRawData} disabled={compacting}>
+                            {compacting && <Loader2 className="w-3 h-3 mr-1 animate-spin" />}
+                            转换已有数据
+                        </Button>
+                    </CardContent>
+                </Card>
+            )}
+
+            {osmSettings && (
+                <Card className="overflow-hidden">
+                    <CardHeader className="border-b border-border/50 bg-gradient-to-r from-muted/50 to-transparent">
+                        <CardTitle className="text-sm flex items-center gap-2">
+                            <div className="w-6 h-6 rounded-lg bg-primary/20 flex items-center justify-center">
+                                <Globe className="w-3 h-3 text-primary" />
+                            </div>
+                            OSM 标签
+                        </CardTitle>
+                        <CardDescription>OSM 采集时额外保留的标签，导出时可展开为列，之后启动的采集生效</CardDescription>
+                    </CardHeader>
+                    <CardContent className="pt-4">
+                        <input
+                            key={osmSettings.extra_tags.join(',')}
+                            defaultValue={osmSettings.extra_tags.join(', ')}
+                            placeholder="如 opening_hours, website, wheelchair"
+                            onBlur={(e) => {
+                                if (e.target.value !== osmSettings.extra_tags.join(', ')) {
+                                    saveOsmTags(e.target.value);
+                                }
+                            }}
+                            className="w-full h-8 px-2 text-sm rounded-md border border-input bg-background"
+                        />
+                    </CardContent>
+                </Card>
+            )}
+
+            {power && (
+                <Card className="overflow-hidden">
+                    <CardHeader className="border-b border-border/50 bg-gradient-to-r from-muted/50 to-transparent">
+                        <CardTitle className="text-sm flex items-center gap-2">
+                            <div className="w-6 h-6 rounded-lg bg-primary/20 flex items-center justify-center">
+                                <Moon className="w-3 h-3 text-primary" />
+                            </div>
+                            休眠阻止
+                        </CardTitle>
+                        <CardDescription>长时间下载或采集时防止电脑自动休眠导致任务中断，任务全部结束后恢复</CardDescription>
+                    </CardHeader>
+                    <CardContent className="pt-4 space-y-2">
+                        <label className="flex items-center gap-2 text-sm cursor-pointer">
+                            <input
+                                type="checkbox"
+                                checked={power.prevent_sleep}
+                                onChange={(e) => savePowerSettings(e.target.checked)}
+                            />
+                            任务运行期间阻止系统休眠
+                        </label>
+                        {power.inhibiting && (
+                            <p className="text-xs text-muted-foreground">当前有任务运行，已阻止系统休眠</p>
+                        )}
+                    </CardContent>
+                </Card>
+            )}
+
+            {restartPolicy && (
+                <Card className="overflow-hidden">
+                    <CardHeader className="border-b border-border/50 bg-gradient-to-r from-muted/50 to-transparent">
+                        <CardTitle className="text-sm flex items-center gap-2">
+                            <div className="w-6 h-6 rounded-lg bg-primary/20 flex items-center justify-center">
+                                <RotateCcw className="w-3 h-3 text-primary" />
+                            </div>
+                            失败自动重启
+                        </CardTitle>
+                        <CardDescription>采集因限流、IP 受限或 Key 配额等故障停止后，按递增间隔自动继续未完成的类别，每次重启记录在采集日志中</CardDescription>
+                    </CardHeader>
+                    <CardContent className="pt-4 space-y-3">
+                        <label className="flex items-center gap-2 text-sm cursor-pointer">
+                            <input
+                                type="checkbox"
+                                checked={restartPolicy.enabled}
+                                onChange={(e) => saveRestartPolicy({ ...restartPolicy, enabled: e.target.checked })}
+                            />
+                            启用自动重启
+                        </label>
+                        <div className="flex flex-wrap items-center gap-2 text-sm text-muted-foreground">
+                            最多重启
+                            <input
+                                type="number"
+                                min={1}
+                                max={20}
+                                defaultValue={restartPolicy.max_restarts}
+                                key={`max-${restartPolicy.max_restarts}`}
+                                onBlur={(e) => {
+                                    const value = Number(e.target.value);
+                                    if (value !== restartPolicy.max_restarts) {
+                                        saveRestartPolicy({ ...restartPolicy, max_restarts: value });
+                                    }
+                                }}
+                                className="w-16 h-8 px-2 rounded-md border border-input bg-background text-foreground"
+                            />
+                            次，首次间隔
+                            <input
+                                type="number"
+                                min={1}
+                                defaultValue={Math.round(restartPolicy.base_delay_secs / 60)}
+                                key={`delay-${restartPolicy.base_delay_secs}`}
+                                onBlur={(e) => {
+                                    const value = Number(e.target.value) * 60;
+                                    if (value !== restartPolicy.base_delay_secs) {
+                                        saveRestartPolicy({ ...restartPolicy, base_delay_secs: value });
+                                    }
+                                }}
+                                className="w-16 h-8 px-2 rounded-md border border-input bg-background text-foreground"
+                            />
+                            分钟，之后每次翻倍
+                        </div>
+                    </CardContent>
+                </Card>
+            )}
+
+            {encryption && (
+                <Card className="overflow-hidden">
+                    <CardHeader className="border-b border-border/50 bg-gradient-to-r from-muted/50 to-transparent">
+                        <CardTitle className="text-sm flex items-center gap-2">
+                            <div className="w-6 h-6 rounded-lg bg-primary/20 flex items-center justify-center">
+                                <Lock className="w-3 h-3 text-primary" />
+                            </div>
+                            数据库加密
+                        </CardTitle>
+                        <CardDescription>
+                            {!encryption.supported
+                                ? '当前版本未启用 SQLCipher，不支持数据库加密'
+                                : encryption.locked
+                                    ? '数据库已加密，输入密码解锁后才能查看和采集数据'
+                                    : encryption.encrypted
+                                        ? '数据库已加密，密码仅保存在内存中，每次启动需重新解锁'
+                                        : '设置密码后将当前数据库迁移为加密库，请牢记密码，遗失后无法恢复数据'}
+                        </CardDescription>
+                    </CardHeader>
+                    {(encryption.supported || encryption.locked) && (
+                        <CardContent className="pt-4 flex flex-wrap items-center gap-2">
+                            <input
+                                type="password"
+                                placeholder={encryption.encrypted && !encryption.locked ? '当前密码' : '密码'}
+                                value={password}
+                                onChange={(e) => setPassword(e.target.value)}
+                                className="w-40 h-8 px-2 rounded-md border border-input bg-background text-sm"
+                            />
+                            {encryption.encrypted && !encryption.locked && (
+                                <input
+                                    type="password"
+                                    placeholder="新密码"
+                                    value={newPassword}
+                                    onChange={(e) => setNewPassword(e.target.value)}
+                                    className="w-40 h-8 px-2 rounded-md border border-input bg-background text-sm"
+                                />
+                            )}
+                            {encrypting && <Loader2 className="w-4 h-4 animate-spin text-muted-foreground" />}
+                            {encryption.locked ? (
+                                <Button size="sm" disabled={!password || encrypting}
+                                    onClick={() => runEncryption('unlock_database', { password })}>
+                                    解锁
+                                </Button>
+                            ) : encryption.encrypted ? (
+                                <>
+                                    <Button size="sm" disabled={!password || !newPassword || encrypting}
+                                        onClick={() => runEncryption('change_database_password', { oldPassword: password, newPassword })}>
+                                        修改密码
+                                    </Button>
+                                    <Button size="sm" variant="outline" disabled={!password || encrypting}
+                                        onClick={() => runEncryption('decrypt_database', { password }, '取消加密后数据库将以明文保存，是否继续？')}>
+                                        取消加密
+                                    </Button>
+                                </>
+                            ) : (
+                                <Button size="sm" disabled={!password || encrypting}
+                                    onClick={() => runEncryption('encrypt_database', { password }, '将当前数据库迁移为加密库，数据量大时耗时较长，是否继续？')}>
+                                    设置密码
+                                </Button>
+                            )}
+                        </CardContent>
+                    )}
+                </Card>
+            )}
+
             <Card className="overflow-hidden">
                 <CardHeader className="border-b border-border/50 bg-gradient-to-r from-muted/50 to-transparent">
                     <CardTitle className="text-sm flex items-center gap-2">
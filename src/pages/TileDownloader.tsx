@@ -3,6 +3,7 @@ import { invoke } from '@tauri-apps/api/core';
 // HMR trigger: 2026-01-07T21:57:00
 import { listen } from '@tauri-apps/api/event';
 import { save, open as openDialog } from '@tauri-apps/plugin-dialog';
+import { openUrl } from '@tauri-apps/plugin-opener';
 import {
     Play,
     Pause,
@@ -16,8 +17,11 @@ import {
     Search,
     Download,
     History,
+    Smartphone,
+    ListFilter,
 } from 'lucide-react';
 import { TileBoundsMap } from '@/components/TileBoundsMap';
+import { completionActionNames, type CompletionAction } from '@/components/CompletionActionDialog';
 import { Button } from '@/components/ui/button';
 import { Card, CardContent, CardHeader, CardTitle } from '@/components/ui/card';
 import { Progress } from '@/components/ui/progress';
@@ -39,7 +43,7 @@ import {
 import { Input } from '@/components/ui/input';
 import { Label } from '@/components/ui/label';
 import { Slider } from '@/components/ui/slider';
-import { cn } from '@/lib/utils';
+import { cn, errorMessage } from '@/lib/utils';
 import SimpleBar from 'simplebar-react';
 
 // 类型定义
@@ -70,6 +74,7 @@ interface TaskInfo {
     completed_at: string | null;
     error_message: string | null;
     download_speed: number;
+    on_complete: CompletionAction;
 }
 
 interface PlatformInfo {
@@ -80,6 +85,9 @@ interface PlatformInfo {
     max_zoom: number;
     map_types: string[];
     requires_key: boolean;
+    /** 可填写 Key，包括 Key 为可选的平台 */
+    accepts_key?: boolean;
+    key_apply_url?: string | null;
 }
 
 interface TileEstimate {
@@ -119,6 +127,11 @@ const mapTypeNames: Record<string, string> = {
     terrain: '地形图',
     roadnet: '路网图',
     annotation: '注记图',
+    annotation_en: '英文注记',
+    traffic: '实时路况',
+    satellite_annotation: '影像注记',
+    terrain_annotation: '地形注记',
+    boundary: '境界线',
 };
 
 // 状态名称和颜色
@@ -129,6 +142,7 @@ const statusInfo: Record<string, { name: string; color: string }> = {
     completed: { name: '已完成', color: 'text-green-500' },
     failed: { name: '失败', color: 'text-red-500' },
     cancelled: { name: '已取消', color: 'text-muted-foreground' },
+    suspect: { name: '可疑', color: 'text-orange-500' },
 };
 
 export default function TileDownloader() {
@@ -139,6 +153,7 @@ export default function TileDownloader() {
     const [loading, setLoading] = useState(false);
     const [savedApiKeys, setSavedApiKeys] = useState<Record<string, { id: number; api_key: string }[]>>({});
     const [showTasksDialog, setShowTasksDialog] = useState(false);
+    const [packageTask, setPackageTask] = useState<TaskInfo | null>(null);
 
     // 新建任务表单
     const [taskName, setTaskName] = useState('');
@@ -153,12 +168,15 @@ export default function TileDownloader() {
     const [zoomLevels, setZoomLevels] = useState<number[]>([10, 11, 12, 13, 14]);
     const [threadCount, setThreadCount] = useState(8);
     const [outputFormat, setOutputFormat] = useState('folder');
+    const [onComplete, setOnComplete] = useState<CompletionAction>('none');
     const [apiKey, setApiKey] = useState('');
     const [estimate, setEstimate] = useState<TileEstimate | null>(null);
     const [selectionMode, setSelectionMode] = useState<'draw' | 'region'>('draw');
     const [selectedRegionCode, setSelectedRegionCode] = useState<string | null>(null);
     const [regionSearchQuery, setRegionSearchQuery] = useState('');
     const [regionSearchResults, setRegionSearchResults] = useState<{ code: string; name: string; level: string }[]>([]);
+    // GeoJSON 范围文件，设置后按多边形裁剪瓦片
+    const [geojsonPath, setGeojsonPath] = useState<string | null>(null);
 
     // 加载平台列表和已保存的 API Keys
     useEffect(() => {
@@ -186,7 +204,7 @@ export default function TileDownloader() {
             setMapType(selectedPlatform.map_types[0] || 'street');
         }
         // 自动加载已保存的 API Key
-        if (selectedPlatform?.requires_key) {
+        if (selectedPlatform?.requires_key || selectedPlatform?.accepts_key) {
             const keys = savedApiKeys[platform] || [];
             if (keys.length > 0 && !apiKey) {
                 setApiKey(keys[0].api_key);
@@ -227,6 +245,10 @@ export default function TileDownloader() {
                         : task
                 )
             );
+            // 抽样校验发现瓦片无法解码，提示用户检查网络环境
+            if (progress.status === 'suspect' && progress.message) {
+                alert(progress.message);
+            }
         });
 
         return () => {
@@ -237,11 +259,30 @@ export default function TileDownloader() {
     // 计算瓦片估算
     useEffect(() => {
         if (bounds.north > bounds.south && bounds.east > bounds.west && zoomLevels.length > 0) {
-            invoke<TileEstimate>('calculate_tiles_count', { bounds, zoomLevels }).then(
+            invoke<TileEstimate>('calculate_tiles_count', { bounds, zoomLevels, geojsonPath }).then(
                 setEstimate
             );
         }
-    }, [bounds, zoomLevels]);
+    }, [bounds, zoomLevels, geojsonPath]);
+
+    // 导入 GeoJSON 文件作为下载范围
+    const handleImportGeojson = async () => {
+        try {
+            const selected = await openDialog({
+                title: '选择范围文件',
+                filters: [{ name: 'GeoJSON', extensions: ['geojson', 'json'] }],
+            });
+            if (!selected) return;
+            const info = await invoke<{ bounds: Bounds; rings: number; vertices: number }>(
+                'load_clip_geojson',
+                { path: selected as string }
+            );
+            setGeojsonPath(selected as string);
+            setBounds(info.bounds);
+        } catch (e) {
+            alert(`导入范围失败: ${errorMessage(e)}`);
+        }
+    };
 
     // 创建任务
     const handleCreateTask = async () => {
@@ -259,8 +300,12 @@ export default function TileDownloader() {
         try {
             // 选择保存路径
             let outputPath: string | null = null;
+            const outputSettings = await invoke<{ output_root: string | null }>('get_tile_output_settings');
 
-            if (outputFormat === 'folder') {
+            if (outputSettings.output_root) {
+                // 已配置输出根目录时由后端按模板生成路径
+                outputPath = '';
+            } else if (outputFormat === 'folder') {
                 outputPath = await save({
                     title: '选择保存位置',
                     defaultPath: `${taskName}`,
@@ -274,7 +319,7 @@ export default function TileDownloader() {
                 });
             }
 
-            if (!outputPath) {
+            if (outputPath === null || (!outputPath && !outputSettings.output_root)) {
                 setLoading(false);
                 return;
             }
@@ -291,6 +336,8 @@ export default function TileDownloader() {
                     thread_count: threadCount,
                     retry_count: 3,
                     api_key: apiKey.trim() || null,
+                    geojson_path: geojsonPath,
+                    on_complete: onComplete,
                 },
             });
 
@@ -298,7 +345,7 @@ export default function TileDownloader() {
             loadTasks();
         } catch (e) {
             console.error('创建任务失败:', e);
-            alert(`创建任务失败: ${e}`);
+            alert(`创建任务失败: ${errorMessage(e)}`);
         } finally {
             setLoading(false);
         }
@@ -313,11 +360,13 @@ export default function TileDownloader() {
         setZoomLevels([10, 11, 12, 13, 14]);
         setThreadCount(8);
         setOutputFormat('folder');
+        setOnComplete('none');
         setApiKey('');
         setSelectionMode('draw');
         setSelectedRegionCode(null);
         setRegionSearchQuery('');
         setRegionSearchResults([]);
+        setGeojsonPath(null);
     };
 
     // 搜索行政区域
@@ -345,7 +394,7 @@ export default function TileDownloader() {
             loadTasks();
         } catch (e) {
             console.error('启动下载失败:', e);
-            alert(`启动下载失败: ${e}`);
+            alert(`启动下载失败: ${errorMessage(e)}`);
         }
     };
 
@@ -396,6 +445,32 @@ export default function TileDownloader() {
         }
     };
 
+    // 导入瓦片坐标列表（如失败清单 CSV），任务只下载列表中的瓦片
+    const handleImportTileList = async (taskId: string) => {
+        try {
+            const selected = await openDialog({
+                title: '选择瓦片列表文件（每行 z,x,y）',
+                filters: [{ name: '瓦片列表', extensions: ['csv', 'txt'] }],
+            });
+            if (!selected) return;
+            const count = await invoke<number>('set_tile_task_list', { taskId, path: selected as string });
+            alert(`任务将只下载列表中的 ${count} 个瓦片`);
+            loadTasks();
+        } catch (e) {
+            alert(`导入瓦片列表失败: ${errorMessage(e)}`);
+        }
+    };
+
+    // 修改完成后动作，运行中的任务同样生效
+    const handleCompletionActionChange = async (taskId: string, action: CompletionAction) => {
+        try {
+            await invoke('set_tile_task_completion_action', { taskId, action });
+            loadTasks();
+        } catch (e) {
+            alert(`修改完成后动作失败: ${errorMessage(e)}`);
+        }
+    };
+
     // 格式化速度
     const formatSpeed = (speed: number) => {
         if (speed < 1) return `${(speed * 60).toFixed(1)}/分`;
@@ -470,7 +545,14 @@ export default function TileDownloader() {
                                     <div className="grid grid-cols-2 gap-3">
                                         <div className="space-y-2">
                                             <Label>地图平台</Label>
-                                            <Select value={platform} onValueChange={setPlatform}>
+                                            <Select
+                                                value={platform}
+                                                onValueChange={(value) => {
+                                                    // 切换平台时清空 Key，避免把其他平台的 Key 带过去
+                                                    setApiKey('');
+                                                    setPlatform(value);
+                                                }}
+                                            >
                                                 <SelectTrigger className="h-9">
                                                     <SelectValue />
                                                 </SelectTrigger>
@@ -532,13 +614,30 @@ export default function TileDownloader() {
                                     </div>
 
                                     {/* API Key */}
-                                    {currentPlatform?.requires_key && (
+                                    {(currentPlatform?.requires_key || currentPlatform?.accepts_key) && (
                                         <div className="space-y-2">
-                                            <Label>API Key <span className="text-red-500">*</span></Label>
+                                            <div className="flex items-center justify-between">
+                                                <Label>
+                                                    API Key {currentPlatform.requires_key
+                                                        ? <span className="text-red-500">*</span>
+                                                        : <span className="text-xs text-muted-foreground">（可选）</span>}
+                                                </Label>
+                                                {currentPlatform.key_apply_url && (
+                                                    <button
+                                                        type="button"
+                                                        className="text-xs text-primary hover:underline"
+                                                        onClick={() => openUrl(currentPlatform.key_apply_url!)}
+                                                    >
+                                                        申请 Key
+                                                    </button>
+                                                )}
+                                            </div>
                                             <Input
                                                 value={apiKey}
                                                 onChange={(e) => setApiKey(e.target.value)}
-                                                placeholder={`输入 ${currentPlatform.name} API Key`}
+                                                placeholder={currentPlatform.requires_key
+                                                    ? `输入 ${currentPlatform.name} API Key`
+                                                    : `输入 ${currentPlatform.name} API Key（可选）`}
                                                 className="h-9"
                                             />
                                         </div>
@@ -580,6 +679,25 @@ export default function TileDownloader() {
                                         </div>
                                     )}
 
+                                    {/* GeoJSON 范围 */}
+                                    <div className="space-y-2">
+                                        <Label>范围文件</Label>
+                                        {geojsonPath ? (
+                                            <div className="flex items-center gap-2">
+                                                <span className="flex-1 text-xs truncate" title={geojsonPath}>
+                                                    {geojsonPath.split(/[\\/]/).pop()}（按多边形裁剪）
+                                                </span>
+                                                <Button variant="ghost" size="sm" onClick={() => setGeojsonPath(null)}>
+                                                    清除
+                                                </Button>
+                                            </div>
+                                        ) : (
+                                            <Button variant="outline" size="sm" className="w-full" onClick={handleImportGeojson}>
+                                                导入 GeoJSON 范围
+                                            </Button>
+                                        )}
+                                    </div>
+
                                     {/* 线程数 */}
                                     <div className="space-y-2">
                                         <Label>下载线程: {threadCount}</Label>
@@ -592,6 +710,31 @@ export default function TileDownloader() {
                                         />
                                     </div>
 
+                                    {/* 完成后动作 */}
+                                    <div className="space-y-2">
+                                        <Label>完成后</Label>
+                                        <Select
+                                            value={onComplete}
+                                            onValueChange={(value) => setOnComplete(value as CompletionAction)}
+                                        >
+                                            <SelectTrigger className="h-9">
+                                                <SelectValue />
+                                            </SelectTrigger>
+                                            <SelectContent>
+                                                {Object.entries(completionActionNames).map(([value, name]) => (
+                                                    <SelectItem key={value} value={value}>
+                                                        {name}
+                                                    </SelectItem>
+                                                ))}
+                                            </SelectContent>
+                                        </Select>
+                                        {onComplete !== 'none' && (
+                                            <p className="text-xs text-muted-foreground">
+                                                下载完成且没有其他任务运行时，倒计时 60 秒后执行，可随时取消
+                                            </p>
+                                        )}
+                                    </div>
+
                                     {/* 层级选择 */}
                                     <div className="space-y-2">
                                         <div className="flex items-center justify-between">
@@ -703,13 +846,139 @@ export default function TileDownloader() {
                 onPause={handlePause}
                 onCancel={handleCancel}
                 onRetry={handleRetry}
+                onImportTileList={handleImportTileList}
                 onDelete={handleDelete}
+                onPackage={setPackageTask}
+                onCompletionActionChange={handleCompletionActionChange}
                 formatSpeed={formatSpeed}
             />
+
+            {/* 离线包打包对话框 */}
+            <PackageDialog task={packageTask} onClose={() => setPackageTask(null)} />
         </div>
     );
 }
 
+// 目标 App 与对应的离线包格式
+const packageTargets = [
+    { id: 'ovital', name: '奥维互动地图', format: 'MBTiles' },
+    { id: 'liangbulu', name: '两步路', format: 'sqlitedb' },
+    { id: 'oruxmaps', name: 'OruxMaps', format: 'OruxMaps 离线地图' },
+    { id: 'locus', name: 'Locus Map', format: 'sqlitedb' },
+    { id: 'generic', name: '其他 App', format: 'MBTiles' },
+];
+
+interface PackageResult {
+    path: string;
+    tile_count: number;
+    size_bytes: number;
+    copy_hint: string;
+}
+
+// 离线包打包对话框：按目标 App 生成可直接拷贝到设备的离线地图
+function PackageDialog({ task, onClose }: { task: TaskInfo | null; onClose: () => void }) {
+    const [target, setTarget] = useState('ovital');
+    const [name, setName] = useState('');
+    const [loading, setLoading] = useState(false);
+    const [result, setResult] = useState<PackageResult | null>(null);
+
+    useEffect(() => {
+        if (task) {
+            setName(task.name);
+            setResult(null);
+        }
+    }, [task]);
+
+    const handlePackage = async () => {
+        if (!task) return;
+        const outputDir = await openDialog({ title: '选择离线包保存目录', directory: true });
+        if (!outputDir) return;
+
+        setLoading(true);
+        try {
+            const invokePackage = (overwrite: boolean) =>
+                invoke<PackageResult>('package_offline_map', {
+                    taskId: task.id,
+                    target,
+                    outputDir,
+                    name,
+                    overwrite,
+                });
+            try {
+                setResult(await invokePackage(false));
+            } catch (e) {
+                if (!errorMessage(e).includes('已存在') || !confirm(`${errorMessage(e)}，是否覆盖？`)) {
+                    throw e;
+                }
+                setResult(await invokePackage(true));
+            }
+        } catch (e) {
+            console.error('打包失败:', e);
+            alert(`打包失败: ${errorMessage(e)}`);
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    const selected = packageTargets.find((t) => t.id === target);
+
+    return (
+        <Dialog open={task !== null} onOpenChange={(open) => !open && onClose()}>
+            <DialogContent>
+                <DialogHeader>
+                    <DialogTitle>打包到移动设备</DialogTitle>
+                    <DialogDescription>按目标 App 自动选择兼容格式与命名，生成可直接拷贝的离线包</DialogDescription>
+                </DialogHeader>
+
+                <div className="space-y-4 mt-4">
+                    <div className="space-y-2">
+                        <Label>目标 App</Label>
+                        <Select value={target} onValueChange={(v) => { setTarget(v); setResult(null); }}>
+                            <SelectTrigger>
+                                <SelectValue />
+                            </SelectTrigger>
+                            <SelectContent>
+                                {packageTargets.map((t) => (
+                                    <SelectItem key={t.id} value={t.id}>
+                                        {t.name}
+                                    </SelectItem>
+                                ))}
+                            </SelectContent>
+                        </Select>
+                        {selected && (
+                            <p className="text-xs text-muted-foreground">输出格式：{selected.format}</p>
+                        )}
+                    </div>
+
+                    <div className="space-y-2">
+                        <Label>离线包名称</Label>
+                        <Input value={name} onChange={(e) => setName(e.target.value)} placeholder="缺省使用任务名称" />
+                    </div>
+
+                    {result && (
+                        <div className="p-3 bg-muted rounded-lg text-sm space-y-1">
+                            <p className="font-medium break-all">{result.path}</p>
+                            <p className="text-muted-foreground">
+                                {result.tile_count} 个瓦片，{(result.size_bytes / 1024 / 1024).toFixed(1)} MB
+                            </p>
+                            <p>{result.copy_hint}</p>
+                        </div>
+                    )}
+                </div>
+
+                <DialogFooter>
+                    <Button variant="outline" onClick={onClose}>
+                        关闭
+                    </Button>
+                    <Button onClick={handlePackage} disabled={loading}>
+                        {loading ? '打包中...' : '选择目录并打包'}
+                    </Button>
+                </DialogFooter>
+            </DialogContent>
+        </Dialog>
+    );
+}
+
 // 转换对话框组件
 function ConvertDialog({
     open,
@@ -777,7 +1046,7 @@ function ConvertDialog({
             onOpenChange(false);
         } catch (e) {
             console.error('转换失败:', e);
-            alert(`转换失败: ${e}`);
+            alert(`转换失败: ${errorMessage(e)}`);
         } finally {
             setLoading(false);
         }
@@ -847,7 +1116,10 @@ function TasksDialog({
     onPause,
     onCancel,
     onRetry,
+    onImportTileList,
     onDelete,
+    onPackage,
+    onCompletionActionChange,
     formatSpeed,
 }: {
     open: boolean;
@@ -859,7 +1131,10 @@ function TasksDialog({
     onPause: (taskId: string) => void;
     onCancel: (taskId: string) => void;
     onRetry: (taskId: string) => void;
+    onImportTileList: (taskId: string) => void;
     onDelete: (taskId: string, deleteFiles: boolean) => void;
+    onPackage: (task: TaskInfo) => void;
+    onCompletionActionChange: (taskId: string, action: CompletionAction) => void;
     formatSpeed: (speed: number) => string;
 }) {
     return (
@@ -988,6 +1263,34 @@ function TasksDialog({
                                                         <RefreshCw className="h-3 w-3" />
                                                     </Button>
                                                 )}
+                                                {task.status !== 'downloading' && task.status !== 'paused' && (
+                                                    <Button
+                                                        size="sm"
+                                                        variant="ghost"
+                                                        className="h-7 px-2"
+                                                        title="仅下载指定瓦片列表"
+                                                        onClick={(e) => {
+                                                            e.stopPropagation();
+                                                            onImportTileList(task.id);
+                                                        }}
+                                                    >
+                                                        <ListFilter className="h-3 w-3" />
+                                                    </Button>
+                                                )}
+                                                {task.status === 'completed' && (
+                                                    <Button
+                                                        size="sm"
+                                                        variant="ghost"
+                                                        className="h-7 px-2"
+                                                        title="打包到移动设备"
+                                                        onClick={(e) => {
+                                                            e.stopPropagation();
+                                                            onPackage(task);
+                                                        }}
+                                                    >
+                                                        <Smartphone className="h-3 w-3" />
+                                                    </Button>
+                                                )}
                                                 <Button
                                                     size="sm"
                                                     variant="ghost"
@@ -999,6 +1302,27 @@ function TasksDialog({
                                                 >
                                                     <Trash2 className="h-3 w-3" />
                                                 </Button>
+                                                {['pending', 'downloading', 'paused'].includes(task.status) && (
+                                                    <div className="ml-auto" onClick={(e) => e.stopPropagation()}>
+                                                        <Select
+                                                            value={task.on_complete}
+                                                            onValueChange={(value) =>
+                                                                onCompletionActionChange(task.id, value as CompletionAction)
+                                                            }
+                                                        >
+                                                            <SelectTrigger className="h-7 w-28 text-xs" title="完成后动作">
+                                                                <SelectValue />
+                                                            </SelectTrigger>
+                                                            <SelectContent>
+                                                                {Object.entries(completionActionNames).map(([value, name]) => (
+                                                                    <SelectItem key={value} value={value}>
+                                                                        完成后{name}
+                                                                    </SelectItem>
+                                                                ))}
+                                                            </SelectContent>
+                                                        </Select>
+                                                    </div>
+                                                )}
                                             </div>
                                         </CardContent>
                                     </Card>